@@ -0,0 +1,157 @@
+use crate::journal::{JournalEvent, JournalRecord};
+
+/// How far the execution threshold moves per adjustment, in percentage points
+const STEP_PCT: f64 = 0.05;
+/// Realized profit may fall this many percentage points short of the estimate and still count as accurate
+const ACCURACY_TOLERANCE_PCT: f64 = 0.1;
+/// Accurate, profitable fills in a row before the threshold is lowered
+const ACCURATE_STREAK: u32 = 3;
+
+/// A change of the execution threshold and why it was made
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThresholdAdjustment {
+    pub from_pct: f64,
+    pub to_pct: f64,
+    pub reason: String,
+}
+
+/// Nudges the execution threshold up when trades realize less than estimated
+/// and down after a streak of accurate, profitable fills, within fixed bounds.
+#[derive(Debug, Clone)]
+pub struct AdaptiveThreshold {
+    current_pct: f64,
+    min_pct: f64,
+    max_pct: f64,
+    accurate_streak: u32,
+}
+
+impl AdaptiveThreshold {
+    pub fn new(initial_pct: f64, min_pct: f64, max_pct: f64) -> Self {
+        Self {
+            current_pct: initial_pct.clamp(min_pct, max_pct),
+            min_pct,
+            max_pct,
+            accurate_streak: 0,
+        }
+    }
+
+    pub fn current(&self) -> f64 {
+        self.current_pct
+    }
+
+    /// Pick up where the last session left off, using the most recent adjustment in the journal
+    pub fn resume_from(&mut self, records: &[JournalRecord]) -> Option<f64> {
+        let last = records.iter().rev().find_map(|r| match r.event {
+            JournalEvent::ThresholdAdjusted { to_pct, .. } => Some(to_pct),
+            _ => None,
+        })?;
+        self.current_pct = last.clamp(self.min_pct, self.max_pct);
+        Some(self.current_pct)
+    }
+
+    /// Feed the estimated and realized profit of an executed trade
+    pub fn record(&mut self, estimated_pct: f64, realized_pct: f64) -> Option<ThresholdAdjustment> {
+        let divergence = realized_pct - estimated_pct;
+
+        if divergence < -ACCURACY_TOLERANCE_PCT {
+            self.accurate_streak = 0;
+            return self.move_to(
+                self.current_pct + STEP_PCT,
+                format!(
+                    "realized {realized_pct:.3}% vs estimated {estimated_pct:.3}% ({divergence:+.3}pp)"
+                ),
+            );
+        }
+
+        if realized_pct <= 0.0 {
+            self.accurate_streak = 0;
+            return None;
+        }
+
+        self.accurate_streak += 1;
+        if self.accurate_streak < ACCURATE_STREAK {
+            return None;
+        }
+        self.accurate_streak = 0;
+        self.move_to(
+            self.current_pct - STEP_PCT,
+            format!("{ACCURATE_STREAK} accurate profitable fills in a row"),
+        )
+    }
+
+    fn move_to(&mut self, target_pct: f64, reason: String) -> Option<ThresholdAdjustment> {
+        let to_pct = target_pct.clamp(self.min_pct, self.max_pct);
+        if (to_pct - self.current_pct).abs() < 1e-9 {
+            return None;
+        }
+        let adjustment = ThresholdAdjustment {
+            from_pct: self.current_pct,
+            to_pct,
+            reason,
+        };
+        self.current_pct = to_pct;
+        Some(adjustment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_underperformance_raises_within_bounds() {
+        let mut threshold = AdaptiveThreshold::new(0.25, 0.1, 0.3);
+
+        let adjustment = threshold.record(0.5, 0.2).unwrap();
+        assert_eq!(adjustment.from_pct, 0.25);
+        assert!((adjustment.to_pct - 0.3).abs() < 1e-9);
+
+        // Already at the upper bound
+        assert_eq!(threshold.record(0.5, 0.2), None);
+        assert!((threshold.current() - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_accurate_streak_lowers_threshold() {
+        let mut threshold = AdaptiveThreshold::new(0.25, 0.1, 0.5);
+
+        assert_eq!(threshold.record(0.3, 0.28), None);
+        assert_eq!(threshold.record(0.3, 0.3), None);
+        let adjustment = threshold.record(0.3, 0.35).unwrap();
+        assert!((adjustment.to_pct - 0.2).abs() < 1e-9);
+
+        // A losing fill breaks the streak
+        threshold.record(0.3, 0.3);
+        threshold.record(0.3, 0.3);
+        assert_eq!(threshold.record(0.05, 0.0), None);
+        assert_eq!(threshold.record(0.3, 0.3), None);
+        assert!((threshold.current() - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_resume_from_journal_clamps_to_bounds() {
+        let mut threshold = AdaptiveThreshold::new(0.25, 0.1, 0.5);
+        let records = vec![
+            JournalRecord {
+                ts: Utc::now(),
+                event: JournalEvent::ThresholdAdjusted {
+                    from_pct: 0.25,
+                    to_pct: 0.3,
+                    reason: String::new(),
+                },
+            },
+            JournalRecord {
+                ts: Utc::now(),
+                event: JournalEvent::ThresholdAdjusted {
+                    from_pct: 0.3,
+                    to_pct: 0.9,
+                    reason: String::new(),
+                },
+            },
+        ];
+
+        assert_eq!(threshold.resume_from(&records), Some(0.5));
+        assert_eq!(threshold.resume_from(&[]), None);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::digest::Digest;
+use crate::journal::{Journal, JournalEvent};
+use crate::models::{ArbitrageOpportunity, Pct};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Minimum time before the same triangle is alerted again
+const ALERT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Logs and journals opportunities above the alert threshold for analysis,
+/// independently of whether they clear the execution threshold. Either sink can batch
+/// them, and minor warnings, into a periodic digest instead.
+pub struct OpportunityAlerts {
+    threshold_pct: Pct,
+    execution_threshold_pct: Pct,
+    last_alerted: HashMap<String, Instant>,
+    alerted: u64,
+    below_execution: u64,
+    log_digest: Option<Digest>,
+    journal_digest: Option<Digest>,
+}
+
+impl OpportunityAlerts {
+    pub fn new(threshold_pct: f64, execution_threshold_pct: f64) -> Self {
+        Self {
+            threshold_pct: Pct::new(threshold_pct),
+            execution_threshold_pct: Pct::new(execution_threshold_pct),
+            last_alerted: HashMap::new(),
+            alerted: 0,
+            below_execution: 0,
+            log_digest: None,
+            journal_digest: None,
+        }
+    }
+
+    /// Digest intervals of the log and the journal, 0 to report each event on its own
+    pub fn with_digests(mut self, log_secs: u64, journal_secs: u64) -> Self {
+        let now = Instant::now();
+        self.log_digest = Digest::new(log_secs, now);
+        self.journal_digest = Digest::new(journal_secs, now);
+        self
+    }
+
+    /// Follow the execution threshold when the adaptive controller moves it
+    pub fn set_execution_threshold(&mut self, threshold_pct: f64) {
+        self.execution_threshold_pct = Pct::new(threshold_pct);
+    }
+
+    /// Alert on each qualifying opportunity not seen within the cooldown
+    pub fn process(&mut self, opportunities: &[ArbitrageOpportunity], journal: Option<&Journal>) {
+        let now = Instant::now();
+        self.last_alerted
+            .retain(|_, seen| now.duration_since(*seen) < ALERT_COOLDOWN);
+
+        for opp in opportunities
+            .iter()
+            .filter(|o| o.estimated_profit_pct >= self.threshold_pct)
+        {
+            let key = opp.pairs.join(",");
+            if self.last_alerted.contains_key(&key) {
+                continue;
+            }
+            self.last_alerted.insert(key, now);
+
+            let executable = opp.estimated_profit_pct >= self.execution_threshold_pct;
+            self.alerted += 1;
+            if !executable {
+                self.below_execution += 1;
+            }
+            if let Some(digest) = self.log_digest.as_mut() {
+                digest.add_alert(opp, executable);
+            } else if executable {
+                warn!(
+                    "🔔 ALERT: {:.4}% via {} (above execution threshold)",
+                    opp.estimated_profit_pct,
+                    opp.display_pairs()
+                );
+            } else {
+                info!(
+                    "🔔 ALERT: {:.4}% via {} (below execution threshold {:.2}%)",
+                    opp.estimated_profit_pct,
+                    opp.display_pairs(),
+                    self.execution_threshold_pct
+                );
+            }
+
+            if let Some(digest) = self.journal_digest.as_mut() {
+                digest.add_alert(opp, executable);
+            } else if let Some(journal) = journal {
+                journal.record(JournalEvent::OpportunityAlert {
+                    path: opp.display_path(),
+                    pairs: opp.display_pairs(),
+                    estimated_profit_pct: opp.estimated_profit_pct.value(),
+                    above_execution_threshold: executable,
+                });
+            }
+        }
+        self.send_digests(now, journal, false);
+    }
+
+    /// Batch a warning the bot recovers from into the log digest. False if the log has no
+    /// digest, so the caller should log it as usual.
+    pub fn digest_warning(&mut self, context: &str, message: String) -> bool {
+        match self.log_digest.as_mut() {
+            Some(digest) => {
+                digest.add_warning(context, message);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Send the digests whose window is over, or all of them with `force`, e.g. at shutdown
+    pub fn send_digests(&mut self, now: Instant, journal: Option<&Journal>, force: bool) {
+        if let Some(digest) = self.log_digest.as_mut() {
+            digest.log_if_due(now, force);
+        }
+        if let (Some(digest), Some(journal)) = (self.journal_digest.as_mut(), journal) {
+            if let Some(event) = digest.journal_if_due(now, force) {
+                journal.record(event);
+            }
+        }
+    }
+
+    /// Alerts raised so far: (total, below the execution threshold)
+    #[cfg(test)]
+    pub fn counts(&self) -> (u64, u64) {
+        (self.alerted, self.below_execution)
+    }
+
+    pub fn log_summary(&self) {
+        info!(
+            "🔔 Alerts (threshold {:.2}% vs execution {:.2}%): {} raised, {} below the execution threshold",
+            self.threshold_pct, self.execution_threshold_pct, self.alerted, self.below_execution
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::JournalRecord;
+
+    #[test]
+    fn test_alerts_split_by_execution_threshold() {
+        let path = std::env::temp_dir().join(format!("alerts_test_{}.jsonl", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let journal = Journal::open(&path).unwrap();
+        let mut alerts = OpportunityAlerts::new(0.05, 0.25);
+
+        let opps = vec![
+            ArbitrageOpportunity::test()
+                .with_pairs(&["BTCUSDT", "ETHBTC", "ETHUSDT"])
+                .with_profit_pct(0.3),
+            ArbitrageOpportunity::test()
+                .with_pairs(&["SOLUSDT", "SOLBTC", "BTCUSDT"])
+                .with_profit_pct(0.1),
+            ArbitrageOpportunity::test()
+                .with_pairs(&["XRPUSDT", "XRPBTC", "BTCUSDT"])
+                .with_profit_pct(0.01),
+        ];
+        alerts.process(&opps, Some(&journal));
+        assert_eq!(alerts.counts(), (2, 1));
+
+        // Same triangles within the cooldown are not alerted again
+        alerts.process(&opps, Some(&journal));
+        assert_eq!(alerts.counts(), (2, 1));
+
+        let records: Vec<JournalRecord> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert!(matches!(
+            records[1].event,
+            JournalEvent::OpportunityAlert {
+                above_execution_threshold: false,
+                ..
+            }
+        ));
+        std::fs::remove_file(&path).ok();
+    }
+}
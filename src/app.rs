@@ -0,0 +1,3057 @@
+use anyhow::{Context, Result};
+use futures_util::FutureExt;
+use std::collections::{BTreeMap, HashMap};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{sleep, Duration};
+use tracing::{debug, error, info, warn};
+
+use crate::adaptive::AdaptiveThreshold;
+use crate::alerts::OpportunityAlerts;
+use crate::approval::ApprovalGate;
+use crate::arbitrage::ArbitrageEngine;
+use crate::availability::AvailabilityTracker;
+use crate::backend::{ExecutionBackend, NoopTrader};
+use crate::balance::{BalanceManager, BalanceSnapshot};
+use crate::balance_sync::BalanceSync;
+use crate::bandwidth::{BandwidthBudget, BandwidthMeter};
+use crate::blacklist;
+use crate::bundle::{DebugBundle, Quote};
+use crate::client::BybitClient;
+use crate::config::Config;
+use crate::config_cmd;
+use crate::crosscheck::FeedCrossCheck;
+use crate::deadlines::EdgeDecay;
+use crate::degraded::{DegradedPricing, FeedHealth};
+use crate::depth::{DepthPricer, DepthUpdate};
+use crate::dust::DustInventory;
+use crate::errors::BybitError;
+use crate::events::{ChangeTracker, EventLog, SessionEvent};
+use crate::exchange::ExchangeApi;
+use crate::executor::{Executor, PendingTriangle, TriangleOrder, TriangleReport};
+use crate::fees::FeeSchedule;
+use crate::flows::{self, ExternalFlow};
+use crate::focus::{Focus, FocusFilter};
+use crate::funding::{self, FundingMode};
+use crate::graph::Algorithm;
+use crate::imbalance::TopOfBook;
+use crate::journal::{Journal, JournalEvent, JournalLeg};
+use crate::ledger::TriangleLedger;
+use crate::limits::{self, IntermediateLimits, OverlapCooldown};
+use crate::logger::*;
+use crate::maintenance::{self, MaintenanceWatch};
+use crate::maker::ExecutionStrategy;
+use crate::models::{ArbitrageOpportunity, BalanceMap, TickerInfo};
+use crate::network::WsTransport;
+use crate::notifier::{self, Notifications, OutageAlert, TelegramNotifier};
+use crate::pairs::PairManager;
+use crate::paper;
+use crate::positions::PositionManager;
+use crate::precision::PrecisionManager;
+use crate::quote_delay::QuoteDelays;
+use crate::reconcile::{self, ReconcileReport};
+use crate::recorder::MarketRecorder;
+use crate::reporting::ReportingCurrency;
+use crate::resume::{Checkpoint, ResumeMode};
+use crate::risk::{RiskLimits, RiskManager};
+use crate::run_state::{RunEvent, RunState};
+use crate::shadow::ShadowTrader;
+use crate::shutdown::{ExecutionGuard, ShutdownCoordinator};
+use crate::sizing::SizingPolicy;
+use crate::stable::StableScanner;
+use crate::state::{self, SharedState};
+use crate::storage::{GroupBy, TradeStore};
+use crate::strategy::{self, MarketView, Strategy};
+use crate::strategy_ab::{self, StrategyAb};
+use crate::stream::{self, StreamTaps};
+use crate::supervisor::{self, Backoff};
+use crate::sweeper::{DustSweeper, SWEEP_COIN};
+use crate::symbols::SymbolRegistry;
+use crate::trader::ArbitrageTrader;
+use crate::utilization::CapitalUtilization;
+use crate::wallet_stream::WalletFeed;
+use crate::warmup::QuoteWarmup;
+
+/// How often parked positions are checked for fills and expiry
+const POSITION_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// An opportunity to execute, the amount to trade and, in race mode, a disjoint runner-up
+type Candidate = (
+    ArbitrageOpportunity,
+    f64,
+    Option<(ArbitrageOpportunity, f64)>,
+);
+
+/// A triangle handed to the execution task, with what its outcome is recorded against
+struct InFlight {
+    pending: PendingTriangle,
+    /// Start amounts held until the execution is over, so no scan sizes a trade on them
+    reservations: Vec<(String, f64)>,
+    /// Quotes and balances as the execution started, for a debug bundle if it fails
+    quotes: BTreeMap<String, Quote>,
+    balances_before: BalanceMap,
+    started_at: chrono::DateTime<chrono::Utc>,
+    execution_start: Instant,
+    /// USD prices the trade was handed over at, covering every start coin
+    usd_prices: HashMap<String, f64>,
+    /// Strategy the A/B comparison picked for this execution
+    strategy: Option<ExecutionStrategy>,
+    /// Keeps shutdown waiting until the outcome is recorded
+    _guard: ExecutionGuard,
+}
+
+/// The running bot. This is the scanning loop; prices, balances, execution and the journal
+/// each run in their own supervised task, so one of them failing doesn't stop the others.
+/// Scanning goes on while a trade executes; its outcome is picked up on a later cycle.
+/// Bybit is the exchange in production; every request and stream goes through `E`.
+pub struct App<E: ExchangeApi = BybitClient> {
+    config: Config,
+    client: E,
+    state: SharedState,
+    shutdown: Arc<ShutdownCoordinator>,
+    balance_manager: BalanceManager,
+    balance_sync: BalanceSync,
+    balances: watch::Receiver<Option<BalanceSnapshot>>,
+    pair_manager: PairManager,
+    arbitrage_engine: ArbitrageEngine,
+    /// Strategies proposing trades next to the triangular engine
+    strategies: Vec<Box<dyn Strategy>>,
+    executor: Executor,
+    /// Trade the execution task is working on
+    in_flight: Option<InFlight>,
+    /// Dry-run trader for shadow mode's simulations
+    simulator: ArbitrageTrader<E>,
+    journal: Option<Journal>,
+    trade_store: Option<TradeStore>,
+    notifications: Option<Notifications>,
+    outage_alert: OutageAlert,
+    /// Loss, exposure and kill-switch limits consulted before every execution
+    risk: RiskManager,
+    /// Sequenced domain events for replay, and what changed since they were last logged
+    events: Option<EventLog>,
+    /// Pairs and tickers as they arrive, for `backtest`
+    recorder: Option<MarketRecorder>,
+    event_changes: ChangeTracker,
+    shadow_trader: Option<ShadowTrader>,
+    /// Alternates execution strategies across triangles and compares their realized edge
+    strategy_ab: Option<StrategyAb>,
+    alerts: OpportunityAlerts,
+    adaptive_threshold: Option<AdaptiveThreshold>,
+    intermediate_limits: IntermediateLimits,
+    /// Timers keeping executions on overlapping symbols apart
+    overlap_cooldown: OverlapCooldown,
+    stable_scanner: Option<StableScanner>,
+    /// Converts the dust inventory back to USDT now and then
+    dust_sweeper: Option<DustSweeper>,
+    edge_decay: EdgeDecay,
+    funding_mode: FundingMode,
+    /// Size of the next trade, fixed or laddered
+    sizing: SizingPolicy,
+    /// Coins executions left unconverted, kept across runs in live mode
+    dust_inventory: DustInventory,
+    /// Track record of each triangle, kept across runs in live mode
+    triangle_ledger: TriangleLedger,
+    /// When the bot was fully operational, kept across runs in live mode
+    availability: AvailabilityTracker,
+    /// Notional traded and time spent executing, against the account's value
+    utilization: CapitalUtilization,
+    max_trades: u32,
+    rx: tokio::sync::mpsc::Receiver<TickerInfo>,
+    /// Symbols still priced from REST until their first WebSocket quote
+    warmup: QuoteWarmup,
+    /// Depth books for legs too big for top-of-book pricing
+    depth_pricer: Option<DepthPricer>,
+    depth_rx: tokio::sync::mpsc::Receiver<DepthUpdate>,
+    /// Bytes received by the WebSockets, and the budget trimming their subscriptions
+    bandwidth: Arc<BandwidthMeter>,
+    bandwidth_budget: BandwidthBudget,
+    /// Symbols the WebSockets stream while focus mode is on
+    focus_filter: Arc<FocusFilter>,
+    /// Second feed the symbols of the best opportunities are checked against
+    cross_check: Option<Arc<FeedCrossCheck>>,
+    /// Holds trades until they are approved via the control API
+    approval: Option<ApprovalGate>,
+    /// How late each symbol's quotes arrive, for discounting edges through laggards
+    quote_delays: Option<Arc<QuoteDelays>>,
+    /// Top-of-book connections up, and REST polling while none are
+    feed_health: Arc<FeedHealth>,
+    degraded: DegradedPricing,
+    /// Converts USD profits for logs and the status report
+    reporting: ReportingCurrency,
+    /// Realized profit of the trades completed this session
+    session_profit_usd: f64,
+    /// Reports of the job checking journaled fills against the exchange
+    reconciliation: Option<mpsc::UnboundedReceiver<ReconcileReport>>,
+    /// Deposits and withdrawals made outside the bot, polled in live mode
+    external_flows: Option<mpsc::UnboundedReceiver<ExternalFlow>>,
+    /// Bybit's maintenance windows and symbol suspensions, polled in live mode
+    maintenance: Option<MaintenanceWatch>,
+    /// Coins of the blacklist file each time it changes
+    blacklist: Option<mpsc::UnboundedReceiver<Vec<String>>>,
+    cycle_count: u64,
+    initial_scan_logged: bool,
+    trades_completed: u32,
+    last_status_publish: Instant,
+    last_position_check: Instant,
+    /// Restart delay after the scanner panics
+    scan_backoff: Backoff,
+    last_scan_panic: Instant,
+    start_time: Instant,
+}
+
+/// Trader for the execution task, rebuilt whenever the task restarts
+fn build_trader<E: ExchangeApi>(
+    config: &Config,
+    backend: ExecutionBackend,
+    client: E,
+    precision_manager: PrecisionManager,
+    fees: FeeSchedule,
+    symbols: Arc<SymbolRegistry>,
+    wallet: Option<Arc<WalletFeed>>,
+) -> ArbitrageTrader<E> {
+    // Positions parked by a previous run are still managed, even with hedging since disabled
+    let positions = PositionManager::load(
+        chrono::Duration::seconds(config.position_max_hold_secs as i64),
+        std::path::Path::new(&config.positions_path),
+    );
+    if !positions.positions().is_empty() {
+        warn!(
+            "🅿️ Resuming {} parked position(s) from {}",
+            positions.positions().len(),
+            config.positions_path
+        );
+    }
+    let mut trader = ArbitrageTrader::new(client, config.dry_run, precision_manager)
+        .with_symbols(symbols)
+        .with_positions(positions)
+        .with_order_link_prefix(&config.order_link_prefix)
+        .with_fees(fees, config.fee_coin())
+        .with_min_profit_usd(config.min_profit_usd);
+    if let Some(loss_pct) = config.hedge_min_rollback_loss_pct {
+        info!(
+            "🅿️ HEDGING: Parking leg-3 failures that would lose {loss_pct:.2}% or more on rollback (max hold {}s)",
+            config.position_max_hold_secs
+        );
+        trader = trader.with_hedging(loss_pct);
+    }
+    match ExecutionStrategy::parse(&config.execution_strategy) {
+        Ok(strategy) => {
+            if strategy.maker_leg1() {
+                info!(
+                    "🪙 MAKER EXECUTION: Offering leg 1 as a post-only limit for {}ms ({strategy:?})",
+                    config.maker_timeout_ms
+                );
+            }
+            // Set with taker too: the A/B comparison may place maker legs
+            trader = trader
+                .with_execution_strategy(strategy, Duration::from_millis(config.maker_timeout_ms));
+        }
+        Err(e) => warn!("⚠️ Ignoring EXECUTION_STRATEGY: {e:#}"),
+    }
+    match backend {
+        ExecutionBackend::Live | ExecutionBackend::Simulated => {}
+        ExecutionBackend::Paper => match paper::parse_balances(&config.paper_balances) {
+            Ok(balances) => {
+                info!(
+                    "📝 PAPER TRADING: Filling dry runs against the live book from {}",
+                    config.paper_balances
+                );
+                trader = trader.with_paper_trading(balances);
+            }
+            Err(e) => warn!("⚠️ Ignoring PAPER_TRADING: PAPER_BALANCES is invalid: {e:#}"),
+        },
+        ExecutionBackend::Noop => {
+            info!("💤 NOOP BACKEND: Opportunities are reported but never executed");
+            trader = trader.with_backend(Box::new(NoopTrader));
+        }
+    }
+    if let Some(wallet) = wallet {
+        trader = trader.with_wallet_feed(wallet);
+    }
+    if config.batch_legs {
+        info!("📦 BATCH LEGS: Sending legs 2 and 3 in one batch request");
+        trader = trader.with_batch_legs();
+    }
+    if let Some(max_ratio) = config.book_imbalance_max_ratio {
+        info!("⚖️ IMBALANCE FILTER: Avoiding books stacked more than {max_ratio}x against a leg");
+        trader = trader.with_imbalance_filter(max_ratio);
+    }
+    if config.order_audit && !config.dry_run {
+        // Written synchronously, unlike the main journal, so each intent is on disk before
+        // its order leaves
+        match Journal::open(std::path::Path::new(&config.journal_path)) {
+            Ok(journal) => {
+                info!("📝 ORDER AUDIT: Journaling every order's intent before sending it");
+                trader = trader.with_order_audit(journal);
+            }
+            Err(e) => warn!("⚠️ Order audit disabled: {e:#}"),
+        }
+    }
+    if !config.dry_run {
+        trader = trader.with_checkpoint(Checkpoint::new(std::path::Path::new(
+            &config.execution_state_path,
+        )));
+    }
+    trader
+}
+
+/// Net amount of each coin the legs took out of the wallet, fees included; negative for
+/// coins they brought in
+fn net_spent(legs: &[JournalLeg], pair_manager: &PairManager) -> HashMap<String, f64> {
+    let mut spent: HashMap<String, f64> = HashMap::new();
+    for leg in legs {
+        let Some(pair) = pair_manager.get_pair(&leg.symbol) else {
+            warn!("⚠️ Unknown symbol {} in a filled leg", leg.symbol);
+            continue;
+        };
+        let (bought, sold) = if leg.side == "Buy" {
+            ((&pair.base, leg.qty), (&pair.quote, leg.value))
+        } else {
+            ((&pair.quote, leg.value), (&pair.base, leg.qty))
+        };
+        *spent.entry(bought.0.clone()).or_default() -= bought.1;
+        *spent.entry(sold.0.clone()).or_default() += sold.1;
+        for (coin, fee) in &leg.fees {
+            *spent.entry(coin.clone()).or_default() += fee;
+        }
+    }
+    spent
+}
+
+/// Risk limits, counting what was already realized today and positions parked by a
+/// previous run
+fn build_risk_manager(
+    config: &Config,
+    trade_store: Option<&TradeStore>,
+    pair_manager: &PairManager,
+) -> RiskManager {
+    let now = chrono::Utc::now();
+    let today = now.format("%Y-%m-%d").to_string();
+    let daily_pnl = trade_store
+        .and_then(|store| {
+            store
+                .pnl(GroupBy::Day, config.dry_run)
+                .map_err(|e| warn!("⚠️ Daily loss starts from zero: {e:#}"))
+                .ok()
+        })
+        .and_then(|rows| rows.into_iter().find(|row| row.key == today))
+        .map_or(0.0, |row| row.profit_usd);
+    let limits = RiskLimits::from_config(config);
+    let mut risk = RiskManager::new(limits, now).with_daily_pnl(daily_pnl);
+    let parked = PositionManager::load(
+        chrono::Duration::seconds(config.position_max_hold_secs as i64),
+        std::path::Path::new(&config.positions_path),
+    );
+    for position in parked.positions() {
+        let price = pair_manager.usd_price(&position.target_coin).unwrap_or(1.0);
+        risk.position_parked(&position.order_id, position.breakeven_amount * price);
+    }
+    if config.max_daily_loss_usd > 0.0
+        || config.max_consecutive_failures > 0
+        || config.max_open_exposure_usd > 0.0
+    {
+        info!(
+            "🛡️ RISK LIMITS: daily loss ${} (${:.2} so far today), {} failures in a row, exposure ${} (0 = no limit)",
+            config.max_daily_loss_usd,
+            -risk.daily_pnl_usd(),
+            config.max_consecutive_failures,
+            config.max_open_exposure_usd
+        );
+    }
+    risk
+}
+
+impl<E: ExchangeApi + Clone + 'static> App<E> {
+    /// Load precision data and pairs, build every subsystem and connect the WebSockets.
+    /// The run state stays `Initializing` until `run` is called.
+    pub async fn initialize(config: Config, client: E, state: SharedState) -> Result<Self> {
+        // Initialize managers and trader
+        let balance_manager = BalanceManager::new();
+        // One registry, so a symbol has the same id in the pair manager, engine and trader
+        let symbols = SymbolRegistry::new();
+        let mut pair_manager = PairManager::new(config.clone()).with_symbols(symbols.clone());
+        // Coins in the blacklist file are excluded from the first load of pairs on
+        let blacklist = (!config.blacklist_file.is_empty())
+            .then(|| std::path::PathBuf::from(&config.blacklist_file));
+        if let Some(path) = blacklist.as_ref().filter(|p| p.exists()) {
+            let tokens = blacklist::load(path)?;
+            info!(
+                "🚫 {} coins blacklisted by {}",
+                tokens.len(),
+                path.display()
+            );
+            pair_manager.set_blacklist(blacklist::merge(&config.blacklisted_tokens, &tokens));
+        }
+        let blacklist = blacklist.map(|path| blacklist::spawn(path, config.restart_on_panic));
+        // Alerts, shadow mode and the adaptive threshold need the engine to report
+        // opportunities down to the lowest threshold
+        let scan_threshold = config
+            .shadow_min_profit_threshold
+            .unwrap_or(f64::INFINITY)
+            .min(config.alert_profit_threshold)
+            .min(config.min_profit_threshold)
+            .min(config.adaptive_threshold_min.unwrap_or(f64::INFINITY));
+        let algorithm = Algorithm::parse(&config.arbitrage_algorithm).unwrap_or_else(|e| {
+            warn!("⚠️ Ignoring ARBITRAGE_ALGORITHM: {e:#}");
+            Algorithm::Triangles
+        });
+        if algorithm == Algorithm::Graph {
+            info!(
+                "🕸️ GRAPH SEARCH: Looking for profitable cycles of up to {} legs (only 3-leg cycles trade)",
+                config.graph_max_cycle_legs
+            );
+        }
+        let mut arbitrage_engine = ArbitrageEngine::with_config(
+            scan_threshold,
+            config.max_triangles_to_scan,
+            config.effective_fee_rate(),
+        )
+        .with_execution_threshold(config.min_profit_threshold)
+        .with_min_profit_usd(config.min_profit_usd)
+        .with_algorithm(algorithm, config.graph_max_cycle_legs)
+        // An invalid strategy is warned about, and traded as taker, by the trader
+        .with_execution_strategy(
+            ExecutionStrategy::parse(&config.execution_strategy)
+                .unwrap_or(ExecutionStrategy::Taker),
+        )
+        .with_symbols(symbols.clone());
+
+        // Initialize precision manager with dynamic data from the exchange
+        info!(
+            "🔧 INIT: Fetching precision data from {} API",
+            client.name()
+        );
+        let mut precision_manager = PrecisionManager::new();
+
+        // Load cached precision data if available
+        if let Err(e) = precision_manager
+            .load_cache_from_file("precision_cache.json")
+            .await
+        {
+            warn!("⚠️ Failed to load precision cache: {e}");
+        }
+
+        // Balance changes are pushed on the private stream, with REST polling while it is down
+        let wallet_feed = if config.wallet_stream {
+            let feed = WalletFeed::new();
+            let (exchange, transport, task_feed) = (
+                client.clone(),
+                WsTransport::from_config(&config)?,
+                feed.clone(),
+            );
+            supervisor::spawn_supervised(
+                "Wallet stream".to_string(),
+                config.restart_on_panic,
+                move || exchange.wallet_stream(task_feed.clone(), transport.clone()),
+            );
+            Some(feed)
+        } else {
+            None
+        };
+
+        // Balances are fetched on their own task; wait for the first ones before scanning
+        let balance_sync = BalanceSync::spawn(
+            Arc::new(client.clone()),
+            config.balance_refresh_interval_secs,
+            wallet_feed.clone(),
+            config.restart_on_panic,
+        );
+
+        // Endpoint probing and pre-warmed connections, before the first requests
+        client.start_upkeep(&config).await;
+
+        // Precision data, instruments with tickers, and balances are independent; fetch
+        // them together so the first scan waits only for the slowest
+        info!("🔧 INIT: Fetching precision data, trading pairs and balances");
+        let init_start = Instant::now();
+        let load_precision = async {
+            loop {
+                match precision_manager.initialize(&client).await {
+                    Ok(_) => break,
+                    Err(e) => {
+                        warn!("⚠️ Failed to initialize precision manager: {e}");
+                        warn!("🔄 Retrying in 5 seconds...");
+                        sleep(Duration::from_secs(5)).await;
+                    }
+                }
+            }
+        };
+        let load_pairs = async {
+            loop {
+                match pair_manager.update_pairs_and_prices(&client).await {
+                    Ok(_) => break,
+                    Err(e) => {
+                        warn!("⚠️ Failed to fetch initial pairs: {e}");
+                        warn!("🔄 Retrying in 5 seconds...");
+                        sleep(Duration::from_secs(5)).await;
+                    }
+                }
+            }
+        };
+        let load_fees = FeeSchedule::load(&client, config.trading_fee_rate, config.fee_discount);
+        let ((), (), balances_loaded, fees) = tokio::join!(
+            load_precision,
+            load_pairs,
+            balance_sync.refresh(),
+            load_fees
+        );
+        arbitrage_engine.set_fee_schedule(fees.clone());
+        if !balances_loaded {
+            warn!("⚠️ No account balances yet - scanning starts without them");
+        }
+        let balances = balance_sync.subscribe();
+        info!(
+            "⏱️ INIT: Startup data loaded in {}ms",
+            init_start.elapsed().as_millis()
+        );
+        precision_manager.print_precision_summary();
+
+        let audit = precision_manager.audit_rounding();
+        if audit.issues.is_empty() {
+            info!(
+                "📏 Rounding audit passed: {} quantities across {} symbols",
+                audit.quantities_checked, audit.symbols
+            );
+        } else {
+            warn!(
+                "⚠️ Rounding audit found {} issue(s); affected orders may be rejected",
+                audit.issues.len()
+            );
+            for issue in audit.issues.iter().take(5) {
+                warn!("   {issue}");
+            }
+        }
+
+        // Display precision cache statistics
+        let (total_cached, _) = precision_manager.get_cache_stats();
+        info!("📊 Precision Cache: {total_cached} symbols cached");
+
+        log_success("Initialization", "Precision data loaded successfully");
+
+        // Trades run on their own task; shadow mode only needs a simulator
+        let dry_run = config.dry_run;
+        let backend = ExecutionBackend::resolve(
+            &config.execution_backend,
+            config.dry_run,
+            config.paper_trading,
+        )?;
+        let max_trades = config.max_trades;
+        // Dry runs ladder in memory only, so simulated profits never size a live trade
+        let sizing = if config.order_size_step_pct > 0.0 && !dry_run {
+            SizingPolicy::load(
+                config.order_size,
+                config.order_size_step_pct,
+                config.order_size_max,
+                std::path::Path::new(&config.order_size_ladder_path),
+            )
+        } else {
+            SizingPolicy::new(
+                config.order_size,
+                config.order_size_step_pct,
+                config.order_size_max,
+            )
+        };
+        let dust_inventory = if dry_run {
+            DustInventory::default()
+        } else {
+            DustInventory::load(std::path::Path::new(&config.dust_inventory_path))
+        };
+        let triangle_ledger = if dry_run {
+            TriangleLedger::default()
+        } else {
+            TriangleLedger::load(std::path::Path::new(&config.triangle_ledger_path))
+        };
+        if triangle_ledger.len() > 0 {
+            info!(
+                "📒 TRIANGLE LEDGER: Ranking with the track record of {} triangles",
+                triangle_ledger.len()
+            );
+        }
+        arbitrage_engine.set_triangle_adjustments(triangle_ledger.ranking_adjustments());
+        let availability = if dry_run {
+            AvailabilityTracker::default()
+        } else {
+            AvailabilityTracker::load(std::path::Path::new(&config.availability_path))
+        };
+        if sizing.is_ladder() {
+            info!(
+                "🪜 ORDER SIZE LADDER: ${:.2} now, +{}% per profitable trade up to ${:.2}",
+                sizing.order_size(),
+                config.order_size_step_pct,
+                sizing.max_order_size()
+            );
+        }
+        let executor = Executor::spawn(config.restart_on_panic, {
+            let (config, client, precision, fees, symbols) = (
+                config.clone(),
+                client.clone(),
+                precision_manager.clone(),
+                fees.clone(),
+                symbols.clone(),
+            );
+            move || {
+                build_trader(
+                    &config,
+                    backend,
+                    client.clone(),
+                    precision.clone(),
+                    fees.clone(),
+                    symbols.clone(),
+                    wallet_feed.clone(),
+                )
+            }
+        });
+        let simulator = ArbitrageTrader::new(client.clone(), true, precision_manager.clone())
+            .with_symbols(symbols.clone())
+            .with_fees(fees.clone(), config.fee_coin());
+
+        if dry_run {
+            info!(
+                "🧪 Running in DRY RUN mode ({} backend) - no actual trades will be executed",
+                backend.as_str()
+            );
+            info!("🎯 TRADE LIMIT: Bot will execute {max_trades} trade(s) and then stop");
+        } else {
+            info!("🚀 Running in LIVE TRADING mode - real trades will be executed!");
+            info!("🎯 TRADE LIMIT: Bot will execute {max_trades} trade(s) and then stop");
+        }
+
+        let journal = if config.journal_path.is_empty() {
+            None
+        } else {
+            match Journal::spawn_writer(
+                std::path::Path::new(&config.journal_path),
+                config.restart_on_panic,
+            ) {
+                Ok(journal) => {
+                    info!("📓 Journaling trades to {}", config.journal_path);
+                    Some(journal)
+                }
+                Err(e) => {
+                    warn!("⚠️ Trade journal disabled: {e:#}");
+                    None
+                }
+            }
+        };
+        let trade_store = if config.trade_db_path.is_empty() {
+            None
+        } else {
+            match TradeStore::spawn_writer(
+                std::path::Path::new(&config.trade_db_path),
+                config.restart_on_panic,
+            ) {
+                Ok(store) => {
+                    info!("📚 Storing trade history in {}", config.trade_db_path);
+                    Some(store)
+                }
+                Err(e) => {
+                    warn!("⚠️ Trade history disabled: {e:#}");
+                    None
+                }
+            }
+        };
+        let notifications = if config.telegram_bot_token.is_empty() {
+            None
+        } else {
+            match TelegramNotifier::new(&config) {
+                Ok(telegram) => {
+                    info!("📨 Sending trade and connectivity notifications to Telegram");
+                    Some(Notifications::spawn(
+                        Arc::new(telegram),
+                        config.restart_on_panic,
+                    ))
+                }
+                Err(e) => {
+                    warn!("⚠️ Telegram notifications disabled: {e:#}");
+                    None
+                }
+            }
+        };
+        let outage_alert = OutageAlert::new(config.notify_ws_down_secs);
+        // Check journaled fills against the exchange's execution history
+        let reconciliation = (journal.is_some() && !dry_run && config.reconcile_interval_secs > 0)
+            .then(|| {
+                info!(
+                    "🧾 Reconciling the journal with the exchange every {}s",
+                    config.reconcile_interval_secs
+                );
+                reconcile::spawn(
+                    Arc::new(client.clone()),
+                    config.journal_path.clone().into(),
+                    config.order_link_prefix.clone(),
+                    config.reconcile_interval_secs,
+                    config.restart_on_panic,
+                )
+            });
+        // Keep deposits and withdrawals apart from trading results
+        let external_flows = (!dry_run && config.external_flow_poll_secs > 0).then(|| {
+            info!(
+                "💸 Checking for deposits and withdrawals every {}s",
+                config.external_flow_poll_secs
+            );
+            flows::spawn(
+                Arc::new(client.clone()),
+                config.external_flow_poll_secs,
+                config.restart_on_panic,
+            )
+        });
+        // Pause trading through exchange maintenance and skip suspended symbols
+        let maintenance = (!dry_run && config.maintenance_poll_secs > 0).then(|| {
+            info!(
+                "🛠️ Checking {}'s maintenance windows and symbol statuses every {}s",
+                client.name(),
+                config.maintenance_poll_secs
+            );
+            maintenance::spawn(
+                Arc::new(client.clone()),
+                config.maintenance_poll_secs,
+                config.restart_on_panic,
+            )
+        });
+        if let Some(journal) = &journal {
+            journal.record(JournalEvent::SessionStarted {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                dry_run,
+                live_threshold_pct: config.min_profit_threshold,
+                shadow_threshold_pct: config.shadow_min_profit_threshold,
+                alert_threshold_pct: Some(config.alert_profit_threshold),
+            });
+        }
+
+        let events = if config.event_log_path.is_empty() {
+            None
+        } else {
+            match EventLog::spawn(
+                std::path::Path::new(&config.event_log_path),
+                config.restart_on_panic,
+            ) {
+                Ok(events) => {
+                    info!("⏺️ Recording session events to {}", config.event_log_path);
+                    Some(events)
+                }
+                Err(e) => {
+                    warn!("⚠️ Event log disabled: {e:#}");
+                    None
+                }
+            }
+        };
+        let event_changes = ChangeTracker::new(config.event_log_quote_interval_secs);
+        let recorder = if config.market_data_path.is_empty() {
+            None
+        } else {
+            match MarketRecorder::spawn(
+                std::path::Path::new(&config.market_data_path),
+                config.restart_on_panic,
+            ) {
+                Ok(recorder) => {
+                    info!("🎞️ Recording market data to {}", config.market_data_path);
+                    Some(recorder)
+                }
+                Err(e) => {
+                    warn!("⚠️ Market data recording disabled: {e:#}");
+                    None
+                }
+            }
+        };
+        if let Some(events) = &events {
+            events.record(SessionEvent::SessionStarted {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                dry_run,
+            });
+            for (key, value) in config_cmd::display_values(&config) {
+                events.record(SessionEvent::ConfigChanged {
+                    key: key.to_string(),
+                    value,
+                    reason: None,
+                });
+            }
+        }
+
+        let shadow_trader = config.shadow_min_profit_threshold.map(|threshold| {
+            info!(
+                "👥 SHADOW MODE: Simulating every opportunity above {threshold:.2}% (live threshold {:.2}%)",
+                config.min_profit_threshold
+            );
+            ShadowTrader::new(threshold, config.min_profit_threshold)
+        });
+
+        let strategy_ab = match strategy_ab::parse(&config.execution_ab_strategies) {
+            Ok(strategies) if strategies.is_empty() => None,
+            Ok(strategies) => {
+                info!(
+                    "🆎 A/B EXECUTION: Alternating {} per triangle",
+                    strategies
+                        .iter()
+                        .map(|s| s.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" / ")
+                );
+                Some(StrategyAb::new(strategies))
+            }
+            Err(e) => {
+                warn!("⚠️ Ignoring EXECUTION_AB_STRATEGIES: {e:#}");
+                None
+            }
+        };
+
+        let mut alerts =
+            OpportunityAlerts::new(config.alert_profit_threshold, config.min_profit_threshold)
+                .with_digests(config.log_digest_secs, config.journal_digest_secs);
+        info!(
+            "🔔 Alerting on opportunities above {:.2}%, executing above {:.2}%",
+            config.alert_profit_threshold, config.min_profit_threshold
+        );
+
+        let adaptive_threshold = config.adaptive_threshold_bounds().map(|(min, max)| {
+            let mut controller = AdaptiveThreshold::new(config.min_profit_threshold, min, max);
+            if !config.journal_path.is_empty() {
+                match Journal::read_records(std::path::Path::new(&config.journal_path)) {
+                    Ok(records) => {
+                        if let Some(resumed) = controller.resume_from(&records) {
+                            info!("🎚️ Resuming adaptive execution threshold at {resumed:.2}% from the journal");
+                        }
+                    }
+                    Err(e) => warn!("⚠️ Could not read journal for the adaptive threshold: {e:#}"),
+                }
+            }
+            info!(
+                "🎚️ ADAPTIVE THRESHOLD: {:.2}% within [{min:.2}%, {max:.2}%]",
+                controller.current()
+            );
+            arbitrage_engine.set_execution_threshold(controller.current());
+            alerts.set_execution_threshold(controller.current());
+            controller
+        });
+
+        let intermediate_limits = match limits::parse_limits(&config.intermediate_coin_limits) {
+            Ok(caps) if !caps.is_empty() => {
+                info!("🚧 Intermediate coin limits per hour: {caps:?}");
+                IntermediateLimits::load(
+                    caps,
+                    std::path::Path::new(&config.intermediate_usage_path),
+                )
+            }
+            Ok(_) => IntermediateLimits::new(Default::default()),
+            Err(e) => {
+                warn!("⚠️ Ignoring INTERMEDIATE_COIN_LIMITS: {e:#}");
+                IntermediateLimits::new(Default::default())
+            }
+        };
+
+        let overlap_cooldown =
+            OverlapCooldown::new(Duration::from_secs(config.overlap_cooldown_secs));
+        if config.overlap_cooldown_secs > 0 {
+            info!(
+                "⏱️ Overlap cooldown: {}s between executions sharing a symbol",
+                config.overlap_cooldown_secs
+            );
+        }
+
+        let funding_mode = FundingMode::parse(&config.funding_mode, &config.funding_coins)
+            .unwrap_or_else(|e| {
+                warn!("⚠️ Ignoring FUNDING_MODE: {e:#}");
+                FundingMode::UsdtOnly
+            });
+        if config.race_mode {
+            info!("🏁 RACE MODE: Racing leg 1 of the top two disjoint opportunities");
+        }
+        match &funding_mode {
+            FundingMode::UsdtOnly => {}
+            FundingMode::Coins(coins) => info!(
+                "💵 FUNDING: Trading from {}, sized at live prices",
+                coins.join(", ")
+            ),
+            mode => info!("💵 FUNDING: Trading from stablecoins ({mode:?})"),
+        }
+
+        let stable_scanner = config.stable_min_deviation_pct.map(|threshold| {
+            info!(
+                "🪙 STABLE FAST PATH: Converting stable pairs {:.2}% or more from peg (max ${:.0} inventory per coin)",
+                threshold, config.stable_max_inventory_usd
+            );
+            StableScanner::new(
+                threshold,
+                config.stable_max_inventory_usd,
+                fees.clone(),
+            )
+        });
+
+        let dust_sweeper = (config.dust_sweep_interval_secs > 0).then(|| {
+            info!(
+                "🧹 DUST SWEEPER: Converting leftovers worth up to ${:.2} per coin back to {SWEEP_COIN} every {}s",
+                config.dust_sweep_max_usd, config.dust_sweep_interval_secs
+            );
+            DustSweeper::new(
+                Duration::from_secs(config.dust_sweep_interval_secs),
+                config.dust_sweep_max_usd,
+            )
+        });
+
+        // Setup WebSocket
+        let (tx, rx) = tokio::sync::mpsc::channel(10000);
+        let (depth_tx, depth_rx) = tokio::sync::mpsc::channel(10000);
+        let depth_pricer = config.depth_pricing_top_fraction.map(|fraction| {
+            info!(
+                "📚 DEPTH PRICING: Walking the book for legs above {:.0}% of the top level",
+                fraction * 100.0
+            );
+            DepthPricer::new(fraction, fees.clone())
+        });
+
+        let bandwidth = BandwidthMeter::new();
+        if let Some(kbps) = config.ws_bandwidth_budget_kbps {
+            info!("📶 BANDWIDTH BUDGET: Trimming WebSocket subscriptions above {kbps:.1} KB/s");
+        }
+        let bandwidth_budget =
+            BandwidthBudget::new(config.ws_bandwidth_budget_kbps, Instant::now());
+        let feed_health = FeedHealth::new(Instant::now());
+        let focus_filter = FocusFilter::new();
+        let cross_check = (config.cross_check_top_n > 0).then(|| {
+            info!(
+                "🔀 CROSS-CHECK: Mirroring the symbols of the top {} opportunities on a second connection",
+                config.cross_check_top_n
+            );
+            FeedCrossCheck::new(
+                config.cross_check_tolerance_pct,
+                config.cross_check_silence_ms,
+            )
+        });
+        let quote_delays = (config.quote_delay_discount_pct > 0.0)
+            .then(|| QuoteDelays::new(config.quote_delay_discount_pct));
+        let degraded = DegradedPricing::new(
+            config.ws_degraded_after_secs,
+            config.rest_poll_interval_secs,
+        );
+
+        // Optimization: Only subscribe to liquid symbols to save bandwidth and connections
+        let all_symbols_count = pair_manager.get_pairs().len();
+        let symbols = pair_manager.get_liquid_symbols();
+
+        info!(
+            "🔌 Optimizing WebSocket: Selected {} liquid symbols out of {} total",
+            symbols.len(),
+            all_symbols_count
+        );
+
+        if symbols.is_empty() {
+            warn!("⚠️ No liquid symbols found! WebSocket will not subscribe to any pairs.");
+        } else {
+            info!(
+                "🔌 Connecting to WebSocket for {} liquid symbols...",
+                symbols.len()
+            );
+
+            // Split symbols into chunks to respect the exchange's per-connection topic limit
+            let max_topics = client.max_stream_symbols();
+            let chunks = stream::connections(&symbols, max_topics);
+
+            info!(
+                "🔌 Spawning {} WebSocket connections to handle liquid symbols",
+                chunks.len()
+            );
+
+            // Depth only where the order size outgrows the displayed top level
+            let depth_chunks = stream::connections(
+                &depth_pricer
+                    .as_ref()
+                    .map(|pricer| {
+                        pricer.symbols_needing_depth(&pair_manager, sizing.max_order_size())
+                    })
+                    .unwrap_or_default(),
+                max_topics,
+            );
+            if depth_pricer.is_some() {
+                info!(
+                    "📚 DEPTH PRICING: Streaming depth for {} of {} liquid symbols",
+                    depth_chunks.iter().map(Vec::len).sum::<usize>(),
+                    symbols.len()
+                );
+            }
+
+            // Connect in the background: scanning starts on the REST snapshot right away
+            let restart_on_panic = config.restart_on_panic;
+            let exchange = client.clone();
+            let taps = StreamTaps {
+                transport: WsTransport::from_config(&config)?,
+                bandwidth: bandwidth.clone(),
+                health: feed_health.clone(),
+                focus: focus_filter.clone(),
+                cross_check: cross_check.clone(),
+                quote_delays: quote_delays.clone(),
+            };
+            tokio::spawn(async move {
+                for (i, chunk) in chunks.into_iter().enumerate() {
+                    let (exchange, taps, tx) = (exchange.clone(), taps.clone(), tx.clone());
+                    let conn_id = i + 1;
+                    info!("🔌 Connection #{conn_id}: Managing {} symbols", chunk.len());
+                    supervisor::spawn_supervised(
+                        format!("WebSocket connection #{conn_id}"),
+                        restart_on_panic,
+                        move || exchange.ticker_stream(conn_id, chunk.clone(), tx.clone(), &taps),
+                    );
+                    // Add a small delay between connections to avoid rate limits
+                    sleep(Duration::from_millis(100)).await;
+                }
+                for (i, chunk) in depth_chunks.into_iter().enumerate() {
+                    let (exchange, taps, depth_tx) =
+                        (exchange.clone(), taps.clone(), depth_tx.clone());
+                    let conn_id = i + 1;
+                    supervisor::spawn_supervised(
+                        format!("Depth WebSocket connection #{conn_id}"),
+                        restart_on_panic,
+                        move || {
+                            exchange.depth_stream(conn_id, chunk.clone(), depth_tx.clone(), &taps)
+                        },
+                    );
+                    sleep(Duration::from_millis(100)).await;
+                }
+                if let Some(cross_check) = taps.cross_check.clone() {
+                    // Not counted in the feed health: it streams too few symbols to price from
+                    supervisor::spawn_supervised(
+                        "Mirror WebSocket connection".to_string(),
+                        restart_on_panic,
+                        move || exchange.mirror_stream(cross_check.clone(), &taps),
+                    );
+                }
+            });
+        }
+        let warmup = QuoteWarmup::new(&symbols, Instant::now());
+
+        // Ctrl+C handling: stop new trades, let in-flight ones finish, force exit on third press
+        let shutdown = ShutdownCoordinator::new(Duration::from_secs(config.shutdown_timeout_secs));
+        shutdown.spawn_signal_listener();
+
+        state.update_snapshot(|s| s.dry_run = dry_run);
+        let reporting = ReportingCurrency::new(&config.reporting_currency);
+
+        let risk = build_risk_manager(&config, trade_store.as_ref(), &pair_manager);
+        let strategies = strategy::additional(&config);
+        let approval = config
+            .trade_approval
+            .then(|| ApprovalGate::new(config.approval_window_secs));
+        if !strategies.is_empty() {
+            let names: Vec<_> = strategies.iter().map(|s| s.name()).collect();
+            info!(
+                "🧩 Strategies: {}, {}",
+                arbitrage_engine.name(),
+                names.join(", ")
+            );
+        }
+
+        Ok(Self {
+            config,
+            client,
+            state,
+            shutdown,
+            balance_manager,
+            balance_sync,
+            balances,
+            pair_manager,
+            arbitrage_engine,
+            strategies,
+            executor,
+            in_flight: None,
+            simulator,
+            journal,
+            trade_store,
+            notifications,
+            outage_alert,
+            risk,
+            events,
+            recorder,
+            event_changes,
+            shadow_trader,
+            strategy_ab,
+            alerts,
+            adaptive_threshold,
+            intermediate_limits,
+            overlap_cooldown,
+            stable_scanner,
+            dust_sweeper,
+            edge_decay: EdgeDecay::new(),
+            funding_mode,
+            sizing,
+            dust_inventory,
+            triangle_ledger,
+            availability,
+            utilization: CapitalUtilization::new(chrono::Utc::now()),
+            max_trades,
+            rx,
+            warmup,
+            depth_pricer,
+            depth_rx,
+            bandwidth,
+            bandwidth_budget,
+            focus_filter,
+            cross_check,
+            approval,
+            quote_delays,
+            feed_health,
+            degraded,
+            reporting,
+            session_profit_usd: 0.0,
+            reconciliation,
+            external_flows,
+            maintenance,
+            blacklist,
+            cycle_count: 0,
+            initial_scan_logged: false,
+            trades_completed: 0,
+            last_status_publish: Instant::now(),
+            last_position_check: Instant::now(),
+            scan_backoff: Backoff::new(),
+            last_scan_panic: Instant::now(),
+            start_time: Instant::now(),
+        })
+    }
+
+    /// Scan and trade until shutdown is requested or the trade limit is reached
+    pub async fn run(mut self) -> Result<()> {
+        self.recover_order_intents().await;
+        self.resume_interrupted().await;
+        self.state.transition(RunEvent::Initialized);
+        info!("🚀 Bot started. Press Ctrl+C to stop.");
+
+        // Main application loop - will exit after reaching max trades
+        loop {
+            // 0. Record a trade the execution task has finished with
+            if self.collect_execution().await {
+                self.state.transition(RunEvent::Shutdown);
+                break;
+            }
+
+            // 1. Exit or liquidate parked positions and sweep dust between trades (NOT cancellable)
+            if self.in_flight.is_none() {
+                self.manage_positions().await;
+                self.sweep_dust().await;
+            }
+
+            // 2. Scan for opportunities, also while a trade executes (cancellable)
+            let candidate = self.scan().await;
+            if self.state.run_state() == RunState::ShuttingDown {
+                break;
+            }
+
+            if let Some((opportunity, amount, runner_up)) = candidate {
+                // 3. Hand the trade to the execution task, one at a time
+                if self.in_flight.is_some() {
+                    continue;
+                }
+                if self.shutdown.is_requested() {
+                    info!("🛑 Shutdown in progress - not starting a new trade");
+                    self.state.transition(RunEvent::Shutdown);
+                    break;
+                }
+                let Some(start_usd) = self.pair_manager.usd_price(&opportunity.path[0]) else {
+                    warn!(
+                        "⚠️ Skipping {}: no live USD price for {}",
+                        opportunity.display_pairs(),
+                        opportunity.path[0]
+                    );
+                    continue;
+                };
+                if self.risk_halts(amount * start_usd) {
+                    continue;
+                }
+                if !self.prices_fresh(&opportunity) {
+                    continue;
+                }
+                let runner_up = runner_up.filter(|(runner_up, _)| self.prices_fresh(runner_up));
+                if !self.state.transition(RunEvent::ExecutionStarted) {
+                    debug!(
+                        "⏸️ Trading {} - skipping {:.2}% opportunity via {}",
+                        self.state.run_state(),
+                        opportunity.estimated_profit_pct,
+                        opportunity.display_pairs()
+                    );
+                    continue;
+                }
+
+                self.dispatch(opportunity, amount, runner_up);
+            } else if self.in_flight.is_none() {
+                // 4. Stable-to-stable conversion when no triangle qualifies (NOT cancellable)
+                self.convert_stable().await;
+            }
+        }
+
+        self.finish().await;
+        Ok(())
+    }
+
+    /// Halt trading if a risk limit forbids a trade of `amount_usd`; true if one does.
+    /// Scanning carries on until an operator resumes trading.
+    fn risk_halts(&mut self, amount_usd: f64) -> bool {
+        let Some(reason) = self.risk.check(amount_usd, chrono::Utc::now()) else {
+            return false;
+        };
+        if self.state.transition(RunEvent::Halt(reason.clone())) {
+            error!(
+                "🛑 RISK LIMIT: {reason} - trading HALTED, scanning continues until trading is resumed (SIGUSR2)"
+            );
+            if let Some(notifications) = &self.notifications {
+                notifications.notify(format!("🛑 Trading halted: {reason}"));
+            }
+        }
+        true
+    }
+
+    async fn manage_positions(&mut self) {
+        if self.shutdown.is_requested()
+            || self.last_position_check.elapsed() < POSITION_CHECK_INTERVAL
+        {
+            return;
+        }
+        self.last_position_check = Instant::now();
+        let execution_guard = self.shutdown.begin_execution();
+        let (closed, orders) = self.executor.manage_positions().await;
+        drop(execution_guard);
+        let legs = JournalLeg::from_orders(&orders);
+
+        if !closed.is_empty() {
+            self.refresh_balances().await;
+        }
+        for outcome in closed {
+            self.risk.position_closed(&outcome.position.order_id);
+            if let Some(journal) = &self.journal {
+                let liquidation = legs
+                    .iter()
+                    .filter(|leg| leg.symbol == outcome.position.symbol)
+                    .cloned()
+                    .collect();
+                journal.record(JournalEvent::position_closed(&outcome).with_legs(liquidation));
+            }
+        }
+    }
+
+    /// Run one scan cycle, or move to `ShuttingDown` if Ctrl+C arrives first.
+    /// A panicking cycle is logged and retried after a backoff.
+    async fn scan(&mut self) -> Option<Candidate> {
+        // Demand a wider margin while prices come from REST polling
+        if let Some(degraded) = self.degraded.update(&self.feed_health, Instant::now()) {
+            let margin = if degraded {
+                self.config.degraded_extra_profit_pct
+            } else {
+                0.0
+            };
+            self.arbitrage_engine.set_execution_margin(margin);
+        }
+        if let Some(notifications) = &self.notifications {
+            let down_for = self.feed_health.all_down_for(Instant::now());
+            if let Some(message) = self.outage_alert.update(down_for) {
+                notifications.notify(message);
+            }
+        }
+        self.apply_trading_status();
+        if let Some(focus) = self.state.take_focus_request() {
+            self.apply_focus(focus);
+        }
+        while let Ok(update) = self.depth_rx.try_recv() {
+            if let Some(pricer) = self.depth_pricer.as_mut() {
+                pricer.apply(&update);
+            }
+        }
+        let cycle = AssertUnwindSafe(scan_arbitrage_cycle(
+            &self.config,
+            &self.client,
+            &mut self.balances,
+            &mut self.balance_manager,
+            &mut self.pair_manager,
+            &mut self.arbitrage_engine,
+            &mut self.strategies,
+            self.depth_pricer.as_ref(),
+            self.cycle_count + 1,
+            &mut self.initial_scan_logged,
+            self.sizing.order_size(),
+            &self.funding_mode,
+            &self.intermediate_limits,
+            &self.overlap_cooldown,
+            &mut self.rx,
+            &mut self.warmup,
+            &mut self.degraded,
+            self.recorder.as_ref(),
+        ))
+        .catch_unwind();
+        let res = tokio::select! {
+            _ = self.shutdown.wait_for_request() => {
+                info!("🛑 Received Ctrl+C signal. Shutting down...");
+                self.state.transition(RunEvent::Shutdown);
+                return None;
+            }
+            res = cycle => res,
+        };
+        let res = match res {
+            Ok(res) => res,
+            Err(panic) => {
+                error!("💥 Scanner panicked: {}", supervisor::panic_message(panic));
+                let delay = self.scan_backoff.next_delay(self.last_scan_panic.elapsed());
+                self.last_scan_panic = Instant::now();
+                warn!("🔄 Resuming scanning in {}s", delay.as_secs());
+                sleep(delay).await;
+                return None;
+            }
+        };
+        self.cycle_count += 1;
+        self.state.record_cycle();
+
+        // Keep the feeds within the bandwidth budget, dropping the lowest-volume symbols
+        let pair_manager = &self.pair_manager;
+        let trimmed = self
+            .bandwidth_budget
+            .check(&self.bandwidth, Instant::now(), |symbol| {
+                pair_manager
+                    .get_pair(symbol)
+                    .map_or(0.0, |p| p.volume_24h_usd)
+            });
+        if !trimmed.is_empty() {
+            self.pair_manager.unsubscribe(&trimmed);
+        }
+
+        // Publish slow-changing status for the control API at most once per second
+        if self.last_status_publish.elapsed() >= Duration::from_secs(1) {
+            self.last_status_publish = Instant::now();
+            if let Some(events) = &self.events {
+                let changes = &mut self.event_changes;
+                let quotes = changes.quotes(self.pair_manager.get_pairs(), Instant::now());
+                let balances = changes.balances(
+                    self.balance_manager
+                        .get_significant_balances(0.000001)
+                        .into_iter()
+                        .collect(),
+                );
+                quotes
+                    .into_iter()
+                    .chain(balances)
+                    .for_each(|event| events.record(event));
+            }
+            let reports: Vec<ReconcileReport> = self
+                .reconciliation
+                .as_mut()
+                .map(|rx| std::iter::from_fn(|| rx.try_recv().ok()).collect())
+                .unwrap_or_default();
+            for report in reports {
+                self.apply_reconciliation(report);
+            }
+            let flows: Vec<ExternalFlow> = self
+                .external_flows
+                .as_mut()
+                .map(|rx| std::iter::from_fn(|| rx.try_recv().ok()).collect())
+                .unwrap_or_default();
+            for flow in flows {
+                self.apply_external_flow(flow);
+            }
+            let reloaded = self
+                .blacklist
+                .as_mut()
+                .and_then(|rx| std::iter::from_fn(|| rx.try_recv().ok()).last());
+            if let Some(tokens) = reloaded {
+                self.apply_blacklist(tokens);
+            }
+            if let Some(delays) = &self.quote_delays {
+                self.arbitrage_engine
+                    .set_delay_discounts(self.pair_manager.symbols().by_id(delays.discounts()));
+            }
+            let utilization = self
+                .utilization
+                .summary(chrono::Utc::now(), self.account_value_usd());
+            let (balance_manager, pair_manager, arbitrage_engine) = (
+                &self.balance_manager,
+                &self.pair_manager,
+                &self.arbitrage_engine,
+            );
+            let bandwidth = self.bandwidth.status(&self.bandwidth_budget);
+            let connection = self.client.connection_status();
+            let (prices_degraded, ws_connections) =
+                (self.degraded.is_active(), self.feed_health.connected());
+            // Fully operational: prices on WebSockets, the cycle's API calls answered and
+            // trading neither halted nor waiting out maintenance
+            let operational = !prices_degraded
+                && ws_connections > 0
+                && res.is_ok()
+                && !matches!(
+                    self.state.run_state(),
+                    RunState::Halted | RunState::Maintenance
+                );
+            let now = chrono::Utc::now();
+            self.availability.record(now, operational);
+            let availability = self.availability.summary(now);
+            self.reporting
+                .update(|symbol| pair_manager.get_pair(symbol));
+            let reporting = (!self.reporting.is_usd()).then(|| self.reporting.clone());
+            self.state.update_snapshot(|s| {
+                s.balances = balance_manager
+                    .get_significant_balances(0.000001)
+                    .into_iter()
+                    .collect();
+                s.balances_updated_at = balance_manager.last_updated();
+                s.last_price_update = pair_manager.last_price_update();
+                s.best_opportunity = arbitrage_engine.last_scan_best.as_ref().map(Into::into);
+                s.endpoints = connection.endpoints;
+                s.endpoint_switches = connection.endpoint_switches;
+                s.bandwidth = bandwidth;
+                s.request_latency = connection.request_latency;
+                s.prices_degraded = prices_degraded;
+                s.ws_connections = ws_connections;
+                s.reporting = reporting;
+                s.availability = availability;
+                s.utilization = utilization;
+            });
+        }
+        match res {
+            Ok(outcome) => {
+                // Only log every 10000 cycles to reduce spam
+                if self.cycle_count.is_multiple_of(100000) {
+                    debug!(
+                        "✅ Status: Completed {} cycles successfully (Trades: {}/{})",
+                        self.cycle_count, self.trades_completed, self.max_trades
+                    );
+                }
+                self.edge_decay.observe(&outcome.candidates, Instant::now());
+                self.triangle_ledger.observe(&outcome.candidates);
+                self.alerts
+                    .process(&outcome.candidates, self.journal.as_ref());
+                if let Some(shadow) = self.shadow_trader.as_mut() {
+                    shadow.process(
+                        &outcome.candidates,
+                        &self.simulator,
+                        self.sizing.order_size(),
+                        self.journal.as_ref(),
+                    );
+                }
+                self.watch_best(&outcome.candidates);
+                let (opportunity, amount) = self.unexpired(outcome.live?)?;
+                if !self.cross_checked(&opportunity) {
+                    return None;
+                }
+                // While paused or executing nothing new executes, so there is nothing to approve
+                if let Some(gate) = self
+                    .approval
+                    .as_mut()
+                    .filter(|_| !self.state.is_paused() && self.in_flight.is_none())
+                {
+                    if !gate.check(&opportunity, &self.state, chrono::Utc::now()) {
+                        return None;
+                    }
+                    // Only the approved triangle may execute, so it doesn't race
+                    return Some((opportunity, amount, None));
+                }
+                let runner_up = outcome
+                    .runner_up
+                    .and_then(|runner_up| self.unexpired(runner_up))
+                    .filter(|(runner_up, _)| self.cross_checked(runner_up));
+                Some((opportunity, amount, runner_up))
+            }
+            Err(e) => {
+                if !self
+                    .alerts
+                    .digest_warning("Arbitrage Cycle errors", format!("{e:#}"))
+                {
+                    log_error_with_context("Arbitrage Cycle", &*e);
+                    log_warning("Recovery", "Continuing to next cycle after error");
+                }
+                // Successful cycles send due digests when processing alerts
+                self.alerts
+                    .send_digests(Instant::now(), self.journal.as_ref(), false);
+                None
+            }
+        }
+    }
+
+    /// USD value of every balance with a price, as the capital turnover is measured against
+    fn account_value_usd(&self) -> f64 {
+        self.balance_manager
+            .get_all_balances()
+            .iter()
+            .filter_map(|(coin, qty)| Some(qty * self.pair_manager.usd_price(coin)?))
+            .sum()
+    }
+
+    /// Drop an opportunity that expired while the scan was running, e.g. after a slow
+    /// balance refresh, rather than trade on its stale prices
+    fn unexpired(
+        &self,
+        (mut opportunity, amount): (ArbitrageOpportunity, f64),
+    ) -> Option<(ArbitrageOpportunity, f64)> {
+        self.edge_decay.limit_expiry(&mut opportunity);
+        let now = chrono::Utc::now();
+        if !opportunity.is_expired(now) {
+            return Some((opportunity, amount));
+        }
+        let late = opportunity
+            .valid_until
+            .map_or(0, |valid_until| (now - valid_until).num_milliseconds());
+        info!(
+            "⌛ Dropping {}: expired {late}ms before it could execute",
+            opportunity.display_path()
+        );
+        None
+    }
+
+    /// Whether every leg's price arrived within MAX_PRICE_AGE_MS, so a feed that died
+    /// silently can't get a triangle traded on its last prices
+    fn prices_fresh(&mut self, opportunity: &ArbitrageOpportunity) -> bool {
+        let max_age = Duration::from_millis(self.config.max_price_age_ms);
+        if max_age.is_zero() {
+            return true;
+        }
+        let Some((symbol, age)) =
+            self.pair_manager
+                .stale_price(&opportunity.pairs, max_age, Instant::now())
+        else {
+            return true;
+        };
+        let age = age.map_or("never".to_string(), |age| {
+            format!("{}ms ago", age.as_millis())
+        });
+        let message = format!(
+            "Not executing {}: last {symbol} price arrived {age}",
+            opportunity.display_path()
+        );
+        if !self.alerts.digest_warning("Stale prices", message.clone()) {
+            warn!("🧊 {message}");
+        }
+        false
+    }
+
+    /// Cross-check the symbols of the best opportunities from now on
+    fn watch_best(&self, candidates: &[ArbitrageOpportunity]) {
+        let Some(cross_check) = &self.cross_check else {
+            return;
+        };
+        // A cycle without price changes finds nothing new; keep watching the last best
+        if candidates.is_empty() {
+            return;
+        }
+        let symbols = candidates
+            .iter()
+            .take(self.config.cross_check_top_n)
+            .flat_map(|opportunity| opportunity.pairs.iter().cloned())
+            .collect();
+        cross_check.watch(symbols);
+    }
+
+    /// Whether both feeds agree on every leg's quote, or cross-checking is off
+    fn cross_checked(&self, opportunity: &ArbitrageOpportunity) -> bool {
+        let Some(cross_check) = &self.cross_check else {
+            return true;
+        };
+        match opportunity
+            .pairs
+            .iter()
+            .find_map(|symbol| cross_check.unreliable_reason(symbol))
+        {
+            Some(reason) => {
+                debug!("🔀 Skipping {}: {reason}", opportunity.path.join(" → "));
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// Hand a triangle, or a race against the runner-up, to the execution task. Scanning
+    /// carries on; the outcome is recorded by `collect_execution` once it is in.
+    fn dispatch(
+        &mut self,
+        best_opportunity: ArbitrageOpportunity,
+        trade_amount: f64,
+        runner_up: Option<(ArbitrageOpportunity, f64)>,
+    ) {
+        warn!(
+            "💰 EXECUTING TRADE #{}: Found profitable opportunity {:.2}% - executing!",
+            self.trades_completed + 1,
+            best_opportunity.estimated_profit_pct
+        );
+
+        // Hold the start amounts until the execution is over, so no scan sizes a trade on them
+        if let Err(e) = self
+            .balance_manager
+            .reserve(&best_opportunity.path[0], trade_amount)
+        {
+            warn!("⚠️ Skipping trade: {e:#}");
+            self.state.transition(RunEvent::ExecutionFinished);
+            return;
+        }
+        let runner_up = runner_up.filter(|(opportunity, amount)| {
+            if self.pair_manager.usd_price(&opportunity.path[0]).is_none() {
+                warn!(
+                    "⚠️ Trading without the runner-up: no live USD price for {}",
+                    opportunity.path[0]
+                );
+                return false;
+            }
+            match self.balance_manager.reserve(&opportunity.path[0], *amount) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("⚠️ Trading without the runner-up: {e:#}");
+                    false
+                }
+            }
+        });
+        let reservations: Vec<(String, f64)> = std::iter::once((&best_opportunity, trade_amount))
+            .chain(runner_up.iter().map(|(o, amount)| (o, *amount)))
+            .map(|(o, amount)| (o.path[0].clone(), amount))
+            .collect();
+
+        let strategy = self
+            .strategy_ab
+            .as_ref()
+            .map(|ab| ab.pick(&best_opportunity));
+        let order = TriangleOrder {
+            strategy,
+            edge_half_life: self.edge_decay.half_life(&best_opportunity),
+            local_book: best_opportunity
+                .pairs
+                .iter()
+                .chain(runner_up.iter().flat_map(|(o, _)| &o.pairs))
+                .filter_map(|symbol| {
+                    let id = self.pair_manager.symbols().symbol_id(symbol)?;
+                    Some((id, TopOfBook::from_pair(self.pair_manager.pair(id)?)))
+                })
+                .collect(),
+            usd_prices: best_opportunity
+                .path
+                .iter()
+                .chain(runner_up.iter().flat_map(|(o, _)| &o.path))
+                .filter_map(|coin| Some((coin.clone(), self.pair_manager.usd_price(coin)?)))
+                .collect(),
+            depth_books: best_opportunity
+                .pairs
+                .iter()
+                .chain(runner_up.iter().flat_map(|(o, _)| &o.pairs))
+                .filter_map(|symbol| {
+                    let book = self.depth_pricer.as_ref()?.book(symbol)?;
+                    Some((self.pair_manager.symbols().symbol_id(symbol)?, book.clone()))
+                })
+                .collect(),
+            opportunity: best_opportunity,
+            amount: trade_amount,
+            runner_up,
+        };
+        // Quotes and balances as the execution starts, for a debug bundle if it fails
+        let quotes: BTreeMap<String, Quote> = order
+            .local_book
+            .keys()
+            .filter_map(|&id| {
+                let pair = self.pair_manager.pair(id)?;
+                Some((pair.symbol.clone(), Quote::from_pair(pair)))
+            })
+            .collect();
+        let balances_before = self.balance_manager.get_all_balances().clone();
+        let usd_prices = order.usd_prices.clone();
+        self.record_event(|| SessionEvent::OpportunityDetected {
+            opportunity: order.opportunity.clone(),
+            amount: order.amount,
+        });
+        let guard = self.shutdown.begin_execution();
+        match self.executor.submit(order) {
+            Ok(pending) => {
+                self.in_flight = Some(InFlight {
+                    pending,
+                    reservations,
+                    quotes,
+                    balances_before,
+                    started_at: chrono::Utc::now(),
+                    execution_start: Instant::now(),
+                    usd_prices,
+                    strategy,
+                    _guard: guard,
+                });
+            }
+            Err(e) => {
+                warn!("⚠️ Skipping trade: {e:#}");
+                for (coin, amount) in &reservations {
+                    self.balance_manager.release(coin, *amount, 0.0);
+                }
+                self.state.transition(RunEvent::ExecutionFinished);
+            }
+        }
+    }
+
+    /// Record the trade in flight if the execution task has reported back. Returns true
+    /// once the trade limit is reached.
+    async fn collect_execution(&mut self) -> bool {
+        let Some(report) = self.in_flight.as_mut().and_then(|f| f.pending.try_report()) else {
+            return false;
+        };
+        let in_flight = self.in_flight.take().expect("report of a trade in flight");
+        self.settle(in_flight, report).await
+    }
+
+    /// Release what the trade held and record its outcome. Returns true once the trade
+    /// limit is reached.
+    async fn settle(&mut self, in_flight: InFlight, report: TriangleReport) -> bool {
+        let limit_reached = self.record_execution(in_flight, report).await;
+        self.state.transition(RunEvent::ExecutionFinished);
+        // Halt right away on a loss limit, rather than at the next opportunity
+        self.risk_halts(0.0);
+        limit_reached
+    }
+
+    /// Record a finished execution in the journal, stats and limits. Returns true once the
+    /// trade limit is reached.
+    async fn record_execution(&mut self, in_flight: InFlight, report: TriangleReport) -> bool {
+        let InFlight {
+            reservations,
+            quotes,
+            balances_before,
+            started_at,
+            execution_start,
+            usd_prices,
+            strategy,
+            _guard: guard,
+            ..
+        } = in_flight;
+        drop(guard);
+        let TriangleReport {
+            opportunity: best_opportunity,
+            amount: trade_amount,
+            execution,
+            parked,
+            penalties,
+            orders,
+        } = report;
+        let legs = JournalLeg::from_orders(&orders);
+        let mut spent = net_spent(&legs, &self.pair_manager);
+        for (coin, amount) in &reservations {
+            // Both legs 1 of a race may start from one coin; what was spent counts once
+            let spent = spent.remove(coin).unwrap_or(0.0);
+            self.balance_manager.release(coin, *amount, spent);
+        }
+        // Amounts and profits are in the start coin, which need not be a USD stablecoin.
+        // Only start coins with a USD price are dispatched, so the price is always there.
+        let start_usd = usd_prices
+            .get(&best_opportunity.path[0])
+            .copied()
+            .unwrap_or_default();
+        let profit_usd = execution
+            .as_ref()
+            .map_or(0.0, |r| r.actual_profit * start_usd);
+        // A completed triangle trades its amount once per leg; otherwise count the fills
+        let legs_traded = if execution.as_ref().is_ok_and(|r| r.success) {
+            3
+        } else {
+            orders.fills.len()
+        };
+        self.utilization.record(
+            started_at,
+            execution_start.elapsed().as_secs_f64(),
+            trade_amount * start_usd * legs_traded as f64,
+        );
+        let orders_sent = !orders.placed.is_empty();
+        if let (Some(ab), Some(strategy)) = (&mut self.strategy_ab, strategy) {
+            // Executions that never reached the exchange say nothing about the strategy
+            if orders_sent {
+                ab.record(
+                    strategy,
+                    &best_opportunity,
+                    execution.as_ref().ok().map(|r| r.actual_profit_pct),
+                );
+            }
+        }
+        match &execution {
+            Ok(result) => self.check_exchange_error(result.exchange_error.as_deref()),
+            Err(e) => self.check_exchange_error(BybitError::find(e)),
+        }
+
+        if let Some(events) = &self.events {
+            for order in &orders.placed {
+                events.record(SessionEvent::OrderPlaced {
+                    order: order.clone(),
+                });
+            }
+            for order in &orders.fills {
+                events.record(SessionEvent::OrderFilled {
+                    order: order.clone(),
+                });
+            }
+            events.record(SessionEvent::TradeFinished {
+                path: best_opportunity.display_path(),
+                success: execution.as_ref().is_ok_and(|r| r.success),
+                profit_usd,
+                error: match &execution {
+                    Ok(r) => r.error_message.clone(),
+                    Err(e) => Some(e.to_string()),
+                },
+            });
+        }
+
+        if !execution.as_ref().is_ok_and(|r| r.success) && !self.config.debug_bundle_dir.is_empty()
+        {
+            // Balances after whatever the failed legs and rollback left behind
+            self.refresh_balances().await;
+            let balances_after = self
+                .balances
+                .borrow()
+                .as_ref()
+                .map(|snapshot| snapshot.balances.clone().into_iter().collect())
+                .unwrap_or_default();
+            let bundle = DebugBundle {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                created_at: chrono::Utc::now(),
+                dry_run: self.config.dry_run,
+                precision: best_opportunity
+                    .pairs
+                    .iter()
+                    .filter_map(|symbol| {
+                        let info = self
+                            .simulator
+                            .get_precision_manager()
+                            .get_symbol_precision(symbol)?;
+                        Some((symbol.clone(), info.clone()))
+                    })
+                    .collect(),
+                opportunity: best_opportunity.clone(),
+                amount: trade_amount,
+                error: match &execution {
+                    Ok(r) => r.error_message.clone(),
+                    Err(e) => Some(format!("{e:#}")),
+                },
+                result: execution.as_ref().ok().cloned(),
+                quotes,
+                orders,
+                balances_before: balances_before.into_iter().collect(),
+                balances_after,
+            };
+            let config = &self.config;
+            let secrets = [
+                config.api_key.as_str(),
+                &config.api_secret,
+                &config.api_key_secondary,
+                &config.api_secret_secondary,
+                &config.telegram_bot_token,
+            ];
+            match bundle
+                .write(std::path::Path::new(&config.debug_bundle_dir), &secrets)
+                .await
+            {
+                Ok(path) => warn!(
+                    "🧰 Debug bundle saved to {} - attach it to bug reports",
+                    path.display()
+                ),
+                Err(e) => warn!("⚠️ Failed to save debug bundle: {e:#}"),
+            }
+        }
+
+        self.state.update_snapshot(|s| {
+            s.last_trade = Some(state::TradeSummary {
+                at: chrono::Utc::now(),
+                path: best_opportunity.display_path(),
+                success: execution.as_ref().is_ok_and(|r| r.success),
+                profit_usd,
+                profit_pct: execution.as_ref().map_or(0.0, |r| r.actual_profit_pct),
+                error: match &execution {
+                    Ok(r) => r.error_message.clone(),
+                    Err(e) => Some(e.to_string()),
+                },
+            });
+        });
+
+        if let Some(notifications) = &self.notifications {
+            notifications.notify(notifier::trade_message(
+                &best_opportunity,
+                trade_amount,
+                &execution,
+                legs.len(),
+                !parked.is_empty(),
+            ));
+        }
+        if let Some(store) = &self.trade_store {
+            store.record(
+                &best_opportunity,
+                trade_amount,
+                self.config.dry_run,
+                &execution,
+                &legs,
+            );
+        }
+        if let Some(journal) = &self.journal {
+            journal.record(
+                JournalEvent::live_trade(&best_opportunity, trade_amount, &execution)
+                    .with_legs(legs),
+            );
+        }
+        for position in parked {
+            let price = self
+                .pair_manager
+                .usd_price(&position.target_coin)
+                .unwrap_or(1.0);
+            self.risk
+                .position_parked(&position.order_id, position.breakeven_amount * price);
+            if let Some(journal) = &self.journal {
+                journal.record(JournalEvent::position_parked(&position));
+            }
+        }
+        match &execution {
+            Ok(result) => self
+                .risk
+                .record_trade(profit_usd, result.success, chrono::Utc::now()),
+            Err(_) => self.risk.record_trade(0.0, false, chrono::Utc::now()),
+        }
+
+        self.arbitrage_engine
+            .set_symbol_penalties(self.pair_manager.symbols().by_id(penalties));
+
+        if execution.is_ok() && !self.intermediate_limits.is_empty() {
+            self.intermediate_limits
+                .record(&best_opportunity, chrono::Utc::now());
+        }
+        if let Ok(result) = &execution {
+            self.sizing.record(profit_usd);
+            self.dust_inventory.record(&result.dust);
+            self.triangle_ledger
+                .record_execution(&best_opportunity, result);
+            self.arbitrage_engine
+                .set_triangle_adjustments(self.triangle_ledger.ranking_adjustments());
+        }
+        // Any order sent may have moved the books the next triangle would trade on
+        if execution.as_ref().is_ok_and(|r| r.success) || orders_sent {
+            self.overlap_cooldown
+                .record(&best_opportunity, Instant::now());
+        }
+
+        if let (Some(controller), Ok(result)) = (self.adaptive_threshold.as_mut(), &execution) {
+            if let Some(adjustment) = controller.record(
+                best_opportunity.estimated_profit_pct.value(),
+                result.actual_profit_pct,
+            ) {
+                warn!(
+                    "🎚️ Execution threshold {:.2}% → {:.2}%: {}",
+                    adjustment.from_pct, adjustment.to_pct, adjustment.reason
+                );
+                self.arbitrage_engine
+                    .set_execution_threshold(adjustment.to_pct);
+                self.alerts.set_execution_threshold(adjustment.to_pct);
+                self.record_event(|| SessionEvent::ConfigChanged {
+                    key: "MIN_PROFIT_THRESHOLD".to_string(),
+                    value: adjustment.to_pct.to_string(),
+                    reason: Some(adjustment.reason.clone()),
+                });
+                if let Some(journal) = &self.journal {
+                    journal.record(JournalEvent::ThresholdAdjusted {
+                        from_pct: adjustment.from_pct,
+                        to_pct: adjustment.to_pct,
+                        reason: adjustment.reason,
+                    });
+                }
+            }
+        }
+
+        match execution {
+            Ok(result) => {
+                if result.success {
+                    self.trades_completed += 1; // Only increment on successful trades
+                    self.session_profit_usd += profit_usd;
+                    self.state.record_trade();
+                    let money = |usd| self.reporting.format(usd, 6);
+                    warn!("✅ TRADE #{} SUCCESS!", self.trades_completed);
+                    warn!(
+                        "   Realized Profit: {} ({:.2}%)",
+                        money(profit_usd),
+                        result.actual_profit_pct
+                    );
+                    if result.dust_value_usd > 0.0 {
+                        warn!("   Dust Value: {}", money(result.dust_value_usd));
+                        let total_profit = profit_usd + result.dust_value_usd;
+                        let total_pct =
+                            (total_profit / (result.initial_amount * start_usd)) * 100.0;
+                        warn!(
+                            "   Total Profit (inc. Dust): {} ({:.2}%)",
+                            money(total_profit),
+                            total_pct
+                        );
+                    }
+                    warn!("   Execution time: {}ms", result.execution_time_ms);
+                    warn!("   Total fees: {}", money(result.total_fees * start_usd));
+
+                    // Force balance refresh after successful trade
+                    self.refresh_balances().await;
+
+                    if self.trades_completed >= self.max_trades {
+                        warn!(
+                            "🏁 All {} trade(s) completed successfully - stopping bot",
+                            self.max_trades
+                        );
+                        return true;
+                    }
+                    warn!(
+                        "⏳ Trade {}/{} completed, continuing to look for next opportunity...",
+                        self.trades_completed, self.max_trades
+                    );
+                } else {
+                    let error_msg = result
+                        .error_message
+                        .unwrap_or_else(|| "Unknown error".to_string());
+                    warn!("❌ TRADE FAILED: {error_msg}");
+
+                    // Check if it's a recoverable error (API restrictions, etc.)
+                    match result.exchange_error.as_deref() {
+                        Some(BybitError::GeoRestricted { .. }) => {
+                            warn!("🚫 Trade failed due to geographical/API restrictions - continuing to scan for other opportunities");
+                        }
+                        Some(BybitError::RateLimited { .. }) => {
+                            warn!("🐢 Trade failed on Bybit's rate limit - continuing to scan");
+                        }
+                        _ => warn!("⚠️ Trade failed with different error - continuing to scan"),
+                    }
+
+                    // Don't increment trade counter for failed trades - keep looking for opportunities
+                    info!("🔄 Continuing to scan for other profitable opportunities...");
+                }
+            }
+            Err(e) => {
+                let error_str = e.to_string();
+                warn!("❌ Trade execution error: {error_str}");
+                warn!("⚠️ Trade failed with different error - continuing to scan");
+                info!("🔄 Continuing to scan for other profitable opportunities...");
+            }
+        }
+        false
+    }
+
+    /// Convert between stablecoins off their peg, if the fast path is enabled and trading is free
+    async fn convert_stable(&mut self) {
+        let Some(scanner) = self.stable_scanner.as_mut() else {
+            return;
+        };
+        if self.shutdown.is_requested() || self.state.run_state() != RunState::Scanning {
+            return;
+        }
+        let Some(signal) = scanner.scan(
+            &self.pair_manager,
+            &self.balance_manager,
+            self.sizing.order_size(),
+        ) else {
+            return;
+        };
+        if !self.state.transition(RunEvent::ExecutionStarted) {
+            return;
+        }
+
+        warn!(
+            "🪙 STABLE: {} {:.4} on {} @ {} ({:.3}% from peg after fees)",
+            signal.side, signal.amount, signal.symbol, signal.price, signal.net_deviation_pct
+        );
+        let execution_guard = self.shutdown.begin_execution();
+        let (started_at, execution_start) = (chrono::Utc::now(), Instant::now());
+        let (execution, orders) = self
+            .executor
+            .convert(&signal.symbol, signal.side, signal.amount, signal.price)
+            .await;
+        drop(execution_guard);
+        self.state.transition(RunEvent::ExecutionFinished);
+        self.utilization.record(
+            started_at,
+            execution_start.elapsed().as_secs_f64(),
+            execution
+                .as_ref()
+                .map_or(0.0, |fill| fill.executed_value.value()),
+        );
+
+        scanner.mark_traded(&signal.symbol);
+        self.refresh_balances().await;
+        match &execution {
+            Ok(fill) => info!(
+                "✅ STABLE: {} {} filled @ {} (fee {:.6})",
+                fill.executed_quantity, signal.base, fill.executed_price, fill.fee
+            ),
+            Err(e) => {
+                warn!("❌ STABLE conversion on {} failed: {e:#}", signal.symbol);
+                self.check_exchange_error(BybitError::find(e));
+            }
+        }
+        if let Some(journal) = &self.journal {
+            journal.record(
+                JournalEvent::stable_conversion(&signal, &execution)
+                    .with_legs(JournalLeg::from_orders(&orders)),
+            );
+        }
+    }
+
+    /// Convert the dust inventory back to USDT, one market order per coin, once the sweep
+    /// interval has passed
+    async fn sweep_dust(&mut self) {
+        let Some(sweeper) = self.dust_sweeper.as_mut() else {
+            return;
+        };
+        if self.shutdown.is_requested()
+            || self.state.run_state() != RunState::Scanning
+            || !sweeper.due()
+        {
+            return;
+        }
+        let sweeps = sweeper.plan(
+            &self.dust_inventory,
+            &self.balance_manager,
+            &self.pair_manager,
+        );
+        if sweeps.is_empty() {
+            debug!("🧹 No dust worth sweeping");
+            return;
+        }
+
+        let mut reclaimed = 0.0;
+        for sweep in sweeps {
+            if !self.state.transition(RunEvent::ExecutionStarted) {
+                break;
+            }
+            info!(
+                "🧹 SWEEP: {} {:.8} {} on {} @ {}",
+                sweep.side, sweep.amount, sweep.coin, sweep.symbol, sweep.price
+            );
+            let execution_guard = self.shutdown.begin_execution();
+            let (execution, orders) = self
+                .executor
+                .convert(&sweep.symbol, sweep.side, sweep.amount, sweep.price)
+                .await;
+            drop(execution_guard);
+            self.state.transition(RunEvent::ExecutionFinished);
+
+            match &execution {
+                Ok(fill) => {
+                    info!(
+                        "✅ SWEEP: {:.8} {} → {:.6} {SWEEP_COIN}",
+                        sweep.amount,
+                        sweep.coin,
+                        fill.received()
+                    );
+                    reclaimed += fill.received();
+                    self.dust_inventory.remove(&sweep.coin);
+                }
+                Err(e) => {
+                    warn!(
+                        "❌ SWEEP of {} on {} failed: {e:#}",
+                        sweep.coin, sweep.symbol
+                    );
+                    self.check_exchange_error(BybitError::find(e));
+                }
+            }
+            if let Some(journal) = &self.journal {
+                journal.record(
+                    JournalEvent::dust_swept(&sweep, &execution)
+                        .with_legs(JournalLeg::from_orders(&orders)),
+                );
+            }
+        }
+
+        self.refresh_balances().await;
+        if reclaimed > 0.0 {
+            if let Some(sweeper) = self.dust_sweeper.as_mut() {
+                sweeper.record(reclaimed);
+            }
+            info!(
+                "🧹 Dust sweep reclaimed {}",
+                self.reporting.format(reclaimed, 4)
+            );
+        }
+    }
+
+    /// Pause for exchange maintenance and stop trading suspended symbols, as the maintenance
+    /// monitor reports them
+    fn apply_trading_status(&mut self) {
+        let Some(status) = self
+            .maintenance
+            .as_mut()
+            .and_then(MaintenanceWatch::changed)
+        else {
+            return;
+        };
+        match status.maintenance {
+            Some(reason) => self.state.transition(RunEvent::MaintenanceStarted(reason)),
+            None => self.state.transition(RunEvent::MaintenanceEnded),
+        };
+        self.pair_manager
+            .set_suspended(status.suspended.iter().cloned().collect());
+        self.state
+            .update_snapshot(|s| s.suspended_symbols = status.suspended.into_iter().collect());
+    }
+
+    /// Stop trading coins added to the blacklist file; coins taken off it trade again from
+    /// the next full refresh of pairs
+    fn apply_blacklist(&mut self, tokens: Vec<String>) {
+        let merged = blacklist::merge(&self.config.blacklisted_tokens, &tokens);
+        let dropped = self.pair_manager.set_blacklist(merged);
+        info!(
+            "🚫 Blacklist reloaded: {} coins from {}, {} pairs dropped",
+            tokens.len(),
+            self.config.blacklist_file,
+            dropped
+        );
+    }
+
+    /// Limit scanning and the WebSocket feeds to a focus, or go back to the full universe
+    fn apply_focus(&mut self, focus: Option<Focus>) {
+        let label = focus.as_ref().map(ToString::to_string);
+        let triangles = match self.pair_manager.set_focus(focus) {
+            Ok(triangles) => triangles,
+            Err(e) => {
+                warn!("⚠️ Focus refused: {e:#}");
+                return;
+            }
+        };
+        let symbols = self.pair_manager.focused_symbols();
+        match (&label, &symbols) {
+            (Some(label), Some(symbols)) => info!(
+                "🎯 FOCUS MODE on {label}: scanning {triangles} triangles over {} symbols",
+                symbols.len()
+            ),
+            _ => info!("🎯 Focus mode off: scanning all {triangles} triangles"),
+        }
+        self.focus_filter.set(symbols);
+        self.state.update_snapshot(|s| s.focus = label);
+    }
+
+    /// Pause trading at once when Bybit rejects a request because its services are restarting
+    fn check_exchange_error(&self, error: Option<&BybitError>) {
+        let Some(reason) = self
+            .maintenance
+            .as_ref()
+            .zip(error)
+            .and_then(|(maintenance, error)| maintenance.report_error(error))
+        else {
+            return;
+        };
+        if self
+            .state
+            .transition(RunEvent::MaintenanceStarted(reason.to_string()))
+        {
+            warn!("🛠️ {reason} - trading paused until the exchange is verified");
+        }
+    }
+
+    /// Check orders the previous session journaled the intent of but died before
+    /// accounting for, and report any that filled like any other unknown fill
+    async fn recover_order_intents(&mut self) {
+        let Some(journal) = self.journal.as_ref().filter(|_| !self.config.dry_run) else {
+            return;
+        };
+        // This session's start marks where the previous one ended
+        journal.flush().await;
+        let path = std::path::Path::new(&self.config.journal_path);
+        match reconcile::recover_intents(&self.client, path).await {
+            Ok(report) if report.legs_checked > 0 => {
+                warn!(
+                    "📝 Previous session died with {} order intent(s) unaccounted for: {} filled",
+                    report.legs_checked,
+                    report.mismatches.len()
+                );
+                self.apply_reconciliation(report);
+            }
+            Ok(_) => {}
+            Err(e) => warn!("⚠️ Could not check the previous session's order intents: {e:#}"),
+        }
+    }
+
+    /// Roll back or complete the triangle a previous run crashed or was killed in the
+    /// middle of, as `RESUME_INTERRUPTED` says
+    async fn resume_interrupted(&mut self) {
+        if self.config.dry_run {
+            return;
+        }
+        let checkpoint = Checkpoint::new(std::path::Path::new(&self.config.execution_state_path));
+        let state = match checkpoint.load() {
+            Ok(Some(state)) => state,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("⚠️ Ignoring the interrupted execution state: {e:#}");
+                checkpoint.clear();
+                return;
+            }
+        };
+        warn!(
+            "♻️ Previous session was interrupted during {} with {} leg(s) filled, holding {:.8} {} (started {})",
+            state.opportunity.display_pairs(),
+            state.completed_legs,
+            state.held_amount,
+            state.held_coin,
+            state.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        let mode = ResumeMode::parse(&self.config.resume_interrupted).unwrap_or_else(|e| {
+            warn!("⚠️ Ignoring RESUME_INTERRUPTED: {e:#}");
+            ResumeMode::Rollback
+        });
+        let (outcome, orders) = if mode == ResumeMode::Manual {
+            warn!(
+                "✋ RESUME_INTERRUPTED=manual - leaving the {} for you to convert",
+                state.held_coin
+            );
+            (Ok(()), Default::default())
+        } else {
+            self.executor.resume(state.clone(), mode).await
+        };
+        if let Err(e) = &outcome {
+            error!("❌ Failed to resolve the interrupted trade: {e:#}");
+        }
+        if let Some(journal) = &self.journal {
+            journal.record(
+                JournalEvent::interrupted_trade(&state, mode, &outcome)
+                    .with_legs(JournalLeg::from_orders(&orders)),
+            );
+        }
+        checkpoint.clear();
+        self.refresh_balances().await;
+    }
+
+    /// Alert on fills that don't reconcile, and correct the session's realized profit for
+    /// fees charged differently than journaled
+    fn apply_reconciliation(&mut self, report: ReconcileReport) {
+        for mismatch in &report.mismatches {
+            error!("🚨 RECONCILE: {mismatch}");
+        }
+        let pair_manager = &self.pair_manager;
+        let adjustment = report.pnl_adjustment_usd(|symbol| pair_manager.get_pair(symbol));
+        if adjustment != 0.0 {
+            self.session_profit_usd += adjustment;
+            warn!(
+                "🧾 Realized profit corrected by {} for fees charged differently than journaled",
+                self.reporting.format(adjustment, 6)
+            );
+        }
+        if let (Some(journal), false) = (&self.journal, report.mismatches.is_empty()) {
+            journal.record(JournalEvent::Reconciled {
+                legs_checked: report.legs_checked,
+                mismatches: report.mismatches.iter().map(ToString::to_string).collect(),
+                pnl_adjustment_usd: adjustment,
+            });
+        }
+        self.state.update_snapshot(|s| {
+            let status = &mut s.reconciliation;
+            status.runs += 1;
+            status.legs_checked += report.legs_checked as u64;
+            for mismatch in &report.mismatches {
+                *status
+                    .mismatches
+                    .entry(mismatch.kind().to_string())
+                    .or_insert(0) += 1;
+            }
+            status.pnl_adjustment_usd += adjustment;
+            status.last_run_at = Some(chrono::Utc::now());
+        });
+    }
+
+    /// Book a deposit or withdrawal made outside the bot apart from trading profit
+    fn apply_external_flow(&mut self, flow: ExternalFlow) {
+        let value_usd = self
+            .pair_manager
+            .usd_price(&flow.coin)
+            .map(|price| flow.amount * price);
+        let value = value_usd
+            .map(|usd| format!(" ({})", self.reporting.format(usd, 2)))
+            .unwrap_or_default();
+        info!(
+            "💸 External {}: {:+} {}{value} - not counted as trading profit",
+            flow.kind(),
+            flow.amount,
+            flow.coin
+        );
+        self.balance_manager
+            .record_external_flow(&flow.coin, flow.amount);
+        if let Some(journal) = &self.journal {
+            journal.record(JournalEvent::ExternalFlow {
+                flow_id: flow.id,
+                coin: flow.coin,
+                amount: flow.amount,
+                value_usd,
+            });
+        }
+        let flows = self.balance_manager.external_flows().clone();
+        self.state
+            .update_snapshot(|s| s.external_flows = flows.into_iter().collect());
+    }
+
+    /// Wait for in-flight work, log the session summary and save state for the next run
+    async fn finish(mut self) {
+        // Wait for an execution in flight and record how it ended, for what is left of the
+        // grace period. Past it, state is still saved and the logs flushed below.
+        let timeout = self.shutdown.grace_remaining();
+        let finished = if let Some(mut in_flight) = self.in_flight.take() {
+            info!("⏳ Waiting for the in-flight execution to finish...");
+            match tokio::time::timeout(timeout, in_flight.pending.report()).await {
+                Ok(report) => {
+                    self.settle(in_flight, report).await;
+                    info!("✅ In-flight execution finished - shutting down");
+                    true
+                }
+                Err(_) => false,
+            }
+        } else {
+            self.shutdown.wait_for_in_flight(timeout).await
+        };
+        if !finished {
+            error!(
+                "❌ In-flight execution did not finish within {}s - shutting down without it. Check open positions manually!",
+                self.config.shutdown_timeout_secs
+            );
+        }
+
+        let duration = self.start_time.elapsed();
+        info!("📊 Session Summary:");
+        info!("   • Runtime: {duration:.2?}");
+        info!("   • Total Cycles: {}", self.cycle_count);
+        info!(
+            "   • Trades Executed: {}/{}",
+            self.trades_completed, self.max_trades
+        );
+        info!(
+            "   • Realized Profit: {}",
+            self.reporting.format(self.session_profit_usd, 6)
+        );
+        if !self.dust_inventory.coins().is_empty() {
+            let pair_manager = &self.pair_manager;
+            info!(
+                "   • Dust Inventory: {} ({} coins)",
+                self.reporting.format(
+                    self.dust_inventory
+                        .value_usd(|coin| pair_manager.usd_price(coin)),
+                    4
+                ),
+                self.dust_inventory.coins().len()
+            );
+        }
+        if let Some(sweeper) = self
+            .dust_sweeper
+            .as_ref()
+            .filter(|s| s.reclaimed_usd() > 0.0)
+        {
+            info!(
+                "   • Dust Reclaimed: {}",
+                self.reporting.format(sweeper.reclaimed_usd(), 4)
+            );
+        }
+        let availability = self.availability.summary(chrono::Utc::now());
+        if let (Some(day), Some(week)) = (availability.last_24h_pct, availability.last_7d_pct) {
+            info!("   • Availability: {day:.2}% (24h), {week:.2}% (7d)");
+        }
+        let utilization = self
+            .utilization
+            .summary(chrono::Utc::now(), self.account_value_usd());
+        if let (Some(turnover), Some(idle)) = (
+            utilization.turnover_per_hour_session,
+            utilization.idle_session_pct,
+        ) {
+            info!(
+                "   • Capital Utilization: {} traded, {turnover:.2}x the account per hour, idle {idle:.1}% of the time",
+                self.reporting.format(utilization.notional_session_usd, 2)
+            );
+        }
+        if let Err(e) = self.availability.save() {
+            warn!("⚠️ Failed to save availability history: {e:#}");
+        }
+        if let Err(e) = self.triangle_ledger.save() {
+            warn!("⚠️ Failed to save triangle ledger: {e:#}");
+        }
+        let external_flows = self.balance_manager.external_flows();
+        if !external_flows.is_empty() {
+            let mut flows: Vec<String> = external_flows
+                .iter()
+                .map(|(coin, amount)| format!("{amount:+} {coin}"))
+                .collect();
+            flows.sort();
+            info!(
+                "   • External Flows: {} (not in realized profit)",
+                flows.join(", ")
+            );
+        }
+        info!("   • Final Mode: {}", self.state.mode());
+        self.alerts
+            .send_digests(Instant::now(), self.journal.as_ref(), true);
+        self.alerts.log_summary();
+        if finished {
+            self.executor.finish().await;
+        } else {
+            warn!("⚠️ Execution task still busy - execution stats and precision cache not saved");
+        }
+        if let Some(shadow) = &self.shadow_trader {
+            shadow.log_summary();
+        }
+        if let Some(ab) = &self.strategy_ab {
+            ab.log_summary();
+        }
+        if let Some(journal) = &self.journal {
+            journal.record(JournalEvent::SessionEnded {
+                cycles: self.cycle_count,
+                trades_completed: self.trades_completed as u64,
+            });
+            journal.flush().await;
+        }
+        if let Some(store) = &self.trade_store {
+            store.flush().await;
+        }
+        if let Some(events) = &self.events {
+            events.record(SessionEvent::SessionEnded {
+                cycles: self.cycle_count,
+                trades_completed: self.trades_completed as u64,
+            });
+            events.flush().await;
+        }
+    }
+
+    /// Append an event to the event log, if it is enabled
+    fn record_event(&self, event: impl FnOnce() -> SessionEvent) {
+        if let Some(events) = &self.events {
+            events.record(event());
+        }
+    }
+
+    /// Fetch balances now and apply them before the next scan
+    async fn refresh_balances(&mut self) {
+        if !self.balance_sync.refresh().await {
+            warn!("⚠️ Balance refresh timed out - the next scan may use stale balances");
+        }
+    }
+}
+
+/// Result of one scan cycle
+#[derive(Default)]
+struct ScanOutcome {
+    /// Best opportunity that passes the live threshold and balance checks, with the amount to trade
+    live: Option<(crate::models::ArbitrageOpportunity, f64)>,
+    /// In race mode, the next executable opportunity sharing no symbols with `live`
+    runner_up: Option<(crate::models::ArbitrageOpportunity, f64)>,
+    /// Everything the engine found this cycle, for alerts and shadow mode
+    candidates: Vec<crate::models::ArbitrageOpportunity>,
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn scan_arbitrage_cycle<E: ExchangeApi + ?Sized>(
+    config: &Config,
+    client: &E,
+    balances: &mut watch::Receiver<Option<BalanceSnapshot>>,
+    balance_manager: &mut BalanceManager,
+    pair_manager: &mut PairManager,
+    arbitrage_engine: &mut ArbitrageEngine,
+    strategies: &mut [Box<dyn Strategy>],
+    depth_pricer: Option<&DepthPricer>,
+    cycle_count: u64,
+    initial_scan_logged: &mut bool,
+    min_trade_amount: f64,
+    funding_mode: &FundingMode,
+    intermediate_limits: &IntermediateLimits,
+    overlap_cooldown: &OverlapCooldown,
+    rx: &mut tokio::sync::mpsc::Receiver<crate::models::TickerInfo>,
+    warmup: &mut QuoteWarmup,
+    degraded: &mut DegradedPricing,
+    recorder: Option<&MarketRecorder>,
+) -> Result<ScanOutcome> {
+    let cycle_start = Instant::now();
+
+    // Only log cycle start every 10000 cycles to reduce spam
+    if cycle_count.is_multiple_of(100000) {
+        debug!("🔄 Cycle #{cycle_count} - Scanning for arbitrage opportunities");
+    }
+
+    // Phase 1: Take up balances published by the balance sync task
+    let mut balance_updated = false;
+    if balances.has_changed().unwrap_or(false) {
+        let snapshot = balances.borrow_and_update().clone();
+        if let Some(snapshot) = snapshot {
+            balance_manager.apply_snapshot(&snapshot);
+            balance_updated = true;
+
+            // Log initial scanning info only once after first balance update
+            if !*initial_scan_logged {
+                balance_manager
+                    .log_initial_scanning_info_with_min_amount(min_trade_amount, |coin| {
+                        pair_manager.usd_price(coin)
+                    });
+                *initial_scan_logged = true;
+            }
+
+            if cycle_count.is_multiple_of(100) {
+                debug!(
+                    "💰 BALANCE: Took up balances for {} assets",
+                    balance_manager.get_all_balances().len()
+                );
+                log_balance_summary(&balance_manager.get_balance_summary());
+            }
+        }
+    }
+
+    // Phase 2: Update trading pairs and prices
+    // Full refresh (instruments + prices) every 2000 cycles or if empty
+    let needs_full_refresh =
+        pair_manager.get_pairs().is_empty() || cycle_count.is_multiple_of(2000);
+
+    let mut prices_updated = false;
+    if needs_full_refresh {
+        debug!(
+            "📊 PAIRS: Performing FULL refresh of trading pairs and prices (Instruments + Tickers)"
+        );
+        let pairs_start = Instant::now();
+
+        pair_manager
+            .update_pairs_and_prices(client)
+            .await
+            .context("Failed to update pairs and prices")?;
+
+        prices_updated = true;
+        if let Some(recorder) = recorder {
+            recorder.record_pairs(pair_manager.get_pairs());
+        }
+
+        log_performance_metrics(
+            "Full pairs refresh",
+            pairs_start.elapsed().as_millis() as u64,
+            Some(pair_manager.get_pairs().len()),
+        );
+
+        log_pair_statistics(&pair_manager.get_statistics());
+    }
+    // Process WebSocket updates for prices
+    else {
+        let mut updates_count = 0;
+        while let Ok(ticker) = rx.try_recv() {
+            pair_manager.update_from_ticker(&ticker);
+            if let Some(recorder) = recorder {
+                recorder.record_ticker(&ticker);
+            }
+            for strategy in strategies.iter_mut() {
+                strategy.on_quote(&ticker);
+            }
+            updates_count += 1;
+            if warmup.confirm(&ticker.symbol) {
+                info!(
+                    "⚡ WebSocket quotes live for all {} subscribed symbols - REST snapshot retired",
+                    warmup.total()
+                );
+            }
+        }
+
+        // Until every symbol has a WebSocket quote, keep the rest priced from REST
+        if warmup.rest_refresh_due(Instant::now()) {
+            match client.get_tickers("spot").await {
+                Ok(tickers) => {
+                    for ticker in tickers
+                        .list
+                        .iter()
+                        .filter(|t| warmup.pending().contains(&t.symbol))
+                    {
+                        pair_manager.update_from_ticker(ticker);
+                        if let Some(recorder) = recorder {
+                            recorder.record_ticker(ticker);
+                        }
+                        prices_updated = true;
+                    }
+                    debug!(
+                        "📸 Re-priced {} of {} symbols from REST while WebSockets warm up",
+                        warmup.pending().len(),
+                        warmup.total()
+                    );
+                }
+                Err(e) => warn!("⚠️ REST price refresh failed during warm-up: {e:#}"),
+            }
+        }
+
+        // With every WebSocket down, poll all prices from REST instead
+        if degraded.poll_due(Instant::now()) {
+            match client.get_tickers("spot").await {
+                Ok(tickers) => {
+                    for ticker in &tickers.list {
+                        pair_manager.update_from_ticker(ticker);
+                        if let Some(recorder) = recorder {
+                            recorder.record_ticker(ticker);
+                        }
+                    }
+                    prices_updated = true;
+                }
+                Err(e) => warn!("⚠️ REST price poll failed while degraded: {e:#}"),
+            }
+        }
+
+        if updates_count > 0 {
+            prices_updated = true;
+            if cycle_count.is_multiple_of(100) {
+                debug!("⚡ Processed {updates_count} WebSocket ticker updates");
+            }
+        } else if cycle_count.is_multiple_of(100) {
+            // Only warn if we haven't received updates for a while
+            // warn!("⚠️ No WebSocket updates received in this cycle (Check connection/subscription)");
+        }
+    }
+
+    for strategy in strategies.iter_mut() {
+        strategy.on_timer(cycle_start);
+    }
+
+    // Phase 3: Scan for arbitrage opportunities
+    // Optimization: Only scan if prices or balances have changed
+    if !prices_updated && !balance_updated {
+        // No changes, skip scanning to save CPU
+        return Ok(ScanOutcome::default());
+    }
+
+    let arbitrage_start = Instant::now();
+
+    // Start triangles from the funding sources; with none funded, every tradeable coin
+    // is still scanned so alerts and shadow mode keep seeing the market
+    let sources =
+        funding::funding_sources(funding_mode, balance_manager, min_trade_amount, |coin| {
+            pair_manager.usd_price(coin)
+        });
+    // Profit floors are in USD, trade amounts in the start coin; a start coin without a
+    // live USD price can't be held to them
+    let amount_usd = |o: &ArbitrageOpportunity, amount: f64| {
+        pair_manager
+            .usd_price(&o.path[0])
+            .map(|price| amount * price)
+    };
+    arbitrage_engine.set_base_currencies(
+        (!sources.is_empty()).then(|| sources.iter().map(|s| s.coin.clone()).collect()),
+    );
+
+    let market = MarketView {
+        pairs: pair_manager,
+        balances: balance_manager,
+        min_trade_amount,
+    };
+    let mut opportunities = strategy::propose_all(arbitrage_engine, strategies, &market);
+
+    // Price each candidate for the amount it would trade, walking the book on legs too big
+    // for the top level, and rank them again on those prices
+    let trade_amount = |o: &ArbitrageOpportunity| {
+        sources
+            .iter()
+            .find(|s| s.coin == o.path[0])
+            .map_or(min_trade_amount, |s| s.amount)
+    };
+    if let Some(pricer) = depth_pricer {
+        opportunities = pricer.reprice_all(opportunities, trade_amount, pair_manager);
+        arbitrage_engine.rank(&mut opportunities);
+    }
+
+    let mut outcome = ScanOutcome {
+        candidates: opportunities.clone(),
+        ..Default::default()
+    };
+
+    // Return profitable opportunities (only the most profitable one per cycle),
+    // skipping triangles starting from a coin without a USD price, through coins that
+    // used up their hourly allowance or on symbols still cooling down from the last
+    // execution
+    let now = chrono::Utc::now();
+    let tradeable = |o: &ArbitrageOpportunity| {
+        pair_manager.usd_price(&o.path[0]).is_some()
+            && intermediate_limits.blocked_coin(o, now).is_none()
+            && overlap_cooldown.blocked_symbol(o, Instant::now()).is_none()
+    };
+    if let Some(best_opportunity) = opportunities.iter().find(|o| tradeable(o)) {
+        // Only log periodically to avoid spam
+        if cycle_count.is_multiple_of(10) {
+            log_arbitrage_opportunity(best_opportunity, 1);
+        }
+
+        // Check if profit is above the execution threshold and we have sufficient balance
+        // (the engine scans down to the alert/shadow thresholds), at the size it would trade
+        let start = &best_opportunity.path[0];
+        let source = sources.iter().find(|s| &s.coin == start);
+        let amount = source.map_or(min_trade_amount, |s| s.amount);
+        if amount_usd(best_opportunity, amount)
+            .is_some_and(|usd| arbitrage_engine.is_executable(best_opportunity, usd))
+        {
+            if let Some(source) = source {
+                if config.race_mode {
+                    outcome.runner_up = opportunities
+                        .iter()
+                        .filter(|o| {
+                            tradeable(o)
+                                && !o.pairs.iter().any(|p| best_opportunity.pairs.contains(p))
+                        })
+                        .find_map(|o| {
+                            let funded = sources.iter().find(|s| s.coin == o.path[0])?;
+                            // Both legs 1 may spend the same coin, which then has to cover both
+                            let committed = if o.path[0] == *start {
+                                funded.amount + source.amount
+                            } else {
+                                funded.amount
+                            };
+                            (amount_usd(o, funded.amount)
+                                .is_some_and(|usd| arbitrage_engine.is_executable(o, usd))
+                                && balance_manager.get_balance(&o.path[0]) >= committed)
+                                .then(|| (o.clone(), funded.amount))
+                        });
+                }
+                outcome.live = Some((best_opportunity.clone(), source.amount));
+                return Ok(outcome);
+            } else if cycle_count.is_multiple_of(100) {
+                warn!(
+                    "⚠️ Found opportunity {:.2}% but {start} is not funded: {:.2} {start} (order size {:.2}, funding {funding_mode:?})",
+                    best_opportunity.estimated_profit_pct,
+                    balance_manager.get_balance(start),
+                    min_trade_amount
+                );
+            }
+        }
+    }
+
+    // Only log cycle summary every 300 cycles
+    if cycle_count.is_multiple_of(config.cycle_summary_interval as u64) {
+        let cycle_duration = cycle_start.elapsed();
+        log_performance_metrics(
+            "Arbitrage scan",
+            arbitrage_start.elapsed().as_millis() as u64,
+            Some(opportunities.len()),
+        );
+
+        log_arbitrage_statistics(&arbitrage_engine.get_statistics());
+
+        debug!("📊 Cycle #{} Summary:", cycle_count);
+        debug!("  • Trading pairs: {}", pair_manager.get_pairs().len());
+        debug!("  • Total opportunities: {}", opportunities.len());
+        debug!(
+            "  • Triangles evaluated: {}",
+            arbitrage_engine.last_scan_evaluated
+        );
+        if let Some(cycle) = &arbitrage_engine.last_scan_long_cycle {
+            debug!(
+                "  • Best longer cycle: {:.4}% via {} (not traded)",
+                cycle.profit_pct,
+                cycle.path.join(" → ")
+            );
+        }
+        debug!("  • Cycle time: {:.2}ms", cycle_duration.as_millis());
+    }
+
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_full_arbitrage_against_mock_exchange() {
+        use crate::exchange::{MockExchange, MockMarket};
+        use crate::journal::JournalRecord;
+
+        // USDT → BTC → ETH → USDT is ~1.7% profitable after fees
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_market(
+                "ETHBTC",
+                MockMarket::new("ETH", "BTC", 0.04999, 0.05).with_base_decimals(5),
+            )
+            .with_market(
+                "ETHUSDT",
+                MockMarket::new("ETH", "USDT", 2_550.0, 2_551.0).with_base_decimals(5),
+            )
+            .with_balance("USDT", 1_000.0);
+        let config = Config {
+            min_profit_threshold: 0.5,
+            ..Config::test_config()
+        };
+        let amount = 100.0;
+
+        // Detection, exactly as one main loop cycle does it
+        let mut balance_manager = BalanceManager::new();
+        let mut pair_manager = PairManager::new(config.clone());
+        let mut engine = ArbitrageEngine::with_config(
+            config.min_profit_threshold,
+            config.max_triangles_to_scan,
+            config.trading_fee_rate,
+        );
+        let (_tx, mut rx) = tokio::sync::mpsc::channel(1);
+        let mut fetched = BalanceManager::new();
+        fetched.update_balances(&exchange).await.unwrap();
+        let (balance_tx, mut balances) = watch::channel(None);
+        balance_tx.send_replace(fetched.snapshot(0));
+        let outcome = scan_arbitrage_cycle(
+            &config,
+            &exchange,
+            &mut balances,
+            &mut balance_manager,
+            &mut pair_manager,
+            &mut engine,
+            &mut [],
+            None,
+            1,
+            &mut false,
+            amount,
+            &FundingMode::UsdtOnly,
+            &IntermediateLimits::new(Default::default()),
+            &OverlapCooldown::new(Duration::ZERO),
+            &mut rx,
+            &mut QuoteWarmup::new(&[], Instant::now()),
+            &mut DegradedPricing::new(0, 3),
+            None,
+        )
+        .await
+        .unwrap();
+        let (opportunity, funded) = outcome
+            .live
+            .expect("profitable triangle should be detected");
+        assert_eq!(funded, amount);
+        assert_eq!(opportunity.pairs, ["BTCUSDT", "ETHBTC", "ETHUSDT"]);
+        assert!(opportunity.estimated_profit_pct.value() > 1.0);
+
+        // Execution of all three legs
+        let precision =
+            PrecisionManager::from_instruments(exchange.get_all_spot_instruments().await.unwrap());
+        let mut trader = ArbitrageTrader::new(exchange, false, precision);
+        trader.set_usd_prices(
+            opportunity
+                .path
+                .iter()
+                .filter_map(|coin| Some((coin.clone(), pair_manager.usd_price(coin)?)))
+                .collect(),
+        );
+        let execution = trader.execute_arbitrage(&opportunity, amount).await;
+        let result = execution.as_ref().unwrap();
+        assert!(result.success, "{:?}", result.error_message);
+        let spent = net_spent(
+            &JournalLeg::from_orders(&trader.take_order_log()),
+            &pair_manager,
+        );
+
+        let exchange = trader.client();
+        let orders = exchange.orders();
+        let legs: Vec<_> = orders
+            .iter()
+            .map(|o| (o.symbol.as_str(), o.side.as_str(), o.qty.as_str()))
+            .collect();
+        assert_eq!(
+            legs,
+            [
+                ("BTCUSDT", "Buy", "100.000000"),
+                ("ETHBTC", "Buy", "0.00199"),
+                ("ETHUSDT", "Sell", "0.03976"),
+            ]
+        );
+
+        // Balances reflect the realized profit
+        balance_manager.update_balances(exchange).await.unwrap();
+        let usdt_gain = balance_manager.get_balance("USDT") - 1_000.0;
+        assert!(usdt_gain > 1.0);
+        assert!((usdt_gain - result.actual_profit).abs() < 1e-9);
+        // What the legs took out of the wallet, as a reservation is released against
+        assert!((spent["USDT"] + usdt_gain).abs() < 1e-9);
+        assert!(result
+            .dust
+            .iter()
+            .all(|(coin, dust)| (spent[coin] + dust).abs() < 1e-9));
+        assert!(result.dust_value_usd > 0.0);
+        assert!(result
+            .dust
+            .keys()
+            .all(|coin| opportunity.path.contains(coin)));
+
+        // The journal records the same PnL
+        let path = std::env::temp_dir().join(format!("e2e_journal_{}.jsonl", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let journal = Journal::open(&path).unwrap();
+        journal.record(JournalEvent::live_trade(&opportunity, amount, &execution));
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let record: JournalRecord = serde_json::from_str(contents.trim()).unwrap();
+        match record.event {
+            JournalEvent::LiveTrade {
+                success,
+                profit_usd,
+                fees_usd,
+                error,
+                ..
+            } => {
+                assert!(success);
+                assert_eq!(profit_usd, result.actual_profit);
+                assert!((fees_usd - result.total_fees).abs() < 1e-12);
+                assert!(error.is_none());
+            }
+            other => panic!("unexpected journal event {other:?}"),
+        }
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_candidates_are_priced_from_depth_for_the_trade_size() {
+        use crate::exchange::{MockExchange, MockMarket};
+
+        // The same ~1.7% triangle at the top of book
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_market("ETHBTC", MockMarket::new("ETH", "BTC", 0.04999, 0.05))
+            .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_551.0))
+            .with_balance("USDT", 1_000.0);
+        let config = Config {
+            min_profit_threshold: 0.5,
+            ..Config::test_config()
+        };
+        let book = |symbol: &str, bids: &[(f64, f64)], asks: &[(f64, f64)]| DepthUpdate {
+            symbol: symbol.to_string(),
+            snapshot: true,
+            bids: bids.to_vec(),
+            asks: asks.to_vec(),
+        };
+
+        let scan = |pricer: DepthPricer| {
+            let (exchange, config) = (&exchange, &config);
+            async move {
+                let mut engine = ArbitrageEngine::with_config(
+                    config.min_profit_threshold,
+                    config.max_triangles_to_scan,
+                    config.trading_fee_rate,
+                );
+                let (_tx, mut rx) = tokio::sync::mpsc::channel(1);
+                let mut fetched = BalanceManager::new();
+                fetched.update_balances(exchange).await.unwrap();
+                let (balance_tx, mut balances) = watch::channel(None);
+                balance_tx.send_replace(fetched.snapshot(0));
+                scan_arbitrage_cycle(
+                    config,
+                    exchange,
+                    &mut balances,
+                    &mut BalanceManager::new(),
+                    &mut PairManager::new(config.clone()),
+                    &mut engine,
+                    &mut [],
+                    Some(&pricer),
+                    1,
+                    &mut false,
+                    100.0,
+                    &FundingMode::UsdtOnly,
+                    &IntermediateLimits::new(Default::default()),
+                    &OverlapCooldown::new(Duration::ZERO),
+                    &mut rx,
+                    &mut QuoteWarmup::new(&[], Instant::now()),
+                    &mut DegradedPricing::new(0, 3),
+                    None,
+                )
+                .await
+                .unwrap()
+            }
+        };
+
+        // Every leg walks the book; the ETH bids thin out after 0.01 ETH
+        let mut pricer = DepthPricer::new(0.0, FeeSchedule::flat(config.trading_fee_rate));
+        pricer.apply(&book("BTCUSDT", &[], &[(50_000.0, 1.0)]));
+        pricer.apply(&book("ETHBTC", &[], &[(0.05, 10.0)]));
+        pricer.apply(&book("ETHUSDT", &[(2_550.0, 0.01), (2_400.0, 10.0)], &[]));
+        let outcome = scan(pricer).await;
+        assert!(outcome.live.is_none());
+        let candidate = outcome
+            .candidates
+            .iter()
+            .find(|o| o.pairs == ["BTCUSDT", "ETHBTC", "ETHUSDT"])
+            .unwrap();
+        // A 100 USDT trade gets ~2437 for its ~0.04 ETH, not 2550
+        assert!(
+            candidate.estimated_profit_pct.value() < -2.0,
+            "{candidate:?}"
+        );
+        assert!((candidate.prices[2].value() - 2_437.5).abs() < 1.0);
+
+        // With deep books the triangle still trades, and one without a book isn't a candidate
+        let mut pricer = DepthPricer::new(0.0, FeeSchedule::flat(config.trading_fee_rate));
+        pricer.apply(&book("BTCUSDT", &[], &[(50_000.0, 1.0)]));
+        pricer.apply(&book("ETHBTC", &[], &[(0.05, 10.0)]));
+        pricer.apply(&book("ETHUSDT", &[(2_550.0, 10.0)], &[]));
+        let outcome = scan(pricer).await;
+        let (opportunity, _) = outcome.live.unwrap();
+        assert_eq!(opportunity.pairs, ["BTCUSDT", "ETHBTC", "ETHUSDT"]);
+        assert!(outcome.candidates.iter().all(|o| o.pairs[0] == "BTCUSDT"));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::models::ArbitrageOpportunity;
+use crate::state::BotState;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// A trade held until someone approves it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingApproval {
+    pub id: u64,
+    pub path: String,
+    pub pairs: Vec<String>,
+    pub profit_pct: f64,
+    pub expires_at: DateTime<Utc>,
+    pub approved: bool,
+}
+
+/// Holds trades for a human to approve via the control API. One trade awaits approval at
+/// a time; once approved, its triangle executes the next time it is the best
+/// opportunity, at that moment's prices, until the window ends.
+pub struct ApprovalGate {
+    window: Duration,
+    next_id: u64,
+}
+
+impl ApprovalGate {
+    pub fn new(window_secs: u64) -> Self {
+        Self {
+            window: Duration::seconds(window_secs as i64),
+            next_id: 0,
+        }
+    }
+
+    /// Whether `opportunity` may execute now. Otherwise it is held, and approval asked
+    /// for it unless another trade awaits approval.
+    pub fn check(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        state: &BotState,
+        now: DateTime<Utc>,
+    ) -> bool {
+        match state.pending_approval() {
+            Some(pending) if now > pending.expires_at => {
+                info!(
+                    "⌛ Trade #{} via {} was not approved in time",
+                    pending.id, pending.path
+                );
+                state.set_pending_approval(None);
+            }
+            Some(pending) if pending.approved && pending.pairs == opportunity.pairs => {
+                state.set_pending_approval(None);
+                info!(
+                    "✅ Executing approved trade #{} via {}",
+                    pending.id, pending.path
+                );
+                return true;
+            }
+            Some(_) => return false,
+            None => {}
+        }
+
+        self.next_id += 1;
+        let pending = PendingApproval {
+            id: self.next_id,
+            path: opportunity.display_path(),
+            pairs: opportunity.pairs.clone(),
+            profit_pct: opportunity.estimated_profit_pct.value(),
+            expires_at: now + self.window,
+            approved: false,
+        };
+        warn!(
+            "🙋 APPROVAL NEEDED for trade #{}: {:.2}% via {} - POST /approve?id={} within {}s",
+            pending.id,
+            pending.profit_pct,
+            pending.path,
+            pending.id,
+            self.window.num_seconds()
+        );
+        state.set_pending_approval(Some(pending));
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trades_execute_only_once_approved_within_the_window() {
+        let state = BotState::new();
+        let mut gate = ApprovalGate::new(30);
+        let now = Utc::now();
+        let btc = ArbitrageOpportunity::test()
+            .with_pairs(&["BTCUSDT", "ETHBTC", "ETHUSDT"])
+            .with_profit_pct(0.3);
+        let sol = ArbitrageOpportunity::test()
+            .with_pairs(&["SOLUSDT", "SOLBTC", "BTCUSDT"])
+            .with_profit_pct(0.3);
+
+        assert!(!gate.check(&btc, &state, now));
+        // Another triangle waits while the first awaits approval
+        assert!(!gate.check(&sol, &state, now));
+        assert!(state.approve(2, "test").is_err());
+        assert_eq!(state.approve(1, "test").unwrap().path, btc.display_path());
+
+        // The approval is for the first triangle only
+        assert!(!gate.check(&sol, &state, now));
+        assert!(gate.check(&btc, &state, now));
+        assert!(state.pending_approval().is_none());
+
+        // Unapproved requests lapse, and the next opportunity asks again
+        assert!(!gate.check(&btc, &state, now));
+        let later = now + Duration::seconds(31);
+        assert!(!gate.check(&sol, &state, later));
+        let pending = state.pending_approval().unwrap();
+        assert_eq!((pending.id, pending.pairs), (3, sol.pairs));
+    }
+}
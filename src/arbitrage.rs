@@ -1,16 +1,74 @@
 use crate::balance::BalanceManager;
-use crate::models::ArbitrageOpportunity;
+use crate::fees::FeeSchedule;
+use crate::graph::{Algorithm, CurrencyGraph, Cycle};
+use crate::maker::{self, ExecutionStrategy};
+use crate::models::{ArbitrageOpportunity, Notional, Pct, Price, SCHEMA_VERSION};
 use crate::pairs::{PairManager, TriangleDefinition};
+use crate::stable::STABLECOINS;
+use crate::strategy::{MarketView, Strategy};
+use crate::symbols::{SymbolId, SymbolRegistry};
 use chrono::Utc;
 use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
 use tracing::debug;
 
+/// Evaluation of every cached triangle of one base currency, kept between scans
+struct BaseScan {
+    test_amount: f64,
+    /// Result of each triangle, by position in the base currency's cache
+    results: Vec<Option<ArbitrageOpportunity>>,
+}
+
+/// Triangle results of the previous scan, reused for triangles none of whose legs were
+/// updated since. Valid while the triangle cache and the discounts are unchanged.
+struct ScanCache {
+    generation: u64,
+    update_seq: u64,
+    bases: HashMap<String, BaseScan>,
+}
+
 pub struct ArbitrageEngine {
     opportunities: Vec<ArbitrageOpportunity>,
+    /// Lowest profit reported by scans (alerts and shadow mode may sit below execution)
     profit_threshold: f64,
+    /// Profit required before an opportunity is traded
+    execution_threshold: f64,
+    /// Extra percentage points required on top of the threshold, e.g. while prices are degraded
+    execution_margin: f64,
+    /// Expected USD profit a trade must also clear at its sized amount
+    min_profit_usd: f64,
     max_scan_count: usize,
-    trading_fee_rate: f64, // Bybit spot trading fee (usually 0.1%)
+    /// Maker and taker fee rates of each symbol
+    fees: FeeSchedule,
+    /// How the trader places leg 1, which sets the price and fee it is estimated at
+    execution_strategy: ExecutionStrategy,
+    /// Registry the symbol IDs below refer to, shared with the pair manager
+    symbols: Arc<SymbolRegistry>,
+    /// Percentage points knocked off the ranking of triangles through poorly executing symbols
+    symbol_penalties: HashMap<SymbolId, f64>,
+    /// Percentage points knocked off the edge of triangles through symbols whose quotes
+    /// arrive systematically late
+    delay_discounts: HashMap<SymbolId, f64>,
+    /// Percentage points added to the ranking of triangles by their track record, keyed by
+    /// their pairs joined with commas
+    triangle_adjustments: HashMap<String, f64>,
+    /// Coins triangles may start from; None scans every coin with a tradeable balance
+    base_currencies: Option<Vec<String>>,
+    /// Enumerate the cached triangles or search the currency graph
+    algorithm: Algorithm,
+    /// Longest cycle the graph search looks for
+    max_cycle_legs: usize,
+    scan_cache: Option<ScanCache>,
     pub global_best: Option<ArbitrageOpportunity>,
+    /// Best triangle of the most recent scan, profitable or not
+    pub last_scan_best: Option<ArbitrageOpportunity>,
+    /// Triangles the most recent scan evaluated rather than reused from the one before
+    pub last_scan_evaluated: usize,
+    /// Best cycle of more than three legs the most recent graph search found; reported only,
+    /// as trades are triangles
+    pub last_scan_long_cycle: Option<Cycle>,
 }
 
 impl ArbitrageEngine {
@@ -18,9 +76,24 @@ impl ArbitrageEngine {
         Self {
             opportunities: Vec::new(),
             profit_threshold: 0.05,
+            execution_threshold: 0.05,
+            execution_margin: 0.0,
+            min_profit_usd: 0.0,
             max_scan_count: 2000,
-            trading_fee_rate: 0.001, // 0.1% trading fee
+            fees: FeeSchedule::flat(0.001), // 0.1% trading fee
+            execution_strategy: ExecutionStrategy::Taker,
+            symbols: SymbolRegistry::new(),
+            symbol_penalties: HashMap::new(),
+            delay_discounts: HashMap::new(),
+            triangle_adjustments: HashMap::new(),
+            base_currencies: None,
+            algorithm: Algorithm::Triangles,
+            max_cycle_legs: 3,
+            scan_cache: None,
             global_best: None,
+            last_scan_best: None,
+            last_scan_evaluated: 0,
+            last_scan_long_cycle: None,
         }
     }
 
@@ -28,10 +101,104 @@ impl ArbitrageEngine {
         Self {
             opportunities: Vec::new(),
             profit_threshold,
+            execution_threshold: profit_threshold,
+            execution_margin: 0.0,
+            min_profit_usd: 0.0,
             max_scan_count,
-            trading_fee_rate: fee_rate,
+            fees: FeeSchedule::flat(fee_rate),
+            execution_strategy: ExecutionStrategy::Taker,
+            symbols: SymbolRegistry::new(),
+            symbol_penalties: HashMap::new(),
+            delay_discounts: HashMap::new(),
+            triangle_adjustments: HashMap::new(),
+            base_currencies: None,
+            algorithm: Algorithm::Triangles,
+            max_cycle_legs: 3,
+            scan_cache: None,
             global_best: None,
+            last_scan_best: None,
+            last_scan_evaluated: 0,
+            last_scan_long_cycle: None,
+        }
+    }
+
+    /// Estimate leg 1 the way `strategy` places it: resting at the best bid/ask for the
+    /// maker fee, rather than crossing the spread
+    pub fn with_execution_strategy(mut self, strategy: ExecutionStrategy) -> Self {
+        self.execution_strategy = strategy;
+        self
+    }
+
+    /// Look symbols up in `registry`, the one the pair manager interns into
+    pub fn with_symbols(mut self, registry: Arc<SymbolRegistry>) -> Self {
+        self.symbols = registry;
+        self
+    }
+
+    /// Trade only above this threshold, while scans still report down to the scan threshold
+    pub fn with_execution_threshold(mut self, threshold: f64) -> Self {
+        self.execution_threshold = threshold;
+        self
+    }
+
+    /// Also require this much expected profit in USD at the trade's sized amount
+    pub fn with_min_profit_usd(mut self, min_profit_usd: f64) -> Self {
+        self.min_profit_usd = min_profit_usd;
+        self
+    }
+
+    /// Find opportunities with `algorithm`, the graph search looking for cycles of up to
+    /// `max_cycle_legs` legs
+    pub fn with_algorithm(mut self, algorithm: Algorithm, max_cycle_legs: usize) -> Self {
+        self.algorithm = algorithm;
+        self.max_cycle_legs = max_cycle_legs;
+        self
+    }
+
+    pub fn set_execution_threshold(&mut self, threshold: f64) {
+        self.execution_threshold = threshold;
+    }
+
+    pub fn set_execution_margin(&mut self, margin_pct: f64) {
+        self.execution_margin = margin_pct;
+    }
+
+    /// Replace the per-symbol ranking penalties, e.g. from the trader's execution stats
+    pub fn set_symbol_penalties(&mut self, penalties: HashMap<SymbolId, f64>) {
+        self.symbol_penalties = penalties;
+    }
+
+    /// Replace the per-triangle ranking adjustments, e.g. from the triangle ledger
+    pub fn set_triangle_adjustments(&mut self, adjustments: HashMap<String, f64>) {
+        self.triangle_adjustments = adjustments;
+    }
+
+    /// Replace the per-symbol edge discounts for quotes that arrive late
+    pub fn set_delay_discounts(&mut self, discounts: HashMap<SymbolId, f64>) {
+        if discounts != self.delay_discounts {
+            self.scan_cache = None;
         }
+        self.delay_discounts = discounts;
+    }
+
+    /// Price every leg at its symbol's taker fee, e.g. the account's rates once fetched
+    pub fn set_fee_schedule(&mut self, fees: FeeSchedule) {
+        self.fees = fees;
+        // Cached results were priced at the old rates
+        self.scan_cache = None;
+    }
+
+    /// Only start triangles from these coins, e.g. the funding sources
+    pub fn set_base_currencies(&mut self, bases: Option<Vec<String>>) {
+        self.base_currencies = bases;
+    }
+
+    /// Whether trading `amount_usd` worth of the opportunity's start coin clears the
+    /// execution threshold, any margin on top and the USD profit floor
+    pub fn is_executable(&self, opportunity: &ArbitrageOpportunity, amount_usd: f64) -> bool {
+        let profit_pct = opportunity.estimated_profit_pct.value();
+        profit_pct >= self.execution_threshold + self.execution_margin
+            && amount_usd * profit_pct / 100.0 >= self.min_profit_usd
     }
 
     #[cfg(test)]
@@ -47,7 +214,11 @@ impl ArbitrageEngine {
         min_trade_amount: f64,
     ) -> Vec<ArbitrageOpportunity> {
         self.opportunities.clear();
-        let mut tradeable_coins = balance_manager.get_tradeable_coins(min_trade_amount);
+        let mut tradeable_coins = match &self.base_currencies {
+            Some(bases) => bases.clone(),
+            None => balance_manager
+                .get_tradeable_coins(min_trade_amount, |coin| pair_manager.usd_price(coin)),
+        };
 
         // Exclude MNT from being a base currency (start of loop) to preserve it for fees
         tradeable_coins.retain(|coin| coin != "MNT");
@@ -70,38 +241,21 @@ impl ArbitrageEngine {
             tradeable_coins
         };
 
-        // Use Rayon for parallel scanning
-        let results: Vec<(
-            usize,
-            Vec<ArbitrageOpportunity>,
-            Option<ArbitrageOpportunity>,
-        )> = coins_to_scan
-            .par_iter()
-            .map(|base_currency| {
-                let balance = balance_manager.get_balance(base_currency);
-                // Use the minimum trade amount or a portion of balance, whichever is larger
-                let test_amount = min_trade_amount.max((balance * 0.1).min(1000.0));
-
-                self.scan_for_base_currency(base_currency, test_amount, pair_manager)
-            })
-            .collect();
-
-        let mut _total_scanned = 0;
-        let mut cycle_best: Option<ArbitrageOpportunity> = None;
-
-        for (scanned, opps, best_in_coin) in results {
-            _total_scanned += scanned;
-            self.opportunities.extend(opps);
-
-            if let Some(best) = best_in_coin {
-                if cycle_best
-                    .as_ref()
-                    .is_none_or(|o| best.estimated_profit_pct > o.estimated_profit_pct)
-                {
-                    cycle_best = Some(best);
-                }
-            }
-        }
+        let (evaluated, cycle_best) = match self.algorithm {
+            Algorithm::Triangles => self.scan_triangles(
+                pair_manager,
+                balance_manager,
+                coins_to_scan,
+                min_trade_amount,
+            ),
+            Algorithm::Graph => self.scan_graph(
+                pair_manager,
+                balance_manager,
+                coins_to_scan,
+                min_trade_amount,
+            ),
+        };
+        self.last_scan_evaluated = evaluated;
 
         // Update global best
         if let Some(ref current) = cycle_best {
@@ -114,6 +268,8 @@ impl ArbitrageEngine {
             }
         }
 
+        self.last_scan_best = cycle_best.clone();
+
         // Log best opportunities
         if let Some(best) = &cycle_best {
             debug!(
@@ -132,72 +288,255 @@ impl ArbitrageEngine {
             );
         }
 
-        // Sort opportunities by profit percentage (highest first)
-        self.opportunities.sort_by(|a, b| {
-            b.estimated_profit_pct
-                .partial_cmp(&a.estimated_profit_pct)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        // Sort opportunities by profit percentage (highest first), less execution penalties
+        let mut opportunities = std::mem::take(&mut self.opportunities);
+        self.rank(&mut opportunities);
+        self.opportunities = opportunities;
 
         // Only log detailed scan results occasionally
         // debug!(
         //     "🔁 Found {} potential arbitrage opportunities from {} triangles scanned",
         //     self.opportunities.len(),
-        //     total_scanned
+        //     evaluated
         // );
 
         self.opportunities.clone()
     }
 
-    /// Scan for arbitrage opportunities using a specific base currency
+    /// Evaluate the cached triangles of each coin, reusing the previous results of those
+    /// none of whose legs were updated since. Returns the number evaluated and the best.
+    fn scan_triangles(
+        &mut self,
+        pair_manager: &PairManager,
+        balance_manager: &BalanceManager,
+        coins_to_scan: Vec<String>,
+        min_trade_amount: f64,
+    ) -> (usize, Option<ArbitrageOpportunity>) {
+        // Only triangles with a leg updated since the last scan need evaluating again,
+        // unless the triangle cache was rebuilt or pairs stopped or resumed trading
+        let generation = pair_manager.generation();
+        let previous = self
+            .scan_cache
+            .take()
+            .filter(|cache| cache.generation == generation);
+        let updated = previous
+            .as_ref()
+            .map(|cache| pair_manager.updated_triangles(cache.update_seq));
+        let mut previous_bases = previous.map(|cache| cache.bases).unwrap_or_default();
+        let work: Vec<(String, f64, Option<BaseScan>)> = coins_to_scan
+            .into_iter()
+            .map(|base_currency| {
+                let test_amount = test_amount(balance_manager, &base_currency, min_trade_amount);
+                // A different amount changes every result of the base currency
+                let previous = previous_bases
+                    .remove(&base_currency)
+                    .filter(|scan| scan.test_amount == test_amount);
+                (base_currency, test_amount, previous)
+            })
+            .collect();
+
+        // Use Rayon for parallel scanning
+        let now = Utc::now();
+        let results: Vec<_> = work
+            .into_par_iter()
+            .map(|(base_currency, test_amount, previous)| {
+                let updated = updated.as_ref().and_then(|updated| {
+                    updated.get(&pair_manager.symbols().currency_id(&base_currency)?)
+                });
+                let (scanned, opps, best, scan) = self.scan_for_base_currency(
+                    &base_currency,
+                    test_amount,
+                    pair_manager,
+                    previous,
+                    updated,
+                    now,
+                );
+                (base_currency, scanned, opps, best, scan)
+            })
+            .collect();
+
+        let mut evaluated = 0;
+        let mut cycle_best: Option<ArbitrageOpportunity> = None;
+        let mut bases = HashMap::new();
+
+        for (base_currency, scanned, opps, best_in_coin, scan) in results {
+            evaluated += scanned;
+            self.opportunities.extend(opps);
+            bases.insert(base_currency, scan);
+
+            if let Some(best) = best_in_coin {
+                if cycle_best
+                    .as_ref()
+                    .is_none_or(|o| best.estimated_profit_pct > o.estimated_profit_pct)
+                {
+                    cycle_best = Some(best);
+                }
+            }
+        }
+
+        self.scan_cache = Some(ScanCache {
+            generation,
+            update_seq: pair_manager.update_seq(),
+            bases,
+        });
+        (evaluated, cycle_best)
+    }
+
+    /// Search the currency graph for the best cycles through each coin. Triangles are
+    /// priced like cached ones. Only triangles can trade: a cycle of four or more legs never
+    /// becomes an opportunity, and the best one is only kept in `last_scan_long_cycle` for
+    /// the debug log. Returns the number of triangles evaluated and the best.
+    fn scan_graph(
+        &mut self,
+        pair_manager: &PairManager,
+        balance_manager: &BalanceManager,
+        coins_to_scan: Vec<String>,
+        min_trade_amount: f64,
+    ) -> (usize, Option<ArbitrageOpportunity>) {
+        let graph = CurrencyGraph::build(pair_manager, &self.fees);
+        let mut evaluated = 0;
+        let mut cycle_best: Option<ArbitrageOpportunity> = None;
+        self.last_scan_long_cycle = None;
+
+        for base_currency in coins_to_scan {
+            let test_amount = test_amount(balance_manager, &base_currency, min_trade_amount);
+            for cycle in
+                graph.cycles_through(&base_currency, self.max_cycle_legs, f64::NEG_INFINITY)
+            {
+                let &[a, b, c] = cycle.pairs.as_slice() else {
+                    if self
+                        .last_scan_long_cycle
+                        .as_ref()
+                        .is_none_or(|best| cycle.profit_pct > best.profit_pct)
+                    {
+                        self.last_scan_long_cycle = Some(cycle);
+                    }
+                    continue;
+                };
+                let triangle = pair_manager.triangle_at([a, b, c], cycle.path);
+                if !self.is_triangle_liquid_enough(&triangle, pair_manager, test_amount) {
+                    continue;
+                }
+                evaluated += 1;
+                let Some(opportunity) =
+                    self.calculate_arbitrage_profit(&triangle, test_amount, pair_manager)
+                else {
+                    continue;
+                };
+                if cycle_best
+                    .as_ref()
+                    .is_none_or(|o| opportunity.estimated_profit_pct > o.estimated_profit_pct)
+                {
+                    cycle_best = Some(opportunity.clone());
+                }
+                if opportunity.estimated_profit_pct.value() >= self.profit_threshold {
+                    self.opportunities.push(opportunity);
+                }
+            }
+        }
+        (evaluated, cycle_best)
+    }
+
+    /// Order opportunities best first by estimated profit minus the penalties of their
+    /// symbols, adjusted by the triangle's track record
+    pub fn rank(&self, opportunities: &mut [ArbitrageOpportunity]) {
+        let score = |o: &ArbitrageOpportunity| {
+            let penalty: f64 = o
+                .pairs
+                .iter()
+                .filter_map(|symbol| self.symbol_penalties.get(&self.symbols.symbol_id(symbol)?))
+                .sum();
+            let track_record = if self.triangle_adjustments.is_empty() {
+                0.0
+            } else {
+                self.triangle_adjustments
+                    .get(&o.pairs.join(","))
+                    .copied()
+                    .unwrap_or(0.0)
+            };
+            o.estimated_profit_pct.value() - penalty + track_record
+        };
+        opportunities.sort_by(|a, b| score(b).total_cmp(&score(a)));
+    }
+
+    /// Scan for arbitrage opportunities using a specific base currency. With the results
+    /// of the previous scan, only the `updated` triangles and those whose quotes have since
+    /// expired are evaluated again; the number evaluated is returned first.
     fn scan_for_base_currency(
         &self,
         base_currency: &str,
         test_amount: f64,
         pair_manager: &PairManager,
+        previous: Option<BaseScan>,
+        updated: Option<&HashSet<usize>>,
+        now: chrono::DateTime<Utc>,
     ) -> (
         usize,
         Vec<ArbitrageOpportunity>,
         Option<ArbitrageOpportunity>,
+        BaseScan,
     ) {
         let empty_vec = Vec::new();
         let triangles = pair_manager
-            .get_cached_triangles(base_currency)
+            .symbols()
+            .currency_id(base_currency)
+            .and_then(|base| pair_manager.get_cached_triangles(base))
             .unwrap_or(&empty_vec);
+        let count = triangles.len().min(self.max_scan_count);
+        let mut scan = previous
+            .filter(|scan| scan.results.len() == count)
+            .unwrap_or_else(|| BaseScan {
+                test_amount,
+                results: Vec::new(),
+            });
+        let full = scan.results.is_empty();
+        if full {
+            scan.results = vec![None; count];
+        }
         let mut scanned_count = 0;
         let mut found_opportunities = Vec::new();
         let mut best_opp: Option<ArbitrageOpportunity> = None;
 
-        for triangle in triangles.iter().take(self.max_scan_count) {
-            // Pre-filter triangles by liquidity
-            if !self.is_triangle_liquid_enough(triangle, pair_manager, test_amount) {
+        for (position, triangle) in triangles.iter().take(count).enumerate() {
+            let expired = scan.results[position]
+                .as_ref()
+                .is_some_and(|o| o.is_expired(now));
+            if full || expired || updated.is_some_and(|u| u.contains(&position)) {
                 scanned_count += 1;
-                continue;
+                // Pre-filter triangles by liquidity
+                scan.results[position] =
+                    if self.is_triangle_liquid_enough(triangle, pair_manager, test_amount) {
+                        self.calculate_arbitrage_profit(triangle, test_amount, pair_manager)
+                    } else {
+                        None
+                    };
             }
 
-            if let Some(opportunity) =
-                self.calculate_arbitrage_profit(triangle, test_amount, pair_manager)
-            {
+            if let Some(opportunity) = &scan.results[position] {
+                // Stamped as found now, as a full evaluation would
+                let stamped = || ArbitrageOpportunity {
+                    timestamp: now,
+                    ..opportunity.clone()
+                };
                 if best_opp
                     .as_ref()
                     .is_none_or(|o| opportunity.estimated_profit_pct > o.estimated_profit_pct)
                 {
-                    best_opp = Some(opportunity.clone());
+                    best_opp = Some(stamped());
                 }
 
-                if opportunity.estimated_profit_pct >= self.profit_threshold {
-                    found_opportunities.push(opportunity);
+                if opportunity.estimated_profit_pct.value() >= self.profit_threshold {
+                    found_opportunities.push(stamped());
                 }
             }
-            scanned_count += 1;
         }
 
         // debug!("Scanned {} triangles for {}", scanned_count, base_currency);
-        (scanned_count, found_opportunities, best_opp)
+        (scanned_count, found_opportunities, best_opp, scan)
     }
 
     /// Check if triangle meets minimum liquidity requirements
-    fn is_triangle_liquid_enough(
+    pub fn is_triangle_liquid_enough(
         &self,
         triangle: &TriangleDefinition,
         pair_manager: &PairManager,
@@ -210,8 +549,9 @@ impl ArbitrageEngine {
 
         let pairs = [p1, p2, p3];
         let min_trade_size_usd = test_amount.max(pair_manager.config.min_trade_amount_usd);
+        let now = Instant::now();
 
-        for pair in &pairs {
+        for (&idx, pair) in triangle.indices.iter().zip(&pairs) {
             // Volume filter - must have sufficient 24h volume
             if pair.volume_24h_usd < pair_manager.config.min_volume_24h_usd {
                 // debug!(
@@ -246,17 +586,22 @@ impl ArbitrageEngine {
                 return false;
             }
 
-            // Liquidity flag check
-            if !pair.is_liquid {
+            // Liquidity flag check; inactive pairs have no live prices
+            if !pair.is_liquid || !pair.is_active {
                 // debug!("❌ {} marked as illiquid", pair.symbol);
                 return false;
             }
+
+            // Staleness filter - the quote must be recent enough for this pair
+            if pair_manager.is_quote_stale(idx, now) {
+                return false;
+            }
         }
         true
     }
 
     /// Calculate profit for a specific triangle using realistic bid/ask prices
-    fn calculate_arbitrage_profit(
+    pub fn calculate_arbitrage_profit(
         &self,
         triangle: &TriangleDefinition,
         initial_amount: f64,
@@ -279,6 +624,26 @@ impl ArbitrageEngine {
         for (i, pair) in pairs.iter().enumerate() {
             let from_currency = &path[i];
 
+            // A maker leg 1 joins the best price on its own side of the book
+            if i == 0 && self.execution_strategy.maker_leg1() {
+                let side = if pair.base == *from_currency {
+                    "Sell"
+                } else {
+                    "Buy"
+                };
+                let price = maker::join_price(side, pair.bid_price, pair.ask_price);
+                if price <= 0.0 {
+                    return None;
+                }
+                let received = match side {
+                    "Sell" => current_amount * price,
+                    _ => current_amount / price,
+                };
+                prices.push(Price::new(price));
+                current_amount = received * (1.0 - self.fees.maker(&pair.symbol));
+                continue;
+            }
+
             // Determine if we're buying or selling and use appropriate price
             let (amount_after_trade, _effective_price) = if pair.base == *from_currency {
                 // Selling base for quote (from_currency/to_currency)
@@ -287,7 +652,7 @@ impl ArbitrageEngine {
                     return None; // Invalid price
                 }
                 let received = current_amount * pair.bid_price;
-                prices.push(pair.bid_price);
+                prices.push(Price::new(pair.bid_price));
                 (received, pair.bid_price)
             } else {
                 // Buying base with quote (to_currency/from_currency)
@@ -296,12 +661,12 @@ impl ArbitrageEngine {
                     return None; // Invalid price
                 }
                 let received = current_amount / pair.ask_price;
-                prices.push(pair.ask_price);
+                prices.push(Price::new(pair.ask_price));
                 (received, pair.ask_price)
             };
 
-            // Apply trading fee (typically 0.1% for Bybit)
-            current_amount = amount_after_trade * (1.0 - self.trading_fee_rate);
+            // Apply the symbol's trading fee (typically 0.1% for Bybit)
+            current_amount = amount_after_trade * (1.0 - self.fees.taker(&pair.symbol));
         }
 
         // Calculate profit with additional slippage buffer
@@ -309,21 +674,26 @@ impl ArbitrageEngine {
         let profit_pct = (profit_amount / test_amount) * 100.0;
 
         // Apply realistic slippage penalty (0.05% per trade = 0.15% total for 3 trades)
-        let slippage_penalty = 0.15;
+        // plus the discounts of symbols whose quotes arrive late, so are older than they look
+        let delay_discount: f64 = triangle
+            .symbols
+            .iter()
+            .filter_map(|id| self.delay_discounts.get(id))
+            .sum();
+        let slippage_penalty = 0.15 + delay_discount;
         let profit_pct_with_slippage = profit_pct - slippage_penalty;
 
-        // Estimate profit in USD (assuming USDT ≈ USD)
-        let estimated_usd_profit =
-            if triangle.base_currency == "USDT" || triangle.base_currency == "USDC" {
-                (profit_amount - (test_amount * slippage_penalty / 100.0))
-                    * (initial_amount / test_amount)
-            } else {
-                // For non-USD base currencies, we'd need price conversion
-                // For now, use a conservative estimate
-                (profit_amount - (test_amount * slippage_penalty / 100.0))
-                    * 0.5
-                    * (initial_amount / test_amount)
-            };
+        // Estimate profit in USD (assuming stablecoins ≈ USD)
+        let estimated_usd_profit = if STABLECOINS.contains(&triangle.base_currency.as_str()) {
+            (profit_amount - (test_amount * slippage_penalty / 100.0))
+                * (initial_amount / test_amount)
+        } else {
+            // For non-USD base currencies, we'd need price conversion
+            // For now, use a conservative estimate
+            (profit_amount - (test_amount * slippage_penalty / 100.0))
+                * 0.5
+                * (initial_amount / test_amount)
+        };
 
         if profit_pct_with_slippage > -1.0 && profit_pct_with_slippage.is_finite() {
             // Sanity check: Filter out unrealistic profits (> 100%) which usually indicate bad data
@@ -343,14 +713,17 @@ impl ArbitrageEngine {
                 pair_manager.pairs[triangle.indices[1]].symbol.clone(),
                 pair_manager.pairs[triangle.indices[2]].symbol.clone(),
             ];
+            let valid_until = pair_manager.quotes_valid_until(&triangle.indices);
 
             let opportunity = ArbitrageOpportunity {
+                schema_version: SCHEMA_VERSION,
                 path: path.clone(),
                 pairs: pair_symbols,
                 prices,
-                estimated_profit_pct: profit_pct_with_slippage,
-                estimated_profit_usd: estimated_usd_profit,
+                estimated_profit_pct: Pct::new(profit_pct_with_slippage),
+                estimated_profit_usd: Notional::new(estimated_usd_profit),
                 timestamp: Utc::now(),
+                valid_until,
             };
 
             // Return any profitable opportunity (threshold handled in main)
@@ -364,7 +737,7 @@ impl ArbitrageEngine {
     pub fn get_profitable_opportunities(&self, min_profit_pct: f64) -> Vec<&ArbitrageOpportunity> {
         self.opportunities
             .iter()
-            .filter(|opp| opp.estimated_profit_pct >= min_profit_pct)
+            .filter(|opp| opp.estimated_profit_pct.value() >= min_profit_pct)
             .collect()
     }
 
@@ -380,14 +753,14 @@ impl ArbitrageEngine {
         let max_profit = self
             .opportunities
             .iter()
-            .map(|opp| opp.estimated_profit_pct)
+            .map(|opp| opp.estimated_profit_pct.value())
             .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
             .unwrap_or(0.0);
 
         let avg_profit = if total_opportunities > 0 {
             self.opportunities
                 .iter()
-                .map(|opp| opp.estimated_profit_pct)
+                .map(|opp| opp.estimated_profit_pct.value())
                 .sum::<f64>()
                 / total_opportunities as f64
         } else {
@@ -398,7 +771,8 @@ impl ArbitrageEngine {
             .opportunities
             .iter()
             .map(|opp| opp.estimated_profit_usd)
-            .sum();
+            .sum::<Notional>()
+            .value();
 
         ArbitrageStatistics {
             total_opportunities,
@@ -411,6 +785,31 @@ impl ArbitrageEngine {
     }
 }
 
+/// Amount of `base_currency` triangles are priced for: the minimum trade amount or a
+/// portion of the balance, whichever is larger
+fn test_amount(
+    balance_manager: &BalanceManager,
+    base_currency: &str,
+    min_trade_amount: f64,
+) -> f64 {
+    let balance = balance_manager.get_balance(base_currency);
+    min_trade_amount.max((balance * 0.1).min(1000.0))
+}
+
+impl Strategy for ArbitrageEngine {
+    fn name(&self) -> &str {
+        "triangular"
+    }
+
+    fn propose_trades(&mut self, market: &MarketView) -> Vec<ArbitrageOpportunity> {
+        self.scan_opportunities_with_min_amount(
+            market.pairs,
+            market.balances,
+            market.min_trade_amount,
+        )
+    }
+}
+
 impl Default for ArbitrageEngine {
     fn default() -> Self {
         Self::new()
@@ -449,7 +848,6 @@ impl ArbitrageStatistics {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::MarketPair;
     // use crate::pairs::TrianglePairs;
 
     // #[allow(dead_code)]
@@ -536,8 +934,150 @@ mod tests {
     fn test_arbitrage_engine_with_config() {
         let engine = ArbitrageEngine::with_config(0.5, 100, 0.002);
         assert_eq!(engine.profit_threshold, 0.5);
+        assert_eq!(engine.execution_threshold, 0.5);
         assert_eq!(engine.max_scan_count, 100);
-        assert_eq!(engine.trading_fee_rate, 0.002);
+        assert_eq!(engine.fees.taker("BTCUSDT"), 0.002);
+    }
+
+    #[test]
+    fn test_execution_threshold_is_separate_from_scan_threshold() {
+        let engine = ArbitrageEngine::with_config(0.05, 100, 0.001).with_execution_threshold(0.25);
+        let mut opportunity = ArbitrageOpportunity::test().with_profit_pct(0.1);
+        assert!(!engine.is_executable(&opportunity, 100.0));
+
+        opportunity.estimated_profit_pct = Pct::new(0.25);
+        assert!(engine.is_executable(&opportunity, 100.0));
+
+        // 0.25% of $10 is 2.5 cents, below a 10 cent floor that $100 clears
+        let engine = engine.with_min_profit_usd(0.1);
+        assert!(!engine.is_executable(&opportunity, 10.0));
+        assert!(engine.is_executable(&opportunity, 100.0));
+    }
+
+    #[test]
+    fn test_penalized_symbols_rank_lower() {
+        let symbols = SymbolRegistry::new();
+        let pepe_btc = symbols.intern_symbol("PEPEBTC", "PEPE", "BTC");
+        let mut engine = ArbitrageEngine::new().with_symbols(symbols);
+        let opportunity = |pairs: [&str; 3], profit: f64| {
+            ArbitrageOpportunity::test()
+                .with_path(&["USDT", "X", "Y", "USDT"])
+                .with_pairs(&pairs)
+                .with_profit_pct(profit)
+        };
+        let mut opps = vec![
+            opportunity(["BTCUSDT", "ETHBTC", "ETHUSDT"], 0.3),
+            opportunity(["PEPEUSDT", "PEPEBTC", "BTCUSDT"], 0.4),
+        ];
+
+        engine.rank(&mut opps);
+        assert_eq!(opps[0].pairs[0], "PEPEUSDT");
+
+        engine.set_symbol_penalties(HashMap::from([(pepe_btc, 0.2)]));
+        engine.rank(&mut opps);
+        assert_eq!(opps[0].pairs[0], "BTCUSDT");
+        // The estimate itself is left untouched
+        assert_eq!(opps[1].estimated_profit_pct, Pct::new(0.4));
+
+        // A triangle that has paid more than estimated makes up for it
+        engine.set_triangle_adjustments(HashMap::from([(
+            "PEPEUSDT,PEPEBTC,BTCUSDT".to_string(),
+            0.15,
+        )]));
+        engine.rank(&mut opps);
+        assert_eq!(opps[0].pairs[0], "PEPEUSDT");
+    }
+
+    #[test]
+    fn test_rescans_only_triangles_through_updated_symbols() {
+        use crate::config::Config;
+        use crate::models::{MarketPair, TickerInfo};
+
+        let pair =
+            |base: &str, quote: &str, price: f64| MarketPair::test(base, quote, price, price);
+        let mut manager = PairManager::from_pairs(
+            Config::test_config(),
+            vec![
+                pair("BTC", "USDT", 50_000.0),
+                pair("ETH", "USDT", 2_500.0),
+                pair("ETH", "BTC", 0.05),
+                pair("SOL", "USDT", 100.0),
+                pair("SOL", "BTC", 0.002),
+            ],
+        );
+        let balances = BalanceManager::new();
+        // Report every triangle, losing ones included
+        let scan = |engine: &mut ArbitrageEngine, manager: &PairManager| {
+            engine.set_base_currencies(Some(vec!["USDT".to_string()]));
+            engine
+                .scan_opportunities_with_min_amount(manager, &balances, 100.0)
+                .into_iter()
+                .map(|o| (o.pairs, o.estimated_profit_pct.value()))
+                .collect::<Vec<_>>()
+        };
+        let mut engine = ArbitrageEngine::with_config(-1.0, 2000, 0.001);
+
+        let first = scan(&mut engine, &manager);
+        assert_eq!((first.len(), engine.last_scan_evaluated), (4, 4));
+        assert_eq!(scan(&mut engine, &manager), first);
+        assert_eq!(engine.last_scan_evaluated, 0);
+
+        // Only the two triangles through ETHBTC see its new quote
+        let ticker: TickerInfo = serde_json::from_value(serde_json::json!({
+            "symbol": "ETHBTC",
+            "bid1Price": "0.0502",
+            "ask1Price": "0.0503",
+        }))
+        .unwrap();
+        manager.update_from_ticker(&ticker);
+        let incremental = scan(&mut engine, &manager);
+        assert_eq!(engine.last_scan_evaluated, 2);
+        assert_ne!(incremental, first);
+        let full = scan(
+            &mut ArbitrageEngine::with_config(-1.0, 2000, 0.001),
+            &manager,
+        );
+        assert_eq!(incremental, full);
+    }
+
+    #[test]
+    fn test_graph_search_trades_triangles_and_reports_longer_cycles() {
+        use crate::config::Config;
+        use crate::models::MarketPair;
+
+        let pair =
+            |base: &str, quote: &str, price: f64| MarketPair::test(base, quote, price, price);
+        // USDT → BTC → ETH → USDT returns 2%, and USDT → BTC → ETH → SOL → USDT 3%
+        let manager = PairManager::from_pairs(
+            Config::test_config(),
+            vec![
+                pair("BTC", "USDT", 50_000.0),
+                pair("ETH", "BTC", 0.05),
+                pair("ETH", "USDT", 2_550.0),
+                pair("SOL", "ETH", 0.04),
+                pair("SOL", "USDT", 103.0),
+            ],
+        );
+        let scan = |algorithm: Algorithm| {
+            let mut engine =
+                ArbitrageEngine::with_config(1.0, 2000, 0.0).with_algorithm(algorithm, 4);
+            engine.set_base_currencies(Some(vec!["USDT".to_string()]));
+            let found =
+                engine.scan_opportunities_with_min_amount(&manager, &BalanceManager::new(), 100.0);
+            let found: Vec<_> = found
+                .into_iter()
+                .map(|o| (o.pairs, o.estimated_profit_pct.value()))
+                .collect();
+            (found, engine)
+        };
+
+        // The triangle is priced as the enumeration prices it
+        let (found, engine) = scan(Algorithm::Graph);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, ["BTCUSDT", "ETHBTC", "ETHUSDT"]);
+        assert_eq!(found, scan(Algorithm::Triangles).0);
+        let long = engine.last_scan_long_cycle.as_ref().unwrap();
+        assert_eq!(long.path.join(","), "USDT,BTC,ETH,SOL,USDT");
     }
 
     #[test]
@@ -547,4 +1087,181 @@ mod tests {
         assert_eq!(stats.total_opportunities, 0);
         assert_eq!(stats.profitable_count, 0);
     }
+
+    mod profit_properties {
+        use super::*;
+        use crate::config::Config;
+        use crate::models::{FeeRateInfo, MarketPair};
+        use proptest::prelude::*;
+
+        const FEE_RATE: f64 = 0.001;
+        /// Slippage penalty the engine subtracts from every estimate
+        const SLIPPAGE_PCT: f64 = 0.15;
+
+        fn market(base: &str, quote: &str, mid: f64, half_spread: f64) -> MarketPair {
+            MarketPair::test(
+                base,
+                quote,
+                mid * (1.0 - half_spread),
+                mid * (1.0 + half_spread),
+            )
+            .with_size(1.0)
+            .with_volume(1000.0)
+        }
+
+        /// BTCUSDT, ETHBTC and ETHUSDT with the given mid prices
+        fn pair_manager(
+            btc_usdt: f64,
+            eth_btc: f64,
+            eth_usdt: f64,
+            half_spread: f64,
+        ) -> PairManager {
+            let mut manager = PairManager::new(Config::test_config());
+            manager.pairs = vec![
+                market("BTC", "USDT", btc_usdt, half_spread),
+                market("ETH", "BTC", eth_btc, half_spread),
+                market("ETH", "USDT", eth_usdt, half_spread),
+            ];
+            manager
+        }
+
+        /// Triangle over `path`, using the pair that connects each consecutive coin
+        fn triangle(manager: &PairManager, path: [&str; 3]) -> TriangleDefinition {
+            let leg = |from: &str, to: &str| {
+                manager
+                    .pairs
+                    .iter()
+                    .position(|p| {
+                        (p.base == from && p.quote == to) || (p.base == to && p.quote == from)
+                    })
+                    .unwrap()
+            };
+            manager.triangle_at(
+                [
+                    leg(path[0], path[1]),
+                    leg(path[1], path[2]),
+                    leg(path[2], path[0]),
+                ],
+                [path[0], path[1], path[2], path[0]]
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect(),
+            )
+        }
+
+        /// Net return multiple of one pass through the triangle, with the slippage penalty removed.
+        /// None when the engine discards the triangle (losses beyond 1%)
+        fn round_trip(
+            engine: &ArbitrageEngine,
+            manager: &PairManager,
+            path: [&str; 3],
+        ) -> Option<f64> {
+            engine
+                .calculate_arbitrage_profit(&triangle(manager, path), 100.0, manager)
+                .map(|opp| 1.0 + (opp.estimated_profit_pct.value() + SLIPPAGE_PCT) / 100.0)
+        }
+
+        #[test]
+        fn late_quotes_discount_the_edge() {
+            let manager = pair_manager(50_000.0, 0.05, 2_500.0, 0.0);
+            let mut engine = ArbitrageEngine::with_config(0.0, 100, FEE_RATE)
+                .with_symbols(manager.symbols().clone());
+            let path = ["USDT", "BTC", "ETH"];
+            let fresh = round_trip(&engine, &manager, path).unwrap();
+
+            engine.set_delay_discounts(
+                manager
+                    .symbols()
+                    .by_id(HashMap::from([("ETHBTC".to_string(), 0.05)])),
+            );
+            let late = round_trip(&engine, &manager, path).unwrap();
+            assert!((fresh - late - 0.0005).abs() < 1e-9, "{fresh} vs {late}");
+        }
+
+        #[test]
+        fn maker_leg1_joins_the_book_at_the_maker_fee() {
+            let manager = pair_manager(50_000.0, 0.05, 2_500.0, 0.001);
+            let fees = FeeSchedule::flat(FEE_RATE).with_rates(&[FeeRateInfo {
+                symbol: "BTCUSDT".to_string(),
+                taker_fee_rate: FEE_RATE.to_string(),
+                maker_fee_rate: "0".to_string(),
+            }]);
+            let mut engine = ArbitrageEngine::with_config(0.0, 100, FEE_RATE);
+            engine.set_fee_schedule(fees);
+            let path = ["USDT", "BTC", "ETH"];
+            let taker = round_trip(&engine, &manager, path).unwrap();
+
+            let engine = engine.with_execution_strategy(ExecutionStrategy::MakerFirst);
+            let opportunity = engine
+                .calculate_arbitrage_profit(&triangle(&manager, path), 100.0, &manager)
+                .unwrap();
+            // The BTC is bought resting at the bid, not crossing to the ask
+            assert_eq!(opportunity.prices[0], Price::new(49_950.0));
+            let maker = round_trip(&engine, &manager, path).unwrap();
+            let expected = (50_050.0 / 49_950.0) / (1.0 - FEE_RATE);
+            assert!(
+                (maker / taker - expected).abs() < 1e-9,
+                "{maker} vs {taker}"
+            );
+        }
+
+        proptest! {
+            #[test]
+            fn consistent_prices_never_show_profit(
+                btc_usd in 1.0f64..100_000.0,
+                eth_usd in 0.01f64..10_000.0,
+                half_spread in 0.0f64..0.01,
+            ) {
+                // Cross rate derived from the two USDT prices: no arbitrage by construction
+                let manager = pair_manager(btc_usd, eth_usd / btc_usd, eth_usd, half_spread);
+                let engine = ArbitrageEngine::with_config(0.0, 100, FEE_RATE);
+                let max_return = (1.0 - FEE_RATE).powi(3);
+
+                for path in [["USDT", "BTC", "ETH"], ["USDT", "ETH", "BTC"]] {
+                    if let Some(multiple) = round_trip(&engine, &manager, path) {
+                        prop_assert!(multiple <= max_return * (1.0 + 1e-9), "{path:?}: {multiple}");
+                    }
+                }
+            }
+
+            #[test]
+            fn profit_is_invariant_to_rotation(
+                btc_usd in 1.0f64..100_000.0,
+                eth_usd in 0.01f64..10_000.0,
+                mispricing in -0.003f64..0.003,
+                half_spread in 0.0f64..0.0005,
+            ) {
+                let eth_btc = eth_usd / btc_usd * (1.0 + mispricing);
+                let manager = pair_manager(btc_usd, eth_btc, eth_usd, half_spread);
+                let engine = ArbitrageEngine::with_config(0.0, 100, FEE_RATE);
+
+                let start_usdt = round_trip(&engine, &manager, ["USDT", "BTC", "ETH"]).unwrap();
+                let start_btc = round_trip(&engine, &manager, ["BTC", "ETH", "USDT"]).unwrap();
+                let start_eth = round_trip(&engine, &manager, ["ETH", "USDT", "BTC"]).unwrap();
+                prop_assert!((start_usdt - start_btc).abs() < 1e-9);
+                prop_assert!((start_usdt - start_eth).abs() < 1e-9);
+            }
+
+            #[test]
+            fn opposite_directions_cannot_both_profit(
+                btc_usd in 1.0f64..100_000.0,
+                eth_usd in 0.01f64..10_000.0,
+                mispricing in -0.003f64..0.003,
+                half_spread in prop_oneof![Just(0.0), 0.0f64..0.0005],
+            ) {
+                let eth_btc = eth_usd / btc_usd * (1.0 + mispricing);
+                let manager = pair_manager(btc_usd, eth_btc, eth_usd, half_spread);
+                let engine = ArbitrageEngine::with_config(0.0, 100, 0.0);
+
+                // Without fees or spread the two directions are exact reciprocals;
+                // spread can only make the combined round trip worse
+                let forward = round_trip(&engine, &manager, ["USDT", "BTC", "ETH"]).unwrap();
+                let backward = round_trip(&engine, &manager, ["USDT", "ETH", "BTC"]).unwrap();
+                prop_assert!(forward * backward <= 1.0 + 1e-9);
+                if half_spread == 0.0 {
+                    prop_assert!((forward * backward - 1.0).abs() < 1e-9);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,169 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Where the availability history is kept so the 7-day window survives restarts
+pub const AVAILABILITY_FILE: &str = "availability.json";
+/// Operational time is summed per bucket of this many seconds
+const BUCKET_SECS: i64 = 600;
+/// A longer gap between samples, e.g. a stalled main loop, is counted as down
+const MAX_SAMPLE_GAP_SECS: f64 = 60.0;
+/// Windows availability is reported over, with their labels
+pub const WINDOWS: [(&str, i64); 2] = [("24h", 24), ("7d", 7 * 24)];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Bucket {
+    start: DateTime<Utc>,
+    up_secs: f64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct History {
+    /// When tracking began; time since then the bot wasn't running counts as down
+    tracking_since: Option<DateTime<Utc>>,
+    buckets: VecDeque<Bucket>,
+}
+
+/// Share of time the bot was fully operational, as reported by /status and /metrics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AvailabilitySummary {
+    pub last_24h_pct: Option<f64>,
+    pub last_7d_pct: Option<f64>,
+}
+
+/// Rolling record of when the bot was fully operational: price feeds on WebSockets, the
+/// API answering and trading neither halted nor waiting out maintenance
+#[derive(Debug, Default)]
+pub struct AvailabilityTracker {
+    history: History,
+    last_sample: Option<(DateTime<Utc>, bool)>,
+    path: Option<PathBuf>,
+}
+
+impl AvailabilityTracker {
+    /// Resume the history of previous runs; a missing or unreadable file starts afresh
+    pub fn load(path: &Path) -> Self {
+        Self {
+            history: crate::persist::load_json(path),
+            last_sample: None,
+            path: Some(path.to_path_buf()),
+        }
+    }
+
+    /// Note whether the bot is operational now. The time since the previous sample counts
+    /// as up if the bot was operational then.
+    pub fn record(&mut self, now: DateTime<Utc>, operational: bool) {
+        self.history.tracking_since.get_or_insert(now);
+        if let Some((at, up)) = self.last_sample {
+            let gap = (now - at).as_seconds_f64();
+            if up && (0.0..=MAX_SAMPLE_GAP_SECS).contains(&gap) {
+                self.add_up_time(now, gap);
+            }
+        }
+        self.last_sample = Some((now, operational));
+    }
+
+    fn add_up_time(&mut self, now: DateTime<Utc>, secs: f64) {
+        let start = now
+            .duration_trunc(Duration::seconds(BUCKET_SECS))
+            .unwrap_or(now);
+        match self.history.buckets.back_mut() {
+            Some(bucket) if bucket.start == start => bucket.up_secs += secs,
+            _ => {
+                self.history.buckets.push_back(Bucket {
+                    start,
+                    up_secs: secs,
+                });
+                let oldest = now - Duration::hours(WINDOWS[1].1) - Duration::seconds(BUCKET_SECS);
+                while self
+                    .history
+                    .buckets
+                    .front()
+                    .is_some_and(|b| b.start < oldest)
+                {
+                    self.history.buckets.pop_front();
+                }
+                // Once per bucket is often enough to lose little in a crash
+                if let Err(e) = self.save() {
+                    warn!("⚠️ Failed to save availability history: {e:#}");
+                }
+            }
+        }
+    }
+
+    /// Percentage of the last `hours`, or of the time since tracking began if shorter, the
+    /// bot was operational
+    pub fn availability_pct(&self, now: DateTime<Utc>, hours: i64) -> Option<f64> {
+        let since = (now - Duration::hours(hours)).max(self.history.tracking_since?);
+        let span = (now - since).as_seconds_f64();
+        if span < 1.0 {
+            return None;
+        }
+        let up: f64 = self
+            .history
+            .buckets
+            .iter()
+            .filter(|b| b.start + Duration::seconds(BUCKET_SECS) > since)
+            .map(|b| b.up_secs)
+            .sum();
+        Some((up / span * 100.0).min(100.0))
+    }
+
+    pub fn summary(&self, now: DateTime<Utc>) -> AvailabilitySummary {
+        AvailabilitySummary {
+            last_24h_pct: self.availability_pct(now, WINDOWS[0].1),
+            last_7d_pct: self.availability_pct(now, WINDOWS[1].1),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        crate::persist::write_atomic(path, &serde_json::to_string(&self.history)?)?;
+        debug!("💾 Saved availability history to {}", path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_availability_counts_outages_and_time_between_runs_as_down() {
+        let path =
+            std::env::temp_dir().join(format!("availability_test_{}.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let start = Utc::now() - Duration::hours(2);
+        let mut tracker = AvailabilityTracker::load(&path);
+        assert_eq!(tracker.summary(start), AvailabilitySummary::default());
+
+        // 30 minutes up, then 30 minutes with the feeds down
+        for secs in (0..=1800).step_by(10) {
+            tracker.record(start + Duration::seconds(secs), true);
+        }
+        for secs in (1810..=3600).step_by(10) {
+            tracker.record(start + Duration::seconds(secs), false);
+        }
+        let pct = tracker
+            .availability_pct(start + Duration::hours(1), 24)
+            .unwrap();
+        assert!((pct - 50.0).abs() < 0.5, "{pct}");
+        tracker.save().unwrap();
+
+        // The hour the bot wasn't running counts as down too
+        let mut tracker = AvailabilityTracker::load(&path);
+        let restart = start + Duration::hours(2);
+        tracker.record(restart, true);
+        tracker.record(restart + Duration::seconds(1), true);
+        let summary = tracker.summary(restart + Duration::seconds(1));
+        let pct = summary.last_24h_pct.unwrap();
+        assert!((pct - 25.0).abs() < 0.5, "{pct}");
+        assert_eq!(summary.last_24h_pct, summary.last_7d_pct);
+        std::fs::remove_file(&path).ok();
+    }
+}
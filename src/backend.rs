@@ -0,0 +1,188 @@
+use crate::depth::DepthBook;
+use crate::exchange::ExchangeApi;
+use crate::imbalance::TopOfBook;
+use crate::models::{ArbitrageOpportunity, SCHEMA_VERSION};
+use crate::resume::{ExecutionState, ResumeMode};
+use crate::symbols::SymbolId;
+use crate::trader::{ArbitrageExecutionResult, ArbitrageTrader};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+
+/// Where trades go, chosen by `EXECUTION_BACKEND`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExecutionBackend {
+    /// Real orders on Bybit
+    Live,
+    /// Fills against the live book out of a virtual ledger
+    Paper,
+    /// The built-in estimate with a fixed slippage guess
+    Simulated,
+    /// Nothing is executed, e.g. to only watch opportunities
+    Noop,
+}
+
+impl ExecutionBackend {
+    /// Parse `EXECUTION_BACKEND`: `live`, `paper`, `simulated` or `noop`. Empty follows
+    /// `DRY_RUN` and `PAPER_TRADING`; only `live` may run with `DRY_RUN=false`.
+    pub fn resolve(value: &str, dry_run: bool, paper_trading: bool) -> Result<Self> {
+        let backend = match value.trim().to_lowercase().as_str() {
+            "" if !dry_run => ExecutionBackend::Live,
+            "" if paper_trading => ExecutionBackend::Paper,
+            "" => ExecutionBackend::Simulated,
+            "live" => ExecutionBackend::Live,
+            "paper" => ExecutionBackend::Paper,
+            "simulated" => ExecutionBackend::Simulated,
+            "noop" => ExecutionBackend::Noop,
+            other => {
+                return Err(anyhow!(
+                    "unknown execution backend '{other}' (expected live, paper, simulated or noop)"
+                ))
+            }
+        };
+        if paper_trading && backend != ExecutionBackend::Paper {
+            bail!("PAPER_TRADING needs DRY_RUN=true and no other EXECUTION_BACKEND");
+        }
+        if (backend == ExecutionBackend::Live) == dry_run {
+            bail!(
+                "EXECUTION_BACKEND={} needs DRY_RUN={}",
+                backend.as_str(),
+                !dry_run
+            );
+        }
+        Ok(backend)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExecutionBackend::Live => "live",
+            ExecutionBackend::Paper => "paper",
+            ExecutionBackend::Simulated => "simulated",
+            ExecutionBackend::Noop => "noop",
+        }
+    }
+}
+
+/// Trades triangles and reports the outcome, on the exchange, on paper or not at all
+#[async_trait]
+pub trait TradeExecutor: Send + Sync {
+    async fn execute_arbitrage(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        amount: f64,
+    ) -> Result<ArbitrageExecutionResult>;
+
+    /// Convert what the filled legs of a half-done triangle left back to its start coin
+    async fn rollback(&mut self, state: &ExecutionState) -> Result<()>;
+
+    /// One line on the backend's state, for the logs
+    fn health(&self) -> String;
+
+    /// The market the next trade fills against, for backends that simulate fills
+    fn set_market(
+        &mut self,
+        _book: &HashMap<SymbolId, TopOfBook>,
+        _depth: &HashMap<SymbolId, DepthBook>,
+        _usd_prices: &HashMap<String, f64>,
+    ) {
+    }
+}
+
+#[async_trait]
+impl<E: ExchangeApi> TradeExecutor for ArbitrageTrader<E> {
+    async fn execute_arbitrage(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        amount: f64,
+    ) -> Result<ArbitrageExecutionResult> {
+        ArbitrageTrader::execute_arbitrage(self, opportunity, amount).await
+    }
+
+    async fn rollback(&mut self, state: &ExecutionState) -> Result<()> {
+        self.resume_interrupted(state, ResumeMode::Rollback).await
+    }
+
+    fn health(&self) -> String {
+        ArbitrageTrader::health(self)
+    }
+}
+
+/// Refuses every trade, so the bot only scans and reports
+pub struct NoopTrader;
+
+#[async_trait]
+impl TradeExecutor for NoopTrader {
+    async fn execute_arbitrage(
+        &mut self,
+        _opportunity: &ArbitrageOpportunity,
+        amount: f64,
+    ) -> Result<ArbitrageExecutionResult> {
+        Ok(ArbitrageExecutionResult {
+            schema_version: SCHEMA_VERSION,
+            success: false,
+            initial_amount: amount,
+            actual_profit: 0.0,
+            actual_profit_pct: 0.0,
+            dust_value_usd: 0.0,
+            dust: BTreeMap::new(),
+            total_fees: 0.0,
+            execution_time_ms: 0,
+            error_message: Some("Execution disabled (EXECUTION_BACKEND=noop)".to_string()),
+            exchange_error: None,
+        })
+    }
+
+    async fn rollback(&mut self, _state: &ExecutionState) -> Result<()> {
+        Ok(())
+    }
+
+    fn health(&self) -> String {
+        "noop: no trades are executed".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::MockExchange;
+
+    use crate::precision::PrecisionManager;
+
+    #[test]
+    fn test_resolve_follows_dry_run_and_refuses_conflicts() {
+        let resolve = ExecutionBackend::resolve;
+        assert_eq!(resolve("", false, false).unwrap(), ExecutionBackend::Live);
+        assert_eq!(
+            resolve("", true, false).unwrap(),
+            ExecutionBackend::Simulated
+        );
+        assert_eq!(resolve("", true, true).unwrap(), ExecutionBackend::Paper);
+        assert_eq!(
+            resolve(" NOOP ", true, false).unwrap(),
+            ExecutionBackend::Noop
+        );
+        // Never trades for real behind DRY_RUN=true, nor pretends to with DRY_RUN=false
+        assert!(resolve("live", true, false).is_err());
+        assert!(resolve("noop", false, false).is_err());
+        assert!(resolve("", false, true).is_err());
+        assert!(resolve("noop", true, true).is_err());
+        assert!(resolve("backtest", true, false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dry_runs_go_to_the_configured_backend() {
+        let mut trader = ArbitrageTrader::new(MockExchange::new(), true, PrecisionManager::new())
+            .with_backend(Box::new(NoopTrader));
+        let opportunity = ArbitrageOpportunity::test().with_profit_pct(1.7);
+
+        let executor: &mut dyn TradeExecutor = &mut trader;
+        let result = executor
+            .execute_arbitrage(&opportunity, 100.0)
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error_message.unwrap().contains("noop"));
+        assert_eq!(executor.health(), "noop: no trades are executed");
+        assert!(trader.client().orders().is_empty());
+    }
+}
@@ -0,0 +1,370 @@
+use crate::arbitrage::ArbitrageEngine;
+use crate::balance::BalanceManager;
+use crate::config::Config;
+use crate::graph::Algorithm;
+use crate::models::ArbitrageOpportunity;
+use crate::pairs::PairManager;
+use crate::recorder::{MarketData, MarketRecord, MarketRecorder};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// Coin every backtested triangle starts and ends in
+const BACKTEST_BASE: &str = "USDT";
+/// Paths listed in the report
+const REPORT_PATHS: usize = 10;
+
+/// Execution assumptions of a backtest
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BacktestSettings {
+    /// Time from detecting an opportunity to leg 1 filling, and between legs
+    pub latency: Duration,
+    /// Price moved against each leg on top of the recorded top of book, in percent
+    pub slippage_pct: f64,
+}
+
+/// One simulated execution
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedTrade {
+    pub ts: DateTime<Utc>,
+    pub path: String,
+    pub estimated_profit_pct: f64,
+    /// False when a leg's symbol had no usable quote, so the triangle was abandoned
+    pub filled: bool,
+    pub profit_usd: f64,
+    pub profit_pct: f64,
+    pub fees_usd: f64,
+}
+
+/// Triangle being executed, one leg per latency step
+struct PendingTrade {
+    opportunity: ArbitrageOpportunity,
+    started: DateTime<Utc>,
+    amount: f64,
+    held: f64,
+    leg: usize,
+    due: DateTime<Utc>,
+    fees_usd: f64,
+}
+
+/// What a recording would have traded under the current configuration
+#[derive(Debug, Default)]
+pub struct BacktestReport {
+    pub records: usize,
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+    pub trades: Vec<SimulatedTrade>,
+}
+
+impl BacktestReport {
+    pub fn profit_usd(&self) -> f64 {
+        self.trades.iter().map(|t| t.profit_usd).sum()
+    }
+
+    /// Largest fall of cumulative profit from its running peak
+    fn max_drawdown(&self) -> f64 {
+        let (mut total, mut peak, mut drawdown) = (0.0_f64, 0.0_f64, 0.0_f64);
+        for trade in &self.trades {
+            total += trade.profit_usd;
+            peak = peak.max(total);
+            drawdown = drawdown.max(peak - total);
+        }
+        drawdown
+    }
+
+    fn print(&self, config: &Config, settings: &BacktestSettings) {
+        let (Some(first), Some(last)) = (self.first, self.last) else {
+            println!("No market data in the recording");
+            return;
+        };
+        println!(
+            "📈 BACKTEST: {} records from {} to {} ({}s)",
+            self.records,
+            first.format("%Y-%m-%d %H:%M:%S"),
+            last.format("%Y-%m-%d %H:%M:%S"),
+            (last - first).num_seconds()
+        );
+        println!(
+            "   Threshold {:.3}% · ${:.2} per trade · latency {}ms per leg · slippage {:.3}% per leg · fees {:.3}% per leg",
+            config.min_profit_threshold,
+            config.order_size,
+            settings.latency.as_millis(),
+            settings.slippage_pct,
+            config.effective_fee_rate() * 100.0
+        );
+        if self.trades.is_empty() {
+            println!("   No opportunity cleared the threshold");
+            return;
+        }
+
+        let filled: Vec<&SimulatedTrade> = self.trades.iter().filter(|t| t.filled).collect();
+        let won = filled.iter().filter(|t| t.profit_usd > 0.0).count();
+        let mean = |values: Vec<f64>| values.iter().sum::<f64>() / values.len().max(1) as f64;
+        println!(
+            "   Trades: {} ({} won, {} lost, {} abandoned)",
+            self.trades.len(),
+            won,
+            filled.len() - won,
+            self.trades.len() - filled.len()
+        );
+        println!(
+            "   Realized: ${:.4}, fees ${:.4}, max drawdown ${:.4}",
+            self.profit_usd(),
+            self.trades.iter().map(|t| t.fees_usd).sum::<f64>(),
+            self.max_drawdown()
+        );
+        println!(
+            "   Average trade: {:.4}% realized vs {:.4}% estimated",
+            mean(filled.iter().map(|t| t.profit_pct).collect()),
+            mean(filled.iter().map(|t| t.estimated_profit_pct).collect())
+        );
+
+        let mut by_path: BTreeMap<&str, (usize, f64)> = BTreeMap::new();
+        for trade in &self.trades {
+            let entry = by_path.entry(&trade.path).or_default();
+            entry.0 += 1;
+            entry.1 += trade.profit_usd;
+        }
+        let mut by_path: Vec<_> = by_path.into_iter().collect();
+        by_path.sort_by(|a, b| b.1 .1.total_cmp(&a.1 .1));
+        println!("   By path:");
+        for (path, (trades, profit)) in by_path.iter().take(REPORT_PATHS) {
+            println!("     {path:<32} {trades:>5} trades  ${profit:>10.4}");
+        }
+    }
+}
+
+/// Replay a recording through the pair manager and arbitrage engine, executing each
+/// opportunity above the threshold one leg per latency step at the quotes of that moment
+pub fn run(
+    config: &Config,
+    records: &[MarketRecord],
+    settings: BacktestSettings,
+) -> BacktestReport {
+    let algorithm = Algorithm::parse(&config.arbitrage_algorithm).unwrap_or(Algorithm::Triangles);
+    let mut engine = ArbitrageEngine::with_config(
+        config.min_profit_threshold,
+        config.max_triangles_to_scan,
+        config.effective_fee_rate(),
+    )
+    .with_execution_threshold(config.min_profit_threshold)
+    .with_min_profit_usd(config.min_profit_usd)
+    .with_algorithm(algorithm, config.graph_max_cycle_legs);
+    engine.set_base_currencies(Some(vec![BACKTEST_BASE.to_string()]));
+    let balances = BalanceManager::new();
+    let latency = chrono::Duration::from_std(settings.latency).unwrap_or_default();
+
+    let mut report = BacktestReport {
+        records: records.len(),
+        first: records.first().map(|r| r.ts),
+        last: records.last().map(|r| r.ts),
+        trades: Vec::new(),
+    };
+    let mut pair_manager: Option<PairManager> = None;
+    let mut pending: Option<PendingTrade> = None;
+    let mut i = 0;
+    while i < records.len() {
+        let ts = records[i].ts;
+        // Legs due before these updates fill at the quotes that held until then
+        if let Some(pairs) = &pair_manager {
+            while let Some(trade) = pending.as_mut().filter(|t| t.due < ts) {
+                if let Some(done) = fill_next_leg(trade, pairs, config, &settings, latency) {
+                    report.trades.push(done);
+                    pending = None;
+                }
+            }
+        }
+        while let Some(record) = records.get(i).filter(|r| r.ts == ts) {
+            match &record.data {
+                MarketData::Pairs { pairs } => {
+                    pair_manager = Some(PairManager::from_pairs(config.clone(), pairs.clone()));
+                }
+                MarketData::Ticker { ticker } => {
+                    if let Some(pairs) = pair_manager.as_mut() {
+                        pairs.update_from_ticker(ticker);
+                    }
+                }
+            }
+            i += 1;
+        }
+
+        let Some(pairs) = &pair_manager else { continue };
+        if pending.is_some() {
+            continue;
+        }
+        let opportunities =
+            engine.scan_opportunities_with_min_amount(pairs, &balances, config.order_size);
+        if let Some(best) = opportunities
+            .into_iter()
+            .find(|o| engine.is_executable(o, config.order_size))
+        {
+            pending = Some(PendingTrade {
+                opportunity: best,
+                started: ts,
+                amount: config.order_size,
+                held: config.order_size,
+                leg: 0,
+                due: ts + latency,
+                fees_usd: 0.0,
+            });
+        }
+    }
+    // Whatever is still executing finishes at the last quotes recorded
+    if let (Some(mut trade), Some(pairs)) = (pending, &pair_manager) {
+        loop {
+            if let Some(done) = fill_next_leg(&mut trade, pairs, config, &settings, latency) {
+                report.trades.push(done);
+                break;
+            }
+        }
+    }
+    report
+}
+
+/// Fill the next leg of `trade` at the current top of book, less slippage and fees.
+/// Returns the finished trade after the last leg, or when a leg can't be priced.
+fn fill_next_leg(
+    trade: &mut PendingTrade,
+    pairs: &PairManager,
+    config: &Config,
+    settings: &BacktestSettings,
+    latency: chrono::Duration,
+) -> Option<SimulatedTrade> {
+    let opportunity = &trade.opportunity;
+    let from = &opportunity.path[trade.leg];
+    let to = &opportunity.path[trade.leg + 1];
+    let slippage = settings.slippage_pct / 100.0;
+    let gross = pairs
+        .get_pair(&opportunity.pairs[trade.leg])
+        .and_then(|pair| {
+            if &pair.base == from {
+                (pair.bid_price > 0.0).then_some(trade.held * pair.bid_price * (1.0 - slippage))
+            } else {
+                (pair.ask_price > 0.0).then_some(trade.held / (pair.ask_price * (1.0 + slippage)))
+            }
+        });
+    let finished = |trade: &PendingTrade, filled: bool| {
+        let profit_usd = if filled {
+            (trade.held - trade.amount) * pairs.usd_price(BACKTEST_BASE).unwrap_or(1.0)
+        } else {
+            0.0
+        };
+        SimulatedTrade {
+            ts: trade.started,
+            path: trade.opportunity.display_path(),
+            estimated_profit_pct: trade.opportunity.estimated_profit_pct.value(),
+            filled,
+            profit_usd,
+            profit_pct: profit_usd / trade.amount * 100.0,
+            fees_usd: trade.fees_usd,
+        }
+    };
+    let Some(gross) = gross else {
+        return Some(finished(trade, false));
+    };
+
+    let fee = gross * config.effective_fee_rate();
+    trade.fees_usd += fee * pairs.usd_price(to).unwrap_or(0.0);
+    trade.held = gross - fee;
+    trade.leg += 1;
+    trade.due += latency;
+    (trade.leg == 3).then(|| finished(trade, true))
+}
+
+/// Backtest a recording made with MARKET_DATA_PATH and print the report
+pub fn run_backtest(config: Config, path: &Path, settings: BacktestSettings) -> Result<()> {
+    let records = MarketRecorder::read(path)?;
+    if !records
+        .iter()
+        .any(|r| matches!(r.data, MarketData::Pairs { .. }))
+    {
+        return Err(anyhow!(
+            "{} has no pairs snapshot to start from (record with MARKET_DATA_PATH from startup)",
+            path.display()
+        ));
+    }
+    let report = run(&config, &records, settings);
+    report.print(&config, &settings);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{MarketPair, TickerInfo};
+
+    fn ticker(symbol: &str, bid: f64, ask: f64) -> TickerInfo {
+        serde_json::from_value(serde_json::json!({
+            "symbol": symbol,
+            "bid1Price": bid.to_string(),
+            "ask1Price": ask.to_string(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_latency_turns_a_fading_edge_into_a_loss() {
+        let start: DateTime<Utc> = "2026-03-01T10:00:00Z".parse().unwrap();
+        let at = |ms: i64| start + chrono::Duration::milliseconds(ms);
+        let recording = [
+            MarketRecord {
+                ts: at(0),
+                data: MarketData::Pairs {
+                    pairs: vec![
+                        MarketPair::test("BTC", "USDT", 49_990.0, 50_000.0),
+                        MarketPair::test("ETH", "BTC", 0.0595, 0.0596),
+                        MarketPair::test("ETH", "USDT", 3_040.0, 3_041.0),
+                    ],
+                },
+            },
+            // ETH catches up with BTC 50ms later, closing the edge
+            MarketRecord {
+                ts: at(50),
+                data: MarketData::Ticker {
+                    ticker: Box::new(ticker("ETHUSDT", 2_970.0, 2_971.0)),
+                },
+            },
+            MarketRecord {
+                ts: at(1_000),
+                data: MarketData::Ticker {
+                    ticker: Box::new(ticker("BTCUSDT", 49_990.0, 50_000.0)),
+                },
+            },
+        ];
+        // Recordings go through JSON, so the backtest reads what the recorder writes
+        let records: Vec<MarketRecord> = recording
+            .iter()
+            .map(|r| serde_json::from_str(&serde_json::to_string(r).unwrap()).unwrap())
+            .collect();
+
+        let mut config = Config::test_config();
+        config.min_profit_threshold = 0.5;
+        config.order_size = 100.0;
+        let fast = run(
+            &config,
+            &records,
+            BacktestSettings {
+                latency: Duration::from_millis(10),
+                slippage_pct: 0.0,
+            },
+        );
+        assert_eq!(fast.trades.len(), 1);
+        let trade = &fast.trades[0];
+        assert_eq!(trade.path, "USDT → BTC → ETH → USDT");
+        assert!(trade.filled && trade.profit_usd > 1.0, "{trade:?}");
+
+        let slow = run(
+            &config,
+            &records,
+            BacktestSettings {
+                latency: Duration::from_millis(100),
+                slippage_pct: 0.05,
+            },
+        );
+        assert_eq!(slow.trades.len(), 1);
+        assert!(slow.profit_usd() < 0.0, "{:?}", slow.trades);
+        assert!(slow.max_drawdown() > 0.0);
+    }
+}
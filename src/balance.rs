@@ -1,13 +1,48 @@
-use crate::client::BybitClient;
+use crate::clock::{Clock, SystemClock};
+use crate::exchange::ExchangeApi;
 use crate::models::BalanceMap;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use futures_util::future::join_all;
 use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
+/// Balances as of one fetch, handed from the balance sync task to the scanner
+#[derive(Debug, Clone)]
+pub struct BalanceSnapshot {
+    pub balances: BalanceMap,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// Refresh request this fetch answers, see `BalanceSync::refresh`
+    pub ticket: u64,
+}
+
 pub struct BalanceManager {
     balances: BalanceMap,
     last_updated: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the balances were last fetched, for the refresh interval
+    refreshed_at: Option<Instant>,
+    clock: Arc<dyn Clock>,
+    /// Net deposits less withdrawals per coin this session, kept apart from trading results
+    external_flows: BalanceMap,
+    /// Amounts held back for trades in flight, per coin
+    reserved: HashMap<String, Reservation>,
+}
+
+/// What trades in flight hold back of a coin
+#[derive(Debug, Clone, Copy)]
+struct Reservation {
+    amount: f64,
+    /// Balance of the coin when it was reserved. The trades spend out of the reservation,
+    /// so whatever the balance fell below this is no longer held back on top of it.
+    baseline: f64,
+}
+
+impl Reservation {
+    /// Part of the reservation the balance doesn't show as spent yet
+    fn outstanding(&self, balance: f64) -> f64 {
+        (self.amount - (self.baseline - balance).max(0.0)).max(0.0)
+    }
 }
 
 impl BalanceManager {
@@ -15,25 +50,32 @@ impl BalanceManager {
         Self {
             balances: HashMap::new(),
             last_updated: None,
+            refreshed_at: None,
+            clock: SystemClock::shared(),
+            external_flows: HashMap::new(),
+            reserved: HashMap::new(),
         }
     }
 
+    #[cfg(test)]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Fetch and update account balances
-    pub async fn update_balances(&mut self, client: &BybitClient) -> Result<()> {
+    pub async fn update_balances<E: ExchangeApi + ?Sized>(&mut self, client: &E) -> Result<()> {
         debug!("Updating account balances...");
 
         // Try different account types to find balances in parallel
         let account_types = ["UNIFIED", "SPOT", "CONTRACT"];
 
         // Create futures for all requests
-        let futures = account_types.iter().map(|&account_type| {
-            let client = client.clone();
-            async move {
-                (
-                    account_type,
-                    client.get_wallet_balance(Some(account_type)).await,
-                )
-            }
+        let futures = account_types.iter().map(|&account_type| async move {
+            (
+                account_type,
+                client.get_wallet_balance(Some(account_type)).await,
+            )
         });
 
         // Execute all requests concurrently
@@ -93,6 +135,7 @@ impl BalanceManager {
         }
 
         self.last_updated = Some(chrono::Utc::now());
+        self.refreshed_at = Some(self.clock.now());
 
         debug!("✅ Updated balances for {} assets", self.balances.len());
         self.log_balances();
@@ -100,9 +143,46 @@ impl BalanceManager {
         Ok(())
     }
 
-    /// Get balance for a specific coin
+    /// Get balance for a specific coin, less what trades in flight have reserved and not
+    /// spent yet
     pub fn get_balance(&self, coin: &str) -> f64 {
-        self.balances.get(coin).copied().unwrap_or(0.0)
+        let balance = self.balances.get(coin).copied().unwrap_or(0.0);
+        let reserved = self
+            .reserved
+            .get(coin)
+            .map_or(0.0, |r| r.outstanding(balance));
+        (balance - reserved).max(0.0)
+    }
+
+    /// Hold back `amount` of `coin` for a trade about to start, so scans don't size another
+    /// trade on it. Fails when less than that is unreserved.
+    pub fn reserve(&mut self, coin: &str, amount: f64) -> Result<()> {
+        let available = self.get_balance(coin);
+        if amount > available {
+            bail!("{amount:.8} {coin} needed but only {available:.8} is unreserved");
+        }
+        let balance = self.balances.get(coin).copied().unwrap_or(0.0);
+        self.reserved
+            .entry(coin.to_string())
+            .or_insert(Reservation {
+                amount: 0.0,
+                baseline: balance,
+            })
+            .amount += amount;
+        Ok(())
+    }
+
+    /// Give back a reservation once its trade has finished. What the trade actually spent
+    /// of the coin, net of what came back, leaves the baseline with it, so it isn't counted
+    /// against the trades still in flight.
+    pub fn release(&mut self, coin: &str, amount: f64, spent: f64) {
+        if let Some(reserved) = self.reserved.get_mut(coin) {
+            reserved.amount -= amount;
+            reserved.baseline -= spent;
+            if reserved.amount <= f64::EPSILON {
+                self.reserved.remove(coin);
+            }
+        }
     }
 
     /// Get all balances
@@ -115,21 +195,61 @@ impl BalanceManager {
         self.balances.keys().cloned().collect()
     }
 
+    /// Time of the last successful balance refresh
+    pub fn last_updated(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.last_updated
+    }
+
     /// Check if balances need refresh (based on configured interval)
     pub fn needs_refresh(&self, interval_secs: u64) -> bool {
-        match self.last_updated {
+        match self.refreshed_at {
             None => true,
-            Some(last_update) => {
-                let now = chrono::Utc::now();
-                let duration = now.signed_duration_since(last_update);
-                duration.num_seconds() as u64 >= interval_secs
+            Some(refreshed_at) => {
+                self.clock.now().duration_since(refreshed_at) >= Duration::from_secs(interval_secs)
             }
         }
     }
 
+    /// Current balances, if they have been fetched
+    pub fn snapshot(&self, ticket: u64) -> Option<BalanceSnapshot> {
+        Some(BalanceSnapshot {
+            balances: self.balances.clone(),
+            updated_at: self.last_updated?,
+            ticket,
+        })
+    }
+
+    /// Replace the balances with ones fetched elsewhere
+    pub fn apply_snapshot(&mut self, snapshot: &BalanceSnapshot) {
+        self.balances = snapshot.balances.clone();
+        self.last_updated = Some(snapshot.updated_at);
+        self.refreshed_at = Some(self.clock.now());
+    }
+
+    /// Take up balances pushed by the wallet stream; a coin pushed at zero is dropped
+    pub fn apply_pushed(&mut self, pushed: &BalanceMap) {
+        for (coin, &balance) in pushed {
+            if balance > 0.0 {
+                self.balances.insert(coin.clone(), balance);
+            } else {
+                self.balances.remove(coin);
+            }
+        }
+        self.last_updated = Some(chrono::Utc::now());
+    }
+
+    /// Note a deposit (positive) or withdrawal (negative) made outside the bot
+    pub fn record_external_flow(&mut self, coin: &str, amount: f64) {
+        *self.external_flows.entry(coin.to_string()).or_insert(0.0) += amount;
+    }
+
+    pub fn external_flows(&self) -> &BalanceMap {
+        &self.external_flows
+    }
+
     /// Force a balance refresh on the next update cycle
     pub fn force_refresh(&mut self) {
-        self.last_updated = None;
+        self.refreshed_at = None;
     }
 
     /// Log current balances for debugging
@@ -149,7 +269,11 @@ impl BalanceManager {
     }
 
     /// Log initial account scanning configuration with minimum trade amount filtering
-    pub fn log_initial_scanning_info_with_min_amount(&self, min_trade_amount: f64) {
+    pub fn log_initial_scanning_info_with_min_amount(
+        &self,
+        min_trade_amount: f64,
+        usd_price: impl Fn(&str) -> Option<f64>,
+    ) {
         let all_coins = self.get_available_coins();
 
         if all_coins.is_empty() {
@@ -163,13 +287,8 @@ impl BalanceManager {
 
         for coin in &all_coins {
             let balance = self.get_balance(coin);
-            let usd_value = if coin == "USDT" || coin == "USDC" || coin == "BUSD" {
-                balance // These are already in USD
-            } else {
-                // For other coins, we'd need price data to convert to USD
-                // For now, assume we need the minimum in the coin itself
-                balance
-            };
+            // A coin without a USD price can't be sized, so it counts as insufficient
+            let usd_value = usd_price(coin).map_or(0.0, |price| balance * price);
 
             if usd_value >= min_trade_amount {
                 sufficient_coins.push((coin.clone(), balance, usd_value));
@@ -203,23 +322,18 @@ impl BalanceManager {
         }
     }
 
-    /// Get coins that have sufficient balance for trading
-    pub fn get_tradeable_coins(&self, min_trade_amount: f64) -> Vec<String> {
+    /// Get coins whose balance is worth at least `min_trade_amount` USD, valued at
+    /// `usd_price`; coins without a price are left out
+    pub fn get_tradeable_coins(
+        &self,
+        min_trade_amount: f64,
+        usd_price: impl Fn(&str) -> Option<f64>,
+    ) -> Vec<String> {
         self.balances
-            .iter()
-            .filter_map(|(coin, &balance)| {
-                let usd_value = if coin == "USDT" || coin == "USDC" || coin == "BUSD" {
-                    balance // These are already in USD
-                } else {
-                    // For other coins, assume we need the minimum in the coin itself
-                    balance
-                };
-
-                if usd_value >= min_trade_amount {
-                    Some(coin.clone())
-                } else {
-                    None
-                }
+            .keys()
+            .filter_map(|coin| {
+                let usd_value = self.get_balance(coin) * usd_price(coin)?;
+                (usd_value >= min_trade_amount).then(|| coin.clone())
             })
             .collect()
     }
@@ -342,4 +456,86 @@ mod tests {
         assert!(significant.contains_key("USDT"));
         assert!(!significant.contains_key("ETH"));
     }
+
+    #[test]
+    fn test_reservations_hold_back_balance() {
+        let mut manager = BalanceManager::new();
+        manager.balances.insert("USDT".to_string(), 100.0);
+
+        manager.reserve("USDT", 60.0).unwrap();
+        assert_eq!(manager.get_balance("USDT"), 40.0);
+        assert!(manager.reserve("USDT", 50.0).is_err());
+        assert!(manager.get_tradeable_coins(50.0, |_| Some(1.0)).is_empty());
+
+        // A refresh mid-trade keeps the reservation, less what leg 1 already spent of it
+        manager.apply_pushed(&BalanceMap::from([("USDT".to_string(), 90.0)]));
+        assert_eq!(manager.get_balance("USDT"), 40.0);
+        manager.apply_pushed(&BalanceMap::from([("USDT".to_string(), 40.0)]));
+        assert_eq!(manager.get_balance("USDT"), 40.0);
+
+        // A second trade reserves on top; the first one's spending isn't credited to it
+        manager.reserve("USDT", 30.0).unwrap();
+        assert_eq!(manager.get_balance("USDT"), 10.0);
+        manager.release("USDT", 60.0, 60.0);
+        assert_eq!(manager.get_balance("USDT"), 10.0);
+        manager.release("USDT", 30.0, 0.0);
+        assert_eq!(manager.get_balance("USDT"), 40.0);
+        manager.release("USDT", 10.0, 0.0);
+        assert_eq!(manager.get_balance("USDT"), 40.0);
+    }
+
+    #[test]
+    fn test_release_of_a_trade_that_spent_nothing_keeps_the_baseline() {
+        let mut manager = BalanceManager::new();
+        manager.balances.insert("USDT".to_string(), 100.0);
+        manager.reserve("USDT", 40.0).unwrap();
+        manager.reserve("USDT", 40.0).unwrap();
+        assert_eq!(manager.get_balance("USDT"), 20.0);
+
+        // The second trade spends its 40 while the first fails before any order fills
+        manager.apply_pushed(&BalanceMap::from([("USDT".to_string(), 60.0)]));
+        assert_eq!(manager.get_balance("USDT"), 20.0);
+        manager.release("USDT", 40.0, 0.0);
+        assert_eq!(manager.get_balance("USDT"), 60.0);
+
+        // The second completes and brings back a little more than it spent
+        manager.apply_pushed(&BalanceMap::from([("USDT".to_string(), 100.4)]));
+        manager.release("USDT", 40.0, -0.4);
+        assert_eq!(manager.get_balance("USDT"), 100.4);
+    }
+
+    #[test]
+    fn test_tradeable_coins_are_valued_in_usd() {
+        let mut manager = BalanceManager::new();
+        manager.balances.insert("BTC".to_string(), 0.0001); // ~$6
+        manager.balances.insert("ETH".to_string(), 0.01); // ~$30
+        manager.balances.insert("USDT".to_string(), 25.0);
+        manager.balances.insert("XYZ".to_string(), 1_000.0); // No price
+
+        let prices = HashMap::from([("BTC", 60_000.0), ("ETH", 3_000.0), ("USDT", 1.0)]);
+        let mut coins = manager.get_tradeable_coins(10.0, |coin| prices.get(coin).copied());
+        coins.sort();
+        assert_eq!(coins, ["ETH", "USDT"]);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_interval_follows_clock() {
+        let clock = Arc::new(crate::clock::ManualClock::new());
+        let exchange = crate::exchange::MockExchange::new().with_balance("USDT", 50.0);
+        let mut manager = BalanceManager::new().with_clock(clock.clone());
+        assert!(manager.needs_refresh(60));
+
+        manager.update_balances(&exchange).await.unwrap();
+        assert!(!manager.needs_refresh(60));
+
+        clock.advance(Duration::from_secs(59));
+        assert!(!manager.needs_refresh(60));
+        clock.advance(Duration::from_secs(1));
+        assert!(manager.needs_refresh(60));
+
+        manager.update_balances(&exchange).await.unwrap();
+        manager.force_refresh();
+        assert!(manager.needs_refresh(60));
+        assert_eq!(manager.get_balance("USDT"), 50.0);
+    }
 }
@@ -0,0 +1,173 @@
+use crate::balance::{BalanceManager, BalanceSnapshot};
+use crate::exchange::ExchangeApi;
+use crate::supervisor;
+use crate::wallet_stream::WalletFeed;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{watch, Notify};
+use tokio::time::{sleep, timeout, Duration};
+use tracing::warn;
+
+/// Longest `refresh` waits for fresh balances
+const REFRESH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Handle to the supervised task that keeps account balances fresh
+pub struct BalanceSync {
+    requested: Arc<AtomicU64>,
+    refresh: Arc<Notify>,
+    rx: watch::Receiver<Option<BalanceSnapshot>>,
+}
+
+impl BalanceSync {
+    /// Fetch balances every `interval_secs` and whenever a refresh is requested. While
+    /// `wallet` streams, its pushes are published instead of the timed fetches.
+    pub fn spawn(
+        client: Arc<dyn ExchangeApi>,
+        interval_secs: u64,
+        wallet: Option<Arc<WalletFeed>>,
+        restart_on_panic: bool,
+    ) -> Self {
+        let (tx, rx) = watch::channel(None);
+        let tx = Arc::new(tx);
+        let requested = Arc::new(AtomicU64::new(0));
+        let refresh = Arc::new(Notify::new());
+
+        let (task_requested, task_refresh) = (requested.clone(), refresh.clone());
+        supervisor::spawn_supervised("Balance sync".to_string(), restart_on_panic, move || {
+            run(
+                client.clone(),
+                interval_secs,
+                wallet.clone(),
+                task_requested.clone(),
+                task_refresh.clone(),
+                tx.clone(),
+            )
+        });
+
+        Self {
+            requested,
+            refresh,
+            rx,
+        }
+    }
+
+    /// Receiver of every snapshot the task publishes
+    pub fn subscribe(&self) -> watch::Receiver<Option<BalanceSnapshot>> {
+        self.rx.clone()
+    }
+
+    /// Fetch balances now and wait until a fetch started after this call has been published,
+    /// e.g. so the next scan sees what a trade left behind. Returns false on timeout.
+    pub async fn refresh(&self) -> bool {
+        let ticket = self.requested.fetch_add(1, Ordering::SeqCst) + 1;
+        self.refresh.notify_one();
+        let mut rx = self.rx.clone();
+        let fresh = timeout(
+            REFRESH_TIMEOUT,
+            rx.wait_for(|s| s.as_ref().is_some_and(|s| s.ticket >= ticket)),
+        )
+        .await;
+        matches!(fresh, Ok(Ok(_)))
+    }
+}
+
+async fn run(
+    client: Arc<dyn ExchangeApi>,
+    interval_secs: u64,
+    wallet: Option<Arc<WalletFeed>>,
+    requested: Arc<AtomicU64>,
+    refresh: Arc<Notify>,
+    tx: Arc<watch::Sender<Option<BalanceSnapshot>>>,
+) {
+    let mut manager = BalanceManager::new();
+    let mut pushes = wallet.as_ref().map(|wallet| wallet.subscribe());
+    // Ticket of the last fetch: a push doesn't answer refresh requests made before it
+    let mut fetched_ticket = 0;
+    let mut forced = true;
+    loop {
+        let streaming = wallet.as_ref().is_some_and(|wallet| wallet.is_connected());
+        if forced || (!streaming && manager.needs_refresh(interval_secs)) {
+            let ticket = requested.load(Ordering::SeqCst);
+            match manager.update_balances(&*client).await {
+                Ok(()) => {
+                    fetched_ticket = ticket;
+                    forced = false;
+                    tx.send_replace(manager.snapshot(ticket));
+                }
+                Err(e) => warn!("⚠️ Balance sync failed: {e:#}"),
+            }
+        }
+        let pushed = async {
+            match pushes.as_mut() {
+                Some(rx) => rx.changed().await.is_ok(),
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = sleep(Duration::from_secs(interval_secs)) => {}
+            _ = refresh.notified() => {
+                forced = true;
+                manager.force_refresh();
+            }
+            true = pushed => {
+                if let Some(rx) = pushes.as_mut() {
+                    let balances = rx.borrow_and_update().clone();
+                    manager.apply_pushed(&balances);
+                    tx.send_replace(manager.snapshot(fetched_ticket));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::MockExchange;
+    use crate::models::BalanceMap;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_refresh_waits_for_a_fetch_after_the_request() {
+        let exchange = Arc::new(MockExchange::new().with_balance("USDT", 100.0));
+        let sync = BalanceSync::spawn(exchange.clone(), 60, None, true);
+
+        assert!(sync.refresh().await);
+        let first = sync.subscribe().borrow().clone().unwrap();
+        assert_eq!(first.balances.get("USDT"), Some(&100.0));
+
+        // Well inside the interval, a refresh still fetches again
+        let calls = exchange.wallet_calls();
+        assert!(sync.refresh().await);
+        let second = sync.subscribe().borrow().clone().unwrap();
+        assert!(second.ticket > first.ticket);
+        assert!(exchange.wallet_calls() > calls);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wallet_pushes_replace_timed_fetches() {
+        let exchange = Arc::new(MockExchange::new().with_balance("USDT", 100.0));
+        let wallet = WalletFeed::new();
+        wallet.set_connected(true);
+        let sync = BalanceSync::spawn(exchange.clone(), 60, Some(wallet.clone()), true);
+        assert!(sync.refresh().await);
+        let calls = exchange.wallet_calls();
+
+        wallet.apply(BalanceMap::from([
+            ("USDT".to_string(), 0.0),
+            ("BTC".to_string(), 0.002),
+        ]));
+        let mut rx = sync.subscribe();
+        let pushed = rx
+            .wait_for(|s| s.as_ref().is_some_and(|s| s.balances.contains_key("BTC")))
+            .await
+            .unwrap()
+            .clone()
+            .unwrap();
+        assert_eq!(pushed.balances.get("BTC"), Some(&0.002));
+        assert!(!pushed.balances.contains_key("USDT"));
+
+        // Nothing is polled while the stream is up
+        sleep(Duration::from_secs(600)).await;
+        assert_eq!(exchange.wallet_calls(), calls);
+    }
+}
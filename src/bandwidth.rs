@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Traffic is averaged over this long before the budget is checked
+pub const BUDGET_WINDOW: Duration = Duration::from_secs(60);
+
+/// WebSocket traffic as reported by /status and /metrics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BandwidthStatus {
+    /// Average over the last complete window
+    pub bytes_per_sec: f64,
+    pub budget_bytes_per_sec: Option<f64>,
+    /// Bytes received per connection, e.g. `orderbook.1#2`
+    pub connections: BTreeMap<String, u64>,
+    /// Bytes received per topic, e.g. `orderbook.1.BTCUSDT`
+    pub topics: BTreeMap<String, u64>,
+    /// Symbols unsubscribed to stay within the budget
+    pub trimmed: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    connections: HashMap<String, u64>,
+    topics: HashMap<String, u64>,
+}
+
+/// Bytes received by every WebSocket connection, and the symbols they should drop.
+/// Shared by the connections and the main loop.
+#[derive(Debug, Default)]
+pub struct BandwidthMeter {
+    counters: Mutex<Counters>,
+    trimmed: Mutex<BTreeSet<String>>,
+}
+
+impl BandwidthMeter {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// A message of `bytes` arrived on `connection`
+    pub fn record_message(&self, connection: &str, bytes: usize) {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        add(&mut counters.connections, connection, bytes);
+    }
+
+    /// `bytes` of a message were data for `topic`
+    pub fn record_topic(&self, topic: &str, bytes: usize) {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        add(&mut counters.topics, topic, bytes);
+    }
+
+    pub fn is_trimmed(&self, symbol: &str) -> bool {
+        self.trimmed
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains(symbol)
+    }
+
+    fn trim(&self, symbols: &[String]) {
+        self.trimmed
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend(symbols.iter().cloned());
+    }
+
+    fn totals(&self) -> (u64, HashMap<String, u64>) {
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        (counters.connections.values().sum(), counters.topics.clone())
+    }
+
+    pub fn status(&self, budget: &BandwidthBudget) -> BandwidthStatus {
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        BandwidthStatus {
+            bytes_per_sec: budget.bytes_per_sec,
+            budget_bytes_per_sec: budget.limit,
+            connections: counters.connections.clone().into_iter().collect(),
+            topics: counters.topics.clone().into_iter().collect(),
+            trimmed: self
+                .trimmed
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// Add to a counter, allocating its key only the first time
+fn add(counters: &mut HashMap<String, u64>, key: &str, bytes: usize) {
+    match counters.get_mut(key) {
+        Some(total) => *total += bytes as u64,
+        None => {
+            counters.insert(key.to_string(), bytes as u64);
+        }
+    }
+}
+
+/// The symbol a topic like `orderbook.50.BTCUSDT` is for
+fn topic_symbol(topic: &str) -> &str {
+    topic.rsplit('.').next().unwrap_or(topic)
+}
+
+/// Measures the feed rate once per window and trims subscriptions when it's over budget
+pub struct BandwidthBudget {
+    limit: Option<f64>,
+    window_start: Instant,
+    total_at_start: u64,
+    topics_at_start: HashMap<String, u64>,
+    bytes_per_sec: f64,
+}
+
+impl BandwidthBudget {
+    /// `limit_kbps` in kilobytes (1000 bytes) per second; None only measures
+    pub fn new(limit_kbps: Option<f64>, now: Instant) -> Self {
+        Self {
+            limit: limit_kbps.map(|kb| kb * 1000.0),
+            window_start: now,
+            total_at_start: 0,
+            topics_at_start: HashMap::new(),
+            bytes_per_sec: 0.0,
+        }
+    }
+
+    /// At the end of each window, measure the rate and, when it's over budget, trim the
+    /// lowest-scored symbols until the rest would fit. Returns the symbols newly trimmed.
+    pub fn check(
+        &mut self,
+        meter: &BandwidthMeter,
+        now: Instant,
+        score: impl Fn(&str) -> f64,
+    ) -> Vec<String> {
+        let elapsed = now.duration_since(self.window_start);
+        if elapsed < BUDGET_WINDOW {
+            return Vec::new();
+        }
+        let (total, topics) = meter.totals();
+        let secs = elapsed.as_secs_f64();
+        self.bytes_per_sec = total.saturating_sub(self.total_at_start) as f64 / secs;
+        let mut symbol_rates: HashMap<&str, f64> = HashMap::new();
+        for (topic, &bytes) in &topics {
+            let before = self.topics_at_start.get(topic).copied().unwrap_or(0);
+            *symbol_rates.entry(topic_symbol(topic)).or_default() +=
+                bytes.saturating_sub(before) as f64 / secs;
+        }
+        debug!(
+            "📶 WebSocket feeds averaged {:.1} KB/s",
+            self.bytes_per_sec / 1000.0
+        );
+
+        let mut trimmed = Vec::new();
+        if let Some(limit) = self.limit.filter(|&limit| self.bytes_per_sec > limit) {
+            let mut candidates: Vec<(&str, f64)> = symbol_rates
+                .into_iter()
+                .filter(|&(symbol, rate)| rate > 0.0 && !meter.is_trimmed(symbol))
+                .collect();
+            candidates.sort_by(|a, b| score(a.0).total_cmp(&score(b.0)).then(a.0.cmp(b.0)));
+            let mut remaining = self.bytes_per_sec;
+            for (symbol, rate) in candidates {
+                if remaining <= limit {
+                    break;
+                }
+                remaining -= rate;
+                trimmed.push(symbol.to_string());
+            }
+            if remaining > limit {
+                warn!(
+                    "📶 WebSocket feeds still need ~{:.1} KB/s with every symbol trimmed, over the {:.1} KB/s budget",
+                    remaining / 1000.0,
+                    limit / 1000.0
+                );
+            }
+            if !trimmed.is_empty() {
+                warn!(
+                    "📶 WebSocket feeds at {:.1} KB/s, over the {:.1} KB/s budget - unsubscribing {} lowest-ranked symbol(s): {}",
+                    self.bytes_per_sec / 1000.0,
+                    limit / 1000.0,
+                    trimmed.len(),
+                    trimmed.join(", ")
+                );
+                meter.trim(&trimmed);
+            }
+        }
+
+        self.window_start = now;
+        self.total_at_start = total;
+        self.topics_at_start = topics;
+        trimmed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(meter: &BandwidthMeter, topic: &str, bytes: usize) {
+        meter.record_message("orderbook.1#1", bytes + 20);
+        meter.record_topic(topic, bytes);
+    }
+
+    #[test]
+    fn test_over_budget_trims_lowest_scored_until_it_fits() {
+        let start = Instant::now();
+        let meter = BandwidthMeter::new();
+        // 1.5 KB/s allowed; the window sees ~2.5 KB/s
+        let mut budget = BandwidthBudget::new(Some(1.5), start);
+        feed(&meter, "orderbook.1.BTCUSDT", 60_000);
+        feed(&meter, "orderbook.1.PEPEUSDT", 45_000);
+        feed(&meter, "orderbook.50.PEPEUSDT", 15_000);
+        feed(&meter, "orderbook.1.DOGEBTC", 30_000);
+
+        let volume = |symbol: &str| match symbol {
+            "BTCUSDT" => 1e9,
+            "PEPEUSDT" => 1e6,
+            _ => 1e5,
+        };
+        assert!(budget.check(&meter, start, volume).is_empty());
+        let trimmed = budget.check(&meter, start + BUDGET_WINDOW, volume);
+        assert_eq!(trimmed, ["DOGEBTC", "PEPEUSDT"]);
+        assert!(meter.is_trimmed("PEPEUSDT"));
+        assert!(!meter.is_trimmed("BTCUSDT"));
+
+        let status = meter.status(&budget);
+        assert!((status.bytes_per_sec - 150_080.0 / 60.0).abs() < 1e-9);
+        assert_eq!(status.connections["orderbook.1#1"], 150_080);
+        assert_eq!(status.topics["orderbook.50.PEPEUSDT"], 15_000);
+        assert_eq!(status.trimmed, ["DOGEBTC", "PEPEUSDT"]);
+    }
+
+    #[test]
+    fn test_rate_is_measured_per_window_without_a_budget() {
+        let start = Instant::now();
+        let meter = BandwidthMeter::new();
+        let mut budget = BandwidthBudget::new(None, start);
+        feed(&meter, "orderbook.1.BTCUSDT", 580);
+        assert!(budget
+            .check(&meter, start + BUDGET_WINDOW, |_| 0.0)
+            .is_empty());
+        assert!((meter.status(&budget).bytes_per_sec - 10.0).abs() < 1e-9);
+
+        // Only the traffic since the last window counts
+        feed(&meter, "orderbook.1.BTCUSDT", 1180);
+        budget.check(&meter, start + BUDGET_WINDOW * 2, |_| 0.0);
+        assert!((meter.status(&budget).bytes_per_sec - 20.0).abs() < 1e-9);
+        assert!(meter.status(&budget).trimmed.is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::supervisor;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, Duration};
+use tracing::warn;
+
+/// How often the blacklist file is checked for changes
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BlacklistFile {
+    List(Vec<String>),
+    Table { tokens: Vec<String> },
+}
+
+/// Coins in a blacklist file: TOML (`tokens = ["LUNA"]`) or, for a `.json` file, a JSON
+/// array or `{"tokens": [...]}`
+pub fn load(path: &Path) -> Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read blacklist {}", path.display()))?;
+    let file: BlacklistFile = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse blacklist {}", path.display()))?
+    } else {
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse blacklist {}", path.display()))?
+    };
+    let (BlacklistFile::List(tokens) | BlacklistFile::Table { tokens }) = file;
+    Ok(tokens
+        .iter()
+        .map(|t| t.trim().to_uppercase())
+        .filter(|t| !t.is_empty())
+        .collect())
+}
+
+/// The configured blacklist with the file's coins added, comma-separated
+pub fn merge(configured: &str, file_tokens: &[String]) -> String {
+    let mut tokens: Vec<String> = Vec::new();
+    for token in configured
+        .split(',')
+        .map(|t| t.trim().to_uppercase())
+        .chain(file_tokens.iter().cloned())
+    {
+        if !token.is_empty() && !tokens.contains(&token) {
+            tokens.push(token);
+        }
+    }
+    tokens.join(",")
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Watch the blacklist file on a supervised task, sending its coins whenever it changes
+/// (none once it is deleted). A file that doesn't parse is reported and the previous list
+/// kept.
+pub fn spawn(path: PathBuf, restart_on_panic: bool) -> mpsc::UnboundedReceiver<Vec<String>> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    // Survives restarts of the task, so an unchanged file isn't sent again
+    let seen = Arc::new(Mutex::new(modified(&path)));
+    supervisor::spawn_supervised(
+        "Blacklist watcher".to_string(),
+        restart_on_panic,
+        move || {
+            let (path, tx, seen) = (path.clone(), tx.clone(), seen.clone());
+            async move {
+                loop {
+                    sleep(POLL_INTERVAL).await;
+                    let current = modified(&path);
+                    if std::mem::replace(&mut *seen.lock().unwrap(), current) == current {
+                        continue;
+                    }
+                    let tokens = match current {
+                        Some(_) => load(&path),
+                        None => Ok(Vec::new()),
+                    };
+                    match tokens {
+                        Ok(tokens) => {
+                            if tx.send(tokens).is_err() {
+                                return;
+                            }
+                        }
+                        Err(e) => warn!("⚠️ Keeping the previous blacklist: {e:#}"),
+                    }
+                }
+            }
+        },
+    );
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_toml_and_json_blacklists() {
+        let dir = std::env::temp_dir().join(format!("blacklist-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let toml_path = dir.join("blacklist.toml");
+        std::fs::write(&toml_path, "tokens = [\"luna\", \" FTT \"]\n").unwrap();
+        assert_eq!(load(&toml_path).unwrap(), ["LUNA", "FTT"]);
+
+        let json_path = dir.join("blacklist.json");
+        std::fs::write(&json_path, r#"["RDNT"]"#).unwrap();
+        assert_eq!(load(&json_path).unwrap(), ["RDNT"]);
+        std::fs::write(&json_path, r#"{"tokens": ["HOOK"]}"#).unwrap();
+        assert_eq!(load(&json_path).unwrap(), ["HOOK"]);
+
+        std::fs::write(&toml_path, "tokens = \"LUNA\"\n").unwrap();
+        assert!(load(&toml_path).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_merge_adds_file_tokens_once() {
+        assert_eq!(
+            merge("USDR,luna", &["LUNA".to_string(), "RDNT".to_string()]),
+            "USDR,LUNA,RDNT"
+        );
+        assert_eq!(merge("", &[]), "");
+    }
+}
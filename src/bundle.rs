@@ -0,0 +1,174 @@
+use crate::models::{
+    ArbitrageOpportunity, MarketPair, OrderInfo, PlaceOrderRequest, PlaceOrderResult,
+};
+use crate::precision::PrecisionInfo;
+use crate::trader::ArbitrageExecutionResult;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// An order the trader sent, and Bybit's answer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderRecord {
+    pub at: DateTime<Utc>,
+    pub request: PlaceOrderRequest,
+    pub response: Option<PlaceOrderResult>,
+    pub error: Option<String>,
+}
+
+impl OrderRecord {
+    pub fn new(request: PlaceOrderRequest, result: &Result<PlaceOrderResult>) -> Self {
+        Self {
+            at: Utc::now(),
+            request,
+            response: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().map(|e| format!("{e:#}")),
+        }
+    }
+}
+
+/// Orders an execution placed and the fills it read back
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrderLog {
+    pub placed: Vec<OrderRecord>,
+    pub fills: Vec<OrderInfo>,
+    /// Fees of each fill as the trader accounted for them, by order id and coin
+    #[serde(default)]
+    pub fees: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+/// Top of book of a symbol when the execution started
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Quote {
+    pub bid: f64,
+    pub ask: f64,
+    pub bid_size: f64,
+    pub ask_size: f64,
+}
+
+impl Quote {
+    pub fn from_pair(pair: &MarketPair) -> Self {
+        Self {
+            bid: pair.bid_price,
+            ask: pair.ask_price,
+            bid_size: pair.bid_size,
+            ask_size: pair.ask_size,
+        }
+    }
+}
+
+/// Everything needed to reproduce a failed execution, written to a single file that can
+/// be attached to a bug report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebugBundle {
+    pub version: String,
+    pub created_at: DateTime<Utc>,
+    pub dry_run: bool,
+    pub opportunity: ArbitrageOpportunity,
+    pub amount: f64,
+    pub error: Option<String>,
+    /// The execution result, when the trader got as far as producing one
+    pub result: Option<ArbitrageExecutionResult>,
+    pub quotes: BTreeMap<String, Quote>,
+    pub orders: OrderLog,
+    pub balances_before: BTreeMap<String, f64>,
+    pub balances_after: BTreeMap<String, f64>,
+    pub precision: BTreeMap<String, PrecisionInfo>,
+}
+
+impl DebugBundle {
+    /// Write the bundle to `dir` as `failed-<timestamp>.json`, with every secret redacted.
+    /// Returns the file written.
+    pub async fn write(&self, dir: &Path, secrets: &[&str]) -> Result<PathBuf> {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let json = redact(&serde_json::to_string_pretty(self)?, secrets);
+        let path = dir.join(format!(
+            "failed-{}.json",
+            self.created_at.format("%Y%m%dT%H%M%S%.3fZ")
+        ));
+        tokio::fs::write(&path, json)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// Replace every occurrence of each non-empty secret
+pub fn redact(text: &str, secrets: &[&str]) -> String {
+    secrets
+        .iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(text.to_string(), |text, secret| {
+            text.replace(secret, "[REDACTED]")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bundle_is_written_with_secrets_redacted() {
+        let request = PlaceOrderRequest {
+            category: "spot".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: "Buy".to_string(),
+            order_type: "Market".to_string(),
+            qty: "100".to_string(),
+            price: None,
+            time_in_force: Some("IOC".to_string()),
+            order_link_id: Some("arb_1".to_string()),
+            reduce_only: None,
+        };
+        let bundle = DebugBundle {
+            version: "test".to_string(),
+            created_at: Utc::now(),
+            dry_run: false,
+            opportunity: ArbitrageOpportunity::test(),
+            amount: 100.0,
+            error: Some("API error 10003: invalid key abc123key".to_string()),
+            result: None,
+            quotes: BTreeMap::from([(
+                "BTCUSDT".to_string(),
+                Quote {
+                    bid: 49_990.0,
+                    ask: 50_000.0,
+                    bid_size: 1.0,
+                    ask_size: 2.0,
+                },
+            )]),
+            orders: OrderLog {
+                placed: vec![OrderRecord::new(
+                    request,
+                    &Err(anyhow::anyhow!("signature for s3cret rejected")),
+                )],
+                fills: Vec::new(),
+                fees: BTreeMap::new(),
+            },
+            balances_before: BTreeMap::from([("USDT".to_string(), 200.0)]),
+            balances_after: BTreeMap::from([("USDT".to_string(), 200.0)]),
+            precision: BTreeMap::new(),
+        };
+
+        let dir = std::env::temp_dir().join(format!("debug_bundle_test_{}", std::process::id()));
+        let path = bundle
+            .write(&dir, &["abc123key", "s3cret", ""])
+            .await
+            .unwrap();
+        let json = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(!json.contains("abc123key") && !json.contains("s3cret"));
+        let read: DebugBundle = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.orders.placed.len(), 1);
+        assert_eq!(
+            read.orders.placed[0].error.as_deref(),
+            Some("signature for [REDACTED] rejected")
+        );
+        assert_eq!(read.quotes["BTCUSDT"].ask, 50_000.0);
+    }
+}
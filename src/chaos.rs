@@ -0,0 +1,454 @@
+//! Fault-injection harness for trade execution.
+//!
+//! Wraps the mock exchange, injects failures while a triangle is being executed and
+//! checks the trader always ends in a safe state. Run with `cargo test --features chaos`.
+
+use crate::errors::BybitError;
+use crate::exchange::{ExchangeApi, MockExchange, MockMarket};
+use crate::journal::{Journal, JournalEvent, JournalRecord};
+use crate::models::{
+    AmendOrderRequest, ArbitrageOpportunity, DepositRecord, ExecutionRecord, FeeRateInfo,
+    InstrumentInfo, MaintenanceInfo, OrderInfo, OrderState, PlaceOrderRequest, PlaceOrderResult,
+    TickerInfo, TickersResult, WalletBalanceResult, WithdrawalRecord,
+};
+use crate::precision::PrecisionManager;
+use crate::stream::{StreamTaps, StreamTask};
+use crate::trader::{ArbitrageExecutionResult, ArbitrageTrader};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+
+const START_BALANCE: f64 = 1_000.0;
+const TRADE_AMOUNT: f64 = 100.0;
+
+/// A failure injected into one order placement
+#[derive(Debug, Clone)]
+enum OrderFault {
+    /// The exchange rejects the order outright
+    Reject(BybitError),
+    /// Only this fraction fills, the IOC remainder is cancelled
+    PartialFill(f64),
+    /// The order reports `New` for this many status polls before it is filled
+    DelayedFill(usize),
+}
+
+/// Mock exchange with scripted faults
+struct ChaosExchange {
+    inner: MockExchange,
+    /// Faults by placement number (1 = first order of the triangle)
+    order_faults: HashMap<usize, OrderFault>,
+    /// Wallet requests that fail before the wallet recovers (usize::MAX = never)
+    wallet_failures: AtomicUsize,
+    /// Market data is unreachable, like a dropped WebSocket with no REST fallback
+    market_data_down: bool,
+    placed: AtomicUsize,
+    /// Status the exchange reports instead of the fill, with the polls left before it clears
+    status_overrides: Mutex<HashMap<String, (&'static str, usize)>>,
+    /// Last status handed to the trader for each order
+    last_status: Mutex<HashMap<String, String>>,
+}
+
+impl ChaosExchange {
+    fn new() -> Self {
+        // USDT → BTC → ETH → USDT is ~1.7% profitable after fees
+        let inner = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_market(
+                "ETHBTC",
+                MockMarket::new("ETH", "BTC", 0.04999, 0.05).with_base_decimals(5),
+            )
+            .with_market(
+                "ETHUSDT",
+                MockMarket::new("ETH", "USDT", 2_550.0, 2_551.0).with_base_decimals(5),
+            )
+            .with_balance("USDT", START_BALANCE);
+        Self {
+            inner,
+            order_faults: HashMap::new(),
+            wallet_failures: AtomicUsize::new(0),
+            market_data_down: false,
+            placed: AtomicUsize::new(0),
+            status_overrides: Mutex::new(HashMap::new()),
+            last_status: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn fault_on_order(mut self, placement: usize, fault: OrderFault) -> Self {
+        self.order_faults.insert(placement, fault);
+        self
+    }
+
+    fn fail_wallet(self, calls: usize) -> Self {
+        self.wallet_failures.store(calls, Ordering::SeqCst);
+        self
+    }
+
+    fn market_data_down(mut self) -> Self {
+        self.market_data_down = true;
+        self
+    }
+
+    /// Orders whose last reported status was not final
+    fn unsettled_orders(&self) -> Vec<String> {
+        self.last_status
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, status)| {
+                !OrderState::from_status(status).is_some_and(OrderState::is_terminal)
+            })
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ExchangeApi for ChaosExchange {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn max_stream_symbols(&self) -> usize {
+        self.inner.max_stream_symbols()
+    }
+
+    fn ticker_stream(
+        &self,
+        conn_id: usize,
+        symbols: Vec<String>,
+        tx: mpsc::Sender<TickerInfo>,
+        taps: &StreamTaps,
+    ) -> StreamTask {
+        self.inner.ticker_stream(conn_id, symbols, tx, taps)
+    }
+
+    async fn check_connection(&self) -> Result<f64> {
+        self.inner.check_connection().await
+    }
+
+    async fn get_wallet_balance(&self, account_type: Option<&str>) -> Result<WalletBalanceResult> {
+        let failing = self
+            .wallet_failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| match left {
+                0 => None,
+                usize::MAX => Some(usize::MAX),
+                n => Some(n - 1),
+            })
+            .is_ok();
+        if failing {
+            return Err(BybitError::Http {
+                status: 503,
+                body: "Service Unavailable".to_string(),
+            }
+            .into());
+        }
+        self.inner.get_wallet_balance(account_type).await
+    }
+
+    async fn get_all_spot_instruments(&self) -> Result<Vec<InstrumentInfo>> {
+        self.inner.get_all_spot_instruments().await
+    }
+
+    async fn get_tickers(&self, category: &str) -> Result<TickersResult> {
+        if self.market_data_down {
+            return Err(anyhow!("Failed to send request: connection reset"));
+        }
+        self.inner.get_tickers(category).await
+    }
+
+    async fn get_ticker(&self, category: &str, symbol: &str) -> Result<TickersResult> {
+        if self.market_data_down {
+            return Err(anyhow!("Failed to send request: connection reset"));
+        }
+        self.inner.get_ticker(category, symbol).await
+    }
+
+    async fn place_order(&self, mut order_request: PlaceOrderRequest) -> Result<PlaceOrderResult> {
+        let placement = self.placed.fetch_add(1, Ordering::SeqCst) + 1;
+        let fault = self.order_faults.get(&placement).cloned();
+
+        let status = match fault {
+            Some(OrderFault::Reject(error)) => return Err(error.into()),
+            Some(OrderFault::PartialFill(fraction)) => {
+                let decimals = order_request
+                    .qty
+                    .split_once('.')
+                    .map_or(0, |(_, d)| d.len());
+                let filled = order_request.qty.parse::<f64>()? * fraction;
+                order_request.qty = format!("{filled:.decimals$}");
+                Some(("PartiallyFilledCanceled", 0))
+            }
+            Some(OrderFault::DelayedFill(polls)) => Some(("New", polls)),
+            None => None,
+        };
+
+        let result = self.inner.place_order(order_request).await?;
+        if let Some(status) = status {
+            self.status_overrides
+                .lock()
+                .unwrap()
+                .insert(result.order_id.clone(), status);
+        }
+        Ok(result)
+    }
+
+    async fn get_order_by_link_id(
+        &self,
+        category: &str,
+        order_link_id: &str,
+        symbol: &str,
+    ) -> Result<Option<OrderInfo>> {
+        self.inner
+            .get_order_by_link_id(category, order_link_id, symbol)
+            .await
+    }
+
+    async fn get_order(&self, category: &str, order_id: &str, symbol: &str) -> Result<OrderInfo> {
+        let mut order = self.inner.get_order(category, order_id, symbol).await?;
+        if let Some((status, polls_left)) = self.status_overrides.lock().unwrap().get_mut(order_id)
+        {
+            if *status == "New" && *polls_left == 0 {
+                // The delayed fill has arrived
+            } else {
+                if *status == "New" {
+                    *polls_left -= 1;
+                    order.cum_exec_qty = "0".to_string();
+                    order.cum_exec_value = "0".to_string();
+                    order.cum_exec_fee = "0".to_string();
+                    order.avg_price = String::new();
+                }
+                order.order_status = status.to_string();
+            }
+        }
+        self.last_status
+            .lock()
+            .unwrap()
+            .insert(order_id.to_string(), order.order_status.clone());
+        Ok(order)
+    }
+
+    async fn cancel_order(&self, category: &str, order_id: &str, symbol: &str) -> Result<()> {
+        self.inner.cancel_order(category, order_id, symbol).await
+    }
+
+    async fn cancel_all_orders(
+        &self,
+        category: &str,
+        symbol: &str,
+    ) -> Result<Vec<PlaceOrderResult>> {
+        self.inner.cancel_all_orders(category, symbol).await
+    }
+
+    async fn amend_order(&self, request: AmendOrderRequest) -> Result<PlaceOrderResult> {
+        self.inner.amend_order(request).await
+    }
+
+    async fn get_open_orders(&self, category: &str) -> Result<Vec<OrderInfo>> {
+        self.inner.get_open_orders(category).await
+    }
+
+    async fn get_executions(&self, category: &str, order_id: &str) -> Result<Vec<ExecutionRecord>> {
+        self.inner.get_executions(category, order_id).await
+    }
+
+    async fn get_executions_since(
+        &self,
+        category: &str,
+        start: DateTime<Utc>,
+    ) -> Result<Vec<ExecutionRecord>> {
+        self.inner.get_executions_since(category, start).await
+    }
+
+    async fn get_system_status(&self) -> Result<Vec<MaintenanceInfo>> {
+        self.inner.get_system_status().await
+    }
+
+    async fn get_fee_rates(&self, category: &str) -> Result<Vec<FeeRateInfo>> {
+        self.inner.get_fee_rates(category).await
+    }
+
+    async fn get_deposits_since(&self, start: DateTime<Utc>) -> Result<Vec<DepositRecord>> {
+        self.inner.get_deposits_since(start).await
+    }
+
+    async fn get_withdrawals_since(&self, start: DateTime<Utc>) -> Result<Vec<WithdrawalRecord>> {
+        self.inner.get_withdrawals_since(start).await
+    }
+}
+
+/// Safe end states of an execution attempt
+#[derive(Debug, PartialEq)]
+enum SafeState {
+    /// All legs done; anything left outside USDT is reported as dust
+    Completed,
+    /// Failed, but holdings were converted back to USDT
+    RolledBack,
+    /// Failed with coins left mid-path, and the journal records the failure
+    JournaledForRecovery,
+}
+
+/// USD price of every coin with a USDT pair, at mid prices
+async fn usd_prices(exchange: &MockExchange) -> HashMap<String, f64> {
+    let tickers = exchange.get_tickers("spot").await.unwrap();
+    let mut prices: HashMap<String, f64> = tickers
+        .list
+        .iter()
+        .filter_map(|ticker| {
+            let coin = ticker.symbol.strip_suffix("USDT")?;
+            let bid: f64 = ticker.bid1_price.as_ref()?.parse().ok()?;
+            let ask: f64 = ticker.ask1_price.as_ref()?.parse().ok()?;
+            Some((coin.to_string(), (bid + ask) / 2.0))
+        })
+        .collect();
+    prices.insert("USDT".to_string(), 1.0);
+    prices
+}
+
+/// USD value of everything held outside USDT, at mid prices
+async fn value_outside_usdt(exchange: &MockExchange) -> f64 {
+    let prices = usd_prices(exchange).await;
+    let wallet = exchange.get_wallet_balance(None).await.unwrap();
+    wallet.list[0]
+        .coin
+        .iter()
+        .filter(|c| c.coin != "USDT")
+        .map(|c| {
+            let amount: f64 = c.wallet_balance.as_ref().unwrap().parse().unwrap();
+            amount * prices[&c.coin]
+        })
+        .sum()
+}
+
+/// Execute the triangle against the faulty exchange and classify where it ended up
+async fn run_scenario(exchange: ChaosExchange) -> SafeState {
+    let precision = PrecisionManager::from_instruments(
+        exchange.inner.get_all_spot_instruments().await.unwrap(),
+    );
+    let opportunity = ArbitrageOpportunity::test().with_profit_pct(1.7);
+    let prices = usd_prices(&exchange.inner).await;
+    let mut trader = ArbitrageTrader::new(exchange, false, precision);
+    // The main loop prices the path's coins from its live pairs
+    trader.set_usd_prices(prices);
+    let execution = trader.execute_arbitrage(&opportunity, TRADE_AMOUNT).await;
+    let exchange = trader.client();
+
+    let unsettled = exchange.unsettled_orders();
+    assert!(unsettled.is_empty(), "orders left open: {unsettled:?}");
+
+    let stranded_usd = value_outside_usdt(&exchange.inner).await;
+    match &execution {
+        Ok(result) if result.success => {
+            // Leftovers must be accounted for, not silently lost
+            assert!(
+                stranded_usd <= result.dust_value_usd + 0.01 * TRADE_AMOUNT,
+                "${stranded_usd:.4} held outside USDT but only ${:.4} reported as dust",
+                result.dust_value_usd
+            );
+            SafeState::Completed
+        }
+        // Rollback sells 99% of each holding, so up to ~1% per undone leg stays behind
+        _ if stranded_usd < 0.025 * TRADE_AMOUNT => SafeState::RolledBack,
+        _ => {
+            assert_journaled(&opportunity, &execution);
+            SafeState::JournaledForRecovery
+        }
+    }
+}
+
+/// The failure must reach the journal with its cause
+fn assert_journaled(
+    opportunity: &ArbitrageOpportunity,
+    execution: &Result<ArbitrageExecutionResult>,
+) {
+    let path = std::env::temp_dir().join(format!(
+        "chaos_journal_{}_{}.jsonl",
+        std::process::id(),
+        uuid::Uuid::new_v4().simple()
+    ));
+    let journal = Journal::open(&path).unwrap();
+    journal.record(JournalEvent::live_trade(
+        opportunity,
+        TRADE_AMOUNT,
+        execution,
+    ));
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    let record: JournalRecord = serde_json::from_str(contents.trim()).unwrap();
+    match record.event {
+        JournalEvent::LiveTrade { success, error, .. } => {
+            assert!(!success);
+            assert!(error.is_some_and(|e| !e.is_empty()));
+        }
+        other => panic!("unexpected journal event {other:?}"),
+    }
+}
+
+fn risk_reject() -> BybitError {
+    BybitError::from_response(170130, "Order rejected by risk control")
+}
+
+#[tokio::test(start_paused = true)]
+async fn chaos_without_faults_completes() {
+    assert_eq!(
+        run_scenario(ChaosExchange::new()).await,
+        SafeState::Completed
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn chaos_rejected_legs_roll_back() {
+    for leg in 1..=3 {
+        let exchange = ChaosExchange::new().fault_on_order(leg, OrderFault::Reject(risk_reject()));
+        assert_eq!(
+            run_scenario(exchange).await,
+            SafeState::RolledBack,
+            "reject on leg {leg}"
+        );
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn chaos_partial_fills_complete_with_dust() {
+    for leg in 1..=3 {
+        let exchange = ChaosExchange::new().fault_on_order(leg, OrderFault::PartialFill(0.5));
+        assert_eq!(
+            run_scenario(exchange).await,
+            SafeState::Completed,
+            "partial fill on leg {leg}"
+        );
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn chaos_delayed_fills_complete() {
+    let exchange = ChaosExchange::new()
+        .fault_on_order(1, OrderFault::DelayedFill(3))
+        .fault_on_order(2, OrderFault::DelayedFill(1))
+        .fault_on_order(3, OrderFault::DelayedFill(5));
+    assert_eq!(run_scenario(exchange).await, SafeState::Completed);
+}
+
+#[tokio::test(start_paused = true)]
+async fn chaos_balance_api_failures() {
+    // A short outage is ridden out by the settlement wait
+    let exchange = ChaosExchange::new().fail_wallet(3);
+    assert_eq!(run_scenario(exchange).await, SafeState::Completed);
+
+    // A lasting outage leaves coins mid-path, which must be journaled
+    let exchange = ChaosExchange::new().fail_wallet(usize::MAX);
+    assert_ne!(run_scenario(exchange).await, SafeState::Completed);
+}
+
+#[tokio::test(start_paused = true)]
+async fn chaos_market_data_disconnect_completes() {
+    let exchange = ChaosExchange::new().market_data_down();
+    assert_eq!(run_scenario(exchange).await, SafeState::Completed);
+}
@@ -0,0 +1,536 @@
+use crate::storage::GroupBy;
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Run the arbitrage bot (default when no subcommand is given)
+    Run,
+    /// Run environment diagnostics and exit
+    Doctor,
+    /// Interactively create the configuration file
+    Setup,
+    /// Print the effective configuration with the source of each value
+    ConfigShow { env_file: PathBuf },
+    /// Compare the configuration resolved from two env files
+    ConfigDiff { left: PathBuf, right: PathBuf },
+    /// Print the status of a running instance via its control API
+    Status { port: Option<u16> },
+    /// Price and execute a single user-specified triangle
+    TradePath {
+        path: Vec<String>,
+        amount: f64,
+        assume_yes: bool,
+    },
+    /// Rebuild the bot's state from an event log
+    Replay { path: PathBuf, until: Option<u64> },
+    /// Cancel the open orders this bot left behind, and no one else's
+    CancelOrders,
+    /// Zip the latest session's results for sharing
+    Export { output: Option<PathBuf> },
+    /// Replay recorded market data through the engine with simulated execution
+    Backtest {
+        path: PathBuf,
+        latency_ms: u64,
+        slippage_pct: f64,
+    },
+    /// Print realized PnL from the trade database
+    History { by: GroupBy },
+    /// Print usage information
+    Help,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cli {
+    pub command: Command,
+    /// Run non-interactively under a service manager
+    pub daemon: bool,
+    pub pid_file: Option<PathBuf>,
+    pub log_file: Option<PathBuf>,
+    /// Skip the startup latency and wallet checks (offline development, backtests)
+    pub skip_connectivity_check: bool,
+    /// Start even if another instance appears to be running
+    pub force: bool,
+    /// Configuration overrides from --set KEY=VALUE, highest precedence
+    pub overrides: Vec<(String, String)>,
+}
+
+impl Cli {
+    /// Parse the process arguments
+    pub fn parse() -> Result<Self> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    /// Parse a list of arguments (without the program name)
+    pub fn parse_from<I, S>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut args = args.into_iter().map(Into::into);
+        let mut command = Command::Run;
+        let mut daemon = false;
+        let mut pid_file = None;
+        let mut log_file = None;
+        let mut skip_connectivity_check = false;
+        let mut force = false;
+        let mut overrides = Vec::new();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--daemon" => daemon = true,
+                "--pid-file" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow!("--pid-file requires a path"))?;
+                    pid_file = Some(PathBuf::from(value));
+                }
+                "--log-file" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow!("--log-file requires a path"))?;
+                    log_file = Some(PathBuf::from(value));
+                }
+                "--skip-connectivity-check" => skip_connectivity_check = true,
+                "--force" => force = true,
+                "run" => command = Command::Run,
+                "doctor" => command = Command::Doctor,
+                "setup" => command = Command::Setup,
+                "trade-path" => command = parse_trade_path(&mut args)?,
+                "status" => command = Command::Status { port: None },
+                "config" => command = parse_config(&mut args)?,
+                "replay" => command = parse_replay(&mut args)?,
+                "backtest" => command = parse_backtest(&mut args)?,
+                "cancel-orders" => command = Command::CancelOrders,
+                "export" => command = Command::Export { output: None },
+                "history" => command = Command::History { by: GroupBy::Path },
+                "--by" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow!("--by requires day, symbol or path"))?;
+                    match &mut command {
+                        Command::History { by } => *by = GroupBy::parse(&value)?,
+                        _ => return Err(anyhow!("--by is only valid for the history command")),
+                    }
+                }
+                "--output" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow!("--output requires a path"))?;
+                    match &mut command {
+                        Command::Export { output } => *output = Some(PathBuf::from(value)),
+                        _ => return Err(anyhow!("--output is only valid for the export command")),
+                    }
+                }
+                "--set" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow!("--set requires KEY=VALUE"))?;
+                    let (key, val) = value
+                        .split_once('=')
+                        .ok_or_else(|| anyhow!("--set expects KEY=VALUE (got {value})"))?;
+                    let key = key.trim().to_uppercase();
+                    if !crate::config::CONFIG_KEYS.iter().any(|(k, _)| *k == key) {
+                        return Err(anyhow!("Unknown configuration key in --set: {key}"));
+                    }
+                    overrides.push((key, val.trim().to_string()));
+                }
+                "--port" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow!("--port requires a value"))?;
+                    let port = value
+                        .parse()
+                        .map_err(|_| anyhow!("Invalid --port: {value}"))?;
+                    match &mut command {
+                        Command::Status { port: p } => *p = Some(port),
+                        _ => return Err(anyhow!("--port is only valid for the status command")),
+                    }
+                }
+                "help" | "-h" | "--help" => command = Command::Help,
+                other => return Err(anyhow!("Unknown argument: {other} (see --help)")),
+            }
+        }
+
+        Ok(Self {
+            command,
+            daemon,
+            pid_file,
+            log_file,
+            skip_connectivity_check,
+            force,
+            overrides,
+        })
+    }
+}
+
+/// Parse `config show [--env-file PATH]` and `config diff A B`
+fn parse_config(args: &mut impl Iterator<Item = String>) -> Result<Command> {
+    match args.next().as_deref() {
+        Some("show") => {
+            let mut env_file = PathBuf::from(".env");
+            if let Some(flag) = args.next() {
+                if flag != "--env-file" {
+                    return Err(anyhow!("Unknown config show argument: {flag}"));
+                }
+                env_file = PathBuf::from(
+                    args.next()
+                        .ok_or_else(|| anyhow!("--env-file requires a path"))?,
+                );
+            }
+            Ok(Command::ConfigShow { env_file })
+        }
+        Some("diff") => {
+            let left = args
+                .next()
+                .ok_or_else(|| anyhow!("config diff requires two env files"))?;
+            let right = args
+                .next()
+                .ok_or_else(|| anyhow!("config diff requires two env files"))?;
+            Ok(Command::ConfigDiff {
+                left: PathBuf::from(left),
+                right: PathBuf::from(right),
+            })
+        }
+        Some(other) => Err(anyhow!("Unknown config subcommand: {other} (show, diff)")),
+        None => Err(anyhow!("config requires a subcommand: show or diff")),
+    }
+}
+
+/// Parse `replay PATH [--until SEQ]`
+fn parse_replay(args: &mut impl Iterator<Item = String>) -> Result<Command> {
+    let path = args
+        .next()
+        .ok_or_else(|| anyhow!("replay requires an event log path"))?;
+    let until = match args.next().as_deref() {
+        None => None,
+        Some("--until") => {
+            let value = args
+                .next()
+                .ok_or_else(|| anyhow!("--until requires a sequence number"))?;
+            Some(
+                value
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid --until: {value}"))?,
+            )
+        }
+        Some(other) => return Err(anyhow!("Unknown replay argument: {other}")),
+    };
+    Ok(Command::Replay {
+        path: PathBuf::from(path),
+        until,
+    })
+}
+
+/// Parse `backtest PATH [--latency-ms N] [--slippage-pct X]`
+fn parse_backtest(args: &mut impl Iterator<Item = String>) -> Result<Command> {
+    let path = args
+        .next()
+        .ok_or_else(|| anyhow!("backtest requires a market data recording"))?;
+    let mut latency_ms = 100;
+    let mut slippage_pct = 0.0;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--latency-ms" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| anyhow!("--latency-ms requires a value"))?;
+                latency_ms = value
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid --latency-ms: {value}"))?;
+            }
+            "--slippage-pct" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| anyhow!("--slippage-pct requires a value"))?;
+                slippage_pct = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|v| *v >= 0.0)
+                    .ok_or_else(|| anyhow!("Invalid --slippage-pct: {value}"))?;
+            }
+            other => return Err(anyhow!("Unknown backtest argument: {other}")),
+        }
+    }
+
+    Ok(Command::Backtest {
+        path: PathBuf::from(path),
+        latency_ms,
+        slippage_pct,
+    })
+}
+
+/// Parse the flags of the trade-path subcommand
+fn parse_trade_path(args: &mut impl Iterator<Item = String>) -> Result<Command> {
+    let mut path = None;
+    let mut amount = None;
+    let mut assume_yes = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--path" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| anyhow!("--path requires a value, e.g. USDT,BTC,ETH"))?;
+                path = Some(parse_path(&value)?);
+            }
+            "--amount" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| anyhow!("--amount requires a value"))?;
+                let parsed: f64 = value
+                    .parse()
+                    .map_err(|_| anyhow!("Invalid --amount: {value}"))?;
+                if parsed <= 0.0 {
+                    return Err(anyhow!("--amount must be positive"));
+                }
+                amount = Some(parsed);
+            }
+            "-y" | "--yes" => assume_yes = true,
+            other => return Err(anyhow!("Unknown trade-path argument: {other}")),
+        }
+    }
+
+    Ok(Command::TradePath {
+        path: path.ok_or_else(|| anyhow!("trade-path requires --path"))?,
+        amount: amount.ok_or_else(|| anyhow!("trade-path requires --amount"))?,
+        assume_yes,
+    })
+}
+
+/// Parse "USDT,BTC,ETH" into a closed path [USDT, BTC, ETH, USDT]
+fn parse_path(value: &str) -> Result<Vec<String>> {
+    let mut coins: Vec<String> = value
+        .split(',')
+        .map(|c| c.trim().to_uppercase())
+        .filter(|c| !c.is_empty())
+        .collect();
+
+    if coins.len() == 4 && coins[0] == coins[3] {
+        coins.pop();
+    }
+    if coins.len() != 3 {
+        return Err(anyhow!(
+            "--path must list exactly three coins, e.g. USDT,BTC,ETH (got {value})"
+        ));
+    }
+    if coins[0] == coins[1] || coins[1] == coins[2] || coins[0] == coins[2] {
+        return Err(anyhow!("--path coins must be distinct (got {value})"));
+    }
+
+    coins.push(coins[0].clone());
+    Ok(coins)
+}
+
+/// Print CLI usage
+pub fn print_usage() {
+    println!(
+        "Bybit Triangular Arbitrage Bot v{}
+
+USAGE:
+    bybit-arbitrage-bot [COMMAND] [OPTIONS]
+
+COMMANDS:
+    run       Start scanning and trading (default)
+    setup     Interactively create the .env configuration
+    doctor    Check API keys, IP whitelist, clock drift, connectivity and config
+    config show [--env-file PATH]
+              Print the effective configuration and where each value came from
+    config diff A B
+              Compare the configuration resolved from two env files (profiles)
+    status [--port N]
+              Show mode, uptime, balances, last trade and health of a running bot
+    trade-path --path A,B,C --amount N [--yes]
+              Price the triangle A → B → C → A with live data and execute it once
+    replay PATH [--until SEQ]
+              Rebuild balances, quotes, orders and config from an event log
+    backtest PATH [--latency-ms N] [--slippage-pct X]
+              Trade a MARKET_DATA_PATH recording at the configured threshold, print PnL
+    cancel-orders
+              Cancel open orders tagged with ORDER_LINK_PREFIX (keeps parked positions)
+    export [--output PATH]
+              Zip the latest session's journal, summary, redacted config and state files
+    history [--by day|symbol|path]
+              Realized PnL from TRADE_DB_PATH, by path unless grouped otherwise
+
+OPTIONS:
+    --set KEY=VALUE    Override a configuration value (highest precedence)
+    --daemon           Run under a service manager: no prompts, PID file, log to file
+    --pid-file PATH    PID file location (default with --daemon: bybit-arbitrage-bot.pid)
+    --log-file PATH    Append logs to PATH instead of stdout
+                       (default with --daemon: logs/bybit-arbitrage-bot.log)
+    --skip-connectivity-check
+                       Start without the API latency and wallet checks (offline work)
+    --force            Start even if another instance appears to be running
+    help      Print this message",
+        env!("CARGO_PKG_VERSION")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_command_is_run() {
+        let cli = Cli::parse_from(Vec::<String>::new()).unwrap();
+        assert_eq!(cli.command, Command::Run);
+    }
+
+    #[test]
+    fn test_parse_doctor() {
+        let cli = Cli::parse_from(["doctor"]).unwrap();
+        assert_eq!(cli.command, Command::Doctor);
+
+        let cli = Cli::parse_from(["setup"]).unwrap();
+        assert_eq!(cli.command, Command::Setup);
+    }
+
+    #[test]
+    fn test_parse_trade_path() {
+        let cli =
+            Cli::parse_from(["trade-path", "--path", "usdt,btc,eth", "--amount", "25"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::TradePath {
+                path: vec!["USDT".into(), "BTC".into(), "ETH".into(), "USDT".into()],
+                amount: 25.0,
+                assume_yes: false,
+            }
+        );
+
+        assert!(Cli::parse_from(["trade-path", "--path", "USDT,BTC", "--amount", "25"]).is_err());
+        assert!(Cli::parse_from(["trade-path", "--path", "USDT,BTC,ETH"]).is_err());
+        assert!(
+            Cli::parse_from(["trade-path", "--path", "USDT,BTC,ETH", "--amount", "-1"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_replay() {
+        let cli = Cli::parse_from(["replay", "events.jsonl", "--until", "42"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::Replay {
+                path: PathBuf::from("events.jsonl"),
+                until: Some(42),
+            }
+        );
+
+        assert!(Cli::parse_from(["replay"]).is_err());
+        assert!(Cli::parse_from(["replay", "events.jsonl", "--until", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let cli = Cli::parse_from(["status", "--port", "9191"]).unwrap();
+        assert_eq!(cli.command, Command::Status { port: Some(9191) });
+
+        assert!(Cli::parse_from(["--port", "9191"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_export() {
+        let cli = Cli::parse_from(["export"]).unwrap();
+        assert_eq!(cli.command, Command::Export { output: None });
+
+        let cli = Cli::parse_from(["export", "--output", "run.zip"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::Export {
+                output: Some(PathBuf::from("run.zip"))
+            }
+        );
+        assert!(Cli::parse_from(["--output", "run.zip"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_backtest() {
+        let cli = Cli::parse_from(["backtest", "market.jsonl"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::Backtest {
+                path: PathBuf::from("market.jsonl"),
+                latency_ms: 100,
+                slippage_pct: 0.0,
+            }
+        );
+
+        let cli = Cli::parse_from([
+            "backtest",
+            "market.jsonl",
+            "--latency-ms",
+            "250",
+            "--slippage-pct",
+            "0.02",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.command,
+            Command::Backtest {
+                path: PathBuf::from("market.jsonl"),
+                latency_ms: 250,
+                slippage_pct: 0.02,
+            }
+        );
+        assert!(Cli::parse_from(["backtest"]).is_err());
+        assert!(Cli::parse_from(["backtest", "m.jsonl", "--slippage-pct", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_history() {
+        let cli = Cli::parse_from(["history"]).unwrap();
+        assert_eq!(cli.command, Command::History { by: GroupBy::Path });
+
+        let cli = Cli::parse_from(["history", "--by", "day"]).unwrap();
+        assert_eq!(cli.command, Command::History { by: GroupBy::Day });
+        assert!(Cli::parse_from(["history", "--by", "week"]).is_err());
+        assert!(Cli::parse_from(["--by", "day"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_config_commands() {
+        let cli = Cli::parse_from(["config", "show"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::ConfigShow {
+                env_file: PathBuf::from(".env")
+            }
+        );
+
+        let cli = Cli::parse_from(["config", "diff", ".env", ".env.prod"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::ConfigDiff {
+                left: PathBuf::from(".env"),
+                right: PathBuf::from(".env.prod")
+            }
+        );
+
+        let cli = Cli::parse_from(["--set", "order_size=25"]).unwrap();
+        assert_eq!(cli.overrides, vec![("ORDER_SIZE".into(), "25".into())]);
+        assert!(Cli::parse_from(["--set", "ORDER_SIZ=25"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_daemon_flags() {
+        let cli = Cli::parse_from(["--daemon", "--pid-file", "/run/bot.pid"]).unwrap();
+        assert_eq!(cli.command, Command::Run);
+        assert!(cli.daemon);
+        assert_eq!(cli.pid_file, Some(PathBuf::from("/run/bot.pid")));
+        assert_eq!(cli.log_file, None);
+        assert!(!cli.skip_connectivity_check);
+
+        let cli = Cli::parse_from(["--skip-connectivity-check", "--force"]).unwrap();
+        assert!(cli.skip_connectivity_check);
+        assert!(cli.force);
+
+        assert!(Cli::parse_from(["--log-file"]).is_err());
+    }
+
+    #[test]
+    fn test_unknown_argument_is_rejected() {
+        assert!(Cli::parse_from(["--bogus"]).is_err());
+    }
+}
@@ -1,26 +1,117 @@
 use crate::config::Config;
+use crate::credentials::{Credentials, KeyRing};
+use crate::endpoints::Endpoints;
+use crate::errors::BybitError;
+use crate::latency::RequestLatencies;
 use crate::models::*;
+use crate::network::http_client;
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use reqwest::{
     header::{HeaderMap, HeaderValue},
     Client,
 };
-use std::time::{SystemTime, UNIX_EPOCH};
-use tracing::{debug, error, info};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tracing::{debug, error, info, warn};
+
+/// Receive window (ms) sent with every signed request
+pub const RECV_WINDOW_MS: u64 = 5000;
+/// Clock offsets at least this large are logged as a warning
+const CLOCK_OFFSET_WARN_MS: i64 = 1000;
+
+/// Local time in milliseconds since the Unix epoch
+fn local_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Bybit time in milliseconds: the local clock corrected by a synced offset
+pub fn synced_millis(offset_ms: &AtomicI64) -> u64 {
+    (local_millis() as i64 + offset_ms.load(Ordering::SeqCst)) as u64
+}
+
+/// Parse a response envelope, turning a non-zero retCode into a `BybitError`
+fn parse_api_response<T>(buffer: &mut [u8]) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let api_response: ApiResponse<T> =
+        simd_json::from_slice(buffer).context("Failed to parse API response structure")?;
+
+    if !api_response.is_success() {
+        return Err(BybitError::from_response(api_response.ret_code, api_response.ret_msg).into());
+    }
+    api_response
+        .into_result()
+        .map_err(|e| anyhow::anyhow!("API error: {}", e))
+}
+
+/// Outcome of each order of a batch response: its ids, or the error code it was rejected
+/// with in `retExtInfo`
+fn batch_outcomes(response_text: &str) -> Result<Vec<Result<PlaceOrderResult>>> {
+    let response: ApiResponse<BatchOrderResult> = serde_json::from_str(response_text)
+        .with_context(|| format!("Failed to parse batch response: {response_text}"))?;
+    let codes: Vec<(i32, String)> = response
+        .ret_ext_info
+        .as_ref()
+        .and_then(|info| info.get("list")?.as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .map(|entry| {
+            let code = entry.get("code").and_then(|c| c.as_i64()).unwrap_or(0) as i32;
+            let msg = entry.get("msg").and_then(|m| m.as_str()).unwrap_or("");
+            (code, msg.to_string())
+        })
+        .collect();
+    if !response.is_success() {
+        return Err(anyhow::Error::new(BybitError::from_response(
+            response.ret_code,
+            response.ret_msg,
+        ))
+        .context("Batch order placement failed"));
+    }
+    let batch = response
+        .into_result()
+        .map_err(|e| anyhow::anyhow!("Batch order placement failed - {e}"))?;
+    Ok(batch
+        .list
+        .into_iter()
+        .enumerate()
+        .map(|(i, order)| match codes.get(i) {
+            Some((code, msg)) if *code != 0 => Err(anyhow::Error::new(BybitError::from_response(
+                *code,
+                msg.as_str(),
+            ))
+            .context("Order placement failed")),
+            _ if order.order_id.is_empty() => Err(anyhow::anyhow!(
+                "Order placement failed - API Error: no order id for {}",
+                order.order_link_id
+            )),
+            _ => Ok(order),
+        })
+        .collect())
+}
 
 #[derive(Debug, Clone)]
 pub struct BybitClient {
     client: Client,
-    config: Config,
+    endpoints: Arc<Endpoints>,
+    latencies: Arc<RequestLatencies>,
+    keys: Arc<KeyRing>,
+    /// Milliseconds Bybit's clock is ahead of the local one, added to signed timestamps
+    time_offset_ms: Arc<AtomicI64>,
 }
 
 impl BybitClient {
     pub fn new(config: Config) -> Result<Self> {
         let mut headers = HeaderMap::new();
         headers.insert("Content-Type", HeaderValue::from_static("application/json"));
-        headers.insert("X-BAPI-API-KEY", HeaderValue::from_str(&config.api_key)?);
 
-        let client = Client::builder()
+        let client = http_client(&config)?
             .timeout(std::time::Duration::from_secs(config.request_timeout_secs))
             .tcp_nodelay(true)
             .tcp_keepalive(std::time::Duration::from_secs(60)) // Keep connections alive
@@ -35,21 +126,161 @@ impl BybitClient {
             .default_headers(headers)
             .build()?;
 
-        Ok(BybitClient { client, config })
+        let endpoints = Endpoints::new(config.endpoint_sets());
+        Ok(BybitClient {
+            client,
+            endpoints,
+            latencies: RequestLatencies::new(),
+            keys: KeyRing::from_config(&config),
+            time_offset_ms: Arc::new(AtomicI64::new(0)),
+        })
+    }
+
+    /// The endpoint sets and which one requests currently go to
+    pub fn endpoints(&self) -> &Arc<Endpoints> {
+        &self.endpoints
+    }
+
+    /// The API keys requests are signed with
+    pub fn keys(&self) -> &Arc<KeyRing> {
+        &self.keys
+    }
+
+    /// Response times per API path
+    pub fn latencies(&self) -> &Arc<RequestLatencies> {
+        &self.latencies
+    }
+
+    fn base_url(&self) -> &str {
+        &self.endpoints.active().rest_url
+    }
+
+    /// Round trip in milliseconds of a server time request to `rest_url`, which need not
+    /// be the active endpoint
+    pub async fn latency_to(&self, rest_url: &str) -> Result<f64> {
+        let start = std::time::Instant::now();
+        self.client
+            .get(format!("{rest_url}/v5/market/time"))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(start.elapsed().as_secs_f64() * 1000.0)
+    }
+
+    /// Send `count` server time requests to the active endpoint at once, so the pool holds
+    /// that many open connections for the next orders
+    pub async fn warm_connections(&self, count: usize) -> Result<()> {
+        let url = format!("{}/v5/market/time", self.base_url());
+        let requests = (0..count).map(|_| async {
+            let start = Instant::now();
+            let response = self.client.get(&url).send().await;
+            self.latencies.record(&url, start.elapsed());
+            self.record_outcome(&response);
+            // Read the body so the connection goes back to the pool
+            response?.error_for_status()?.bytes().await?;
+            Ok::<_, anyhow::Error>(())
+        });
+        futures_util::future::try_join_all(requests).await?;
+        Ok(())
+    }
+
+    /// Count transport failures and server errors towards failing over; API errors don't
+    fn record_outcome(&self, response: &reqwest::Result<reqwest::Response>) {
+        match response {
+            Ok(r) if !r.status().is_server_error() => self.endpoints.record_success(),
+            _ => self.endpoints.record_failure(),
+        }
     }
 
     /// Check connection to Bybit API and return latency in milliseconds
     pub async fn check_connection(&self) -> Result<f64> {
         let start = std::time::Instant::now();
-        let url = format!("{}/v5/market/time", self.config.base_url);
+        let url = format!("{}/v5/market/time", self.base_url());
         let _response: serde_json::Value = self.public_request(&url, "").await?;
         let duration = start.elapsed();
         Ok(duration.as_secs_f64() * 1000.0)
     }
 
+    /// Fetch the exchange server time
+    pub async fn get_server_time(&self) -> Result<ServerTimeResult> {
+        let url = format!("{}/v5/market/time", self.base_url());
+        self.public_request::<ServerTimeResult>(&url, "").await
+    }
+
+    /// Measure how far Bybit's clock is from the local one and sign with its time from now
+    /// on. Returns the new offset in milliseconds.
+    pub async fn sync_time(&self) -> Result<i64> {
+        let before = local_millis();
+        let server_time = self.get_server_time().await?;
+        let after = local_millis();
+        let server_ms = server_time
+            .as_millis()
+            .context("Server returned an unparseable time")?;
+
+        // Compare against the midpoint of the request to cancel out network latency
+        let offset = server_ms as i64 - ((before + after) / 2) as i64;
+        let previous = self.time_offset_ms.swap(offset, Ordering::SeqCst);
+        if offset.abs() >= CLOCK_OFFSET_WARN_MS {
+            warn!(
+                "🕒 Local clock is {:+}ms off Bybit's; signing with Bybit's time",
+                -offset
+            );
+        } else if offset != previous {
+            debug!("🕒 Clock offset to Bybit: {offset:+}ms");
+        }
+        Ok(offset)
+    }
+
+    /// Milliseconds Bybit's clock was ahead of the local one at the last sync
+    #[cfg(test)]
+    pub fn time_offset_ms(&self) -> i64 {
+        self.time_offset_ms.load(Ordering::SeqCst)
+    }
+
+    /// The synced offset itself, for signing done outside the client (the private stream)
+    pub fn time_offset(&self) -> &Arc<AtomicI64> {
+        &self.time_offset_ms
+    }
+
+    /// Resync the clock after a request was rejected for its timestamp, so the next one
+    /// is signed in time
+    async fn resync_on_timestamp_error(&self, error: &anyhow::Error) {
+        if !matches!(
+            BybitError::find(error),
+            Some(BybitError::InvalidTimestamp { .. })
+        ) {
+            return;
+        }
+        warn!("🕒 Bybit rejected a request timestamp, resyncing the clock");
+        if let Err(e) = self.sync_time().await {
+            warn!("⚠️ Clock resync failed: {e:#}");
+        }
+    }
+
+    /// Fetch the exchange's scheduled, ongoing and recent maintenance windows
+    pub async fn get_system_status(&self) -> Result<Vec<MaintenanceInfo>> {
+        let url = format!("{}/v5/system/status", self.base_url());
+        let result = self.public_request::<SystemStatusResult>(&url, "").await?;
+        Ok(result.list)
+    }
+
+    /// The account's maker and taker fee rates on every symbol of a category
+    pub async fn get_fee_rates(&self, category: &str) -> Result<Vec<crate::models::FeeRateInfo>> {
+        let url = format!("{}/v5/account/fee-rate", self.base_url());
+        let query_params = format!("category={category}");
+        let result: crate::models::FeeRateResult = self.signed_request(&url, &query_params).await?;
+        Ok(result.list)
+    }
+
+    /// Fetch information about the API key in use (permissions, IP binding)
+    pub async fn get_api_key_info(&self) -> Result<ApiKeyInfo> {
+        let url = format!("{}/v5/user/query-api", self.base_url());
+        self.signed_request::<ApiKeyInfo>(&url, "").await
+    }
+
     /// Generate HMAC SHA256 signature for Bybit API
     fn generate_signature(
-        &self,
+        credentials: &Credentials,
         timestamp: u64,
         method: &str,
         _path: &str,
@@ -61,36 +292,33 @@ impl BybitClient {
 
         type HmacSha256 = Hmac<Sha256>;
 
-        let recv_window = "5000";
+        let recv_window = RECV_WINDOW_MS;
 
         // For POST requests with body, include the body in the signature
         let param_str = if method == "POST" && !body.is_empty() {
             format!(
                 "{}{}{}{}",
-                timestamp, &self.config.api_key, recv_window, body
+                timestamp, &credentials.api_key, recv_window, body
             )
         } else if !query_params.is_empty() {
             format!(
                 "{}{}{}{}",
-                timestamp, &self.config.api_key, recv_window, query_params
+                timestamp, &credentials.api_key, recv_window, query_params
             )
         } else {
-            format!("{}{}{}", timestamp, &self.config.api_key, recv_window)
+            format!("{}{}{}", timestamp, &credentials.api_key, recv_window)
         };
 
-        let mut mac = HmacSha256::new_from_slice(self.config.api_secret.as_bytes())
+        let mut mac = HmacSha256::new_from_slice(credentials.api_secret.as_bytes())
             .map_err(|e| anyhow::anyhow!("Failed to create HMAC: {}", e))?;
 
         mac.update(param_str.as_bytes());
         Ok(hex::encode(mac.finalize().into_bytes()))
     }
 
-    /// Get current timestamp in milliseconds
-    fn get_timestamp_ms() -> u64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64
+    /// Current Bybit time in milliseconds: the local clock corrected by the last sync
+    fn get_timestamp_ms(&self) -> u64 {
+        synced_millis(&self.time_offset_ms)
     }
 
     /// Execute a signed GET request to Bybit API
@@ -98,8 +326,10 @@ impl BybitClient {
     where
         T: serde::de::DeserializeOwned,
     {
-        let timestamp = Self::get_timestamp_ms();
-        let signature = self.generate_signature(timestamp, "GET", endpoint, query_params, "")?;
+        let timestamp = self.get_timestamp_ms();
+        let keys = self.keys.signing().await;
+        let signature =
+            Self::generate_signature(&keys.active, timestamp, "GET", endpoint, query_params, "")?;
 
         let mut url = endpoint.to_string();
         if !query_params.is_empty() {
@@ -109,22 +339,29 @@ impl BybitClient {
 
         debug!("Making signed request to: {}", url);
 
+        let start = Instant::now();
         let response = self
             .client
             .get(&url)
+            .header("X-BAPI-API-KEY", &keys.active.api_key)
             .header("X-BAPI-SIGN", signature)
             .header("X-BAPI-TIMESTAMP", timestamp.to_string())
-            .header("X-BAPI-RECV-WINDOW", "5000")
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW_MS.to_string())
             .send()
-            .await
-            .context("Failed to send request")?;
+            .await;
+        self.latencies.record(endpoint, start.elapsed());
+        self.record_outcome(&response);
+        let response = response.context("Failed to send request")?;
 
         let status = response.status();
 
         if !status.is_success() {
-            let response_text = response.text().await.unwrap_or_default();
-            error!("HTTP error {}: {}", status, response_text);
-            return Err(anyhow::anyhow!("HTTP error {}: {}", status, response_text));
+            let error = BybitError::Http {
+                status: status.as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            };
+            error!("{error}");
+            return Err(error.into());
         }
 
         // Optimization: Use simd-json for faster parsing and avoid double-parsing
@@ -133,14 +370,11 @@ impl BybitClient {
             .bytes()
             .await
             .context("Failed to get response bytes")?;
-        let mut buffer = bytes.to_vec();
-
-        let api_response: ApiResponse<T> =
-            simd_json::from_slice(&mut buffer).context("Failed to parse API response structure")?;
-
-        api_response
-            .into_result()
-            .map_err(|e| anyhow::anyhow!("API error: {}", e))
+        let result = parse_api_response(&mut bytes.to_vec());
+        if let Err(e) = &result {
+            self.resync_on_timestamp_error(e).await;
+        }
+        result
     }
 
     /// Execute an unsigned GET request (for public endpoints)
@@ -156,19 +390,21 @@ impl BybitClient {
 
         debug!("Making public request to: {}", url);
 
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .context("Failed to send request")?;
+        let start = Instant::now();
+        let response = self.client.get(&url).send().await;
+        self.latencies.record(endpoint, start.elapsed());
+        self.record_outcome(&response);
+        let response = response.context("Failed to send request")?;
 
         let status = response.status();
 
         if !status.is_success() {
-            let response_text = response.text().await.unwrap_or_default();
-            error!("HTTP error {}: {}", status, response_text);
-            return Err(anyhow::anyhow!("HTTP error {}: {}", status, response_text));
+            let error = BybitError::Http {
+                status: status.as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            };
+            error!("{error}");
+            return Err(error.into());
         }
 
         // Optimization: Use simd-json
@@ -176,14 +412,7 @@ impl BybitClient {
             .bytes()
             .await
             .context("Failed to get response bytes")?;
-        let mut buffer = bytes.to_vec();
-
-        let api_response: ApiResponse<T> =
-            simd_json::from_slice(&mut buffer).context("Failed to parse API response structure")?;
-
-        api_response
-            .into_result()
-            .map_err(|e| anyhow::anyhow!("API error: {}", e))
+        parse_api_response(&mut bytes.to_vec())
     }
 
     /// Fetch account wallet balance
@@ -198,7 +427,7 @@ impl BybitClient {
 
         let result = self
             .signed_request::<WalletBalanceResult>(
-                &self.config.wallet_balance_endpoint(),
+                &self.endpoints.active().wallet_balance_endpoint(),
                 &query_params,
             )
             .await?;
@@ -211,34 +440,6 @@ impl BybitClient {
         Ok(result)
     }
 
-    /// Fetch trading instruments info
-    pub async fn get_instruments_info(
-        &self,
-        category: &str,
-        limit: Option<u32>,
-    ) -> Result<InstrumentsInfoResult> {
-        debug!("Fetching instruments info for category: {}", category);
-
-        let mut query_params = format!("category={category}");
-        if let Some(lmt) = limit {
-            query_params.push_str(&format!("&limit={lmt}"));
-        }
-
-        let result = self
-            .public_request::<InstrumentsInfoResult>(
-                &self.config.instruments_info_endpoint(),
-                &query_params,
-            )
-            .await?;
-
-        debug!(
-            "Successfully fetched {} instruments for category {}",
-            result.list.len(),
-            category
-        );
-        Ok(result)
-    }
-
     /// Fetch all spot instruments with pagination
     pub async fn get_all_spot_instruments(&self) -> Result<Vec<InstrumentInfo>> {
         debug!("Fetching all spot instruments...");
@@ -257,7 +458,7 @@ impl BybitClient {
 
             let result = self
                 .public_request::<InstrumentsInfoResult>(
-                    &self.config.instruments_info_endpoint(),
+                    &self.endpoints.active().instruments_info_endpoint(),
                     &query_params,
                 )
                 .await?;
@@ -291,7 +492,10 @@ impl BybitClient {
         let query_params = format!("category={category}");
 
         let result = self
-            .public_request::<TickersResult>(&self.config.tickers_endpoint(), &query_params)
+            .public_request::<TickersResult>(
+                &self.endpoints.active().tickers_endpoint(),
+                &query_params,
+            )
             .await?;
 
         debug!(
@@ -309,7 +513,10 @@ impl BybitClient {
         let query_params = format!("category={category}&symbol={symbol}");
 
         let result = self
-            .public_request::<TickersResult>(&self.config.tickers_endpoint(), &query_params)
+            .public_request::<TickersResult>(
+                &self.endpoints.active().tickers_endpoint(),
+                &query_params,
+            )
             .await?;
 
         Ok(result)
@@ -323,25 +530,36 @@ impl BybitClient {
         // info!("Placing {} order: {} {} @ {:?}",
         //       order_request.side, order_request.qty, order_request.symbol, order_request.price);
 
-        let endpoint = format!("{}/v5/order/create", self.config.base_url);
+        let endpoint = format!("{}/v5/order/create", self.base_url());
         let body = serde_json::to_string(&order_request)?;
-        let timestamp = Self::get_timestamp_ms();
-
-        let client = reqwest::Client::new();
-        let signature =
-            self.generate_signature(timestamp, "POST", "/v5/order/create", "", &body)?;
-
-        let response = client
+        let timestamp = self.get_timestamp_ms();
+
+        let keys = self.keys.signing().await;
+        let signature = Self::generate_signature(
+            &keys.active,
+            timestamp,
+            "POST",
+            "/v5/order/create",
+            "",
+            &body,
+        )?;
+
+        // The shared client reuses a pooled (pre-warmed) connection instead of a new handshake
+        let start = Instant::now();
+        let response = self
+            .client
             .post(&endpoint)
-            .header("X-BAPI-API-KEY", &self.config.api_key)
+            .header("X-BAPI-API-KEY", &keys.active.api_key)
             .header("X-BAPI-SIGN", signature)
             .header("X-BAPI-SIGN-TYPE", "2")
             .header("X-BAPI-TIMESTAMP", timestamp.to_string())
-            .header("X-BAPI-RECV-WINDOW", "5000")
-            .header("Content-Type", "application/json")
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW_MS.to_string())
             .body(body.clone())
             .send()
-            .await?;
+            .await;
+        self.latencies.record(&endpoint, start.elapsed());
+        self.record_outcome(&response);
+        let response = response?;
 
         let response_text = response.text().await?;
         debug!("Place order response: {}", response_text);
@@ -357,16 +575,12 @@ impl BybitClient {
             })?;
 
         if !api_response.is_success() {
+            let error = BybitError::from_response(api_response.ret_code, api_response.ret_msg);
             error!("Order placement failed. Request: {}", body);
-            error!(
-                "API Error {}: {}",
-                api_response.ret_code, api_response.ret_msg
-            );
-            return Err(anyhow::anyhow!(
-                "Order placement failed - API Error {}: {}",
-                api_response.ret_code,
-                api_response.ret_msg
-            ));
+            error!("{error}");
+            let error = anyhow::Error::new(error).context("Order placement failed");
+            self.resync_on_timestamp_error(&error).await;
+            return Err(error);
         }
 
         // Now parse the successful response as PlaceOrderResult
@@ -387,6 +601,68 @@ impl BybitClient {
         Ok(result)
     }
 
+    /// Place up to 10 orders of one category in a single request. Each order succeeds or
+    /// fails on its own, so the outcome of each is returned in request order.
+    pub async fn place_batch_orders(
+        &self,
+        category: &str,
+        requests: Vec<PlaceOrderRequest>,
+    ) -> Result<Vec<Result<PlaceOrderResult>>> {
+        let endpoint = format!("{}/v5/order/create-batch", self.base_url());
+        // The category is given once for the whole batch
+        let orders = requests
+            .iter()
+            .map(|request| {
+                let mut order = serde_json::to_value(request)?;
+                if let Some(order) = order.as_object_mut() {
+                    order.remove("category");
+                }
+                Ok(order)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let body = serde_json::to_string(&serde_json::json!({
+            "category": category,
+            "request": orders,
+        }))?;
+        let timestamp = self.get_timestamp_ms();
+
+        let keys = self.keys.signing().await;
+        let signature = Self::generate_signature(
+            &keys.active,
+            timestamp,
+            "POST",
+            "/v5/order/create-batch",
+            "",
+            &body,
+        )?;
+
+        let start = Instant::now();
+        let response = self
+            .client
+            .post(&endpoint)
+            .header("X-BAPI-API-KEY", &keys.active.api_key)
+            .header("X-BAPI-SIGN", signature)
+            .header("X-BAPI-SIGN-TYPE", "2")
+            .header("X-BAPI-TIMESTAMP", timestamp.to_string())
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW_MS.to_string())
+            .body(body.clone())
+            .send()
+            .await;
+        self.latencies.record(&endpoint, start.elapsed());
+        self.record_outcome(&response);
+        let response = response?;
+
+        let response_text = response.text().await?;
+        debug!("Batch order response: {}", response_text);
+        let results = batch_outcomes(&response_text);
+        if let Err(e) = &results {
+            error!("Batch order placement failed. Request: {}", body);
+            error!("{e:#}");
+            self.resync_on_timestamp_error(e).await;
+        }
+        results
+    }
+
     /// Get order information
     pub async fn get_order(
         &self,
@@ -398,7 +674,7 @@ impl BybitClient {
 
         let query_params = format!("category={category}&orderId={order_id}&symbol={symbol}");
 
-        let endpoint = format!("{}/v5/order/realtime", self.config.base_url);
+        let endpoint = format!("{}/v5/order/realtime", self.base_url());
 
         // Get the raw response to debug the structure
         let response = self
@@ -432,6 +708,202 @@ impl BybitClient {
             }
         }
     }
+
+    /// Order placed under `order_link_id`, open or recently closed; None if Bybit has no
+    /// such order
+    pub async fn get_order_by_link_id(
+        &self,
+        category: &str,
+        order_link_id: &str,
+        symbol: &str,
+    ) -> Result<Option<crate::models::OrderInfo>> {
+        let endpoint = format!("{}/v5/order/realtime", self.base_url());
+        let query_params =
+            format!("category={category}&orderLinkId={order_link_id}&symbol={symbol}");
+        let orders: crate::models::OrderListResult =
+            self.signed_request(&endpoint, &query_params).await?;
+        Ok(orders.list.into_iter().next())
+    }
+
+    /// Open orders on the account (the first 50), whichever client placed them
+    pub async fn get_open_orders(&self, category: &str) -> Result<Vec<crate::models::OrderInfo>> {
+        let endpoint = format!("{}/v5/order/realtime", self.base_url());
+        let query_params = format!("category={category}&openOnly=0&limit=50");
+        let orders: crate::models::OrderListResult =
+            self.signed_request(&endpoint, &query_params).await?;
+        Ok(orders.list)
+    }
+
+    /// Fills of an order, each with the coin its fee was charged in
+    pub async fn get_executions(
+        &self,
+        category: &str,
+        order_id: &str,
+    ) -> Result<Vec<crate::models::ExecutionRecord>> {
+        let endpoint = format!("{}/v5/execution/list", self.base_url());
+        let query_params = format!("category={category}&orderId={order_id}");
+        let executions: crate::models::ExecutionListResult =
+            self.signed_request(&endpoint, &query_params).await?;
+        Ok(executions.list)
+    }
+
+    /// Fills of every order executed since `start`, across all pages
+    pub async fn get_executions_since(
+        &self,
+        category: &str,
+        start: DateTime<Utc>,
+    ) -> Result<Vec<crate::models::ExecutionRecord>> {
+        let endpoint = format!("{}/v5/execution/list", self.base_url());
+        let mut executions = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut query_params = format!(
+                "category={category}&startTime={}&limit=100",
+                start.timestamp_millis()
+            );
+            if let Some(ref c) = cursor {
+                query_params.push_str(&format!("&cursor={c}"));
+            }
+            let page: crate::models::ExecutionListResult =
+                self.signed_request(&endpoint, &query_params).await?;
+            let count = page.list.len();
+            executions.extend(page.list);
+            match page.next_page_cursor {
+                Some(next) if !next.is_empty() && count > 0 => cursor = Some(next),
+                _ => break,
+            }
+        }
+        Ok(executions)
+    }
+
+    /// Every page of an asset history endpoint since `start`
+    async fn get_asset_records<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        start: DateTime<Utc>,
+    ) -> Result<Vec<T>> {
+        let endpoint = format!("{}{path}", self.base_url());
+        let mut records = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut query_params = format!("startTime={}&limit=50", start.timestamp_millis());
+            if let Some(ref c) = cursor {
+                query_params.push_str(&format!("&cursor={c}"));
+            }
+            let page: crate::models::AssetRecordsResult<T> =
+                self.signed_request(&endpoint, &query_params).await?;
+            let count = page.rows.len();
+            records.extend(page.rows);
+            match page.next_page_cursor {
+                Some(next) if !next.is_empty() && count > 0 => cursor = Some(next),
+                _ => break,
+            }
+        }
+        Ok(records)
+    }
+
+    /// Deposits into the account since `start`
+    pub async fn get_deposits_since(
+        &self,
+        start: DateTime<Utc>,
+    ) -> Result<Vec<crate::models::DepositRecord>> {
+        self.get_asset_records("/v5/asset/deposit/query-record", start)
+            .await
+    }
+
+    /// Withdrawals out of the account since `start`
+    pub async fn get_withdrawals_since(
+        &self,
+        start: DateTime<Utc>,
+    ) -> Result<Vec<crate::models::WithdrawalRecord>> {
+        self.get_asset_records("/v5/asset/withdraw/query-record", start)
+            .await
+    }
+
+    /// Cancel a resting order
+    pub async fn cancel_order(&self, category: &str, order_id: &str, symbol: &str) -> Result<()> {
+        let body = serde_json::json!({
+            "category": category,
+            "symbol": symbol,
+            "orderId": order_id,
+        });
+        self.signed_post::<PlaceOrderResult>("/v5/order/cancel", &body)
+            .await
+            .context("Order cancel failed")?;
+        info!("Order cancelled: {order_id}");
+        Ok(())
+    }
+
+    /// Cancel every open order on `symbol`, from any client, including manual orders and
+    /// other bots' on the same account. Returns the orders cancelled. Use
+    /// `cancel_tagged_orders` to cancel only this instance's orders.
+    pub async fn cancel_all_orders(
+        &self,
+        category: &str,
+        symbol: &str,
+    ) -> Result<Vec<PlaceOrderResult>> {
+        let body = serde_json::json!({
+            "category": category,
+            "symbol": symbol,
+        });
+        let result: CancelAllResult = self
+            .signed_post("/v5/order/cancel-all", &body)
+            .await
+            .with_context(|| format!("Cancelling all orders on {symbol} failed"))?;
+        info!("{} order(s) on {symbol} cancelled", result.list.len());
+        Ok(result.list)
+    }
+
+    /// Change the quantity or price of an open order in place, keeping its id
+    pub async fn amend_order(&self, request: AmendOrderRequest) -> Result<PlaceOrderResult> {
+        let body = serde_json::to_value(&request)?;
+        let result: PlaceOrderResult = self
+            .signed_post("/v5/order/amend", &body)
+            .await
+            .context("Order amend failed")?;
+        info!(
+            "Order amended: {} (qty {}, price {})",
+            result.order_id,
+            request.qty.as_deref().unwrap_or("unchanged"),
+            request.price.as_deref().unwrap_or("unchanged")
+        );
+        Ok(result)
+    }
+
+    /// Execute a signed POST request with a JSON body to an API path
+    async fn signed_post<T>(&self, path: &str, body: &serde_json::Value) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let endpoint = format!("{}{path}", self.base_url());
+        let body = body.to_string();
+        let timestamp = self.get_timestamp_ms();
+        let keys = self.keys.signing().await;
+        let signature = Self::generate_signature(&keys.active, timestamp, "POST", path, "", &body)?;
+
+        let start = Instant::now();
+        let response = self
+            .client
+            .post(&endpoint)
+            .header("X-BAPI-API-KEY", &keys.active.api_key)
+            .header("X-BAPI-SIGN", signature)
+            .header("X-BAPI-SIGN-TYPE", "2")
+            .header("X-BAPI-TIMESTAMP", timestamp.to_string())
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW_MS.to_string())
+            .body(body)
+            .send()
+            .await;
+        self.latencies.record(&endpoint, start.elapsed());
+        self.record_outcome(&response);
+        let response_text = response.context("Failed to send request")?.text().await?;
+        debug!("{path} response: {response_text}");
+
+        let result = parse_api_response(&mut response_text.into_bytes());
+        if let Err(e) = &result {
+            self.resync_on_timestamp_error(e).await;
+        }
+        result
+    }
 }
 
 #[cfg(test)]
@@ -440,15 +912,9 @@ mod tests {
 
     fn create_test_config() -> Config {
         Config {
-            api_key: "test_key".to_string(),
-            api_secret: "test_secret".to_string(),
             base_url: "https://api-testnet.bybit.com".to_string(),
             testnet: true,
-            request_timeout_secs: 30,
-            max_retries: 3,
-            order_size: 100.0,
-            min_profit_threshold: 0.5,
-            trading_fee_rate: 0.001,
+            ..Config::test_config()
         }
     }
 
@@ -461,9 +927,276 @@ mod tests {
 
     #[test]
     fn test_timestamp_generation() {
-        let ts1 = BybitClient::get_timestamp_ms();
+        let client = BybitClient::new(create_test_config()).unwrap();
+        let ts1 = client.get_timestamp_ms();
         std::thread::sleep(std::time::Duration::from_millis(1));
-        let ts2 = BybitClient::get_timestamp_ms();
+        let ts2 = client.get_timestamp_ms();
         assert!(ts2 > ts1);
     }
+
+    #[test]
+    fn test_timestamps_follow_the_synced_offset() {
+        let client = BybitClient::new(create_test_config()).unwrap();
+        // Bybit's clock 7s ahead of a local clock that drifted behind
+        client.time_offset_ms.store(7_000, Ordering::SeqCst);
+        let shifted = client.get_timestamp_ms() as i64 - local_millis() as i64;
+        assert!((6_990..=7_000).contains(&shifted), "{shifted}");
+        // Clones share the offset, so a resync reaches every task's client
+        assert_eq!(client.clone().time_offset_ms(), 7_000);
+        // The private stream signs its auth with the same offset
+        let stream = synced_millis(client.time_offset()) as i64 - local_millis() as i64;
+        assert!((6_990..=7_000).contains(&stream), "{stream}");
+    }
+
+    /// Parse a recorded Bybit payload the same way live responses are parsed
+    fn parse_fixture<T: serde::de::DeserializeOwned>(json: &str) -> Result<T> {
+        parse_api_response(&mut json.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_fixture_spot_tickers() {
+        let tickers: TickersResult = parse_fixture(include_str!(
+            "../tests/fixtures/bybit/rest/tickers_spot.json"
+        ))
+        .unwrap();
+        assert_eq!(tickers.category, "spot");
+        assert_eq!(tickers.list.len(), 3);
+
+        let btc = &tickers.list[0];
+        assert_eq!(btc.bid1_price.as_deref(), Some("67012.5"));
+        assert_eq!(btc.ask1_size.as_deref(), Some("1.208894"));
+
+        // A market without bids must not produce a tradable pair
+        let instrument = InstrumentInfo {
+            symbol: "NEWCOINUSDT".to_string(),
+            base_coin: "NEWCOIN".to_string(),
+            quote_coin: "USDT".to_string(),
+            ..spot_instruments().list[0].clone()
+        };
+        assert!(MarketPair::new(&instrument, &tickers.list[2], &Config::test_config()).is_none());
+    }
+
+    fn spot_instruments() -> InstrumentsInfoResult {
+        parse_fixture(include_str!(
+            "../tests/fixtures/bybit/rest/instruments_spot.json"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fixture_spot_instruments() {
+        let instruments = spot_instruments();
+        assert_eq!(instruments.list.len(), 3);
+        assert_eq!(instruments.list[2].status, "Closed");
+
+        let pepe = &instruments.list[1];
+        let lot_size = pepe.lot_size_filter.as_ref().unwrap();
+        assert!(lot_size.qty_step.is_none());
+        assert_eq!(lot_size.base_precision.as_deref(), Some("1"));
+        assert_eq!(lot_size.min_order_amt.as_deref(), Some("1"));
+
+        // Spot lot sizes come from basePrecision and minOrderAmt
+        let tickers: TickersResult = parse_fixture(include_str!(
+            "../tests/fixtures/bybit/rest/tickers_spot.json"
+        ))
+        .unwrap();
+        let btc = MarketPair::new(
+            &instruments.list[0],
+            &tickers.list[0],
+            &Config::test_config(),
+        )
+        .unwrap();
+        assert_eq!(btc.qty_step, 0.000001);
+        assert_eq!(btc.min_notional, 1.0);
+    }
+
+    #[test]
+    fn test_fixture_wallet_balance() {
+        let wallet: WalletBalanceResult = parse_fixture(include_str!(
+            "../tests/fixtures/bybit/rest/wallet_balance_unified.json"
+        ))
+        .unwrap();
+        let account = &wallet.list[0];
+        assert_eq!(account.account_type.as_deref(), Some("UNIFIED"));
+        assert_eq!(account.coin.len(), 2);
+        assert_eq!(account.coin[1].coin, "BTC");
+        assert_eq!(account.coin[0].available_to_withdraw.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn test_fixture_order_lifecycle() {
+        let created: PlaceOrderResult = parse_fixture(include_str!(
+            "../tests/fixtures/bybit/rest/order_create.json"
+        ))
+        .unwrap();
+        assert_eq!(created.order_id, "1745612033410857216");
+
+        let states = [
+            (
+                include_str!("../tests/fixtures/bybit/rest/order_realtime_new.json"),
+                "New",
+            ),
+            (
+                include_str!("../tests/fixtures/bybit/rest/order_realtime_partially_filled.json"),
+                "PartiallyFilled",
+            ),
+            (
+                include_str!("../tests/fixtures/bybit/rest/order_realtime_filled.json"),
+                "Filled",
+            ),
+            (
+                include_str!("../tests/fixtures/bybit/rest/order_realtime_cancelled.json"),
+                "Cancelled",
+            ),
+        ];
+        let amended: PlaceOrderResult = parse_fixture(include_str!(
+            "../tests/fixtures/bybit/rest/order_amend.json"
+        ))
+        .unwrap();
+        assert_eq!(amended.order_id, created.order_id);
+        let cancelled: CancelAllResult = parse_fixture(include_str!(
+            "../tests/fixtures/bybit/rest/order_cancel_all.json"
+        ))
+        .unwrap();
+        assert_eq!(cancelled.list.len(), 2);
+        assert_eq!(cancelled.list[0].order_link_id, created.order_link_id);
+
+        for (json, status) in states {
+            let orders: OrderListResult = parse_fixture(json).unwrap();
+            let order = &orders.list[0];
+            assert_eq!(order.order_status, status);
+            assert_eq!(order.order_id, created.order_id);
+        }
+
+        let filled: OrderListResult = parse_fixture(include_str!(
+            "../tests/fixtures/bybit/rest/order_realtime_filled.json"
+        ))
+        .unwrap();
+        assert_eq!(filled.list[0].cum_exec_qty, "0.00757");
+        assert_eq!(filled.list[0].avg_price, "3301.55");
+
+        // The fee of a discounted fill is charged in another coin
+        let executions: ExecutionListResult = parse_fixture(include_str!(
+            "../tests/fixtures/bybit/rest/execution_list.json"
+        ))
+        .unwrap();
+        let fees: Vec<_> = executions
+            .list
+            .iter()
+            .map(|e| (e.fee_currency.as_str(), e.exec_fee.as_str()))
+            .collect();
+        assert_eq!(fees, [("MNT", "0.0261"), ("ETH", "0.00000257")]);
+        assert_eq!(executions.list[0].order_id, created.order_id);
+        assert_eq!(
+            executions.list[0].order_link_id,
+            "arb_0f8e4c1a2b3d4e5f6071_1"
+        );
+    }
+
+    #[test]
+    fn test_fixture_batch_order_create() {
+        let outcomes = batch_outcomes(include_str!(
+            "../tests/fixtures/bybit/rest/order_create_batch.json"
+        ))
+        .unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(
+            outcomes[0].as_ref().unwrap().order_id,
+            "1745612033410857301"
+        );
+        let rejected = outcomes[1].as_ref().unwrap_err();
+        assert!(matches!(
+            BybitError::find(rejected),
+            Some(BybitError::InsufficientBalance { .. })
+        ));
+        assert!(format!("{rejected:#}").contains("API Error 170131"));
+        assert!(batch_outcomes(include_str!(
+            "../tests/fixtures/bybit/rest/error_insufficient_balance.json"
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_fixture_deposit_and_withdrawal_records() {
+        let deposits: AssetRecordsResult<DepositRecord> = parse_fixture(include_str!(
+            "../tests/fixtures/bybit/rest/deposit_records.json"
+        ))
+        .unwrap();
+        assert_eq!(deposits.rows[0].coin, "USDT");
+        assert_eq!(deposits.rows[0].amount, "999.0496");
+        assert_eq!(deposits.rows[0].status, 3);
+
+        let withdrawals: AssetRecordsResult<WithdrawalRecord> = parse_fixture(include_str!(
+            "../tests/fixtures/bybit/rest/withdraw_records.json"
+        ))
+        .unwrap();
+        let statuses: Vec<_> = withdrawals
+            .rows
+            .iter()
+            .map(|w| (w.withdraw_id.as_str(), w.status.as_str()))
+            .collect();
+        assert_eq!(statuses, [("9976", "SecurityCheck"), ("9975", "success")]);
+        assert_eq!(withdrawals.rows[1].withdraw_fee, "0.0015");
+    }
+
+    #[test]
+    fn test_fixture_system_status() {
+        let status: SystemStatusResult = parse_fixture(include_str!(
+            "../tests/fixtures/bybit/rest/system_status.json"
+        ))
+        .unwrap();
+        assert_eq!(status.list.len(), 2);
+        assert_eq!(status.list[1].state, "completed");
+
+        // The scheduled window pauses trading shortly before it begins
+        let begin = status.list[0].window().unwrap().0;
+        let active = |now| crate::maintenance::active_window(&status.list, now);
+        assert!(active(begin - chrono::Duration::minutes(5)).is_none());
+        assert_eq!(
+            active(begin - chrono::Duration::seconds(10)).unwrap().title,
+            "Scheduled system upgrade"
+        );
+    }
+
+    #[test]
+    fn test_fixture_server_time_and_api_key() {
+        let time: ServerTimeResult = parse_fixture(include_str!(
+            "../tests/fixtures/bybit/rest/server_time.json"
+        ))
+        .unwrap();
+        assert_eq!(time.as_millis(), Some(1_718_000_000_423));
+
+        let key: ApiKeyInfo =
+            parse_fixture(include_str!("../tests/fixtures/bybit/rest/query_api.json")).unwrap();
+        assert_eq!(key.read_only, 0);
+        assert!(key.has_permission("Spot", "SpotTrade"));
+        assert!(!key.is_ip_unrestricted());
+    }
+
+    #[test]
+    fn test_fixture_errors_report_ret_code() {
+        let cases = [
+            (
+                include_str!("../tests/fixtures/bybit/rest/error_ip_not_whitelisted.json"),
+                "10010",
+            ),
+            (
+                include_str!("../tests/fixtures/bybit/rest/error_insufficient_balance.json"),
+                "170131",
+            ),
+            (
+                include_str!("../tests/fixtures/bybit/rest/error_too_many_decimals.json"),
+                "170137",
+            ),
+        ];
+        for (json, code) in cases {
+            // Error bodies carry `"result": {}`, which must not hide the retCode
+            let error = parse_fixture::<TickersResult>(json).unwrap_err();
+            assert!(error.to_string().contains(code), "{error}");
+            assert_eq!(
+                BybitError::find(&error).map(|e| e.code().to_string()),
+                Some(code.to_string())
+            );
+        }
+    }
 }
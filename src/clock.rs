@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+/// Source of time for timeouts and refresh intervals, so tests can run on virtual time
+#[async_trait]
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+
+    async fn sleep(&self, duration: Duration);
+}
+
+/// Real time, via tokio (which also honours paused time in tests)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl SystemClock {
+    pub fn shared() -> Arc<dyn Clock> {
+        Arc::new(SystemClock)
+    }
+}
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+#[cfg(test)]
+pub use manual::ManualClock;
+
+#[cfg(test)]
+mod manual {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Virtual clock that only moves when slept on or advanced, so sleeps return immediately
+    pub struct ManualClock {
+        start: Instant,
+        elapsed: Mutex<Duration>,
+    }
+
+    impl ManualClock {
+        pub fn new() -> Self {
+            Self {
+                start: Instant::now(),
+                elapsed: Mutex::new(Duration::ZERO),
+            }
+        }
+
+        pub fn advance(&self, duration: Duration) {
+            *self.elapsed.lock().unwrap() += duration;
+        }
+
+        /// Virtual time passed since the clock was created
+        pub fn elapsed(&self) -> Duration {
+            *self.elapsed.lock().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            self.start + self.elapsed()
+        }
+
+        async fn sleep(&self, duration: Duration) {
+            self.advance(duration);
+            // Still yield so other tasks get to run
+            tokio::task::yield_now().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_manual_clock_sleeps_in_virtual_time() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+
+        clock.sleep(Duration::from_secs(3600)).await;
+        clock.advance(Duration::from_millis(500));
+
+        assert_eq!(clock.now() - start, Duration::from_millis(3_600_500));
+    }
+}
@@ -0,0 +1,588 @@
+use crate::focus::Focus;
+use crate::run_state::RunState;
+use crate::state::SharedState;
+use anyhow::{Context, Result};
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info, warn};
+
+/// Maximum request size accepted by the control API
+const MAX_REQUEST_BYTES: usize = 8192;
+
+/// HTTP response produced by a route handler
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    fn text(status: u16, body: String) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; version=0.0.4",
+            body,
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            202 => "Accepted",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Start the local control API (health and metrics) on 127.0.0.1
+pub async fn spawn_control_api(port: u16, state: SharedState) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("Failed to bind control API on 127.0.0.1:{port}"))?;
+    info!(
+        "🩺 Control API listening on http://127.0.0.1:{port} (/health, /metrics, /status, POST /rotate-key, POST/DELETE /focus)"
+    );
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let state = state.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(stream, &state).await {
+                            debug!("Control API connection error: {e}");
+                        }
+                    });
+                }
+                Err(e) => warn!("⚠️ Control API accept failed: {e}"),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+async fn handle_connection(mut stream: TcpStream, state: &SharedState) -> Result<()> {
+    let mut buf = vec![0u8; MAX_REQUEST_BYTES];
+    let mut len = 0;
+
+    // Read until the end of the request headers
+    loop {
+        let n = stream.read(&mut buf[len..]).await?;
+        if n == 0 {
+            break;
+        }
+        len += n;
+        if buf[..len].windows(4).any(|w| w == b"\r\n\r\n") || len == buf.len() {
+            break;
+        }
+    }
+
+    let request = String::from_utf8_lossy(&buf[..len]);
+    let response = match request.lines().next().and_then(parse_request_line) {
+        Some((method, target)) => route(method, target, state),
+        None => Response::json(400, json!({"error": "malformed request"})),
+    };
+
+    let header = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len()
+    );
+    stream.write_all(header.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await.ok();
+
+    Ok(())
+}
+
+fn parse_request_line(line: &str) -> Option<(&str, &str)> {
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    Some((method, target))
+}
+
+/// Value of `key` in a query string; values are comma lists of coins, so no decoding
+fn query_param<'a>(query: &'a str, key: &str) -> &'a str {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map_or("", |(_, value)| value)
+}
+
+/// Dispatch a request to its handler
+pub fn route(method: &str, target: &str, state: &SharedState) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    match (method, path) {
+        ("GET", "/health") => health(state),
+        ("GET", "/metrics") => metrics(state),
+        ("GET", "/status") => status(state),
+        ("POST", "/rotate-key") => {
+            state.request_key_rotation("control API");
+            Response::json(
+                202,
+                json!({"rotation": "requested", "result": "see /status key_rotation"}),
+            )
+        }
+        ("POST", "/focus") => {
+            let coins = query_param(query, "coins");
+            let triangles = query_param(query, "triangles");
+            match Focus::parse(coins, triangles) {
+                Ok(focus) => {
+                    let body = json!({"focus": focus.to_string(), "result": "see /status focus"});
+                    state.request_focus(Some(focus), "control API");
+                    Response::json(202, body)
+                }
+                Err(e) => Response::json(400, json!({"error": format!("{e:#}")})),
+            }
+        }
+        ("DELETE", "/focus") => {
+            state.request_focus(None, "control API");
+            Response::json(202, json!({"focus": null, "result": "see /status focus"}))
+        }
+        ("POST", "/approve") => match query_param(query, "id").parse::<u64>() {
+            Ok(id) => match state.approve(id, "control API") {
+                Ok(trade) => Response::json(
+                    202,
+                    json!({"approved": trade.id, "path": trade.path, "expires_at": trade.expires_at}),
+                ),
+                Err(e) => Response::json(409, json!({"error": format!("{e:#}")})),
+            },
+            Err(_) => Response::json(400, json!({"error": "id must be a trade number"})),
+        },
+        (_, "/health" | "/metrics" | "/status" | "/rotate-key" | "/focus" | "/approve") => {
+            Response::json(405, json!({"error": "method not allowed"}))
+        }
+        _ => Response::json(404, json!({"error": "not found"})),
+    }
+}
+
+fn health(state: &SharedState) -> Response {
+    Response::json(
+        200,
+        json!({
+            "status": "ok",
+            "trading": if state.is_paused() { "paused" } else { "active" },
+            "mode": state.mode(),
+            "state": state.run_state(),
+            "uptime_secs": state.uptime_secs(),
+        }),
+    )
+}
+
+fn status(state: &SharedState) -> Response {
+    match serde_json::to_value(state.status_report()) {
+        Ok(body) => Response::json(200, body),
+        Err(e) => Response::json(500, json!({"error": e.to_string()})),
+    }
+}
+
+fn metrics(state: &SharedState) -> Response {
+    let current = state.run_state();
+    let run_state: String = RunState::ALL
+        .iter()
+        .map(|s| {
+            format!(
+                "arb_run_state{{state=\"{s}\"}} {}\n",
+                u8::from(*s == current)
+            )
+        })
+        .collect();
+    let report = state.status_report();
+    let endpoint_active: String = report
+        .endpoints
+        .iter()
+        .map(|e| {
+            format!(
+                "arb_endpoint_active{{endpoint=\"{}\"}} {}\n",
+                e.name,
+                u8::from(e.active)
+            )
+        })
+        .collect();
+    let endpoint_latency: String = report
+        .endpoints
+        .iter()
+        .filter_map(|e| {
+            let ms = e.latency_ms?;
+            Some(format!(
+                "arb_endpoint_latency_ms{{endpoint=\"{}\"}} {ms:.1}\n",
+                e.name
+            ))
+        })
+        .collect();
+    let bandwidth = &report.bandwidth;
+    let connection_bytes: String = bandwidth
+        .connections
+        .iter()
+        .map(|(connection, bytes)| {
+            format!("arb_ws_received_bytes_total{{connection=\"{connection}\"}} {bytes}\n")
+        })
+        .collect();
+    let topic_bytes: String = bandwidth
+        .topics
+        .iter()
+        .map(|(topic, bytes)| {
+            format!("arb_ws_topic_received_bytes_total{{topic=\"{topic}\"}} {bytes}\n")
+        })
+        .collect();
+    let budget = bandwidth
+        .budget_bytes_per_sec
+        .map(|limit| {
+            format!(
+                "# HELP arb_ws_bandwidth_budget_bytes_per_second Configured WebSocket bandwidth budget\n\
+                 # TYPE arb_ws_bandwidth_budget_bytes_per_second gauge\n\
+                 arb_ws_bandwidth_budget_bytes_per_second {limit:.0}\n"
+            )
+        })
+        .unwrap_or_default();
+    let request_duration: String = report
+        .request_latency
+        .iter()
+        .map(|(path, histogram)| {
+            let buckets: String = crate::latency::BUCKETS_SECS
+                .iter()
+                .zip(&histogram.buckets)
+                .map(|(le, count)| {
+                    format!(
+                        "arb_http_request_duration_seconds_bucket{{endpoint=\"{path}\",le=\"{le}\"}} {count}\n"
+                    )
+                })
+                .collect();
+            format!(
+                "{buckets}\
+                 arb_http_request_duration_seconds_bucket{{endpoint=\"{path}\",le=\"+Inf\"}} {count}\n\
+                 arb_http_request_duration_seconds_sum{{endpoint=\"{path}\"}} {:.6}\n\
+                 arb_http_request_duration_seconds_count{{endpoint=\"{path}\"}} {count}\n",
+                histogram.sum_secs,
+                count = histogram.count
+            )
+        })
+        .collect();
+    let reporting_rate = report
+        .reporting
+        .as_ref()
+        .and_then(|r| r.per_usd.map(|rate| (r.currency.as_str(), rate)))
+        .map(|(currency, rate)| {
+            format!(
+                "# HELP arb_reporting_currency_per_usd Units of the reporting currency per USD\n\
+                 # TYPE arb_reporting_currency_per_usd gauge\n\
+                 arb_reporting_currency_per_usd{{currency=\"{currency}\"}} {rate}\n"
+            )
+        })
+        .unwrap_or_default();
+    let reconciliation = &report.reconciliation;
+    let reconcile_mismatches: String = crate::reconcile::Mismatch::KINDS
+        .iter()
+        .map(|kind| {
+            format!(
+                "arb_reconcile_mismatches_total{{kind=\"{kind}\"}} {}\n",
+                reconciliation.mismatches.get(*kind).unwrap_or(&0)
+            )
+        })
+        .collect();
+    let availability: String = crate::availability::WINDOWS
+        .iter()
+        .zip([
+            report.availability.last_24h_pct,
+            report.availability.last_7d_pct,
+        ])
+        .filter_map(|((window, _), pct)| {
+            Some(format!(
+                "arb_availability_percent{{window=\"{window}\"}} {:.3}\n",
+                pct?
+            ))
+        })
+        .collect();
+    let utilization = &report.utilization;
+    let turnover = utilization
+        .turnover_last_hour
+        .map(|turnover| {
+            format!(
+                "# HELP arb_capital_turnover_last_hour Notional traded over the last hour as a multiple of the account value\n\
+                 # TYPE arb_capital_turnover_last_hour gauge\n\
+                 arb_capital_turnover_last_hour {turnover:.4}\n"
+            )
+        })
+        .unwrap_or_default();
+    let idle = utilization
+        .idle_last_hour_pct
+        .map(|idle| {
+            format!(
+                "# HELP arb_idle_percent Share of the last hour no trade was executing\n\
+                 # TYPE arb_idle_percent gauge\n\
+                 arb_idle_percent {idle:.3}\n"
+            )
+        })
+        .unwrap_or_default();
+    let body = format!(
+        "# HELP arb_trading_paused 1 if trading is paused (scan-only), 0 otherwise\n\
+         # TYPE arb_trading_paused gauge\n\
+         arb_trading_paused {}\n\
+         # HELP arb_uptime_seconds Seconds since the bot started\n\
+         # TYPE arb_uptime_seconds gauge\n\
+         arb_uptime_seconds {}\n\
+         # HELP arb_scan_cycles_total Completed scan cycles\n\
+         # TYPE arb_scan_cycles_total counter\n\
+         arb_scan_cycles_total {}\n\
+         # HELP arb_trades_completed_total Successfully completed arbitrage trades\n\
+         # TYPE arb_trades_completed_total counter\n\
+         arb_trades_completed_total {}\n\
+         # HELP arb_run_state 1 for the main loop's current state, 0 for the others\n\
+         # TYPE arb_run_state gauge\n\
+         {run_state}\
+         # HELP arb_endpoint_switches_total Times the active Bybit endpoint changed\n\
+         # TYPE arb_endpoint_switches_total counter\n\
+         arb_endpoint_switches_total {}\n\
+         # HELP arb_endpoint_active 1 for the Bybit endpoint in use, 0 for the others\n\
+         # TYPE arb_endpoint_active gauge\n\
+         {endpoint_active}\
+         # HELP arb_endpoint_latency_ms Latest probe round trip to each endpoint\n\
+         # TYPE arb_endpoint_latency_ms gauge\n\
+         {endpoint_latency}\
+         # HELP arb_price_feed_degraded 1 while prices are polled from REST because every WebSocket is down\n\
+         # TYPE arb_price_feed_degraded gauge\n\
+         arb_price_feed_degraded {}\n\
+         # HELP arb_ws_connections Top-of-book WebSocket connections currently up\n\
+         # TYPE arb_ws_connections gauge\n\
+         arb_ws_connections {}\n\
+         # HELP arb_ws_received_bytes_total Bytes received on each WebSocket connection\n\
+         # TYPE arb_ws_received_bytes_total counter\n\
+         {connection_bytes}\
+         # HELP arb_ws_topic_received_bytes_total Bytes of data received for each WebSocket topic\n\
+         # TYPE arb_ws_topic_received_bytes_total counter\n\
+         {topic_bytes}\
+         # HELP arb_ws_bandwidth_bytes_per_second WebSocket download averaged over the last minute\n\
+         # TYPE arb_ws_bandwidth_bytes_per_second gauge\n\
+         arb_ws_bandwidth_bytes_per_second {:.0}\n\
+         {budget}\
+         # HELP arb_ws_trimmed_symbols Symbols unsubscribed to stay within the bandwidth budget\n\
+         # TYPE arb_ws_trimmed_symbols gauge\n\
+         arb_ws_trimmed_symbols {}\n\
+         # HELP arb_http_request_duration_seconds Time to response headers per REST endpoint\n\
+         # TYPE arb_http_request_duration_seconds histogram\n\
+         {request_duration}\
+         # HELP arb_reconcile_runs_total Checks of journaled fills against the exchange\n\
+         # TYPE arb_reconcile_runs_total counter\n\
+         arb_reconcile_runs_total {}\n\
+         # HELP arb_reconcile_mismatches_total Fills that didn't reconcile with the exchange, by kind\n\
+         # TYPE arb_reconcile_mismatches_total counter\n\
+         {reconcile_mismatches}\
+         # HELP arb_reconcile_pnl_adjustment_usd Corrections to realized profit from reconciliation\n\
+         # TYPE arb_reconcile_pnl_adjustment_usd gauge\n\
+         arb_reconcile_pnl_adjustment_usd {}\n\
+         # HELP arb_availability_percent Time fully operational (feeds, API, not halted) over each window\n\
+         # TYPE arb_availability_percent gauge\n\
+         {availability}\
+         # HELP arb_notional_traded_usd_total Notional traded by executions this session\n\
+         # TYPE arb_notional_traded_usd_total counter\n\
+         arb_notional_traded_usd_total {:.2}\n\
+         # HELP arb_account_value_usd Account value capital turnover is measured against\n\
+         # TYPE arb_account_value_usd gauge\n\
+         arb_account_value_usd {:.2}\n\
+         {turnover}\
+         {idle}\
+         {reporting_rate}",
+        u8::from(state.is_paused()),
+        state.uptime_secs(),
+        state.cycles(),
+        state.trades_completed(),
+        report.endpoint_switches,
+        u8::from(report.prices_degraded),
+        report.ws_connections,
+        bandwidth.bytes_per_sec,
+        bandwidth.trimmed.len(),
+        reconciliation.runs,
+        reconciliation.pnl_adjustment_usd,
+        utilization.notional_session_usd,
+        utilization.account_value_usd,
+    );
+    Response::text(200, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::BotState;
+
+    #[test]
+    fn test_health_reflects_pause_state() {
+        let state = BotState::new();
+        let response = route("GET", "/health", &state);
+        assert_eq!(response.status, 200);
+        assert!(response.body.contains("\"trading\":\"active\""));
+
+        state.pause("test");
+        let response = route("GET", "/health", &state);
+        assert!(response.body.contains("\"trading\":\"paused\""));
+
+        let response = route("GET", "/metrics", &state);
+        assert!(response.body.contains("arb_trading_paused 1"));
+        assert!(response
+            .body
+            .contains("arb_run_state{state=\"initializing\"} 1"));
+        assert!(response.body.contains("arb_run_state{state=\"paused\"} 0"));
+
+        state.update_snapshot(|s| {
+            s.endpoints = vec![crate::endpoints::EndpointStatus {
+                name: "bytick.com".to_string(),
+                active: true,
+                latency_ms: Some(42.0),
+            }];
+            s.endpoint_switches = 1;
+            s.prices_degraded = true;
+            let mut eur = crate::reporting::ReportingCurrency::new("EUR");
+            eur.per_usd = Some(0.92);
+            s.reporting = Some(eur);
+            s.bandwidth
+                .connections
+                .insert("orderbook.1#1".to_string(), 2048);
+            s.bandwidth
+                .topics
+                .insert("orderbook.1.BTCUSDT".to_string(), 1024);
+            s.bandwidth.trimmed = vec!["PEPEBTC".to_string()];
+            let mut histogram = crate::latency::LatencyHistogram::default();
+            histogram.observe(std::time::Duration::from_millis(20));
+            s.request_latency
+                .insert("/v5/order/create".to_string(), histogram);
+            s.reconciliation.runs = 2;
+            s.availability.last_24h_pct = Some(99.5);
+            s.reconciliation
+                .mismatches
+                .insert("fee_discrepancy".to_string(), 1);
+        });
+        let response = route("GET", "/metrics", &state);
+        assert!(response
+            .body
+            .contains("arb_endpoint_active{endpoint=\"bytick.com\"} 1"));
+        assert!(response
+            .body
+            .contains("arb_endpoint_latency_ms{endpoint=\"bytick.com\"} 42.0"));
+        assert!(response.body.contains("arb_endpoint_switches_total 1"));
+        assert!(response
+            .body
+            .contains("arb_ws_received_bytes_total{connection=\"orderbook.1#1\"} 2048"));
+        assert!(response
+            .body
+            .contains("arb_ws_topic_received_bytes_total{topic=\"orderbook.1.BTCUSDT\"} 1024"));
+        assert!(response.body.contains("arb_ws_trimmed_symbols 1"));
+        assert!(response.body.contains("arb_price_feed_degraded 1"));
+        assert!(response
+            .body
+            .contains("arb_reporting_currency_per_usd{currency=\"EUR\"} 0.92"));
+        assert!(response.body.contains(
+            "arb_http_request_duration_seconds_bucket{endpoint=\"/v5/order/create\",le=\"0.025\"} 1"
+        ));
+        assert!(response.body.contains(
+            "arb_http_request_duration_seconds_bucket{endpoint=\"/v5/order/create\",le=\"0.01\"} 0"
+        ));
+        assert!(response
+            .body
+            .contains("arb_http_request_duration_seconds_count{endpoint=\"/v5/order/create\"} 1"));
+        assert!(response.body.contains("arb_reconcile_runs_total 2"));
+        assert!(response
+            .body
+            .contains("arb_availability_percent{window=\"24h\"} 99.500"));
+        assert!(!response
+            .body
+            .contains("arb_availability_percent{window=\"7d\"}"));
+        assert!(response
+            .body
+            .contains("arb_reconcile_mismatches_total{kind=\"fee_discrepancy\"} 1"));
+        assert!(response
+            .body
+            .contains("arb_reconcile_mismatches_total{kind=\"missing_fill\"} 0"));
+    }
+
+    #[test]
+    fn test_status_route_serializes_report() {
+        let state = BotState::new();
+        let response = route("GET", "/status", &state);
+        assert_eq!(response.status, 200);
+        let report: crate::state::StatusReport = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(report.mode, "scan-and-trade");
+    }
+
+    #[tokio::test]
+    async fn test_rotate_key_is_requested_by_post_only() {
+        let state = BotState::new();
+        assert_eq!(route("GET", "/rotate-key", &state).status, 405);
+        let response = route("POST", "/rotate-key", &state);
+        assert_eq!(response.status, 202);
+        tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            state.key_rotation_requested(),
+        )
+        .await
+        .expect("rotation was not requested");
+    }
+
+    #[test]
+    fn test_unknown_routes() {
+        let state = BotState::new();
+        assert_eq!(route("GET", "/nope", &state).status, 404);
+        assert_eq!(route("POST", "/health", &state).status, 405);
+        assert_eq!(
+            parse_request_line("GET /health?x=1 HTTP/1.1"),
+            Some(("GET", "/health?x=1"))
+        );
+        assert_eq!(route("GET", "/health?x=1", &state).status, 200);
+    }
+
+    #[test]
+    fn test_focus_is_validated_and_handed_to_the_main_loop() {
+        let state = BotState::new();
+        assert_eq!(route("POST", "/focus", &state).status, 400);
+        assert_eq!(
+            route("POST", "/focus?triangles=USDT-BTC", &state).status,
+            400
+        );
+        assert!(state.take_focus_request().is_none());
+
+        let response = route("POST", "/focus?coins=SOL&triangles=USDT-BTC-ETH", &state);
+        assert_eq!(response.status, 202);
+        let focus = state.take_focus_request().unwrap().unwrap();
+        assert_eq!(focus.to_string(), "SOL, BTC-ETH-USDT");
+
+        assert_eq!(route("DELETE", "/focus", &state).status, 202);
+        assert_eq!(state.take_focus_request(), Some(None));
+        assert_eq!(route("GET", "/focus", &state).status, 405);
+    }
+
+    #[test]
+    fn test_approve_needs_the_id_of_the_trade_awaiting_approval() {
+        let state = BotState::new();
+        assert_eq!(route("POST", "/approve?id=1", &state).status, 409);
+        state.set_pending_approval(Some(crate::approval::PendingApproval {
+            id: 1,
+            path: "USDT → BTC → ETH → USDT".to_string(),
+            pairs: vec![],
+            profit_pct: 0.3,
+            expires_at: chrono::Utc::now() + chrono::Duration::seconds(30),
+            approved: false,
+        }));
+        assert_eq!(route("POST", "/approve", &state).status, 400);
+        assert_eq!(route("POST", "/approve?id=2", &state).status, 409);
+        assert_eq!(route("POST", "/approve?id=1", &state).status, 202);
+        assert!(state.pending_approval().unwrap().approved);
+        assert_eq!(route("GET", "/approve", &state).status, 405);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::client::BybitClient;
+use crate::config::Config;
+use crate::config_cmd::mask;
+use crate::state::{KeyRotationSummary, SharedState};
+use crate::supervisor;
+use anyhow::{bail, Result};
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::{RwLock, RwLockReadGuard};
+use tracing::{error, info, warn};
+
+/// An API key and its secret
+#[derive(Clone, PartialEq)]
+pub struct Credentials {
+    pub api_key: String,
+    pub api_secret: String,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Credentials({})", mask(&self.api_key))
+    }
+}
+
+/// The key requests are signed with, and the one a rotation switches to
+#[derive(Debug)]
+pub struct Keys {
+    pub active: Credentials,
+    pub standby: Option<Credentials>,
+}
+
+/// Credentials shared by every clone of the client. Signed requests hold a read guard
+/// while in flight, so a rotation waits for them to finish before swapping keys.
+#[derive(Debug)]
+pub struct KeyRing {
+    keys: RwLock<Keys>,
+}
+
+impl KeyRing {
+    pub fn from_config(config: &Config) -> Arc<Self> {
+        let standby = (!config.api_key_secondary.is_empty()).then(|| Credentials {
+            api_key: config.api_key_secondary.clone(),
+            api_secret: config.api_secret_secondary.clone(),
+        });
+        Arc::new(Self {
+            keys: RwLock::new(Keys {
+                active: Credentials {
+                    api_key: config.api_key.clone(),
+                    api_secret: config.api_secret.clone(),
+                },
+                standby,
+            }),
+        })
+    }
+
+    /// Keys for signing a request; hold the guard until the response is read
+    pub async fn signing(&self) -> RwLockReadGuard<'_, Keys> {
+        self.keys.read().await
+    }
+
+    /// Wait for signed requests in flight, then make the standby key active
+    pub async fn swap(&self) -> Result<()> {
+        let mut keys = self.keys.write().await;
+        let Some(standby) = keys.standby.take() else {
+            bail!("no secondary key configured (BYBIT_API_KEY_SECONDARY)");
+        };
+        let previous = std::mem::replace(&mut keys.active, standby);
+        keys.standby = Some(previous);
+        Ok(())
+    }
+
+    /// The active key, masked for logs
+    pub async fn active_key(&self) -> String {
+        mask(&self.keys.read().await.active.api_key)
+    }
+}
+
+/// Switch signing to the standby key and verify it with a balance call. A key that fails
+/// verification is swapped back out. Returns the new key, masked.
+pub async fn rotate(client: &BybitClient) -> Result<String> {
+    let keys = client.keys();
+    keys.swap().await?;
+    match client.get_wallet_balance(None).await {
+        Ok(_) => Ok(keys.active_key().await),
+        Err(e) => {
+            keys.swap().await?;
+            bail!(
+                "new key failed verification, kept {}: {e:#}",
+                keys.active_key().await
+            )
+        }
+    }
+}
+
+/// Rotate keys whenever SIGHUP or the control API asks, on a supervised task
+pub fn spawn_rotation_listener(client: BybitClient, state: SharedState, config: &Config) {
+    if config.api_key_secondary.is_empty() {
+        warn!("🔑 No BYBIT_API_KEY_SECONDARY set; key rotation requests will be refused");
+    } else {
+        info!(
+            "🔑 Key rotation ready: send SIGHUP or POST /rotate-key to switch to the secondary key"
+        );
+    }
+    supervisor::spawn_supervised(
+        "Key rotation".to_string(),
+        config.restart_on_panic,
+        move || {
+            let (client, state) = (client.clone(), state.clone());
+            async move {
+                loop {
+                    state.key_rotation_requested().await;
+                    info!("🔑 Rotating API key: draining signed requests in flight...");
+                    let result = rotate(&client).await;
+                    match &result {
+                        Ok(key) => info!("🔑 API key rotated: now signing with {key}"),
+                        Err(e) => error!("🔑 API key rotation failed: {e:#}"),
+                    }
+                    let active_key = client.keys().active_key().await;
+                    state.update_snapshot(|s| {
+                        s.key_rotation = Some(KeyRotationSummary {
+                            at: chrono::Utc::now(),
+                            success: result.is_ok(),
+                            active_key,
+                            error: result.as_ref().err().map(|e| format!("{e:#}")),
+                        })
+                    });
+                }
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_swap_waits_for_requests_in_flight() {
+        let config = Config {
+            api_key_secondary: "new_key".to_string(),
+            api_secret_secondary: "new_secret".to_string(),
+            ..Config::test_config()
+        };
+        let keys = KeyRing::from_config(&config);
+
+        let in_flight = keys.signing().await;
+        let swap = tokio::spawn({
+            let keys = keys.clone();
+            async move { keys.swap().await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!swap.is_finished());
+        assert_eq!(in_flight.active.api_key, "test_key");
+        drop(in_flight);
+
+        swap.await.unwrap().unwrap();
+        assert_eq!(keys.signing().await.active.api_secret, "new_secret");
+        // Rotating again goes back to the original key
+        keys.swap().await.unwrap();
+        assert_eq!(keys.signing().await.active.api_key, "test_key");
+        assert!(!format!("{:?}", keys.signing().await.active).contains("test_key"));
+
+        let single = KeyRing::from_config(&Config::test_config());
+        assert!(single.swap().await.is_err());
+    }
+}
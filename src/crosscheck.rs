@@ -0,0 +1,186 @@
+use crate::models::TickerInfo;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Which of the two WebSocket feeds a quote came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteSource {
+    /// The regular top-of-book connections
+    Primary,
+    /// The second connection streaming only the watched symbols
+    Mirror,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Quote {
+    bid: f64,
+    ask: f64,
+    at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Watched {
+    symbols: HashSet<String>,
+    /// Latest primary and mirror quote of each watched symbol
+    quotes: HashMap<String, [Option<Quote>; 2]>,
+    /// Watched symbols currently blocked, so each change is logged once
+    unreliable: HashSet<String>,
+}
+
+/// Cross-check of the symbols in the best opportunities against a second, independent
+/// WebSocket subscription. A symbol is unreliable while the two feeds' quotes diverge
+/// beyond tolerance or one of them has gone silent. Shared by the connections and the
+/// main loop.
+#[derive(Debug)]
+pub struct FeedCrossCheck {
+    tolerance_pct: f64,
+    silence: Duration,
+    watched: Mutex<Watched>,
+}
+
+impl FeedCrossCheck {
+    pub fn new(tolerance_pct: f64, silence_ms: u64) -> Arc<Self> {
+        Arc::new(Self {
+            tolerance_pct,
+            silence: Duration::from_millis(silence_ms),
+            watched: Mutex::new(Watched::default()),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Watched> {
+        self.watched.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Cross-check exactly these symbols from now on
+    pub fn watch(&self, symbols: HashSet<String>) {
+        let mut watched = self.lock();
+        watched.quotes.retain(|symbol, _| symbols.contains(symbol));
+        watched.unreliable.retain(|symbol| symbols.contains(symbol));
+        watched.symbols = symbols;
+    }
+
+    /// Symbols the mirror connection should stream
+    pub fn symbols(&self) -> Vec<String> {
+        self.lock().symbols.iter().cloned().collect()
+    }
+
+    /// Note a quote from one feed; quotes of unwatched symbols are ignored
+    pub fn record(&self, source: QuoteSource, ticker: &TickerInfo, now: Instant) {
+        let price = |value: &Option<String>| value.as_deref().and_then(|v| v.parse::<f64>().ok());
+        let (Some(bid), Some(ask)) = (price(&ticker.bid1_price), price(&ticker.ask1_price)) else {
+            return;
+        };
+        let mut watched = self.lock();
+        if !watched.symbols.contains(&ticker.symbol) {
+            return;
+        }
+        let quotes = watched.quotes.entry(ticker.symbol.clone()).or_default();
+        quotes[source as usize] = Some(Quote { bid, ask, at: now });
+    }
+
+    /// Why `symbol`'s quote can't be trusted right now, None if both feeds agree or it
+    /// isn't watched
+    pub fn unreliable_reason(&self, symbol: &str) -> Option<String> {
+        let mut watched = self.lock();
+        if !watched.symbols.contains(symbol) {
+            return None;
+        }
+        let reason = match watched.quotes.get(symbol).copied().unwrap_or_default() {
+            [None, _] => Some("no primary quote yet".to_string()),
+            [_, None] => Some("no mirror quote yet".to_string()),
+            [Some(primary), Some(mirror)] => {
+                let lag = primary.at.max(mirror.at) - primary.at.min(mirror.at);
+                let silent = if primary.at < mirror.at {
+                    "primary"
+                } else {
+                    "mirror"
+                };
+                let divergence_pct = |a: f64, b: f64| (a - b).abs() / a.min(b) * 100.0;
+                let divergence = divergence_pct(primary.bid, mirror.bid)
+                    .max(divergence_pct(primary.ask, mirror.ask));
+                // A quiet book sends nothing on either feed; only a feed that missed a
+                // change the other saw is silent
+                if divergence > 0.0 && lag > self.silence {
+                    Some(format!("the {silent} feed is silent for {lag:.1?}"))
+                } else if divergence > self.tolerance_pct {
+                    Some(format!("the feeds diverge by {divergence:.3}%"))
+                } else {
+                    None
+                }
+            }
+        };
+        match &reason {
+            Some(reason) if watched.unreliable.insert(symbol.to_string()) => {
+                warn!("🔀 Not trading on {symbol}: {reason}");
+            }
+            None if watched.unreliable.remove(symbol) => {
+                info!("🔀 Feeds agree on {symbol} again");
+            }
+            _ => {}
+        }
+        reason
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticker(symbol: &str, bid: f64, ask: f64) -> TickerInfo {
+        serde_json::from_value(serde_json::json!({
+            "symbol": symbol,
+            "bid1Price": bid.to_string(),
+            "ask1Price": ask.to_string(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_watched_symbols_are_blocked_until_both_feeds_agree() {
+        let check = FeedCrossCheck::new(0.1, 2000);
+        let start = Instant::now();
+        check.watch(HashSet::from(["BTCUSDT".to_string()]));
+        check.record(
+            QuoteSource::Primary,
+            &ticker("BTCUSDT", 50_000.0, 50_010.0),
+            start,
+        );
+        assert!(check.unreliable_reason("BTCUSDT").is_some());
+        assert!(check.unreliable_reason("ETHUSDT").is_none());
+
+        check.record(
+            QuoteSource::Mirror,
+            &ticker("BTCUSDT", 50_001.0, 50_010.0),
+            start,
+        );
+        assert_eq!(check.unreliable_reason("BTCUSDT"), None);
+
+        // The mirror moves on while the primary stays at its old quote
+        let later = start + Duration::from_secs(3);
+        check.record(
+            QuoteSource::Mirror,
+            &ticker("BTCUSDT", 50_100.0, 50_110.0),
+            later,
+        );
+        let reason = check.unreliable_reason("BTCUSDT").unwrap();
+        assert!(reason.contains("primary feed is silent"), "{reason}");
+
+        // Back in step, but one feed far off the other
+        check.record(
+            QuoteSource::Primary,
+            &ticker("BTCUSDT", 50_200.0, 50_210.0),
+            later,
+        );
+        let reason = check.unreliable_reason("BTCUSDT").unwrap();
+        assert!(reason.contains("diverge"), "{reason}");
+
+        check.record(
+            QuoteSource::Primary,
+            &ticker("BTCUSDT", 50_100.0, 50_110.0),
+            later,
+        );
+        assert_eq!(check.unreliable_reason("BTCUSDT"), None);
+    }
+}
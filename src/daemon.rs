@@ -0,0 +1,87 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Default PID file used by --daemon
+pub const DEFAULT_PID_FILE: &str = "bybit-arbitrage-bot.pid";
+
+/// Default log file used by --daemon
+pub const DEFAULT_LOG_FILE: &str = "logs/bybit-arbitrage-bot.log";
+
+/// PID file that is removed again when dropped
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    /// Write the current process id, refusing to start if another live instance owns the file
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Ok(contents) = std::fs::read_to_string(path) {
+            if let Ok(pid) = contents.trim().parse::<u32>() {
+                if pid != std::process::id() && process_alive(pid) {
+                    return Err(anyhow!(
+                        "Another instance is already running (PID {pid}, PID file {})",
+                        path.display()
+                    ));
+                }
+            }
+            warn!("⚠️ Removing stale PID file {}", path.display());
+        }
+
+        crate::logger::create_parent_dir(path)?;
+        std::fs::write(path, format!("{}\n", std::process::id()))
+            .with_context(|| format!("Failed to write PID file {}", path.display()))?;
+        info!(
+            "📄 PID file written: {} (PID {})",
+            path.display(),
+            std::process::id()
+        );
+
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.path).ok();
+    }
+}
+
+/// Best-effort check whether a process with the given id is still running
+fn process_alive(pid: u32) -> bool {
+    if cfg!(target_os = "linux") {
+        Path::new(&format!("/proc/{pid}")).exists()
+    } else {
+        // Without a portable check, assume the owner is alive so two instances never trade at once
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pid_file_written_and_removed() {
+        let path = std::env::temp_dir().join(format!("bybit_pid_test_{}.pid", std::process::id()));
+        {
+            let _pid = PidFile::create(&path).unwrap();
+            let contents = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(contents.trim(), std::process::id().to_string());
+        }
+        assert!(!path.exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_stale_pid_file_is_replaced() {
+        let path = std::env::temp_dir().join(format!("bybit_stale_{}.pid", std::process::id()));
+        // PIDs above the kernel's pid_max never exist
+        std::fs::write(&path, "4294967294\n").unwrap();
+        let pid = PidFile::create(&path).unwrap();
+        drop(pid);
+        assert!(!path.exists());
+    }
+}
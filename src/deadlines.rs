@@ -0,0 +1,164 @@
+use crate::models::ArbitrageOpportunity;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Leg deadline before a symbol has any fill history
+const DEFAULT_LEG_DEADLINE: Duration = Duration::from_secs(10);
+/// Never give up on a leg sooner than this, however fast fills or decay have been
+const MIN_LEG_DEADLINE: Duration = Duration::from_secs(2);
+const MAX_LEG_DEADLINE: Duration = Duration::from_secs(30);
+/// Multiple of the p95 fill latency a leg may take
+const LATENCY_HEADROOM: u32 = 2;
+/// Weight of each new half-life sample in a triangle's running estimate
+const DECAY_SMOOTHING: f64 = 0.3;
+/// Triangles not seen for this long are forgotten
+const DECAY_STALE_AFTER: Duration = Duration::from_secs(60);
+
+/// How long a leg may wait for its fill: twice the p95 fill latency, but no longer than
+/// the edge takes to halve when it is known to decay
+pub fn leg_deadline(p95_fill_latency: Option<Duration>, half_life: Option<Duration>) -> Duration {
+    let latency_budget = p95_fill_latency
+        .map_or(DEFAULT_LEG_DEADLINE, |p95| p95 * LATENCY_HEADROOM)
+        .clamp(MIN_LEG_DEADLINE, MAX_LEG_DEADLINE);
+    match half_life {
+        Some(half_life) => latency_budget.min(half_life.max(MIN_LEG_DEADLINE)),
+        None => latency_budget,
+    }
+}
+
+/// Estimates how fast each triangle's edge decays from its estimated profit across scans
+#[derive(Debug, Default)]
+pub struct EdgeDecay {
+    /// Last changed profit of each triangle and when it changed
+    last: HashMap<String, (f64, Instant)>,
+    half_lives: HashMap<String, Duration>,
+}
+
+impl EdgeDecay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn key(opportunity: &ArbitrageOpportunity) -> String {
+        opportunity.pairs.join(">")
+    }
+
+    /// Record a scan's opportunities; a shrinking edge yields a half-life sample
+    pub fn observe(&mut self, opportunities: &[ArbitrageOpportunity], now: Instant) {
+        for opportunity in opportunities {
+            let key = Self::key(opportunity);
+            let profit = opportunity.estimated_profit_pct.value();
+            match self.last.get(&key) {
+                Some((previous, _)) if *previous == profit => continue,
+                Some((previous, at)) if *previous > profit && profit > 0.0 => {
+                    let elapsed = now.duration_since(*at).as_secs_f64();
+                    let sample = elapsed * std::f64::consts::LN_2 / (previous / profit).ln();
+                    let estimate = match self.half_lives.get(&key) {
+                        Some(current) => {
+                            current.as_secs_f64() * (1.0 - DECAY_SMOOTHING)
+                                + sample * DECAY_SMOOTHING
+                        }
+                        None => sample,
+                    };
+                    self.half_lives
+                        .insert(key.clone(), Duration::from_secs_f64(estimate));
+                }
+                _ => {}
+            }
+            self.last.insert(key, (profit, now));
+        }
+
+        self.last
+            .retain(|_, (_, at)| now.duration_since(*at) < DECAY_STALE_AFTER);
+        let last = &self.last;
+        self.half_lives.retain(|key, _| last.contains_key(key));
+    }
+
+    /// Estimated time for the opportunity's edge to halve, if it has been seen shrinking
+    pub fn half_life(&self, opportunity: &ArbitrageOpportunity) -> Option<Duration> {
+        self.half_lives.get(&Self::key(opportunity)).copied()
+    }
+
+    /// Bring an expiring opportunity's expiry forward to when its edge should have halved
+    pub fn limit_expiry(&self, opportunity: &mut ArbitrageOpportunity) {
+        let Some(valid_until) = opportunity.valid_until else {
+            return;
+        };
+        if let Some(half_life) = self
+            .half_life(opportunity)
+            .and_then(|half_life| chrono::Duration::from_std(half_life).ok())
+        {
+            opportunity.valid_until = Some(valid_until.min(opportunity.timestamp + half_life));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leg_deadline_from_latency_and_decay() {
+        assert_eq!(leg_deadline(None, None), DEFAULT_LEG_DEADLINE);
+        // Calm market, slow but reliable fills: wait for them
+        assert_eq!(
+            leg_deadline(Some(Duration::from_secs(6)), None),
+            Duration::from_secs(12)
+        );
+        // The edge halves in 3s, so a 12s wait is pointless
+        assert_eq!(
+            leg_deadline(Some(Duration::from_secs(6)), Some(Duration::from_secs(3))),
+            Duration::from_secs(3)
+        );
+        // Fast fills and fast decay still get the minimum
+        assert_eq!(
+            leg_deadline(
+                Some(Duration::from_millis(100)),
+                Some(Duration::from_millis(500))
+            ),
+            MIN_LEG_DEADLINE
+        );
+    }
+
+    #[test]
+    fn test_half_life_from_shrinking_edge() {
+        let mut decay = EdgeDecay::new();
+        let start = Instant::now();
+
+        decay.observe(&[ArbitrageOpportunity::test().with_profit_pct(1.0)], start);
+        decay.observe(
+            &[ArbitrageOpportunity::test().with_profit_pct(1.0)],
+            start + Duration::from_secs(1),
+        );
+        assert_eq!(
+            decay.half_life(&ArbitrageOpportunity::test().with_profit_pct(1.0)),
+            None
+        );
+
+        // Halved two seconds after it was first seen
+        decay.observe(
+            &[ArbitrageOpportunity::test().with_profit_pct(0.5)],
+            start + Duration::from_secs(2),
+        );
+        let half_life = decay
+            .half_life(&ArbitrageOpportunity::test().with_profit_pct(0.5))
+            .unwrap();
+        assert!((half_life.as_secs_f64() - 2.0).abs() < 1e-9);
+
+        // A decaying edge expires once it should have halved, before its quotes do
+        let mut expiring = ArbitrageOpportunity::test().with_profit_pct(0.5);
+        expiring.valid_until = Some(expiring.timestamp + chrono::Duration::seconds(5));
+        decay.limit_expiry(&mut expiring);
+        assert_eq!(
+            expiring.valid_until,
+            Some(expiring.timestamp + chrono::Duration::seconds(2))
+        );
+
+        // Forgotten once it stops showing up
+        decay.observe(&[], start + Duration::from_secs(120));
+        assert_eq!(
+            decay.half_life(&ArbitrageOpportunity::test().with_profit_pct(0.5)),
+            None
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// How many top-of-book WebSocket connections are up, and since when none have been.
+/// Shared with every connection.
+#[derive(Debug)]
+pub struct FeedHealth {
+    connected: AtomicUsize,
+    /// When the last connection dropped, or startup if none has connected yet
+    down_since: Mutex<Option<Instant>>,
+}
+
+impl FeedHealth {
+    pub fn new(now: Instant) -> Arc<Self> {
+        Arc::new(Self {
+            connected: AtomicUsize::new(0),
+            down_since: Mutex::new(Some(now)),
+        })
+    }
+
+    /// A connection is up until the returned guard is dropped
+    pub fn connect(self: &Arc<Self>) -> ConnectionGuard {
+        self.connected.fetch_add(1, Ordering::Relaxed);
+        *self.down_since.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        ConnectionGuard {
+            health: self.clone(),
+        }
+    }
+
+    pub fn connected(&self) -> usize {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// How long every connection has been down, None while any is up
+    pub fn all_down_for(&self, now: Instant) -> Option<Duration> {
+        self.down_since
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .map(|since| now.saturating_duration_since(since))
+    }
+}
+
+/// Marks one connection as up while alive
+pub struct ConnectionGuard {
+    health: Arc<FeedHealth>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        if self.health.connected.fetch_sub(1, Ordering::Relaxed) == 1 {
+            *self
+                .health
+                .down_since
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+        }
+    }
+}
+
+/// Falls back to polling REST tickers while every WebSocket is down, and back again once
+/// one reconnects
+pub struct DegradedPricing {
+    /// None never degrades
+    after: Option<Duration>,
+    poll_interval: Duration,
+    active: bool,
+    last_poll: Option<Instant>,
+}
+
+impl DegradedPricing {
+    /// Degrade after `after_secs` with every connection down (0 never does), then poll
+    /// every `poll_interval_secs`
+    pub fn new(after_secs: u64, poll_interval_secs: u64) -> Self {
+        Self {
+            after: (after_secs > 0).then(|| Duration::from_secs(after_secs)),
+            poll_interval: Duration::from_secs(poll_interval_secs),
+            active: false,
+            last_poll: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Enter or leave degraded mode. Returns the new state when it changed.
+    pub fn update(&mut self, health: &FeedHealth, now: Instant) -> Option<bool> {
+        let after = self.after?;
+        let down_for = health.all_down_for(now);
+        match (self.active, down_for) {
+            (false, Some(down)) if down >= after => {
+                warn!(
+                    "🐢 DEGRADED: No WebSocket connection for {}s - polling REST tickers every {}s",
+                    down.as_secs(),
+                    self.poll_interval.as_secs()
+                );
+                self.active = true;
+                self.last_poll = None;
+                Some(true)
+            }
+            (true, None) => {
+                info!(
+                    "⚡ WebSocket prices restored ({} connection(s)) - REST polling stopped",
+                    health.connected()
+                );
+                self.active = false;
+                Some(false)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether a REST poll is due now; records it as done
+    pub fn poll_due(&mut self, now: Instant) -> bool {
+        if !self.active
+            || self
+                .last_poll
+                .is_some_and(|last| now.duration_since(last) < self.poll_interval)
+        {
+            return false;
+        }
+        self.last_poll = Some(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_degrades_after_threshold_and_recovers_on_reconnect() {
+        let start = Instant::now();
+        let health = FeedHealth::new(start);
+        let mut pricing = DegradedPricing::new(30, 3);
+
+        let first = health.connect();
+        let second = health.connect();
+        drop(first);
+        assert_eq!(health.all_down_for(start), None);
+        drop(second);
+        let down_at = Instant::now();
+        assert_eq!(
+            pricing.update(&health, down_at + Duration::from_secs(10)),
+            None
+        );
+        assert!(!pricing.poll_due(down_at + Duration::from_secs(10)));
+
+        assert_eq!(
+            pricing.update(&health, down_at + Duration::from_secs(31)),
+            Some(true)
+        );
+        let t = down_at + Duration::from_secs(31);
+        assert!(pricing.poll_due(t));
+        assert!(!pricing.poll_due(t + Duration::from_secs(2)));
+        assert!(pricing.poll_due(t + Duration::from_secs(3)));
+
+        let _back = health.connect();
+        assert_eq!(
+            pricing.update(&health, t + Duration::from_secs(4)),
+            Some(false)
+        );
+        assert!(!pricing.is_active());
+        assert!(!pricing.poll_due(t + Duration::from_secs(10)));
+
+        // 0 never degrades
+        let mut never = DegradedPricing::new(0, 3);
+        let idle = FeedHealth::new(start);
+        assert_eq!(never.update(&idle, start + Duration::from_secs(3600)), None);
+    }
+}
@@ -0,0 +1,308 @@
+use crate::fees::FeeSchedule;
+use crate::models::{ArbitrageOpportunity, MarketPair, Notional, Pct, Price};
+use crate::pairs::PairManager;
+use crate::stable::STABLECOINS;
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Same per-triangle slippage allowance the engine deducts from top-of-book profit
+const SLIPPAGE_PENALTY_PCT: f64 = 0.15;
+
+/// How a leg is priced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PricingModel {
+    /// Best bid/ask, when the leg takes only a small part of the displayed level
+    TopOfBook,
+    /// Average fill price walking the order book
+    Depth,
+}
+
+/// One depth message: a full snapshot or changed levels, sizes of 0 removing a level
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepthUpdate {
+    pub symbol: String,
+    pub snapshot: bool,
+    pub bids: Vec<(f64, f64)>,
+    pub asks: Vec<(f64, f64)>,
+}
+
+/// Price levels of one symbol, best first
+#[derive(Debug, Default, Clone)]
+pub struct DepthBook {
+    bids: Vec<(f64, f64)>,
+    asks: Vec<(f64, f64)>,
+}
+
+impl DepthBook {
+    pub fn apply(&mut self, update: &DepthUpdate) {
+        if update.snapshot {
+            self.bids.clear();
+            self.asks.clear();
+        }
+        merge(&mut self.bids, &update.bids);
+        merge(&mut self.asks, &update.asks);
+        self.bids.sort_by(|a, b| b.0.total_cmp(&a.0));
+        self.asks.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+
+    /// Average price of a market order spending `amount`: base into the bids for a sell,
+    /// quote into the asks for a buy. None if the book is too thin to fill it.
+    pub fn average_price(&self, sell: bool, amount: f64) -> Option<f64> {
+        let (mut base, mut quote, mut left) = (0.0, 0.0, amount);
+        let levels = if sell { &self.bids } else { &self.asks };
+        for &(price, size) in levels {
+            if left <= 0.0 {
+                break;
+            }
+            // What this level can absorb, in the coin being spent
+            let capacity = if sell { size } else { size * price };
+            let taken = left.min(capacity);
+            if sell {
+                base += taken;
+                quote += taken * price;
+            } else {
+                quote += taken;
+                base += taken / price;
+            }
+            left -= taken;
+        }
+        (left <= amount * 1e-9 && base > 0.0).then(|| quote / base)
+    }
+
+    /// No levels yet, e.g. before the first snapshot
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+}
+
+fn merge(levels: &mut Vec<(f64, f64)>, changes: &[(f64, f64)]) {
+    for &(price, size) in changes {
+        match levels.iter().position(|l| l.0 == price) {
+            Some(i) if size <= 0.0 => {
+                levels.remove(i);
+            }
+            Some(i) => levels[i].1 = size,
+            None if size > 0.0 => levels.push((price, size)),
+            None => {}
+        }
+    }
+}
+
+/// Which model prices a leg spending `amount` (base for a sell, quote for a buy)
+pub fn pricing_model(
+    pair: &MarketPair,
+    sell: bool,
+    amount: f64,
+    top_fraction: f64,
+) -> PricingModel {
+    let displayed = if sell {
+        pair.bid_size
+    } else {
+        pair.ask_size * pair.ask_price
+    };
+    if amount <= displayed * top_fraction {
+        PricingModel::TopOfBook
+    } else {
+        PricingModel::Depth
+    }
+}
+
+/// Prices each leg of a triangle from top of book or depth, depending on its size
+pub struct DepthPricer {
+    top_fraction: f64,
+    fees: FeeSchedule,
+    books: HashMap<String, DepthBook>,
+}
+
+impl DepthPricer {
+    pub fn new(top_fraction: f64, fees: FeeSchedule) -> Self {
+        Self {
+            top_fraction,
+            fees,
+            books: HashMap::new(),
+        }
+    }
+
+    pub fn apply(&mut self, update: &DepthUpdate) {
+        self.books
+            .entry(update.symbol.clone())
+            .or_default()
+            .apply(update);
+    }
+
+    /// Depth of a symbol, if it is streamed
+    pub fn book(&self, symbol: &str) -> Option<&DepthBook> {
+        self.books.get(symbol)
+    }
+
+    /// Liquid symbols whose displayed top level is too thin for `order_size_usd`, the only
+    /// ones worth a depth subscription
+    pub fn symbols_needing_depth(
+        &self,
+        pair_manager: &PairManager,
+        order_size_usd: f64,
+    ) -> Vec<String> {
+        pair_manager
+            .get_pairs()
+            .iter()
+            .filter(|p| p.is_liquid && p.is_active)
+            .filter(|p| {
+                let quote_usd = if STABLECOINS.contains(&p.quote.as_str()) {
+                    Some(1.0)
+                } else {
+                    pair_manager
+                        .get_pair(&format!("{}USDT", p.quote))
+                        .map(|q| q.price)
+                };
+                // With no USD price for the quote, assume the level is too thin
+                quote_usd.is_none_or(|usd| {
+                    let top_usd = (p.bid_size * p.bid_price).min(p.ask_size * p.ask_price) * usd;
+                    top_usd * self.top_fraction < order_size_usd
+                })
+            })
+            .map(|p| p.symbol.clone())
+            .collect()
+    }
+
+    /// Re-price a triangle for `amount`, walking the book on legs too big for top of book.
+    /// Fails when such a leg has no depth book yet or the book can't fill it.
+    pub fn reprice(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        amount: f64,
+        pair_manager: &PairManager,
+    ) -> Result<ArbitrageOpportunity> {
+        let mut current = amount;
+        let mut prices = Vec::with_capacity(3);
+        let mut depth_legs = 0;
+        for (i, symbol) in opportunity.pairs.iter().enumerate() {
+            let pair = pair_manager
+                .get_pair(symbol)
+                .ok_or_else(|| anyhow!("{symbol} is no longer listed"))?;
+            let sell = pair.base == opportunity.path[i];
+            let price = match pricing_model(pair, sell, current, self.top_fraction) {
+                PricingModel::TopOfBook if sell => pair.bid_price,
+                PricingModel::TopOfBook => pair.ask_price,
+                PricingModel::Depth => {
+                    depth_legs += 1;
+                    let Some(book) = self.books.get(symbol) else {
+                        bail!("No depth book for {symbol} yet");
+                    };
+                    book.average_price(sell, current).ok_or_else(|| {
+                        anyhow!(
+                            "Depth on {symbol} too thin for {current:.6} {}",
+                            opportunity.path[i]
+                        )
+                    })?
+                }
+            };
+            prices.push(Price::new(price));
+            let received = if sell {
+                current * price
+            } else {
+                current / price
+            };
+            current = received * (1.0 - self.fees.taker(symbol));
+        }
+        if depth_legs == 0 {
+            return Ok(opportunity.clone());
+        }
+
+        let profit_amount = current - amount - amount * SLIPPAGE_PENALTY_PCT / 100.0;
+        let profit_pct = (current - amount) / amount * 100.0 - SLIPPAGE_PENALTY_PCT;
+        let profit_usd = if STABLECOINS.contains(&opportunity.path[0].as_str()) {
+            profit_amount
+        } else {
+            // Same conservative estimate the engine uses for non-USD starts
+            profit_amount * 0.5
+        };
+        Ok(ArbitrageOpportunity {
+            prices,
+            estimated_profit_pct: Pct::new(profit_pct),
+            estimated_profit_usd: Notional::new(profit_usd),
+            ..opportunity.clone()
+        })
+    }
+
+    /// Re-price every opportunity for the amount it would trade, dropping those with a
+    /// leg that can't be priced from depth yet
+    pub fn reprice_all(
+        &self,
+        opportunities: Vec<ArbitrageOpportunity>,
+        amount: impl Fn(&ArbitrageOpportunity) -> f64,
+        pair_manager: &PairManager,
+    ) -> Vec<ArbitrageOpportunity> {
+        opportunities
+            .into_iter()
+            .filter_map(|o| match self.reprice(&o, amount(&o), pair_manager) {
+                Ok(priced) => Some(priced),
+                Err(e) => {
+                    debug!("📚 Dropping {}: {e:#}", o.display_path());
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(
+        symbol: &str,
+        snapshot: bool,
+        bids: &[(f64, f64)],
+        asks: &[(f64, f64)],
+    ) -> DepthUpdate {
+        DepthUpdate {
+            symbol: symbol.to_string(),
+            snapshot,
+            bids: bids.to_vec(),
+            asks: asks.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_deltas_keep_the_book_sorted_and_walkable() {
+        let mut book = DepthBook::default();
+        book.apply(&update(
+            "BTCUSDT",
+            true,
+            &[(99.0, 1.0), (100.0, 1.0)],
+            &[(101.0, 1.0), (102.0, 2.0)],
+        ));
+        // 101 is pulled, 103 added
+        book.apply(&update(
+            "BTCUSDT",
+            false,
+            &[],
+            &[(101.0, 0.0), (103.0, 1.0)],
+        ));
+
+        // Selling 1.5 BTC takes 1 at 100 and 0.5 at 99
+        let sell = book.average_price(true, 1.5).unwrap();
+        assert!((sell - 99.666_666).abs() < 1e-5);
+        // Spending 306 USDT takes all 204 at 102, then 102 worth at 103
+        let buy = book.average_price(false, 306.0).unwrap();
+        assert!((buy - 306.0 / (2.0 + 102.0 / 103.0)).abs() < 1e-9);
+        assert_eq!(book.average_price(true, 5.0), None);
+    }
+
+    #[test]
+    fn test_small_legs_use_top_of_book() {
+        let pair = MarketPair::test("BTC", "USDT", 100.0, 101.0).with_size(2.0);
+        assert_eq!(
+            pricing_model(&pair, true, 1.0, 0.5),
+            PricingModel::TopOfBook
+        );
+        assert_eq!(pricing_model(&pair, true, 1.5, 0.5), PricingModel::Depth);
+        // Buys spend quote: the ask shows 202 USDT
+        assert_eq!(
+            pricing_model(&pair, false, 100.0, 0.5),
+            PricingModel::TopOfBook
+        );
+        assert_eq!(pricing_model(&pair, false, 150.0, 0.5), PricingModel::Depth);
+    }
+}
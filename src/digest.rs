@@ -0,0 +1,158 @@
+use crate::journal::JournalEvent;
+use crate::models::{ArbitrageOpportunity, Pct};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Low-severity events batched for one sink, summarized once per interval instead of
+/// reported one by one
+#[derive(Debug)]
+pub struct Digest {
+    interval: Duration,
+    window_start: Instant,
+    alerts: u64,
+    above_execution: u64,
+    best: Option<(Pct, String)>,
+    /// Warnings by context, with the latest message
+    warnings: BTreeMap<String, (u64, String)>,
+}
+
+impl Digest {
+    /// None for an interval of 0, i.e. the sink reports every event as it happens
+    pub fn new(interval_secs: u64, now: Instant) -> Option<Self> {
+        (interval_secs > 0).then(|| Self {
+            interval: Duration::from_secs(interval_secs),
+            window_start: now,
+            alerts: 0,
+            above_execution: 0,
+            best: None,
+            warnings: BTreeMap::new(),
+        })
+    }
+
+    pub fn add_alert(&mut self, opp: &ArbitrageOpportunity, executable: bool) {
+        self.alerts += 1;
+        if executable {
+            self.above_execution += 1;
+        }
+        if self
+            .best
+            .as_ref()
+            .is_none_or(|(pct, _)| opp.estimated_profit_pct > *pct)
+        {
+            self.best = Some((opp.estimated_profit_pct, opp.display_pairs()));
+        }
+    }
+
+    pub fn add_warning(&mut self, context: &str, message: String) {
+        let entry = self
+            .warnings
+            .entry(context.to_string())
+            .or_insert((0, String::new()));
+        entry.0 += 1;
+        entry.1 = message;
+    }
+
+    fn is_empty(&self) -> bool {
+        self.alerts == 0 && self.warnings.is_empty()
+    }
+
+    /// Close the window once its interval is over, or at once with `force`. Returns the
+    /// window's length if anything happened in it.
+    fn close(&mut self, now: Instant, force: bool) -> Option<Duration> {
+        let elapsed = now.duration_since(self.window_start);
+        if elapsed < self.interval && !force {
+            return None;
+        }
+        self.window_start = now;
+        (!self.is_empty()).then_some(elapsed)
+    }
+
+    fn reset(&mut self) {
+        self.alerts = 0;
+        self.above_execution = 0;
+        self.best = None;
+        self.warnings.clear();
+    }
+
+    /// Log the summary of a closed window
+    pub fn log_if_due(&mut self, now: Instant, force: bool) {
+        let Some(elapsed) = self.close(now, force) else {
+            return;
+        };
+        if self.alerts > 0 {
+            let best = self
+                .best
+                .as_ref()
+                .map(|(pct, pairs)| format!(", best {pct:.4}% via {pairs}"))
+                .unwrap_or_default();
+            info!(
+                "📬 Digest of the last {elapsed:.0?}: {} alerts, {} above the execution threshold{best}",
+                self.alerts, self.above_execution
+            );
+        }
+        for (context, (count, last)) in &self.warnings {
+            info!("📬 Digest: {count}× {context} (last: {last})");
+        }
+        self.reset();
+    }
+
+    /// Journal event summarizing a closed window
+    pub fn journal_if_due(&mut self, now: Instant, force: bool) -> Option<JournalEvent> {
+        let elapsed = self.close(now, force)?;
+        let event = JournalEvent::AlertDigest {
+            window_secs: elapsed.as_secs(),
+            alerts: self.alerts,
+            above_execution_threshold: self.above_execution,
+            best_profit_pct: self.best.as_ref().map(|(pct, _)| pct.value()),
+            best_pairs: self.best.as_ref().map(|(_, pairs)| pairs.clone()),
+        };
+        self.reset();
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_summarizes_a_window_once_it_is_over() {
+        let start = Instant::now();
+        assert!(Digest::new(0, start).is_none());
+        let mut digest = Digest::new(60, start).unwrap();
+        digest.add_alert(
+            &ArbitrageOpportunity::test()
+                .with_pairs(&["BTCUSDT", "ETHBTC", "ETHUSDT"])
+                .with_profit_pct(0.1),
+            false,
+        );
+        digest.add_alert(
+            &ArbitrageOpportunity::test()
+                .with_pairs(&["SOLUSDT", "SOLBTC", "BTCUSDT"])
+                .with_profit_pct(0.3),
+            true,
+        );
+
+        assert!(digest
+            .journal_if_due(start + Duration::from_secs(30), false)
+            .is_none());
+        let Some(JournalEvent::AlertDigest {
+            window_secs,
+            alerts,
+            above_execution_threshold,
+            best_pairs,
+            ..
+        }) = digest.journal_if_due(start + Duration::from_secs(61), false)
+        else {
+            panic!("the window should have closed");
+        };
+        assert_eq!((window_secs, alerts, above_execution_threshold), (61, 2, 1));
+        assert_eq!(best_pairs.as_deref(), Some("SOLUSDT → SOLBTC → BTCUSDT"));
+
+        // A quiet window has nothing to report, even when forced at shutdown
+        assert!(digest
+            .journal_if_due(start + Duration::from_secs(200), true)
+            .is_none());
+    }
+}
@@ -0,0 +1,662 @@
+use crate::client::{BybitClient, RECV_WINDOW_MS};
+use crate::config::Config;
+use crate::credentials::Credentials;
+use crate::endpoints::EndpointSet;
+use crate::errors::BybitError;
+use crate::network::WsTransport;
+use crate::precision::{PrecisionManager, RoundingAudit, CACHE_FILE};
+use crate::websocket;
+use anyhow::Result;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Topic the public stream is probed with
+const PROBE_TOPIC: &str = "orderbook.1.BTCUSDT";
+const STREAM_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A stream the bot connects to at runtime
+#[derive(Debug, Clone, PartialEq)]
+struct RequiredStream {
+    name: String,
+    url: String,
+    /// The authenticated wallet stream rather than the public spot stream
+    private: bool,
+}
+
+/// Streams of every configured domain: the public spot stream, and the wallet stream when
+/// WALLET_STREAM is on. Without a configuration only the default public stream is known.
+fn required_streams(config: Option<&Config>) -> Vec<RequiredStream> {
+    let Some(config) = config else {
+        let endpoints = EndpointSet::for_domain("bybit.com", false);
+        return vec![RequiredStream {
+            name: "Public spot stream".to_string(),
+            url: endpoints.ws_url,
+            private: false,
+        }];
+    };
+    let mut streams = Vec::new();
+    for endpoints in config.endpoint_sets() {
+        streams.push(RequiredStream {
+            name: format!("Public spot stream ({})", endpoints.name),
+            url: endpoints.ws_url,
+            private: false,
+        });
+        if config.wallet_stream {
+            streams.push(RequiredStream {
+                name: format!("Wallet stream ({})", endpoints.name),
+                url: endpoints.private_ws_url,
+                private: true,
+            });
+        }
+    }
+    streams
+}
+
+/// Files the bot writes while running, as configured; disabled ones are left out
+fn writable_files(config: Option<&Config>) -> Vec<String> {
+    let mut files = vec![CACHE_FILE.to_string()];
+    if let Some(config) = config {
+        files.extend(
+            [
+                config.journal_path.as_str(),
+                &config.event_log_path,
+                &config.trade_db_path,
+                &config.market_data_path,
+            ]
+            .into_iter()
+            .chain(config.state_paths().into_iter().map(|(_, path)| path))
+            .filter(|path| !path.is_empty())
+            .map(|path| path.to_string()),
+        );
+    }
+    files
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "✅ PASS",
+            CheckStatus::Warn => "⚠️ WARN",
+            CheckStatus::Fail => "❌ FAIL",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub hint: Option<String>,
+}
+
+impl CheckResult {
+    fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &str, detail: impl Into<String>, hint: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            hint: Some(hint.to_string()),
+        }
+    }
+
+    fn fail(name: &str, detail: impl Into<String>, hint: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            hint: Some(hint.to_string()),
+        }
+    }
+}
+
+/// Run every diagnostic check. Checks that need credentials are skipped when
+/// the configuration could not be loaded.
+pub async fn run_checks(config: Result<Config>) -> Vec<CheckResult> {
+    let mut results = Vec::new();
+
+    let config = match config {
+        Ok(config) => {
+            results.push(check_config(&config));
+            Some(config)
+        }
+        Err(e) => {
+            results.push(CheckResult::fail(
+                "Configuration",
+                format!("{e:#}"),
+                "Copy .env.sample to .env and set BYBIT_API_KEY / BYBIT_API_SECRET",
+            ));
+            None
+        }
+    };
+
+    let base_url = config
+        .as_ref()
+        .map(|c| c.base_url.clone())
+        .unwrap_or_else(|| "https://api.bybit.com".to_string());
+    results.push(check_dns(&base_url).await);
+
+    let mut client = None;
+    if let Some(config) = &config {
+        match BybitClient::new(config.clone()) {
+            Ok(created) => {
+                results.push(check_latency(&created).await);
+                results.push(check_clock_drift(&created).await);
+                results.extend(check_api_key(&created).await);
+                results.push(check_wallet_access(&created).await);
+                results.push(check_rounding(&created).await);
+                client = Some(created);
+            }
+            Err(e) => results.push(CheckResult::fail(
+                "API client",
+                format!("Failed to create client: {e}"),
+                "Check that BYBIT_API_KEY contains only printable ASCII characters",
+            )),
+        }
+    }
+
+    let transport = match config.as_ref().map(WsTransport::from_config) {
+        Some(Ok(transport)) => transport,
+        Some(Err(e)) => {
+            results.push(CheckResult::fail(
+                "WebSocket proxy/TLS",
+                format!("{e:#}"),
+                "Check WS_PROXY and CA_BUNDLE_PATH",
+            ));
+            WsTransport::default()
+        }
+        None => WsTransport::default(),
+    };
+    for stream in required_streams(config.as_ref()) {
+        if !stream.private {
+            results.push(check_stream(&stream.name, &stream.url, &transport).await);
+        } else if let (Some(config), Some(client)) = (&config, &client) {
+            let credentials = Credentials {
+                api_key: config.api_key.clone(),
+                api_secret: config.api_secret.clone(),
+            };
+            results.push(check_wallet_stream(&stream, &credentials, client, &transport).await);
+        }
+    }
+
+    for file in writable_files(config.as_ref()) {
+        results.push(check_disk_writable(&file));
+    }
+
+    results
+}
+
+fn check_config(config: &Config) -> CheckResult {
+    let issues = config.validate();
+    if issues.is_empty() {
+        CheckResult::pass("Configuration", "All values look sane")
+    } else {
+        CheckResult::fail(
+            "Configuration",
+            issues.join("; "),
+            "Fix the listed values in .env",
+        )
+    }
+}
+
+async fn check_dns(base_url: &str) -> CheckResult {
+    let host = match url::Url::parse(base_url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+    {
+        Some(host) => host,
+        None => {
+            return CheckResult::fail(
+                "DNS",
+                format!("Invalid API base URL: {base_url}"),
+                "Check BYBIT_TESTNET / base URL configuration",
+            )
+        }
+    };
+
+    let start = Instant::now();
+    let lookup = tokio::net::lookup_host(format!("{host}:443")).await;
+    match lookup {
+        Ok(addrs) => {
+            let count = addrs.count();
+            CheckResult::pass(
+                "DNS",
+                format!(
+                    "{host} resolved to {count} address(es) in {}ms",
+                    start.elapsed().as_millis()
+                ),
+            )
+        }
+        Err(e) => CheckResult::fail(
+            "DNS",
+            format!("Failed to resolve {host}: {e}"),
+            "Check the server's DNS configuration (/etc/resolv.conf) and outbound network access",
+        ),
+    }
+}
+
+async fn check_latency(client: &BybitClient) -> CheckResult {
+    match client.check_connection().await {
+        Ok(latency) if latency < 200.0 => {
+            CheckResult::pass("REST latency", format!("{latency:.1}ms round trip"))
+        }
+        Ok(latency) => CheckResult::warn(
+            "REST latency",
+            format!("{latency:.1}ms round trip"),
+            "Host the bot closer to Bybit's servers (e.g. AWS ap-southeast-1) to reduce latency",
+        ),
+        Err(e) => CheckResult::fail(
+            "REST latency",
+            format!("Request failed: {e}"),
+            "Check outbound HTTPS access to the Bybit API",
+        ),
+    }
+}
+
+async fn check_clock_drift(client: &BybitClient) -> CheckResult {
+    let drift_ms = match client.sync_time().await {
+        // The offset is how far Bybit is ahead of the local clock
+        Ok(offset) => -offset,
+        Err(e) => {
+            return CheckResult::fail(
+                "Clock drift",
+                format!("Failed to fetch server time: {e:#}"),
+                "Check outbound HTTPS access to the Bybit API",
+            )
+        }
+    };
+    let detail = format!("Local clock is {drift_ms:+}ms relative to Bybit");
+    let hint = "Enable NTP time sync (e.g. `timedatectl set-ntp true`)";
+
+    if drift_ms.unsigned_abs() < 1000 {
+        CheckResult::pass("Clock drift", detail)
+    } else if drift_ms.unsigned_abs() < RECV_WINDOW_MS {
+        CheckResult::warn("Clock drift", detail, hint)
+    } else {
+        CheckResult::fail(
+            "Clock drift",
+            format!("{detail} (exceeds {RECV_WINDOW_MS}ms recv window)"),
+            hint,
+        )
+    }
+}
+
+pub async fn check_api_key(client: &BybitClient) -> Vec<CheckResult> {
+    match client.get_api_key_info().await {
+        Ok(info) => {
+            let mut results = Vec::new();
+            let note = info.note.clone().unwrap_or_default();
+            results.push(CheckResult::pass(
+                "API key",
+                format!(
+                    "Key accepted{}",
+                    if note.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" ({note})")
+                    }
+                ),
+            ));
+
+            if info.read_only != 0 {
+                results.push(CheckResult::fail(
+                    "API permissions",
+                    "Key is read-only; orders will be rejected",
+                    "Create a key with Read-Write access in Bybit API management",
+                ));
+            } else if !info.has_permission("Spot", "SpotTrade") {
+                results.push(CheckResult::fail(
+                    "API permissions",
+                    "Key lacks the Spot trading permission",
+                    "Enable Unified Trading → Spot → Trade for this key",
+                ));
+            } else {
+                results.push(CheckResult::pass(
+                    "API permissions",
+                    "Read-write key with Spot trading enabled",
+                ));
+            }
+
+            if info.is_ip_unrestricted() {
+                results.push(CheckResult::warn(
+                    "IP whitelist",
+                    "Key is not bound to any IP address",
+                    "Bind the key to this server's IP in Bybit API management for safety",
+                ));
+            } else {
+                results.push(CheckResult::pass(
+                    "IP whitelist",
+                    format!("Key bound to {}", info.ips.join(", ")),
+                ));
+            }
+
+            results
+        }
+        Err(e) => {
+            let message = e.to_string();
+            let error = BybitError::find(&e);
+            if matches!(error, Some(BybitError::IpNotWhitelisted { .. })) {
+                vec![CheckResult::fail(
+                    "IP whitelist",
+                    "This server's IP is not whitelisted for the key",
+                    "Add this server's public IP to the key's whitelist in Bybit API management",
+                )]
+            } else if matches!(error, Some(BybitError::InvalidTimestamp { .. })) {
+                vec![CheckResult::fail(
+                    "API key",
+                    "Request rejected because of timestamp skew",
+                    "Sync the system clock (see the clock drift check)",
+                )]
+            } else if error.is_some_and(BybitError::is_unauthorized) {
+                vec![CheckResult::fail(
+                    "API key",
+                    format!("Key or signature rejected: {message}"),
+                    "Verify BYBIT_API_KEY / BYBIT_API_SECRET and BYBIT_TESTNET match the key's environment",
+                )]
+            } else {
+                vec![CheckResult::fail(
+                    "API key",
+                    format!("Failed to query key info: {message}"),
+                    "Verify the API credentials and network access",
+                )]
+            }
+        }
+    }
+}
+
+async fn check_wallet_access(client: &BybitClient) -> CheckResult {
+    match client.get_wallet_balance(None).await {
+        Ok(result) => {
+            let coins: usize = result.list.iter().map(|a| a.coin.len()).sum();
+            CheckResult::pass(
+                "Wallet access",
+                format!("Unified wallet readable ({coins} coin balance(s))"),
+            )
+        }
+        Err(e) => CheckResult::fail(
+            "Wallet access",
+            format!("Failed to read wallet balance: {e}"),
+            "Enable the Wallet read permission and make sure the account is a Unified Trading Account",
+        ),
+    }
+}
+
+async fn check_rounding(client: &BybitClient) -> CheckResult {
+    let instruments = match client.get_all_spot_instruments().await {
+        Ok(instruments) => instruments,
+        Err(e) => {
+            return CheckResult::fail(
+                "Rounding audit",
+                format!("Failed to fetch instruments: {e}"),
+                "Check outbound HTTPS access to the Bybit API",
+            )
+        }
+    };
+    let mut manager = PrecisionManager::from_instruments(instruments);
+    // Learned decimals take priority when formatting, so audit them too
+    if let Err(e) = manager.load_cache_from_file("precision_cache.json").await {
+        return CheckResult::fail(
+            "Rounding audit",
+            format!("Failed to load precision cache: {e}"),
+            "Delete precision_cache.json; it is rebuilt from the instrument rules on startup",
+        );
+    }
+    rounding_result(&manager.audit_rounding())
+}
+
+fn rounding_result(audit: &RoundingAudit) -> CheckResult {
+    match audit.issues.first() {
+        None => CheckResult::pass(
+            "Rounding audit",
+            format!(
+                "{} quantities across {} symbols round safely",
+                audit.quantities_checked, audit.symbols
+            ),
+        ),
+        Some(first) => CheckResult::fail(
+            "Rounding audit",
+            format!("{} issue(s), e.g. {first}", audit.issues.len()),
+            "Delete precision_cache.json so decimals are relearned from the instrument rules",
+        ),
+    }
+}
+
+async fn check_stream(name: &str, url: &str, transport: &WsTransport) -> CheckResult {
+    match websocket::probe_stream(url, PROBE_TOPIC, transport, STREAM_TIMEOUT).await {
+        Ok(elapsed) => CheckResult::pass(
+            name,
+            format!(
+                "First {PROBE_TOPIC} message after {}ms",
+                elapsed.as_millis()
+            ),
+        ),
+        Err(e) => CheckResult::fail(
+            name,
+            format!("{e}"),
+            "Check that outbound WebSocket (wss, port 443) traffic is allowed",
+        ),
+    }
+}
+
+async fn check_wallet_stream(
+    stream: &RequiredStream,
+    credentials: &Credentials,
+    client: &BybitClient,
+    transport: &WsTransport,
+) -> CheckResult {
+    let probe = crate::wallet_stream::probe(
+        &stream.url,
+        credentials,
+        client.time_offset(),
+        transport,
+        STREAM_TIMEOUT,
+    );
+    match probe.await {
+        Ok(elapsed) => CheckResult::pass(
+            &stream.name,
+            format!("Authenticated and subscribed to wallet in {}ms", elapsed.as_millis()),
+        ),
+        Err(e) => CheckResult::fail(
+            &stream.name,
+            format!("{e:#}"),
+            "Check the key's Wallet permission and outbound WebSocket access, or set WALLET_STREAM=false",
+        ),
+    }
+}
+
+fn check_disk_writable(file: &str) -> CheckResult {
+    let name = format!("Disk ({file})");
+    let path = Path::new(file);
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    // Missing directories are created on first write, so their nearest existing parent
+    // must be writable instead
+    let dir = dir
+        .ancestors()
+        .find(|d| d.as_os_str().is_empty() || d.exists())
+        .filter(|d| !d.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+
+    let probe = dir.join(".doctor_write_test");
+    if let Err(e) = std::fs::write(&probe, b"ok") {
+        return CheckResult::fail(
+            &name,
+            format!("Directory {} is not writable: {e}", dir.display()),
+            "Run the bot from a writable working directory or fix permissions",
+        );
+    }
+    std::fs::remove_file(&probe).ok();
+
+    if path.exists() {
+        if let Err(e) = std::fs::OpenOptions::new().append(true).open(path) {
+            return CheckResult::fail(
+                &name,
+                format!("Existing file is not writable: {e}"),
+                "Fix the file's ownership/permissions (chmod u+w)",
+            );
+        }
+    }
+
+    CheckResult::pass(&name, format!("{} is writable", dir.display()))
+}
+
+/// Print the results as a pass/fail table with remediation hints
+pub fn print_report(results: &[CheckResult]) {
+    println!();
+    println!("🩺 Doctor Report");
+    println!("{}", "─".repeat(80));
+    for result in results {
+        println!(
+            "{:<9} {:<22} {}",
+            result.status.label(),
+            result.name,
+            result.detail
+        );
+        if let Some(hint) = &result.hint {
+            println!("{:<9} {:<22} ↳ {}", "", "", hint);
+        }
+    }
+    println!("{}", "─".repeat(80));
+
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    println!(
+        "{} passed, {} warning(s), {} failed",
+        count(CheckStatus::Pass),
+        count(CheckStatus::Warn),
+        count(CheckStatus::Fail)
+    );
+}
+
+/// True when no check failed
+pub fn all_passed(results: &[CheckResult]) -> bool {
+    results.iter().all(|r| r.status != CheckStatus::Fail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_check_reports_issues() {
+        let config = Config {
+            order_size: 0.0,
+            ..Config::test_config()
+        };
+        let result = check_config(&config);
+        assert_eq!(result.status, CheckStatus::Fail);
+        assert!(result.detail.contains("ORDER_SIZE"));
+
+        let result = check_config(&Config::test_config());
+        assert_eq!(result.status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_disk_check_on_writable_dir() {
+        let dir = std::env::temp_dir().join("bybit_doctor_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("cache.json");
+        let result = check_disk_writable(file.to_str().unwrap());
+        assert_eq!(result.status, CheckStatus::Pass);
+
+        // A directory the bot would create is checked through its parent
+        let nested = dir.join("state").join("ledger.json");
+        let result = check_disk_writable(nested.to_str().unwrap());
+        assert_eq!(result.status, CheckStatus::Pass);
+        assert!(!dir.join("state").exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_streams_and_files_follow_the_config() {
+        let mut config = Config {
+            testnet: true,
+            endpoint_domains: "bybit.com,bytick.com".to_string(),
+            wallet_stream: true,
+            journal_path: "data/journal.jsonl".to_string(),
+            event_log_path: String::new(),
+            trade_db_path: "data/trades.db".to_string(),
+            market_data_path: "data/market.jsonl".to_string(),
+            execution_state_path: "state/checkpoint.json".to_string(),
+            ..Config::test_config()
+        };
+        let streams = required_streams(Some(&config));
+        let urls: Vec<(&str, bool)> = streams
+            .iter()
+            .map(|s| (s.url.as_str(), s.private))
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                ("wss://stream.bybit.com/v5/public/spot", false),
+                ("wss://stream-testnet.bybit.com/v5/private", true),
+                ("wss://stream.bytick.com/v5/public/spot", false),
+                ("wss://stream-testnet.bytick.com/v5/private", true),
+            ]
+        );
+        config.wallet_stream = false;
+        assert!(required_streams(Some(&config)).iter().all(|s| !s.private));
+
+        let files = writable_files(Some(&config));
+        for file in [
+            CACHE_FILE,
+            "data/journal.jsonl",
+            "data/trades.db",
+            "data/market.jsonl",
+            "state/checkpoint.json",
+            crate::positions::POSITIONS_FILE,
+        ] {
+            assert!(files.iter().any(|f| f == file), "{file} missing");
+        }
+        assert!(!files.iter().any(String::is_empty));
+    }
+
+    #[test]
+    fn test_rounding_result_fails_on_issues() {
+        let mut audit = RoundingAudit {
+            symbols: 1,
+            quantities_checked: 6,
+            issues: Vec::new(),
+        };
+        assert_eq!(rounding_result(&audit).status, CheckStatus::Pass);
+
+        audit.issues.push(crate::precision::RoundingIssue {
+            symbol: "PEPEUSDT".to_string(),
+            balance: 1500.5,
+            formatted: "1500.50".to_string(),
+            problem: "is not a multiple of qty_step 1".to_string(),
+        });
+        let result = rounding_result(&audit);
+        assert_eq!(result.status, CheckStatus::Fail);
+        assert!(result.detail.contains("PEPEUSDT: 1500.50"));
+    }
+
+    #[test]
+    fn test_all_passed_ignores_warnings() {
+        let results = vec![
+            CheckResult::pass("a", "ok"),
+            CheckResult::warn("b", "meh", "hint"),
+        ];
+        assert!(all_passed(&results));
+
+        let results = vec![CheckResult::fail("c", "bad", "hint")];
+        assert!(!all_passed(&results));
+    }
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Where leftover coins are kept so the inventory survives restarts
+pub const DUST_FILE: &str = "dust_inventory.json";
+
+/// Coins executions left behind, accumulated across trades
+#[derive(Debug, Default)]
+pub struct DustInventory {
+    coins: BTreeMap<String, f64>,
+    path: Option<PathBuf>,
+}
+
+impl DustInventory {
+    /// Restore the inventory of a previous run; a missing or unreadable file starts empty
+    pub fn load(path: &Path) -> Self {
+        Self {
+            coins: crate::persist::load_json(path),
+            path: Some(path.to_path_buf()),
+        }
+    }
+
+    pub fn coins(&self) -> &BTreeMap<String, f64> {
+        &self.coins
+    }
+
+    /// Add an execution's leftovers and persist the inventory
+    pub fn record(&mut self, dust: &BTreeMap<String, f64>) {
+        if dust.is_empty() {
+            return;
+        }
+        for (coin, qty) in dust {
+            *self.coins.entry(coin.clone()).or_insert(0.0) += qty;
+        }
+        if let Err(e) = self.save() {
+            warn!("⚠️ Failed to save dust inventory: {e:#}");
+        }
+    }
+
+    /// Drop a coin that was swept, persisting the inventory
+    pub fn remove(&mut self, coin: &str) {
+        if self.coins.remove(coin).is_none() {
+            return;
+        }
+        if let Err(e) = self.save() {
+            warn!("⚠️ Failed to save dust inventory: {e:#}");
+        }
+    }
+
+    /// USD value of the inventory at `usd_price`; coins without a price are left out
+    pub fn value_usd(&self, usd_price: impl Fn(&str) -> Option<f64>) -> f64 {
+        self.coins
+            .iter()
+            .filter_map(|(coin, qty)| usd_price(coin).map(|price| qty * price))
+            .sum()
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        crate::persist::save_json(path, &self.coins)?;
+        debug!("💾 Saved dust inventory to {}", path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dust_accumulates_per_coin_across_restarts() {
+        let path = std::env::temp_dir().join(format!("dust_test_{}.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let mut inventory = DustInventory::load(&path);
+        inventory.record(&BTreeMap::from([("BTC".to_string(), 0.000_01)]));
+        inventory.record(&BTreeMap::from([
+            ("BTC".to_string(), 0.000_02),
+            ("PEPE".to_string(), 150.0),
+        ]));
+
+        let inventory = DustInventory::load(&path);
+        assert!((inventory.coins()["BTC"] - 0.000_03).abs() < 1e-12);
+        // PEPE has no price, so only the BTC counts
+        let value = inventory.value_usd(|coin| (coin == "BTC").then_some(50_000.0));
+        assert!((value - 1.5).abs() < 1e-9, "{value}");
+        std::fs::remove_file(&path).ok();
+    }
+}
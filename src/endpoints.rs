@@ -0,0 +1,299 @@
+use crate::client::BybitClient;
+use crate::supervisor;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info, warn};
+
+/// Consecutive failed requests on the active endpoint before failing over
+const FAILURES_BEFORE_FAILOVER: u32 = 3;
+/// A probed endpoint must be at least this much faster than the active one to take over,
+/// so two similar endpoints don't flap
+const SWITCH_MARGIN: f64 = 0.75;
+
+/// REST and WebSocket URLs served from one Bybit domain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointSet {
+    /// The domain, e.g. `bybit.com` or `bytick.com`
+    pub name: String,
+    pub rest_url: String,
+    pub ws_url: String,
+    /// Authenticated stream of account updates, e.g. wallet balances
+    pub private_ws_url: String,
+}
+
+impl EndpointSet {
+    /// REST on `api.<domain>` (`api-testnet.` on testnet), public spot stream on `stream.<domain>`
+    /// and the private stream on `stream.<domain>` (`stream-testnet.` on testnet)
+    pub fn for_domain(domain: &str, testnet: bool) -> Self {
+        let api_host = if testnet { "api-testnet" } else { "api" };
+        let stream_host = if testnet { "stream-testnet" } else { "stream" };
+        Self {
+            name: domain.to_string(),
+            rest_url: format!("https://{api_host}.{domain}"),
+            ws_url: format!("wss://stream.{domain}/v5/public/spot"),
+            private_ws_url: format!("wss://{stream_host}.{domain}/v5/private"),
+        }
+    }
+
+    /// Get the wallet balance endpoint
+    pub fn wallet_balance_endpoint(&self) -> String {
+        format!("{}/v5/account/wallet-balance", self.rest_url)
+    }
+
+    /// Get the instruments info endpoint
+    pub fn instruments_info_endpoint(&self) -> String {
+        format!("{}/v5/market/instruments-info", self.rest_url)
+    }
+
+    /// Get the ticker endpoint for 24hr price data
+    pub fn tickers_endpoint(&self) -> String {
+        format!("{}/v5/market/tickers", self.rest_url)
+    }
+}
+
+/// Parse a comma-separated list of Bybit domains, e.g. `bybit.com,bytick.com`
+pub fn parse_domains(spec: &str) -> Result<Vec<String>> {
+    let domains: Vec<String> = spec
+        .split(',')
+        .map(|d| d.trim().to_lowercase())
+        .filter(|d| !d.is_empty())
+        .collect();
+    if domains.is_empty() {
+        bail!("no domains given");
+    }
+    for domain in &domains {
+        if domain.contains("://") || domain.contains('/') || !domain.contains('.') {
+            bail!("'{domain}' is not a bare domain like bybit.com");
+        }
+    }
+    Ok(domains)
+}
+
+/// An endpoint set as reported by /status and /metrics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointStatus {
+    pub name: String,
+    pub active: bool,
+    /// Latest probe round trip; None if unreachable or not yet probed
+    pub latency_ms: Option<f64>,
+}
+
+/// The endpoint sets to choose from and which one is in use. Shared by every clone of the
+/// client and by the WebSocket connections, so a failover moves all of them.
+#[derive(Debug)]
+pub struct Endpoints {
+    sets: Vec<EndpointSet>,
+    active: AtomicUsize,
+    consecutive_failures: AtomicU32,
+    switches: AtomicU64,
+    latency_ms: Mutex<Vec<Option<f64>>>,
+}
+
+impl Endpoints {
+    pub fn new(sets: Vec<EndpointSet>) -> Arc<Self> {
+        assert!(!sets.is_empty(), "at least one endpoint set is required");
+        Arc::new(Self {
+            latency_ms: Mutex::new(vec![None; sets.len()]),
+            sets,
+            active: AtomicUsize::new(0),
+            consecutive_failures: AtomicU32::new(0),
+            switches: AtomicU64::new(0),
+        })
+    }
+
+    pub fn sets(&self) -> &[EndpointSet] {
+        &self.sets
+    }
+
+    pub fn active(&self) -> &EndpointSet {
+        &self.sets[self.active.load(Ordering::Relaxed)]
+    }
+
+    /// How many times the active endpoint has changed
+    pub fn switches(&self) -> u64 {
+        self.switches.load(Ordering::Relaxed)
+    }
+
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        let active = self.active.load(Ordering::Relaxed);
+        let latency = self.latency_ms.lock().unwrap_or_else(|e| e.into_inner());
+        self.sets
+            .iter()
+            .enumerate()
+            .map(|(i, set)| EndpointStatus {
+                name: set.name.clone(),
+                active: i == active,
+                latency_ms: latency[i],
+            })
+            .collect()
+    }
+
+    /// A request on the active endpoint reached the exchange
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+    }
+
+    /// A request on the active endpoint failed in transport or with a server error.
+    /// Enough in a row fail over to the fastest other endpoint.
+    pub fn record_failure(&self) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures < FAILURES_BEFORE_FAILOVER || self.sets.len() < 2 {
+            return;
+        }
+        let active = self.active.load(Ordering::Relaxed);
+        let latency = self
+            .latency_ms
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        // Fastest other endpoint, or the next one if none has been probed successfully
+        let fallback = (0..self.sets.len())
+            .filter(|&i| i != active)
+            .filter_map(|i| Some((i, latency[i]?)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or((active + 1) % self.sets.len(), |(i, _)| i);
+        self.switch_to(fallback, &format!("{failures} failed requests in a row"));
+    }
+
+    /// Record probe results, one per set, and move to a clearly faster endpoint
+    pub fn apply_probe(&self, results: &[Option<f64>]) {
+        *self.latency_ms.lock().unwrap_or_else(|e| e.into_inner()) = results.to_vec();
+        let active = self.active.load(Ordering::Relaxed);
+        let Some((fastest, fastest_ms)) = results
+            .iter()
+            .enumerate()
+            .filter_map(|(i, ms)| Some((i, (*ms)?)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+        else {
+            warn!("⚠️ No Bybit endpoint answered the latency probe");
+            return;
+        };
+        if fastest == active {
+            return;
+        }
+        match results[active] {
+            None => self.switch_to(fastest, "active endpoint did not answer the probe"),
+            Some(active_ms) if fastest_ms < active_ms * SWITCH_MARGIN => {
+                self.switch_to(fastest, &format!("{fastest_ms:.0}ms vs {active_ms:.0}ms"))
+            }
+            Some(_) => {}
+        }
+    }
+
+    fn switch_to(&self, index: usize, reason: &str) {
+        let previous = self.active.swap(index, Ordering::Relaxed);
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+        if previous != index {
+            self.switches.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "🌐 Switching endpoint {} → {} ({reason})",
+                self.sets[previous].name, self.sets[index].name
+            );
+        }
+    }
+}
+
+/// Measure every endpoint set once and pick the fastest
+pub async fn probe(client: &BybitClient) {
+    let endpoints = client.endpoints();
+    let mut results = Vec::with_capacity(endpoints.sets().len());
+    for set in endpoints.sets() {
+        let latency = match client.latency_to(&set.rest_url).await {
+            Ok(ms) => Some(ms),
+            Err(e) => {
+                debug!("🌐 Probe of {} failed: {e:#}", set.name);
+                None
+            }
+        };
+        results.push(latency);
+    }
+    endpoints.apply_probe(&results);
+}
+
+/// Probe at startup, then every `interval_secs` on a supervised task. Does nothing with a
+/// single endpoint set.
+pub async fn start_probing(client: &BybitClient, interval_secs: u64, restart_on_panic: bool) {
+    if client.endpoints().sets().len() < 2 {
+        return;
+    }
+    probe(client).await;
+    info!(
+        "🌐 Using endpoint {} of {:?}",
+        client.endpoints().active().name,
+        client
+            .endpoints()
+            .sets()
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect::<Vec<_>>()
+    );
+    let client = client.clone();
+    supervisor::spawn_supervised("Endpoint probe".to_string(), restart_on_panic, move || {
+        let client = client.clone();
+        async move {
+            loop {
+                sleep(Duration::from_secs(interval_secs)).await;
+                probe(&client).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoints() -> Arc<Endpoints> {
+        Endpoints::new(
+            ["bybit.com", "bytick.com", "example.com"]
+                .iter()
+                .map(|d| EndpointSet::for_domain(d, false))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_probe_switches_only_to_a_clearly_faster_endpoint() {
+        let endpoints = endpoints();
+        assert_eq!(
+            endpoints.active().ws_url,
+            "wss://stream.bybit.com/v5/public/spot"
+        );
+
+        endpoints.apply_probe(&[Some(100.0), Some(90.0), None]);
+        assert_eq!(endpoints.active().name, "bybit.com");
+        endpoints.apply_probe(&[Some(100.0), Some(60.0), None]);
+        assert_eq!(endpoints.active().rest_url, "https://api.bytick.com");
+
+        // An unreachable active endpoint loses to any that answered
+        endpoints.apply_probe(&[Some(80.0), None, Some(500.0)]);
+        assert_eq!(endpoints.active().name, "bybit.com");
+        assert_eq!(endpoints.switches(), 2);
+        assert!(endpoints.status()[0].active);
+        assert_eq!(endpoints.status()[2].latency_ms, Some(500.0));
+    }
+
+    #[test]
+    fn test_repeated_failures_fail_over_to_the_fastest_other() {
+        let endpoints = endpoints();
+        endpoints.apply_probe(&[Some(50.0), Some(80.0), Some(70.0)]);
+
+        endpoints.record_failure();
+        endpoints.record_failure();
+        endpoints.record_success();
+        endpoints.record_failure();
+        endpoints.record_failure();
+        assert_eq!(endpoints.active().name, "bybit.com");
+        endpoints.record_failure();
+        assert_eq!(endpoints.active().name, "example.com");
+
+        assert!(parse_domains("https://api.bybit.com").is_err());
+        assert_eq!(
+            parse_domains(" bybit.com, BYTICK.com ").unwrap(),
+            ["bybit.com", "bytick.com"]
+        );
+    }
+}
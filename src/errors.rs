@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// An error Bybit answered a request with, by its retCode. Displays as Bybit's
+/// `API Error <code>: <msg>`; codes without a variant of their own are kept as `Other`.
+#[derive(Debug, Clone, PartialEq, Error, Serialize, Deserialize)]
+pub enum BybitError {
+    /// 10002: the request timestamp is outside the receive window
+    #[error("API Error {code}: {msg}")]
+    InvalidTimestamp { code: i32, msg: String },
+    /// 10003, 10004: the key is unknown or the signature doesn't match it
+    #[error("API Error {code}: {msg}")]
+    InvalidCredentials { code: i32, msg: String },
+    /// 10005: the key lacks the permission the request needs
+    #[error("API Error {code}: {msg}")]
+    PermissionDenied { code: i32, msg: String },
+    /// 10006, 10018: too many requests for the key or the IP
+    #[error("API Error {code}: {msg}")]
+    RateLimited { code: i32, msg: String },
+    /// 10010: the request came from an IP the key isn't bound to
+    #[error("API Error {code}: {msg}")]
+    IpNotWhitelisted { code: i32, msg: String },
+    /// 10016: an internal error while Bybit's services restart
+    #[error("API Error {code}: {msg}")]
+    ServiceRestarting { code: i32, msg: String },
+    /// 10024, 170348: the account may not trade from this region or product
+    #[error("API Error {code}: {msg}")]
+    GeoRestricted { code: i32, msg: String },
+    /// 170121: no such symbol
+    #[error("API Error {code}: {msg}")]
+    InvalidSymbol { code: i32, msg: String },
+    /// 170131: the wallet holds less than the order spends
+    #[error("API Error {code}: {msg}")]
+    InsufficientBalance { code: i32, msg: String },
+    /// 170137, 170148: the quantity has more decimals than the symbol allows
+    #[error("API Error {code}: {msg}")]
+    PrecisionTooLong { code: i32, msg: String },
+    /// 170213, 110001: the order doesn't exist, or no longer can be changed
+    #[error("API Error {code}: {msg}")]
+    OrderNotFound { code: i32, msg: String },
+    #[error("API Error {code}: {msg}")]
+    Other { code: i32, msg: String },
+    /// An HTTP error status before any retCode, e.g. 401 for a malformed key
+    #[error("HTTP error {status}: {body}")]
+    Http { status: u16, body: String },
+}
+
+impl BybitError {
+    /// The variant of a non-zero retCode and its message
+    pub fn from_response(code: i32, msg: impl Into<String>) -> Self {
+        let msg = msg.into();
+        match code {
+            10002 => Self::InvalidTimestamp { code, msg },
+            10003 | 10004 => Self::InvalidCredentials { code, msg },
+            10005 => Self::PermissionDenied { code, msg },
+            10006 | 10018 => Self::RateLimited { code, msg },
+            10010 => Self::IpNotWhitelisted { code, msg },
+            10016 => Self::ServiceRestarting { code, msg },
+            10024 | 170348 => Self::GeoRestricted { code, msg },
+            170121 => Self::InvalidSymbol { code, msg },
+            170131 => Self::InsufficientBalance { code, msg },
+            170137 | 170148 => Self::PrecisionTooLong { code, msg },
+            170213 | 110001 => Self::OrderNotFound { code, msg },
+            _ => Self::Other { code, msg },
+        }
+    }
+
+    /// The retCode, or the status of an HTTP error
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::InvalidTimestamp { code, .. }
+            | Self::InvalidCredentials { code, .. }
+            | Self::PermissionDenied { code, .. }
+            | Self::RateLimited { code, .. }
+            | Self::IpNotWhitelisted { code, .. }
+            | Self::ServiceRestarting { code, .. }
+            | Self::GeoRestricted { code, .. }
+            | Self::InvalidSymbol { code, .. }
+            | Self::InsufficientBalance { code, .. }
+            | Self::PrecisionTooLong { code, .. }
+            | Self::OrderNotFound { code, .. }
+            | Self::Other { code, .. } => *code,
+            Self::Http { status, .. } => i32::from(*status),
+        }
+    }
+
+    /// Whether the key or its signature was rejected, by retCode or with HTTP 401
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(
+            self,
+            Self::InvalidCredentials { .. } | Self::Http { status: 401, .. }
+        )
+    }
+
+    /// The Bybit error anywhere in an error's chain of causes, if Bybit answered at all
+    pub fn find(error: &anyhow::Error) -> Option<&BybitError> {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<BybitError>())
+    }
+
+    /// Parse `API Error <code>: <msg>` or `HTTP error <status>: <body>` out of a scripted
+    /// error message
+    #[cfg(test)]
+    pub fn parse(text: &str) -> Option<Self> {
+        if let Some((_, rest)) = text.split_once("HTTP error ") {
+            let (status, body) = rest.split_once(": ")?;
+            return Some(Self::Http {
+                status: status.parse().ok()?,
+                body: body.to_string(),
+            });
+        }
+        let (_, rest) = text.split_once("API Error ")?;
+        let (code, msg) = rest.split_once(": ")?;
+        Some(Self::from_response(code.parse().ok()?, msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_ret_codes_map_to_variants_and_survive_context() {
+        let error = BybitError::from_response(170131, "Insufficient balance.");
+        assert!(matches!(error, BybitError::InsufficientBalance { .. }));
+        assert_eq!(error.to_string(), "API Error 170131: Insufficient balance.");
+        assert_eq!(BybitError::from_response(99999, "new").code(), 99999);
+
+        let wrapped = Err::<(), _>(anyhow::Error::new(error.clone()))
+            .context("Order placement failed")
+            .unwrap_err();
+        assert_eq!(BybitError::find(&wrapped), Some(&error));
+        assert_eq!(
+            format!("{wrapped:#}"),
+            "Order placement failed: API Error 170131: Insufficient balance."
+        );
+        assert!(BybitError::find(&anyhow::anyhow!("connection reset")).is_none());
+
+        let unauthorized = BybitError::parse("HTTP error 401: Unauthorized").unwrap();
+        assert!(unauthorized.is_unauthorized());
+        assert_eq!(unauthorized.code(), 401);
+        assert!(BybitError::from_response(10003, "Invalid api_key.").is_unauthorized());
+    }
+}
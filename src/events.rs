@@ -0,0 +1,432 @@
+use crate::bundle::{OrderRecord, Quote};
+use crate::models::{ArbitrageOpportunity, MarketPair, OrderInfo};
+use crate::writer::{RecordSink, SupervisedWriter};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Domain events of a session, enough to rebuild the bot's view of the market and account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SessionEvent {
+    SessionStarted {
+        version: String,
+        dry_run: bool,
+    },
+    /// A setting's effective value at startup, or after the bot changed it
+    ConfigChanged {
+        key: String,
+        value: String,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// Top of book of every symbol that moved since the previous summary
+    QuoteSummary {
+        quotes: BTreeMap<String, Quote>,
+    },
+    /// Opportunity picked for execution
+    OpportunityDetected {
+        opportunity: ArbitrageOpportunity,
+        amount: f64,
+    },
+    OrderPlaced {
+        order: OrderRecord,
+    },
+    OrderFilled {
+        order: OrderInfo,
+    },
+    /// Every non-zero balance, whenever one of them changed
+    BalancesChanged {
+        balances: BTreeMap<String, f64>,
+    },
+    TradeFinished {
+        path: String,
+        success: bool,
+        profit_usd: f64,
+        error: Option<String>,
+    },
+    SessionEnded {
+        cycles: u64,
+        trades_completed: u64,
+    },
+}
+
+/// One line of the event log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRecord {
+    /// Increases by one per event, across sessions appending to the same file
+    pub seq: u64,
+    pub ts: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: SessionEvent,
+}
+
+/// Append-only JSONL log of session events with sequence numbers, written on a
+/// supervised task
+pub struct EventLog {
+    /// Next sequence number; held while sending so records reach the file in order
+    next: Mutex<u64>,
+    writer: SupervisedWriter<Box<EventRecord>>,
+}
+
+impl EventLog {
+    /// Append to `path`, continuing the sequence of any records already in it
+    pub fn spawn(path: &Path, restart_on_panic: bool) -> Result<Self> {
+        let next_seq = Self::read(path)?.last().map_or(1, |record| record.seq + 1);
+        let file = open(path)?;
+        let path = path.to_path_buf();
+        let writer =
+            SupervisedWriter::spawn("Event log writer", restart_on_panic, file, move || {
+                open(&path)
+            });
+        Ok(Self {
+            next: Mutex::new(next_seq),
+            writer,
+        })
+    }
+
+    /// Every record of a log in sequence order, skipping lines that don't parse (e.g. a
+    /// line cut short by a crash). A missing file has no records.
+    pub fn read(path: &Path) -> Result<Vec<EventRecord>> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read event log {}", path.display()))?;
+        let mut records: Vec<EventRecord> = contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        records.sort_by_key(|record| record.seq);
+        Ok(records)
+    }
+
+    /// Append an event; failures are logged rather than interrupting trading
+    pub fn record(&self, event: SessionEvent) {
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        let record = EventRecord {
+            seq: *next,
+            ts: Utc::now(),
+            event,
+        };
+        if let Err(e) = self.writer.send(Box::new(record)) {
+            warn!("⚠️ Failed to write event: {e}");
+            return;
+        }
+        *next += 1;
+    }
+
+    /// Wait until every event recorded so far is on disk
+    pub async fn flush(&self) {
+        self.writer.flush().await;
+    }
+}
+
+fn open(path: &Path) -> Result<LogFile> {
+    crate::logger::create_parent_dir(path)?;
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open event log {}", path.display()))?;
+    Ok(LogFile(BufWriter::new(file)))
+}
+
+/// The log file, flushed whenever the writer's queue runs dry
+struct LogFile(BufWriter<File>);
+
+impl RecordSink<Box<EventRecord>> for LogFile {
+    fn write(&mut self, record: Box<EventRecord>, more: bool) -> Result<()> {
+        write_record(&mut self.0, &record, !more)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(self.0.flush()?)
+    }
+}
+
+fn write_record(writer: &mut BufWriter<File>, record: &EventRecord, flush: bool) -> Result<()> {
+    let line = serde_json::to_string(record)?;
+    writeln!(writer, "{line}")?;
+    if flush {
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Turns the quotes and balances the bot holds into events for what changed
+pub struct ChangeTracker {
+    /// None never summarizes quotes
+    quote_interval: Option<Duration>,
+    last_quote_summary: Option<Instant>,
+    quotes: HashMap<String, Quote>,
+    balances: BTreeMap<String, f64>,
+}
+
+impl ChangeTracker {
+    /// Summarize quotes every `quote_interval_secs` (0 never does)
+    pub fn new(quote_interval_secs: u64) -> Self {
+        Self {
+            quote_interval: (quote_interval_secs > 0)
+                .then(|| Duration::from_secs(quote_interval_secs)),
+            last_quote_summary: None,
+            quotes: HashMap::new(),
+            balances: BTreeMap::new(),
+        }
+    }
+
+    /// Quotes that moved since the last summary, once every quote interval
+    pub fn quotes(&mut self, pairs: &[MarketPair], now: Instant) -> Option<SessionEvent> {
+        let interval = self.quote_interval?;
+        if self
+            .last_quote_summary
+            .is_some_and(|last| now.duration_since(last) < interval)
+        {
+            return None;
+        }
+        self.last_quote_summary = Some(now);
+        let mut changed = BTreeMap::new();
+        for pair in pairs
+            .iter()
+            .filter(|p| p.bid_price > 0.0 && p.ask_price > 0.0)
+        {
+            let quote = Quote::from_pair(pair);
+            if self.quotes.get(&pair.symbol) != Some(&quote) {
+                self.quotes.insert(pair.symbol.clone(), quote);
+                changed.insert(pair.symbol.clone(), quote);
+            }
+        }
+        (!changed.is_empty()).then_some(SessionEvent::QuoteSummary { quotes: changed })
+    }
+
+    /// Every balance, if any changed
+    pub fn balances(&mut self, balances: BTreeMap<String, f64>) -> Option<SessionEvent> {
+        if balances == self.balances {
+            return None;
+        }
+        self.balances = balances.clone();
+        Some(SessionEvent::BalancesChanged { balances })
+    }
+}
+
+/// The bot's state rebuilt from its event log
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayState {
+    pub events: u64,
+    pub last_seq: u64,
+    pub last_event_at: Option<DateTime<Utc>>,
+    /// Sequence numbers missing from the log, as inclusive ranges
+    pub gaps: Vec<(u64, u64)>,
+    pub sessions: u64,
+    pub version: Option<String>,
+    pub dry_run: bool,
+    pub config: BTreeMap<String, String>,
+    pub quotes: BTreeMap<String, Quote>,
+    pub balances: BTreeMap<String, f64>,
+    pub opportunities: u64,
+    pub orders_placed: u64,
+    pub orders_rejected: u64,
+    pub fills: u64,
+    pub trades: u64,
+    pub trades_succeeded: u64,
+    pub realized_profit_usd: f64,
+    pub last_error: Option<String>,
+}
+
+impl ReplayState {
+    /// Apply records in order, stopping after sequence number `until` if given
+    pub fn replay(records: &[EventRecord], until: Option<u64>) -> Self {
+        let mut state = Self::default();
+        for record in records
+            .iter()
+            .take_while(|r| until.is_none_or(|until| r.seq <= until))
+        {
+            state.apply(record);
+        }
+        state
+    }
+
+    pub fn apply(&mut self, record: &EventRecord) {
+        if self.events > 0 && record.seq > self.last_seq + 1 {
+            self.gaps.push((self.last_seq + 1, record.seq - 1));
+        }
+        self.events += 1;
+        self.last_seq = record.seq;
+        self.last_event_at = Some(record.ts);
+
+        match &record.event {
+            SessionEvent::SessionStarted { version, dry_run } => {
+                self.sessions += 1;
+                self.version = Some(version.clone());
+                self.dry_run = *dry_run;
+            }
+            SessionEvent::ConfigChanged { key, value, .. } => {
+                self.config.insert(key.clone(), value.clone());
+            }
+            SessionEvent::QuoteSummary { quotes } => {
+                self.quotes
+                    .extend(quotes.iter().map(|(s, q)| (s.clone(), *q)));
+            }
+            SessionEvent::OpportunityDetected { .. } => self.opportunities += 1,
+            SessionEvent::OrderPlaced { order } => {
+                self.orders_placed += 1;
+                if order.error.is_some() {
+                    self.orders_rejected += 1;
+                }
+            }
+            SessionEvent::OrderFilled { .. } => self.fills += 1,
+            SessionEvent::BalancesChanged { balances } => self.balances = balances.clone(),
+            SessionEvent::TradeFinished {
+                success,
+                profit_usd,
+                error,
+                ..
+            } => {
+                self.trades += 1;
+                if *success {
+                    self.trades_succeeded += 1;
+                    self.realized_profit_usd += profit_usd;
+                }
+                if error.is_some() {
+                    self.last_error = error.clone();
+                }
+            }
+            SessionEvent::SessionEnded { .. } => {}
+        }
+    }
+}
+
+/// Print the state rebuilt from the event log at `path`, up to sequence number `until`
+pub fn run_replay(path: &Path, until: Option<u64>) -> Result<()> {
+    let records = EventLog::read(path)?;
+    if records.is_empty() {
+        anyhow::bail!("No events in {}", path.display());
+    }
+    let state = ReplayState::replay(&records, until);
+
+    println!("⏪ Replayed {} from {}", state.events, path.display());
+    println!(
+        "   Sequence: up to #{} at {}",
+        state.last_seq,
+        state
+            .last_event_at
+            .map_or("-".to_string(), |ts| ts.to_rfc3339())
+    );
+    for (from, to) in &state.gaps {
+        println!("   ⚠️ Missing events #{from}..=#{to}");
+    }
+    println!(
+        "   Sessions: {} (last v{}, {})",
+        state.sessions,
+        state.version.as_deref().unwrap_or("?"),
+        if state.dry_run { "dry run" } else { "live" }
+    );
+    println!(
+        "   Trades:   {} ({} succeeded, ${:.6} realized)",
+        state.trades, state.trades_succeeded, state.realized_profit_usd
+    );
+    println!(
+        "   Orders:   {} placed, {} rejected, {} fills, {} opportunities",
+        state.orders_placed, state.orders_rejected, state.fills, state.opportunities
+    );
+    if let Some(error) = &state.last_error {
+        println!("   Last error: {error}");
+    }
+    println!("   Balances:");
+    for (coin, amount) in &state.balances {
+        println!("      {coin:<8} {amount:.8}");
+    }
+    println!("   Quotes: {} symbols", state.quotes.len());
+    println!("   Config:");
+    for (key, value) in &state.config {
+        println!("      {key}={value}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_log_replays_to_the_state_it_recorded() {
+        let path = std::env::temp_dir().join(format!("events_test_{}.jsonl", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let log = EventLog::spawn(&path, true).unwrap();
+        let mut tracker = ChangeTracker::new(5);
+        let start = Instant::now();
+        log.record(SessionEvent::SessionStarted {
+            version: "1.0.0".to_string(),
+            dry_run: true,
+        });
+        log.record(
+            tracker
+                .quotes(
+                    &[MarketPair::test("BTC", "USDT", 49_990.0, 50_000.0)],
+                    start,
+                )
+                .unwrap(),
+        );
+        // Within the interval nothing is summarized, and unchanged quotes never are
+        assert!(tracker
+            .quotes(
+                &[MarketPair::test("BTC", "USDT", 1.0, 2.0)],
+                start + Duration::from_secs(1)
+            )
+            .is_none());
+        assert!(tracker
+            .quotes(
+                &[MarketPair::test("BTC", "USDT", 49_990.0, 50_000.0)],
+                start + Duration::from_secs(5)
+            )
+            .is_none());
+        let balances = BTreeMap::from([("USDT".to_string(), 100.0)]);
+        log.record(tracker.balances(balances.clone()).unwrap());
+        assert!(tracker.balances(balances).is_none());
+        log.record(SessionEvent::TradeFinished {
+            path: "USDT → BTC → ETH → USDT".to_string(),
+            success: true,
+            profit_usd: 0.5,
+            error: None,
+        });
+        log.flush().await;
+        drop(log);
+
+        // A second session continues the sequence
+        let log = EventLog::spawn(&path, true).unwrap();
+        log.record(SessionEvent::BalancesChanged {
+            balances: BTreeMap::from([("USDT".to_string(), 100.5)]),
+        });
+        log.flush().await;
+
+        let records = EventLog::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let seqs: Vec<u64> = records.iter().map(|r| r.seq).collect();
+        assert_eq!(seqs, vec![1, 2, 3, 4, 5]);
+
+        let state = ReplayState::replay(&records, None);
+        assert_eq!(state.balances["USDT"], 100.5);
+        assert_eq!(state.quotes["BTCUSDT"].ask, 50_000.0);
+        assert_eq!(
+            (state.trades_succeeded, state.realized_profit_usd),
+            (1, 0.5)
+        );
+        assert!(state.gaps.is_empty());
+        assert_eq!(
+            ReplayState::replay(&records, Some(3)).balances["USDT"],
+            100.0
+        );
+
+        let mut gapped = records.clone();
+        gapped.remove(2);
+        assert_eq!(ReplayState::replay(&gapped, None).gaps, vec![(3, 3)]);
+    }
+}
@@ -0,0 +1,1163 @@
+use crate::client::BybitClient;
+use crate::config::Config;
+use crate::crosscheck::FeedCrossCheck;
+use crate::depth::DepthUpdate;
+use crate::endpoints::{self, EndpointStatus};
+use crate::latency::LatencyHistogram;
+use crate::models::{
+    AmendOrderRequest, DepositRecord, ExecutionRecord, FeeRateInfo, InstrumentInfo,
+    MaintenanceInfo, OrderInfo, PlaceOrderRequest, PlaceOrderResult, TickerInfo, TickersResult,
+    WalletBalanceResult, WithdrawalRecord,
+};
+use crate::network::WsTransport;
+use crate::prewarm;
+use crate::stream::{StreamTaps, StreamTask};
+use crate::wallet_stream::{WalletFeed, WalletStream};
+use crate::websocket::BybitWebsocket;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Endpoints and request latencies of the connection to the exchange, for the status report
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionStatus {
+    pub endpoints: Vec<EndpointStatus>,
+    pub endpoint_switches: u64,
+    pub request_latency: BTreeMap<String, LatencyHistogram>,
+}
+
+/// Exchange operations the bot depends on, so the engine can run against a mock or
+/// another exchange: REST requests, and the market-data and wallet streams
+#[async_trait]
+pub trait ExchangeApi: Send + Sync {
+    /// Exchange name, for logs
+    fn name(&self) -> &'static str;
+
+    /// Most symbols one market-data stream connection may subscribe to
+    fn max_stream_symbols(&self) -> usize;
+
+    /// Connection streaming the top of book of `symbols` into `tx`
+    fn ticker_stream(
+        &self,
+        conn_id: usize,
+        symbols: Vec<String>,
+        tx: mpsc::Sender<TickerInfo>,
+        taps: &StreamTaps,
+    ) -> StreamTask;
+
+    /// Connection streaming order book depth of `symbols` into `tx`. Without a depth
+    /// stream nothing is ever sent, and legs are priced from the top of book.
+    fn depth_stream(
+        &self,
+        _conn_id: usize,
+        _symbols: Vec<String>,
+        _tx: mpsc::Sender<DepthUpdate>,
+        _taps: &StreamTaps,
+    ) -> StreamTask {
+        Box::pin(std::future::pending())
+    }
+
+    /// Second top-of-book connection feeding `cross_check`. Without one, the cross-check
+    /// never has a mirror quote to compare against.
+    fn mirror_stream(&self, _cross_check: Arc<FeedCrossCheck>, _taps: &StreamTaps) -> StreamTask {
+        Box::pin(std::future::pending())
+    }
+
+    /// Private connection pushing balance changes into `feed`. Without one the feed never
+    /// connects, and balances are polled over REST.
+    fn wallet_stream(&self, _feed: Arc<WalletFeed>, _transport: WsTransport) -> StreamTask {
+        Box::pin(std::future::pending())
+    }
+
+    /// Start the background tasks keeping requests to the exchange fast, if it has any
+    async fn start_upkeep(&self, _config: &Config) {}
+
+    fn connection_status(&self) -> ConnectionStatus {
+        ConnectionStatus::default()
+    }
+
+    /// Round-trip latency to the exchange in milliseconds
+    async fn check_connection(&self) -> Result<f64>;
+
+    /// Wallet balances for an account type (UNIFIED when None)
+    async fn get_wallet_balance(&self, account_type: Option<&str>) -> Result<WalletBalanceResult>;
+
+    /// Every spot instrument, across all pages
+    async fn get_all_spot_instruments(&self) -> Result<Vec<InstrumentInfo>>;
+
+    /// Tickers for every symbol in a category
+    async fn get_tickers(&self, category: &str) -> Result<TickersResult>;
+
+    /// Ticker for a single symbol
+    async fn get_ticker(&self, category: &str, symbol: &str) -> Result<TickersResult>;
+
+    async fn place_order(&self, order_request: PlaceOrderRequest) -> Result<PlaceOrderResult>;
+
+    /// Place several orders in one round trip where the exchange supports it; each order
+    /// succeeds or fails on its own. By default they are placed one after the other.
+    async fn place_batch_orders(
+        &self,
+        category: &str,
+        requests: Vec<PlaceOrderRequest>,
+    ) -> Result<Vec<Result<PlaceOrderResult>>> {
+        let mut results = Vec::with_capacity(requests.len());
+        for request in requests {
+            results.push(
+                self.place_order(PlaceOrderRequest {
+                    category: category.to_string(),
+                    ..request
+                })
+                .await,
+            );
+        }
+        Ok(results)
+    }
+
+    async fn get_order(&self, category: &str, order_id: &str, symbol: &str) -> Result<OrderInfo>;
+
+    /// Order placed under `order_link_id`, or None if it never reached Bybit
+    async fn get_order_by_link_id(
+        &self,
+        category: &str,
+        order_link_id: &str,
+        symbol: &str,
+    ) -> Result<Option<OrderInfo>>;
+
+    /// Cancel a resting order
+    async fn cancel_order(&self, category: &str, order_id: &str, symbol: &str) -> Result<()>;
+
+    /// Cancel every open order on `symbol`, from any client; returns the orders cancelled.
+    /// This includes manual orders and other bots' on the account, so prefer
+    /// `cancel_tagged_orders` unless the whole symbol must be cleared.
+    #[allow(dead_code)]
+    async fn cancel_all_orders(
+        &self,
+        category: &str,
+        symbol: &str,
+    ) -> Result<Vec<PlaceOrderResult>>;
+
+    /// Change the quantity or price of an open order in place
+    async fn amend_order(&self, request: AmendOrderRequest) -> Result<PlaceOrderResult>;
+
+    /// Orders still open on the account, from any client
+    async fn get_open_orders(&self, category: &str) -> Result<Vec<OrderInfo>>;
+
+    /// Fills of an order, with the coin each fee was charged in
+    async fn get_executions(&self, category: &str, order_id: &str) -> Result<Vec<ExecutionRecord>>;
+
+    /// Fills of every order executed since `start`, from any client
+    async fn get_executions_since(
+        &self,
+        category: &str,
+        start: DateTime<Utc>,
+    ) -> Result<Vec<ExecutionRecord>>;
+
+    /// Scheduled, ongoing and recent exchange maintenance windows
+    async fn get_system_status(&self) -> Result<Vec<MaintenanceInfo>>;
+
+    /// The account's maker and taker fee rates on every symbol of a category
+    async fn get_fee_rates(&self, category: &str) -> Result<Vec<FeeRateInfo>>;
+
+    /// Deposits into the account since `start`
+    async fn get_deposits_since(&self, start: DateTime<Utc>) -> Result<Vec<DepositRecord>>;
+
+    /// Withdrawals out of the account since `start`
+    async fn get_withdrawals_since(&self, start: DateTime<Utc>) -> Result<Vec<WithdrawalRecord>>;
+
+    /// Open orders an instance using `prefix` placed, leaving manual orders and other
+    /// bots' out
+    async fn get_tagged_open_orders(&self, category: &str, prefix: &str) -> Result<Vec<OrderInfo>> {
+        Ok(self
+            .get_open_orders(category)
+            .await?
+            .into_iter()
+            .filter(|order| crate::instance::is_tagged(&order.order_link_id, prefix))
+            .collect())
+    }
+
+    /// Cancel the open orders tagged with `prefix`, except the ids in `keep`. Returns the
+    /// orders cancelled; one that fails (e.g. filled meanwhile) is skipped with a warning.
+    async fn cancel_tagged_orders(
+        &self,
+        category: &str,
+        prefix: &str,
+        keep: &[String],
+    ) -> Result<Vec<OrderInfo>> {
+        let mut cancelled = Vec::new();
+        for order in self.get_tagged_open_orders(category, prefix).await? {
+            if keep.contains(&order.order_id) {
+                continue;
+            }
+            match self
+                .cancel_order(category, &order.order_id, &order.symbol)
+                .await
+            {
+                Ok(()) => cancelled.push(order),
+                Err(e) => warn!(
+                    "⚠️ Could not cancel {} ({}): {e:#}",
+                    order.order_id, order.order_link_id
+                ),
+            }
+        }
+        Ok(cancelled)
+    }
+}
+
+#[async_trait]
+impl ExchangeApi for BybitClient {
+    fn name(&self) -> &'static str {
+        "Bybit"
+    }
+
+    /// Bybit allows 100 topics per connection
+    fn max_stream_symbols(&self) -> usize {
+        100
+    }
+
+    fn ticker_stream(
+        &self,
+        conn_id: usize,
+        symbols: Vec<String>,
+        tx: mpsc::Sender<TickerInfo>,
+        taps: &StreamTaps,
+    ) -> StreamTask {
+        let mut connection = BybitWebsocket::new(conn_id, symbols, tx)
+            .with_endpoints(self.endpoints().clone())
+            .with_transport(taps.transport.clone())
+            .with_bandwidth(taps.bandwidth.clone())
+            .with_health(taps.health.clone())
+            .with_focus(taps.focus.clone());
+        if let Some(cross_check) = &taps.cross_check {
+            connection = connection.with_cross_check(cross_check.clone());
+        }
+        if let Some(delays) = &taps.quote_delays {
+            connection = connection.with_quote_delays(delays.clone());
+        }
+        Box::pin(connection.run())
+    }
+
+    fn depth_stream(
+        &self,
+        conn_id: usize,
+        symbols: Vec<String>,
+        tx: mpsc::Sender<DepthUpdate>,
+        taps: &StreamTaps,
+    ) -> StreamTask {
+        Box::pin(
+            BybitWebsocket::depth(conn_id, symbols, tx)
+                .with_endpoints(self.endpoints().clone())
+                .with_transport(taps.transport.clone())
+                .with_bandwidth(taps.bandwidth.clone())
+                .with_focus(taps.focus.clone())
+                .run(),
+        )
+    }
+
+    fn mirror_stream(&self, cross_check: Arc<FeedCrossCheck>, taps: &StreamTaps) -> StreamTask {
+        Box::pin(
+            BybitWebsocket::mirror(0, cross_check)
+                .with_endpoints(self.endpoints().clone())
+                .with_transport(taps.transport.clone())
+                .with_bandwidth(taps.bandwidth.clone())
+                .run(),
+        )
+    }
+
+    fn wallet_stream(&self, feed: Arc<WalletFeed>, transport: WsTransport) -> StreamTask {
+        Box::pin(
+            WalletStream::new(
+                self.endpoints().clone(),
+                self.keys().clone(),
+                transport,
+                feed,
+                self.time_offset().clone(),
+            )
+            .run(),
+        )
+    }
+
+    /// Pick the fastest endpoint and keep measuring, and keep one hot connection per order
+    /// that can be in flight at once
+    async fn start_upkeep(&self, config: &Config) {
+        endpoints::start_probing(
+            self,
+            config.endpoint_probe_interval_secs,
+            config.restart_on_panic,
+        )
+        .await;
+        prewarm::start_prewarming(
+            self,
+            config.connection_prewarm_interval_secs,
+            if config.race_mode { 2 } else { 1 },
+            config.restart_on_panic,
+        )
+        .await;
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        ConnectionStatus {
+            endpoints: self.endpoints().status(),
+            endpoint_switches: self.endpoints().switches(),
+            request_latency: self.latencies().snapshot(),
+        }
+    }
+
+    async fn check_connection(&self) -> Result<f64> {
+        BybitClient::check_connection(self).await
+    }
+
+    async fn get_wallet_balance(&self, account_type: Option<&str>) -> Result<WalletBalanceResult> {
+        BybitClient::get_wallet_balance(self, account_type).await
+    }
+
+    async fn get_all_spot_instruments(&self) -> Result<Vec<InstrumentInfo>> {
+        BybitClient::get_all_spot_instruments(self).await
+    }
+
+    async fn get_tickers(&self, category: &str) -> Result<TickersResult> {
+        BybitClient::get_tickers(self, category).await
+    }
+
+    async fn get_ticker(&self, category: &str, symbol: &str) -> Result<TickersResult> {
+        BybitClient::get_ticker(self, category, symbol).await
+    }
+
+    async fn place_order(&self, order_request: PlaceOrderRequest) -> Result<PlaceOrderResult> {
+        BybitClient::place_order(self, order_request).await
+    }
+
+    async fn place_batch_orders(
+        &self,
+        category: &str,
+        requests: Vec<PlaceOrderRequest>,
+    ) -> Result<Vec<Result<PlaceOrderResult>>> {
+        BybitClient::place_batch_orders(self, category, requests).await
+    }
+
+    async fn get_order(&self, category: &str, order_id: &str, symbol: &str) -> Result<OrderInfo> {
+        BybitClient::get_order(self, category, order_id, symbol).await
+    }
+
+    async fn get_order_by_link_id(
+        &self,
+        category: &str,
+        order_link_id: &str,
+        symbol: &str,
+    ) -> Result<Option<OrderInfo>> {
+        BybitClient::get_order_by_link_id(self, category, order_link_id, symbol).await
+    }
+
+    async fn cancel_order(&self, category: &str, order_id: &str, symbol: &str) -> Result<()> {
+        BybitClient::cancel_order(self, category, order_id, symbol).await
+    }
+
+    async fn cancel_all_orders(
+        &self,
+        category: &str,
+        symbol: &str,
+    ) -> Result<Vec<PlaceOrderResult>> {
+        BybitClient::cancel_all_orders(self, category, symbol).await
+    }
+
+    async fn amend_order(&self, request: AmendOrderRequest) -> Result<PlaceOrderResult> {
+        BybitClient::amend_order(self, request).await
+    }
+
+    async fn get_open_orders(&self, category: &str) -> Result<Vec<OrderInfo>> {
+        BybitClient::get_open_orders(self, category).await
+    }
+
+    async fn get_executions(&self, category: &str, order_id: &str) -> Result<Vec<ExecutionRecord>> {
+        BybitClient::get_executions(self, category, order_id).await
+    }
+
+    async fn get_executions_since(
+        &self,
+        category: &str,
+        start: DateTime<Utc>,
+    ) -> Result<Vec<ExecutionRecord>> {
+        BybitClient::get_executions_since(self, category, start).await
+    }
+
+    async fn get_system_status(&self) -> Result<Vec<MaintenanceInfo>> {
+        BybitClient::get_system_status(self).await
+    }
+
+    async fn get_fee_rates(&self, category: &str) -> Result<Vec<FeeRateInfo>> {
+        BybitClient::get_fee_rates(self, category).await
+    }
+
+    async fn get_deposits_since(&self, start: DateTime<Utc>) -> Result<Vec<DepositRecord>> {
+        BybitClient::get_deposits_since(self, start).await
+    }
+
+    async fn get_withdrawals_since(&self, start: DateTime<Utc>) -> Result<Vec<WithdrawalRecord>> {
+        BybitClient::get_withdrawals_since(self, start).await
+    }
+}
+
+#[cfg(test)]
+pub use mock::{MockExchange, MockMarket};
+
+#[cfg(test)]
+mod mock {
+    use super::*;
+    use crate::errors::BybitError;
+    use anyhow::anyhow;
+    use serde_json::json;
+    use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    /// A scripted error as the client returns it: a `BybitError` if it carries a retCode
+    fn scripted_error(error: &str) -> anyhow::Error {
+        BybitError::parse(error).map_or_else(|| anyhow!(error.to_string()), Into::into)
+    }
+
+    /// Top of book and lot sizes of a simulated spot market
+    #[derive(Debug, Clone)]
+    pub struct MockMarket {
+        pub base: String,
+        pub quote: String,
+        pub bid: f64,
+        pub ask: f64,
+        /// Decimals accepted for base quantities (Sells)
+        pub base_decimals: u32,
+        /// Decimals accepted for quote amounts (market Buys)
+        pub quote_decimals: u32,
+        /// Maker and taker fee rate of the account on this market, instead of the exchange's
+        pub fee_rate: Option<f64>,
+    }
+
+    impl MockMarket {
+        pub fn new(base: &str, quote: &str, bid: f64, ask: f64) -> Self {
+            Self {
+                base: base.to_string(),
+                quote: quote.to_string(),
+                bid,
+                ask,
+                base_decimals: 6,
+                quote_decimals: 8,
+                fee_rate: None,
+            }
+        }
+
+        /// Charge this fee rate on the market, as a VIP tier or a zero-fee pair would
+        pub fn with_fee_rate(mut self, rate: f64) -> Self {
+            self.fee_rate = Some(rate);
+            self
+        }
+
+        pub fn with_base_decimals(mut self, decimals: u32) -> Self {
+            self.base_decimals = decimals;
+            self
+        }
+    }
+
+    fn step(decimals: u32) -> String {
+        format!("{}", 10f64.powi(-(decimals as i32)))
+    }
+
+    /// In-memory exchange with scripted failures for tests.
+    /// Market orders fill immediately at the top of book, fees are charged in the received coin
+    /// unless a fee coin is set. Limit orders that don't cross the book rest until filled or
+    /// cancelled.
+    #[derive(Default)]
+    pub struct MockExchange {
+        pub latency_ms: f64,
+        pub fee_rate: f64,
+        /// Coin fees are charged in instead of the received coin, as a 1:1 amount
+        fee_coin: Option<String>,
+        markets: Mutex<BTreeMap<String, MockMarket>>,
+        balances: Mutex<HashMap<String, f64>>,
+        orders: Mutex<Vec<OrderInfo>>,
+        executions: Mutex<Vec<ExecutionRecord>>,
+        wallet_errors: Mutex<VecDeque<String>>,
+        wallet_calls: AtomicUsize,
+        /// Error and number of market orders left to reject, per symbol
+        market_order_errors: Mutex<HashMap<String, (String, usize)>>,
+        /// Share of the next market order per symbol that fills before the order stalls
+        partial_fills: Mutex<HashMap<String, f64>>,
+        /// Share of the next resting limit order per symbol that fills at its price
+        resting_fills: Mutex<HashMap<String, f64>>,
+        lost_responses: Mutex<HashMap<String, String>>,
+        maintenance: Mutex<Vec<MaintenanceInfo>>,
+        /// Symbols listed with a status other than Trading
+        suspended: Mutex<HashSet<String>>,
+        deposits: Mutex<Vec<DepositRecord>>,
+        withdrawals: Mutex<Vec<WithdrawalRecord>>,
+    }
+
+    impl MockExchange {
+        pub fn new() -> Self {
+            Self {
+                latency_ms: 10.0,
+                fee_rate: 0.001,
+                ..Default::default()
+            }
+        }
+
+        pub fn with_market(mut self, symbol: &str, market: MockMarket) -> Self {
+            self.markets
+                .get_mut()
+                .unwrap()
+                .insert(symbol.to_string(), market);
+            self
+        }
+
+        /// Move the best bid and ask of `symbol`, as the market would between polls
+        pub fn set_quote(&self, symbol: &str, bid: f64, ask: f64) {
+            let mut markets = self.markets.lock().unwrap();
+            let market = markets.get_mut(symbol).unwrap();
+            market.bid = bid;
+            market.ask = ask;
+        }
+
+        /// Charge fees in `coin` (e.g. a discount token) instead of the received coin
+        pub fn with_fee_coin(mut self, coin: &str) -> Self {
+            self.fee_coin = Some(coin.to_string());
+            self
+        }
+
+        pub fn with_balance(self, coin: &str, amount: f64) -> Self {
+            self.balances
+                .lock()
+                .unwrap()
+                .insert(coin.to_string(), amount);
+            self
+        }
+
+        /// Fail the next wallet balance requests with these errors, in order
+        pub fn fail_wallet_with(self, errors: &[&str]) -> Self {
+            *self.wallet_errors.lock().unwrap() = errors.iter().map(|e| e.to_string()).collect();
+            self
+        }
+
+        pub fn wallet_calls(&self) -> usize {
+            self.wallet_calls.load(Ordering::SeqCst)
+        }
+
+        /// Orders placed so far, oldest first
+        pub fn orders(&self) -> Vec<OrderInfo> {
+            self.orders.lock().unwrap().clone()
+        }
+
+        /// Reject the next market order on `symbol` with this error
+        pub fn fail_next_market_order(self, symbol: &str, error: &str) -> Self {
+            self.fail_market_orders(symbol, error, 1)
+        }
+
+        /// Reject the next `times` market orders on `symbol` with this error
+        pub fn fail_market_orders(self, symbol: &str, error: &str, times: usize) -> Self {
+            self.market_order_errors
+                .lock()
+                .unwrap()
+                .insert(symbol.to_string(), (error.to_string(), times));
+            self
+        }
+
+        /// Fill only `ratio` of the next market order on `symbol` and leave the rest open
+        /// until it is cancelled, as a thin book would
+        pub fn partially_fill_next_market_order(self, symbol: &str, ratio: f64) -> Self {
+            self.partial_fills
+                .lock()
+                .unwrap()
+                .insert(symbol.to_string(), ratio);
+            self
+        }
+
+        /// Fill only `ratio` of the next limit order on `symbol` that rests on the book, at
+        /// its limit price, and leave the rest open until it is cancelled
+        pub fn partially_fill_next_resting_order(self, symbol: &str, ratio: f64) -> Self {
+            self.resting_fills
+                .lock()
+                .unwrap()
+                .insert(symbol.to_string(), ratio);
+            self
+        }
+
+        /// Place the next market order on `symbol` but lose Bybit's response, failing the
+        /// request with this error as a timed out connection would
+        pub fn lose_next_order_response(self, symbol: &str, error: &str) -> Self {
+            self.lost_responses
+                .lock()
+                .unwrap()
+                .insert(symbol.to_string(), error.to_string());
+            self
+        }
+
+        /// Report these maintenance windows from the system status endpoint
+        pub fn set_maintenance(&self, windows: Vec<MaintenanceInfo>) {
+            *self.maintenance.lock().unwrap() = windows;
+        }
+
+        /// List `symbol` as closed, or trading again
+        pub fn set_suspended(&self, symbol: &str, suspended: bool) {
+            let mut symbols = self.suspended.lock().unwrap();
+            if suspended {
+                symbols.insert(symbol.to_string());
+            } else {
+                symbols.remove(symbol);
+            }
+        }
+
+        /// Credit a deposit made outside the bot
+        pub fn deposit(&self, coin: &str, amount: f64) {
+            *self
+                .balances
+                .lock()
+                .unwrap()
+                .entry(coin.to_string())
+                .or_insert(0.0) += amount;
+            let mut deposits = self.deposits.lock().unwrap();
+            let tx_id = format!("0xdeposit{}", deposits.len() + 1);
+            deposits.push(DepositRecord {
+                coin: coin.to_string(),
+                amount: amount.to_string(),
+                tx_id,
+                status: 3,
+                success_at: Utc::now().timestamp_millis().to_string(),
+            });
+        }
+
+        /// Debit a withdrawal made outside the bot, fee included
+        pub fn withdraw(&self, coin: &str, amount: f64, fee: f64) {
+            *self
+                .balances
+                .lock()
+                .unwrap()
+                .entry(coin.to_string())
+                .or_insert(0.0) -= amount + fee;
+            let mut withdrawals = self.withdrawals.lock().unwrap();
+            let withdraw_id = (withdrawals.len() + 1).to_string();
+            withdrawals.push(WithdrawalRecord {
+                withdraw_id,
+                coin: coin.to_string(),
+                amount: amount.to_string(),
+                withdraw_fee: fee.to_string(),
+                status: "success".to_string(),
+                update_time: Utc::now().timestamp_millis().to_string(),
+            });
+        }
+
+        /// Fill every resting limit order at its limit price, as if the market came to it
+        pub fn fill_resting_orders(&self) {
+            let mut orders = self.orders.lock().unwrap();
+            for order in orders.iter_mut().filter(|o| o.order_status == "New") {
+                let market = self.markets.lock().unwrap()[&order.symbol].clone();
+                let buy = order.side == "Buy";
+                let price: f64 = order.price.parse().unwrap();
+                let qty: f64 = order.qty.parse().unwrap();
+                if let Ok((fee, fee_coin)) = self.settle(&market, buy, qty, qty * price) {
+                    order.order_status = "Filled".to_string();
+                    order.avg_price = order.price.clone();
+                    order.cum_exec_qty = qty.to_string();
+                    order.cum_exec_value = (qty * price).to_string();
+                    order.cum_exec_fee = fee.to_string();
+                    self.record_execution(order, fee_coin);
+                }
+            }
+        }
+
+        /// Ticker of every market, with deep books at its quotes
+        fn tickers(&self) -> Vec<TickerInfo> {
+            let tickers = self
+                .markets
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(symbol, m)| {
+                    json!({
+                        "symbol": symbol,
+                        "bid1Price": m.bid.to_string(),
+                        "bid1Size": "1000000",
+                        "ask1Price": m.ask.to_string(),
+                        "ask1Size": "1000000",
+                        "lastPrice": ((m.bid + m.ask) / 2.0).to_string(),
+                        "volume24h": "1000000",
+                        "turnover24h": "100000000"
+                    })
+                })
+                .collect::<Vec<_>>();
+            serde_json::from_value(json!(tickers)).unwrap()
+        }
+
+        fn fill(&self, request: &PlaceOrderRequest) -> Result<OrderInfo> {
+            let market = self
+                .markets
+                .lock()
+                .unwrap()
+                .get(&request.symbol)
+                .cloned()
+                .ok_or_else(|| {
+                    BybitError::from_response(170121, format!("Invalid symbol {}", request.symbol))
+                })?;
+            let buy = request.side == "Buy";
+            let limit = match request.order_type.as_str() {
+                "Limit" => Some(
+                    request
+                        .price
+                        .as_deref()
+                        .ok_or_else(|| {
+                            BybitError::from_response(170130, "Limit order without price")
+                        })?
+                        .parse::<f64>()?,
+                ),
+                _ => None,
+            };
+
+            if limit.is_none() {
+                let mut errors = self.market_order_errors.lock().unwrap();
+                if let Some((error, left)) = errors.get_mut(&request.symbol) {
+                    let error = scripted_error(error);
+                    *left -= 1;
+                    if *left == 0 {
+                        errors.remove(&request.symbol);
+                    }
+                    return Err(error);
+                }
+            }
+
+            // Market Buys are sized in the quote coin, everything else in the base coin
+            let allowed = if buy && limit.is_none() {
+                market.quote_decimals
+            } else {
+                market.base_decimals
+            };
+            let decimals = request.qty.split_once('.').map_or(0, |(_, d)| d.len());
+            if decimals > allowed as usize {
+                return Err(BybitError::from_response(
+                    170137,
+                    "Order quantity has too many decimals.",
+                )
+                .into());
+            }
+
+            let qty: f64 = request.qty.parse()?;
+            let marketable = limit.is_none_or(|limit| {
+                if buy {
+                    limit >= market.ask
+                } else {
+                    limit <= market.bid
+                }
+            });
+
+            let ratio = match limit {
+                None => self.partial_fills.lock().unwrap().remove(&request.symbol),
+                Some(_) => None,
+            };
+            let post_only = request.time_in_force.as_deref() == Some("PostOnly");
+            let resting_fill = match limit {
+                Some(limit) if !marketable => self
+                    .resting_fills
+                    .lock()
+                    .unwrap()
+                    .remove(&request.symbol)
+                    .map(|ratio| (limit, qty * ratio)),
+                _ => None,
+            };
+            let (status, price, exec_qty, exec_value, fee, fee_coin) =
+                if let Some((limit, exec_qty)) = resting_fill {
+                    // Part of it was taken at the limit; the rest stays on the book
+                    let (fee, fee_coin) = self.settle(&market, buy, exec_qty, exec_qty * limit)?;
+                    (
+                        "PartiallyFilled",
+                        limit,
+                        exec_qty,
+                        exec_qty * limit,
+                        fee,
+                        fee_coin,
+                    )
+                } else if !marketable {
+                    // Rests on the book until filled or cancelled
+                    ("New", 0.0, 0.0, 0.0, 0.0, String::new())
+                } else if post_only {
+                    // Would take liquidity, so Bybit cancels it instead
+                    ("Cancelled", 0.0, 0.0, 0.0, 0.0, String::new())
+                } else {
+                    let (price, exec_qty, exec_value) = match (buy, limit) {
+                        (true, None) => (market.ask, qty / market.ask, qty),
+                        (true, Some(_)) => (market.ask, qty, qty * market.ask),
+                        (false, _) => (market.bid, qty, qty * market.bid),
+                    };
+                    let (status, ratio) = match ratio {
+                        Some(ratio) => ("PartiallyFilled", ratio),
+                        None => ("Filled", 1.0),
+                    };
+                    let (exec_qty, exec_value) = (exec_qty * ratio, exec_value * ratio);
+                    let (fee, fee_coin) = self.settle(&market, buy, exec_qty, exec_value)?;
+                    (status, price, exec_qty, exec_value, fee, fee_coin)
+                };
+
+            let mut orders = self.orders.lock().unwrap();
+            let order = OrderInfo {
+                order_id: format!("mock-{}", orders.len() + 1),
+                order_link_id: request.order_link_id.clone().unwrap_or_default(),
+                symbol: request.symbol.clone(),
+                order_status: status.to_string(),
+                side: request.side.clone(),
+                order_type: request.order_type.clone(),
+                qty: request.qty.clone(),
+                price: request.price.clone().unwrap_or_else(|| "0".to_string()),
+                avg_price: price.to_string(),
+                cum_exec_qty: exec_qty.to_string(),
+                cum_exec_value: exec_value.to_string(),
+                cum_exec_fee: fee.to_string(),
+                created_time: "0".to_string(),
+                updated_time: "0".to_string(),
+            };
+            orders.push(order.clone());
+            if exec_qty > 0.0 {
+                self.record_execution(&order, fee_coin);
+            }
+            Ok(order)
+        }
+
+        fn record_execution(&self, order: &OrderInfo, fee_coin: String) {
+            self.executions.lock().unwrap().push(ExecutionRecord {
+                order_id: order.order_id.clone(),
+                order_link_id: order.order_link_id.clone(),
+                symbol: order.symbol.clone(),
+                side: order.side.clone(),
+                exec_price: order.avg_price.clone(),
+                exec_qty: order.cum_exec_qty.clone(),
+                exec_value: order.cum_exec_value.clone(),
+                exec_fee: order.cum_exec_fee.clone(),
+                fee_currency: fee_coin,
+                exec_time: Utc::now().timestamp_millis().to_string(),
+            });
+        }
+
+        /// Move balances for a fill and return the fee and the coin it was charged in
+        fn settle(
+            &self,
+            market: &MockMarket,
+            buy: bool,
+            exec_qty: f64,
+            exec_value: f64,
+        ) -> Result<(f64, String)> {
+            let (spend_coin, receive_coin, spent, received) = if buy {
+                (&market.quote, &market.base, exec_value, exec_qty)
+            } else {
+                (&market.base, &market.quote, exec_qty, exec_value)
+            };
+            let fee = received * market.fee_rate.unwrap_or(self.fee_rate);
+            let fee_coin = self.fee_coin.as_ref().unwrap_or(receive_coin);
+
+            let mut balances = self.balances.lock().unwrap();
+            let available = balances.get(spend_coin).copied().unwrap_or(0.0);
+            if available + 1e-12 < spent {
+                return Err(BybitError::from_response(170131, "Insufficient balance.").into());
+            }
+            balances.insert(spend_coin.clone(), available - spent);
+            *balances.entry(receive_coin.clone()).or_insert(0.0) += received;
+            *balances.entry(fee_coin.clone()).or_insert(0.0) -= fee;
+            Ok((fee, fee_coin.clone()))
+        }
+    }
+
+    #[async_trait]
+    impl ExchangeApi for MockExchange {
+        fn name(&self) -> &'static str {
+            "Mock"
+        }
+
+        fn max_stream_symbols(&self) -> usize {
+            100
+        }
+
+        async fn check_connection(&self) -> Result<f64> {
+            Ok(self.latency_ms)
+        }
+
+        async fn get_wallet_balance(
+            &self,
+            _account_type: Option<&str>,
+        ) -> Result<WalletBalanceResult> {
+            self.wallet_calls.fetch_add(1, Ordering::SeqCst);
+            if let Some(error) = self.wallet_errors.lock().unwrap().pop_front() {
+                return Err(scripted_error(&error));
+            }
+            let coins: Vec<_> = self
+                .balances
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(coin, amount)| json!({ "coin": coin, "walletBalance": amount.to_string() }))
+                .collect();
+            Ok(serde_json::from_value(json!({
+                "list": [{ "accountType": "UNIFIED", "coin": coins }]
+            }))?)
+        }
+
+        async fn get_all_spot_instruments(&self) -> Result<Vec<InstrumentInfo>> {
+            let suspended = self.suspended.lock().unwrap();
+            let instruments = self
+                .markets
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(symbol, m)| {
+                    json!({
+                        "symbol": symbol,
+                        "baseCoin": m.base,
+                        "quoteCoin": m.quote,
+                        "status": if suspended.contains(symbol) { "Closed" } else { "Trading" },
+                        "lotSizeFilter": {
+                            "basePrecision": step(m.base_decimals),
+                            "quotePrecision": step(m.quote_decimals),
+                            "minOrderQty": step(m.base_decimals),
+                            "maxOrderQty": "1000000",
+                            // A dollar on stablecoin markets, one tick of the quote coin elsewhere
+                            "minOrderAmt": if m.quote.starts_with("USD") { "1".to_string() } else { step(m.quote_decimals) },
+                            "maxOrderAmt": "1000000"
+                        },
+                        "priceFilter": { "tickSize": "0.00000001" }
+                    })
+                })
+                .collect();
+            Ok(serde_json::from_value(serde_json::Value::Array(
+                instruments,
+            ))?)
+        }
+
+        fn ticker_stream(
+            &self,
+            _conn_id: usize,
+            symbols: Vec<String>,
+            tx: mpsc::Sender<TickerInfo>,
+            _taps: &StreamTaps,
+        ) -> StreamTask {
+            // The current quote of each symbol, then silence as if the book never moved
+            let tickers = self.tickers();
+            let quotes: Vec<TickerInfo> = symbols
+                .iter()
+                .filter_map(|symbol| tickers.iter().find(|t| &t.symbol == symbol).cloned())
+                .collect();
+            Box::pin(async move {
+                for ticker in quotes {
+                    if tx.send(ticker).await.is_err() {
+                        return;
+                    }
+                }
+                std::future::pending::<()>().await
+            })
+        }
+
+        async fn get_tickers(&self, category: &str) -> Result<TickersResult> {
+            Ok(TickersResult {
+                category: category.to_string(),
+                list: self.tickers(),
+            })
+        }
+
+        async fn get_ticker(&self, category: &str, symbol: &str) -> Result<TickersResult> {
+            let mut tickers = self.get_tickers(category).await?;
+            tickers.list.retain(|t| t.symbol == symbol);
+            Ok(tickers)
+        }
+
+        async fn place_order(&self, order_request: PlaceOrderRequest) -> Result<PlaceOrderResult> {
+            let order = self.fill(&order_request)?;
+            if let Some(error) = self.lost_responses.lock().unwrap().remove(&order.symbol) {
+                return Err(scripted_error(&error));
+            }
+            Ok(PlaceOrderResult {
+                order_id: order.order_id,
+                order_link_id: order.order_link_id,
+            })
+        }
+
+        async fn get_order(
+            &self,
+            _category: &str,
+            order_id: &str,
+            _symbol: &str,
+        ) -> Result<OrderInfo> {
+            self.orders
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|o| o.order_id == order_id)
+                .cloned()
+                .ok_or_else(|| anyhow!("Order not found in response"))
+        }
+
+        async fn get_order_by_link_id(
+            &self,
+            _category: &str,
+            order_link_id: &str,
+            _symbol: &str,
+        ) -> Result<Option<OrderInfo>> {
+            Ok(self
+                .orders
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|o| o.order_link_id == order_link_id)
+                .cloned())
+        }
+
+        async fn cancel_order(&self, _category: &str, order_id: &str, _symbol: &str) -> Result<()> {
+            let mut orders = self.orders.lock().unwrap();
+            match orders.iter_mut().find(|o| {
+                o.order_id == order_id
+                    && matches!(o.order_status.as_str(), "New" | "PartiallyFilled")
+            }) {
+                Some(order) => {
+                    order.order_status = if order.order_status == "PartiallyFilled" {
+                        "PartiallyFilledCanceled".to_string()
+                    } else {
+                        "Cancelled".to_string()
+                    };
+                    Ok(())
+                }
+                None => Err(BybitError::from_response(170213, "Order does not exist.").into()),
+            }
+        }
+
+        async fn cancel_all_orders(
+            &self,
+            category: &str,
+            symbol: &str,
+        ) -> Result<Vec<PlaceOrderResult>> {
+            let open: Vec<_> = self
+                .get_open_orders(category)
+                .await?
+                .into_iter()
+                .filter(|o| o.symbol == symbol)
+                .collect();
+            for order in &open {
+                self.cancel_order(category, &order.order_id, symbol).await?;
+            }
+            Ok(open
+                .into_iter()
+                .map(|o| PlaceOrderResult {
+                    order_id: o.order_id,
+                    order_link_id: o.order_link_id,
+                })
+                .collect())
+        }
+
+        async fn amend_order(&self, request: AmendOrderRequest) -> Result<PlaceOrderResult> {
+            let mut orders = self.orders.lock().unwrap();
+            let order = orders
+                .iter_mut()
+                .find(|o| {
+                    o.order_id == request.order_id
+                        && matches!(o.order_status.as_str(), "New" | "PartiallyFilled")
+                })
+                .ok_or_else(|| BybitError::from_response(170213, "Order does not exist."))?;
+            if let Some(qty) = request.qty {
+                if qty.parse::<f64>()? < order.cum_exec_qty.parse::<f64>()? {
+                    return Err(BybitError::from_response(
+                        170136,
+                        "Order quantity is below the executed quantity.",
+                    )
+                    .into());
+                }
+                order.qty = qty;
+            }
+            if let Some(price) = request.price {
+                order.price = price;
+            }
+            Ok(PlaceOrderResult {
+                order_id: order.order_id.clone(),
+                order_link_id: order.order_link_id.clone(),
+            })
+        }
+
+        async fn get_open_orders(&self, _category: &str) -> Result<Vec<OrderInfo>> {
+            Ok(self
+                .orders
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|o| matches!(o.order_status.as_str(), "New" | "PartiallyFilled"))
+                .cloned()
+                .collect())
+        }
+
+        async fn get_executions(
+            &self,
+            _category: &str,
+            order_id: &str,
+        ) -> Result<Vec<ExecutionRecord>> {
+            Ok(self
+                .executions
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|e| e.order_id == order_id)
+                .cloned()
+                .collect())
+        }
+
+        async fn get_executions_since(
+            &self,
+            _category: &str,
+            start: DateTime<Utc>,
+        ) -> Result<Vec<ExecutionRecord>> {
+            Ok(self
+                .executions
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|e| e.exec_time.parse().unwrap_or(0) >= start.timestamp_millis())
+                .cloned()
+                .collect())
+        }
+
+        async fn get_system_status(&self) -> Result<Vec<MaintenanceInfo>> {
+            Ok(self.maintenance.lock().unwrap().clone())
+        }
+
+        async fn get_fee_rates(&self, _category: &str) -> Result<Vec<FeeRateInfo>> {
+            Ok(self
+                .markets
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(symbol, market)| {
+                    let rate = market.fee_rate.unwrap_or(self.fee_rate).to_string();
+                    FeeRateInfo {
+                        symbol: symbol.clone(),
+                        taker_fee_rate: rate.clone(),
+                        maker_fee_rate: rate,
+                    }
+                })
+                .collect())
+        }
+
+        async fn get_deposits_since(&self, start: DateTime<Utc>) -> Result<Vec<DepositRecord>> {
+            let since = start.timestamp_millis();
+            Ok(self
+                .deposits
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|d| d.success_at.parse().unwrap_or(0) >= since)
+                .cloned()
+                .collect())
+        }
+
+        async fn get_withdrawals_since(
+            &self,
+            start: DateTime<Utc>,
+        ) -> Result<Vec<WithdrawalRecord>> {
+            let since = start.timestamp_millis();
+            Ok(self
+                .withdrawals
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|w| w.update_time.parse().unwrap_or(0) >= since)
+                .cloned()
+                .collect())
+        }
+    }
+}
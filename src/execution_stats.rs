@@ -0,0 +1,215 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tracing::info;
+
+/// Orders on a symbol before its history is trusted enough to penalize it
+const MIN_ORDERS: u32 = 3;
+/// Ranking penalty for a symbol whose orders always fail, in percentage points
+const FAILURE_PENALTY_PCT: f64 = 0.5;
+/// Fills slower than this on average are penalized
+const SLOW_FILL: Duration = Duration::from_secs(1);
+/// Ranking penalty per second of average fill latency above `SLOW_FILL`
+const LATENCY_PENALTY_PCT_PER_SEC: f64 = 0.05;
+/// Ranking penalty per precision retry an order needs on average
+const RETRY_PENALTY_PCT: f64 = 0.02;
+/// Fill latencies kept per symbol for percentiles
+const LATENCY_SAMPLES: usize = 50;
+
+/// Execution history of one symbol
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SymbolStats {
+    pub orders: u32,
+    pub filled: u32,
+    pub total_fill_latency: Duration,
+    pub precision_retries: u32,
+    /// Most recent fill latencies, oldest first
+    pub recent_latencies: VecDeque<Duration>,
+}
+
+impl SymbolStats {
+    pub fn success_rate(&self) -> f64 {
+        if self.orders == 0 {
+            return 1.0;
+        }
+        self.filled as f64 / self.orders as f64
+    }
+
+    pub fn avg_fill_latency(&self) -> Duration {
+        if self.filled == 0 {
+            return Duration::ZERO;
+        }
+        self.total_fill_latency / self.filled
+    }
+
+    /// How much worse than estimated a triangle through this symbol is likely to do,
+    /// in percentage points; zero until there are enough orders to judge
+    pub fn penalty_pct(&self) -> f64 {
+        if self.orders < MIN_ORDERS {
+            return 0.0;
+        }
+        let failures = (1.0 - self.success_rate()) * FAILURE_PENALTY_PCT;
+        let slow_secs = self
+            .avg_fill_latency()
+            .saturating_sub(SLOW_FILL)
+            .as_secs_f64();
+        let retries = self.precision_retries as f64 / self.orders as f64;
+        failures + slow_secs * LATENCY_PENALTY_PCT_PER_SEC + retries * RETRY_PENALTY_PCT
+    }
+}
+
+/// Per-symbol order success, fill latency and precision retries, as seen by the trader
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionStats {
+    symbols: HashMap<String, SymbolStats>,
+}
+
+impl ExecutionStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An order that filled, `latency` after it was first submitted
+    pub fn record_fill(&mut self, symbol: &str, latency: Duration) {
+        let stats = self.symbols.entry(symbol.to_string()).or_default();
+        stats.orders += 1;
+        stats.filled += 1;
+        stats.total_fill_latency += latency;
+        if stats.recent_latencies.len() == LATENCY_SAMPLES {
+            stats.recent_latencies.pop_front();
+        }
+        stats.recent_latencies.push_back(latency);
+    }
+
+    /// An order that was rejected, or never filled
+    pub fn record_failure(&mut self, symbol: &str) {
+        self.symbols.entry(symbol.to_string()).or_default().orders += 1;
+    }
+
+    pub fn record_precision_retry(&mut self, symbol: &str) {
+        self.symbols
+            .entry(symbol.to_string())
+            .or_default()
+            .precision_retries += 1;
+    }
+
+    #[cfg(test)]
+    pub fn get(&self, symbol: &str) -> Option<&SymbolStats> {
+        self.symbols.get(symbol)
+    }
+
+    /// Recent fill latency percentile (0.0-1.0) of a symbol, or across all symbols while
+    /// the symbol has too few fills of its own; None without any history
+    pub fn fill_latency_percentile(&self, symbol: &str, percentile: f64) -> Option<Duration> {
+        let mut samples: Vec<Duration> = match self.symbols.get(symbol) {
+            Some(stats) if stats.recent_latencies.len() >= MIN_ORDERS as usize => {
+                stats.recent_latencies.iter().copied().collect()
+            }
+            _ => self
+                .symbols
+                .values()
+                .flat_map(|stats| stats.recent_latencies.iter().copied())
+                .collect(),
+        };
+        if samples.len() < MIN_ORDERS as usize {
+            return None;
+        }
+        samples.sort();
+        let rank = (percentile * samples.len() as f64).ceil() as usize;
+        Some(samples[rank.clamp(1, samples.len()) - 1])
+    }
+
+    /// Ranking penalties for symbols with a poor execution history
+    pub fn penalties(&self) -> HashMap<String, f64> {
+        self.symbols
+            .iter()
+            .map(|(symbol, stats)| (symbol.clone(), stats.penalty_pct()))
+            .filter(|(_, penalty)| *penalty > 0.0)
+            .collect()
+    }
+
+    pub fn log_summary(&self) {
+        if self.symbols.is_empty() {
+            return;
+        }
+        info!("📈 Execution stats by symbol:");
+        let mut symbols: Vec<_> = self.symbols.iter().collect();
+        symbols.sort_by(|a, b| a.0.cmp(b.0));
+        for (symbol, stats) in symbols {
+            info!(
+                "   • {symbol}: {}/{} filled, avg fill {:?}, {} precision retries, penalty {:.3}pp",
+                stats.filled,
+                stats.orders,
+                stats.avg_fill_latency(),
+                stats.precision_retries,
+                stats.penalty_pct()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_penalty_needs_history_and_grows_with_failures() {
+        let mut stats = ExecutionStats::new();
+        stats.record_failure("PEPEUSDT");
+        stats.record_failure("PEPEUSDT");
+        assert!(stats.penalties().is_empty());
+
+        stats.record_fill("PEPEUSDT", Duration::from_millis(200));
+        stats.record_fill("PEPEUSDT", Duration::from_millis(400));
+        let pepe = stats.get("PEPEUSDT").unwrap();
+        assert_eq!(pepe.success_rate(), 0.5);
+        assert_eq!(pepe.avg_fill_latency(), Duration::from_millis(300));
+        assert!((stats.penalties()["PEPEUSDT"] - 0.25).abs() < 1e-9);
+
+        // A clean history carries no penalty
+        for _ in 0..3 {
+            stats.record_fill("BTCUSDT", Duration::from_millis(100));
+        }
+        assert!(!stats.penalties().contains_key("BTCUSDT"));
+    }
+
+    #[test]
+    fn test_slow_fills_and_retries_are_penalized() {
+        let mut stats = ExecutionStats::new();
+        for _ in 0..4 {
+            stats.record_fill("SHIBUSDT", Duration::from_secs(3));
+            stats.record_precision_retry("SHIBUSDT");
+        }
+        // 2s over the slow-fill mark plus one retry per order
+        let expected = 2.0 * LATENCY_PENALTY_PCT_PER_SEC + RETRY_PENALTY_PCT;
+        assert!((stats.penalties()["SHIBUSDT"] - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fill_latency_percentile_falls_back_to_all_symbols() {
+        let mut stats = ExecutionStats::new();
+        assert_eq!(stats.fill_latency_percentile("BTCUSDT", 0.95), None);
+
+        for ms in 1..=20 {
+            stats.record_fill("BTCUSDT", Duration::from_millis(ms * 100));
+        }
+        assert_eq!(
+            stats.fill_latency_percentile("BTCUSDT", 0.95),
+            Some(Duration::from_millis(1900))
+        );
+        assert_eq!(
+            stats.fill_latency_percentile("BTCUSDT", 0.5),
+            Some(Duration::from_millis(1000))
+        );
+
+        // One fill isn't a history: use everything seen so far
+        stats.record_fill("PEPEUSDT", Duration::from_secs(5));
+        assert_eq!(
+            stats.fill_latency_percentile("PEPEUSDT", 1.0),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            stats.fill_latency_percentile("PEPEUSDT", 0.5),
+            Some(Duration::from_millis(1100))
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use crate::backend::TradeExecutor;
+use crate::bundle::OrderLog;
+use crate::depth::DepthBook;
+use crate::exchange::ExchangeApi;
+use crate::imbalance::TopOfBook;
+use crate::maker::ExecutionStrategy;
+use crate::models::{ArbitrageOpportunity, Price};
+use crate::positions::{ClosedPosition, OpenPosition};
+use crate::resume::{ExecutionState, ResumeMode};
+use crate::supervisor;
+use crate::symbols::SymbolId;
+use crate::trader::{ArbitrageExecutionResult, ArbitrageTrader, TradeExecution};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tracing::{info, warn};
+
+/// A triangle to execute, raced against `runner_up` when there is one
+pub struct TriangleOrder {
+    pub opportunity: ArbitrageOpportunity,
+    pub amount: f64,
+    pub runner_up: Option<(ArbitrageOpportunity, f64)>,
+    /// Top of book of every symbol involved, for the imbalance filter
+    pub local_book: HashMap<SymbolId, TopOfBook>,
+    /// USD price of every coin involved, for valuing dust
+    pub usd_prices: HashMap<String, f64>,
+    /// Depth of every symbol involved that is streamed, for paper fills
+    pub depth_books: HashMap<SymbolId, DepthBook>,
+    pub edge_half_life: Option<Duration>,
+    /// Strategy to place the triangle with instead of the trader's, when comparing them
+    pub strategy: Option<ExecutionStrategy>,
+}
+
+/// What became of a triangle order
+pub struct TriangleReport {
+    /// The opportunity traded, which is the runner-up if it won the race
+    pub opportunity: ArbitrageOpportunity,
+    pub amount: f64,
+    pub execution: Result<ArbitrageExecutionResult>,
+    /// Positions parked by this execution
+    pub parked: Vec<OpenPosition>,
+    /// Symbol penalties from the execution stats, for ranking
+    pub penalties: HashMap<String, f64>,
+    /// Every order placed by this execution, with Bybit's responses, and their fills
+    pub orders: OrderLog,
+}
+
+enum Request {
+    Triangle(Box<TriangleOrder>, oneshot::Sender<TriangleReport>),
+    Conversion {
+        symbol: String,
+        side: &'static str,
+        amount: f64,
+        price: Price,
+        reply: oneshot::Sender<(Result<TradeExecution>, OrderLog)>,
+    },
+    ManagePositions(oneshot::Sender<(Vec<ClosedPosition>, OrderLog)>),
+    Resume {
+        state: Box<ExecutionState>,
+        mode: ResumeMode,
+        reply: oneshot::Sender<(Result<()>, OrderLog)>,
+    },
+    Finish(oneshot::Sender<()>),
+}
+
+/// Handle to the supervised task that owns the trader and places every order. Triangles
+/// are handed over without waiting, so the scanner keeps going while one executes.
+/// A panicking trader loses only the request in flight; the task restarts with a fresh one.
+pub struct Executor {
+    tx: mpsc::Sender<Request>,
+}
+
+/// A triangle handed to the execution task that hasn't reported back yet
+pub struct PendingTriangle {
+    opportunity: ArbitrageOpportunity,
+    amount: f64,
+    reply: oneshot::Receiver<TriangleReport>,
+}
+
+impl PendingTriangle {
+    /// The report once the execution is over, without waiting for it
+    pub fn try_report(&mut self) -> Option<TriangleReport> {
+        match self.reply.try_recv() {
+            Ok(report) => Some(report),
+            Err(oneshot::error::TryRecvError::Empty) => None,
+            Err(oneshot::error::TryRecvError::Closed) => Some(self.lost()),
+        }
+    }
+
+    /// Wait for the execution to finish
+    pub async fn report(&mut self) -> TriangleReport {
+        match (&mut self.reply).await {
+            Ok(report) => report,
+            Err(_) => self.lost(),
+        }
+    }
+
+    /// Report of a triangle whose execution task went away mid-request
+    fn lost(&self) -> TriangleReport {
+        failed_report(
+            self.opportunity.clone(),
+            self.amount,
+            anyhow!("Execution task stopped before finishing the request"),
+        )
+    }
+}
+
+fn failed_report(
+    opportunity: ArbitrageOpportunity,
+    amount: f64,
+    error: anyhow::Error,
+) -> TriangleReport {
+    TriangleReport {
+        opportunity,
+        amount,
+        execution: Err(error),
+        parked: Vec::new(),
+        penalties: HashMap::new(),
+        orders: OrderLog::default(),
+    }
+}
+
+impl Executor {
+    /// Start the execution task; `build` creates the trader, again after every restart
+    pub fn spawn<E, F>(restart_on_panic: bool, build: F) -> Self
+    where
+        E: ExchangeApi + 'static,
+        F: Fn() -> ArbitrageTrader<E> + Send + Sync + 'static,
+    {
+        let (tx, rx) = mpsc::channel(1);
+        let rx = Arc::new(Mutex::new(rx));
+        supervisor::spawn_supervised("Execution".to_string(), restart_on_panic, move || {
+            run(build(), rx.clone())
+        });
+        Self { tx }
+    }
+
+    async fn request<T>(&self, make: impl FnOnce(oneshot::Sender<T>) -> Request) -> Result<T> {
+        let (reply, response) = oneshot::channel();
+        self.tx
+            .send(make(reply))
+            .await
+            .map_err(|_| anyhow!("Execution task is not running"))?;
+        response
+            .await
+            .map_err(|_| anyhow!("Execution task stopped before finishing the request"))
+    }
+
+    /// Hand a triangle to the execution task without waiting for it. Fails if the task
+    /// is still busy with an earlier request or isn't running.
+    pub fn submit(&self, order: TriangleOrder) -> Result<PendingTriangle> {
+        let (opportunity, amount) = (order.opportunity.clone(), order.amount);
+        let (reply, response) = oneshot::channel();
+        self.tx
+            .try_send(Request::Triangle(Box::new(order), reply))
+            .map_err(|e| match e {
+                mpsc::error::TrySendError::Full(_) => anyhow!("Execution task is busy"),
+                mpsc::error::TrySendError::Closed(_) => anyhow!("Execution task is not running"),
+            })?;
+        Ok(PendingTriangle {
+            opportunity,
+            amount,
+            reply: response,
+        })
+    }
+
+    /// Place a single market order, e.g. a stablecoin conversion, and wait for the fill.
+    /// Also returns the orders it placed and filled.
+    pub async fn convert(
+        &self,
+        symbol: &str,
+        side: &'static str,
+        amount: f64,
+        price: Price,
+    ) -> (Result<TradeExecution>, OrderLog) {
+        self.request(|reply| Request::Conversion {
+            symbol: symbol.to_string(),
+            side,
+            amount,
+            price,
+            reply,
+        })
+        .await
+        .unwrap_or_else(|e| (Err(e), OrderLog::default()))
+    }
+
+    /// Exit or liquidate parked positions; returns those closed and the orders placed
+    pub async fn manage_positions(&self) -> (Vec<ClosedPosition>, OrderLog) {
+        self.request(Request::ManagePositions)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("⚠️ Parked positions not checked: {e}");
+                (Vec::new(), OrderLog::default())
+            })
+    }
+
+    /// Roll back or complete a triangle a previous run left half-done; also returns the
+    /// orders placed
+    pub async fn resume(&self, state: ExecutionState, mode: ResumeMode) -> (Result<()>, OrderLog) {
+        self.request(|reply| Request::Resume {
+            state: Box::new(state),
+            mode,
+            reply,
+        })
+        .await
+        .unwrap_or_else(|e| (Err(e), OrderLog::default()))
+    }
+
+    /// Log the execution stats and save the precision cache
+    pub async fn finish(&self) {
+        if let Err(e) = self.request(Request::Finish).await {
+            warn!("⚠️ Execution task did not finish cleanly: {e}");
+        }
+    }
+}
+
+async fn run<E: ExchangeApi>(
+    mut trader: ArbitrageTrader<E>,
+    rx: Arc<Mutex<mpsc::Receiver<Request>>>,
+) {
+    let mut rx = rx.lock().await;
+    info!("🩺 Execution backend: {}", trader.health());
+    while let Some(request) = rx.recv().await {
+        match request {
+            Request::Triangle(order, reply) => {
+                reply.send(execute_triangle(&mut trader, *order).await).ok();
+            }
+            Request::Conversion {
+                symbol,
+                side,
+                amount,
+                price,
+                reply,
+            } => {
+                let execution = trader
+                    .execute_conversion(&symbol, side, amount, price)
+                    .await;
+                reply.send((execution, trader.take_order_log())).ok();
+            }
+            Request::ManagePositions(reply) => {
+                let closed = if trader.has_open_positions() {
+                    trader.manage_positions(chrono::Utc::now()).await
+                } else {
+                    Vec::new()
+                };
+                reply.send((closed, trader.take_order_log())).ok();
+            }
+            Request::Resume { state, mode, reply } => {
+                let outcome = match mode {
+                    ResumeMode::Rollback => TradeExecutor::rollback(&mut trader, &state).await,
+                    mode => trader.resume_interrupted(&state, mode).await,
+                };
+                reply.send((outcome, trader.take_order_log())).ok();
+            }
+            Request::Finish(reply) => {
+                trader.execution_stats().log_summary();
+                info!("🩺 Execution backend: {}", trader.health());
+                if let Err(e) = trader.get_precision_manager().auto_save_cache().await {
+                    warn!("⚠️ Failed to save precision cache on exit: {e}");
+                }
+                reply.send(()).ok();
+            }
+        }
+    }
+    // Every handle is gone; park instead of returning so the supervisor doesn't restart us
+    std::future::pending::<()>().await;
+}
+
+async fn execute_triangle<E: ExchangeApi>(
+    trader: &mut ArbitrageTrader<E>,
+    order: TriangleOrder,
+) -> TriangleReport {
+    // Orders of conversions and parked position exits aren't part of this triangle
+    trader.take_order_log();
+    trader.set_edge_half_life(order.edge_half_life);
+    trader.set_local_book(order.local_book);
+    trader.set_usd_prices(order.usd_prices);
+    trader.set_depth_books(order.depth_books);
+    if let Some(strategy) = order.strategy {
+        trader.set_execution_strategy(strategy);
+    }
+    let (opportunity, amount, execution) = match order.runner_up {
+        Some((second, second_amount)) => {
+            let (winner, execution) = trader
+                .execute_race([(&order.opportunity, order.amount), (&second, second_amount)])
+                .await;
+            if winner == 0 {
+                (order.opportunity, order.amount, execution)
+            } else {
+                (second, second_amount, execution)
+            }
+        }
+        None => {
+            let execution = trader
+                .execute_arbitrage(&order.opportunity, order.amount)
+                .await;
+            (order.opportunity, order.amount, execution)
+        }
+    };
+
+    // Save precision cache after successful trade
+    if execution.as_ref().is_ok_and(|r| r.success) {
+        if let Err(e) = trader.get_precision_manager().auto_save_cache().await {
+            warn!("⚠️ Failed to save precision cache: {e}");
+        }
+    }
+
+    TriangleReport {
+        opportunity,
+        amount,
+        execution,
+        parked: trader.take_parked_positions(),
+        penalties: trader.execution_stats().penalties(),
+        orders: trader.take_order_log(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{MockExchange, MockMarket};
+
+    use crate::precision::PrecisionManager;
+
+    fn exchange() -> MockExchange {
+        MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_market("ETHBTC", MockMarket::new("ETH", "BTC", 0.0499, 0.05))
+            .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_560.0))
+            .with_balance("USDT", 200.0)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_triangle_runs_on_the_execution_task() {
+        let precision = PrecisionManager::from_instruments(
+            exchange().get_all_spot_instruments().await.unwrap(),
+        );
+        let executor = Executor::spawn(true, move || {
+            ArbitrageTrader::new(exchange(), false, precision.clone())
+        });
+
+        let order = || TriangleOrder {
+            opportunity: ArbitrageOpportunity::test(),
+            amount: 100.0,
+            runner_up: None,
+            local_book: HashMap::new(),
+            usd_prices: HashMap::new(),
+            depth_books: HashMap::new(),
+            edge_half_life: None,
+            strategy: None,
+        };
+        let mut pending = executor.submit(order()).unwrap();
+
+        // Handed over without waiting: nothing is reported until the task has run it
+        assert!(pending.try_report().is_none());
+        let report = pending.report().await;
+
+        let result = report.execution.unwrap();
+        assert!(result.success, "{:?}", result.error_message);
+        assert_eq!(report.amount, 100.0);
+        assert!(report.parked.is_empty());
+        assert_eq!(report.orders.placed.len(), 3);
+        assert_eq!(report.orders.fills.len(), 3);
+        assert!(executor.manage_positions().await.0.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_busy_or_lost_triangles_are_reported() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let executor = Executor { tx };
+        let order = || TriangleOrder {
+            opportunity: ArbitrageOpportunity::test(),
+            amount: 100.0,
+            runner_up: None,
+            local_book: HashMap::new(),
+            usd_prices: HashMap::new(),
+            depth_books: HashMap::new(),
+            edge_half_life: None,
+            strategy: None,
+        };
+
+        let mut pending = executor.submit(order()).unwrap();
+        let busy = executor.submit(order()).err().unwrap();
+        assert_eq!(busy.to_string(), "Execution task is busy");
+
+        // The task takes the triangle, then goes away without answering
+        drop(rx.recv().await);
+        let report = pending.report().await;
+        let error = report.execution.unwrap_err().to_string();
+        assert!(error.contains("stopped before finishing"), "{error}");
+        assert_eq!(report.amount, 100.0);
+    }
+}
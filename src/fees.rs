@@ -0,0 +1,132 @@
+use crate::exchange::ExchangeApi;
+use crate::models::FeeRateInfo;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+/// Maker and taker fee rates of a symbol, as fractions (0.001 is 0.1%)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeRate {
+    pub maker: f64,
+    pub taker: f64,
+}
+
+/// The account's fee rates per symbol, from Bybit's fee-rate endpoint. Symbols it didn't
+/// list pay the configured rate; every rate is scaled by the configured fee discount.
+#[derive(Debug, Clone)]
+pub struct FeeSchedule {
+    default: FeeRate,
+    rates: HashMap<String, FeeRate>,
+    discount: f64,
+}
+
+impl FeeSchedule {
+    /// Every symbol at `rate`, maker and taker alike
+    pub fn flat(rate: f64) -> Self {
+        Self {
+            default: FeeRate {
+                maker: rate,
+                taker: rate,
+            },
+            rates: HashMap::new(),
+            discount: 1.0,
+        }
+    }
+
+    /// Scale every rate, e.g. by the discount for fees paid in MNT
+    pub fn with_discount(mut self, discount: f64) -> Self {
+        self.discount = discount;
+        self
+    }
+
+    /// Take up the account's rates; entries that don't parse keep the default
+    pub fn with_rates(mut self, list: &[FeeRateInfo]) -> Self {
+        for info in list {
+            let (Ok(maker), Ok(taker)) = (
+                info.maker_fee_rate.parse::<f64>(),
+                info.taker_fee_rate.parse::<f64>(),
+            ) else {
+                continue;
+            };
+            self.rates
+                .insert(info.symbol.clone(), FeeRate { maker, taker });
+        }
+        self
+    }
+
+    /// The account's spot rates, or every symbol at `rate` if they can't be fetched
+    pub async fn load<E: ExchangeApi + ?Sized>(client: &E, rate: f64, discount: f64) -> Self {
+        let schedule = Self::flat(rate).with_discount(discount);
+        match client.get_fee_rates("spot").await {
+            Ok(list) => {
+                let schedule = schedule.with_rates(&list);
+                let takers = schedule.rates.values().map(|r| r.taker * 100.0);
+                let low = takers.clone().fold(f64::INFINITY, f64::min);
+                let high = takers.fold(f64::NEG_INFINITY, f64::max);
+                if schedule.rates.is_empty() {
+                    warn!(
+                        "⚠️ No account fee rates listed - using {:.4}% on every symbol",
+                        rate * 100.0
+                    );
+                } else {
+                    info!(
+                        "💸 FEES: Account rates for {} symbols, taker {low:.4}%-{high:.4}%",
+                        schedule.rates.len()
+                    );
+                }
+                schedule
+            }
+            Err(e) => {
+                warn!(
+                    "⚠️ Could not fetch account fee rates ({e:#}) - using {:.4}% on every symbol",
+                    rate * 100.0
+                );
+                schedule
+            }
+        }
+    }
+
+    /// Rate paid by market orders on `symbol`
+    pub fn taker(&self, symbol: &str) -> f64 {
+        self.rates.get(symbol).unwrap_or(&self.default).taker * self.discount
+    }
+
+    /// Rate paid by resting limit orders on `symbol`
+    pub fn maker(&self, symbol: &str) -> f64 {
+        self.rates.get(symbol).unwrap_or(&self.default).maker * self.discount
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{MockExchange, MockMarket};
+
+    #[tokio::test]
+    async fn test_account_rates_override_the_configured_rate() {
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0).with_fee_rate(0.0006),
+            )
+            .with_market(
+                "USDCUSDT",
+                MockMarket::new("USDC", "USDT", 0.9999, 1.0).with_fee_rate(0.0),
+            );
+        let fees = FeeSchedule::load(&exchange, 0.001, 0.5).await;
+
+        assert_eq!(fees.taker("BTCUSDT"), 0.0003);
+        assert_eq!(fees.maker("USDCUSDT"), 0.0);
+        // Not listed: the configured rate, discounted
+        assert_eq!(fees.taker("ETHBTC"), 0.0005);
+    }
+
+    #[test]
+    fn test_unparseable_rates_keep_the_default() {
+        let fees = FeeSchedule::flat(0.001).with_rates(&[FeeRateInfo {
+            symbol: "BTCUSDT".to_string(),
+            taker_fee_rate: "".to_string(),
+            maker_fee_rate: "0.0008".to_string(),
+        }]);
+        assert_eq!(fees.taker("BTCUSDT"), 0.001);
+    }
+}
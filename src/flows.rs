@@ -0,0 +1,135 @@
+use crate::exchange::ExchangeApi;
+use crate::supervisor;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::{sleep, Duration};
+use tracing::warn;
+
+/// Deposit status once Bybit has credited it
+const DEPOSIT_CREDITED: i32 = 3;
+
+/// A deposit or withdrawal made outside the bot
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalFlow {
+    pub id: String,
+    pub coin: String,
+    /// Positive for a deposit, negative for a withdrawal including its fee
+    pub amount: f64,
+}
+
+impl ExternalFlow {
+    pub fn kind(&self) -> &'static str {
+        if self.amount >= 0.0 {
+            "deposit"
+        } else {
+            "withdrawal"
+        }
+    }
+}
+
+/// Deposits credited and withdrawals completed since `since` that aren't in `seen` yet
+async fn poll<E: ExchangeApi + ?Sized>(
+    exchange: &E,
+    since: DateTime<Utc>,
+    seen: &mut HashSet<String>,
+) -> Result<Vec<ExternalFlow>> {
+    let deposits = exchange
+        .get_deposits_since(since)
+        .await
+        .context("Failed to fetch deposits")?;
+    let withdrawals = exchange
+        .get_withdrawals_since(since)
+        .await
+        .context("Failed to fetch withdrawals")?;
+
+    let deposits = deposits
+        .into_iter()
+        .filter(|d| d.status == DEPOSIT_CREDITED)
+        .map(|d| ExternalFlow {
+            id: format!("deposit:{}", d.tx_id),
+            amount: d.amount.parse().unwrap_or(0.0),
+            coin: d.coin,
+        });
+    let withdrawals = withdrawals
+        .into_iter()
+        .filter(|w| w.status == "success")
+        .map(|w| {
+            let amount: f64 = w.amount.parse().unwrap_or(0.0);
+            let fee: f64 = w.withdraw_fee.parse().unwrap_or(0.0);
+            ExternalFlow {
+                id: format!("withdrawal:{}", w.withdraw_id),
+                amount: -(amount + fee),
+                coin: w.coin,
+            }
+        });
+    Ok(deposits
+        .chain(withdrawals)
+        .filter(|flow| flow.amount != 0.0 && seen.insert(flow.id.clone()))
+        .collect())
+}
+
+/// Poll the account's deposit and withdrawal history every `interval_secs` on a supervised
+/// task, sending each flow completed since startup once
+pub fn spawn(
+    exchange: Arc<dyn ExchangeApi>,
+    interval_secs: u64,
+    restart_on_panic: bool,
+) -> mpsc::UnboundedReceiver<ExternalFlow> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let since = Utc::now();
+    // Survives restarts of the task, so no flow is reported twice
+    let seen = Arc::new(Mutex::new(HashSet::new()));
+    supervisor::spawn_supervised("External flows".to_string(), restart_on_panic, move || {
+        let (exchange, tx, seen) = (exchange.clone(), tx.clone(), seen.clone());
+        async move {
+            loop {
+                sleep(Duration::from_secs(interval_secs)).await;
+                let mut seen = seen.lock().await;
+                match poll(&*exchange, since, &mut seen).await {
+                    Ok(flows) => {
+                        for flow in flows {
+                            if tx.send(flow).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(e) => warn!("⚠️ Could not check deposits and withdrawals: {e:#}"),
+                }
+            }
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::MockExchange;
+
+    #[tokio::test]
+    async fn test_completed_flows_since_startup_are_reported_once() {
+        let exchange = MockExchange::new();
+        let since = Utc::now() - chrono::Duration::seconds(1);
+        let mut seen = HashSet::new();
+        exchange.deposit("USDT", 500.0);
+        exchange.withdraw("ETH", 1.5, 0.25);
+
+        let flows = poll(&exchange, since, &mut seen).await.unwrap();
+        let summary: Vec<_> = flows
+            .iter()
+            .map(|f| (f.kind(), f.coin.as_str(), f.amount))
+            .collect();
+        assert_eq!(
+            summary,
+            [("deposit", "USDT", 500.0), ("withdrawal", "ETH", -1.75)]
+        );
+
+        exchange.deposit("USDT", 20.0);
+        let flows = poll(&exchange, since, &mut seen).await.unwrap();
+        assert_eq!(flows.len(), 1);
+        assert_eq!(flows[0].amount, 20.0);
+    }
+}
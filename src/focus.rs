@@ -0,0 +1,114 @@
+use anyhow::{bail, Result};
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// A reduced universe to scan, e.g. during a volatile event on one asset: triangles
+/// through any of `coins`, plus exactly the listed `triangles`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Focus {
+    coins: BTreeSet<String>,
+    /// Triangles by their three coins, in either direction and from any start
+    triangles: Vec<BTreeSet<String>>,
+}
+
+impl Focus {
+    /// Parse comma-separated coins (`BTC,SOL`) and triangles (`USDT-BTC-ETH`)
+    pub fn parse(coins: &str, triangles: &str) -> Result<Self> {
+        let list = |value: &str| -> Vec<String> {
+            value
+                .split(',')
+                .map(|item| item.trim().to_uppercase())
+                .filter(|item| !item.is_empty())
+                .collect()
+        };
+        let coins: BTreeSet<String> = list(coins).into_iter().collect();
+        let mut parsed = Vec::new();
+        for triangle in list(triangles) {
+            let triangle_coins: BTreeSet<String> = triangle
+                .split('-')
+                .map(|coin| coin.trim().to_string())
+                .collect();
+            if triangle_coins.len() != 3 || triangle_coins.contains("") {
+                bail!("'{triangle}' is not a triangle of three coins like USDT-BTC-ETH");
+            }
+            parsed.push(triangle_coins);
+        }
+        if coins.is_empty() && parsed.is_empty() {
+            bail!("name at least one coin or triangle to focus on");
+        }
+        Ok(Self {
+            coins,
+            triangles: parsed,
+        })
+    }
+
+    /// Whether a triangle path such as [USDT, BTC, ETH, USDT] is in the focus
+    pub fn allows(&self, path: &[String]) -> bool {
+        path.iter().any(|coin| self.coins.contains(coin))
+            || self
+                .triangles
+                .iter()
+                .any(|triangle| path.len() == 4 && path.iter().all(|coin| triangle.contains(coin)))
+    }
+}
+
+impl fmt::Display for Focus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let triangles = self
+            .triangles
+            .iter()
+            .map(|t| t.iter().cloned().collect::<Vec<_>>().join("-"));
+        let items: Vec<String> = self.coins.iter().cloned().chain(triangles).collect();
+        write!(f, "{}", items.join(", "))
+    }
+}
+
+/// Symbols the WebSocket connections stream while a focus is active, all of theirs
+/// otherwise. Shared by the connections and the main loop.
+#[derive(Debug, Default)]
+pub struct FocusFilter {
+    symbols: Mutex<Option<HashSet<String>>>,
+}
+
+impl FocusFilter {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Stream only `symbols`, or everything again with None
+    pub fn set(&self, symbols: Option<HashSet<String>>) {
+        *self.symbols.lock().unwrap_or_else(|e| e.into_inner()) = symbols;
+    }
+
+    pub fn allows(&self, symbol: &str) -> bool {
+        self.symbols
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .is_none_or(|symbols| symbols.contains(symbol))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(coins: &str) -> Vec<String> {
+        coins.split('-').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_focus_on_coins_and_triangles() {
+        let focus = Focus::parse("sol", "USDT-BTC-ETH").unwrap();
+        assert_eq!(focus.to_string(), "SOL, BTC-ETH-USDT");
+        assert!(focus.allows(&path("USDT-SOL-BTC-USDT")));
+        // A listed triangle matches in either direction and from any start
+        assert!(focus.allows(&path("USDT-ETH-BTC-USDT")));
+        assert!(focus.allows(&path("BTC-ETH-USDT-BTC")));
+        assert!(!focus.allows(&path("USDT-BTC-XRP-USDT")));
+
+        assert!(Focus::parse("", "").is_err());
+        assert!(Focus::parse("", "USDT-BTC").is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use crate::balance::BalanceManager;
+use crate::stable::STABLECOINS;
+use anyhow::{anyhow, Result};
+
+/// Proportional shares below this fraction of ORDER_SIZE aren't worth a trade
+const MIN_SHARE_OF_ORDER: f64 = 0.5;
+
+/// Which coins trades are funded from, and so which coins triangles start and end in
+#[derive(Debug, Clone, PartialEq)]
+pub enum FundingMode {
+    /// Only USDT
+    UsdtOnly,
+    /// Whichever USD stablecoin has the largest balance
+    LargestStable,
+    /// Every USD stablecoin, with trade sizes proportional to its share of stable holdings
+    Proportional,
+    /// Every listed coin, stablecoin or not, holding enough for a trade
+    Coins(Vec<String>),
+}
+
+impl FundingMode {
+    /// Parse `FUNDING_MODE`: `usdt`, `largest_stable`, `proportional` or `coins`, which
+    /// funds trades from the `FUNDING_COINS` list
+    pub fn parse(value: &str, coins: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "usdt" => Ok(FundingMode::UsdtOnly),
+            "largest_stable" => Ok(FundingMode::LargestStable),
+            "proportional" => Ok(FundingMode::Proportional),
+            "coins" => {
+                let coins: Vec<String> = coins
+                    .split(',')
+                    .map(|c| c.trim().to_uppercase())
+                    .filter(|c| !c.is_empty())
+                    .collect();
+                if coins.is_empty() {
+                    return Err(anyhow!("FUNDING_MODE=coins needs FUNDING_COINS"));
+                }
+                Ok(FundingMode::Coins(coins))
+            }
+            other => Err(anyhow!(
+                "unknown funding mode '{other}' (expected usdt, largest_stable, proportional or coins)"
+            )),
+        }
+    }
+}
+
+/// A coin trades may start from, and how much of it to commit per trade
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundingSource {
+    pub coin: String,
+    pub amount: f64,
+}
+
+/// Coins that can fund a trade worth `order_size` USD under the given mode, largest
+/// holding first. Amounts are in each coin, converted at `usd_price`; a stablecoin
+/// without a price counts at par, any other coin without one can't fund a trade.
+pub fn funding_sources(
+    mode: &FundingMode,
+    balance_manager: &BalanceManager,
+    order_size: f64,
+    usd_price: impl Fn(&str) -> Option<f64>,
+) -> Vec<FundingSource> {
+    let candidates: Vec<&str> = match mode {
+        FundingMode::Coins(coins) => coins.iter().map(String::as_str).collect(),
+        _ => STABLECOINS.to_vec(),
+    };
+    // (coin, USD price, USD value held)
+    let mut holdings: Vec<(&str, f64, f64)> = candidates
+        .into_iter()
+        .filter_map(|coin| {
+            let price = usd_price(coin)
+                .or_else(|| STABLECOINS.contains(&coin).then_some(1.0))
+                .filter(|p| *p > 0.0)?;
+            let value = balance_manager.get_balance(coin) * price;
+            (value > 0.0).then_some((coin, price, value))
+        })
+        .collect();
+    holdings.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+    let funded = |coin: &str, price: f64, amount_usd: f64| FundingSource {
+        coin: coin.to_string(),
+        amount: amount_usd / price,
+    };
+
+    match mode {
+        FundingMode::UsdtOnly => holdings
+            .iter()
+            .filter(|(coin, _, value)| *coin == "USDT" && *value >= order_size)
+            .map(|(coin, price, _)| funded(coin, *price, order_size))
+            .collect(),
+        FundingMode::LargestStable => holdings
+            .first()
+            .filter(|(_, _, value)| *value >= order_size)
+            .map(|(coin, price, _)| funded(coin, *price, order_size))
+            .into_iter()
+            .collect(),
+        FundingMode::Proportional => {
+            // Sized so the average trade is ORDER_SIZE, never more than the coin holds
+            let total: f64 = holdings.iter().map(|(_, _, value)| value).sum();
+            let count = holdings.len() as f64;
+            holdings
+                .iter()
+                .map(|(coin, price, value)| {
+                    let amount_usd = (order_size * count * value / total).min(*value);
+                    (funded(coin, *price, amount_usd), amount_usd)
+                })
+                .filter(|(_, amount_usd)| *amount_usd >= order_size * MIN_SHARE_OF_ORDER)
+                .map(|(source, _)| source)
+                .collect()
+        }
+        FundingMode::Coins(_) => holdings
+            .iter()
+            .filter(|(_, _, value)| *value >= order_size)
+            .map(|(coin, price, _)| funded(coin, *price, order_size))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::MockExchange;
+
+    async fn balances(coins: &[(&str, f64)]) -> BalanceManager {
+        let exchange = coins
+            .iter()
+            .fold(MockExchange::new(), |ex, (coin, amount)| {
+                ex.with_balance(coin, *amount)
+            });
+        let mut manager = BalanceManager::new();
+        manager.update_balances(&exchange).await.unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_parse_funding_mode() {
+        assert_eq!(FundingMode::parse("", "").unwrap(), FundingMode::UsdtOnly);
+        assert_eq!(
+            FundingMode::parse(" Largest_Stable ", "").unwrap(),
+            FundingMode::LargestStable
+        );
+        assert_eq!(
+            FundingMode::parse("coins", "usdt, BTC,").unwrap(),
+            FundingMode::Coins(vec!["USDT".to_string(), "BTC".to_string()])
+        );
+        assert!(FundingMode::parse("coins", "").is_err());
+        assert!(FundingMode::parse("usdc", "").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sources_per_mode() {
+        let manager =
+            balances(&[("USDT", 5.0), ("USDC", 60.0), ("FDUSD", 20.0), ("BTC", 1.0)]).await;
+        let coins = |sources: Vec<FundingSource>| {
+            sources
+                .into_iter()
+                .map(|s| (s.coin, s.amount))
+                .collect::<Vec<_>>()
+        };
+
+        let par = |_: &str| None;
+
+        // Not enough USDT for a $10 order
+        assert!(funding_sources(&FundingMode::UsdtOnly, &manager, 10.0, par).is_empty());
+        assert_eq!(
+            coins(funding_sources(
+                &FundingMode::LargestStable,
+                &manager,
+                10.0,
+                par
+            )),
+            vec![("USDC".to_string(), 10.0)]
+        );
+        // $85 across three stables: USDC gets 60/85 of $30, FDUSD 20/85, USDT too little
+        let proportional = coins(funding_sources(
+            &FundingMode::Proportional,
+            &manager,
+            10.0,
+            par,
+        ));
+        assert_eq!(proportional.len(), 2);
+        assert_eq!(proportional[0].0, "USDC");
+        assert!((proportional[0].1 - 30.0 * 60.0 / 85.0).abs() < 1e-9);
+        assert_eq!(proportional[1].0, "FDUSD");
+    }
+
+    #[tokio::test]
+    async fn test_listed_coins_are_sized_at_live_prices() {
+        let manager = balances(&[("USDC", 60.0), ("BTC", 0.001), ("ETH", 0.002)]).await;
+        let prices = |coin: &str| match coin {
+            "BTC" => Some(50_000.0),
+            "USDC" => Some(0.999),
+            _ => None,
+        };
+        let mode = FundingMode::parse("coins", "BTC,ETH,USDC").unwrap();
+
+        let sources = funding_sources(&mode, &manager, 10.0, prices);
+        // BTC holds $50, USDC $59.94; ETH has no price, so it can't be sized
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].coin, "USDC");
+        assert!((sources[0].amount - 10.0 / 0.999).abs() < 1e-9);
+        assert_eq!(sources[1].coin, "BTC");
+        assert!((sources[1].amount - 0.0002).abs() < 1e-12);
+        assert!(funding_sources(&mode, &manager, 55.0, prices).len() == 1);
+    }
+}
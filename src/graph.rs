@@ -0,0 +1,235 @@
+use crate::fees::FeeSchedule;
+use crate::pairs::PairManager;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// How the engine looks for arbitrage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    /// Evaluate every cached triangle
+    Triangles,
+    /// Search a currency graph for profitable cycles with Bellman-Ford
+    Graph,
+}
+
+impl Algorithm {
+    /// Parse `ARBITRAGE_ALGORITHM`: `triangles` or `graph`
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "triangles" => Ok(Algorithm::Triangles),
+            "graph" => Ok(Algorithm::Graph),
+            other => Err(anyhow!(
+                "unknown arbitrage algorithm '{other}' (expected triangles or graph)"
+            )),
+        }
+    }
+}
+
+/// A cycle of trades from a coin back to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cycle {
+    /// Coins from the start back to it
+    pub path: Vec<String>,
+    /// Index in the pair manager's pairs of the pair traded at each leg
+    pub pairs: Vec<usize>,
+    /// What trading through the cycle at the best bid/ask returns after fees, in percent
+    pub profit_pct: f64,
+}
+
+/// One way to convert a coin: selling into the bid or buying at the ask of a pair
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    from: usize,
+    to: usize,
+    pair: usize,
+    /// -ln of the coins received per coin spent, after fees, so a cycle of negative total
+    /// weight is profitable
+    weight: f64,
+}
+
+/// Currencies as nodes and every tradeable pair as an edge each way
+pub struct CurrencyGraph {
+    currencies: Vec<String>,
+    index: HashMap<String, usize>,
+    edges: Vec<Edge>,
+}
+
+impl CurrencyGraph {
+    /// Graph of the liquid, active pairs with fresh quotes on both sides, limited to the
+    /// focused symbols while a focus is set
+    pub fn build(pair_manager: &PairManager, fees: &FeeSchedule) -> Self {
+        let mut graph = Self {
+            currencies: Vec::new(),
+            index: HashMap::new(),
+            edges: Vec::new(),
+        };
+        let now = Instant::now();
+        let focused = pair_manager.focused_symbols();
+        for (idx, pair) in pair_manager.get_pairs().iter().enumerate() {
+            if !pair.is_liquid
+                || !pair.is_active
+                || pair.bid_price <= 0.0
+                || pair.ask_price <= 0.0
+                || pair_manager.is_quote_stale(idx, now)
+                || focused.as_ref().is_some_and(|f| !f.contains(&pair.symbol))
+            {
+                continue;
+            }
+            let keep = (1.0 - fees.taker(&pair.symbol)).ln();
+            let (base, quote) = (graph.node(&pair.base), graph.node(&pair.quote));
+            graph.edges.push(Edge {
+                from: base,
+                to: quote,
+                pair: idx,
+                weight: -(pair.bid_price.ln() + keep),
+            });
+            graph.edges.push(Edge {
+                from: quote,
+                to: base,
+                pair: idx,
+                weight: -(keep - pair.ask_price.ln()),
+            });
+        }
+        graph
+    }
+
+    fn node(&mut self, currency: &str) -> usize {
+        if let Some(&node) = self.index.get(currency) {
+            return node;
+        }
+        self.currencies.push(currency.to_string());
+        self.index
+            .insert(currency.to_string(), self.currencies.len() - 1);
+        self.currencies.len() - 1
+    }
+
+    /// Cycles through `start` of 3 to `max_legs` legs returning at least `min_profit_pct`,
+    /// best first. Hop-bounded Bellman-Ford: after `k` rounds of relaxation each coin holds
+    /// its best path of `k` legs from `start`, and every edge back to `start` closes a cycle
+    /// of `k + 1` legs, so each length yields the best cycle per coin before the last leg.
+    pub fn cycles_through(&self, start: &str, max_legs: usize, min_profit_pct: f64) -> Vec<Cycle> {
+        let Some(&start) = self.index.get(start) else {
+            return Vec::new();
+        };
+        let n = self.currencies.len();
+        let mut dist = vec![f64::INFINITY; n];
+        dist[start] = 0.0;
+        // Edge that reached each coin in each round
+        let mut reached_by: Vec<Vec<Option<usize>>> = vec![vec![None; n]];
+        let mut cycles = Vec::new();
+
+        for legs in 1..max_legs {
+            let mut next = vec![f64::INFINITY; n];
+            let mut via = vec![None; n];
+            for (e, edge) in self.edges.iter().enumerate() {
+                if edge.to != start && dist[edge.from] + edge.weight < next[edge.to] {
+                    next[edge.to] = dist[edge.from] + edge.weight;
+                    via[edge.to] = Some(e);
+                }
+            }
+            dist = next;
+            reached_by.push(via);
+
+            if legs < 2 {
+                continue;
+            }
+            for (e, edge) in self.edges.iter().enumerate() {
+                if edge.to != start || !dist[edge.from].is_finite() {
+                    continue;
+                }
+                let profit_pct = ((-(dist[edge.from] + edge.weight)).exp() - 1.0) * 100.0;
+                if profit_pct < min_profit_pct {
+                    continue;
+                }
+                if let Some(cycle) = self.trace(&reached_by, edge.from, e, profit_pct) {
+                    cycles.push(cycle);
+                }
+            }
+        }
+        cycles.sort_by(|a, b| b.profit_pct.total_cmp(&a.profit_pct));
+        cycles
+    }
+
+    /// Walk the relaxation rounds back from `last`, the coin before the closing edge. None
+    /// when the path visits a coin or pair twice.
+    fn trace(
+        &self,
+        reached_by: &[Vec<Option<usize>>],
+        last: usize,
+        closing: usize,
+        profit_pct: f64,
+    ) -> Option<Cycle> {
+        let mut edges = vec![closing];
+        let mut node = last;
+        for round in reached_by[1..].iter().rev() {
+            let e = round[node]?;
+            edges.push(e);
+            node = self.edges[e].from;
+        }
+        edges.reverse();
+
+        let start = node;
+        let mut path = vec![self.currencies[start].clone()];
+        let mut pairs = Vec::with_capacity(edges.len());
+        for &e in &edges {
+            let edge = &self.edges[e];
+            let coin = &self.currencies[edge.to];
+            if (edge.to != start && path.contains(coin)) || pairs.contains(&edge.pair) {
+                return None;
+            }
+            path.push(coin.clone());
+            pairs.push(edge.pair);
+        }
+        Some(Cycle {
+            path,
+            pairs,
+            profit_pct,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::models::MarketPair;
+
+    fn pair(base: &str, quote: &str, price: f64) -> MarketPair {
+        MarketPair::test(base, quote, price, price)
+    }
+
+    #[test]
+    fn test_finds_profitable_cycles_of_every_length() {
+        // USDT → BTC → ETH → USDT returns 2%, and USDT → BTC → ETH → SOL → USDT 3%
+        let manager = PairManager::from_pairs(
+            Config::test_config(),
+            vec![
+                pair("BTC", "USDT", 50_000.0),
+                pair("ETH", "BTC", 0.05),
+                pair("ETH", "USDT", 2_550.0),
+                pair("SOL", "ETH", 0.04),
+                pair("SOL", "USDT", 103.0),
+            ],
+        );
+        let graph = CurrencyGraph::build(&manager, &FeeSchedule::flat(0.0));
+
+        let cycles = graph.cycles_through("USDT", 4, 1.0);
+        let found: Vec<(String, i64)> = cycles
+            .iter()
+            .map(|c| (c.path.join(","), (c.profit_pct * 100.0).round() as i64))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("USDT,BTC,ETH,SOL,USDT".to_string(), 300),
+                ("USDT,BTC,ETH,USDT".to_string(), 200),
+            ]
+        );
+        assert_eq!(cycles[1].pairs, [0, 1, 2]);
+
+        // Triangles only, and nothing through an unknown coin
+        assert_eq!(graph.cycles_through("USDT", 3, 1.0).len(), 1);
+        assert!(graph.cycles_through("DOGE", 4, 1.0).is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use crate::models::{MarketPair, TickerInfo};
+
+/// Best bid and ask of a symbol
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TopOfBook {
+    pub bid_price: f64,
+    pub ask_price: f64,
+    pub bid_size: f64,
+    pub ask_size: f64,
+}
+
+impl TopOfBook {
+    pub fn from_pair(pair: &MarketPair) -> Self {
+        Self {
+            bid_price: pair.bid_price,
+            ask_price: pair.ask_price,
+            bid_size: pair.bid_size,
+            ask_size: pair.ask_size,
+        }
+    }
+
+    pub fn from_ticker(ticker: &TickerInfo) -> Option<Self> {
+        let parse = |s: &Option<String>| s.as_deref()?.parse::<f64>().ok();
+        Some(Self {
+            bid_price: parse(&ticker.bid1_price)?,
+            ask_price: parse(&ticker.ask1_price)?,
+            bid_size: parse(&ticker.bid1_size)?,
+            ask_size: parse(&ticker.ask1_size)?,
+        })
+    }
+
+    /// How many times deeper the opposite side is than the side a market order on `side`
+    /// takes from. Above 1 the book is stacked against us: a Buy facing a thin ask under a
+    /// heavy bid, or a Sell facing a thin bid under a heavy ask.
+    pub fn imbalance_against(&self, side: &str) -> f64 {
+        let (taken, opposite) = if side == "Buy" {
+            (self.ask_size, self.bid_size)
+        } else {
+            (self.bid_size, self.ask_size)
+        };
+        if taken <= 0.0 {
+            return f64::INFINITY;
+        }
+        opposite / taken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imbalance_is_measured_against_our_side() {
+        let book = TopOfBook {
+            bid_price: 99.0,
+            ask_price: 100.0,
+            bid_size: 50.0,
+            ask_size: 5.0,
+        };
+        // Buyers are piling in over a thin ask: bad for a Buy, fine for a Sell
+        assert_eq!(book.imbalance_against("Buy"), 10.0);
+        assert_eq!(book.imbalance_against("Sell"), 0.1);
+
+        let empty_bid = TopOfBook {
+            bid_size: 0.0,
+            ..book
+        };
+        assert!(empty_bid.imbalance_against("Sell").is_infinite());
+    }
+}
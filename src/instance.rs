@@ -0,0 +1,266 @@
+use crate::client::BybitClient;
+use crate::config::Config;
+use crate::exchange::ExchangeApi;
+use crate::models::OrderInfo;
+use crate::positions::PositionManager;
+use crate::time_sync;
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::Path;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// orderLinkId prefix when ORDER_LINK_PREFIX is not set
+pub const DEFAULT_ORDER_LINK_PREFIX: &str = "arb";
+
+/// Longest prefix that keeps every orderLinkId within Bybit's 36 characters
+const MAX_PREFIX_LEN: usize = 8;
+
+/// Reject prefixes Bybit would refuse, or that would make the orderLinkId too long
+pub fn validate_prefix(prefix: &str) -> Result<()> {
+    ensure!(
+        !prefix.is_empty()
+            && prefix.len() <= MAX_PREFIX_LEN
+            && prefix.chars().all(|c| c.is_ascii_alphanumeric()),
+        "ORDER_LINK_PREFIX must be 1-{MAX_PREFIX_LEN} letters or digits (got {prefix:?})"
+    );
+    Ok(())
+}
+
+/// A fresh orderLinkId `<prefix>_<random>_<tag>`, e.g. `arb_5f0c..._race`
+pub fn order_link_id(prefix: &str, tag: impl std::fmt::Display) -> String {
+    let random = Uuid::new_v4().simple().to_string();
+    format!("{prefix}_{}_{tag}", &random[..20])
+}
+
+/// Whether an orderLinkId was set by an instance using `prefix`
+pub fn is_tagged(order_link_id: &str, prefix: &str) -> bool {
+    order_link_id
+        .strip_prefix(prefix)
+        .is_some_and(|rest| rest.starts_with('_'))
+}
+
+/// Exit orders of positions parked by a previous run, which the next run resumes
+fn parked_order_ids(positions_path: &str) -> Vec<String> {
+    PositionManager::load(chrono::Duration::zero(), Path::new(positions_path))
+        .positions()
+        .iter()
+        .map(|p| p.order_id.clone())
+        .collect()
+}
+
+/// Exclusive lock on a file, held until dropped. The OS releases it when the process
+/// exits, so a crash never leaves a stale lock behind.
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    /// Lock `path`, failing if another process holds it
+    pub fn acquire(path: &Path) -> Result<Self> {
+        crate::logger::create_parent_dir(path)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(path)
+            .with_context(|| format!("Failed to open lock file {}", path.display()))?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let owner = std::fs::read_to_string(path).unwrap_or_default();
+                return Err(anyhow!(
+                    "Another instance is already running (lock file {}{})",
+                    path.display(),
+                    match owner.trim() {
+                        "" => String::new(),
+                        pid => format!(", PID {pid}"),
+                    }
+                ));
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {}", path.display()))
+            }
+        }
+        // The owner's PID, for the error another instance reports
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+        info!("🔒 Instance lock held: {}", path.display());
+        Ok(Self { _file: file })
+    }
+}
+
+/// Open orders tagged with this bot's orderLinkId prefix that none of `own_order_ids`
+/// (e.g. parked positions) accounts for: a sign that another instance is trading on the
+/// same account
+pub async fn foreign_bot_orders<E: ExchangeApi + ?Sized>(
+    exchange: &E,
+    prefix: &str,
+    own_order_ids: &[String],
+) -> Result<Vec<OrderInfo>> {
+    Ok(exchange
+        .get_tagged_open_orders("spot", prefix)
+        .await?
+        .into_iter()
+        .filter(|order| !own_order_ids.contains(&order.order_id))
+        .collect())
+}
+
+/// Refuse to start while open orders suggest another instance is trading on the account,
+/// unless `force` is set. Failing to list orders only logs a warning.
+pub async fn check_exchange<E: ExchangeApi + ?Sized>(
+    exchange: &E,
+    prefix: &str,
+    positions_path: &str,
+    force: bool,
+) -> Result<()> {
+    let parked = parked_order_ids(positions_path);
+    let foreign = match foreign_bot_orders(exchange, prefix, &parked).await {
+        Ok(foreign) => foreign,
+        Err(e) => {
+            warn!("⚠️ Could not check open orders for another instance: {e:#}");
+            return Ok(());
+        }
+    };
+    if foreign.is_empty() {
+        return Ok(());
+    }
+    let orders = foreign
+        .iter()
+        .map(|o| format!("{} {} {} ({})", o.side, o.qty, o.symbol, o.order_link_id))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if force {
+        warn!("⚠️ Open orders from another instance: {orders} - starting anyway (--force)");
+        return Ok(());
+    }
+    bail!(
+        "Another instance appears to be trading on this account, open orders: {orders}. \
+         Stop it, or run `cancel-orders` (or pass --force) if these orders are left over"
+    )
+}
+
+/// Cancel the open orders left behind by this bot, keeping parked positions' exit orders
+/// and never touching manual orders or other bots'. Refuses while an instance is running.
+pub async fn run_cancel_orders(config: Config) -> Result<()> {
+    validate_prefix(&config.order_link_prefix)?;
+    let _lock = if config.instance_lock_file.is_empty() {
+        None
+    } else {
+        Some(
+            InstanceLock::acquire(Path::new(&config.instance_lock_file))
+                .context("Stop the running instance before cancelling its orders")?,
+        )
+    };
+    let client = BybitClient::new(config.clone()).context("Failed to create Bybit client")?;
+    time_sync::start_time_sync(&client, 0, false).await;
+    let cancelled = client
+        .cancel_tagged_orders(
+            "spot",
+            &config.order_link_prefix,
+            &parked_order_ids(&config.positions_path),
+        )
+        .await?;
+    for order in &cancelled {
+        println!(
+            "🗑️ Cancelled {} {} {} ({})",
+            order.side, order.qty, order.symbol, order.order_link_id
+        );
+    }
+    println!(
+        "{} order(s) tagged {}_ cancelled",
+        cancelled.len(),
+        config.order_link_prefix
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{MockExchange, MockMarket};
+    use crate::models::PlaceOrderRequest;
+
+    #[test]
+    fn test_second_lock_is_refused_until_released() {
+        let path =
+            std::env::temp_dir().join(format!("bybit_lock_test_{}.lock", std::process::id()));
+        let lock = InstanceLock::acquire(&path).unwrap();
+        let err = InstanceLock::acquire(&path).err().unwrap().to_string();
+        assert!(err.contains("already running"), "{err}");
+        assert!(err.contains(&std::process::id().to_string()), "{err}");
+        drop(lock);
+        assert!(InstanceLock::acquire(&path).is_ok());
+        std::fs::remove_file(&path).ok();
+    }
+
+    /// Rest a Buy limit below the book, returning its order id
+    async fn rest(exchange: &MockExchange, link: &str) -> String {
+        let request = PlaceOrderRequest {
+            category: "spot".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: "Buy".to_string(),
+            order_type: "Limit".to_string(),
+            qty: "0.001".to_string(),
+            price: Some("40000".to_string()),
+            time_in_force: Some("GTC".to_string()),
+            order_link_id: Some(link.to_string()),
+            reduce_only: None,
+        };
+        exchange.place_order(request).await.unwrap().order_id
+    }
+
+    #[tokio::test]
+    async fn test_open_orders_of_another_instance_are_found() {
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_balance("USDT", 1_000.0);
+        let parked = rest(&exchange, "arb_1_park").await;
+        rest(&exchange, "manual-order").await;
+        rest(&exchange, "arbx_3_race").await;
+        let other = rest(&exchange, "arb_2_race").await;
+
+        let foreign = foreign_bot_orders(&exchange, "arb", &[parked])
+            .await
+            .unwrap();
+        let ids: Vec<_> = foreign.iter().map(|o| o.order_id.clone()).collect();
+        assert_eq!(ids, vec![other]);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_only_touches_tagged_orders() {
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_balance("USDT", 1_000.0);
+        let parked = rest(&exchange, &order_link_id("bot1", "park")).await;
+        let leftover = rest(&exchange, &order_link_id("bot1", "race")).await;
+        let manual = rest(&exchange, "manual-order").await;
+        let other_bot = rest(&exchange, &order_link_id("bot2", "race")).await;
+
+        let cancelled = exchange
+            .cancel_tagged_orders("spot", "bot1", std::slice::from_ref(&parked))
+            .await
+            .unwrap();
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].order_id, leftover);
+
+        let open: Vec<_> = exchange
+            .get_open_orders("spot")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|o| o.order_id)
+            .collect();
+        assert_eq!(open, vec![parked, manual, other_bot]);
+        assert!(order_link_id("bot1", 3).len() <= 36);
+        assert!(validate_prefix("toolongprefix").is_err());
+    }
+}
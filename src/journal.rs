@@ -0,0 +1,534 @@
+use crate::bundle::OrderLog;
+use crate::models::{ArbitrageOpportunity, PlaceOrderRequest};
+use crate::positions::{ClosedPosition, OpenPosition};
+use crate::resume::{ExecutionState, ResumeMode};
+use crate::stable::StableSignal;
+use crate::sweeper::Sweep;
+use crate::trader::{ArbitrageExecutionResult, TradeExecution};
+use crate::writer::{RecordSink, SupervisedWriter};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::warn;
+
+/// Events recorded in the session journal
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEvent {
+    SessionStarted {
+        version: String,
+        dry_run: bool,
+        live_threshold_pct: f64,
+        shadow_threshold_pct: Option<f64>,
+        #[serde(default)]
+        alert_threshold_pct: Option<f64>,
+    },
+    LiveTrade {
+        path: String,
+        pairs: String,
+        estimated_profit_pct: f64,
+        amount: f64,
+        success: bool,
+        profit_usd: f64,
+        profit_pct: f64,
+        fees_usd: f64,
+        execution_time_ms: u64,
+        error: Option<String>,
+        /// Every fill of the execution, rollbacks included
+        #[serde(default)]
+        legs: Vec<JournalLeg>,
+    },
+    ShadowTrade {
+        path: String,
+        pairs: String,
+        estimated_profit_pct: f64,
+        amount: f64,
+        profit_usd: f64,
+        profit_pct: f64,
+        /// Whether the live threshold would also have accepted this opportunity
+        above_live_threshold: bool,
+    },
+    /// Opportunity above the alert threshold, logged for analysis
+    OpportunityAlert {
+        path: String,
+        pairs: String,
+        estimated_profit_pct: f64,
+        above_execution_threshold: bool,
+    },
+    /// Opportunity alerts of a digest window, instead of one record per alert
+    AlertDigest {
+        window_secs: u64,
+        alerts: u64,
+        above_execution_threshold: u64,
+        best_profit_pct: Option<f64>,
+        best_pairs: Option<String>,
+    },
+    /// Deposit (positive amount) or withdrawal made outside the bot, not a trading result
+    ExternalFlow {
+        flow_id: String,
+        coin: String,
+        amount: f64,
+        value_usd: Option<f64>,
+    },
+    /// Single-order conversion between two stablecoins
+    StableConversion {
+        symbol: String,
+        side: String,
+        amount: f64,
+        expected_price: f64,
+        net_deviation_pct: f64,
+        success: bool,
+        executed_qty: f64,
+        executed_value: f64,
+        fee: f64,
+        error: Option<String>,
+        #[serde(default)]
+        legs: Vec<JournalLeg>,
+    },
+    /// Leftover coin converted back to USDT by the dust sweeper
+    DustSwept {
+        coin: String,
+        symbol: String,
+        side: String,
+        amount: f64,
+        success: bool,
+        /// USDT received after fees
+        received: f64,
+        error: Option<String>,
+        #[serde(default)]
+        legs: Vec<JournalLeg>,
+    },
+    /// The adaptive controller moved the execution threshold
+    ThresholdAdjusted {
+        from_pct: f64,
+        to_pct: f64,
+        reason: String,
+    },
+    /// Coin parked behind a resting limit order after leg 3 failed
+    PositionParked {
+        symbol: String,
+        coin: String,
+        quantity: f64,
+        side: String,
+        limit_price: String,
+        order_id: String,
+    },
+    /// Parked position exited, at its limit price or by forced liquidation
+    PositionClosed {
+        symbol: String,
+        coin: String,
+        reason: String,
+        target_coin: String,
+        breakeven_amount: f64,
+        received: f64,
+        /// Market orders that liquidated the position
+        #[serde(default)]
+        legs: Vec<JournalLeg>,
+    },
+    /// Triangle a previous run left half-done, rolled back or completed on startup
+    InterruptedTradeResolved {
+        path: String,
+        pairs: String,
+        completed_legs: usize,
+        held_coin: String,
+        held_amount: f64,
+        /// rollback, complete or manual
+        action: String,
+        success: bool,
+        error: Option<String>,
+        #[serde(default)]
+        legs: Vec<JournalLeg>,
+    },
+    /// An order about to be sent, journaled before it leaves in audit mode
+    OrderIntent {
+        order_link_id: String,
+        symbol: String,
+        side: String,
+        order_type: String,
+        qty: String,
+        /// Limit price, or the price a market order was quoted at
+        quoted_price: Option<f64>,
+        /// Coin expected back at the quoted price, before fees
+        expected_fill: Option<f64>,
+    },
+    /// Journaled fills checked against the exchange's execution history
+    Reconciled {
+        legs_checked: usize,
+        mismatches: Vec<String>,
+        /// Correction to realized profit from fees charged differently than journaled
+        pnl_adjustment_usd: f64,
+    },
+    SessionEnded {
+        cycles: u64,
+        trades_completed: u64,
+    },
+}
+
+impl JournalEvent {
+    /// Live trade entry for an execution attempt, failed or not
+    pub fn live_trade(
+        opportunity: &ArbitrageOpportunity,
+        amount: f64,
+        execution: &Result<ArbitrageExecutionResult>,
+    ) -> Self {
+        JournalEvent::LiveTrade {
+            path: opportunity.display_path(),
+            pairs: opportunity.display_pairs(),
+            estimated_profit_pct: opportunity.estimated_profit_pct.value(),
+            amount,
+            success: execution.as_ref().is_ok_and(|r| r.success),
+            profit_usd: execution.as_ref().map_or(0.0, |r| r.actual_profit),
+            profit_pct: execution.as_ref().map_or(0.0, |r| r.actual_profit_pct),
+            fees_usd: execution.as_ref().map_or(0.0, |r| r.total_fees),
+            execution_time_ms: execution.as_ref().map_or(0, |r| r.execution_time_ms),
+            error: match execution {
+                Ok(r) => r.error_message.clone(),
+                Err(e) => Some(e.to_string()),
+            },
+            legs: Vec::new(),
+        }
+    }
+
+    /// Attach the fills behind a trade, conversion or liquidation
+    pub fn with_legs(mut self, fills: Vec<JournalLeg>) -> Self {
+        match &mut self {
+            JournalEvent::LiveTrade { legs, .. }
+            | JournalEvent::StableConversion { legs, .. }
+            | JournalEvent::DustSwept { legs, .. }
+            | JournalEvent::InterruptedTradeResolved { legs, .. }
+            | JournalEvent::PositionClosed { legs, .. } => *legs = fills,
+            _ => {}
+        }
+        self
+    }
+}
+
+impl JournalEvent {
+    /// Stable fast-path entry for a conversion attempt
+    pub fn stable_conversion(signal: &StableSignal, execution: &Result<TradeExecution>) -> Self {
+        JournalEvent::StableConversion {
+            symbol: signal.symbol.clone(),
+            side: signal.side.to_string(),
+            amount: signal.amount,
+            expected_price: signal.price.value(),
+            net_deviation_pct: signal.net_deviation_pct,
+            success: execution.is_ok(),
+            executed_qty: execution
+                .as_ref()
+                .map_or(0.0, |e| e.executed_quantity.value()),
+            executed_value: execution.as_ref().map_or(0.0, |e| e.executed_value.value()),
+            fee: execution.as_ref().map_or(0.0, |e| e.fee),
+            error: execution.as_ref().err().map(|e| e.to_string()),
+            legs: Vec::new(),
+        }
+    }
+}
+
+impl JournalEvent {
+    pub fn dust_swept(sweep: &Sweep, execution: &Result<TradeExecution>) -> Self {
+        JournalEvent::DustSwept {
+            coin: sweep.coin.clone(),
+            symbol: sweep.symbol.clone(),
+            side: sweep.side.to_string(),
+            amount: sweep.amount,
+            success: execution.is_ok(),
+            received: execution.as_ref().map_or(0.0, |e| e.received()),
+            error: execution.as_ref().err().map(|e| e.to_string()),
+            legs: Vec::new(),
+        }
+    }
+
+    pub fn position_parked(position: &OpenPosition) -> Self {
+        JournalEvent::PositionParked {
+            symbol: position.symbol.clone(),
+            coin: position.coin.clone(),
+            quantity: position.quantity,
+            side: position.side.clone(),
+            limit_price: position.limit_price.clone(),
+            order_id: position.order_id.clone(),
+        }
+    }
+
+    pub fn position_closed(closed: &ClosedPosition) -> Self {
+        JournalEvent::PositionClosed {
+            symbol: closed.position.symbol.clone(),
+            coin: closed.position.coin.clone(),
+            reason: closed.reason.to_string(),
+            target_coin: closed.position.target_coin.clone(),
+            breakeven_amount: closed.position.breakeven_amount,
+            received: closed.received,
+            legs: Vec::new(),
+        }
+    }
+
+    pub fn interrupted_trade(
+        state: &ExecutionState,
+        mode: ResumeMode,
+        outcome: &Result<()>,
+    ) -> Self {
+        JournalEvent::InterruptedTradeResolved {
+            path: state.opportunity.display_path(),
+            pairs: state.opportunity.display_pairs(),
+            completed_legs: state.completed_legs,
+            held_coin: state.held_coin.clone(),
+            held_amount: state.held_amount,
+            action: mode.as_str().to_string(),
+            success: outcome.is_ok(),
+            error: outcome.as_ref().err().map(|e| format!("{e:#}")),
+            legs: Vec::new(),
+        }
+    }
+}
+
+impl JournalEvent {
+    /// Intent to send `request`. Limit orders are priced at their limit, market orders at
+    /// `quoted_price` if known. Market Buys spend quote, every other order spends base.
+    pub fn order_intent(request: &PlaceOrderRequest, quoted_price: Option<f64>) -> Self {
+        let quoted_price = request
+            .price
+            .as_deref()
+            .and_then(|p| p.parse().ok())
+            .or(quoted_price)
+            .filter(|p: &f64| *p > 0.0);
+        let qty = request.qty.parse::<f64>().ok();
+        let expected_fill = match (request.side.as_str(), request.order_type.as_str()) {
+            ("Buy", "Market") => qty.zip(quoted_price).map(|(qty, price)| qty / price),
+            ("Buy", _) => qty,
+            _ => qty.zip(quoted_price).map(|(qty, price)| qty * price),
+        };
+        JournalEvent::OrderIntent {
+            order_link_id: request.order_link_id.clone().unwrap_or_default(),
+            symbol: request.symbol.clone(),
+            side: request.side.clone(),
+            order_type: request.order_type.clone(),
+            qty: request.qty.clone(),
+            quoted_price,
+            expected_fill,
+        }
+    }
+}
+
+/// A fill as the bot accounted for it, checked against the exchange's executions
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JournalLeg {
+    pub order_id: String,
+    pub order_link_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub qty: f64,
+    pub value: f64,
+    /// Fees charged, by coin
+    pub fees: BTreeMap<String, f64>,
+}
+
+impl JournalLeg {
+    /// One leg per filled order of the log, from the last time each was read
+    pub fn from_orders(orders: &OrderLog) -> Vec<Self> {
+        let mut legs: Vec<Self> = Vec::new();
+        for order in &orders.fills {
+            let leg = Self {
+                order_id: order.order_id.clone(),
+                order_link_id: order.order_link_id.clone(),
+                symbol: order.symbol.clone(),
+                side: order.side.clone(),
+                qty: order.cum_exec_qty.parse().unwrap_or(0.0),
+                value: order.cum_exec_value.parse().unwrap_or(0.0),
+                fees: orders
+                    .fees
+                    .get(&order.order_id)
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            match legs.iter_mut().find(|l| l.order_id == leg.order_id) {
+                Some(existing) => *existing = leg,
+                None => legs.push(leg),
+            }
+        }
+        legs.retain(|leg| leg.qty > 0.0);
+        legs
+    }
+}
+
+/// One line of the journal
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JournalRecord {
+    pub ts: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: JournalEvent,
+}
+
+enum Sink {
+    File(Mutex<BufWriter<File>>),
+    /// Records go to a supervised writer task, so a failing disk never stalls trading
+    Task(SupervisedWriter<JournalRecord>),
+}
+
+/// Append-only JSONL journal of trading activity
+pub struct Journal {
+    sink: Sink,
+}
+
+impl Journal {
+    /// Open (or create) the journal for appending
+    pub fn open(path: &Path) -> Result<Self> {
+        crate::logger::create_parent_dir(path)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open journal {}", path.display()))?;
+
+        Ok(Self {
+            sink: Sink::File(Mutex::new(BufWriter::new(file))),
+        })
+    }
+
+    /// Open the journal with writes handed to a supervised background task
+    pub fn spawn_writer(path: &Path, restart_on_panic: bool) -> Result<Self> {
+        // Fail fast on a path that can't be written at all
+        let journal = Self::open(path)?;
+        let path = path.to_path_buf();
+        let writer =
+            SupervisedWriter::spawn("Journal writer", restart_on_panic, journal, move || {
+                Self::open(&path)
+            });
+        Ok(Self {
+            sink: Sink::Task(writer),
+        })
+    }
+
+    /// Read every record of an existing journal, skipping lines that don't parse
+    /// (e.g. a line cut short by a crash). A missing file has no records.
+    pub fn read_records(path: &Path) -> Result<Vec<JournalRecord>> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read journal {}", path.display()))?;
+        Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Append an event; failures are logged rather than interrupting trading
+    pub fn record(&self, event: JournalEvent) {
+        if let Err(e) = self.try_record(event) {
+            warn!("⚠️ Failed to write journal entry: {e}");
+        }
+    }
+
+    /// Append an event, failing if it could not be written. On a journal opened with
+    /// `open`, the event is on disk once this returns.
+    pub fn try_record(&self, event: JournalEvent) -> Result<()> {
+        self.write(JournalRecord {
+            ts: Utc::now(),
+            event,
+        })
+    }
+
+    fn write(&self, record: JournalRecord) -> Result<()> {
+        match &self.sink {
+            Sink::File(writer) => {
+                let line = serde_json::to_string(&record)?;
+                let mut writer = writer
+                    .lock()
+                    .map_err(|_| anyhow::anyhow!("journal lock poisoned"))?;
+                writeln!(writer, "{line}")?;
+                // Flush every entry so the journal survives crashes
+                writer.flush()?;
+            }
+            Sink::Task(writer) => writer.send(record)?,
+        }
+        Ok(())
+    }
+
+    /// Wait until every event recorded so far is on disk
+    pub async fn flush(&self) {
+        if let Sink::Task(writer) = &self.sink {
+            writer.flush().await;
+        }
+    }
+}
+
+/// Journals opened with `open` are what the writer task writes to
+impl RecordSink<JournalRecord> for Journal {
+    fn write(&mut self, record: JournalRecord, _more: bool) -> Result<()> {
+        Journal::write(self, record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_are_appended_as_jsonl() {
+        let path = std::env::temp_dir().join(format!("journal_test_{}.jsonl", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let journal = Journal::open(&path).unwrap();
+        journal.record(JournalEvent::SessionEnded {
+            cycles: 5,
+            trades_completed: 1,
+        });
+        journal.record(JournalEvent::ShadowTrade {
+            path: "USDT → BTC → ETH → USDT".to_string(),
+            pairs: "BTCUSDT → ETHBTC → ETHUSDT".to_string(),
+            estimated_profit_pct: 0.2,
+            amount: 10.0,
+            profit_usd: 0.01,
+            profit_pct: 0.1,
+            above_live_threshold: false,
+        });
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("\"event\":\"session_ended\""));
+
+        let record: JournalRecord = serde_json::from_str(lines[1]).unwrap();
+        assert!(matches!(
+            record.event,
+            JournalEvent::ShadowTrade {
+                above_live_threshold: false,
+                ..
+            }
+        ));
+
+        // A torn final line is skipped when reading back
+        std::fs::write(&path, format!("{contents}{{\"ts\":")).unwrap();
+        assert_eq!(Journal::read_records(&path).unwrap().len(), 2);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_writer_task_persists_records_by_flush() {
+        let path =
+            std::env::temp_dir().join(format!("journal_task_test_{}.jsonl", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let journal = Journal::spawn_writer(&path, true).unwrap();
+        for cycles in 0..3 {
+            journal.record(JournalEvent::SessionEnded {
+                cycles,
+                trades_completed: 0,
+            });
+        }
+        journal.flush().await;
+
+        let records = Journal::read_records(&path).unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(matches!(
+            records[2].event,
+            JournalEvent::SessionEnded { cycles: 2, .. }
+        ));
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Histogram bucket upper bounds in seconds
+pub const BUCKETS_SECS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+/// Response times of one REST endpoint
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyHistogram {
+    /// Requests at or under each of `BUCKETS_SECS`, cumulative like Prometheus buckets
+    pub buckets: Vec<u64>,
+    pub count: u64,
+    pub sum_secs: f64,
+}
+
+impl LatencyHistogram {
+    pub fn observe(&mut self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        self.buckets.resize(BUCKETS_SECS.len(), 0);
+        for (bucket, &bound) in self.buckets.iter_mut().zip(&BUCKETS_SECS) {
+            if secs <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum_secs += secs;
+    }
+}
+
+/// Time to response headers per REST path, shared by every clone of the client
+#[derive(Debug, Default)]
+pub struct RequestLatencies {
+    histograms: Mutex<BTreeMap<String, LatencyHistogram>>,
+}
+
+impl RequestLatencies {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// A request to `url` got its response after `elapsed`; the histogram is keyed by the
+    /// API path, e.g. `/v5/order/create`, whichever domain served it
+    pub fn record(&self, url: &str, elapsed: Duration) {
+        let path = url.find("/v5/").map_or(url, |i| &url[i..]);
+        let path = path.split('?').next().unwrap_or(path);
+        let mut histograms = self.histograms.lock().unwrap_or_else(|e| e.into_inner());
+        match histograms.get_mut(path) {
+            Some(histogram) => histogram.observe(elapsed),
+            None => {
+                let mut histogram = LatencyHistogram::default();
+                histogram.observe(elapsed);
+                histograms.insert(path.to_string(), histogram);
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, LatencyHistogram> {
+        self.histograms
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requests_are_bucketed_per_api_path() {
+        let latencies = RequestLatencies::new();
+        latencies.record(
+            "https://api.bybit.com/v5/order/create",
+            Duration::from_millis(40),
+        );
+        latencies.record(
+            "https://api.bytick.com/v5/order/create",
+            Duration::from_millis(300),
+        );
+        latencies.record(
+            "https://api.bybit.com/v5/market/tickers?category=spot",
+            Duration::from_millis(3),
+        );
+
+        let snapshot = latencies.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        let orders = &snapshot["/v5/order/create"];
+        assert_eq!(orders.count, 2);
+        assert!((orders.sum_secs - 0.34).abs() < 1e-9);
+        // 25ms bucket holds neither, 50ms holds the first, 500ms both
+        assert_eq!(orders.buckets[2], 0);
+        assert_eq!(orders.buckets[3], 1);
+        assert_eq!(orders.buckets[6], 2);
+        assert_eq!(snapshot["/v5/market/tickers"].buckets[0], 1);
+    }
+}
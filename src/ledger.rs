@@ -0,0 +1,178 @@
+use crate::models::ArbitrageOpportunity;
+use crate::trader::ArbitrageExecutionResult;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Where the triangle ledger is kept so it survives restarts
+pub const LEDGER_FILE: &str = "triangle_ledger.json";
+/// Executions a triangle needs before its track record moves it in the ranking
+const MIN_EXECUTIONS: u64 = 3;
+/// Most a track record moves a triangle in the ranking, in percentage points
+const MAX_ADJUSTMENT_PCT: f64 = 0.5;
+
+/// Track record of one triangle, by its pairs in trading order
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TriangleRecord {
+    /// Times it showed up in a scan after being absent from the previous one
+    pub detected: u64,
+    pub executed: u64,
+    pub realized_pnl_usd: f64,
+    /// Estimated less realized profit summed over executions, in percentage points
+    pub slippage_pct_total: f64,
+}
+
+impl TriangleRecord {
+    pub fn average_slippage_pct(&self) -> f64 {
+        if self.executed == 0 {
+            return 0.0;
+        }
+        self.slippage_pct_total / self.executed as f64
+    }
+}
+
+/// Per-triangle detections, executions, realized profit and slippage, accumulated across
+/// runs so the ranking favours the paths that actually pay
+#[derive(Debug, Default)]
+pub struct TriangleLedger {
+    triangles: BTreeMap<String, TriangleRecord>,
+    path: Option<PathBuf>,
+    /// Triangles of the last scan that found any, so a triangle staying profitable over
+    /// many cycles counts as one detection
+    last_seen: HashSet<String>,
+}
+
+fn key(opportunity: &ArbitrageOpportunity) -> String {
+    opportunity.pairs.join(",")
+}
+
+impl TriangleLedger {
+    /// Restore the ledger of previous runs; a missing or unreadable file starts empty
+    pub fn load(path: &Path) -> Self {
+        Self {
+            triangles: crate::persist::load_json(path),
+            path: Some(path.to_path_buf()),
+            last_seen: HashSet::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Count the triangles a scan found that the previous one didn't
+    pub fn observe(&mut self, candidates: &[ArbitrageOpportunity]) {
+        // A cycle without price changes finds nothing new
+        if candidates.is_empty() {
+            return;
+        }
+        let seen: HashSet<String> = candidates.iter().map(key).collect();
+        for triangle in seen.difference(&self.last_seen) {
+            self.triangles.entry(triangle.clone()).or_default().detected += 1;
+        }
+        self.last_seen = seen;
+    }
+
+    /// Add an execution's outcome and persist the ledger
+    pub fn record_execution(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        result: &ArbitrageExecutionResult,
+    ) {
+        let record = self.triangles.entry(key(opportunity)).or_default();
+        record.executed += 1;
+        record.realized_pnl_usd += result.actual_profit;
+        record.slippage_pct_total +=
+            opportunity.estimated_profit_pct.value() - result.actual_profit_pct;
+        if let Err(e) = self.save() {
+            warn!("⚠️ Failed to save triangle ledger: {e:#}");
+        }
+    }
+
+    /// Percentage points to add to each triangle's ranking score: its average realized
+    /// edge over the estimate, once it has executed often enough to tell
+    pub fn ranking_adjustments(&self) -> HashMap<String, f64> {
+        self.triangles
+            .iter()
+            .filter(|(_, record)| record.executed >= MIN_EXECUTIONS)
+            .map(|(triangle, record)| {
+                let adjustment =
+                    (-record.average_slippage_pct()).clamp(-MAX_ADJUSTMENT_PCT, MAX_ADJUSTMENT_PCT);
+                (triangle.clone(), adjustment)
+            })
+            .collect()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        crate::persist::save_json(path, &self.triangles)?;
+        debug!("💾 Saved triangle ledger to {}", path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SCHEMA_VERSION;
+
+    fn result(profit_pct: f64) -> ArbitrageExecutionResult {
+        ArbitrageExecutionResult {
+            schema_version: SCHEMA_VERSION,
+            success: true,
+            initial_amount: 100.0,
+            actual_profit: profit_pct,
+            actual_profit_pct: profit_pct,
+            dust_value_usd: 0.0,
+            dust: BTreeMap::new(),
+            total_fees: 0.0,
+            execution_time_ms: 0,
+            error_message: None,
+            exchange_error: None,
+        }
+    }
+
+    #[test]
+    fn test_track_records_survive_restarts_and_adjust_the_ranking() {
+        let path = std::env::temp_dir().join(format!("ledger_test_{}.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let btc = ArbitrageOpportunity::test()
+            .with_pairs(&["BTCUSDT", "ETHBTC", "ETHUSDT"])
+            .with_profit_pct(0.3);
+        let pepe = ArbitrageOpportunity::test()
+            .with_pairs(&["PEPEUSDT", "PEPEBTC", "BTCUSDT"])
+            .with_profit_pct(0.3);
+
+        let mut ledger = TriangleLedger::load(&path);
+        // Found twice in a row and through a cycle without price changes, then missing
+        // from a scan and found again: two detections
+        for scan in [
+            vec![btc.clone()],
+            vec![btc.clone()],
+            vec![],
+            vec![pepe.clone()],
+            vec![btc.clone(), pepe.clone()],
+        ] {
+            ledger.observe(&scan);
+        }
+        for _ in 0..MIN_EXECUTIONS {
+            ledger.record_execution(&btc, &result(0.2));
+        }
+        ledger.record_execution(&pepe, &result(-0.5));
+
+        let ledger = TriangleLedger::load(&path);
+        assert_eq!(ledger.len(), 2);
+        let record = &ledger.triangles[&key(&btc)];
+        assert_eq!((record.detected, record.executed), (2, 3));
+        assert!((record.realized_pnl_usd - 0.6).abs() < 1e-9);
+        // 0.1 points short of the estimate each time; PEPE hasn't executed enough to count
+        let adjustments = ledger.ranking_adjustments();
+        assert_eq!(adjustments.len(), 1);
+        assert!((adjustments[&key(&btc)] + 0.1).abs() < 1e-9);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,240 @@
+use crate::models::ArbitrageOpportunity;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tracing::{debug, warn};
+
+/// Where execution counts are kept so limits survive restarts
+pub const USAGE_FILE: &str = "intermediate_usage.json";
+
+/// Parse `COIN:N` pairs, e.g. `PEPE:2,SHIB:1`, into per-hour caps
+pub fn parse_limits(spec: &str) -> Result<HashMap<String, u32>> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (coin, max) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow!("expected COIN:N, got '{entry}'"))?;
+            let max = max
+                .trim()
+                .parse::<u32>()
+                .with_context(|| format!("invalid limit for {coin}"))?;
+            Ok((coin.trim().to_uppercase(), max))
+        })
+        .collect()
+}
+
+/// Caps how often a coin may be an intermediate leg of executed trades per hour
+pub struct IntermediateLimits {
+    limits: HashMap<String, u32>,
+    /// Execution times per limited coin, oldest first
+    usage: HashMap<String, VecDeque<DateTime<Utc>>>,
+    path: Option<PathBuf>,
+}
+
+impl IntermediateLimits {
+    pub fn new(limits: HashMap<String, u32>) -> Self {
+        Self {
+            limits,
+            usage: HashMap::new(),
+            path: None,
+        }
+    }
+
+    /// Restore counts persisted by a previous run; a missing or unreadable file starts empty
+    pub fn load(limits: HashMap<String, u32>, path: &Path) -> Self {
+        Self {
+            limits,
+            usage: crate::persist::load_json(path),
+            path: Some(path.to_path_buf()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.limits.is_empty()
+    }
+
+    /// First intermediate coin of the opportunity that has used up its hourly allowance
+    pub fn blocked_coin<'a>(
+        &self,
+        opportunity: &'a ArbitrageOpportunity,
+        now: DateTime<Utc>,
+    ) -> Option<&'a str> {
+        intermediates(opportunity)
+            .find(|coin| {
+                self.limits
+                    .get(*coin)
+                    .is_some_and(|max| self.recent(coin, now) >= *max as usize)
+            })
+            .map(String::as_str)
+    }
+
+    /// Count an executed trade against its intermediate coins and persist the counts
+    pub fn record(&mut self, opportunity: &ArbitrageOpportunity, now: DateTime<Utc>) {
+        for coin in intermediates(opportunity) {
+            if self.limits.contains_key(coin) {
+                self.usage.entry(coin.clone()).or_default().push_back(now);
+            }
+        }
+        self.prune(now);
+        if let Err(e) = self.save() {
+            warn!("⚠️ Failed to save intermediate coin usage: {e:#}");
+        }
+    }
+
+    fn recent(&self, coin: &str, now: DateTime<Utc>) -> usize {
+        self.usage.get(coin).map_or(0, |times| {
+            times
+                .iter()
+                .filter(|t| now - **t < Duration::hours(1))
+                .count()
+        })
+    }
+
+    fn prune(&mut self, now: DateTime<Utc>) {
+        for times in self.usage.values_mut() {
+            while times
+                .front()
+                .is_some_and(|t| now - *t >= Duration::hours(1))
+            {
+                times.pop_front();
+            }
+        }
+        self.usage.retain(|_, times| !times.is_empty());
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        crate::persist::save_json(path, &self.usage)?;
+        debug!("💾 Saved intermediate coin usage to {}", path.display());
+        Ok(())
+    }
+}
+
+/// Keeps executions sharing a symbol at least `interval` apart, so balances and books
+/// settle before a path the last execution just moved is entered again
+pub struct OverlapCooldown {
+    interval: std::time::Duration,
+    /// When each recently executed set of symbols last traded
+    recent: HashMap<BTreeSet<String>, Instant>,
+}
+
+impl OverlapCooldown {
+    pub fn new(interval: std::time::Duration) -> Self {
+        Self {
+            interval,
+            recent: HashMap::new(),
+        }
+    }
+
+    /// A symbol the opportunity shares with an execution less than `interval` ago
+    pub fn blocked_symbol<'a>(
+        &self,
+        opportunity: &'a ArbitrageOpportunity,
+        now: Instant,
+    ) -> Option<&'a str> {
+        opportunity
+            .pairs
+            .iter()
+            .find(|symbol| {
+                self.recent.iter().any(|(symbols, at)| {
+                    symbols.contains(*symbol) && now.duration_since(*at) < self.interval
+                })
+            })
+            .map(String::as_str)
+    }
+
+    /// Start the cooldown of an executed triangle's symbols
+    pub fn record(&mut self, opportunity: &ArbitrageOpportunity, now: Instant) {
+        if self.interval.is_zero() {
+            return;
+        }
+        self.recent
+            .retain(|_, at| now.duration_since(*at) < self.interval);
+        self.recent
+            .insert(opportunity.pairs.iter().cloned().collect(), now);
+    }
+}
+
+/// Coins between the start and end of the cycle
+fn intermediates(opportunity: &ArbitrageOpportunity) -> impl Iterator<Item = &String> {
+    let inner = opportunity.path.len().saturating_sub(1);
+    opportunity.path.iter().take(inner).skip(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_limits() {
+        let limits = parse_limits(" pepe:2, SHIB:1 ,").unwrap();
+        assert_eq!(limits.get("PEPE"), Some(&2));
+        assert_eq!(limits.get("SHIB"), Some(&1));
+        assert!(parse_limits("").unwrap().is_empty());
+        assert!(parse_limits("PEPE").is_err());
+        assert!(parse_limits("PEPE:x").is_err());
+    }
+
+    #[test]
+    fn test_limit_applies_per_hour_and_survives_restart() {
+        let path = std::env::temp_dir().join(format!("usage_test_{}.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let caps = parse_limits("PEPE:2").unwrap();
+        let mut limits = IntermediateLimits::load(caps.clone(), &path);
+        let via_pepe = ArbitrageOpportunity::test().with_path(&["USDT", "PEPE", "BTC", "USDT"]);
+        let via_eth = ArbitrageOpportunity::test().with_path(&["USDT", "ETH", "BTC", "USDT"]);
+        let start = Utc::now();
+
+        limits.record(&via_pepe, start);
+        limits.record(&via_pepe, start + Duration::minutes(10));
+        assert_eq!(
+            limits.blocked_coin(&via_pepe, start + Duration::minutes(20)),
+            Some("PEPE")
+        );
+        assert_eq!(
+            limits.blocked_coin(&via_eth, start + Duration::minutes(20)),
+            None
+        );
+
+        // Counts are reloaded after a restart, and expire after an hour
+        let limits = IntermediateLimits::load(caps, &path);
+        assert_eq!(
+            limits.blocked_coin(&via_pepe, start + Duration::minutes(30)),
+            Some("PEPE")
+        );
+        assert_eq!(
+            limits.blocked_coin(&via_pepe, start + Duration::minutes(61)),
+            None
+        );
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_overlapping_paths_wait_out_the_cooldown() {
+        let triangle = |pairs: [&str; 3]| {
+            ArbitrageOpportunity::test()
+                .with_path(&["USDT", "X", "Y", "USDT"])
+                .with_pairs(&pairs)
+        };
+        let executed = triangle(["ETHUSDT", "ETHBTC", "BTCUSDT"]);
+        let overlapping = triangle(["SOLUSDT", "SOLBTC", "BTCUSDT"]);
+        let disjoint = triangle(["SOLUSDT", "SOLETH", "ETHUSDC"]);
+        let mut cooldown = OverlapCooldown::new(std::time::Duration::from_secs(5));
+        let start = Instant::now();
+        cooldown.record(&executed, start);
+
+        let soon = start + std::time::Duration::from_secs(2);
+        assert_eq!(cooldown.blocked_symbol(&executed, soon), Some("ETHUSDT"));
+        assert_eq!(cooldown.blocked_symbol(&overlapping, soon), Some("BTCUSDT"));
+        assert_eq!(cooldown.blocked_symbol(&disjoint, soon), None);
+
+        let later = start + std::time::Duration::from_secs(5);
+        assert_eq!(cooldown.blocked_symbol(&overlapping, later), None);
+    }
+}
@@ -10,8 +10,22 @@ impl tracing_subscriber::fmt::time::FormatTime for LocalTimer {
     }
 }
 
-/// Initialize the logging system
-pub fn init_logger() -> Result<(), anyhow::Error> {
+/// Create the directory `path` goes in, if it has one and it doesn't exist yet
+pub fn create_parent_dir(path: &std::path::Path) -> anyhow::Result<()> {
+    use anyhow::Context;
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    Ok(())
+}
+
+/// Initialize the logging system, appending to `log_file` instead of stdout when given
+pub fn init_logger(log_file: Option<&std::path::Path>) -> Result<(), anyhow::Error> {
+    // Set up environment filter
+    // Default to INFO level, but allow override via RUST_LOG env var
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
     // Create a custom format for logs
     let fmt_layer = fmt::layer()
         .with_timer(LocalTimer)
@@ -22,15 +36,30 @@ pub fn init_logger() -> Result<(), anyhow::Error> {
         .with_line_number(false)
         .compact();
 
-    // Set up environment filter
-    // Default to INFO level, but allow override via RUST_LOG env var
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-
     // Initialize the subscriber
-    tracing_subscriber::registry()
-        .with(filter)
-        .with(fmt_layer)
-        .init();
+    match log_file {
+        Some(path) => {
+            create_parent_dir(path)?;
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            tracing_subscriber::registry()
+                .with(filter)
+                .with(
+                    fmt_layer
+                        .with_ansi(false)
+                        .with_writer(std::sync::Mutex::new(file)),
+                )
+                .init();
+        }
+        None => {
+            tracing_subscriber::registry()
+                .with(filter)
+                .with(fmt_layer)
+                .init();
+        }
+    }
 
     info!("🚀 Bybit Triangular Arbitrage Bot Starting...");
 
@@ -52,10 +81,22 @@ pub fn log_startup_info(config: &crate::config::Config) {
         "  • Min Profit Threshold: {:.2}%",
         config.min_profit_threshold
     );
+    info!("  • Alert Threshold: {:.2}%", config.alert_profit_threshold);
     info!(
         "  • Trading Fee Rate: {:.2}% per trade",
         config.trading_fee_rate * 100.0
     );
+    if config.fee_discount != 1.0 || !config.fee_coin.is_empty() {
+        info!(
+            "  • Fee Discount: x{} (effective {:.3}%){}",
+            config.fee_discount,
+            config.effective_fee_rate() * 100.0,
+            config
+                .fee_coin()
+                .map(|coin| format!(", paid in {coin} when available"))
+                .unwrap_or_default()
+        );
+    }
     info!(
         "  • Max Triangles to Scan: {}",
         config.max_triangles_to_scan
@@ -65,6 +106,7 @@ pub fn log_startup_info(config: &crate::config::Config) {
         config.balance_refresh_interval_secs
     );
     info!("  • Price Refresh: {}s", config.price_refresh_interval_secs);
+    info!("  • Reporting Currency: {}", config.reporting_currency);
 }
 
 /// Log arbitrage opportunity in a formatted way
@@ -0,0 +1,292 @@
+use crate::errors::BybitError;
+use crate::exchange::ExchangeApi;
+use crate::models::{MaintenanceInfo, TickerInfo};
+use crate::supervisor;
+use anyhow::{ensure, Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Arc;
+use tokio::sync::{watch, Mutex, Notify};
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+/// Stop trading this long before a scheduled window begins
+const LEAD_TIME_SECS: i64 = 60;
+const SERVICE_RESTARTING: &str = "Bybit reported its services restarting";
+
+/// What Bybit currently lets the bot trade
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TradingStatus {
+    /// Why the whole exchange is off limits, e.g. a maintenance window
+    pub maintenance: Option<String>,
+    /// Symbols Bybit stopped trading after they were seen trading
+    pub suspended: BTreeSet<String>,
+}
+
+/// The window in progress, or starting within LEAD_TIME_SECS
+pub fn active_window(windows: &[MaintenanceInfo], now: DateTime<Utc>) -> Option<&MaintenanceInfo> {
+    windows.iter().find(|w| match w.state.as_str() {
+        "ongoing" => true,
+        "completed" => false,
+        _ => w.window().is_some_and(|(begin, end)| {
+            begin - chrono::Duration::seconds(LEAD_TIME_SECS) <= now && now < end
+        }),
+    })
+}
+
+fn has_book(ticker: &TickerInfo) -> bool {
+    let price = |p: &Option<String>| p.as_deref().and_then(|p| p.parse::<f64>().ok());
+    match (price(&ticker.bid1_price), price(&ticker.ask1_price)) {
+        (Some(bid), Some(ask)) => bid > 0.0 && ask > bid,
+        _ => false,
+    }
+}
+
+/// Check Bybit is really back before trading resumes: the API answers, the wallet can be
+/// read and the spot books are quoting again
+async fn verify<E: ExchangeApi + ?Sized>(exchange: &E) -> Result<()> {
+    exchange
+        .check_connection()
+        .await
+        .context("API unreachable")?;
+    exchange
+        .get_wallet_balance(None)
+        .await
+        .context("Wallet unavailable")?;
+    let tickers = exchange
+        .get_tickers("spot")
+        .await
+        .context("Tickers unavailable")?;
+    ensure!(
+        tickers.list.iter().any(has_book),
+        "No spot book is quoting yet"
+    );
+    Ok(())
+}
+
+/// Whether a symbol Bybit lists as trading again has a sane book
+async fn is_quoting<E: ExchangeApi + ?Sized>(exchange: &E, symbol: &str) -> bool {
+    exchange
+        .get_ticker("spot", symbol)
+        .await
+        .is_ok_and(|tickers| tickers.list.iter().any(has_book))
+}
+
+/// Trading status between polls. Pauses start as soon as they're seen, but only end once
+/// verified.
+#[derive(Debug, Default)]
+struct Monitor {
+    status: TradingStatus,
+    /// Symbols seen trading, whose suspension matters
+    listed: HashSet<String>,
+}
+
+impl Monitor {
+    /// Poll maintenance windows and instrument statuses. `suspect` pauses trading like a
+    /// maintenance window, e.g. after a service-restarting error.
+    async fn poll<E: ExchangeApi + ?Sized>(
+        &mut self,
+        exchange: &E,
+        suspect: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let windows = exchange
+            .get_system_status()
+            .await
+            .context("Failed to fetch system status")?;
+        let instruments = exchange
+            .get_all_spot_instruments()
+            .await
+            .context("Failed to fetch instruments")?;
+
+        let reason = active_window(&windows, now)
+            .map(|w| format!("Bybit maintenance: {}", w.title))
+            .or(suspect.map(str::to_string));
+        self.status.maintenance = match (reason, self.status.maintenance.take()) {
+            (Some(reason), previous) => {
+                if previous.is_none() {
+                    warn!("🛠️ {reason} - trading paused");
+                }
+                Some(reason)
+            }
+            (None, Some(previous)) => match verify(exchange).await {
+                Ok(()) => {
+                    info!("✅ Bybit verified after maintenance - trading can resume");
+                    None
+                }
+                Err(e) => {
+                    warn!("⚠️ Bybit not verified after maintenance yet: {e:#}");
+                    Some(previous)
+                }
+            },
+            (None, None) => None,
+        };
+
+        let trading: HashSet<&str> = instruments
+            .iter()
+            .filter(|i| i.status == "Trading")
+            .map(|i| i.symbol.as_str())
+            .collect();
+        // Delisted symbols are forgotten rather than reported as suspended forever
+        let listed: HashSet<&str> = instruments.iter().map(|i| i.symbol.as_str()).collect();
+        self.listed
+            .retain(|symbol| listed.contains(symbol.as_str()));
+        let mut suspended = BTreeSet::new();
+        for symbol in &self.listed {
+            let was_suspended = self.status.suspended.contains(symbol);
+            if !trading.contains(symbol.as_str()) {
+                if !was_suspended {
+                    warn!("⛔ Bybit suspended {symbol} - not trading it until it resumes");
+                }
+                suspended.insert(symbol.clone());
+            } else if was_suspended {
+                if is_quoting(exchange, symbol).await {
+                    info!("✅ {symbol} is trading again");
+                } else {
+                    suspended.insert(symbol.clone());
+                }
+            }
+        }
+        self.listed.extend(trading.into_iter().map(str::to_string));
+        self.status.suspended = suspended;
+        Ok(())
+    }
+}
+
+/// The main loop's end of the maintenance monitor
+pub struct MaintenanceWatch {
+    status: watch::Receiver<TradingStatus>,
+    recheck: Arc<Notify>,
+}
+
+impl MaintenanceWatch {
+    /// The latest status, if it changed since the last call
+    pub fn changed(&mut self) -> Option<TradingStatus> {
+        match self.status.has_changed() {
+            Ok(true) => Some(self.status.borrow_and_update().clone()),
+            _ => None,
+        }
+    }
+
+    /// Treat a service-restarting error like a maintenance window: poll at once and keep
+    /// trading paused until the exchange is verified. Returns the pause reason if it was one.
+    pub fn report_error(&self, error: &BybitError) -> Option<&'static str> {
+        if !matches!(error, BybitError::ServiceRestarting { .. }) {
+            return None;
+        }
+        self.recheck.notify_one();
+        Some(SERVICE_RESTARTING)
+    }
+}
+
+/// Poll Bybit's maintenance windows and symbol statuses every `interval_secs` on a
+/// supervised task
+pub fn spawn(
+    exchange: Arc<dyn ExchangeApi>,
+    interval_secs: u64,
+    restart_on_panic: bool,
+) -> MaintenanceWatch {
+    let (tx, rx) = watch::channel(TradingStatus::default());
+    let recheck = Arc::new(Notify::new());
+    // Survives restarts of the task, so a pause never ends without verification
+    let monitor = Arc::new(Mutex::new(Monitor::default()));
+    let task_recheck = recheck.clone();
+    supervisor::spawn_supervised(
+        "Maintenance monitor".to_string(),
+        restart_on_panic,
+        move || {
+            let (exchange, tx, recheck, monitor) = (
+                exchange.clone(),
+                tx.clone(),
+                task_recheck.clone(),
+                monitor.clone(),
+            );
+            async move {
+                let mut suspect = None;
+                loop {
+                    let mut monitor = monitor.lock().await;
+                    match monitor.poll(&*exchange, suspect, Utc::now()).await {
+                        Ok(()) => {
+                            suspect = None;
+                            tx.send_replace(monitor.status.clone());
+                        }
+                        Err(e) => warn!("⚠️ Could not check Bybit's trading status: {e:#}"),
+                    }
+                    drop(monitor);
+                    tokio::select! {
+                        _ = sleep(Duration::from_secs(interval_secs)) => {}
+                        _ = recheck.notified() => suspect = Some(SERVICE_RESTARTING),
+                    }
+                }
+            }
+        },
+    );
+    MaintenanceWatch {
+        status: rx,
+        recheck,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{MockExchange, MockMarket};
+
+    fn window(state: &str, begin: DateTime<Utc>, end: DateTime<Utc>) -> MaintenanceInfo {
+        MaintenanceInfo {
+            id: "1".to_string(),
+            title: "System upgrade".to_string(),
+            state: state.to_string(),
+            begin: begin.timestamp_millis().to_string(),
+            end: end.timestamp_millis().to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pauses_for_maintenance_and_suspensions_until_verified() {
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_560.0));
+        let mut monitor = Monitor::default();
+        let now = Utc::now();
+        let hour = chrono::Duration::hours(1);
+        monitor.poll(&exchange, None, now).await.unwrap();
+        assert_eq!(monitor.status, TradingStatus::default());
+
+        // A window starting within the lead time already pauses trading
+        exchange.set_maintenance(vec![window(
+            "scheduled",
+            now + chrono::Duration::seconds(30),
+            now + hour,
+        )]);
+        exchange.set_suspended("ETHUSDT", true);
+        monitor.poll(&exchange, None, now).await.unwrap();
+        assert_eq!(
+            monitor.status.maintenance.as_deref(),
+            Some("Bybit maintenance: System upgrade")
+        );
+        assert_eq!(monitor.status.suspended, BTreeSet::from(["ETHUSDT".into()]));
+
+        // Both lift once the window is over and the exchange answers again
+        exchange.set_maintenance(vec![window("completed", now - hour, now)]);
+        exchange.set_suspended("ETHUSDT", false);
+        monitor.poll(&exchange, None, now).await.unwrap();
+        assert_eq!(monitor.status, TradingStatus::default());
+
+        // A service-restarting error pauses trading until the next poll verifies it
+        monitor
+            .poll(&exchange, Some(SERVICE_RESTARTING), now)
+            .await
+            .unwrap();
+        assert!(monitor.status.maintenance.is_some());
+        monitor.poll(&exchange, None, now).await.unwrap();
+        assert_eq!(monitor.status.maintenance, None);
+        assert!(matches!(
+            BybitError::from_response(10016, "Service is restarting"),
+            BybitError::ServiceRestarting { .. }
+        ));
+    }
+}
@@ -0,0 +1,119 @@
+use crate::models::OrderQty;
+use crate::trader::TradeExecution;
+use anyhow::{anyhow, Result};
+
+/// How the trader places the legs of a triangle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExecutionStrategy {
+    /// Every leg is a market order
+    Taker,
+    /// Leg 1 rests as a post-only limit, and whatever hasn't filled by the timeout goes
+    /// to market
+    MakerFirst,
+    /// Leg 1 rests as a post-only limit and never goes to market: unfilled by the timeout,
+    /// the triangle is abandoned, or continues with the part that did fill
+    Hybrid,
+}
+
+impl ExecutionStrategy {
+    /// Parse `EXECUTION_STRATEGY`: `taker`, `maker_first` or `hybrid`
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "taker" => Ok(ExecutionStrategy::Taker),
+            "maker_first" => Ok(ExecutionStrategy::MakerFirst),
+            "hybrid" => Ok(ExecutionStrategy::Hybrid),
+            other => Err(anyhow!(
+                "unknown execution strategy '{other}' (expected taker, maker_first or hybrid)"
+            )),
+        }
+    }
+
+    /// Name as `EXECUTION_STRATEGY` takes it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExecutionStrategy::Taker => "taker",
+            ExecutionStrategy::MakerFirst => "maker_first",
+            ExecutionStrategy::Hybrid => "hybrid",
+        }
+    }
+
+    /// Whether leg 1 is first offered as a maker order
+    pub fn maker_leg1(self) -> bool {
+        self != ExecutionStrategy::Taker
+    }
+}
+
+/// Price a post-only leg joins: the best bid for a Buy, the best ask for a Sell
+pub fn join_price(side: &str, bid: f64, ask: f64) -> f64 {
+    if side == "Buy" {
+        bid
+    } else {
+        ask
+    }
+}
+
+/// What is left of a planned leg after `filled`, in the planned order's unit
+pub fn remainder(planned: OrderQty, filled: &TradeExecution) -> OrderQty {
+    let done = match planned {
+        OrderQty::Base(_) => filled.executed_quantity.value(),
+        OrderQty::Quote(_) => filled.executed_value.value(),
+    };
+    planned.with_value((planned.value() - done).max(0.0))
+}
+
+/// Both fills of one leg as a single execution at their average price
+pub fn merge(mut first: TradeExecution, second: TradeExecution) -> TradeExecution {
+    first.executed_quantity += second.executed_quantity;
+    first.executed_value += second.executed_value;
+    first.executed_price = first.executed_value / first.executed_quantity;
+    first.fee += second.fee;
+    for (coin, fee) in second.other_fees {
+        *first.other_fees.entry(coin).or_insert(0.0) += fee;
+    }
+    first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Notional, Price, Qty};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            ExecutionStrategy::parse("").unwrap(),
+            ExecutionStrategy::Taker
+        );
+        assert_eq!(
+            ExecutionStrategy::parse(" Maker_First ").unwrap(),
+            ExecutionStrategy::MakerFirst
+        );
+        assert!(ExecutionStrategy::parse("maker").is_err());
+    }
+
+    #[test]
+    fn test_partial_maker_fill_leaves_the_rest_and_merges_at_the_average_price() {
+        let fill = |qty: f64, price: f64| TradeExecution {
+            side: "Buy".to_string(),
+            executed_price: Price::new(price),
+            executed_quantity: Qty::new(qty),
+            executed_value: Notional::new(qty * price),
+            fee: qty * 0.001,
+            other_fees: BTreeMap::new(),
+        };
+        let maker = fill(0.001, 49_990.0);
+
+        let rest = remainder(OrderQty::Quote(Notional::new(100.0)), &maker);
+        assert!((rest.value() - 50.01).abs() < 1e-9);
+        assert_eq!(
+            remainder(OrderQty::Base(Qty::new(0.001)), &maker).value(),
+            0.0
+        );
+
+        let merged = merge(maker, fill(0.001, 50_000.0));
+        assert_eq!(merged.executed_quantity, Qty::new(0.002));
+        assert!((merged.executed_price.value() - 49_995.0).abs() < 1e-6);
+        assert!((merged.fee - 0.000002).abs() < 1e-12);
+    }
+}
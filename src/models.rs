@@ -1,14 +1,19 @@
 use crate::config::Config;
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Sub};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: DeserializeOwned"))]
 pub struct ApiResponse<T> {
     #[serde(rename = "retCode")]
     pub ret_code: i32,
     #[serde(rename = "retMsg")]
     pub ret_msg: String,
+    #[serde(default, deserialize_with = "empty_object_as_none")]
     pub result: Option<T>,
     #[serde(rename = "retExtInfo")]
     pub ret_ext_info: Option<serde_json::Value>,
@@ -29,6 +34,22 @@ impl<T> ApiResponse<T> {
     }
 }
 
+/// Bybit sends `"result": {}` with errors; treat it as no result so the
+/// error code is reported instead of a parse failure
+fn empty_object_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    match Option::<serde_json::Value>::deserialize(deserializer)? {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(serde_json::Value::Object(map)) if map.is_empty() => Ok(None),
+        Some(value) => T::deserialize(value)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
 // Wallet Balance Models
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletBalanceResult {
@@ -184,6 +205,15 @@ pub struct LotSizeFilter {
     pub post_only_max_order_qty: Option<String>,
     #[serde(rename = "minNotionalValue")]
     pub min_notional_value: Option<String>,
+    // Spot instruments report basePrecision/minOrderAmt instead of qtyStep/minNotionalValue
+    #[serde(rename = "basePrecision")]
+    pub base_precision: Option<String>,
+    #[serde(rename = "quotePrecision")]
+    pub quote_precision: Option<String>,
+    #[serde(rename = "minOrderAmt")]
+    pub min_order_amt: Option<String>,
+    #[serde(rename = "maxOrderAmt")]
+    pub max_order_amt: Option<String>,
 }
 
 // Ticker Models
@@ -241,6 +271,126 @@ pub struct TickerInfo {
     pub basis: Option<String>,
 }
 
+// Server time model (/v5/market/time)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerTimeResult {
+    #[serde(rename = "timeSecond")]
+    pub time_second: String,
+    #[serde(rename = "timeNano")]
+    pub time_nano: String,
+}
+
+impl ServerTimeResult {
+    /// Server time in milliseconds since the Unix epoch
+    pub fn as_millis(&self) -> Option<u64> {
+        self.time_nano
+            .parse::<u128>()
+            .ok()
+            .map(|nanos| (nanos / 1_000_000) as u64)
+            .or_else(|| self.time_second.parse::<u64>().ok().map(|s| s * 1000))
+    }
+}
+
+// System status model (/v5/system/status)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemStatusResult {
+    #[serde(default)]
+    pub list: Vec<MaintenanceInfo>,
+}
+
+/// A scheduled, ongoing or completed exchange maintenance window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceInfo {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub title: String,
+    /// scheduled, ongoing or completed
+    pub state: String,
+    /// Start and end in milliseconds since the Unix epoch
+    pub begin: String,
+    pub end: String,
+}
+
+impl MaintenanceInfo {
+    /// Start and end of the window, if both parse
+    pub fn window(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let at = |ms: &str| DateTime::from_timestamp_millis(ms.parse().ok()?);
+        Some((at(&self.begin)?, at(&self.end)?))
+    }
+}
+
+// Deposit and withdrawal history (/v5/asset/deposit/query-record, /v5/asset/withdraw/query-record)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetRecordsResult<T> {
+    #[serde(default = "Vec::new")]
+    pub rows: Vec<T>,
+    /// Empty on the last page
+    #[serde(rename = "nextPageCursor", default)]
+    pub next_page_cursor: Option<String>,
+}
+
+/// An on-chain deposit into the account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepositRecord {
+    pub coin: String,
+    pub amount: String,
+    #[serde(rename = "txID", default)]
+    pub tx_id: String,
+    /// 3 once the deposit is credited
+    pub status: i32,
+    /// Milliseconds since the Unix epoch
+    #[serde(rename = "successAt", default)]
+    pub success_at: String,
+}
+
+/// A withdrawal out of the account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WithdrawalRecord {
+    #[serde(rename = "withdrawId")]
+    pub withdraw_id: String,
+    pub coin: String,
+    pub amount: String,
+    #[serde(rename = "withdrawFee", default)]
+    pub withdraw_fee: String,
+    /// "success" once it has left the account
+    pub status: String,
+    /// Milliseconds since the Unix epoch
+    #[serde(rename = "updateTime", default)]
+    pub update_time: String,
+}
+
+// API key information model (/v5/user/query-api)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyInfo {
+    pub id: Option<String>,
+    pub note: Option<String>,
+    #[serde(rename = "apiKey")]
+    pub api_key: Option<String>,
+    #[serde(rename = "readOnly")]
+    pub read_only: i32,
+    #[serde(default)]
+    pub permissions: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub ips: Vec<String>,
+    #[serde(rename = "expiredAt")]
+    pub expired_at: Option<String>,
+}
+
+impl ApiKeyInfo {
+    /// Check whether the key carries a given permission within a permission group
+    pub fn has_permission(&self, group: &str, permission: &str) -> bool {
+        self.permissions
+            .get(group)
+            .is_some_and(|perms| perms.iter().any(|p| p == permission))
+    }
+
+    /// True when the key is not bound to any IP address
+    pub fn is_ip_unrestricted(&self) -> bool {
+        self.ips.is_empty() || self.ips.iter().any(|ip| ip == "*")
+    }
+}
+
 // Order placement models
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlaceOrderRequest {
@@ -268,11 +418,83 @@ pub struct PlaceOrderResult {
     pub order_link_id: String,
 }
 
+/// Change to the quantity or price of an open order; fields left None stay as they are
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmendOrderRequest {
+    pub category: String,
+    pub symbol: String,
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<String>,
+}
+
+/// Orders cancelled by a cancel-all request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelAllResult {
+    pub list: Vec<PlaceOrderResult>,
+}
+
+/// Orders created by one batch request, in request order; a rejected order has empty ids
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchOrderResult {
+    pub list: Vec<PlaceOrderResult>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderListResult {
     pub list: Vec<OrderInfo>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionListResult {
+    pub list: Vec<ExecutionRecord>,
+    /// Empty on the last page
+    #[serde(rename = "nextPageCursor", default)]
+    pub next_page_cursor: Option<String>,
+}
+
+/// The account's maker and taker fee rates on a symbol, as fractions (`/v5/account/fee-rate`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeRateInfo {
+    pub symbol: String,
+    #[serde(rename = "takerFeeRate")]
+    pub taker_fee_rate: String,
+    #[serde(rename = "makerFeeRate")]
+    pub maker_fee_rate: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeRateResult {
+    pub list: Vec<FeeRateInfo>,
+}
+
+/// A single fill of an order, with the coin its fee was charged in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionRecord {
+    #[serde(rename = "orderId")]
+    pub order_id: String,
+    #[serde(rename = "orderLinkId", default)]
+    pub order_link_id: String,
+    pub symbol: String,
+    pub side: String,
+    #[serde(rename = "execPrice")]
+    pub exec_price: String,
+    #[serde(rename = "execQty")]
+    pub exec_qty: String,
+    #[serde(rename = "execValue")]
+    pub exec_value: String,
+    #[serde(rename = "execFee")]
+    pub exec_fee: String,
+    /// Empty on categories that don't report it
+    #[serde(rename = "feeCurrency", default)]
+    pub fee_currency: String,
+    #[serde(rename = "execTime")]
+    pub exec_time: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderInfo {
     #[serde(rename = "orderId")]
@@ -301,8 +523,70 @@ pub struct OrderInfo {
     pub updated_time: String,
 }
 
+impl OrderInfo {
+    /// Lifecycle state parsed from `orderStatus`, None for statuses we don't track
+    pub fn state(&self) -> Option<OrderState> {
+        OrderState::from_status(&self.order_status)
+    }
+}
+
+/// Order lifecycle: Created → Acked → PartiallyFilled → Filled/Cancelled/Rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderState {
+    /// Accepted by the gateway, not yet on the book
+    Created,
+    /// Live on the book (Bybit "New")
+    Acked,
+    PartiallyFilled,
+    Filled,
+    Cancelled,
+    /// Cancelled after a partial fill, e.g. the IOC remainder of a market order
+    PartiallyFilledCancelled,
+    Rejected,
+}
+
+impl OrderState {
+    /// Map a Bybit `orderStatus` onto the lifecycle
+    pub fn from_status(status: &str) -> Option<Self> {
+        match status {
+            "Created" | "Untriggered" | "Triggered" => Some(Self::Created),
+            "New" => Some(Self::Acked),
+            "PartiallyFilled" => Some(Self::PartiallyFilled),
+            "Filled" => Some(Self::Filled),
+            "Cancelled" | "Deactivated" => Some(Self::Cancelled),
+            "PartiallyFilledCanceled" => Some(Self::PartiallyFilledCancelled),
+            "Rejected" => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            Self::Filled | Self::Cancelled | Self::PartiallyFilledCancelled | Self::Rejected
+        )
+    }
+
+    /// Whether an order may move from `self` to `next`. Repeating a state is allowed
+    /// since polls often see the same status twice; nothing leaves a terminal state.
+    pub fn can_transition_to(self, next: Self) -> bool {
+        use OrderState::*;
+        if self == next {
+            return true;
+        }
+        if self.is_terminal() {
+            return false;
+        }
+        match self {
+            Created => true,
+            Acked => !matches!(next, Created | Rejected),
+            _ => matches!(next, Filled | PartiallyFilledCancelled),
+        }
+    }
+}
+
 // Market Pair for internal use
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MarketPair {
     pub base: String,
     pub quote: String,
@@ -335,18 +619,18 @@ impl MarketPair {
             .parse()
             .ok()?;
 
-        let qty_step = instrument
-            .lot_size_filter
-            .as_ref()?
+        let lot_size = instrument.lot_size_filter.as_ref()?;
+        let qty_step = lot_size
             .qty_step
             .as_ref()
+            .or(lot_size.base_precision.as_ref())
             .and_then(|v| v.parse().ok())
             .unwrap_or(0.001); // Default to 0.001 if not available
 
-        let min_notional = instrument
-            .lot_size_filter
+        let min_notional = lot_size
+            .min_notional_value
             .as_ref()
-            .and_then(|f| f.min_notional_value.as_ref())
+            .or(lot_size.min_order_amt.as_ref())
             .and_then(|v| v.parse().ok())
             .unwrap_or(0.0);
 
@@ -421,18 +705,246 @@ impl MarketPair {
     }
 }
 
+/// Pairs for tests: active and liquid, with a deep book and small order minimums
+#[cfg(test)]
+impl MarketPair {
+    pub fn test(base: &str, quote: &str, bid: f64, ask: f64) -> Self {
+        Self {
+            base: base.to_string(),
+            quote: quote.to_string(),
+            symbol: format!("{base}{quote}"),
+            price: bid,
+            bid_price: bid,
+            ask_price: ask,
+            bid_size: 1e6,
+            ask_size: 1e6,
+            volume_24h: 1e9,
+            volume_24h_usd: 1e9 * bid,
+            spread_percent: (ask - bid) / bid * 100.0,
+            min_qty: 0.001,
+            qty_step: 0.001,
+            min_notional: 1.0,
+            is_active: true,
+            is_liquid: true,
+        }
+    }
+
+    /// Quoted with this much on both the bid and the ask
+    pub fn with_size(mut self, size: f64) -> Self {
+        self.bid_size = size;
+        self.ask_size = size;
+        self
+    }
+
+    /// Trading this much base a day
+    pub fn with_volume(mut self, volume_24h: f64) -> Self {
+        self.volume_24h = volume_24h;
+        self.volume_24h_usd = volume_24h * self.price;
+        self
+    }
+}
+
+// Strongly typed amounts
+//
+// Units on a pair BASE/QUOTE:
+// - Price: quote per one base unit
+// - Qty: amount of the base coin
+// - Notional: amount of the quote coin (USD value when the quote is a stablecoin)
+// - Pct: percentage where 1.0 means 1%
+//
+// Qty * Price = Notional and Notional / Price = Qty; anything else needs an
+// explicit `.value()` so unit mix-ups show up in review.
+
+macro_rules! amount_type {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(f64);
+
+        // Not every type needs every helper
+        #[allow(dead_code)]
+        impl $name {
+            pub const ZERO: Self = Self(0.0);
+
+            pub const fn new(value: f64) -> Self {
+                Self(value)
+            }
+
+            pub const fn value(self) -> f64 {
+                self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                // Forward so width/precision flags such as {:.4} keep working
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) {
+                self.0 += rhs.0;
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        /// Scaling by a unitless factor keeps the unit
+        impl Mul<f64> for $name {
+            type Output = Self;
+            fn mul(self, factor: f64) -> Self {
+                Self(self.0 * factor)
+            }
+        }
+
+        impl std::iter::Sum for $name {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                Self(iter.map(|v| v.0).sum())
+            }
+        }
+    };
+}
+
+amount_type!(
+    /// Price of one base unit in the quote coin
+    Price
+);
+amount_type!(
+    /// Amount of a pair's base coin
+    Qty
+);
+amount_type!(
+    /// Amount of a pair's quote coin
+    Notional
+);
+amount_type!(
+    /// Percentage (1.0 = 1%)
+    Pct
+);
+
+impl Mul<Price> for Qty {
+    type Output = Notional;
+    fn mul(self, price: Price) -> Notional {
+        Notional(self.0 * price.0)
+    }
+}
+
+impl Div<Price> for Notional {
+    type Output = Qty;
+    fn div(self, price: Price) -> Qty {
+        Qty(self.0 / price.0)
+    }
+}
+
+/// Implied price of a fill
+impl Div<Qty> for Notional {
+    type Output = Price;
+    fn div(self, qty: Qty) -> Price {
+        Price(self.0 / qty.0)
+    }
+}
+
+/// Round down to `decimals` places so an amount never exceeds the available balance
+pub fn floor_to_decimals(value: f64, decimals: u32) -> f64 {
+    let factor = 10_f64.powi(decimals as i32);
+    (value * factor).floor() / factor
+}
+
+impl Pct {
+    /// Percentage change from `from` to `to`
+    pub fn change(from: Notional, to: Notional) -> Pct {
+        Pct((to.0 - from.0) / from.0 * 100.0)
+    }
+
+    /// This percentage of an amount
+    pub fn of(self, amount: Notional) -> Notional {
+        Notional(amount.0 * self.0 / 100.0)
+    }
+}
+
+/// Size of a spot market order. Bybit sizes market Buys in the quote coin
+/// (amount to spend) and market Sells in the base coin (amount to sell).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderQty {
+    Base(Qty),
+    Quote(Notional),
+}
+
+impl OrderQty {
+    /// Interpret an amount of the currency being converted for the given side
+    pub fn for_side(side: &str, amount: f64) -> Self {
+        if side == "Buy" {
+            OrderQty::Quote(Notional(amount))
+        } else {
+            OrderQty::Base(Qty(amount))
+        }
+    }
+
+    /// Raw number sent to the exchange
+    pub fn value(self) -> f64 {
+        match self {
+            OrderQty::Base(qty) => qty.0,
+            OrderQty::Quote(notional) => notional.0,
+        }
+    }
+
+    /// Same unit with a different amount (e.g. after precision formatting)
+    pub fn with_value(self, value: f64) -> Self {
+        match self {
+            OrderQty::Base(_) => OrderQty::Base(Qty(value)),
+            OrderQty::Quote(_) => OrderQty::Quote(Notional(value)),
+        }
+    }
+
+    /// Order value in the quote coin
+    pub fn notional_at(self, price: Price) -> Notional {
+        match self {
+            OrderQty::Base(qty) => qty * price,
+            OrderQty::Quote(notional) => notional,
+        }
+    }
+}
+
+/// Version of the serialized opportunity and execution result format.
+/// Field names are part of the format: bump this when renaming or removing one.
+pub const SCHEMA_VERSION: u32 = 1;
+
 // Triangular Arbitrage Opportunity
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ArbitrageOpportunity {
+    pub schema_version: u32,
     pub path: Vec<String>,  // [USDT, BTC, ETH, USDT]
     pub pairs: Vec<String>, // [BTCUSDT, ETHBTC, ETHUSDT]
-    pub prices: Vec<f64>,
-    pub estimated_profit_pct: f64,
-    pub estimated_profit_usd: f64,
+    pub prices: Vec<Price>,
+    pub estimated_profit_pct: Pct,
+    pub estimated_profit_usd: Notional,
     pub timestamp: DateTime<Utc>,
+    /// Last moment the opportunity may start executing, None when it never expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<DateTime<Utc>>,
 }
 
 impl ArbitrageOpportunity {
+    /// Whether its quotes are too old, or its edge has likely decayed, to start trading it
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.valid_until
+            .is_some_and(|valid_until| now > valid_until)
+    }
+
     pub fn display_path(&self) -> String {
         self.path.join(" → ")
     }
@@ -442,6 +954,47 @@ impl ArbitrageOpportunity {
     }
 }
 
+/// Opportunities for tests, built up from one USDT → BTC → ETH → USDT triangle
+#[cfg(test)]
+impl ArbitrageOpportunity {
+    /// USDT → BTC → ETH → USDT through BTCUSDT, ETHBTC and ETHUSDT at 50000, 0.05 and 2550,
+    /// with a 1% edge
+    pub fn test() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            path: ["USDT", "BTC", "ETH", "USDT"].map(String::from).to_vec(),
+            pairs: ["BTCUSDT", "ETHBTC", "ETHUSDT"].map(String::from).to_vec(),
+            prices: [50_000.0, 0.05, 2_550.0].map(Price::new).to_vec(),
+            estimated_profit_pct: Pct::new(1.0),
+            estimated_profit_usd: Notional::ZERO,
+            timestamp: Utc::now(),
+            valid_until: None,
+        }
+    }
+
+    /// Through other pairs, each quoted at 1.0
+    pub fn with_pairs(mut self, pairs: &[&str]) -> Self {
+        self.pairs = pairs.iter().map(|p| p.to_string()).collect();
+        self.prices = vec![Price::new(1.0); pairs.len()];
+        self
+    }
+
+    pub fn with_path(mut self, path: &[&str]) -> Self {
+        self.path = path.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    pub fn with_prices(mut self, prices: &[f64]) -> Self {
+        self.prices = prices.iter().copied().map(Price::new).collect();
+        self
+    }
+
+    pub fn with_profit_pct(mut self, profit_pct: f64) -> Self {
+        self.estimated_profit_pct = Pct::new(profit_pct);
+        self
+    }
+}
+
 // Balance mapping for quick lookups
 pub type BalanceMap = HashMap<String, f64>;
 
@@ -476,4 +1029,78 @@ mod tests {
         assert!(!response.is_success());
         assert!(response.into_result().is_err());
     }
+
+    #[test]
+    fn test_opportunity_round_trip() {
+        let opportunity = ArbitrageOpportunity {
+            estimated_profit_usd: Notional::new(0.042),
+            ..ArbitrageOpportunity::test()
+                .with_prices(&[65000.0, 0.05, 3300.0])
+                .with_profit_pct(0.42)
+        };
+
+        let json = serde_json::to_value(&opportunity).unwrap();
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["estimated_profit_pct"], 0.42);
+        assert_eq!(json["prices"][0], 65000.0);
+
+        let parsed: ArbitrageOpportunity = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, opportunity);
+    }
+
+    #[test]
+    fn test_amount_conversions() {
+        let qty = Qty::new(0.5);
+        let price = Price::new(2000.0);
+        assert_eq!(qty * price, Notional::new(1000.0));
+        assert_eq!(Notional::new(1000.0) / price, qty);
+        assert_eq!(Notional::new(1000.0) / qty, price);
+
+        assert_eq!(Pct::new(1.5).of(Notional::new(200.0)), Notional::new(3.0));
+        let change = Pct::change(Notional::new(100.0), Notional::new(101.0));
+        assert!((change.value() - 1.0).abs() < 1e-9);
+        assert_eq!(format!("{:.2}", Pct::new(0.12345)), "0.12");
+    }
+
+    #[test]
+    fn test_rounding_and_order_units() {
+        // Truncation never rounds up past an available balance
+        assert_eq!(floor_to_decimals(1.23999, 2), 1.23);
+        assert_eq!(floor_to_decimals(7.9, 0), 7.0);
+
+        let buy = OrderQty::for_side("Buy", 25.0);
+        assert_eq!(buy, OrderQty::Quote(Notional::new(25.0)));
+        assert_eq!(buy.notional_at(Price::new(3.0)), Notional::new(25.0));
+        let sell = OrderQty::for_side("Sell", 2.0);
+        assert_eq!(sell.notional_at(Price::new(3.0)), Notional::new(6.0));
+        assert_eq!(sell.with_value(1.5), OrderQty::Base(Qty::new(1.5)));
+    }
+
+    #[test]
+    fn test_order_state_from_status() {
+        assert_eq!(OrderState::from_status("New"), Some(OrderState::Acked));
+        assert_eq!(
+            OrderState::from_status("PartiallyFilledCanceled"),
+            Some(OrderState::PartiallyFilledCancelled)
+        );
+        assert_eq!(OrderState::from_status("Bogus"), None);
+        assert!(OrderState::Rejected.is_terminal());
+        assert!(!OrderState::PartiallyFilled.is_terminal());
+    }
+
+    #[test]
+    fn test_order_state_transitions() {
+        use OrderState::*;
+        assert!(Created.can_transition_to(Acked));
+        assert!(Created.can_transition_to(Rejected));
+        assert!(Acked.can_transition_to(PartiallyFilled));
+        assert!(PartiallyFilled.can_transition_to(PartiallyFilled));
+        assert!(PartiallyFilled.can_transition_to(Filled));
+
+        // Going backwards or leaving a final state is a stale or corrupt report
+        assert!(!PartiallyFilled.can_transition_to(Acked));
+        assert!(!PartiallyFilled.can_transition_to(Cancelled));
+        assert!(!Acked.can_transition_to(Rejected));
+        assert!(!Filled.can_transition_to(Cancelled));
+    }
 }
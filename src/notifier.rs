@@ -0,0 +1,230 @@
+use crate::config::Config;
+use crate::models::ArbitrageOpportunity;
+use crate::network::http_client;
+use crate::stable::STABLECOINS;
+use crate::trader::ArbitrageExecutionResult;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Longest a Telegram request may take before the message is dropped
+const TELEGRAM_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Somewhere to push messages to a human
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn send(&self, text: &str) -> Result<()>;
+}
+
+/// Messages to a Telegram chat through a bot
+pub struct TelegramNotifier {
+    client: reqwest::Client,
+    url: String,
+    chat_id: String,
+}
+
+impl TelegramNotifier {
+    pub fn new(config: &Config) -> Result<Self> {
+        let (bot_token, chat_id) = (&config.telegram_bot_token, &config.telegram_chat_id);
+        Ok(Self {
+            client: http_client(config)?.timeout(TELEGRAM_TIMEOUT).build()?,
+            url: format!("https://api.telegram.org/bot{bot_token}/sendMessage"),
+            chat_id: chat_id.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    async fn send(&self, text: &str) -> Result<()> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&serde_json::json!({ "chat_id": self.chat_id, "text": text }))
+            .send()
+            .await
+            // The error would include the URL, and with it the bot token
+            .map_err(|e| anyhow!("Telegram request failed: {}", e.without_url()))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Telegram rejected the message ({status}): {body}"));
+        }
+        Ok(())
+    }
+}
+
+/// Queues messages for a background task to deliver, so a slow or unreachable
+/// notifier never holds up trading
+#[derive(Clone)]
+pub struct Notifications {
+    tx: mpsc::UnboundedSender<String>,
+}
+
+impl Notifications {
+    pub fn spawn(notifier: Arc<dyn Notifier>, restart_on_panic: bool) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let rx = Arc::new(tokio::sync::Mutex::new(rx));
+        crate::supervisor::spawn_supervised("Notifier".to_string(), restart_on_panic, move || {
+            deliver(notifier.clone(), rx.clone())
+        });
+        Self { tx }
+    }
+
+    pub fn notify(&self, text: String) {
+        if self.tx.send(text).is_err() {
+            warn!("⚠️ Notifier stopped; dropping notification");
+        }
+    }
+}
+
+/// Send queued messages one at a time until every `Notifications` is dropped
+async fn deliver(
+    notifier: Arc<dyn Notifier>,
+    rx: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<String>>>,
+) {
+    let mut rx = rx.lock().await;
+    while let Some(text) = rx.recv().await {
+        if let Err(e) = notifier.send(&text).await {
+            warn!("⚠️ Failed to send notification: {e:#}");
+        }
+    }
+    // Nothing left to send; park instead of returning so the supervisor doesn't restart us
+    std::future::pending::<()>().await;
+}
+
+/// Message for an execution attempt: completed, rolled back after a fill, or failed
+/// before anything filled
+pub fn trade_message(
+    opportunity: &ArbitrageOpportunity,
+    amount: f64,
+    execution: &Result<ArbitrageExecutionResult>,
+    filled_legs: usize,
+    parked: bool,
+) -> String {
+    let path = opportunity.display_path();
+    // In the start coin, which for non-stable funding isn't dollars
+    let coin = &opportunity.path[0];
+    let value = |amount: f64, decimals: usize| {
+        if STABLECOINS.contains(&coin.as_str()) {
+            format!("${amount:.decimals$}")
+        } else {
+            format!("{amount:.8} {coin}")
+        }
+    };
+    match execution {
+        Ok(r) if r.success => format!(
+            "✅ Trade executed: {path}\nAmount {} → profit {} ({:.3}%), fees {}, {}ms",
+            value(amount, 2),
+            value(r.actual_profit, 4),
+            r.actual_profit_pct,
+            value(r.total_fees, 4),
+            r.execution_time_ms
+        ),
+        Ok(r) => {
+            let error = r.error_message.as_deref().unwrap_or("unknown error");
+            if parked {
+                format!("🅿️ Trade failed and a position was parked: {path}\n{error}")
+            } else if filled_legs > 0 {
+                format!(
+                    "🔄 Trade rolled back: {path}\n{error}\nRealized {} ({:.3}%)",
+                    value(r.actual_profit, 4),
+                    r.actual_profit_pct
+                )
+            } else {
+                format!("❌ Trade failed: {path}\n{error}")
+            }
+        }
+        Err(e) => format!("❌ Trade failed: {path}\n{e:#}"),
+    }
+}
+
+/// Alerts once when every WebSocket has been down for longer than a threshold, and once
+/// more when one is back
+pub struct OutageAlert {
+    /// None never alerts
+    after: Option<Duration>,
+    alerted: bool,
+}
+
+impl OutageAlert {
+    /// Alert after `after_secs` with every connection down (0 never does)
+    pub fn new(after_secs: u64) -> Self {
+        Self {
+            after: (after_secs > 0).then(|| Duration::from_secs(after_secs)),
+            alerted: false,
+        }
+    }
+
+    /// The message to send, if the outage crossed the threshold or ended after an alert
+    pub fn update(&mut self, down_for: Option<Duration>) -> Option<String> {
+        let after = self.after?;
+        match (self.alerted, down_for) {
+            (false, Some(down)) if down >= after => {
+                self.alerted = true;
+                Some(format!(
+                    "📡 No WebSocket connection to Bybit for {}s",
+                    down.as_secs()
+                ))
+            }
+            (true, None) => {
+                self.alerted = false;
+                Some("📡 WebSocket connection to Bybit restored".to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct Recorder(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl Notifier for Recorder {
+        async fn send(&self, text: &str) -> Result<()> {
+            self.0.lock().unwrap().push(text.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notifications_are_delivered_in_the_background() {
+        let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+        let notifications = Notifications::spawn(recorder.clone(), false);
+        notifications.notify("first".to_string());
+        notifications.notify("second".to_string());
+
+        for _ in 0..100 {
+            if recorder.0.lock().unwrap().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(*recorder.0.lock().unwrap(), ["first", "second"]);
+    }
+
+    #[test]
+    fn test_outage_alerts_once_and_on_recovery() {
+        let mut alert = OutageAlert::new(60);
+        assert_eq!(alert.update(Some(Duration::from_secs(30))), None);
+        assert!(alert
+            .update(Some(Duration::from_secs(61)))
+            .unwrap()
+            .contains("61s"));
+        assert_eq!(alert.update(Some(Duration::from_secs(120))), None);
+        assert!(alert.update(None).unwrap().contains("restored"));
+        assert_eq!(alert.update(None), None);
+
+        assert_eq!(
+            OutageAlert::new(0).update(Some(Duration::from_secs(3600))),
+            None
+        );
+    }
+}
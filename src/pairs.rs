@@ -1,38 +1,103 @@
-use crate::client::BybitClient;
-use crate::config::{self, Config};
+use crate::config::Config;
+use crate::exchange::ExchangeApi;
+use crate::focus::Focus;
 use crate::models::MarketPair;
-use anyhow::{Context, Result};
-use std::collections::HashMap;
-use tracing::debug;
+use crate::stable::STABLECOINS;
+use crate::staleness::QuoteAgeLimits;
+use crate::symbols::{CurrencyId, SymbolId, SymbolRegistry};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
 
 #[derive(Debug, Clone)]
 pub struct TriangleDefinition {
     pub base_currency: String,
     pub indices: [usize; 3],
+    /// Symbols of the three legs, in the shared registry
+    pub symbols: [SymbolId; 3],
     pub path: Vec<String>,
 }
 
 pub struct PairManager {
     pub config: Config,
     pub pairs: Vec<MarketPair>, // Made public for direct access by ArbitrageEngine
-    price_map: HashMap<String, f64>,
-    symbol_to_pair: HashMap<String, usize>,
+    /// Every symbol and currency seen since startup, interned as instruments are loaded and
+    /// shared with the arbitrage engine and the trader
+    registry: Arc<SymbolRegistry>,
+    /// Index in `pairs` of each interned symbol, by SymbolId
+    pair_by_symbol: Vec<Option<usize>>,
     last_updated: Option<chrono::DateTime<chrono::Utc>>,
-    triangle_cache: HashMap<String, Vec<TriangleDefinition>>,
+    last_price_update: Option<chrono::DateTime<chrono::Utc>>,
+    triangle_cache: HashMap<CurrencyId, Vec<TriangleDefinition>>,
+    /// Cached triangles through each pair, by index in `pairs`: their base currency and
+    /// position in that currency's cache
+    triangles_by_pair: Vec<Vec<(CurrencyId, usize)>>,
+    /// Bumped whenever the triangle cache is rebuilt or pairs stop or resume trading, so
+    /// scans know every triangle has to be evaluated again
+    generation: u64,
+    /// Number of ticker updates applied so far
+    update_seq: u64,
+    /// Value of `update_seq` at each pair's last ticker update, by index in `pairs`
+    updated_at_seq: Vec<u64>,
+    /// Symbols no longer streamed, kept inactive across refreshes so stale prices aren't traded
+    unsubscribed: HashSet<String>,
+    /// Symbols Bybit has stopped trading, e.g. suspended for maintenance
+    suspended: HashSet<String>,
+    /// When each pair's bid or ask last changed, by index in `pairs`
+    quoted_at: Vec<Option<Instant>>,
+    /// When each pair's last price message arrived, changed or not, by index in `pairs`
+    received_at: Vec<Option<Instant>>,
+    quote_age_limits: QuoteAgeLimits,
+    /// Reduced universe the triangle cache is limited to, if any
+    focus: Option<Focus>,
 }
 
 impl PairManager {
     pub fn new(config: Config) -> Self {
+        let quote_age_limits =
+            QuoteAgeLimits::parse(config.max_quote_age_ms, &config.quote_age_overrides)
+                .unwrap_or_else(|e| {
+                    warn!("⚠️ Ignoring QUOTE_AGE_OVERRIDES: {e:#}");
+                    QuoteAgeLimits::parse(config.max_quote_age_ms, "").unwrap_or_default()
+                });
         Self {
             config,
             pairs: Vec::new(),
-            price_map: HashMap::new(),
-            symbol_to_pair: HashMap::new(),
+            registry: SymbolRegistry::new(),
+            pair_by_symbol: Vec::new(),
             last_updated: None,
+            last_price_update: None,
             triangle_cache: HashMap::new(),
+            triangles_by_pair: Vec::new(),
+            generation: 0,
+            update_seq: 0,
+            updated_at_seq: Vec::new(),
+            unsubscribed: HashSet::new(),
+            suspended: HashSet::new(),
+            quoted_at: Vec::new(),
+            received_at: Vec::new(),
+            quote_age_limits,
+            focus: None,
         }
     }
 
+    /// Intern symbols into `registry`, shared with the other modules, instead of a
+    /// registry of its own
+    pub fn with_symbols(mut self, registry: Arc<SymbolRegistry>) -> Self {
+        self.registry = registry;
+        self.index_pairs();
+        self.rebuild_triangle_cache();
+        self
+    }
+
+    /// The symbol registry pair and triangle IDs refer to
+    pub fn symbols(&self) -> &Arc<SymbolRegistry> {
+        &self.registry
+    }
+
     #[allow(dead_code)]
     pub fn get_all_symbols(&self) -> Vec<String> {
         self.pairs.iter().map(|p| p.symbol.clone()).collect()
@@ -47,6 +112,68 @@ impl PairManager {
             .collect()
     }
 
+    /// Stop trading `symbols` once their WebSocket subscriptions are dropped
+    pub fn unsubscribe(&mut self, symbols: &[String]) {
+        self.unsubscribed.extend(symbols.iter().cloned());
+        self.generation += 1;
+        for pair in &mut self.pairs {
+            if self.unsubscribed.contains(&pair.symbol) {
+                pair.is_active = false;
+            }
+        }
+    }
+
+    /// Stop trading exactly these symbols while Bybit has them suspended
+    pub fn set_suspended(&mut self, symbols: HashSet<String>) {
+        if symbols != self.suspended {
+            self.generation += 1;
+        }
+        self.suspended = symbols;
+        for pair in &mut self.pairs {
+            pair.is_active =
+                !self.suspended.contains(&pair.symbol) && !self.unsubscribed.contains(&pair.symbol);
+        }
+    }
+
+    /// Replace the blacklist and drop the pairs it now covers. Pairs of coins taken off it
+    /// come back with the next full refresh. Returns the number of pairs dropped.
+    pub fn set_blacklist(&mut self, tokens: String) -> usize {
+        self.config.blacklisted_tokens = tokens;
+        let keep: Vec<bool> = self
+            .pairs
+            .iter()
+            .map(|p| {
+                !self.config.is_token_blacklisted(&p.base)
+                    && !self.config.is_token_blacklisted(&p.quote)
+            })
+            .collect();
+        let dropped = keep.iter().filter(|&&k| !k).count();
+        if dropped == 0 {
+            return 0;
+        }
+        let pairs = std::mem::take(&mut self.pairs);
+        self.pairs = pairs
+            .into_iter()
+            .zip(&keep)
+            .filter_map(|(pair, &k)| k.then_some(pair))
+            .collect();
+        self.quoted_at = self
+            .quoted_at
+            .iter()
+            .zip(&keep)
+            .filter_map(|(quoted, &k)| k.then_some(*quoted))
+            .collect();
+        self.received_at = self
+            .received_at
+            .iter()
+            .zip(&keep)
+            .filter_map(|(received, &k)| k.then_some(*received))
+            .collect();
+        self.index_pairs();
+        self.rebuild_triangle_cache();
+        dropped
+    }
+
     pub fn update_from_ticker(&mut self, ticker: &crate::models::TickerInfo) {
         // if ticker.symbol == "BTCUSDT" || ticker.symbol == "ETHUSDT" {
         //     info!(
@@ -62,12 +189,18 @@ impl PairManager {
             .as_ref()
             .and_then(|s| s.parse::<f64>().ok());
 
-        if let Some(&idx) = self.symbol_to_pair.get(&ticker.symbol) {
+        if let Some(idx) = self.pair_index(&ticker.symbol) {
+            self.update_seq += 1;
+            if let Some(seq) = self.updated_at_seq.get_mut(idx) {
+                *seq = self.update_seq;
+            }
+            if let Some(received_at) = self.received_at.get_mut(idx) {
+                *received_at = Some(Instant::now());
+            }
             if let Some(pair) = self.pairs.get_mut(idx) {
                 // Update last price if available
                 if let Some(price) = price_opt {
                     pair.price = price;
-                    self.price_map.insert(ticker.symbol.clone(), price);
                 }
 
                 // Also update bid/ask if available
@@ -96,6 +229,11 @@ impl PairManager {
                 }
 
                 if prices_updated {
+                    self.last_price_update = Some(chrono::Utc::now());
+                    if let Some(quoted_at) = self.quoted_at.get_mut(idx) {
+                        *quoted_at = Some(Instant::now());
+                    }
+
                     // Re-calculate spread
                     if pair.bid_price > 0.0 {
                         pair.spread_percent =
@@ -161,7 +299,10 @@ impl PairManager {
     }
 
     /// Fetch all trading pairs and their current prices
-    pub async fn update_pairs_and_prices(&mut self, client: &BybitClient) -> Result<()> {
+    pub async fn update_pairs_and_prices<E: ExchangeApi + ?Sized>(
+        &mut self,
+        client: &E,
+    ) -> Result<()> {
         debug!("🔄 Updating trading pairs and prices...");
 
         // Fetch instruments
@@ -182,27 +323,14 @@ impl PairManager {
             ticker_map.insert(ticker.symbol.clone(), ticker);
         }
 
-        // Create price map from tickers (for backward compatibility)
-        let mut price_map = HashMap::new();
-        for ticker in &tickers_result.list {
-            if let Some(price) = ticker
-                .last_price
-                .as_ref()
-                .and_then(|s| s.parse::<f64>().ok())
-            {
-                price_map.insert(ticker.symbol.clone(), price);
-            }
-        }
-
         // Create market pairs with bid/ask data, filtering out blacklisted tokens
         let mut pairs = Vec::new();
-        let mut symbol_to_pair = HashMap::new();
         let mut blacklisted_count = 0;
 
         for instrument in instruments.iter() {
             // Check if base or quote currency is blacklisted
-            if config::is_token_blacklisted(&instrument.base_coin)
-                || config::is_token_blacklisted(&instrument.quote_coin)
+            if self.config.is_token_blacklisted(&instrument.base_coin)
+                || self.config.is_token_blacklisted(&instrument.quote_coin)
             {
                 blacklisted_count += 1;
                 continue;
@@ -224,12 +352,6 @@ impl PairManager {
                 && pair.bid_price < pair.ask_price
         });
 
-        // Rebuild symbol_to_pair map after filtering
-        symbol_to_pair.clear();
-        for (idx, pair) in pairs.iter().enumerate() {
-            symbol_to_pair.insert(pair.symbol.clone(), idx);
-        }
-
         if blacklisted_count > 0 {
             debug!(
                 "🚫 Filtered out {} pairs containing blacklisted tokens",
@@ -237,10 +359,23 @@ impl PairManager {
             );
         }
 
+        for pair in &mut pairs {
+            if self.unsubscribed.contains(&pair.symbol) || self.suspended.contains(&pair.symbol) {
+                pair.is_active = false;
+            }
+        }
+
         self.pairs = pairs;
-        self.price_map = price_map;
-        self.symbol_to_pair = symbol_to_pair;
+        self.index_pairs();
         self.last_updated = Some(chrono::Utc::now());
+        self.last_price_update = self.last_updated;
+        let now = Instant::now();
+        self.quoted_at = self
+            .pairs
+            .iter()
+            .map(|p| (p.bid_price > 0.0 && p.ask_price > 0.0).then_some(now))
+            .collect();
+        self.received_at = vec![Some(now); self.pairs.len()];
 
         // Rebuild triangle cache after updating pairs
         self.rebuild_triangle_cache();
@@ -255,14 +390,46 @@ impl PairManager {
         Ok(())
     }
 
+    /// Intern the symbols of `pairs` and map each to its index
+    fn index_pairs(&mut self) {
+        self.pair_by_symbol.fill(None);
+        self.updated_at_seq = vec![self.update_seq; self.pairs.len()];
+        for (idx, pair) in self.pairs.iter().enumerate() {
+            let id = self
+                .registry
+                .intern_symbol(&pair.symbol, &pair.base, &pair.quote);
+            if self.pair_by_symbol.len() <= id.index() {
+                self.pair_by_symbol.resize(id.index() + 1, None);
+            }
+            self.pair_by_symbol[id.index()] = Some(idx);
+        }
+    }
+
+    fn pair_index(&self, symbol: &str) -> Option<usize> {
+        let id = self.registry.symbol_id(symbol)?;
+        self.pair_by_symbol.get(id.index()).copied().flatten()
+    }
+
+    /// Index of the pair converting `from` into `to`, either way round
+    fn pair_between(&self, from: &str, to: &str) -> Option<usize> {
+        let (id, _) = self.registry.conversion(from, to)?;
+        self.pair_by_symbol.get(id.index()).copied().flatten()
+    }
+
     /// Rebuild the cache of triangle definitions
     /// This is an expensive operation but only needs to run when pairs change
     fn rebuild_triangle_cache(&mut self) {
         debug!("🔄 Rebuilding triangle cache...");
         self.triangle_cache.clear();
+        self.generation += 1;
 
         let currencies = self.get_all_currencies();
         let mut total_triangles = 0;
+        let symbol_ids: Vec<SymbolId> = self
+            .pairs
+            .iter()
+            .map(|p| self.registry.intern_symbol(&p.symbol, &p.base, &p.quote))
+            .collect();
 
         // Pre-calculate liquid pairs indices to speed up the search
         let liquid_indices: Vec<usize> = self
@@ -324,10 +491,16 @@ impl PairManager {
                             && pair3.quote == base_currency)
                             || (pair3.quote == *final_currency && pair3.base == base_currency);
 
-                        if closes_loop {
+                        let path = [&base_currency, intermediate, final_currency, &base_currency];
+                        let focused = self
+                            .focus
+                            .as_ref()
+                            .is_none_or(|focus| focus.allows(&path.map(String::clone)));
+                        if closes_loop && focused {
                             triangles.push(TriangleDefinition {
                                 base_currency: base_currency.clone(),
                                 indices: [idx1, idx2, idx3],
+                                symbols: [idx1, idx2, idx3].map(|idx| symbol_ids[idx]),
                                 path: vec![
                                     base_currency.clone(),
                                     intermediate.clone(),
@@ -342,7 +515,17 @@ impl PairManager {
 
             if !triangles.is_empty() {
                 total_triangles += triangles.len();
-                self.triangle_cache.insert(base_currency, triangles);
+                let base = self.registry.intern_currency(&base_currency);
+                self.triangle_cache.insert(base, triangles);
+            }
+        }
+
+        self.triangles_by_pair = vec![Vec::new(); self.pairs.len()];
+        for (&base, triangles) in &self.triangle_cache {
+            for (position, triangle) in triangles.iter().enumerate() {
+                for idx in triangle.indices {
+                    self.triangles_by_pair[idx].push((base, position));
+                }
             }
         }
 
@@ -352,16 +535,213 @@ impl PairManager {
         );
     }
 
+    /// Manager over a fixed set of pairs, indexed and with its triangles cached
+    pub fn from_pairs(config: Config, pairs: Vec<MarketPair>) -> Self {
+        let mut manager = Self::new(config);
+        manager.received_at = vec![None; pairs.len()];
+        manager.pairs = pairs;
+        manager.index_pairs();
+        manager.rebuild_triangle_cache();
+        manager
+    }
+
+    /// Changes with every rebuild of the triangle cache and every change to which pairs trade
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Sequence number of the latest ticker update, to pass to `updated_triangles` later
+    pub fn update_seq(&self) -> u64 {
+        self.update_seq
+    }
+
+    /// Positions in each base currency's cache of the triangles with a leg updated after
+    /// update `since`
+    pub fn updated_triangles(&self, since: u64) -> HashMap<CurrencyId, HashSet<usize>> {
+        let mut updated: HashMap<CurrencyId, HashSet<usize>> = HashMap::new();
+        for (idx, _) in self
+            .updated_at_seq
+            .iter()
+            .enumerate()
+            .filter(|(_, &seq)| seq > since)
+        {
+            for &(base, position) in self.triangles_by_pair.get(idx).into_iter().flatten() {
+                updated.entry(base).or_default().insert(position);
+            }
+        }
+        updated
+    }
+
+    /// Limit scanning to the triangles in `focus`, or restore the full universe with None.
+    /// A focus matching no triangle is refused and the previous universe kept. Returns the
+    /// number of triangles now cached.
+    pub fn set_focus(&mut self, focus: Option<Focus>) -> Result<usize> {
+        let previous = std::mem::replace(&mut self.focus, focus);
+        self.rebuild_triangle_cache();
+        let triangles = self.triangle_cache.values().map(Vec::len).sum();
+        if triangles == 0 {
+            if let Some(focus) = std::mem::replace(&mut self.focus, previous) {
+                self.rebuild_triangle_cache();
+                bail!("no liquid triangle matches {focus}");
+            }
+        }
+        Ok(triangles)
+    }
+
+    /// Symbols of the focused triangles, None while scanning the full universe
+    pub fn focused_symbols(&self) -> Option<HashSet<String>> {
+        self.focus.as_ref()?;
+        Some(
+            self.triangle_cache
+                .values()
+                .flatten()
+                .flat_map(|t| t.indices)
+                .map(|idx| self.pairs[idx].symbol.clone())
+                .collect(),
+        )
+    }
+
+    /// Build a triangle for an explicit path such as [USDT, BTC, ETH, USDT],
+    /// regardless of whether its pairs pass the liquidity filters
+    pub fn find_triangle(&self, path: &[String]) -> Option<TriangleDefinition> {
+        if path.len() != 4 || path[0] != path[3] {
+            return None;
+        }
+
+        let mut symbols = Vec::with_capacity(3);
+        for step in path.windows(2) {
+            symbols.push(self.registry.conversion(&step[0], &step[1])?.0);
+        }
+        let symbols: [SymbolId; 3] = symbols.try_into().ok()?;
+        self.triangle(symbols, path.to_vec())
+    }
+
+    /// Triangle along `path` through the pairs at `indices` in `pairs`
+    pub fn triangle_at(&self, indices: [usize; 3], path: Vec<String>) -> TriangleDefinition {
+        let symbols = indices.map(|idx| {
+            let pair = &self.pairs[idx];
+            self.registry
+                .intern_symbol(&pair.symbol, &pair.base, &pair.quote)
+        });
+        TriangleDefinition {
+            base_currency: path[0].clone(),
+            indices,
+            symbols,
+            path,
+        }
+    }
+
+    /// Triangle along `path` through the pairs of `symbols`, if all three are loaded
+    pub fn triangle(
+        &self,
+        symbols: [SymbolId; 3],
+        path: Vec<String>,
+    ) -> Option<TriangleDefinition> {
+        let mut indices = [0usize; 3];
+        for (idx, &id) in indices.iter_mut().zip(&symbols) {
+            *idx = self.pair_by_symbol.get(id.index()).copied().flatten()?;
+        }
+        Some(TriangleDefinition {
+            base_currency: path[0].clone(),
+            indices,
+            symbols,
+            path,
+        })
+    }
+
+    /// Time of the most recent bid/ask update from REST or WebSocket
+    pub fn last_price_update(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.last_price_update
+    }
+
     /// Get cached triangle definitions for a base currency
-    pub fn get_cached_triangles(&self, base_currency: &str) -> Option<&Vec<TriangleDefinition>> {
-        self.triangle_cache.get(base_currency)
+    pub fn get_cached_triangles(&self, base: CurrencyId) -> Option<&Vec<TriangleDefinition>> {
+        self.triangle_cache.get(&base)
     }
 
     /// Get all market pairs
+    /// Whether the quote of the pair at `idx` is older than its MAX_QUOTE_AGE_MS or
+    /// QUOTE_AGE_OVERRIDES limit
+    pub fn is_quote_stale(&self, idx: usize, now: Instant) -> bool {
+        let age = self
+            .quoted_at
+            .get(idx)
+            .copied()
+            .flatten()
+            .map(|at| now.saturating_duration_since(at));
+        self.quote_age_limits.is_stale(&self.pairs[idx], age)
+    }
+
+    /// The first of `symbols` whose last price arrived longer than `max_age` ago, with its
+    /// age (None when no price was ever received)
+    pub fn stale_price(
+        &self,
+        symbols: &[String],
+        max_age: Duration,
+        now: Instant,
+    ) -> Option<(String, Option<Duration>)> {
+        symbols.iter().find_map(|symbol| {
+            let age = self
+                .pair_index(symbol)
+                .and_then(|idx| self.received_at.get(idx).copied().flatten())
+                .map(|at| now.saturating_duration_since(at));
+            (age.is_none_or(|age| age > max_age)).then(|| (symbol.clone(), age))
+        })
+    }
+
+    /// Expiry of an opportunity priced from the current quotes of the pairs at `indices`:
+    /// OPPORTUNITY_TTL_MS after the oldest of them, None when expiry is disabled
+    pub fn quotes_valid_until(&self, indices: &[usize]) -> Option<DateTime<Utc>> {
+        let ttl = Duration::from_millis(self.config.opportunity_ttl_ms);
+        if ttl.is_zero() {
+            return None;
+        }
+        let now = Instant::now();
+        let oldest = indices
+            .iter()
+            .filter_map(|&idx| self.quoted_at.get(idx).copied().flatten())
+            .map(|at| now.saturating_duration_since(at))
+            .max()
+            .unwrap_or_default();
+        let ttl = chrono::Duration::from_std(ttl.saturating_sub(oldest)).ok()?;
+        Some(Utc::now() + ttl)
+    }
+
     pub fn get_pairs(&self) -> &[MarketPair] {
         &self.pairs
     }
 
+    /// Look up a pair by symbol
+    pub fn get_pair(&self, symbol: &str) -> Option<&MarketPair> {
+        self.pair_index(symbol).and_then(|idx| self.pairs.get(idx))
+    }
+
+    /// Look up a pair by its ID in the shared registry
+    pub fn pair(&self, id: SymbolId) -> Option<&MarketPair> {
+        let idx = self.pair_by_symbol.get(id.index()).copied().flatten()?;
+        self.pairs.get(idx)
+    }
+
+    /// USD value of one `coin`, from the mid of its USDT pair either way round. USD
+    /// stablecoins without a pair count at par.
+    pub fn usd_price(&self, coin: &str) -> Option<f64> {
+        if coin == "USDT" {
+            return Some(1.0);
+        }
+        let price = self.pair_between(coin, "USDT").map(|idx| {
+            let pair = &self.pairs[idx];
+            let mid = (pair.bid_price + pair.ask_price) / 2.0;
+            if pair.base == coin {
+                mid
+            } else {
+                1.0 / mid
+            }
+        });
+        price
+            .filter(|price| price.is_finite() && *price > 0.0)
+            .or_else(|| STABLECOINS.contains(&coin).then_some(1.0))
+    }
+
     /// Get pairs filtered by base or quote currency
     pub fn get_pairs_with_currency(&self, currency: &str) -> Vec<&MarketPair> {
         self.pairs
@@ -551,41 +931,26 @@ mod tests {
     use super::*;
     use crate::models::MarketPair;
 
-    fn create_test_pair(symbol: &str, base: &str, quote: &str, price: f64) -> MarketPair {
-        MarketPair {
-            base: base.to_string(),
-            quote: quote.to_string(),
-            symbol: symbol.to_string(),
-            price,
-            bid_price: price,
-            ask_price: price,
-            bid_size: 1.0,
-            ask_size: 1.0,
-            volume_24h: 1000.0,
-            volume_24h_usd: 1000.0 * price,
-            spread_percent: 0.0,
-            min_qty: 0.001,
-            qty_step: 0.001,
-            min_notional: 1.0,
-            is_active: true,
-            is_liquid: true,
-        }
+    fn create_test_pair(base: &str, quote: &str, price: f64) -> MarketPair {
+        MarketPair::test(base, quote, price, price)
+            .with_size(1.0)
+            .with_volume(1000.0)
     }
 
     #[test]
     fn test_pair_manager_creation() {
-        let manager = PairManager::new();
+        let manager = PairManager::new(Config::test_config());
         assert_eq!(manager.pairs.len(), 0);
         assert!(manager.last_updated.is_none());
     }
 
     #[test]
     fn test_get_pairs_with_currency() {
-        let mut manager = PairManager::new();
+        let mut manager = PairManager::new(Config::test_config());
         manager.pairs = vec![
-            create_test_pair("BTCUSDT", "BTC", "USDT", 50000.0),
-            create_test_pair("ETHUSDT", "ETH", "USDT", 3000.0),
-            create_test_pair("ETHBTC", "ETH", "BTC", 0.06),
+            create_test_pair("BTC", "USDT", 50000.0),
+            create_test_pair("ETH", "USDT", 3000.0),
+            create_test_pair("ETH", "BTC", 0.06),
         ];
 
         let usdt_pairs = manager.get_pairs_with_currency("USDT");
@@ -597,11 +962,11 @@ mod tests {
 
     #[test]
     fn test_get_all_currencies() {
-        let mut manager = PairManager::new();
+        let mut manager = PairManager::new(Config::test_config());
         manager.pairs = vec![
-            create_test_pair("BTCUSDT", "BTC", "USDT", 50000.0),
-            create_test_pair("ETHUSDT", "ETH", "USDT", 3000.0),
-            create_test_pair("ETHBTC", "ETH", "BTC", 0.06),
+            create_test_pair("BTC", "USDT", 50000.0),
+            create_test_pair("ETH", "USDT", 3000.0),
+            create_test_pair("ETH", "BTC", 0.06),
         ];
 
         let currencies = manager.get_all_currencies();
@@ -613,22 +978,21 @@ mod tests {
 
     #[test]
     fn test_find_triangle_pairs() {
-        let mut manager = PairManager::new();
+        let mut manager = PairManager::new(Config::test_config());
         manager.pairs = vec![
-            create_test_pair("BTCUSDT", "BTC", "USDT", 50000.0),
-            create_test_pair("ETHUSDT", "ETH", "USDT", 3000.0),
-            create_test_pair("ETHBTC", "ETH", "BTC", 0.06),
+            create_test_pair("BTC", "USDT", 50000.0),
+            create_test_pair("ETH", "USDT", 3000.0),
+            create_test_pair("ETH", "BTC", 0.06),
         ];
 
         // Rebuild symbol map
-        for (idx, pair) in manager.pairs.iter().enumerate() {
-            manager.symbol_to_pair.insert(pair.symbol.clone(), idx);
-        }
+        manager.index_pairs();
 
         // Rebuild cache
         manager.rebuild_triangle_cache();
 
-        let triangles = manager.get_cached_triangles("USDT").unwrap();
+        let usdt = manager.symbols().currency_id("USDT").unwrap();
+        let triangles = manager.get_cached_triangles(usdt).unwrap();
         assert!(!triangles.is_empty());
 
         // Should find USDT -> BTC -> ETH -> USDT or USDT -> ETH -> BTC -> USDT
@@ -637,4 +1001,117 @@ mod tests {
         assert_eq!(first_triangle.path[0], "USDT");
         assert_eq!(first_triangle.path[3], "USDT");
     }
+
+    #[test]
+    fn test_focus_limits_the_triangle_cache_and_refuses_an_empty_universe() {
+        let mut manager = PairManager::new(Config::test_config());
+        manager.pairs = vec![
+            create_test_pair("BTC", "USDT", 50000.0),
+            create_test_pair("ETH", "USDT", 3000.0),
+            create_test_pair("ETH", "BTC", 0.06),
+            create_test_pair("SOL", "USDT", 150.0),
+            create_test_pair("SOL", "BTC", 0.003),
+        ];
+        let all = manager.set_focus(None).unwrap();
+        assert!(manager.focused_symbols().is_none());
+
+        let focused = manager
+            .set_focus(Some(Focus::parse("SOL", "").unwrap()))
+            .unwrap();
+        assert!(focused < all);
+        let symbols = manager.focused_symbols().unwrap();
+        assert!(symbols.contains("SOLBTC") && !symbols.contains("ETHBTC"));
+
+        // A focus on nothing tradeable keeps the previous universe
+        assert!(manager
+            .set_focus(Some(Focus::parse("XRP", "").unwrap()))
+            .is_err());
+        assert_eq!(manager.focused_symbols(), Some(symbols));
+        assert_eq!(manager.set_focus(None).unwrap(), all);
+    }
+
+    #[test]
+    fn test_blacklisting_a_coin_drops_its_pairs_and_triangles() {
+        let mut manager = PairManager::from_pairs(
+            Config::test_config(),
+            vec![
+                create_test_pair("BTC", "USDT", 50000.0),
+                create_test_pair("ETH", "USDT", 3000.0),
+                create_test_pair("ETH", "BTC", 0.06),
+                create_test_pair("SOL", "USDT", 150.0),
+            ],
+        );
+        let usdt = manager.symbols().currency_id("USDT").unwrap();
+        assert!(!manager.get_cached_triangles(usdt).unwrap().is_empty());
+
+        assert_eq!(manager.set_blacklist("LUNA".to_string()), 0);
+        assert_eq!(manager.set_blacklist("LUNA,ETH".to_string()), 2);
+        assert_eq!(manager.get_all_symbols(), ["BTCUSDT", "SOLUSDT"]);
+        assert!(manager.get_pair("SOLUSDT").is_some());
+        assert!(manager
+            .get_cached_triangles(usdt)
+            .is_none_or(|triangles| triangles.is_empty()));
+    }
+
+    #[test]
+    fn test_stale_price_names_the_first_leg_past_the_window() {
+        let mut manager = PairManager::from_pairs(
+            Config::test_config(),
+            vec![
+                create_test_pair("BTC", "USDT", 50000.0),
+                create_test_pair("ETH", "USDT", 3000.0),
+            ],
+        );
+        let symbols = ["BTCUSDT".to_string(), "ETHUSDT".to_string()];
+        let window = Duration::from_millis(500);
+        assert_eq!(
+            manager.stale_price(&symbols, window, Instant::now()),
+            Some(("BTCUSDT".to_string(), None))
+        );
+
+        // An unchanged price still counts as a fresh message
+        for symbol in &symbols {
+            let ticker: crate::models::TickerInfo =
+                serde_json::from_value(serde_json::json!({ "symbol": symbol })).unwrap();
+            manager.update_from_ticker(&ticker);
+        }
+        let now = Instant::now();
+        assert_eq!(manager.stale_price(&symbols, window, now), None);
+        let (symbol, age) = manager
+            .stale_price(&symbols, window, now + Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(symbol, "BTCUSDT");
+        assert!(age.unwrap() > window);
+    }
+
+    #[test]
+    fn test_find_triangle_for_explicit_path() {
+        let mut manager = PairManager::new(Config::test_config());
+        manager.pairs = vec![
+            create_test_pair("BTC", "USDT", 50000.0),
+            create_test_pair("ETH", "USDT", 3000.0),
+            create_test_pair("ETH", "BTC", 0.06),
+        ];
+        manager.index_pairs();
+
+        let path: Vec<String> = ["USDT", "BTC", "ETH", "USDT"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let triangle = manager.find_triangle(&path).unwrap();
+        assert_eq!(triangle.indices, [0, 2, 1]);
+        assert_eq!(
+            triangle
+                .symbols
+                .map(|id| manager.symbols().symbol(id).to_string()),
+            ["BTCUSDT", "ETHBTC", "ETHUSDT"]
+        );
+        assert_eq!(manager.pair(triangle.symbols[1]).unwrap().symbol, "ETHBTC");
+
+        let missing: Vec<String> = ["USDT", "BTC", "SOL", "USDT"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(manager.find_triangle(&missing).is_none());
+    }
 }
@@ -0,0 +1,375 @@
+use crate::backend::TradeExecutor;
+use crate::depth::DepthBook;
+use crate::fees::FeeSchedule;
+use crate::imbalance::TopOfBook;
+use crate::models::{ArbitrageOpportunity, OrderQty, Price, Qty, SCHEMA_VERSION};
+use crate::precision::PrecisionManager;
+use crate::resume::ExecutionState;
+use crate::symbols::{SymbolId, SymbolRegistry};
+use crate::trader::ArbitrageExecutionResult;
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::info;
+
+/// Parse `PAPER_BALANCES`, e.g. `USDT:1000,USDC:500`
+pub fn parse_balances(value: &str) -> Result<BTreeMap<String, f64>> {
+    let mut balances = BTreeMap::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (coin, amount) = entry
+            .split_once(':')
+            .ok_or_else(|| anyhow!("'{entry}' is not COIN:AMOUNT"))?;
+        let amount: f64 = amount
+            .trim()
+            .parse()
+            .with_context(|| format!("'{entry}' has no valid amount"))?;
+        if !(amount >= 0.0 && amount.is_finite()) {
+            bail!("'{entry}' must not be negative");
+        }
+        balances.insert(coin.trim().to_uppercase(), amount);
+    }
+    Ok(balances)
+}
+
+/// A triangle filled on paper
+struct PaperFill {
+    /// The ledger after the trade
+    ledger: BTreeMap<String, f64>,
+    /// Start coin back at the end of the last leg
+    final_amount: f64,
+    /// Fees, valued in the start coin
+    fees: f64,
+    /// Rounding leftovers of the intermediate coins
+    dust: BTreeMap<String, f64>,
+}
+
+/// Fills triangles as market orders against the live book, with the exchange's rounding
+/// and fees, out of a virtual balance ledger
+pub struct PaperTrader {
+    precision: PrecisionManager,
+    fees: FeeSchedule,
+    balances: BTreeMap<String, f64>,
+    /// Registry the book's symbol IDs refer to
+    symbols: Arc<SymbolRegistry>,
+    /// Top of book of the symbols the next trade goes through
+    book: HashMap<SymbolId, TopOfBook>,
+    /// Depth of those symbols where streamed, walked instead of the top of book
+    depth: HashMap<SymbolId, DepthBook>,
+    /// USD price of the coins the next trade goes through, for valuing dust
+    usd_prices: HashMap<String, f64>,
+}
+
+impl PaperTrader {
+    pub fn new(
+        precision: PrecisionManager,
+        fees: FeeSchedule,
+        balances: BTreeMap<String, f64>,
+        symbols: Arc<SymbolRegistry>,
+    ) -> Self {
+        Self {
+            precision,
+            fees,
+            balances,
+            symbols,
+            book: HashMap::new(),
+            depth: HashMap::new(),
+            usd_prices: HashMap::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn balances(&self) -> &BTreeMap<String, f64> {
+        &self.balances
+    }
+
+    /// Average price a market order spending `amount` fills at: the depth walked when the
+    /// symbol is streamed, otherwise the whole order at the best bid or ask
+    fn fill_price(&self, symbol: &str, sell: bool, amount: f64) -> Result<f64> {
+        let id = self
+            .symbols
+            .symbol_id(symbol)
+            .ok_or_else(|| anyhow!("No book for {symbol}"))?;
+        if let Some(depth) = self.depth.get(&id).filter(|d| !d.is_empty()) {
+            return depth
+                .average_price(sell, amount)
+                .ok_or_else(|| anyhow!("{symbol} book is too thin to fill {amount}"));
+        }
+        let top = self
+            .book
+            .get(&id)
+            .ok_or_else(|| anyhow!("No book for {symbol}"))?;
+        let price = if sell { top.bid_price } else { top.ask_price };
+        if price <= 0.0 {
+            bail!("{symbol} has no {}", if sell { "bid" } else { "ask" });
+        }
+        Ok(price)
+    }
+
+    /// Walk the legs on a copy of the ledger
+    fn fill(&self, opportunity: &ArbitrageOpportunity, amount: f64) -> Result<PaperFill> {
+        let mut ledger = self.balances.clone();
+        let mut dust = BTreeMap::new();
+        let (mut held, mut fees) = (amount, 0.0);
+        // Start coin each unit of the held coin cost, for valuing fees
+        let mut unit_cost = 1.0;
+
+        for (i, symbol) in opportunity.pairs.iter().enumerate() {
+            let (from, to) = (&opportunity.path[i], &opportunity.path[i + 1]);
+            let leg = || format!("leg {} on {symbol}", i + 1);
+            let info = self
+                .precision
+                .get_symbol_precision(symbol)
+                .ok_or_else(|| anyhow!("{symbol} has no precision data"))?;
+            let sell = if info.base_coin == *from && info.quote_coin == *to {
+                true
+            } else if info.base_coin == *to && info.quote_coin == *from {
+                false
+            } else {
+                bail!("{symbol} does not convert {from} → {to}");
+            };
+            let side = if sell { "Sell" } else { "Buy" };
+
+            let spent: f64 = self
+                .precision
+                .format_order_quantity(symbol, OrderQty::for_side(side, held))
+                .parse()
+                .with_context(leg)?;
+            let available = ledger.get(from).copied().unwrap_or(0.0);
+            if spent > available {
+                bail!(
+                    "Paper balance {available:.8} {from} is short of the {spent:.8} {} spends",
+                    leg()
+                );
+            }
+            let price = self.fill_price(symbol, sell, spent).with_context(leg)?;
+            if sell {
+                self.precision
+                    .validate_quantity(symbol, Qty::new(spent))
+                    .with_context(leg)?;
+            }
+            self.precision
+                .validate_order_value(
+                    symbol,
+                    OrderQty::for_side(side, spent).notional_at(Price::new(price)),
+                )
+                .with_context(leg)?;
+
+            let gross = if sell {
+                spent * price
+            } else {
+                // Market Buys fill whole base steps
+                self.precision
+                    .format_order_quantity(symbol, OrderQty::Base(Qty::new(spent / price)))
+                    .parse()
+                    .with_context(leg)?
+            };
+            let fee = gross * self.fees.taker(symbol);
+            unit_cost *= spent / gross;
+            fees += fee * unit_cost;
+
+            // Rounding leftovers of the start coin simply stay in the ledger
+            if i > 0 && held > spent {
+                *dust.entry(from.clone()).or_insert(0.0) += held - spent;
+            }
+            *ledger.entry(from.clone()).or_insert(0.0) -= spent;
+            *ledger.entry(to.clone()).or_insert(0.0) += gross - fee;
+            held = gross - fee;
+        }
+        Ok(PaperFill {
+            ledger,
+            final_amount: held,
+            fees,
+            dust,
+        })
+    }
+}
+
+#[async_trait]
+impl TradeExecutor for PaperTrader {
+    async fn execute_arbitrage(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        amount: f64,
+    ) -> Result<ArbitrageExecutionResult> {
+        let start = Instant::now();
+        let mut result = ArbitrageExecutionResult {
+            schema_version: SCHEMA_VERSION,
+            success: false,
+            initial_amount: amount,
+            actual_profit: 0.0,
+            actual_profit_pct: 0.0,
+            dust_value_usd: 0.0,
+            dust: BTreeMap::new(),
+            total_fees: 0.0,
+            execution_time_ms: 0,
+            error_message: None,
+            exchange_error: None,
+        };
+        match self.fill(opportunity, amount) {
+            Ok(PaperFill {
+                ledger,
+                final_amount,
+                fees,
+                dust,
+            }) => {
+                self.balances = ledger;
+                result.success = true;
+                result.actual_profit = final_amount - amount;
+                result.actual_profit_pct = (final_amount - amount) / amount * 100.0;
+                result.total_fees = fees;
+                result.dust_value_usd = dust
+                    .iter()
+                    .map(|(coin, qty)| qty * self.usd_prices.get(coin).copied().unwrap_or(0.0))
+                    .sum();
+                result.dust = dust;
+                info!(
+                    "📝 PAPER: {} filled for {:+.6} {} ({:+.4}%)",
+                    opportunity.display_path(),
+                    result.actual_profit,
+                    opportunity.path[0],
+                    result.actual_profit_pct
+                );
+                info!("📒 {}", self.health());
+            }
+            Err(e) => result.error_message = Some(format!("Paper fill: {e:#}")),
+        }
+        result.execution_time_ms = start.elapsed().as_millis() as u64;
+        Ok(result)
+    }
+
+    /// Paper trades fill whole or not at all, so nothing is ever left half-done
+    async fn rollback(&mut self, _state: &ExecutionState) -> Result<()> {
+        Ok(())
+    }
+
+    fn health(&self) -> String {
+        let balances: Vec<String> = self
+            .balances
+            .iter()
+            .filter(|(_, qty)| **qty > 0.0)
+            .map(|(coin, qty)| format!("{qty:.8} {coin}"))
+            .collect();
+        format!("paper: {}", balances.join(", "))
+    }
+
+    fn set_market(
+        &mut self,
+        book: &HashMap<SymbolId, TopOfBook>,
+        depth: &HashMap<SymbolId, DepthBook>,
+        usd_prices: &HashMap<String, f64>,
+    ) {
+        self.book = book.clone();
+        self.depth = depth.clone();
+        self.usd_prices = usd_prices.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::depth::DepthUpdate;
+    use crate::exchange::{ExchangeApi, MockExchange, MockMarket};
+
+    async fn paper_trader(usdt: f64) -> PaperTrader {
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_market(
+                "ETHBTC",
+                MockMarket::new("ETH", "BTC", 0.0499, 0.05).with_base_decimals(5),
+            )
+            .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_560.0));
+        let precision =
+            PrecisionManager::from_instruments(exchange.get_all_spot_instruments().await.unwrap());
+        let symbols = SymbolRegistry::new();
+        symbols.intern_all(&precision);
+        let mut trader = PaperTrader::new(
+            precision,
+            FeeSchedule::flat(0.001),
+            BTreeMap::from([("USDT".to_string(), usdt)]),
+            symbols.clone(),
+        );
+        let top = |bid_price, ask_price| TopOfBook {
+            bid_price,
+            ask_price,
+            bid_size: 10.0,
+            ask_size: 10.0,
+        };
+        trader.set_market(
+            &symbols.by_id(HashMap::from([
+                ("BTCUSDT".to_string(), top(49_990.0, 50_000.0)),
+                ("ETHBTC".to_string(), top(0.0499, 0.05)),
+                ("ETHUSDT".to_string(), top(2_550.0, 2_560.0)),
+            ])),
+            &HashMap::new(),
+            &HashMap::from([("BTC".to_string(), 50_000.0), ("ETH".to_string(), 2_550.0)]),
+        );
+        trader
+    }
+
+    #[tokio::test]
+    async fn test_fills_at_the_book_and_updates_the_ledger() {
+        let mut trader = paper_trader(1_000.0).await;
+        let result = trader
+            .execute_arbitrage(&ArbitrageOpportunity::test().with_profit_pct(1.7), 100.0)
+            .await
+            .unwrap();
+
+        assert!(result.success, "{:?}", result.error_message);
+        // 2% from the prices, less three 0.1% fees and the 0.000008 BTC leg 2 can't spend
+        assert!(
+            (1.2..1.4).contains(&result.actual_profit_pct),
+            "{}",
+            result.actual_profit_pct
+        );
+        assert!(
+            (0.25..0.35).contains(&result.total_fees),
+            "{}",
+            result.total_fees
+        );
+        let usdt = trader.balances()["USDT"];
+        assert!((usdt - (1_000.0 + result.actual_profit)).abs() < 1e-9);
+        // What rounding left of BTC is dust, valued in USD
+        assert!(result.dust.contains_key("BTC"));
+        assert!(result.dust_value_usd > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_thin_depth_and_short_balance_leave_the_ledger_alone() {
+        let mut trader = paper_trader(50.0).await;
+        let result = trader
+            .execute_arbitrage(&ArbitrageOpportunity::test().with_profit_pct(1.7), 100.0)
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error_message.unwrap().contains("short of the"));
+
+        let mut trader = paper_trader(1_000.0).await;
+        let mut depth = DepthBook::default();
+        depth.apply(&DepthUpdate {
+            symbol: "ETHUSDT".to_string(),
+            snapshot: true,
+            bids: vec![(2_550.0, 0.01)],
+            asks: vec![(2_560.0, 1.0)],
+        });
+        let (book, usd_prices) = (trader.book.clone(), trader.usd_prices.clone());
+        let depth = trader
+            .symbols
+            .by_id(HashMap::from([("ETHUSDT".to_string(), depth)]));
+        trader.set_market(&book, &depth, &usd_prices);
+        let result = trader
+            .execute_arbitrage(&ArbitrageOpportunity::test().with_profit_pct(1.7), 100.0)
+            .await
+            .unwrap();
+        assert!(result.error_message.unwrap().contains("too thin"));
+        assert_eq!(trader.balances()["USDT"], 1_000.0);
+        assert_eq!(trader.balances().len(), 1);
+
+        assert!(parse_balances("USDT:1000, usdc: 5").unwrap()["USDC"] == 5.0);
+        assert!(parse_balances("USDT").is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// State kept by a previous run; a missing or unreadable file gives the default
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    match std::fs::read_to_string(path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            warn!("⚠️ Ignoring unreadable {}: {e}", path.display());
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// Save `value` as pretty JSON through `write_atomic`
+pub fn save_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    write_atomic(path, &serde_json::to_string_pretty(value)?)
+}
+
+/// Write through a temporary file next to `path`, so a crash mid-write never leaves half
+/// a file behind
+pub fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    crate::logger::create_parent_dir(path)?;
+    let tmp = temp_path(path);
+    std::fs::write(&tmp, contents).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to replace {}", path.display()))
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    PathBuf::from(tmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_save_replaces_the_file_and_load_falls_back_on_garbage() {
+        let dir = std::env::temp_dir().join(format!("persist_test_{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        let path = dir.join("state").join("coins.json");
+
+        let missing: BTreeMap<String, f64> = load_json(&path);
+        assert!(missing.is_empty());
+
+        let coins = BTreeMap::from([("BTC".to_string(), 0.5)]);
+        save_json(&path, &coins).unwrap();
+        save_json(&path, &coins).unwrap();
+        assert_eq!(load_json::<BTreeMap<String, f64>>(&path), coins);
+        assert!(!temp_path(&path).exists());
+
+        std::fs::write(&path, "{\"BTC\": ").unwrap();
+        assert!(load_json::<BTreeMap<String, f64>>(&path).is_empty());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
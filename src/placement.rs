@@ -0,0 +1,278 @@
+use crate::errors::BybitError;
+use crate::exchange::ExchangeApi;
+use crate::models::{OrderInfo, PlaceOrderRequest, PlaceOrderResult};
+use anyhow::{anyhow, bail, Result};
+use std::sync::{Arc, Mutex};
+use tracing::{error, warn};
+
+/// An order sent whose outcome hasn't been read yet
+#[derive(Debug, Clone, PartialEq)]
+struct PendingOrder {
+    category: String,
+    symbol: String,
+    order_link_id: String,
+}
+
+/// Orders recorded by orderLinkId before they are sent, until their outcome is known. An
+/// entry outlives a placement cancelled mid-request, e.g. by shutdown or a deadline, so
+/// the order can still be looked up before trading on.
+#[derive(Debug, Clone, Default)]
+pub struct InFlightOrders {
+    pending: Arc<Mutex<Vec<PendingOrder>>>,
+}
+
+impl InFlightOrders {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<PendingOrder>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn add(&self, order: PendingOrder) {
+        self.lock().push(order);
+    }
+
+    fn remove(&self, order_link_id: &str) {
+        self.lock().retain(|o| o.order_link_id != order_link_id);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+}
+
+/// Whether a failed placement may still have reached Bybit: anything but Bybit answering
+/// with a retCode, e.g. a timeout or a connection dropped before the response arrived
+pub fn is_ambiguous(error: &anyhow::Error) -> bool {
+    BybitError::find(error).is_none()
+}
+
+/// Place an order so its fate is never unknown. The orderLinkId is recorded before the
+/// request is sent; if the outcome is ambiguous, the order is looked up by it and reported
+/// as placed if Bybit has it.
+pub async fn place_order<E: ExchangeApi + ?Sized>(
+    exchange: &E,
+    request: PlaceOrderRequest,
+    in_flight: &InFlightOrders,
+) -> Result<PlaceOrderResult> {
+    let Some(order_link_id) = request.order_link_id.clone() else {
+        bail!(
+            "Order on {} has no orderLinkId to resolve it by",
+            request.symbol
+        );
+    };
+    let pending = PendingOrder {
+        category: request.category.clone(),
+        symbol: request.symbol.clone(),
+        order_link_id: order_link_id.clone(),
+    };
+    in_flight.add(pending.clone());
+
+    match exchange.place_order(request).await {
+        Ok(result) => {
+            in_flight.remove(&order_link_id);
+            Ok(result)
+        }
+        Err(e) if !is_ambiguous(&e) => {
+            in_flight.remove(&order_link_id);
+            Err(e)
+        }
+        Err(e) => look_up(exchange, pending, e, in_flight).await,
+    }
+}
+
+/// Place orders in one batch request, with the same guarantee as `place_order` for each:
+/// if the whole request's outcome is ambiguous, every order is looked up by its orderLinkId
+pub async fn place_batch<E: ExchangeApi + ?Sized>(
+    exchange: &E,
+    category: &str,
+    requests: Vec<PlaceOrderRequest>,
+    in_flight: &InFlightOrders,
+) -> Result<Vec<Result<PlaceOrderResult>>> {
+    let mut pending = Vec::with_capacity(requests.len());
+    for request in &requests {
+        let Some(order_link_id) = request.order_link_id.clone() else {
+            bail!(
+                "Order on {} has no orderLinkId to resolve it by",
+                request.symbol
+            );
+        };
+        pending.push(PendingOrder {
+            category: category.to_string(),
+            symbol: request.symbol.clone(),
+            order_link_id,
+        });
+    }
+    for order in &pending {
+        in_flight.add(order.clone());
+    }
+
+    match exchange.place_batch_orders(category, requests).await {
+        Ok(results) => {
+            for order in &pending {
+                in_flight.remove(&order.order_link_id);
+            }
+            Ok(results)
+        }
+        Err(e) if !is_ambiguous(&e) => {
+            for order in &pending {
+                in_flight.remove(&order.order_link_id);
+            }
+            Err(e)
+        }
+        Err(e) => {
+            let mut results = Vec::with_capacity(pending.len());
+            for order in pending {
+                results.push(look_up(exchange, order, anyhow!("{e:#}"), in_flight).await);
+            }
+            Ok(results)
+        }
+    }
+}
+
+/// Find out whether an order whose placement failed ambiguously reached Bybit
+async fn look_up<E: ExchangeApi + ?Sized>(
+    exchange: &E,
+    pending: PendingOrder,
+    error: anyhow::Error,
+    in_flight: &InFlightOrders,
+) -> Result<PlaceOrderResult> {
+    let order_link_id = pending.order_link_id.clone();
+    warn!(
+        "⚠️ Outcome of order {order_link_id} on {} unknown ({error:#}) - looking it up",
+        pending.symbol
+    );
+    match exchange
+        .get_order_by_link_id(&pending.category, &order_link_id, &pending.symbol)
+        .await
+    {
+        Ok(Some(order)) => {
+            in_flight.remove(&order_link_id);
+            warn!(
+                "🔎 Order {order_link_id} on {} was placed despite the error: {}",
+                order.symbol, order.order_status
+            );
+            Ok(PlaceOrderResult {
+                order_id: order.order_id,
+                order_link_id: order.order_link_id,
+            })
+        }
+        Ok(None) => {
+            in_flight.remove(&order_link_id);
+            Err(anyhow!(
+                "Order {order_link_id} on {} was not placed: {error:#}",
+                pending.symbol
+            ))
+        }
+        Err(lookup) => Err(anyhow!(
+            "Outcome of order {order_link_id} on {} unknown: {error:#}; looking it up failed: {lookup:#}",
+            pending.symbol
+        )),
+    }
+}
+
+/// Look up the orders whose placement was interrupted. Returns those Bybit has; orders it
+/// doesn't have are forgotten, and orders that can't be looked up yet stay pending.
+pub async fn resolve_pending<E: ExchangeApi + ?Sized>(
+    exchange: &E,
+    in_flight: &InFlightOrders,
+) -> Vec<OrderInfo> {
+    let pending = in_flight.lock().clone();
+    let mut placed = Vec::new();
+    for order in pending {
+        match exchange
+            .get_order_by_link_id(&order.category, &order.order_link_id, &order.symbol)
+            .await
+        {
+            Ok(Some(info)) => {
+                in_flight.remove(&order.order_link_id);
+                error!(
+                    "🚨 Order {} on {} from an interrupted placement exists: {}, filled {}",
+                    order.order_link_id, info.symbol, info.order_status, info.cum_exec_qty
+                );
+                placed.push(info);
+            }
+            Ok(None) => in_flight.remove(&order.order_link_id),
+            Err(e) => warn!(
+                "⚠️ Could not look up interrupted order {}: {e:#}",
+                order.order_link_id
+            ),
+        }
+    }
+    placed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{MockExchange, MockMarket};
+
+    fn market_buy(link: &str) -> PlaceOrderRequest {
+        PlaceOrderRequest {
+            category: "spot".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: "Buy".to_string(),
+            order_type: "Market".to_string(),
+            qty: "100".to_string(),
+            price: None,
+            time_in_force: Some("IOC".to_string()),
+            order_link_id: Some(link.to_string()),
+            reduce_only: None,
+        }
+    }
+
+    fn exchange() -> MockExchange {
+        MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 50_000.0, 50_010.0),
+            )
+            .with_balance("USDT", 1000.0)
+    }
+
+    #[tokio::test]
+    async fn test_ambiguous_placements_are_resolved_by_order_link_id() {
+        let in_flight = InFlightOrders::default();
+
+        // The order went through but the response was lost
+        let lost = exchange().lose_next_order_response("BTCUSDT", "operation timed out");
+        let result = place_order(&lost, market_buy("arb_1"), &in_flight)
+            .await
+            .unwrap();
+        assert_eq!(result.order_link_id, "arb_1");
+
+        // The request never reached Bybit
+        let unreachable = exchange().fail_next_market_order("BTCUSDT", "connection reset");
+        let error = place_order(&unreachable, market_buy("arb_2"), &in_flight)
+            .await
+            .unwrap_err();
+        assert!(format!("{error:#}").contains("was not placed"));
+        assert!(unreachable.orders().is_empty());
+
+        // A rejection is final without a lookup
+        let rejected =
+            exchange().fail_next_market_order("BTCUSDT", "API Error 170131: Insufficient balance");
+        assert!(place_order(&rejected, market_buy("arb_3"), &in_flight)
+            .await
+            .is_err());
+        assert!(in_flight.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_placements_are_looked_up_later() {
+        let exchange = exchange();
+        let in_flight = InFlightOrders::default();
+        exchange.place_order(market_buy("arb_1")).await.unwrap();
+        // Both were recorded, then their placements cancelled; only arb_1 reached Bybit
+        for link in ["arb_1", "arb_2"] {
+            in_flight.add(PendingOrder {
+                category: "spot".to_string(),
+                symbol: "BTCUSDT".to_string(),
+                order_link_id: link.to_string(),
+            });
+        }
+
+        let placed = resolve_pending(&exchange, &in_flight).await;
+        assert_eq!(placed.len(), 1);
+        assert_eq!(placed[0].order_link_id, "arb_1");
+        assert!(in_flight.is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Where parked positions are kept so their exit orders are still managed after a restart
+pub const POSITIONS_FILE: &str = "open_positions.json";
+
+/// A coin parked after leg 3 failed, waiting on a resting limit order to convert it back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenPosition {
+    /// Symbol of the failed leg, where the exit order rests
+    pub symbol: String,
+    /// Side of the exit order
+    pub side: String,
+    /// Coin being held
+    pub coin: String,
+    pub quantity: f64,
+    /// Coin the triangle started from, which the exit order converts back to
+    pub target_coin: String,
+    /// Amount of the target coin the triangle started with; the exit price breaks even on it
+    pub breakeven_amount: f64,
+    pub limit_price: String,
+    pub order_id: String,
+    pub opened_at: DateTime<Utc>,
+}
+
+/// How a parked position ended
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosedPosition {
+    pub position: OpenPosition,
+    /// "filled" at the limit price, or "liquidated" at market
+    pub reason: &'static str,
+    /// Amount of the target coin received
+    pub received: f64,
+}
+
+/// Parked positions with a maximum holding time, after which they are force-liquidated
+#[derive(Debug)]
+pub struct PositionManager {
+    positions: Vec<OpenPosition>,
+    max_hold: Duration,
+    path: Option<PathBuf>,
+}
+
+impl PositionManager {
+    pub fn new(max_hold: Duration) -> Self {
+        Self {
+            positions: Vec::new(),
+            max_hold,
+            path: None,
+        }
+    }
+
+    /// Restore positions parked by a previous run; a missing or unreadable file starts empty
+    pub fn load(max_hold: Duration, path: &Path) -> Self {
+        Self {
+            positions: crate::persist::load_json(path),
+            max_hold,
+            path: Some(path.to_path_buf()),
+        }
+    }
+
+    pub fn positions(&self) -> &[OpenPosition] {
+        &self.positions
+    }
+
+    pub fn open(&mut self, position: OpenPosition) {
+        self.positions.push(position);
+        self.persist();
+    }
+
+    pub fn close(&mut self, order_id: &str) -> Option<OpenPosition> {
+        let index = self.positions.iter().position(|p| p.order_id == order_id)?;
+        let position = self.positions.remove(index);
+        self.persist();
+        Some(position)
+    }
+
+    /// Whether a position has been held past the maximum holding time
+    pub fn is_expired(&self, position: &OpenPosition, now: DateTime<Utc>) -> bool {
+        now - position.opened_at >= self.max_hold
+    }
+
+    fn persist(&self) {
+        if let Err(e) = self.save() {
+            warn!("⚠️ Failed to save open positions: {e:#}");
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        crate::persist::save_json(path, &self.positions)?;
+        debug!(
+            "💾 Saved {} open position(s) to {}",
+            self.positions.len(),
+            path.display()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions_expire_and_survive_restart() {
+        let path = std::env::temp_dir().join(format!("positions_test_{}.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let opened_at = Utc::now();
+        let position = OpenPosition {
+            symbol: "ETHUSDT".to_string(),
+            side: "Sell".to_string(),
+            coin: "ETH".to_string(),
+            quantity: 0.0399,
+            target_coin: "USDT".to_string(),
+            breakeven_amount: 100.0,
+            limit_price: "2508.00".to_string(),
+            order_id: "order-1".to_string(),
+            opened_at,
+        };
+
+        let mut manager = PositionManager::load(Duration::minutes(30), &path);
+        manager.open(position.clone());
+        assert!(!manager.is_expired(&position, opened_at + Duration::minutes(29)));
+        assert!(manager.is_expired(&position, opened_at + Duration::minutes(30)));
+
+        let mut manager = PositionManager::load(Duration::minutes(30), &path);
+        assert_eq!(manager.positions(), std::slice::from_ref(&position));
+        assert_eq!(manager.close("order-1"), Some(position));
+        assert_eq!(manager.close("order-1"), None);
+        assert!(PositionManager::load(Duration::minutes(30), &path)
+            .positions()
+            .is_empty());
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -1,18 +1,61 @@
-use crate::client::BybitClient;
-use crate::models::InstrumentsInfoResult;
+use crate::exchange::ExchangeApi;
+use crate::models::{floor_to_decimals, InstrumentsInfoResult, Notional, OrderQty, Price, Qty};
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::path::Path;
 use tokio::fs;
 use tracing::{debug, info};
 
-#[derive(Debug, Clone)]
+/// Where learned decimals are kept between runs
+pub const CACHE_FILE: &str = "precision_cache.json";
+
+/// Quantity formatting retries the trader may go through (see place_order_with_precision_retry)
+const AUDIT_RETRIES: u32 = 4;
+
+/// A formatted quantity that the exchange would reject or that would overspend the balance
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundingIssue {
+    pub symbol: String,
+    pub balance: f64,
+    pub formatted: String,
+    pub problem: String,
+}
+
+impl fmt::Display for RoundingIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} from balance {} {}",
+            self.symbol, self.formatted, self.balance, self.problem
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RoundingAudit {
+    pub symbols: usize,
+    pub quantities_checked: usize,
+    pub issues: Vec<RoundingIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrecisionInfo {
     pub base_coin: String,
     pub quote_coin: String,
     pub qty_precision: u32,
+    /// Base quantity increment (qtyStep, or basePrecision on spot)
+    pub qty_step: f64,
     pub min_order_qty: f64,
     pub max_order_qty: f64,
+    pub price_precision: u32,
+    /// Price increment for limit orders (0 when unknown)
+    pub tick_size: f64,
+    /// Smallest order value in the quote coin (minNotionalValue, or minOrderAmt on spot;
+    /// 0 when unknown)
+    #[serde(default)]
+    pub min_notional: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -23,6 +66,8 @@ pub struct PrecisionManager {
     coin_precision: HashMap<String, u32>,
     // Cache of working decimal places for each symbol (learned from successful trades)
     working_decimals_cache: HashMap<String, u32>,
+    // File the learned decimals are saved to, if any
+    cache_path: Option<String>,
 }
 
 impl PrecisionManager {
@@ -31,23 +76,47 @@ impl PrecisionManager {
             symbol_precision: HashMap::new(),
             coin_precision: HashMap::new(),
             working_decimals_cache: HashMap::new(),
+            cache_path: Some(CACHE_FILE.to_string()),
         }
     }
 
-    /// Initialize precision data by fetching from Bybit API
-    pub async fn initialize(&mut self, client: &BybitClient) -> Result<()> {
-        info!("🔍 Fetching precision information for all trading pairs...");
+    /// Build from already fetched instruments, without touching the cache file
+    pub fn from_instruments(list: Vec<crate::models::InstrumentInfo>) -> Self {
+        let mut manager = Self {
+            cache_path: None,
+            ..Self::new()
+        };
+        manager
+            .process_instruments_info(InstrumentsInfoResult {
+                category: "spot".to_string(),
+                list,
+                next_page_cursor: None,
+            })
+            .expect("instruments are valid");
+        manager
+    }
+
+    /// Initialize precision data from the exchange's instruments
+    pub async fn initialize<E: ExchangeApi + ?Sized>(&mut self, client: &E) -> Result<()> {
+        info!(
+            "🔍 Fetching precision information for all {} trading pairs...",
+            client.name()
+        );
 
         // Fetch spot instruments info
-        let instruments = client
-            .get_instruments_info("spot", Some(1000))
+        let list = client
+            .get_all_spot_instruments()
             .await
             .context("Failed to fetch instruments info")?;
 
-        self.process_instruments_info(instruments)?;
+        self.process_instruments_info(InstrumentsInfoResult {
+            category: "spot".to_string(),
+            list,
+            next_page_cursor: None,
+        })?;
 
         // Load existing cache if available
-        if let Err(e) = self.load_cache_from_file("precision_cache.json").await {
+        if let Err(e) = self.load_cache_from_file(CACHE_FILE).await {
             debug!("No existing precision cache found or failed to load: {}", e);
         }
 
@@ -66,7 +135,7 @@ impl PrecisionManager {
         if new_entries > 0 {
             info!("♻️  Added {} new symbols to precision cache", new_entries);
             // Save the updated cache immediately to ensure file is up to date
-            self.save_cache_to_file("precision_cache.json").await?;
+            self.auto_save_cache().await?;
         } else {
             info!(
                 "✅ Precision cache is up to date ({} symbols)",
@@ -96,15 +165,20 @@ impl PrecisionManager {
                 .lot_size_filter
                 .as_ref()
                 .and_then(|f| f.qty_step.as_ref());
+            let base_precision_str = instrument
+                .lot_size_filter
+                .as_ref()
+                .and_then(|f| f.base_precision.as_ref());
             let min_qty_str = instrument
                 .lot_size_filter
                 .as_ref()
                 .map(|f| &f.min_order_qty);
 
-            // Try to get precision from qtyStep, fallback to minOrderQty
-            // If both fail, use coin-based heuristics instead of defaulting to 8
+            // Try to get precision from qtyStep (basePrecision on spot), fallback to minOrderQty
+            // If all fail, use coin-based heuristics instead of defaulting to 8
             let mut qty_precision = self
                 .extract_precision_from_step(&qty_step_str)
+                .or_else(|| self.extract_precision_from_step(&base_precision_str))
                 .or_else(|| self.extract_precision_from_step(&min_qty_str))
                 .unwrap_or_else(|| {
                     // Fallback to coin precision heuristics
@@ -118,7 +192,7 @@ impl PrecisionManager {
                 qty_precision = 0;
             }
 
-            let _price_precision = self
+            let price_precision = self
                 .extract_precision_from_step(
                     &instrument
                         .price_filter
@@ -139,14 +213,20 @@ impl PrecisionManager {
                 .map(|f| f.max_order_qty.parse::<f64>().unwrap_or(0.0))
                 .unwrap_or(0.0);
 
-            let _qty_step = instrument
+            let qty_step = qty_step_str
+                .or(base_precision_str)
+                .and_then(|s| s.parse::<f64>().ok())
+                .filter(|step| *step > 0.0)
+                .unwrap_or_else(|| 10f64.powi(-(qty_precision as i32)));
+
+            let min_notional = instrument
                 .lot_size_filter
                 .as_ref()
-                .and_then(|f| f.qty_step.as_ref())
-                .map(|s| s.parse::<f64>().unwrap_or(0.0))
+                .and_then(|f| f.min_notional_value.as_ref().or(f.min_order_amt.as_ref()))
+                .and_then(|s| s.parse::<f64>().ok())
                 .unwrap_or(0.0);
 
-            let _tick_size = instrument
+            let tick_size = instrument
                 .price_filter
                 .as_ref()
                 .and_then(|f| f.tick_size.as_ref())
@@ -157,8 +237,12 @@ impl PrecisionManager {
                 base_coin: instrument.base_coin.clone(),
                 quote_coin: instrument.quote_coin.clone(),
                 qty_precision,
+                qty_step,
                 min_order_qty,
                 max_order_qty,
+                price_precision,
+                tick_size,
+                min_notional,
             };
 
             // debug!(
@@ -233,7 +317,8 @@ impl PrecisionManager {
     }
 
     /// Validate if quantity meets minimum requirements for symbol
-    pub fn validate_quantity(&self, symbol: &str, quantity: f64) -> Result<()> {
+    pub fn validate_quantity(&self, symbol: &str, quantity: Qty) -> Result<()> {
+        let quantity = quantity.value();
         if let Some(precision_info) = self.get_symbol_precision(symbol) {
             if quantity < precision_info.min_order_qty {
                 return Err(anyhow::anyhow!(
@@ -256,27 +341,19 @@ impl PrecisionManager {
         Ok(())
     }
 
-    /// Validate if order value meets minimum requirements for symbol
-    pub fn validate_order_value(&self, symbol: &str, quantity: f64, price: f64) -> Result<()> {
-        let order_value = quantity * price;
-
-        // Common minimum order values by quote currency
-        let min_order_value = if symbol.ends_with("USDT") || symbol.ends_with("USDC") {
-            5.0 // $5 minimum for USDT/USDC pairs
-        } else if symbol.ends_with("BTC") {
-            0.0001 // 0.0001 BTC minimum
-        } else {
-            1.0 // Default $1 minimum
+    /// Check an order value (in the quote coin) against the symbol's minimum notional.
+    /// Symbols without a known minimum pass.
+    pub fn validate_order_value(&self, symbol: &str, order_value: Notional) -> Result<()> {
+        let order_value = order_value.value();
+        let Some(info) = self.get_symbol_precision(symbol) else {
+            return Ok(());
         };
-
-        if order_value < min_order_value {
+        if order_value < info.min_notional {
             return Err(anyhow::anyhow!(
-                "Order value {:.8} is below minimum {:.8} for symbol {} (qty: {:.8}, price: {:.8})",
-                order_value,
-                min_order_value,
-                symbol,
-                quantity,
-                price
+                "Order value {order_value:.8} {quote} is below the {:.8} {quote} minimum for {symbol}: {:.8} {quote} more required",
+                info.min_notional,
+                info.min_notional - order_value,
+                quote = info.quote_coin
             ));
         }
 
@@ -302,14 +379,84 @@ impl PrecisionManager {
         }
     }
 
+    /// Round a limit price onto the symbol's tick grid, up for Sells or down for Buys,
+    /// so the order is never worse than the price asked for
+    pub fn format_price(&self, symbol: &str, price: Price, round_up: bool) -> String {
+        let Some(info) = self
+            .symbol_precision
+            .get(symbol)
+            .filter(|info| info.tick_size > 0.0)
+        else {
+            return format!("{:.8}", price.value());
+        };
+        // Tolerate float noise on prices that are already on the grid
+        let ticks = price.value() / info.tick_size;
+        let ticks = if round_up {
+            (ticks - 1e-9).ceil()
+        } else {
+            (ticks + 1e-9).floor()
+        };
+        format!(
+            "{:.*}",
+            info.price_precision as usize,
+            ticks * info.tick_size
+        )
+    }
+
+    /// Floor a base quantity onto the symbol's qty_step grid, since Bybit rejects anything
+    /// between steps. Unknown symbols are left as they are.
+    pub fn round_to_step(&self, symbol: &str, quantity: Qty) -> Qty {
+        match self
+            .symbol_precision
+            .get(symbol)
+            .filter(|info| info.qty_step > 0.0)
+        {
+            Some(info) => Qty::new(floor_to_grid(quantity.value(), info.qty_step)),
+            None => quantity,
+        }
+    }
+
+    /// Base quantities snapped down onto the qty_step grid after truncating to `decimals`;
+    /// quote amounts only truncated
+    fn floor_quantity(&self, symbol: &str, quantity: OrderQty, decimals: u32) -> f64 {
+        let truncated = floor_to_decimals(quantity.value(), decimals);
+        match quantity {
+            OrderQty::Base(_) => self.round_to_step(symbol, Qty::new(truncated)).value(),
+            OrderQty::Quote(_) => truncated,
+        }
+    }
+
+    /// Round an order quantity down to exactly what the exchange accepts: Sells onto the
+    /// qty_step grid, Buy amounts to the symbol's working decimals
+    pub fn format_order_quantity(&self, symbol: &str, quantity: OrderQty) -> String {
+        let Some(info) = self.symbol_precision.get(symbol) else {
+            return self.format_quantity_with_retry(symbol, quantity, 0);
+        };
+        let cached = self.get_cached_decimals(symbol);
+        let (decimals, value) = match quantity {
+            OrderQty::Base(qty) => {
+                let decimals = cached.map_or(info.qty_precision, |c| c.min(info.qty_precision));
+                (decimals, self.round_to_step(symbol, qty).value())
+            }
+            OrderQty::Quote(notional) => (
+                cached.unwrap_or(info.qty_precision.min(6)),
+                notional.value(),
+            ),
+        };
+        let value = floor_to_grid(value, 10_f64.powi(-(decimals as i32)));
+        format!("{value:.prec$}", prec = decimals as usize)
+    }
+
     /// Format quantity with automatic precision reduction for API compatibility
     /// Starts with 6 decimals max, then reduces based on retry count
     pub fn format_quantity_with_retry(
         &self,
         symbol: &str,
-        quantity: f64,
+        quantity: OrderQty,
         retry_count: u32,
     ) -> String {
+        let order_qty = quantity;
+        let quantity = quantity.value();
         // Aggressive backoff strategy for precision retries
         // 0: 6 decimals (High precision)
         // 1: 4 decimals (Standard crypto)
@@ -339,27 +486,28 @@ impl PrecisionManager {
         if let Some(precision_info) = self.symbol_precision.get(symbol) {
             // Use the smaller of our calculated max_decimals or the symbol's qty_precision
             let actual_decimals = max_decimals.min(precision_info.qty_precision);
-            let factor = 10_f64.powi(actual_decimals as i32);
-            let truncated = (adjusted_quantity * factor).floor() / factor;
+            let truncated = self.floor_quantity(
+                symbol,
+                order_qty.with_value(adjusted_quantity),
+                actual_decimals,
+            );
             let formatted = format!("{:.prec$}", truncated, prec = actual_decimals as usize);
 
             if retry_count > 0 {
                 tracing::info!(
-                    "📏 Precision retry #{} for {}: {} decimals, {:.8} → {} (factor: {})",
+                    "📏 Precision retry #{} for {}: {} decimals, {:.8} → {}",
                     retry_count,
                     symbol,
                     actual_decimals,
                     adjusted_quantity,
-                    formatted,
-                    factor
+                    formatted
                 );
             }
 
             formatted
         } else {
             // Fallback: use max_decimals for unknown symbols
-            let factor = 10_f64.powi(max_decimals as i32);
-            let truncated = (adjusted_quantity * factor).floor() / factor;
+            let truncated = floor_to_decimals(adjusted_quantity, max_decimals);
             let formatted = format!("{:.prec$}", truncated, prec = max_decimals as usize);
 
             if retry_count > 0 {
@@ -377,6 +525,53 @@ impl PrecisionManager {
         }
     }
 
+    /// Check every symbol's formatted quantities against its lot size rules: the string must
+    /// parse back to the quantized value, be a multiple of qty_step and never exceed the balance.
+    /// Balances start at the minimum order size, since smaller sells are rejected anyway.
+    pub fn audit_rounding(&self) -> RoundingAudit {
+        let mut audit = RoundingAudit::default();
+        let mut symbols: Vec<_> = self.symbol_precision.iter().collect();
+        symbols.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (symbol, info) in symbols {
+            audit.symbols += 1;
+            let balances = [
+                info.min_order_qty,
+                info.min_order_qty * 1.5,
+                0.1 + 0.2,
+                1.0 / 3.0,
+                0.999_999_999,
+                2.675,
+                123.456_789_123,
+                99_999.999_999_99,
+            ];
+            for balance in balances
+                .into_iter()
+                .filter(|b| *b > 0.0 && *b >= info.min_order_qty && *b <= info.max_order_qty)
+            {
+                let quantity = OrderQty::Base(Qty::new(balance));
+                let formatted = std::iter::once(self.format_quantity_smart(symbol, quantity))
+                    .chain(
+                        (0..=AUDIT_RETRIES)
+                            .map(|retry| self.format_quantity_with_retry(symbol, quantity, retry)),
+                    );
+                for formatted in formatted {
+                    audit.quantities_checked += 1;
+                    if let Some(problem) = rounding_problem(&formatted, balance, info.qty_step) {
+                        audit.issues.push(RoundingIssue {
+                            symbol: symbol.clone(),
+                            balance,
+                            formatted,
+                            problem,
+                        });
+                    }
+                }
+            }
+        }
+
+        audit
+    }
+
     /// Cache the working decimal places for a symbol after successful trade
     pub fn cache_working_decimals(&mut self, symbol: &str, decimals: u32) {
         info!(
@@ -393,28 +588,31 @@ impl PrecisionManager {
     }
 
     /// Format quantity using cached decimals if available, otherwise use API precision
-    pub fn format_quantity_smart(&self, symbol: &str, quantity: f64) -> String {
+    pub fn format_quantity_smart(&self, symbol: &str, quantity: OrderQty) -> String {
         // First try to use cached working decimals
         if let Some(cached_decimals) = self.get_cached_decimals(symbol) {
             debug!(
                 "🎯 Using cached decimals for {}: {} decimals",
                 symbol, cached_decimals
             );
-            let factor = 10_f64.powi(cached_decimals as i32);
-            let truncated = (quantity * factor).floor() / factor;
+            let truncated = self.floor_quantity(symbol, quantity, cached_decimals);
             return format!("{:.prec$}", truncated, prec = cached_decimals as usize);
         }
 
         // Fallback to regular precision logic
         if let Some(info) = self.symbol_precision.get(symbol) {
-            let adjusted_quantity = quantity.max(info.min_order_qty);
+            // The minimum order quantity is in base units, so it only applies to Sells
+            let adjusted_quantity = match quantity {
+                OrderQty::Base(qty) => qty.value().max(info.min_order_qty),
+                OrderQty::Quote(notional) => notional.value(),
+            };
             let max_decimals = info.qty_precision.min(8);
-            let factor = 10_f64.powi(max_decimals as i32);
-            let truncated = (adjusted_quantity * factor).floor() / factor;
+            let truncated =
+                self.floor_quantity(symbol, quantity.with_value(adjusted_quantity), max_decimals);
             format!("{truncated:.prec$}", prec = max_decimals as usize)
         } else {
             // Ultimate fallback
-            format!("{quantity:.6}")
+            format!("{:.6}", quantity.value())
         }
     }
 
@@ -470,8 +668,180 @@ impl PrecisionManager {
         Ok(())
     }
 
-    /// Auto-save cache periodically or on program exit
+    /// Auto-save cache periodically or on program exit, unless built without a cache file
     pub async fn auto_save_cache(&self) -> Result<()> {
-        self.save_cache_to_file("precision_cache.json").await
+        match &self.cache_path {
+            Some(path) => self.save_cache_to_file(path).await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// Round down onto a grid, tolerating float noise on values already on it
+fn floor_to_grid(value: f64, grid: f64) -> f64 {
+    (value / grid + 1e-9).floor() * grid
+}
+
+fn rounding_problem(formatted: &str, balance: f64, qty_step: f64) -> Option<String> {
+    let Ok(parsed) = formatted.parse::<f64>() else {
+        return Some("does not parse".to_string());
+    };
+    let decimals = formatted.split_once('.').map_or(0, |(_, d)| d.len());
+    if format!("{parsed:.decimals$}") != formatted {
+        return Some(format!("does not round-trip ({parsed})"));
+    }
+    let steps = parsed / qty_step;
+    if (steps - steps.round()).abs() > 1e-6 {
+        return Some(format!("is not a multiple of qty_step {qty_step}"));
+    }
+    if parsed > balance {
+        return Some("rounds up past the balance".to_string());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ApiResponse;
+
+    fn fixture_manager() -> PrecisionManager {
+        let response: ApiResponse<InstrumentsInfoResult> = serde_json::from_str(include_str!(
+            "../tests/fixtures/bybit/rest/instruments_spot.json"
+        ))
+        .unwrap();
+        PrecisionManager::from_instruments(response.into_result().unwrap().list)
+    }
+
+    #[test]
+    fn test_rounding_audit_passes_on_fixture_instruments() {
+        let audit = fixture_manager().audit_rounding();
+
+        assert_eq!(audit.symbols, 2); // OLDUSDT is not trading
+        assert!(audit.quantities_checked > 0);
+        assert!(audit.issues.is_empty(), "{:?}", audit.issues);
+    }
+
+    #[test]
+    fn test_cached_decimals_finer_than_step_still_land_on_it() {
+        let mut manager = fixture_manager();
+        manager.cache_working_decimals("PEPEUSDT", 2);
+
+        let audit = manager.audit_rounding();
+
+        assert!(audit.issues.is_empty(), "{:?}", audit.issues);
+        assert_eq!(
+            manager.format_quantity_smart("PEPEUSDT", OrderQty::Base(Qty::new(123.456))),
+            "123.00"
+        );
+    }
+
+    #[test]
+    fn test_round_to_step_floors_onto_non_decimal_steps() {
+        let mut manager = fixture_manager();
+        manager.symbol_precision.insert(
+            "SOLUSDT".to_string(),
+            PrecisionInfo {
+                base_coin: "SOL".to_string(),
+                quote_coin: "USDT".to_string(),
+                qty_precision: 2,
+                qty_step: 0.05,
+                min_order_qty: 0.05,
+                max_order_qty: 10_000.0,
+                price_precision: 2,
+                tick_size: 0.01,
+                min_notional: 5.0,
+            },
+        );
+
+        assert!((manager.round_to_step("SOLUSDT", Qty::new(1.27)).value() - 1.25).abs() < 1e-12);
+        assert_eq!(
+            manager.round_to_step("XYZUSDT", Qty::new(1.27)),
+            Qty::new(1.27)
+        );
+        let base = OrderQty::Base(Qty::new(1.27));
+        assert_eq!(
+            manager.format_quantity_with_retry("SOLUSDT", base, 0),
+            "1.25"
+        );
+        assert_eq!(manager.format_quantity_smart("SOLUSDT", base), "1.25");
+        assert_eq!(manager.format_order_quantity("SOLUSDT", base), "1.25");
+    }
+
+    #[test]
+    fn test_order_value_checked_against_the_symbols_min_notional() {
+        let manager = fixture_manager();
+        assert_eq!(
+            manager
+                .get_symbol_precision("BTCUSDT")
+                .unwrap()
+                .min_notional,
+            1.0
+        );
+
+        assert!(manager
+            .validate_order_value("BTCUSDT", Notional::new(1.5))
+            .is_ok());
+        let error = manager
+            .validate_order_value("BTCUSDT", Notional::new(0.75))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("0.25000000 USDT more required"), "{error}");
+        assert!(manager
+            .validate_order_value("XYZUSDT", Notional::new(0.01))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_format_price_rounds_onto_tick_grid() {
+        let manager = fixture_manager();
+        assert_eq!(
+            manager.format_price("BTCUSDT", Price::new(50_000.123), true),
+            "50000.13"
+        );
+        assert_eq!(
+            manager.format_price("BTCUSDT", Price::new(50_000.129), false),
+            "50000.12"
+        );
+        assert_eq!(
+            manager.format_price("BTCUSDT", Price::new(50_000.1), true),
+            "50000.10"
+        );
+        assert_eq!(
+            manager.format_price("PEPEUSDT", Price::new(0.0000123456789), true),
+            "0.00001235"
+        );
+    }
+
+    #[test]
+    fn test_format_order_quantity_rounds_down_to_lot() {
+        let manager = fixture_manager();
+        assert_eq!(
+            manager.format_order_quantity("PEPEUSDT", OrderQty::Base(Qty::new(123_456.99))),
+            "123456"
+        );
+        // Float noise on a value already on the grid must not drop a step
+        assert_eq!(
+            manager.format_order_quantity("BTCUSDT", OrderQty::Base(Qty::new(0.1 + 0.2))),
+            "0.300000"
+        );
+        assert_eq!(
+            manager.format_order_quantity("BTCUSDT", OrderQty::Quote(Notional::new(99.9999999))),
+            "99.999999"
+        );
+    }
+
+    #[test]
+    fn test_rounding_problem_checks() {
+        assert_eq!(rounding_problem("1.50", 2.0, 0.01), None);
+        assert!(rounding_problem("1.5000001", 2.0, 0.01)
+            .unwrap()
+            .contains("qty_step"));
+        assert!(rounding_problem("2.01", 2.0, 0.01)
+            .unwrap()
+            .contains("past the balance"));
+        assert!(rounding_problem("1.5e3", 2000.0, 1.0)
+            .unwrap()
+            .contains("round-trip"));
     }
 }
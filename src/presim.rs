@@ -0,0 +1,274 @@
+use crate::fees::FeeSchedule;
+use crate::maker::ExecutionStrategy;
+use crate::models::{ArbitrageOpportunity, OrderQty, Price, Qty};
+use crate::precision::PrecisionManager;
+use anyhow::{anyhow, Context, Result};
+
+/// One leg of a triangle exactly as it will be sent to the exchange
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedLeg {
+    pub symbol: String,
+    pub side: String,
+    /// Rounded order size, in the coin being spent (quote for Buys)
+    pub quantity: OrderQty,
+    /// Quantity string placed on the exchange
+    pub formatted: String,
+    /// Coin received after rounding and fees
+    pub received: f64,
+}
+
+/// A triangle walked through the exchange's lot, tick and minimum-notional rules
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simulation {
+    pub legs: Vec<SimulatedLeg>,
+    /// Start coin back at the end of leg 3
+    pub final_amount: f64,
+    /// Profit after rounding and fees
+    pub profit_pct: f64,
+    /// Profit at the same prices and fees without any rounding
+    pub unrounded_profit_pct: f64,
+}
+
+impl Simulation {
+    /// Percentage points of profit lost to rounding
+    pub fn rounding_cost_pct(&self) -> f64 {
+        self.unrounded_profit_pct - self.profit_pct
+    }
+}
+
+/// Size every leg of an opportunity the way the exchange will fill it, starting from `amount`
+/// of the start coin. Leg 1 pays the maker fee when `strategy` rests it; its quoted price is
+/// then the joined bid/ask. Fails if a leg is unknown or falls below a minimum order size
+/// or value.
+pub fn simulate(
+    precision: &PrecisionManager,
+    opportunity: &ArbitrageOpportunity,
+    amount: f64,
+    fees: &FeeSchedule,
+    strategy: ExecutionStrategy,
+) -> Result<Simulation> {
+    let mut legs = Vec::with_capacity(opportunity.pairs.len());
+    let mut held = amount;
+    let mut unrounded = amount;
+
+    for (i, symbol) in opportunity.pairs.iter().enumerate() {
+        let (from, to) = (&opportunity.path[i], &opportunity.path[i + 1]);
+        let info = precision
+            .get_symbol_precision(symbol)
+            .ok_or_else(|| anyhow!("{symbol} has no precision data"))?;
+        let side = if info.base_coin == *from && info.quote_coin == *to {
+            "Sell"
+        } else if info.base_coin == *to && info.quote_coin == *from {
+            "Buy"
+        } else {
+            return Err(anyhow!("{symbol} does not convert {from} → {to}"));
+        };
+        let quoted = *opportunity
+            .prices
+            .get(i)
+            .ok_or_else(|| anyhow!("No price for leg {} on {symbol}", i + 1))?;
+        // On the tick grid and never better than quoted
+        let price = Price::new(
+            precision
+                .format_price(symbol, quoted, side == "Buy")
+                .parse()
+                .unwrap_or(quoted.value()),
+        );
+
+        let formatted = precision.format_order_quantity(symbol, OrderQty::for_side(side, held));
+        let quantity = OrderQty::for_side(side, formatted.parse()?);
+        if let OrderQty::Base(qty) = quantity {
+            precision
+                .validate_quantity(symbol, qty)
+                .with_context(|| format!("leg {} on {symbol}", i + 1))?;
+        }
+        precision
+            .validate_order_value(symbol, quantity.notional_at(price))
+            .with_context(|| format!("leg {} on {symbol}", i + 1))?;
+
+        let gross = match quantity {
+            OrderQty::Base(qty) => (qty * price).value(),
+            // Market Buys fill whole base steps
+            OrderQty::Quote(notional) => precision
+                .format_order_quantity(symbol, OrderQty::Base(notional / price))
+                .parse()?,
+        };
+        let fee_rate = if i == 0 && strategy.maker_leg1() {
+            fees.maker(symbol)
+        } else {
+            fees.taker(symbol)
+        };
+        held = gross * (1.0 - fee_rate);
+        unrounded = match side {
+            "Sell" => (Qty::new(unrounded) * quoted).value(),
+            _ => unrounded / quoted.value(),
+        } * (1.0 - fee_rate);
+
+        legs.push(SimulatedLeg {
+            symbol: symbol.clone(),
+            side: side.to_string(),
+            quantity,
+            formatted,
+            received: held,
+        });
+    }
+
+    Ok(Simulation {
+        legs,
+        final_amount: held,
+        profit_pct: (held - amount) / amount * 100.0,
+        unrounded_profit_pct: (unrounded - amount) / amount * 100.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{ExchangeApi, MockExchange, MockMarket};
+    use crate::models::FeeRateInfo;
+
+    async fn precision(exchange: MockExchange) -> PrecisionManager {
+        PrecisionManager::from_instruments(exchange.get_all_spot_instruments().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_legs_are_sized_exactly_as_sent() {
+        let precision = precision(
+            MockExchange::new()
+                .with_market(
+                    "BTCUSDT",
+                    MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+                )
+                .with_market(
+                    "ETHBTC",
+                    MockMarket::new("ETH", "BTC", 0.0499, 0.05).with_base_decimals(5),
+                )
+                .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_560.0)),
+        )
+        .await;
+        let triangle = ArbitrageOpportunity::test()
+            .with_path(&["USDT", "BTC", "ETH", "USDT"])
+            .with_pairs(&["BTCUSDT", "ETHBTC", "ETHUSDT"])
+            .with_prices(&[50_000.0, 0.05, 2_550.0]);
+
+        let sim = simulate(
+            &precision,
+            &triangle,
+            100.0,
+            &FeeSchedule::flat(0.001),
+            ExecutionStrategy::Taker,
+        )
+        .unwrap();
+
+        let sent: Vec<_> = sim
+            .legs
+            .iter()
+            .map(|leg| (leg.side.as_str(), leg.formatted.as_str()))
+            .collect();
+        assert_eq!(
+            sent,
+            [
+                ("Buy", "100.000000"),
+                ("Buy", "0.00199"),
+                ("Sell", "0.039760")
+            ]
+        );
+        // Each leg spends no more than the previous one delivered
+        for pair in sim.legs.windows(2) {
+            assert!(pair[1].quantity.value() <= pair[0].received);
+        }
+        assert!(sim.profit_pct > 0.0);
+        assert!(sim.rounding_cost_pct() > 0.0 && sim.rounding_cost_pct() < 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_maker_leg1_pays_the_maker_fee() {
+        let precision = precision(
+            MockExchange::new()
+                .with_market(
+                    "BTCUSDT",
+                    MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+                )
+                .with_market("ETHBTC", MockMarket::new("ETH", "BTC", 0.0499, 0.05))
+                .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_560.0)),
+        )
+        .await;
+        let fees = FeeSchedule::flat(0.001).with_rates(&[FeeRateInfo {
+            symbol: "BTCUSDT".to_string(),
+            taker_fee_rate: "0.001".to_string(),
+            maker_fee_rate: "0".to_string(),
+        }]);
+        let triangle = ArbitrageOpportunity::test()
+            .with_path(&["USDT", "BTC", "ETH", "USDT"])
+            .with_pairs(&["BTCUSDT", "ETHBTC", "ETHUSDT"])
+            .with_prices(&[49_990.0, 0.05, 2_550.0]);
+
+        let taker = simulate(
+            &precision,
+            &triangle,
+            100.0,
+            &fees,
+            ExecutionStrategy::Taker,
+        )
+        .unwrap();
+        let maker = simulate(
+            &precision,
+            &triangle,
+            100.0,
+            &fees,
+            ExecutionStrategy::MakerFirst,
+        )
+        .unwrap();
+        assert!((maker.legs[0].received * 0.999 - taker.legs[0].received).abs() < 1e-12);
+        assert!(
+            (maker.unrounded_profit_pct - taker.unrounded_profit_pct - 0.1).abs() < 0.01,
+            "{} vs {}",
+            maker.unrounded_profit_pct,
+            taker.unrounded_profit_pct
+        );
+    }
+
+    #[tokio::test]
+    async fn test_coarse_lot_size_eats_the_edge() {
+        // Whole-coin lots of a $3 coin: $10 buys 3, stranding a tenth of the trade
+        let precision = precision(
+            MockExchange::new()
+                .with_market(
+                    "XUSDT",
+                    MockMarket::new("X", "USDT", 2.99, 3.0).with_base_decimals(0),
+                )
+                .with_market("XUSDC", MockMarket::new("X", "USDC", 3.1, 3.11))
+                .with_market("USDCUSDT", MockMarket::new("USDC", "USDT", 1.0, 1.0001)),
+        )
+        .await;
+        let triangle = ArbitrageOpportunity::test()
+            .with_path(&["USDT", "X", "USDC", "USDT"])
+            .with_pairs(&["XUSDT", "XUSDC", "USDCUSDT"])
+            .with_prices(&[3.0, 3.1, 1.0]);
+
+        let sim = simulate(
+            &precision,
+            &triangle,
+            10.0,
+            &FeeSchedule::flat(0.001),
+            ExecutionStrategy::Taker,
+        )
+        .unwrap();
+        assert!(sim.unrounded_profit_pct > 2.5);
+        assert!(sim.profit_pct < 0.0);
+
+        // Below the market's $1 minimum order value the leg can't be sent at all
+        let err = simulate(
+            &precision,
+            &triangle,
+            0.5,
+            &FeeSchedule::flat(0.001),
+            ExecutionStrategy::Taker,
+        )
+        .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("below the 1.00000000 USDT minimum for XUSDT"),
+            "{err:#}"
+        );
+    }
+}
@@ -0,0 +1,42 @@
+use crate::client::BybitClient;
+use crate::supervisor;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info, warn};
+
+/// Open `connections` pooled connections to the active endpoint now, then keep them from
+/// going idle by re-sending a server-time request every `interval_secs` on a supervised task.
+/// The first order of an arbitrage then reuses a hot TLS connection instead of paying for
+/// a handshake. Does nothing when `interval_secs` is 0.
+pub async fn start_prewarming(
+    client: &BybitClient,
+    interval_secs: u64,
+    connections: usize,
+    restart_on_panic: bool,
+) {
+    if interval_secs == 0 {
+        return;
+    }
+    match client.warm_connections(connections).await {
+        Ok(()) => info!(
+            "🔥 Keeping {connections} connection(s) to {} warm for orders (every {interval_secs}s)",
+            client.endpoints().active().name
+        ),
+        Err(e) => warn!("⚠️ Failed to pre-warm order connections: {e:#}"),
+    }
+    let client = client.clone();
+    supervisor::spawn_supervised(
+        "Connection pre-warmer".to_string(),
+        restart_on_panic,
+        move || {
+            let client = client.clone();
+            async move {
+                loop {
+                    sleep(Duration::from_secs(interval_secs)).await;
+                    if let Err(e) = client.warm_connections(connections).await {
+                        debug!("🔥 Pre-warm request failed: {e:#}");
+                    }
+                }
+            }
+        },
+    );
+}
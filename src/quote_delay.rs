@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Weight of the newest quote in a symbol's average delay
+const SMOOTHING: f64 = 0.05;
+/// Quotes a symbol needs before its average delay counts
+const MIN_SAMPLES: u64 = 20;
+/// Delay beyond the median that is still jitter rather than a lagging symbol
+const MIN_EXCESS_MS: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy)]
+struct Delay {
+    average_ms: f64,
+    samples: u64,
+}
+
+/// Delay between Bybit stamping each quote and it arriving here, per symbol. The local
+/// clock's offset from Bybit's and the base network latency hit every symbol alike, so only
+/// a symbol's delay beyond the median symbol's counts as systematic, and discounts the
+/// edges through it. Shared by the connections and the main loop.
+#[derive(Debug)]
+pub struct QuoteDelays {
+    discount_pct_per_100ms: f64,
+    delays: Mutex<HashMap<String, Delay>>,
+}
+
+impl QuoteDelays {
+    pub fn new(discount_pct_per_100ms: f64) -> Arc<Self> {
+        Arc::new(Self {
+            discount_pct_per_100ms,
+            delays: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Note a quote of `symbol` stamped by Bybit at `exchange_ms` and received at
+    /// `received_ms`, both in Unix milliseconds
+    pub fn record(&self, symbol: &str, exchange_ms: i64, received_ms: i64) {
+        let delay_ms = (received_ms - exchange_ms) as f64;
+        let mut delays = self.delays.lock().unwrap_or_else(|e| e.into_inner());
+        match delays.get_mut(symbol) {
+            Some(delay) => {
+                delay.average_ms += SMOOTHING * (delay_ms - delay.average_ms);
+                delay.samples += 1;
+            }
+            None => {
+                delays.insert(
+                    symbol.to_string(),
+                    Delay {
+                        average_ms: delay_ms,
+                        samples: 1,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Percentage points to knock off the edge of triangles through each systematically
+    /// delayed symbol
+    pub fn discounts(&self) -> HashMap<String, f64> {
+        let delays = self.delays.lock().unwrap_or_else(|e| e.into_inner());
+        let mut averages: Vec<f64> = delays
+            .values()
+            .filter(|d| d.samples >= MIN_SAMPLES)
+            .map(|d| d.average_ms)
+            .collect();
+        if averages.is_empty() {
+            return HashMap::new();
+        }
+        averages.sort_by(f64::total_cmp);
+        let median = averages[averages.len() / 2];
+        delays
+            .iter()
+            .filter(|(_, d)| d.samples >= MIN_SAMPLES)
+            .filter_map(|(symbol, d)| {
+                let excess_ms = d.average_ms - median;
+                (excess_ms > MIN_EXCESS_MS).then(|| {
+                    (
+                        symbol.clone(),
+                        excess_ms / 100.0 * self.discount_pct_per_100ms,
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_symbols_lagging_the_median_are_discounted() {
+        let delays = QuoteDelays::new(0.01);
+        // The local clock runs 2s behind Bybit's, which every symbol sees alike
+        for i in 0..MIN_SAMPLES as i64 {
+            let stamped = 1_700_000_000_000 + i * 1000;
+            delays.record("BTCUSDT", stamped, stamped - 2000 + 20);
+            delays.record("ETHUSDT", stamped, stamped - 2000 + 25);
+            delays.record("ETHBTC", stamped, stamped - 2000 + 25);
+            delays.record("PEPEBTC", stamped, stamped - 2000 + 525);
+        }
+        delays.record("NEWUSDT", 1_700_000_000_000, 1_700_000_005_000);
+
+        let discounts = delays.discounts();
+        assert_eq!(discounts.len(), 1, "{discounts:?}");
+        // 500ms behind the median symbol
+        assert!((discounts["PEPEBTC"] - 0.05).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,611 @@
+use crate::exchange::ExchangeApi;
+use crate::instance;
+use crate::journal::{Journal, JournalEvent, JournalLeg, JournalRecord};
+use crate::models::{ExecutionRecord, MarketPair};
+use crate::stable::STABLECOINS;
+use crate::supervisor;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, warn};
+
+/// Fills younger than this may not be journaled yet, so they wait for the next run
+const SETTLE_DELAY_SECS: i64 = 30;
+/// Exchange fills are read from this long before the window, for legs journaled after they filled
+const LOOKBACK_SECS: i64 = 600;
+
+/// A fill the journal and the exchange disagree on
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mismatch {
+    /// The exchange filled more of this bot's order than the journal accounts for
+    UnknownFill {
+        order_link_id: String,
+        symbol: String,
+        qty: f64,
+    },
+    /// A journaled fill the exchange executed less of, or not at all
+    MissingFill {
+        order_link_id: String,
+        symbol: String,
+        journaled_qty: f64,
+        executed_qty: f64,
+    },
+    /// Fees in a coin charged differently than journaled
+    FeeDiscrepancy {
+        order_link_id: String,
+        symbol: String,
+        coin: String,
+        journaled: f64,
+        charged: f64,
+    },
+}
+
+impl Mismatch {
+    /// Every `kind`, for metrics
+    pub const KINDS: [&'static str; 3] = ["unknown_fill", "missing_fill", "fee_discrepancy"];
+
+    /// Label for metrics
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Mismatch::UnknownFill { .. } => "unknown_fill",
+            Mismatch::MissingFill { .. } => "missing_fill",
+            Mismatch::FeeDiscrepancy { .. } => "fee_discrepancy",
+        }
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::UnknownFill {
+                order_link_id,
+                symbol,
+                qty,
+            } => write!(f, "unknown fill of {qty} {symbol} ({order_link_id})"),
+            Mismatch::MissingFill {
+                order_link_id,
+                symbol,
+                journaled_qty,
+                executed_qty,
+            } => write!(
+                f,
+                "missing fill on {symbol} ({order_link_id}): journaled {journaled_qty}, executed {executed_qty}"
+            ),
+            Mismatch::FeeDiscrepancy {
+                order_link_id,
+                symbol,
+                coin,
+                journaled,
+                charged,
+            } => write!(
+                f,
+                "fee discrepancy on {symbol} ({order_link_id}): journaled {journaled} {coin}, charged {charged} {coin}"
+            ),
+        }
+    }
+}
+
+/// Outcome of one reconciliation run
+#[derive(Debug, Clone, Default)]
+pub struct ReconcileReport {
+    pub legs_checked: usize,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl ReconcileReport {
+    /// Profit the journal misstated through fees, in USD: positive when it overstated them.
+    /// Fees in coins that can't be priced are left out.
+    pub fn pnl_adjustment_usd<'a>(&self, get_pair: impl Fn(&str) -> Option<&'a MarketPair>) -> f64 {
+        self.mismatches
+            .iter()
+            .filter_map(|mismatch| match mismatch {
+                Mismatch::FeeDiscrepancy {
+                    coin,
+                    journaled,
+                    charged,
+                    ..
+                } => match usd_price(coin, &get_pair) {
+                    Some(price) => Some((journaled - charged) * price),
+                    None => {
+                        warn!("⚠️ Could not price {coin} to correct the profit for a fee discrepancy");
+                        None
+                    }
+                },
+                _ => None,
+            })
+            .sum()
+    }
+}
+
+/// USD value of one `coin`, from the mid of its USDT pair
+fn usd_price<'a>(coin: &str, get_pair: &impl Fn(&str) -> Option<&'a MarketPair>) -> Option<f64> {
+    if STABLECOINS.contains(&coin) {
+        return Some(1.0);
+    }
+    let mid = |pair: &MarketPair| (pair.bid_price + pair.ask_price) / 2.0;
+    get_pair(&format!("{coin}USDT"))
+        .map(mid)
+        .or_else(|| get_pair(&format!("USDT{coin}")).map(|p| 1.0 / mid(p)))
+        .filter(|price| price.is_finite() && *price > 0.0)
+}
+
+fn differs(a: f64, b: f64) -> bool {
+    (a - b).abs() > 1e-12 + 1e-6 * a.abs().max(b.abs())
+}
+
+/// Match journaled legs to the exchange's executions by orderLinkId.
+/// `legs` are those journaled in the window, `known` every orderLinkId and order id the
+/// journal accounts for, and `executions` the exchange's fills from before the window on.
+/// Only fills in `[start, end)` tagged with `prefix` can be unknown.
+pub fn reconcile(
+    legs: &[JournalLeg],
+    known: &HashSet<String>,
+    executions: &[ExecutionRecord],
+    prefix: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<Mismatch> {
+    let mut by_link: HashMap<&str, Vec<&ExecutionRecord>> = HashMap::new();
+    for execution in executions {
+        by_link
+            .entry(execution.order_link_id.as_str())
+            .or_default()
+            .push(execution);
+    }
+    let qty = |fills: &[&ExecutionRecord]| -> f64 {
+        fills
+            .iter()
+            .map(|e| e.exec_qty.parse::<f64>().unwrap_or(0.0))
+            .sum()
+    };
+
+    let mut mismatches = Vec::new();
+    for leg in legs {
+        let fills = by_link
+            .get(leg.order_link_id.as_str())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let executed_qty = qty(fills);
+        if differs(executed_qty, leg.qty) && executed_qty < leg.qty {
+            mismatches.push(Mismatch::MissingFill {
+                order_link_id: leg.order_link_id.clone(),
+                symbol: leg.symbol.clone(),
+                journaled_qty: leg.qty,
+                executed_qty,
+            });
+        } else if differs(executed_qty, leg.qty) {
+            mismatches.push(Mismatch::UnknownFill {
+                order_link_id: leg.order_link_id.clone(),
+                symbol: leg.symbol.clone(),
+                qty: executed_qty - leg.qty,
+            });
+        }
+        if fills.is_empty() {
+            continue;
+        }
+
+        let mut charged: BTreeMap<&str, f64> = BTreeMap::new();
+        for fill in fills {
+            *charged.entry(fill.fee_currency.as_str()).or_insert(0.0) +=
+                fill.exec_fee.parse::<f64>().unwrap_or(0.0);
+        }
+        let coins: BTreeSet<&str> = charged
+            .keys()
+            .copied()
+            .chain(leg.fees.keys().map(String::as_str))
+            .collect();
+        for coin in coins {
+            let journaled = leg.fees.get(coin).copied().unwrap_or(0.0);
+            let charged = charged.get(coin).copied().unwrap_or(0.0);
+            if differs(journaled, charged) {
+                mismatches.push(Mismatch::FeeDiscrepancy {
+                    order_link_id: leg.order_link_id.clone(),
+                    symbol: leg.symbol.clone(),
+                    coin: coin.to_string(),
+                    journaled,
+                    charged,
+                });
+            }
+        }
+    }
+
+    let in_window = |e: &&ExecutionRecord| {
+        let at = e.exec_time.parse::<i64>().unwrap_or(0);
+        at >= start.timestamp_millis() && at < end.timestamp_millis()
+    };
+    let mut unknown: Vec<_> = by_link
+        .iter()
+        .filter(|(link, fills)| {
+            instance::is_tagged(link, prefix)
+                && !known.contains(**link)
+                && !fills.iter().any(|f| known.contains(&f.order_id))
+        })
+        .filter_map(|(link, fills)| {
+            let fills: Vec<&ExecutionRecord> = fills.iter().copied().filter(in_window).collect();
+            (!fills.is_empty()).then(|| Mismatch::UnknownFill {
+                order_link_id: link.to_string(),
+                symbol: fills[0].symbol.clone(),
+                qty: qty(&fills),
+            })
+        })
+        .collect();
+    unknown.sort_by_key(ToString::to_string);
+    mismatches.extend(unknown);
+    mismatches
+}
+
+/// Legs journaled in `[start, end)`, and every orderLinkId and order id the journal knows
+fn journaled_legs(
+    path: &Path,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<(Vec<JournalLeg>, HashSet<String>)> {
+    let mut window = Vec::new();
+    let mut known = HashSet::new();
+    for record in Journal::read_records(path)? {
+        let legs = match record.event {
+            JournalEvent::LiveTrade { legs, .. }
+            | JournalEvent::StableConversion { legs, .. }
+            | JournalEvent::DustSwept { legs, .. }
+            | JournalEvent::InterruptedTradeResolved { legs, .. }
+            | JournalEvent::PositionClosed { legs, .. } => legs,
+            // A parked exit fills whenever the market gets there
+            JournalEvent::PositionParked { order_id, .. } => {
+                known.insert(order_id);
+                continue;
+            }
+            _ => continue,
+        };
+        for leg in legs {
+            known.insert(leg.order_link_id.clone());
+            known.insert(leg.order_id.clone());
+            if record.ts >= start && record.ts < end {
+                window.push(leg);
+            }
+        }
+    }
+    Ok((window, known))
+}
+
+/// Order intents of the previous session that its journal never accounted for, when that
+/// session died instead of ending: orders that may have left just before the process did.
+/// `records` already include the current session's start.
+fn dangling_intents(records: &[JournalRecord]) -> Vec<&JournalRecord> {
+    let starts: Vec<usize> = records
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| matches!(r.event, JournalEvent::SessionStarted { .. }))
+        .map(|(i, _)| i)
+        .collect();
+    let [.., previous, current] = starts[..] else {
+        return Vec::new();
+    };
+    let session = &records[previous..current];
+    if session
+        .iter()
+        .any(|r| matches!(r.event, JournalEvent::SessionEnded { .. }))
+    {
+        return Vec::new();
+    }
+    let mut known = HashSet::new();
+    for record in session {
+        if let JournalEvent::LiveTrade { legs, .. }
+        | JournalEvent::StableConversion { legs, .. }
+        | JournalEvent::DustSwept { legs, .. }
+        | JournalEvent::InterruptedTradeResolved { legs, .. }
+        | JournalEvent::PositionClosed { legs, .. } = &record.event
+        {
+            known.extend(legs.iter().map(|leg| leg.order_link_id.as_str()));
+        }
+    }
+    session
+        .iter()
+        .filter(|r| match &r.event {
+            JournalEvent::OrderIntent { order_link_id, .. } => {
+                !known.contains(order_link_id.as_str())
+            }
+            _ => false,
+        })
+        .collect()
+}
+
+/// Check the order intents a session that died left unaccounted for against the
+/// exchange's executions. Any that filled are reported as unknown fills.
+pub async fn recover_intents<E: ExchangeApi + ?Sized>(
+    exchange: &E,
+    path: &Path,
+) -> Result<ReconcileReport> {
+    let records = Journal::read_records(path)?;
+    let intents = dangling_intents(&records);
+    let Some(since) = intents.iter().map(|r| r.ts).min() else {
+        return Ok(ReconcileReport::default());
+    };
+    let executions = exchange
+        .get_executions_since("spot", since - chrono::Duration::seconds(LOOKBACK_SECS))
+        .await?;
+    let mut mismatches = Vec::new();
+    for intent in &intents {
+        let JournalEvent::OrderIntent {
+            order_link_id,
+            symbol,
+            ..
+        } = &intent.event
+        else {
+            continue;
+        };
+        let qty: f64 = executions
+            .iter()
+            .filter(|e| e.order_link_id == *order_link_id)
+            .map(|e| e.exec_qty.parse::<f64>().unwrap_or(0.0))
+            .sum();
+        if qty > 0.0 {
+            mismatches.push(Mismatch::UnknownFill {
+                order_link_id: order_link_id.clone(),
+                symbol: symbol.clone(),
+                qty,
+            });
+        }
+    }
+    Ok(ReconcileReport {
+        legs_checked: intents.len(),
+        mismatches,
+    })
+}
+
+/// Reconcile the journal at `path` against the exchange for legs journaled in `[start, end)`
+pub async fn run_once<E: ExchangeApi + ?Sized>(
+    exchange: &E,
+    path: &Path,
+    prefix: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<ReconcileReport> {
+    let (legs, known) = journaled_legs(path, start, end)?;
+    let executions = exchange
+        .get_executions_since("spot", start - chrono::Duration::seconds(LOOKBACK_SECS))
+        .await?;
+    Ok(ReconcileReport {
+        legs_checked: legs.len(),
+        mismatches: reconcile(&legs, &known, &executions, prefix, start, end),
+    })
+}
+
+/// Reconcile the journal with the exchange's execution history every `interval_secs` on a
+/// supervised task, from now on. Reports arrive on the returned receiver.
+pub fn spawn(
+    client: Arc<dyn ExchangeApi>,
+    journal_path: PathBuf,
+    prefix: String,
+    interval_secs: u64,
+    restart_on_panic: bool,
+) -> mpsc::UnboundedReceiver<ReconcileReport> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    // Survives restarts of the task, so no window is checked twice or skipped
+    let checked_until = Arc::new(Mutex::new(Utc::now()));
+    supervisor::spawn_supervised("Reconciliation".to_string(), restart_on_panic, move || {
+        let (client, path, prefix, tx, checked_until) = (
+            client.clone(),
+            journal_path.clone(),
+            prefix.clone(),
+            tx.clone(),
+            checked_until.clone(),
+        );
+        async move {
+            loop {
+                sleep(Duration::from_secs(interval_secs)).await;
+                let start = *checked_until.lock().unwrap_or_else(|e| e.into_inner());
+                let end = Utc::now() - chrono::Duration::seconds(SETTLE_DELAY_SECS);
+                if end <= start {
+                    continue;
+                }
+                match run_once(&*client, &path, &prefix, start, end).await {
+                    Ok(report) => {
+                        debug!(
+                            "🧾 Reconciled {} journaled fills: {} mismatches",
+                            report.legs_checked,
+                            report.mismatches.len()
+                        );
+                        *checked_until.lock().unwrap_or_else(|e| e.into_inner()) = end;
+                        if tx.send(report).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => warn!("⚠️ Reconciliation failed, retrying next run: {e:#}"),
+                }
+            }
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bundle::OrderLog;
+    use crate::exchange::{MockExchange, MockMarket};
+    use crate::models::PlaceOrderRequest;
+
+    /// Market Buy 0.001 BTC tagged `link`, returning the filled order
+    async fn buy(exchange: &MockExchange, link: &str) -> crate::models::OrderInfo {
+        let request = PlaceOrderRequest {
+            category: "spot".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: "Buy".to_string(),
+            order_type: "Market".to_string(),
+            qty: "0.001".to_string(),
+            price: None,
+            time_in_force: Some("IOC".to_string()),
+            order_link_id: Some(link.to_string()),
+            reduce_only: None,
+        };
+        let placed = exchange.place_order(request).await.unwrap();
+        exchange
+            .get_order("spot", &placed.order_id, "BTCUSDT")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fills_are_matched_to_journaled_legs() {
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_fee_coin("MNT")
+            .with_balance("USDT", 1_000.0)
+            .with_balance("MNT", 10.0);
+        let start = Utc::now() - chrono::Duration::seconds(1);
+        let matched = buy(&exchange, "arb_1_1").await;
+        let misfeed = buy(&exchange, "arb_2_1").await;
+        let unknown = buy(&exchange, "arb_3_1").await;
+        buy(&exchange, "manual-order").await;
+
+        let fee: f64 = matched.cum_exec_fee.parse().unwrap();
+        let fees = BTreeMap::from([
+            (
+                matched.order_id.clone(),
+                BTreeMap::from([("MNT".to_string(), fee)]),
+            ),
+            // Accounted in the received coin, though charged in MNT
+            (
+                misfeed.order_id.clone(),
+                BTreeMap::from([("BTC".to_string(), fee)]),
+            ),
+        ]);
+        let mut legs = JournalLeg::from_orders(&OrderLog {
+            placed: Vec::new(),
+            fills: vec![matched, misfeed],
+            fees,
+        });
+        legs.push(JournalLeg {
+            order_id: "lost".to_string(),
+            order_link_id: "arb_4_1".to_string(),
+            ..legs[0].clone()
+        });
+
+        let path =
+            std::env::temp_dir().join(format!("reconcile_test_{}.jsonl", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        Journal::open(&path).unwrap().record(
+            JournalEvent::PositionClosed {
+                symbol: "BTCUSDT".to_string(),
+                coin: "BTC".to_string(),
+                reason: "liquidated".to_string(),
+                target_coin: "USDT".to_string(),
+                breakeven_amount: 0.0,
+                received: 0.0,
+                legs: Vec::new(),
+            }
+            .with_legs(legs),
+        );
+        let end = Utc::now() + chrono::Duration::seconds(1);
+        let report = run_once(&exchange, &path, "arb", start, end).await.unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(report.legs_checked, 3);
+        let kinds: Vec<_> = report.mismatches.iter().map(Mismatch::kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                "fee_discrepancy",
+                "fee_discrepancy",
+                "missing_fill",
+                "unknown_fill"
+            ]
+        );
+        assert!(report.mismatches[3]
+            .to_string()
+            .contains(&unknown.order_link_id));
+
+        // The BTC fee never left the received coin, the MNT fee was real
+        let pairs: HashMap<String, MarketPair> = [
+            MarketPair::test("BTC", "USDT", 49_990.0, 50_000.0),
+            MarketPair::test("MNT", "USDT", 0.79, 0.81),
+        ]
+        .into_iter()
+        .map(|p| (p.symbol.clone(), p))
+        .collect();
+        let adjustment = report.pnl_adjustment_usd(|symbol| pairs.get(symbol));
+        let expected = fee * 49_995.0 - fee * 0.8;
+        assert!((adjustment - expected).abs() < 1e-9, "{adjustment}");
+    }
+
+    #[tokio::test]
+    async fn test_intents_of_a_session_that_died_are_checked_once() {
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_balance("USDT", 1_000.0);
+        let traded = buy(&exchange, "arb_1_1").await;
+        buy(&exchange, "arb_2_1").await;
+
+        let path = std::env::temp_dir().join(format!("intents_test_{}.jsonl", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let journal = Journal::open(&path).unwrap();
+        let started = || JournalEvent::SessionStarted {
+            version: "test".to_string(),
+            dry_run: false,
+            live_threshold_pct: 0.5,
+            shadow_threshold_pct: None,
+            alert_threshold_pct: None,
+        };
+        let intent = |link: &str| {
+            let request = PlaceOrderRequest {
+                category: "spot".to_string(),
+                symbol: "BTCUSDT".to_string(),
+                side: "Buy".to_string(),
+                order_type: "Market".to_string(),
+                qty: "0.001".to_string(),
+                price: None,
+                time_in_force: Some("IOC".to_string()),
+                order_link_id: Some(link.to_string()),
+                reduce_only: None,
+            };
+            JournalEvent::order_intent(&request, Some(50_000.0))
+        };
+        journal.record(started());
+        // Journaled as traded, sent then lost with the process, and never sent
+        journal.record(intent("arb_1_1"));
+        journal.record(
+            JournalEvent::PositionClosed {
+                symbol: "BTCUSDT".to_string(),
+                coin: "BTC".to_string(),
+                reason: "liquidated".to_string(),
+                target_coin: "USDT".to_string(),
+                breakeven_amount: 0.0,
+                received: 0.0,
+                legs: Vec::new(),
+            }
+            .with_legs(JournalLeg::from_orders(&OrderLog {
+                fills: vec![traded],
+                ..OrderLog::default()
+            })),
+        );
+        journal.record(intent("arb_2_1"));
+        journal.record(intent("arb_3_1"));
+        journal.record(started());
+
+        let report = recover_intents(&exchange, &path).await.unwrap();
+        assert_eq!(report.legs_checked, 2);
+        assert_eq!(report.mismatches.len(), 1);
+        assert!(report.mismatches[0].to_string().contains("arb_2_1"));
+
+        // The next restart only looks at the session in between, which ended cleanly
+        journal.record(JournalEvent::SessionEnded {
+            cycles: 1,
+            trades_completed: 0,
+        });
+        journal.record(started());
+        let report = recover_intents(&exchange, &path).await.unwrap();
+        assert_eq!(report.legs_checked, 0);
+        std::fs::remove_file(&path).ok();
+    }
+}
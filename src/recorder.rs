@@ -0,0 +1,131 @@
+use crate::models::{MarketPair, TickerInfo};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Market data the bot priced from, as it arrived
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MarketData {
+    /// Every trading pair after a full refresh of instruments and prices
+    Pairs { pairs: Vec<MarketPair> },
+    /// One ticker update, from a WebSocket or a REST poll
+    Ticker { ticker: Box<TickerInfo> },
+}
+
+/// One line of a market data recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketRecord {
+    pub ts: DateTime<Utc>,
+    #[serde(flatten)]
+    pub data: MarketData,
+}
+
+/// Appends the pairs and tickers the bot sees to a JSONL file for backtesting, written on a
+/// supervised task
+pub struct MarketRecorder {
+    tx: mpsc::UnboundedSender<MarketRecord>,
+}
+
+impl MarketRecorder {
+    pub fn spawn(path: &Path, restart_on_panic: bool) -> Result<Self> {
+        // Fail fast on a path that can't be written at all
+        let first = Arc::new(Mutex::new(Some(open(path)?)));
+        let (tx, rx) = mpsc::unbounded_channel();
+        let rx = Arc::new(tokio::sync::Mutex::new(rx));
+        let path = path.to_path_buf();
+        crate::supervisor::spawn_supervised(
+            "Market data recorder".to_string(),
+            restart_on_panic,
+            move || {
+                let file = first.lock().ok().and_then(|mut f| f.take());
+                run_writer(path.clone(), file, rx.clone())
+            },
+        );
+        Ok(Self { tx })
+    }
+
+    pub fn record_pairs(&self, pairs: &[MarketPair]) {
+        self.record(MarketData::Pairs {
+            pairs: pairs.to_vec(),
+        });
+    }
+
+    pub fn record_ticker(&self, ticker: &TickerInfo) {
+        self.record(MarketData::Ticker {
+            ticker: Box::new(ticker.clone()),
+        });
+    }
+
+    fn record(&self, data: MarketData) {
+        let record = MarketRecord {
+            ts: Utc::now(),
+            data,
+        };
+        if self.tx.send(record).is_err() {
+            warn!("⚠️ Market data recorder stopped; dropping update");
+        }
+    }
+
+    /// Every record of a recording, skipping lines that don't parse (e.g. a line cut short
+    /// by a crash)
+    pub fn read(path: &Path) -> Result<Vec<MarketRecord>> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read market data {}", path.display()))?;
+        Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+fn open(path: &Path) -> Result<BufWriter<File>> {
+    crate::logger::create_parent_dir(path)?;
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open market data file {}", path.display()))?;
+    Ok(BufWriter::new(file))
+}
+
+/// Write records until the recorder is dropped, flushing whenever the queue runs dry and
+/// reopening the file after a write error
+async fn run_writer(
+    path: PathBuf,
+    mut file: Option<BufWriter<File>>,
+    rx: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<MarketRecord>>>,
+) {
+    let mut rx = rx.lock().await;
+    while let Some(record) = rx.recv().await {
+        if file.is_none() {
+            file = open(&path)
+                .map_err(|e| warn!("⚠️ Failed to record market data: {e:#}"))
+                .ok();
+        }
+        let Some(writer) = file.as_mut() else {
+            continue;
+        };
+        if let Err(e) = write_record(writer, &record, rx.is_empty()) {
+            warn!("⚠️ Failed to record market data: {e}");
+            file = None;
+        }
+    }
+    // The recorder is gone; park instead of returning so the supervisor doesn't restart us
+    std::future::pending::<()>().await;
+}
+
+fn write_record(writer: &mut BufWriter<File>, record: &MarketRecord, flush: bool) -> Result<()> {
+    let line = serde_json::to_string(record)?;
+    writeln!(writer, "{line}")?;
+    if flush {
+        writer.flush()?;
+    }
+    Ok(())
+}
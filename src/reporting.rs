@@ -0,0 +1,128 @@
+use crate::models::MarketPair;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Coins reported as-is: every valuation in the bot is already in USD(T)
+const USD_CODES: [&str; 2] = ["USD", "USDT"];
+/// USD stablecoins a reporting currency can be priced against, most liquid first
+const USD_QUOTES: [&str; 2] = ["USDT", "USDC"];
+
+/// Converts USD valuations into the currency the user accounts in (e.g. EUR or BTC),
+/// priced from live pairs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportingCurrency {
+    pub currency: String,
+    /// Units of `currency` per USD, None until a live price is known
+    pub per_usd: Option<f64>,
+    #[serde(skip)]
+    warned: bool,
+}
+
+impl ReportingCurrency {
+    pub fn new(currency: &str) -> Self {
+        let currency = currency.trim().to_uppercase();
+        let per_usd = USD_CODES.contains(&currency.as_str()).then_some(1.0);
+        Self {
+            currency,
+            per_usd,
+            warned: false,
+        }
+    }
+
+    pub fn is_usd(&self) -> bool {
+        USD_CODES.contains(&self.currency.as_str())
+    }
+
+    /// Reprice from the mid of `<CUR>USDT`, `USDT<CUR>` or their USDC equivalents
+    pub fn update<'a>(&mut self, get_pair: impl Fn(&str) -> Option<&'a MarketPair>) {
+        if self.is_usd() {
+            return;
+        }
+        let mid = |pair: &MarketPair| (pair.bid_price + pair.ask_price) / 2.0;
+        let rate = USD_QUOTES.iter().find_map(|usd| {
+            get_pair(&format!("{}{usd}", self.currency))
+                .map(|pair| 1.0 / mid(pair))
+                .or_else(|| get_pair(&format!("{usd}{}", self.currency)).map(mid))
+                .filter(|rate| rate.is_finite() && *rate > 0.0)
+        });
+        match (rate, self.per_usd) {
+            (Some(rate), None) => {
+                info!("💱 Reporting in {}: 1 USD = {rate:.8}", self.currency);
+                self.per_usd = Some(rate);
+            }
+            (Some(rate), Some(_)) => self.per_usd = Some(rate),
+            (None, None) if !self.warned => {
+                warn!(
+                    "⚠️ No {0}USDT or USDT{0} pair to price REPORTING_CURRENCY - reporting in USD",
+                    self.currency
+                );
+                self.warned = true;
+            }
+            // Keep the last known rate while the pair is briefly missing
+            (None, _) => {}
+        }
+    }
+
+    /// Format `usd` in the reporting currency with `decimals` decimals worth of USD
+    /// precision, falling back to USD while the price is unknown
+    pub fn format(&self, usd: f64, decimals: usize) -> String {
+        match self.per_usd {
+            Some(rate) if !self.is_usd() => {
+                // Keep the same precision for currencies worth far more than a dollar
+                let extra = (-rate.log10()).round().max(0.0) as usize;
+                let decimals = decimals + extra;
+                format!("{:.decimals$} {}", usd * rate, self.currency)
+            }
+            _ => format!("${usd:.decimals$}"),
+        }
+    }
+}
+
+/// Check a REPORTING_CURRENCY value
+pub fn validate_currency(currency: &str) -> anyhow::Result<()> {
+    let currency = currency.trim();
+    anyhow::ensure!(
+        !currency.is_empty()
+            && currency.len() <= 10
+            && currency.chars().all(|c| c.is_ascii_alphanumeric()),
+        "REPORTING_CURRENCY must be a coin code such as USDT, EUR or BTC (got {currency:?})"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_converts_through_either_pair_direction() {
+        let pairs: HashMap<String, MarketPair> = [
+            MarketPair::test("BTC", "USDT", 49_990.0, 50_010.0),
+            MarketPair::test("USDT", "EUR", 0.91, 0.93),
+        ]
+        .into_iter()
+        .map(|p| (p.symbol.clone(), p))
+        .collect();
+
+        let usd = ReportingCurrency::new("usdt");
+        assert_eq!(usd.format(1.5, 2), "$1.50");
+
+        let mut btc = ReportingCurrency::new("BTC");
+        assert_eq!(btc.format(10.0, 2), "$10.00");
+        btc.update(|symbol| pairs.get(symbol));
+        assert!((btc.per_usd.unwrap() - 1.0 / 50_000.0).abs() < 1e-15);
+        assert_eq!(btc.format(10.0, 2), "0.0002000 BTC");
+
+        let mut eur = ReportingCurrency::new("EUR");
+        eur.update(|symbol| pairs.get(symbol));
+        assert_eq!(eur.format(100.0, 2), "92.00 EUR");
+
+        // Unpriced currencies keep reporting in USD
+        let mut xyz = ReportingCurrency::new("XYZ");
+        xyz.update(|symbol| pairs.get(symbol));
+        assert_eq!(xyz.per_usd, None);
+        assert_eq!(xyz.format(1.0, 2), "$1.00");
+        assert!(validate_currency("EUR").is_ok() && validate_currency("E-R").is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use crate::models::ArbitrageOpportunity;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Where the triangle in progress is checkpointed, so a crash mid-triangle can be resolved
+/// on the next start
+pub const EXECUTION_STATE_FILE: &str = "execution_state.json";
+
+/// What to do on startup with a triangle the previous run left half-done
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResumeMode {
+    /// Convert the held coin back along the filled legs to the starting coin
+    Rollback,
+    /// Trade the remaining legs at market
+    Complete,
+    /// Only report it, leaving the held coin alone
+    Manual,
+}
+
+impl ResumeMode {
+    /// Parse `RESUME_INTERRUPTED`: `rollback`, `complete` or `manual`
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "rollback" => Ok(ResumeMode::Rollback),
+            "complete" => Ok(ResumeMode::Complete),
+            "manual" => Ok(ResumeMode::Manual),
+            other => Err(anyhow!(
+                "unknown resume mode '{other}' (expected rollback, complete or manual)"
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ResumeMode::Rollback => "rollback",
+            ResumeMode::Complete => "complete",
+            ResumeMode::Manual => "manual",
+        }
+    }
+}
+
+/// A triangle in progress, as of just before its next order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionState {
+    pub opportunity: ArbitrageOpportunity,
+    /// Amount of the starting coin the triangle was sized for
+    pub amount: f64,
+    /// Legs filled so far
+    pub completed_legs: usize,
+    /// Coin the filled legs left us holding, which the next leg spends
+    pub held_coin: String,
+    pub held_amount: f64,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Execution state on disk, rewritten before every order of a triangle and removed once
+/// the triangle is done
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    path: PathBuf,
+}
+
+impl Checkpoint {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+        }
+    }
+
+    pub fn save(&self, state: &ExecutionState) -> Result<()> {
+        crate::persist::save_json(&self.path, state)
+    }
+
+    pub fn clear(&self) {
+        match std::fs::remove_file(&self.path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("⚠️ Failed to remove {}: {e}", self.path.display()),
+        }
+    }
+
+    /// The triangle a previous run left unfinished, if any
+    pub fn load(&self) -> Result<Option<ExecutionState>> {
+        let json = match std::fs::read_to_string(&self.path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", self.path.display()))
+            }
+        };
+        serde_json::from_str(&json)
+            .map(Some)
+            .with_context(|| format!("Failed to parse {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let dir = std::env::temp_dir().join(format!("resume-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let checkpoint = Checkpoint::new(&dir.join(EXECUTION_STATE_FILE));
+        assert_eq!(checkpoint.load().unwrap(), None);
+
+        let now = Utc::now();
+        let state = ExecutionState {
+            opportunity: ArbitrageOpportunity {
+                timestamp: now,
+                ..ArbitrageOpportunity::test().with_profit_pct(0.2)
+            },
+            amount: 100.0,
+            completed_legs: 1,
+            held_coin: "BTC".to_string(),
+            held_amount: 0.002,
+            started_at: now,
+            updated_at: now,
+        };
+        checkpoint.save(&state).unwrap();
+        assert_eq!(checkpoint.load().unwrap(), Some(state));
+
+        checkpoint.clear();
+        assert_eq!(checkpoint.load().unwrap(), None);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(ResumeMode::parse("").unwrap(), ResumeMode::Rollback);
+        assert_eq!(
+            ResumeMode::parse(" Complete ").unwrap(),
+            ResumeMode::Complete
+        );
+        assert!(ResumeMode::parse("retry").is_err());
+    }
+}
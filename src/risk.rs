@@ -0,0 +1,177 @@
+use crate::config::Config;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Limits that stop trading when crossed; zero disables each one
+#[derive(Debug, Clone, Default)]
+pub struct RiskLimits {
+    /// Largest realized loss in USD per UTC day
+    pub max_daily_loss_usd: f64,
+    /// Failed trades in a row
+    pub max_consecutive_failures: u32,
+    /// USD in a trade about to start plus parked positions
+    pub max_open_exposure_usd: f64,
+    /// Stops trading while this file exists
+    pub kill_switch_file: Option<PathBuf>,
+    /// Stops trading from the start
+    pub kill_switch: bool,
+}
+
+impl RiskLimits {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_daily_loss_usd: config.max_daily_loss_usd,
+            max_consecutive_failures: config.max_consecutive_failures,
+            max_open_exposure_usd: config.max_open_exposure_usd,
+            kill_switch_file: (!config.kill_switch_file.is_empty())
+                .then(|| PathBuf::from(&config.kill_switch_file)),
+            kill_switch: config.kill_switch,
+        }
+    }
+}
+
+/// Consulted before every execution: tells the bot to stop trading once a loss limit,
+/// the exposure limit or the kill-switch trips
+#[derive(Debug)]
+pub struct RiskManager {
+    limits: RiskLimits,
+    day: NaiveDate,
+    daily_pnl_usd: f64,
+    consecutive_failures: u32,
+    /// USD value of each parked position, by the order id of its exit
+    parked_usd: HashMap<String, f64>,
+}
+
+impl RiskManager {
+    pub fn new(limits: RiskLimits, now: DateTime<Utc>) -> Self {
+        Self {
+            limits,
+            day: now.date_naive(),
+            daily_pnl_usd: 0.0,
+            consecutive_failures: 0,
+            parked_usd: HashMap::new(),
+        }
+    }
+
+    /// Count profit already realized today, e.g. by an earlier run
+    pub fn with_daily_pnl(mut self, pnl_usd: f64) -> Self {
+        self.daily_pnl_usd = pnl_usd;
+        self
+    }
+
+    pub fn daily_pnl_usd(&self) -> f64 {
+        self.daily_pnl_usd
+    }
+
+    /// Why a trade of `amount_usd` must not start, if a limit says so. The failure streak
+    /// is reset once it trips, so a resumed bot gets a fresh count.
+    pub fn check(&mut self, amount_usd: f64, now: DateTime<Utc>) -> Option<String> {
+        self.roll_day(now);
+        let limits = &self.limits;
+        if limits.kill_switch {
+            return Some("KILL_SWITCH is set".to_string());
+        }
+        if let Some(file) = limits.kill_switch_file.as_ref().filter(|f| f.exists()) {
+            return Some(format!("kill-switch file {} exists", file.display()));
+        }
+        if limits.max_daily_loss_usd > 0.0 && -self.daily_pnl_usd >= limits.max_daily_loss_usd {
+            return Some(format!(
+                "daily loss ${:.2} reached the ${:.2} limit",
+                -self.daily_pnl_usd, limits.max_daily_loss_usd
+            ));
+        }
+        if limits.max_consecutive_failures > 0
+            && self.consecutive_failures >= limits.max_consecutive_failures
+        {
+            let failures = std::mem::take(&mut self.consecutive_failures);
+            return Some(format!("{failures} trades failed in a row"));
+        }
+        let exposure = amount_usd + self.parked_usd.values().sum::<f64>();
+        if limits.max_open_exposure_usd > 0.0 && exposure > limits.max_open_exposure_usd {
+            return Some(format!(
+                "open exposure ${exposure:.2} would exceed the ${:.2} limit",
+                limits.max_open_exposure_usd
+            ));
+        }
+        None
+    }
+
+    /// Count a finished execution's realized profit, and whether it succeeded
+    pub fn record_trade(&mut self, profit_usd: f64, success: bool, now: DateTime<Utc>) {
+        self.roll_day(now);
+        self.daily_pnl_usd += profit_usd;
+        if success {
+            self.consecutive_failures = 0;
+        } else {
+            self.consecutive_failures += 1;
+        }
+    }
+
+    pub fn position_parked(&mut self, order_id: &str, value_usd: f64) {
+        self.parked_usd.insert(order_id.to_string(), value_usd);
+    }
+
+    pub fn position_closed(&mut self, order_id: &str) {
+        self.parked_usd.remove(order_id);
+    }
+
+    fn roll_day(&mut self, now: DateTime<Utc>) {
+        let today = now.date_naive();
+        if today != self.day {
+            self.day = today;
+            self.daily_pnl_usd = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loss_limits_trip_and_the_day_rolls_over() {
+        let limits = RiskLimits {
+            max_daily_loss_usd: 5.0,
+            max_consecutive_failures: 3,
+            ..Default::default()
+        };
+        let now = Utc::now();
+        let mut risk = RiskManager::new(limits, now);
+
+        for _ in 0..3 {
+            assert_eq!(risk.check(100.0, now), None);
+            risk.record_trade(-0.5, false, now);
+        }
+        assert!(risk.check(100.0, now).unwrap().contains("3 trades failed"));
+        // The streak starts over once reported
+        assert_eq!(risk.check(100.0, now), None);
+
+        risk.record_trade(-4.0, true, now);
+        assert!(risk.check(100.0, now).unwrap().contains("daily loss $5.50"));
+        assert_eq!(risk.check(100.0, now + chrono::Duration::days(1)), None);
+    }
+
+    #[test]
+    fn test_exposure_and_kill_switch_file() {
+        let file = std::env::temp_dir().join(format!("kill-switch-{}", std::process::id()));
+        let limits = RiskLimits {
+            max_open_exposure_usd: 250.0,
+            kill_switch_file: Some(file.clone()),
+            ..Default::default()
+        };
+        let now = Utc::now();
+        let mut risk = RiskManager::new(limits, now);
+
+        risk.position_parked("exit-1", 120.0);
+        assert_eq!(risk.check(100.0, now), None);
+        assert!(risk.check(150.0, now).unwrap().contains("$270.00"));
+        risk.position_closed("exit-1");
+        assert_eq!(risk.check(150.0, now), None);
+
+        std::fs::write(&file, "").unwrap();
+        assert!(risk.check(100.0, now).unwrap().contains("kill-switch"));
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(risk.check(100.0, now), None);
+    }
+}
@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What the main loop is doing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    /// Loading instruments, precision data and prices
+    #[default]
+    Initializing,
+    /// Looking for opportunities and free to trade
+    Scanning,
+    /// A trade or conversion is in flight
+    Executing,
+    /// Scanning only, by operator request
+    Paused,
+    /// Scanning only until an operator resumes, after a risk limit tripped
+    Halted,
+    /// Scanning only while the exchange is in maintenance, until a verification pass succeeds
+    Maintenance,
+    /// Finishing in-flight work before exit
+    ShuttingDown,
+}
+
+impl RunState {
+    pub const ALL: [RunState; 7] = [
+        RunState::Initializing,
+        RunState::Scanning,
+        RunState::Executing,
+        RunState::Paused,
+        RunState::Halted,
+        RunState::Maintenance,
+        RunState::ShuttingDown,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RunState::Initializing => "initializing",
+            RunState::Scanning => "scanning",
+            RunState::Executing => "executing",
+            RunState::Paused => "paused",
+            RunState::Halted => "halted",
+            RunState::Maintenance => "maintenance",
+            RunState::ShuttingDown => "shutting_down",
+        }
+    }
+}
+
+impl fmt::Display for RunState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Something that moves the main loop between states
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunEvent {
+    Initialized,
+    ExecutionStarted,
+    ExecutionFinished,
+    Pause,
+    Resume,
+    /// A risk limit stopped trading
+    Halt(String),
+    /// The exchange went into maintenance
+    MaintenanceStarted(String),
+    /// The exchange is back and passed verification
+    MaintenanceEnded,
+    Shutdown,
+}
+
+/// The run state plus operator intent that outlives it, e.g. a pause requested mid-trade
+#[derive(Debug, Default)]
+pub struct RunMachine {
+    state: RunState,
+    pause_requested: bool,
+    halt_reason: Option<String>,
+    maintenance: Option<String>,
+}
+
+impl RunMachine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> RunState {
+        self.state
+    }
+
+    /// Whether an operator has asked for scan-only mode
+    pub fn pause_requested(&self) -> bool {
+        self.pause_requested
+    }
+
+    pub fn halt_reason(&self) -> Option<&str> {
+        self.halt_reason.as_deref()
+    }
+
+    pub fn maintenance(&self) -> Option<&str> {
+        self.maintenance.as_deref()
+    }
+
+    /// State to settle in when nothing is in flight
+    fn idle_state(&self) -> RunState {
+        if self.halt_reason.is_some() {
+            RunState::Halted
+        } else if self.maintenance.is_some() {
+            RunState::Maintenance
+        } else if self.pause_requested {
+            RunState::Paused
+        } else {
+            RunState::Scanning
+        }
+    }
+
+    /// Apply an event. Returns false if it was refused or changed nothing, e.g. a trade
+    /// starting while paused or a second pause request.
+    pub fn apply(&mut self, event: RunEvent) -> bool {
+        use RunState::*;
+
+        if self.state == ShuttingDown {
+            return false;
+        }
+        match event {
+            RunEvent::Initialized if self.state == Initializing => {
+                self.state = self.idle_state();
+                true
+            }
+            RunEvent::ExecutionStarted if self.state == Scanning => {
+                self.state = Executing;
+                true
+            }
+            RunEvent::ExecutionFinished if self.state == Executing => {
+                self.state = self.idle_state();
+                true
+            }
+            RunEvent::Pause if !self.pause_requested => {
+                self.pause_requested = true;
+                if self.state == Scanning {
+                    self.state = Paused;
+                }
+                true
+            }
+            RunEvent::Resume if self.pause_requested || self.halt_reason.is_some() => {
+                self.pause_requested = false;
+                self.halt_reason = None;
+                if matches!(self.state, Paused | Halted) {
+                    self.state = self.idle_state();
+                }
+                true
+            }
+            RunEvent::Halt(reason) if self.halt_reason.is_none() => {
+                self.halt_reason = Some(reason);
+                if matches!(self.state, Scanning | Paused | Maintenance) {
+                    self.state = Halted;
+                }
+                true
+            }
+            RunEvent::MaintenanceStarted(reason) if self.maintenance.is_none() => {
+                self.maintenance = Some(reason);
+                if matches!(self.state, Scanning | Paused) {
+                    self.state = Maintenance;
+                }
+                true
+            }
+            RunEvent::MaintenanceEnded if self.maintenance.is_some() => {
+                self.maintenance = None;
+                if self.state == Maintenance {
+                    self.state = self.idle_state();
+                }
+                true
+            }
+            RunEvent::Shutdown => {
+                self.state = ShuttingDown;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trades_only_start_while_scanning() {
+        let mut machine = RunMachine::new();
+        assert!(!machine.apply(RunEvent::ExecutionStarted));
+
+        assert!(machine.apply(RunEvent::Initialized));
+        assert_eq!(machine.state(), RunState::Scanning);
+        assert!(machine.apply(RunEvent::ExecutionStarted));
+        assert!(!machine.apply(RunEvent::ExecutionStarted));
+        assert!(machine.apply(RunEvent::ExecutionFinished));
+        assert_eq!(machine.state(), RunState::Scanning);
+
+        assert!(machine.apply(RunEvent::Pause));
+        assert!(!machine.apply(RunEvent::Pause));
+        assert_eq!(machine.state(), RunState::Paused);
+        assert!(!machine.apply(RunEvent::ExecutionStarted));
+        assert!(machine.apply(RunEvent::Resume));
+        assert!(!machine.apply(RunEvent::Resume));
+        assert_eq!(machine.state(), RunState::Scanning);
+    }
+
+    #[test]
+    fn test_pause_and_halt_wait_for_the_trade_in_flight() {
+        let mut machine = RunMachine::new();
+        machine.apply(RunEvent::Initialized);
+        machine.apply(RunEvent::ExecutionStarted);
+
+        assert!(machine.apply(RunEvent::Pause));
+        assert_eq!(machine.state(), RunState::Executing);
+        machine.apply(RunEvent::ExecutionFinished);
+        assert_eq!(machine.state(), RunState::Paused);
+
+        // A halt outranks the pause until an operator resumes
+        assert!(machine.apply(RunEvent::Halt("daily loss".into())));
+        assert_eq!(machine.state(), RunState::Halted);
+        assert_eq!(machine.halt_reason(), Some("daily loss"));
+        assert!(machine.apply(RunEvent::Resume));
+        assert_eq!(machine.state(), RunState::Scanning);
+        assert_eq!(machine.halt_reason(), None);
+    }
+
+    #[test]
+    fn test_maintenance_resumes_without_an_operator() {
+        let mut machine = RunMachine::new();
+        machine.apply(RunEvent::Initialized);
+        machine.apply(RunEvent::Pause);
+
+        assert!(machine.apply(RunEvent::MaintenanceStarted("upgrade".into())));
+        assert_eq!(machine.state(), RunState::Maintenance);
+        assert!(!machine.apply(RunEvent::ExecutionStarted));
+
+        // Resuming clears the pause but not the maintenance
+        assert!(machine.apply(RunEvent::Resume));
+        assert_eq!(machine.state(), RunState::Maintenance);
+        assert!(machine.apply(RunEvent::MaintenanceEnded));
+        assert!(!machine.apply(RunEvent::MaintenanceEnded));
+        assert_eq!(machine.state(), RunState::Scanning);
+    }
+
+    #[test]
+    fn test_shutdown_is_final() {
+        let mut machine = RunMachine::new();
+        machine.apply(RunEvent::Initialized);
+        machine.apply(RunEvent::ExecutionStarted);
+        assert!(machine.apply(RunEvent::Shutdown));
+
+        for event in [
+            RunEvent::ExecutionFinished,
+            RunEvent::Resume,
+            RunEvent::Pause,
+            RunEvent::Shutdown,
+        ] {
+            assert!(!machine.apply(event));
+        }
+        assert_eq!(machine.state(), RunState::ShuttingDown);
+    }
+}
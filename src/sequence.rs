@@ -0,0 +1,104 @@
+use std::collections::{HashMap, HashSet};
+
+/// What to do with an orderbook message, judged by its update id
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    Apply,
+    /// Older than what was already applied, e.g. delivered out of order
+    Stale,
+    /// Updates were missed since `last` (None: no snapshot yet); the symbol needs a fresh one
+    Gap {
+        last: Option<u64>,
+    },
+}
+
+/// Follows Bybit's per-symbol orderbook update ids (`u`) on one connection, so dropped or
+/// reordered messages aren't applied blindly
+#[derive(Debug, Default)]
+pub struct SequenceTracker {
+    /// Every message is a full snapshot (depth 1), so only order matters, not gaps
+    snapshots_only: bool,
+    last: HashMap<String, u64>,
+    /// Symbols with a gap reported, waiting for their next snapshot
+    awaiting_snapshot: HashSet<String>,
+}
+
+impl SequenceTracker {
+    /// For a feed that pushes snapshots only, like `orderbook.1`
+    pub fn snapshots() -> Self {
+        Self {
+            snapshots_only: true,
+            ..Self::default()
+        }
+    }
+
+    /// For a feed of a snapshot followed by deltas, like `orderbook.50`
+    pub fn deltas() -> Self {
+        Self::default()
+    }
+
+    /// A gap is reported once; later deltas of the symbol are stale until a snapshot
+    pub fn check(&mut self, symbol: &str, snapshot: bool, update_id: u64) -> Verdict {
+        // Bybit restarts the ids at 1 with a snapshot after a service restart
+        let reset = update_id == 1 || (snapshot && !self.snapshots_only);
+        let verdict = match self.last.get(symbol) {
+            _ if reset => Verdict::Apply,
+            _ if self.awaiting_snapshot.contains(symbol) => Verdict::Stale,
+            None if self.snapshots_only => Verdict::Apply,
+            None => Verdict::Gap { last: None },
+            Some(&last) if update_id < last || (!self.snapshots_only && update_id == last) => {
+                Verdict::Stale
+            }
+            Some(&last) if !self.snapshots_only && update_id != last + 1 => {
+                Verdict::Gap { last: Some(last) }
+            }
+            Some(_) => Verdict::Apply,
+        };
+        match verdict {
+            Verdict::Apply => {
+                self.last.insert(symbol.to_string(), update_id);
+                self.awaiting_snapshot.remove(symbol);
+            }
+            // Deltas are useless until the next snapshot
+            Verdict::Gap { .. } => {
+                self.last.remove(symbol);
+                self.awaiting_snapshot.insert(symbol.to_string());
+            }
+            Verdict::Stale => {}
+        }
+        verdict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_feed_drops_out_of_order_messages() {
+        let mut seq = SequenceTracker::snapshots();
+        assert_eq!(seq.check("BTCUSDT", true, 100), Verdict::Apply);
+        // Ids may skip, and repeat when the book hasn't changed
+        assert_eq!(seq.check("BTCUSDT", true, 105), Verdict::Apply);
+        assert_eq!(seq.check("BTCUSDT", true, 105), Verdict::Apply);
+        assert_eq!(seq.check("BTCUSDT", true, 103), Verdict::Stale);
+        assert_eq!(seq.check("BTCUSDT", true, 1), Verdict::Apply);
+    }
+
+    #[test]
+    fn test_delta_feed_detects_gaps_until_the_next_snapshot() {
+        let mut seq = SequenceTracker::deltas();
+        assert_eq!(seq.check("ETHUSDT", false, 7), Verdict::Gap { last: None });
+        assert_eq!(seq.check("ETHUSDT", true, 10), Verdict::Apply);
+        assert_eq!(seq.check("ETHUSDT", false, 11), Verdict::Apply);
+        assert_eq!(seq.check("ETHUSDT", false, 11), Verdict::Stale);
+        assert_eq!(
+            seq.check("ETHUSDT", false, 13),
+            Verdict::Gap { last: Some(11) }
+        );
+        // Nothing applies, nor is reported again, until a snapshot resyncs the book
+        assert_eq!(seq.check("ETHUSDT", false, 14), Verdict::Stale);
+        assert_eq!(seq.check("ETHUSDT", true, 20), Verdict::Apply);
+        assert_eq!(seq.check("ETHUSDT", false, 21), Verdict::Apply);
+    }
+}
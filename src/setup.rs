@@ -0,0 +1,331 @@
+use crate::client::BybitClient;
+use crate::config::Config;
+use crate::doctor::{self, CheckStatus};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+const ENV_FILE: &str = ".env";
+
+/// Values collected by the setup wizard
+#[derive(Debug, Clone)]
+pub struct SetupAnswers {
+    pub api_key: String,
+    pub api_secret: String,
+    pub testnet: bool,
+    pub dry_run: bool,
+    pub order_size: f64,
+    pub min_profit_threshold: f64,
+    pub max_spread_percent: f64,
+    pub max_trades: u32,
+}
+
+impl Default for SetupAnswers {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            api_secret: String::new(),
+            testnet: false,
+            dry_run: true,
+            order_size: 10.0,
+            min_profit_threshold: 0.5,
+            max_spread_percent: 0.4,
+            max_trades: 1,
+        }
+    }
+}
+
+impl SetupAnswers {
+    /// Key/value pairs using the same names as the environment variables
+    fn to_env_pairs(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("BYBIT_API_KEY", self.api_key.clone()),
+            ("BYBIT_API_SECRET", self.api_secret.clone()),
+            ("BYBIT_TESTNET", self.testnet.to_string()),
+            ("DRY_RUN", self.dry_run.to_string()),
+            ("MAX_TRADES", self.max_trades.to_string()),
+            ("ORDER_SIZE", self.order_size.to_string()),
+            (
+                "MIN_PROFIT_THRESHOLD",
+                self.min_profit_threshold.to_string(),
+            ),
+            ("MAX_SPREAD_PERCENT", self.max_spread_percent.to_string()),
+        ]
+    }
+
+    /// Build a Config from the answers, using defaults for everything else
+    pub fn to_config(&self) -> Result<Config> {
+        let values: HashMap<&str, String> = self.to_env_pairs().into_iter().collect();
+        Config::from_lookup(|key| values.get(key).cloned())
+    }
+
+    /// Render the answers as a .env file
+    pub fn render_env(&self) -> String {
+        format!(
+            r#"# Bybit Triangular Arbitrage Bot Configuration
+# Generated by `bybit-arbitrage-bot setup`
+
+# Bybit API credentials
+BYBIT_API_KEY={}
+BYBIT_API_SECRET={}
+
+# Trading Environment
+BYBIT_TESTNET={}
+
+# Trading Mode - set to false to enable LIVE TRADING with real money
+DRY_RUN={}
+
+# Trading Parameters
+MAX_TRADES={}
+ORDER_SIZE={}
+MIN_PROFIT_THRESHOLD={}
+MAX_SPREAD_PERCENT={}
+
+# System Settings
+RUST_LOG=info
+"#,
+            self.api_key,
+            self.api_secret,
+            self.testnet,
+            self.dry_run,
+            self.max_trades,
+            self.order_size,
+            self.min_profit_threshold,
+            self.max_spread_percent,
+        )
+    }
+}
+
+/// Line-based prompter over any input/output pair
+struct Prompter<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    fn ask(&mut self, label: &str, default: Option<&str>) -> Result<String> {
+        loop {
+            match default {
+                Some(d) if !d.is_empty() => write!(self.output, "{label} [{d}]: ")?,
+                _ => write!(self.output, "{label}: ")?,
+            }
+            self.output.flush()?;
+
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                anyhow::bail!("Setup aborted: input closed");
+            }
+            let answer = line.trim();
+
+            if !answer.is_empty() {
+                return Ok(answer.to_string());
+            }
+            if let Some(d) = default {
+                return Ok(d.to_string());
+            }
+            writeln!(self.output, "  A value is required.")?;
+        }
+    }
+
+    fn ask_bool(&mut self, label: &str, default: bool) -> Result<bool> {
+        loop {
+            let answer = self.ask(label, Some(if default { "y" } else { "n" }))?;
+            match answer.to_lowercase().as_str() {
+                "y" | "yes" | "true" => return Ok(true),
+                "n" | "no" | "false" => return Ok(false),
+                _ => writeln!(self.output, "  Please answer y or n.")?,
+            }
+        }
+    }
+
+    fn ask_parse<T>(&mut self, label: &str, default: T) -> Result<T>
+    where
+        T: std::str::FromStr + ToString,
+    {
+        loop {
+            let answer = self.ask(label, Some(&default.to_string()))?;
+            match answer.parse::<T>() {
+                Ok(value) => return Ok(value),
+                Err(_) => writeln!(self.output, "  '{answer}' is not a valid value.")?,
+            }
+        }
+    }
+
+    /// Collect all answers, starting from the given defaults
+    fn collect(&mut self, defaults: &SetupAnswers) -> Result<SetupAnswers> {
+        writeln!(
+            self.output,
+            "🔑 API credentials (https://www.bybit.com/app/user/api-management)"
+        )?;
+        let api_key = self.ask("API key", non_empty(&defaults.api_key))?;
+        let api_secret = self.ask("API secret", non_empty(&defaults.api_secret))?;
+        let testnet = self.ask_bool("Use testnet?", defaults.testnet)?;
+
+        writeln!(self.output, "\n⚖️ Trading and risk limits")?;
+        let dry_run = self.ask_bool("Dry run (no real orders)?", defaults.dry_run)?;
+        let order_size = self.ask_parse("Order size in USD", defaults.order_size)?;
+        let min_profit_threshold = self.ask_parse(
+            "Minimum profit threshold (%)",
+            defaults.min_profit_threshold,
+        )?;
+        let max_spread_percent =
+            self.ask_parse("Maximum bid/ask spread (%)", defaults.max_spread_percent)?;
+        let max_trades =
+            self.ask_parse("Trades to execute before stopping", defaults.max_trades)?;
+
+        Ok(SetupAnswers {
+            api_key,
+            api_secret,
+            testnet,
+            dry_run,
+            order_size,
+            min_profit_threshold,
+            max_spread_percent,
+            max_trades,
+        })
+    }
+}
+
+fn non_empty(value: &str) -> Option<&str> {
+    (!value.is_empty()).then_some(value)
+}
+
+/// Run the interactive setup wizard and write the resulting .env file
+pub async fn run_setup() -> Result<()> {
+    let stdin = io::stdin();
+    let mut prompter = Prompter {
+        input: stdin.lock(),
+        output: io::stdout(),
+    };
+
+    println!("🔧 Bybit Triangular Arbitrage Bot - Setup");
+    println!();
+
+    if Path::new(ENV_FILE).exists()
+        && !prompter.ask_bool(&format!("{ENV_FILE} already exists. Overwrite it?"), false)?
+    {
+        println!("Setup cancelled; existing {ENV_FILE} left untouched.");
+        return Ok(());
+    }
+
+    let mut answers = SetupAnswers::default();
+    loop {
+        answers = prompter.collect(&answers)?;
+        let config = answers.to_config()?;
+
+        let issues = config.validate();
+        for issue in &issues {
+            println!("⚠️ {issue}");
+        }
+
+        println!("\n⚡ Validating credentials against the Bybit API...");
+        let live_ok = match BybitClient::new(config) {
+            Ok(client) => {
+                let results = doctor::check_api_key(&client).await;
+                for result in &results {
+                    println!(
+                        "{:<9} {:<18} {}",
+                        result.status.label(),
+                        result.name,
+                        result.detail
+                    );
+                    if let Some(hint) = &result.hint {
+                        println!("{:<9} {:<18} ↳ {}", "", "", hint);
+                    }
+                }
+                results.iter().all(|r| r.status != CheckStatus::Fail)
+            }
+            Err(e) => {
+                println!("❌ Failed to create API client: {e}");
+                false
+            }
+        };
+
+        if issues.is_empty() && live_ok {
+            break;
+        }
+        if !prompter.ask_bool("Problems were found. Re-enter the values?", true)? {
+            if prompter.ask_bool("Save the configuration anyway?", false)? {
+                break;
+            }
+            println!("Setup cancelled; nothing was written.");
+            return Ok(());
+        }
+        println!();
+    }
+
+    write_env_file(Path::new(ENV_FILE), &answers.render_env())?;
+    println!("\n✅ Configuration written to {ENV_FILE}");
+    println!("   Run `bybit-arbitrage-bot doctor` to verify the full environment.");
+
+    Ok(())
+}
+
+/// Write the env file, restricting permissions since it contains secrets
+fn write_env_file(path: &Path, contents: &str) -> Result<()> {
+    std::fs::write(path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("Failed to set permissions on {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_uses_defaults_for_empty_answers() {
+        let input = b"key123\nsecret456\ny\n\n25\n\n\n3\n";
+        let mut prompter = Prompter {
+            input: &input[..],
+            output: Vec::new(),
+        };
+        let answers = prompter.collect(&SetupAnswers::default()).unwrap();
+
+        assert_eq!(answers.api_key, "key123");
+        assert_eq!(answers.api_secret, "secret456");
+        assert!(answers.testnet);
+        assert!(answers.dry_run);
+        assert_eq!(answers.order_size, 25.0);
+        assert_eq!(answers.min_profit_threshold, 0.5);
+        assert_eq!(answers.max_trades, 3);
+    }
+
+    #[test]
+    fn test_invalid_number_is_reprompted() {
+        let input = b"abc\n12.5\n";
+        let mut prompter = Prompter {
+            input: &input[..],
+            output: Vec::new(),
+        };
+        let value: f64 = prompter.ask_parse("Order size", 10.0).unwrap();
+        assert_eq!(value, 12.5);
+    }
+
+    #[test]
+    fn test_rendered_env_round_trips_into_config() {
+        let answers = SetupAnswers {
+            api_key: "k".to_string(),
+            api_secret: "s".to_string(),
+            testnet: true,
+            order_size: 20.0,
+            ..SetupAnswers::default()
+        };
+        let rendered = answers.render_env();
+        assert!(rendered.contains("BYBIT_TESTNET=true"));
+        assert!(rendered.contains("ORDER_SIZE=20"));
+
+        let config = answers.to_config().unwrap();
+        assert_eq!(config.api_key, "k");
+        assert_eq!(config.base_url, "https://api-testnet.bybit.com");
+        assert_eq!(config.order_size, 20.0);
+    }
+}
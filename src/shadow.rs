@@ -0,0 +1,157 @@
+use crate::exchange::ExchangeApi;
+use crate::journal::{Journal, JournalEvent};
+use crate::models::{ArbitrageOpportunity, Pct};
+use crate::trader::ArbitrageTrader;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// Minimum time before the same triangle is shadow-simulated again
+const SHADOW_COOLDOWN: Duration = Duration::from_secs(10);
+
+/// Aggregated shadow results for the session summary
+#[derive(Debug, Default, Clone)]
+pub struct ShadowStats {
+    pub simulated: u64,
+    pub simulated_profit_usd: f64,
+    /// Opportunities only the lower shadow threshold accepted
+    pub below_live: u64,
+    pub below_live_profit_usd: f64,
+}
+
+/// Simulates every opportunity above a lower threshold while live trading
+/// continues, so the effect of loosening the threshold can be measured.
+pub struct ShadowTrader {
+    threshold_pct: Pct,
+    live_threshold_pct: Pct,
+    last_simulated: HashMap<String, Instant>,
+    stats: ShadowStats,
+}
+
+impl ShadowTrader {
+    pub fn new(threshold_pct: f64, live_threshold_pct: f64) -> Self {
+        Self {
+            threshold_pct: Pct::new(threshold_pct),
+            live_threshold_pct: Pct::new(live_threshold_pct),
+            last_simulated: HashMap::new(),
+            stats: ShadowStats::default(),
+        }
+    }
+
+    /// Simulate and journal each qualifying opportunity not seen within the cooldown
+    pub fn process<E: ExchangeApi>(
+        &mut self,
+        opportunities: &[ArbitrageOpportunity],
+        trader: &ArbitrageTrader<E>,
+        amount: f64,
+        journal: Option<&Journal>,
+    ) {
+        let now = Instant::now();
+        self.last_simulated
+            .retain(|_, seen| now.duration_since(*seen) < SHADOW_COOLDOWN);
+
+        for opp in opportunities
+            .iter()
+            .filter(|o| o.estimated_profit_pct >= self.threshold_pct)
+        {
+            let key = opp.pairs.join(",");
+            if self.last_simulated.contains_key(&key) {
+                continue;
+            }
+            self.last_simulated.insert(key, now);
+
+            let Ok(result) = trader.simulate_execution(opp, amount) else {
+                continue;
+            };
+            let above_live = opp.estimated_profit_pct >= self.live_threshold_pct;
+
+            self.stats.simulated += 1;
+            self.stats.simulated_profit_usd += result.actual_profit;
+            if !above_live {
+                self.stats.below_live += 1;
+                self.stats.below_live_profit_usd += result.actual_profit;
+            }
+
+            debug!(
+                "👥 SHADOW: {:.4}% via {} → simulated ${:.6}{}",
+                opp.estimated_profit_pct,
+                opp.display_pairs(),
+                result.actual_profit,
+                if above_live {
+                    ""
+                } else {
+                    " (below live threshold)"
+                }
+            );
+
+            if let Some(journal) = journal {
+                journal.record(JournalEvent::ShadowTrade {
+                    path: opp.display_path(),
+                    pairs: opp.display_pairs(),
+                    estimated_profit_pct: opp.estimated_profit_pct.value(),
+                    amount,
+                    profit_usd: result.actual_profit,
+                    profit_pct: result.actual_profit_pct,
+                    above_live_threshold: above_live,
+                });
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub fn stats(&self) -> &ShadowStats {
+        &self.stats
+    }
+
+    /// Log what loosening the threshold would have produced
+    pub fn log_summary(&self) {
+        let stats = &self.stats;
+        info!(
+            "👥 Shadow Mode (threshold {:.2}% vs live {:.2}%):",
+            self.threshold_pct, self.live_threshold_pct
+        );
+        info!(
+            "   • Simulated: {} opportunities, ${:.6} total",
+            stats.simulated, stats.simulated_profit_usd
+        );
+        info!(
+            "   • Below live threshold: {} opportunities, ${:.6} that loosening the threshold would have added",
+            stats.below_live, stats.below_live_profit_usd
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::BybitClient;
+    use crate::config::Config;
+
+    use crate::precision::PrecisionManager;
+
+    #[test]
+    fn test_shadow_filters_and_deduplicates() {
+        let client = BybitClient::new(Config::test_config()).unwrap();
+        let trader = ArbitrageTrader::new(client, true, PrecisionManager::new());
+        let mut shadow = ShadowTrader::new(0.2, 1.0);
+
+        let opps = vec![
+            ArbitrageOpportunity::test()
+                .with_pairs(&["BTCUSDT", "ETHBTC", "ETHUSDT"])
+                .with_profit_pct(1.5),
+            ArbitrageOpportunity::test()
+                .with_pairs(&["SOLUSDT", "SOLBTC", "BTCUSDT"])
+                .with_profit_pct(0.5),
+            ArbitrageOpportunity::test()
+                .with_pairs(&["XRPUSDT", "XRPBTC", "BTCUSDT"])
+                .with_profit_pct(0.1),
+        ];
+        shadow.process(&opps, &trader, 10.0, None);
+        assert_eq!(shadow.stats().simulated, 2);
+        assert_eq!(shadow.stats().below_live, 1);
+
+        // Same triangles within the cooldown are ignored
+        shadow.process(&opps, &trader, 10.0, None);
+        assert_eq!(shadow.stats().simulated, 2);
+    }
+}
@@ -0,0 +1,193 @@
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing::{error, info, warn};
+
+/// Number of Ctrl+C presses that forces an immediate exit
+const FORCE_EXIT_PRESSES: u32 = 3;
+
+/// Coordinates shutdown so that an arbitrage in progress is never abandoned mid-way.
+///
+/// The first Ctrl+C stops new executions and lets in-flight ones finish (or roll
+/// back) within the grace period, after which the bot shuts down as usual without
+/// them; the third Ctrl+C exits immediately.
+pub struct ShutdownCoordinator {
+    requested: watch::Sender<bool>,
+    presses: AtomicU32,
+    in_flight: AtomicUsize,
+    grace_period: Duration,
+    /// End of the grace period, set by the first shutdown request
+    deadline: OnceLock<Instant>,
+}
+
+/// Marks an execution as in flight until dropped
+pub struct ExecutionGuard {
+    coordinator: Arc<ShutdownCoordinator>,
+}
+
+impl Drop for ExecutionGuard {
+    fn drop(&mut self) {
+        self.coordinator.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl ShutdownCoordinator {
+    pub fn new(grace_period: Duration) -> Arc<Self> {
+        let (requested, _) = watch::channel(false);
+        Arc::new(Self {
+            requested,
+            presses: AtomicU32::new(0),
+            in_flight: AtomicUsize::new(0),
+            grace_period,
+            deadline: OnceLock::new(),
+        })
+    }
+
+    /// Listen for Ctrl+C for the lifetime of the process
+    pub fn spawn_signal_listener(self: &Arc<Self>) {
+        let coordinator = self.clone();
+        tokio::spawn(async move {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                coordinator.on_interrupt();
+            }
+        });
+    }
+
+    fn on_interrupt(self: &Arc<Self>) {
+        let presses = self.presses.fetch_add(1, Ordering::SeqCst) + 1;
+        println!(); // Newline after ^C
+
+        if presses >= FORCE_EXIT_PRESSES {
+            error!("🛑 Forced exit requested - open positions may need manual cleanup!");
+            std::process::exit(130);
+        }
+
+        if presses == 1 {
+            self.request();
+        }
+
+        let in_flight = self.in_flight();
+        if in_flight > 0 {
+            warn!(
+                "⏳ Waiting up to {}s for {in_flight} in-flight execution(s) to finish or roll back. \
+                 Press Ctrl+C {} more time(s) to force exit.",
+                self.grace_remaining().as_secs(),
+                FORCE_EXIT_PRESSES - presses
+            );
+        } else if presses > 1 {
+            warn!(
+                "🛑 Shutdown already in progress. Press Ctrl+C {} more time(s) to force exit.",
+                FORCE_EXIT_PRESSES - presses
+            );
+        }
+    }
+
+    /// Request a graceful shutdown, starting the grace period
+    pub fn request(&self) {
+        self.deadline
+            .get_or_init(|| Instant::now() + self.grace_period);
+        if !self.requested.send_replace(true) {
+            info!("🛑 Shutdown requested - no new trades will be started");
+        }
+    }
+
+    pub fn is_requested(&self) -> bool {
+        *self.requested.borrow()
+    }
+
+    /// How much longer in-flight executions may take; the full grace period until
+    /// shutdown is requested
+    pub fn grace_remaining(&self) -> Duration {
+        self.deadline.get().map_or(self.grace_period, |deadline| {
+            deadline.saturating_duration_since(Instant::now())
+        })
+    }
+
+    /// Resolves once shutdown has been requested
+    pub async fn wait_for_request(&self) {
+        let mut rx = self.requested.subscribe();
+        // The sender lives as long as self, so this cannot fail
+        let _ = rx.wait_for(|requested| *requested).await;
+    }
+
+    /// Mark the start of an execution that must not be interrupted
+    pub fn begin_execution(self: &Arc<Self>) -> ExecutionGuard {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        ExecutionGuard {
+            coordinator: self.clone(),
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Wait until no executions are in flight. Returns false on timeout
+    pub async fn wait_for_in_flight(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while self.in_flight() > 0 {
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_wakes_waiters() {
+        let coordinator = ShutdownCoordinator::new(Duration::from_secs(1));
+        assert!(!coordinator.is_requested());
+
+        let waiter = {
+            let coordinator = coordinator.clone();
+            tokio::spawn(async move { coordinator.wait_for_request().await })
+        };
+        coordinator.request();
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(coordinator.is_requested());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_grace_period_runs_from_the_first_request() {
+        let coordinator = ShutdownCoordinator::new(Duration::from_secs(10));
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert_eq!(coordinator.grace_remaining(), Duration::from_secs(10));
+
+        coordinator.request();
+        tokio::time::advance(Duration::from_secs(4)).await;
+        coordinator.request();
+        assert_eq!(coordinator.grace_remaining(), Duration::from_secs(6));
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(coordinator.grace_remaining(), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_in_flight() {
+        let coordinator = ShutdownCoordinator::new(Duration::from_secs(1));
+        let guard = coordinator.begin_execution();
+        assert_eq!(coordinator.in_flight(), 1);
+        assert!(
+            !coordinator
+                .wait_for_in_flight(Duration::from_millis(100))
+                .await
+        );
+
+        drop(guard);
+        assert!(
+            coordinator
+                .wait_for_in_flight(Duration::from_millis(100))
+                .await
+        );
+    }
+}
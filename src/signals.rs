@@ -0,0 +1,78 @@
+use crate::state::SharedState;
+use tracing::{info, warn};
+
+/// Listen for OS signals that toggle trading at runtime.
+///
+/// Unix: SIGUSR1 pauses trading (scan-only), SIGUSR2 resumes it, SIGHUP rotates the API key.
+/// Windows: Ctrl+Break toggles between the two modes.
+pub fn spawn_pause_signal_handlers(state: SharedState) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let pause_state = state.clone();
+        tokio::spawn(async move {
+            let mut usr1 = match signal(SignalKind::user_defined1()) {
+                Ok(s) => s,
+                Err(e) => {
+                    warn!("⚠️ Failed to register SIGUSR1 handler: {e}");
+                    return;
+                }
+            };
+            while usr1.recv().await.is_some() {
+                pause_state.pause("SIGUSR1");
+            }
+        });
+
+        let resume_state = state.clone();
+        tokio::spawn(async move {
+            let mut usr2 = match signal(SignalKind::user_defined2()) {
+                Ok(s) => s,
+                Err(e) => {
+                    warn!("⚠️ Failed to register SIGUSR2 handler: {e}");
+                    return;
+                }
+            };
+            while usr2.recv().await.is_some() {
+                resume_state.resume("SIGUSR2");
+            }
+        });
+
+        tokio::spawn(async move {
+            let mut hup = match signal(SignalKind::hangup()) {
+                Ok(s) => s,
+                Err(e) => {
+                    warn!("⚠️ Failed to register SIGHUP handler: {e}");
+                    return;
+                }
+            };
+            while hup.recv().await.is_some() {
+                state.request_key_rotation("SIGHUP");
+            }
+        });
+
+        info!("📡 Signals: SIGUSR1 pauses trading, SIGUSR2 resumes it, SIGHUP rotates the API key");
+    }
+
+    #[cfg(windows)]
+    {
+        tokio::spawn(async move {
+            let mut ctrl_break = match tokio::signal::windows::ctrl_break() {
+                Ok(s) => s,
+                Err(e) => {
+                    warn!("⚠️ Failed to register Ctrl+Break handler: {e}");
+                    return;
+                }
+            };
+            while ctrl_break.recv().await.is_some() {
+                if state.is_paused() {
+                    state.resume("Ctrl+Break");
+                } else {
+                    state.pause("Ctrl+Break");
+                }
+            }
+        });
+
+        info!("📡 Signals: Ctrl+Break toggles between scan-only and scan-and-trade");
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Where the ladder's current rung is kept so it survives restarts
+pub const LADDER_FILE: &str = "order_size_ladder.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct Rung {
+    order_size: f64,
+}
+
+/// Decides how much each trade commits: a fixed ORDER_SIZE, or a ladder that grows by
+/// ORDER_SIZE_STEP_PCT after each profitable trade up to ORDER_SIZE_MAX and drops back to
+/// ORDER_SIZE after a loss
+#[derive(Debug)]
+pub struct SizingPolicy {
+    start: f64,
+    /// Growth per profitable trade in percent, 0 for a fixed size
+    step_pct: f64,
+    cap: f64,
+    current: f64,
+    path: Option<PathBuf>,
+}
+
+impl SizingPolicy {
+    pub fn new(start: f64, step_pct: f64, cap: f64) -> Self {
+        Self {
+            start,
+            step_pct,
+            cap: cap.max(start),
+            current: start,
+            path: None,
+        }
+    }
+
+    /// Resume the rung reached by a previous run, kept within the configured range; a
+    /// missing or unreadable file starts from ORDER_SIZE
+    pub fn load(start: f64, step_pct: f64, cap: f64, path: &Path) -> Self {
+        let mut policy = Self::new(start, step_pct, cap);
+        if let Ok(json) = std::fs::read_to_string(path) {
+            match serde_json::from_str::<Rung>(&json) {
+                Ok(rung) if rung.order_size.is_finite() => {
+                    policy.current = rung.order_size.clamp(policy.start, policy.cap);
+                }
+                Ok(_) => warn!("⚠️ Ignoring invalid order size in {}", path.display()),
+                Err(e) => warn!("⚠️ Ignoring unreadable {}: {e}", path.display()),
+            }
+        }
+        policy.path = Some(path.to_path_buf());
+        policy
+    }
+
+    pub fn is_ladder(&self) -> bool {
+        self.step_pct > 0.0
+    }
+
+    /// Size of the next trade
+    pub fn order_size(&self) -> f64 {
+        self.current
+    }
+
+    /// Largest size a trade may ever commit
+    pub fn max_order_size(&self) -> f64 {
+        if self.is_ladder() {
+            self.cap
+        } else {
+            self.start
+        }
+    }
+
+    /// Climb a rung after a profit, go back to the start after a loss. A trade that made
+    /// nothing, e.g. one rejected before any order, leaves the size alone.
+    pub fn record(&mut self, profit_usd: f64) {
+        if !self.is_ladder() {
+            return;
+        }
+        let (next, outcome) = if profit_usd > 0.0 {
+            let grown = self.current * (1.0 + self.step_pct / 100.0);
+            (grown.min(self.cap), "profitable")
+        } else if profit_usd < 0.0 {
+            (self.start, "losing")
+        } else {
+            return;
+        };
+        if next == self.current {
+            return;
+        }
+        info!(
+            "🪜 Order size ${:.2} → ${next:.2} after a {outcome} trade",
+            self.current
+        );
+        self.current = next;
+        if let Err(e) = self.save() {
+            warn!("⚠️ Failed to save the order size ladder: {e:#}");
+        }
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        crate::persist::save_json(
+            path,
+            &Rung {
+                order_size: self.current,
+            },
+        )?;
+        debug!("💾 Saved order size ladder to {}", path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ladder_climbs_to_cap_resets_on_loss_and_survives_restart() {
+        let path = std::env::temp_dir().join(format!("ladder_test_{}.json", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let mut ladder = SizingPolicy::load(10.0, 50.0, 30.0, &path);
+        assert_eq!(ladder.order_size(), 10.0);
+
+        ladder.record(0.02);
+        assert_eq!(ladder.order_size(), 15.0);
+        ladder.record(0.0);
+        assert_eq!(ladder.order_size(), 15.0);
+        ladder.record(0.03);
+        ladder.record(0.05);
+        assert_eq!(ladder.order_size(), 30.0);
+
+        // The rung is reloaded after a restart, then a loss starts over
+        let mut ladder = SizingPolicy::load(10.0, 50.0, 30.0, &path);
+        assert_eq!(ladder.order_size(), 30.0);
+        ladder.record(-0.01);
+        assert_eq!(ladder.order_size(), 10.0);
+
+        // A lower cap after a restart clamps the saved rung
+        ladder.record(0.02);
+        assert_eq!(
+            SizingPolicy::load(10.0, 50.0, 12.0, &path).order_size(),
+            12.0
+        );
+
+        let mut fixed = SizingPolicy::new(10.0, 0.0, 0.0);
+        fixed.record(1.0);
+        assert_eq!(fixed.order_size(), 10.0);
+        assert_eq!(fixed.max_order_size(), 10.0);
+        std::fs::remove_file(&path).ok();
+    }
+}
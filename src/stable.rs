@@ -0,0 +1,183 @@
+use crate::balance::BalanceManager;
+use crate::fees::FeeSchedule;
+use crate::models::{MarketPair, Price};
+use crate::pairs::PairManager;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Coins pegged 1:1 to the dollar; a pair of two of them should trade at 1.0
+pub const STABLECOINS: &[&str] = &["USDT", "USDC", "FDUSD", "DAI"];
+
+/// Minimum time between conversions on the same pair, so one dislocation isn't traded repeatedly
+const STABLE_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// A stable pair trading away from its peg, worth converting across
+#[derive(Debug, Clone, PartialEq)]
+pub struct StableSignal {
+    pub symbol: String,
+    pub base: String,
+    pub quote: String,
+    /// "Buy" the base under the peg or "Sell" it over the peg
+    pub side: &'static str,
+    pub price: Price,
+    /// Distance from the peg after fees, in percent
+    pub net_deviation_pct: f64,
+    /// Amount to spend, in the quote coin for Buys and the base coin for Sells
+    pub amount: f64,
+}
+
+/// Scans stable-to-stable pairs for peg dislocations: buy the base below 1.0, sell it above.
+/// Separate from the triangle engine, with its own threshold and a cap on base inventory.
+pub struct StableScanner {
+    min_deviation_pct: f64,
+    max_inventory_usd: f64,
+    fees: FeeSchedule,
+    last_traded: HashMap<String, Instant>,
+}
+
+impl StableScanner {
+    pub fn new(min_deviation_pct: f64, max_inventory_usd: f64, fees: FeeSchedule) -> Self {
+        Self {
+            min_deviation_pct,
+            max_inventory_usd,
+            fees,
+            last_traded: HashMap::new(),
+        }
+    }
+
+    /// Best dislocation across all stable pairs that the balances allow trading
+    pub fn scan(
+        &self,
+        pair_manager: &PairManager,
+        balance_manager: &BalanceManager,
+        amount: f64,
+    ) -> Option<StableSignal> {
+        let now = Instant::now();
+        pair_manager
+            .get_pairs()
+            .iter()
+            .filter(|p| p.is_active && is_stable_pair(p))
+            .filter(|p| {
+                self.last_traded
+                    .get(&p.symbol)
+                    .is_none_or(|at| now.duration_since(*at) >= STABLE_COOLDOWN)
+            })
+            .filter_map(|p| self.evaluate(p, balance_manager, amount))
+            .max_by(|a, b| a.net_deviation_pct.total_cmp(&b.net_deviation_pct))
+    }
+
+    /// Start the cooldown for a pair after converting on it
+    pub fn mark_traded(&mut self, symbol: &str) {
+        self.last_traded.insert(symbol.to_string(), Instant::now());
+    }
+
+    fn evaluate(
+        &self,
+        pair: &MarketPair,
+        balance_manager: &BalanceManager,
+        amount: f64,
+    ) -> Option<StableSignal> {
+        let fee_pct = self.fees.taker(&pair.symbol) * 100.0;
+        let base_held = balance_manager.get_balance(&pair.base);
+
+        let buy_deviation = (1.0 - pair.ask_price) * 100.0 - fee_pct;
+        let sell_deviation = (pair.bid_price - 1.0) * 100.0 - fee_pct;
+
+        let (side, price, net_deviation_pct, amount) =
+            if pair.ask_price > 0.0 && buy_deviation >= self.min_deviation_pct {
+                // Don't accumulate more of the base stable than the inventory cap
+                let room = self.max_inventory_usd - base_held;
+                let spend = amount.min(room);
+                if spend <= 0.0 || balance_manager.get_balance(&pair.quote) < spend {
+                    return None;
+                }
+                ("Buy", pair.ask_price, buy_deviation, spend)
+            } else if pair.bid_price > 0.0 && sell_deviation >= self.min_deviation_pct {
+                let sell = amount.min(base_held);
+                if sell <= 0.0 {
+                    return None;
+                }
+                ("Sell", pair.bid_price, sell_deviation, sell)
+            } else {
+                return None;
+            };
+
+        if amount < pair.min_notional {
+            return None;
+        }
+
+        Some(StableSignal {
+            symbol: pair.symbol.clone(),
+            base: pair.base.clone(),
+            quote: pair.quote.clone(),
+            side,
+            price: Price::new(price),
+            net_deviation_pct,
+            amount,
+        })
+    }
+}
+
+/// Whether both coins of the pair are stablecoins, e.g. USDCUSDT
+pub fn is_stable_pair(pair: &MarketPair) -> bool {
+    STABLECOINS.contains(&pair.base.as_str()) && STABLECOINS.contains(&pair.quote.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::exchange::{MockExchange, MockMarket};
+
+    async fn managers(bid: f64, ask: f64, usdc: f64) -> (PairManager, BalanceManager) {
+        let exchange = MockExchange::new()
+            .with_market("USDCUSDT", MockMarket::new("USDC", "USDT", bid, ask))
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_balance("USDT", 1_000.0)
+            .with_balance("USDC", usdc);
+        let mut pairs = PairManager::new(Config::test_config());
+        pairs.update_pairs_and_prices(&exchange).await.unwrap();
+        let mut balances = BalanceManager::new();
+        balances.update_balances(&exchange).await.unwrap();
+        (pairs, balances)
+    }
+
+    #[tokio::test]
+    async fn test_buys_under_peg_within_inventory() {
+        let (pairs, balances) = managers(0.9970, 0.9975, 80.0).await;
+        let scanner = StableScanner::new(0.1, 100.0, FeeSchedule::flat(0.001));
+
+        let signal = scanner.scan(&pairs, &balances, 50.0).unwrap();
+        assert_eq!(signal.symbol, "USDCUSDT");
+        assert_eq!(signal.side, "Buy");
+        // Only $20 of room left under the $100 USDC cap
+        assert!((signal.amount - 20.0).abs() < 1e-9);
+        assert!((signal.net_deviation_pct - 0.15).abs() < 1e-6);
+
+        // Inventory full: nothing to buy
+        let (pairs, balances) = managers(0.9970, 0.9975, 100.0).await;
+        assert_eq!(scanner.scan(&pairs, &balances, 50.0), None);
+    }
+
+    #[tokio::test]
+    async fn test_sells_over_peg_and_respects_threshold() {
+        let (pairs, balances) = managers(1.0025, 1.0030, 30.0).await;
+        let mut scanner = StableScanner::new(0.1, 100.0, FeeSchedule::flat(0.001));
+
+        let signal = scanner.scan(&pairs, &balances, 50.0).unwrap();
+        assert_eq!(signal.side, "Sell");
+        assert!((signal.amount - 30.0).abs() < 1e-9);
+
+        // The same pair is not traded again within the cooldown
+        scanner.mark_traded("USDCUSDT");
+        assert_eq!(scanner.scan(&pairs, &balances, 50.0), None);
+
+        // Inside the fee band there is nothing to do
+        let (pairs, balances) = managers(0.9999, 1.0001, 30.0).await;
+        let scanner = StableScanner::new(0.1, 100.0, FeeSchedule::flat(0.001));
+        assert_eq!(scanner.scan(&pairs, &balances, 50.0), None);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::models::MarketPair;
+use crate::stable;
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Override key matching every stablecoin/stablecoin pair, e.g. USDCUSDT
+pub const STABLE_CLASS: &str = "stable";
+
+/// Oldest quote each pair may be priced from before its triangles are skipped
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuoteAgeLimits {
+    /// None never treats a quote as stale
+    default: Option<Duration>,
+    /// Per-symbol limits, ahead of the class and the default
+    symbols: HashMap<String, Option<Duration>>,
+    /// Limit for stablecoin/stablecoin pairs, ahead of the default
+    stable: Option<Option<Duration>>,
+}
+
+impl QuoteAgeLimits {
+    /// Limits from MAX_QUOTE_AGE_MS and `SYMBOL:MS` / `stable:MS` overrides, e.g.
+    /// `stable:60000,USDEUSDT:30000`. A limit of 0 disables the gate.
+    pub fn parse(default_ms: u64, overrides: &str) -> Result<Self> {
+        let mut limits = Self {
+            default: limit(default_ms),
+            ..Self::default()
+        };
+        for entry in overrides
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+        {
+            let (key, ms) = entry
+                .split_once(':')
+                .ok_or_else(|| anyhow!("expected SYMBOL:MS or {STABLE_CLASS}:MS, got '{entry}'"))?;
+            let ms = ms
+                .trim()
+                .parse::<u64>()
+                .with_context(|| format!("invalid quote age for {key}"))?;
+            match key.trim() {
+                key if key.eq_ignore_ascii_case(STABLE_CLASS) => limits.stable = Some(limit(ms)),
+                symbol => {
+                    limits.symbols.insert(symbol.to_uppercase(), limit(ms));
+                }
+            }
+        }
+        Ok(limits)
+    }
+
+    /// Oldest quote allowed for `pair`, None when it is never stale
+    pub fn limit_for(&self, pair: &MarketPair) -> Option<Duration> {
+        if let Some(limit) = self.symbols.get(&pair.symbol) {
+            return *limit;
+        }
+        match self.stable {
+            Some(limit) if stable::is_stable_pair(pair) => limit,
+            _ => self.default,
+        }
+    }
+
+    /// Whether a quote of this age is too old to trade `pair` on; a pair never quoted
+    /// is stale whenever a limit applies
+    pub fn is_stale(&self, pair: &MarketPair, age: Option<Duration>) -> bool {
+        self.limit_for(pair)
+            .is_some_and(|limit| age.is_none_or(|age| age > limit))
+    }
+}
+
+fn limit(ms: u64) -> Option<Duration> {
+    (ms > 0).then(|| Duration::from_millis(ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(base: &str, quote: &str) -> MarketPair {
+        MarketPair::test(base, quote, 1.0, 1.0)
+    }
+
+    #[test]
+    fn test_overrides_take_precedence_over_the_global_limit() {
+        let limits = QuoteAgeLimits::parse(2_000, "stable:60000, daiusdt:0").unwrap();
+        let secs = |s| Some(Duration::from_secs(s));
+
+        let btc = pair("BTC", "USDT");
+        assert!(!limits.is_stale(&btc, secs(1)));
+        assert!(limits.is_stale(&btc, secs(3)));
+        assert!(limits.is_stale(&btc, None));
+
+        // Stable pairs rarely move, so their quotes may be much older
+        let usdc = pair("USDC", "USDT");
+        assert!(!limits.is_stale(&usdc, secs(30)));
+        assert!(limits.is_stale(&usdc, secs(61)));
+
+        // A symbol override beats its class, and 0 turns the gate off
+        assert!(!limits.is_stale(&pair("DAI", "USDT"), None));
+
+        assert_eq!(
+            QuoteAgeLimits::parse(0, "").unwrap(),
+            QuoteAgeLimits::default()
+        );
+        assert!(QuoteAgeLimits::parse(0, "USDCUSDT").is_err());
+        assert!(QuoteAgeLimits::parse(0, "stable:soon").is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::errors::BybitError;
+use crate::exchange::ExchangeApi;
+use crate::logger::log_success;
+use anyhow::{anyhow, Result};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Delay between wallet checks while waiting for the API key to be usable
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Connectivity checks that gate startup: latency report and a wallet request
+/// that keeps retrying until the key works (e.g. the IP has been whitelisted)
+pub struct StartupChecks<'a, E: ExchangeApi + ?Sized> {
+    exchange: &'a E,
+    retry_delay: Duration,
+    max_attempts: Option<u32>,
+}
+
+impl<'a, E: ExchangeApi + ?Sized> StartupChecks<'a, E> {
+    pub fn new(exchange: &'a E) -> Self {
+        Self {
+            exchange,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            max_attempts: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Give up after this many wallet checks instead of retrying forever
+    #[cfg(test)]
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+
+    /// Run all checks, returning once the wallet endpoint answers
+    pub async fn run(&self) -> Result<()> {
+        self.check_latency().await;
+        self.wait_for_wallet_access().await
+    }
+
+    async fn check_latency(&self) {
+        info!("⚡ Checking latency to {} API...", self.exchange.name());
+        match self.exchange.check_connection().await {
+            Ok(latency) => {
+                info!("✅ API Latency: {:.2}ms", latency);
+                if latency < 50.0 {
+                    info!("🚀 Excellent connection!");
+                } else if latency < 200.0 {
+                    info!("👌 Good connection.");
+                } else {
+                    warn!("⚠️ High latency detected (>200ms).");
+                }
+            }
+            Err(e) => warn!("❌ Failed to check latency: {}", e),
+        }
+    }
+
+    async fn wait_for_wallet_access(&self) -> Result<()> {
+        info!("🔧 INIT: Verifying API connection and IP whitelist...");
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match self.exchange.get_wallet_balance(None).await {
+                Ok(_) => {
+                    log_success("Initialization", "API connection verified successfully");
+                    return Ok(());
+                }
+                Err(e) => {
+                    let error_msg = e.to_string();
+                    warn!("⚠️ API Connection Failed: {error_msg}");
+                    if BybitError::find(&e).is_some_and(|error| {
+                        error.is_unauthorized()
+                            || matches!(error, BybitError::IpNotWhitelisted { .. })
+                    }) {
+                        warn!("🚫 IP Restriction or Unauthorized detected. Please whitelist this IP in Bybit API settings.");
+                    }
+                    if self.max_attempts.is_some_and(|max| attempts >= max) {
+                        return Err(anyhow!(
+                            "API connection check failed after {attempts} attempts: {error_msg}"
+                        ));
+                    }
+                    warn!("🔄 Retrying in {} seconds...", self.retry_delay.as_secs());
+                    tokio::time::sleep(self.retry_delay).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::MockExchange;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_until_wallet_is_reachable() {
+        let exchange =
+            MockExchange::new().fail_wallet_with(&["API Error 10010: Unmatched IP", "timeout"]);
+        let started = tokio::time::Instant::now();
+
+        StartupChecks::new(&exchange).run().await.unwrap();
+
+        assert_eq!(exchange.wallet_calls(), 3);
+        assert_eq!(started.elapsed(), DEFAULT_RETRY_DELAY * 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_gives_up_after_max_attempts() {
+        let exchange = MockExchange::new().fail_wallet_with(&["HTTP error 401: Unauthorized"; 3]);
+
+        let result = StartupChecks::new(&exchange)
+            .with_retry_delay(Duration::from_secs(1))
+            .with_max_attempts(2)
+            .run()
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(exchange.wallet_calls(), 2);
+    }
+}
@@ -0,0 +1,451 @@
+use crate::approval::PendingApproval;
+use crate::availability::AvailabilitySummary;
+use crate::bandwidth::BandwidthStatus;
+use crate::endpoints::EndpointStatus;
+use crate::focus::Focus;
+use crate::latency::LatencyHistogram;
+use crate::models::ArbitrageOpportunity;
+use crate::reporting::ReportingCurrency;
+use crate::run_state::{RunEvent, RunMachine, RunState};
+use crate::utilization::UtilizationSummary;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+
+/// Prices older than this are reported as stale
+const PRICE_STALE_SECS: i64 = 30;
+/// Balances older than this are reported as stale
+const BALANCE_STALE_SECS: i64 = 600;
+
+/// Summary of an opportunity for status reporting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpportunitySummary {
+    pub path: String,
+    pub pairs: String,
+    pub profit_pct: f64,
+    pub profit_usd: f64,
+    pub seen_at: DateTime<Utc>,
+}
+
+impl From<&ArbitrageOpportunity> for OpportunitySummary {
+    fn from(opp: &ArbitrageOpportunity) -> Self {
+        Self {
+            path: opp.display_path(),
+            pairs: opp.display_pairs(),
+            profit_pct: opp.estimated_profit_pct.value(),
+            profit_usd: opp.estimated_profit_usd.value(),
+            seen_at: opp.timestamp,
+        }
+    }
+}
+
+/// Outcome of the most recent trade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeSummary {
+    pub at: DateTime<Utc>,
+    pub path: String,
+    pub success: bool,
+    pub profit_usd: f64,
+    pub profit_pct: f64,
+    pub error: Option<String>,
+}
+
+/// Outcome of the most recent API key rotation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRotationSummary {
+    pub at: DateTime<Utc>,
+    pub success: bool,
+    /// The key now signing requests, masked
+    pub active_key: String,
+    pub error: Option<String>,
+}
+
+/// Totals of the checks of journaled fills against the exchange
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReconcileStatus {
+    pub runs: u64,
+    pub legs_checked: u64,
+    /// Fills that didn't reconcile, by kind
+    pub mismatches: BTreeMap<String, u64>,
+    /// Corrections made to the session's realized profit
+    pub pnl_adjustment_usd: f64,
+    pub last_run_at: Option<DateTime<Utc>>,
+}
+
+/// Slow-changing data published by the main loop for the status endpoint
+#[derive(Debug, Clone, Default)]
+pub struct StatusSnapshot {
+    pub dry_run: bool,
+    pub balances: BTreeMap<String, f64>,
+    pub balances_updated_at: Option<DateTime<Utc>>,
+    pub last_price_update: Option<DateTime<Utc>>,
+    pub best_opportunity: Option<OpportunitySummary>,
+    pub last_trade: Option<TradeSummary>,
+    pub endpoints: Vec<EndpointStatus>,
+    pub endpoint_switches: u64,
+    pub bandwidth: BandwidthStatus,
+    pub request_latency: BTreeMap<String, LatencyHistogram>,
+    pub key_rotation: Option<KeyRotationSummary>,
+    /// Prices polled from REST because every WebSocket is down
+    pub prices_degraded: bool,
+    pub ws_connections: usize,
+    /// Currency to report profits in, None for USD
+    pub reporting: Option<ReportingCurrency>,
+    pub reconciliation: ReconcileStatus,
+    /// Symbols Bybit has stopped trading since startup
+    pub suspended_symbols: Vec<String>,
+    pub availability: AvailabilitySummary,
+    /// Coins and triangles scanning is limited to, None for the full universe
+    pub focus: Option<String>,
+    /// Net deposits less withdrawals per coin this session
+    pub external_flows: BTreeMap<String, f64>,
+    pub utilization: UtilizationSummary,
+}
+
+/// Health flags derived from the snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthFlags {
+    pub prices_fresh: bool,
+    pub balances_fresh: bool,
+    pub last_price_update_age_secs: Option<i64>,
+}
+
+/// Full status as served by /status and printed by the status command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusReport {
+    pub version: String,
+    pub mode: String,
+    #[serde(default)]
+    pub state: RunState,
+    pub dry_run: bool,
+    pub uptime_secs: u64,
+    pub cycles: u64,
+    pub trades_completed: u64,
+    pub balances: BTreeMap<String, f64>,
+    pub balances_updated_at: Option<DateTime<Utc>>,
+    pub best_opportunity: Option<OpportunitySummary>,
+    pub last_trade: Option<TradeSummary>,
+    #[serde(default)]
+    pub endpoints: Vec<EndpointStatus>,
+    #[serde(default)]
+    pub endpoint_switches: u64,
+    #[serde(default)]
+    pub bandwidth: BandwidthStatus,
+    /// Response times per REST API path
+    #[serde(default)]
+    pub request_latency: BTreeMap<String, LatencyHistogram>,
+    #[serde(default)]
+    pub key_rotation: Option<KeyRotationSummary>,
+    #[serde(default)]
+    pub prices_degraded: bool,
+    #[serde(default)]
+    pub ws_connections: usize,
+    #[serde(default)]
+    pub reporting: Option<ReportingCurrency>,
+    #[serde(default)]
+    pub reconciliation: ReconcileStatus,
+    /// Why trading waits for the exchange, e.g. a maintenance window
+    #[serde(default)]
+    pub maintenance: Option<String>,
+    #[serde(default)]
+    pub suspended_symbols: Vec<String>,
+    #[serde(default)]
+    pub availability: AvailabilitySummary,
+    #[serde(default)]
+    pub focus: Option<String>,
+    /// Net deposits less withdrawals per coin this session, not counted as profit
+    #[serde(default)]
+    pub external_flows: BTreeMap<String, f64>,
+    /// Trade held until it is approved, in trade approval mode
+    #[serde(default)]
+    pub pending_approval: Option<PendingApproval>,
+    /// Capital turnover and idle time, to tell whether size or concurrency is the lever
+    #[serde(default)]
+    pub utilization: UtilizationSummary,
+    pub health: HealthFlags,
+}
+
+/// Runtime state shared between the main loop, signal handlers and the control API
+pub struct BotState {
+    run: Mutex<RunMachine>,
+    started_at: Instant,
+    cycles: AtomicU64,
+    trades_completed: AtomicU64,
+    snapshot: Mutex<StatusSnapshot>,
+    key_rotation: Notify,
+    /// Focus change waiting for the main loop: Some(None) restores the full universe
+    focus_request: Mutex<Option<Option<Focus>>>,
+    /// Trade held by the approval gate until someone approves it
+    pending_approval: Mutex<Option<PendingApproval>>,
+}
+
+pub type SharedState = Arc<BotState>;
+
+impl BotState {
+    pub fn new() -> SharedState {
+        Arc::new(Self {
+            run: Mutex::new(RunMachine::new()),
+            started_at: Instant::now(),
+            cycles: AtomicU64::new(0),
+            trades_completed: AtomicU64::new(0),
+            snapshot: Mutex::new(StatusSnapshot::default()),
+            key_rotation: Notify::new(),
+            focus_request: Mutex::new(None),
+            pending_approval: Mutex::new(None),
+        })
+    }
+
+    fn with_machine<T>(&self, f: impl FnOnce(&mut RunMachine) -> T) -> T {
+        let mut machine = self.run.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut machine)
+    }
+
+    /// Apply a run event. Returns false if it was refused or changed nothing
+    pub fn transition(&self, event: RunEvent) -> bool {
+        self.with_machine(|machine| {
+            let from = machine.state();
+            let accepted = machine.apply(event);
+            if machine.state() != from {
+                debug!("🔀 State: {from} → {}", machine.state());
+            }
+            accepted
+        })
+    }
+
+    pub fn run_state(&self) -> RunState {
+        self.with_machine(|machine| machine.state())
+    }
+
+    /// Switch to scan-only mode once any trade in flight finishes.
+    /// Returns false if trading was already paused
+    pub fn pause(&self, source: &str) -> bool {
+        let changed = self.transition(RunEvent::Pause);
+        if changed {
+            warn!(
+                "⏸️ Trading PAUSED via {source} - scanning continues, no trades will be executed"
+            );
+        }
+        changed
+    }
+
+    /// Switch back to scan-and-trade mode, clearing any halt.
+    /// Returns false if trading was neither paused nor halted
+    pub fn resume(&self, source: &str) -> bool {
+        let changed = self.transition(RunEvent::Resume);
+        if changed {
+            warn!("▶️ Trading RESUMED via {source}");
+        }
+        changed
+    }
+
+    /// Whether trading is paused or halted, or will be once the trade in flight finishes
+    pub fn is_paused(&self) -> bool {
+        self.with_machine(|machine| machine.pause_requested() || machine.halt_reason().is_some())
+    }
+
+    /// Why trading waits for the exchange, if it does
+    pub fn maintenance(&self) -> Option<String> {
+        self.with_machine(|machine| machine.maintenance().map(str::to_string))
+    }
+
+    /// Human-readable trading mode
+    pub fn mode(&self) -> &'static str {
+        if self.is_paused() || self.maintenance().is_some() {
+            "scan-only"
+        } else {
+            "scan-and-trade"
+        }
+    }
+
+    /// Ask the key rotation task to switch to the secondary API key
+    pub fn request_key_rotation(&self, source: &str) {
+        info!("🔑 API key rotation requested via {source}");
+        self.key_rotation.notify_one();
+    }
+
+    /// Wait until a key rotation is requested
+    pub async fn key_rotation_requested(&self) {
+        self.key_rotation.notified().await;
+    }
+
+    /// Ask the main loop to limit scanning to `focus`, or to scan everything again with None
+    pub fn request_focus(&self, focus: Option<Focus>, source: &str) {
+        match &focus {
+            Some(focus) => info!("🎯 Focus on {focus} requested via {source}"),
+            None => info!("🎯 End of focus mode requested via {source}"),
+        }
+        *self.focus_request.lock().unwrap_or_else(|e| e.into_inner()) = Some(focus);
+    }
+
+    /// The latest focus change requested since the last call
+    pub fn take_focus_request(&self) -> Option<Option<Focus>> {
+        self.focus_request
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+    }
+
+    /// The trade awaiting approval, or approved and not executed yet
+    pub fn pending_approval(&self) -> Option<PendingApproval> {
+        self.pending_approval
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn set_pending_approval(&self, pending: Option<PendingApproval>) {
+        *self
+            .pending_approval
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = pending;
+    }
+
+    /// Approve trade `id` if it still awaits approval
+    pub fn approve(&self, id: u64, source: &str) -> Result<PendingApproval> {
+        let mut pending = self
+            .pending_approval
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        match pending.as_mut() {
+            Some(trade) if trade.id == id && Utc::now() <= trade.expires_at => {
+                trade.approved = true;
+                warn!("👍 Trade #{id} via {} approved via {source}", trade.path);
+                Ok(trade.clone())
+            }
+            Some(trade) if trade.id == id => bail!("Trade #{id} is no longer awaiting approval"),
+            Some(trade) => bail!("Trade #{id} is not awaiting approval (#{} is)", trade.id),
+            None => bail!("No trade is awaiting approval"),
+        }
+    }
+
+    pub fn uptime_secs(&self) -> u64 {
+        self.started_at.elapsed().as_secs()
+    }
+
+    pub fn record_cycle(&self) {
+        self.cycles.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn cycles(&self) -> u64 {
+        self.cycles.load(Ordering::Relaxed)
+    }
+
+    pub fn record_trade(&self) {
+        self.trades_completed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn trades_completed(&self) -> u64 {
+        self.trades_completed.load(Ordering::Relaxed)
+    }
+
+    /// Modify the published status snapshot
+    pub fn update_snapshot(&self, f: impl FnOnce(&mut StatusSnapshot)) {
+        if let Ok(mut snapshot) = self.snapshot.lock() {
+            f(&mut snapshot);
+        }
+    }
+
+    /// Build the full status report
+    pub fn status_report(&self) -> StatusReport {
+        let snapshot = self.snapshot.lock().map(|s| s.clone()).unwrap_or_default();
+        let now = Utc::now();
+        let age = |t: Option<DateTime<Utc>>| t.map(|t| (now - t).num_seconds());
+
+        let price_age = age(snapshot.last_price_update);
+        let balance_age = age(snapshot.balances_updated_at);
+
+        StatusReport {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            mode: self.mode().to_string(),
+            state: self.run_state(),
+            dry_run: snapshot.dry_run,
+            uptime_secs: self.uptime_secs(),
+            cycles: self.cycles(),
+            trades_completed: self.trades_completed(),
+            balances: snapshot.balances,
+            balances_updated_at: snapshot.balances_updated_at,
+            best_opportunity: snapshot.best_opportunity,
+            last_trade: snapshot.last_trade,
+            endpoints: snapshot.endpoints,
+            endpoint_switches: snapshot.endpoint_switches,
+            bandwidth: snapshot.bandwidth,
+            request_latency: snapshot.request_latency,
+            key_rotation: snapshot.key_rotation,
+            prices_degraded: snapshot.prices_degraded,
+            ws_connections: snapshot.ws_connections,
+            reporting: snapshot.reporting,
+            reconciliation: snapshot.reconciliation,
+            maintenance: self.maintenance(),
+            suspended_symbols: snapshot.suspended_symbols,
+            availability: snapshot.availability,
+            focus: snapshot.focus,
+            external_flows: snapshot.external_flows,
+            pending_approval: self.pending_approval(),
+            utilization: snapshot.utilization,
+            health: HealthFlags {
+                prices_fresh: price_age.is_some_and(|a| a <= PRICE_STALE_SECS),
+                balances_fresh: balance_age.is_some_and(|a| a <= BALANCE_STALE_SECS),
+                last_price_update_age_secs: price_age,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_and_resume_transitions() {
+        let state = BotState::new();
+        assert!(!state.is_paused());
+        assert_eq!(state.mode(), "scan-and-trade");
+
+        assert!(state.pause("test"));
+        assert!(!state.pause("test"));
+        assert!(state.is_paused());
+        assert_eq!(state.mode(), "scan-only");
+
+        assert!(state.resume("test"));
+        assert!(!state.resume("test"));
+        assert!(!state.is_paused());
+    }
+
+    #[test]
+    fn test_pause_mid_trade_takes_effect_after_it() {
+        let state = BotState::new();
+        state.transition(RunEvent::Initialized);
+        assert!(state.transition(RunEvent::ExecutionStarted));
+
+        state.pause("test");
+        assert!(state.is_paused());
+        assert_eq!(state.run_state(), RunState::Executing);
+        state.transition(RunEvent::ExecutionFinished);
+        assert_eq!(state.run_state(), RunState::Paused);
+        assert_eq!(state.status_report().state, RunState::Paused);
+    }
+
+    #[test]
+    fn test_status_report_health_flags() {
+        let state = BotState::new();
+        let report = state.status_report();
+        assert!(!report.health.prices_fresh);
+        assert!(!report.health.balances_fresh);
+
+        state.update_snapshot(|s| {
+            s.last_price_update = Some(Utc::now());
+            s.balances_updated_at = Some(Utc::now() - chrono::Duration::seconds(3600));
+            s.balances.insert("USDT".to_string(), 100.0);
+        });
+        let report = state.status_report();
+        assert!(report.health.prices_fresh);
+        assert!(!report.health.balances_fresh);
+        assert_eq!(report.balances.get("USDT"), Some(&100.0));
+    }
+}
@@ -0,0 +1,215 @@
+use crate::config::Config;
+use crate::network::http_client;
+use crate::state::StatusReport;
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+/// Query the control API of a running instance and print its status. The proxy and CA
+/// settings apply when the configuration loads.
+pub async fn run_status(port: u16, config: Option<&Config>) -> Result<()> {
+    let url = format!("http://127.0.0.1:{port}/status");
+    let builder = match config {
+        Some(config) => http_client(config)?,
+        None => reqwest::Client::builder(),
+    };
+    let client = builder.timeout(Duration::from_secs(5)).build()?;
+
+    let report: StatusReport = client
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| {
+            format!("No running bot found at {url} (is it running with CONTROL_API_PORT={port}?)")
+        })?
+        .error_for_status()?
+        .json()
+        .await
+        .context("Failed to parse status response")?;
+
+    print_report(&report);
+    Ok(())
+}
+
+fn format_uptime(secs: u64) -> String {
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    let (hours, rem) = (rem / 3600, rem % 3600);
+    let (minutes, seconds) = (rem / 60, rem % 60);
+    if days > 0 {
+        format!("{days}d {hours}h {minutes}m")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m {seconds}s")
+    } else {
+        format!("{minutes}m {seconds}s")
+    }
+}
+
+fn flag(ok: bool) -> &'static str {
+    if ok {
+        "✅"
+    } else {
+        "❌"
+    }
+}
+
+fn print_report(report: &StatusReport) {
+    println!("🤖 Bybit Triangular Arbitrage Bot v{}", report.version);
+    println!(
+        "   Mode:     {}{}",
+        report.mode,
+        if report.dry_run { " (dry run)" } else { "" }
+    );
+    println!("   State:    {}", report.state);
+    if let Some(active) = report.endpoints.iter().find(|e| e.active) {
+        println!(
+            "   Endpoint: {} ({} switch(es))",
+            active.name, report.endpoint_switches
+        );
+    }
+    if let Some(rotation) = &report.key_rotation {
+        println!(
+            "   API key:  {} ({} rotation at {})",
+            rotation.active_key,
+            if rotation.success { "last" } else { "failed" },
+            rotation.at.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+    }
+    if report.prices_degraded {
+        println!("   Prices:   ⚠️ REST polling (no WebSocket connection up)");
+    }
+    if let Some(maintenance) = &report.maintenance {
+        println!("   Exchange: 🛠️ {maintenance} - trading paused");
+    }
+    if let Some(focus) = &report.focus {
+        println!("   Focus:    🎯 {focus}");
+    }
+    if let Some(trade) = &report.pending_approval {
+        println!(
+            "   Approval: 🙋 trade #{} {:.2}% via {} {} until {}",
+            trade.id,
+            trade.profit_pct,
+            trade.path,
+            if trade.approved {
+                "approved, executing when still best"
+            } else {
+                "awaiting approval"
+            },
+            trade.expires_at.format("%H:%M:%S UTC")
+        );
+    }
+    if !report.suspended_symbols.is_empty() {
+        println!(
+            "   Halted:   {} (suspended by Bybit)",
+            report.suspended_symbols.join(", ")
+        );
+    }
+    let bandwidth = &report.bandwidth;
+    if !bandwidth.connections.is_empty() {
+        let budget = bandwidth
+            .budget_bytes_per_sec
+            .map(|b| format!(" of {:.1} KB/s budget", b / 1000.0))
+            .unwrap_or_default();
+        println!(
+            "   Feeds:    {:.1} KB/s{budget} ({} symbol(s) trimmed)",
+            bandwidth.bytes_per_sec / 1000.0,
+            bandwidth.trimmed.len()
+        );
+    }
+    println!("   Uptime:   {}", format_uptime(report.uptime_secs));
+    let availability = &report.availability;
+    if let (Some(day), Some(week)) = (availability.last_24h_pct, availability.last_7d_pct) {
+        println!("   Availability: {day:.2}% (24h) | {week:.2}% (7d)");
+    }
+    println!(
+        "   Cycles:   {} | Trades completed: {}",
+        report.cycles, report.trades_completed
+    );
+    let utilization = &report.utilization;
+    if let (Some(turnover), Some(idle)) = (
+        utilization.turnover_last_hour,
+        utilization.idle_last_hour_pct,
+    ) {
+        println!(
+            "   Capital:  ${:.2} traded in the last hour ({turnover:.2}x the account), idle {idle:.1}%",
+            utilization.notional_last_hour_usd
+        );
+    }
+
+    println!();
+    println!("💰 Balances:");
+    if report.balances.is_empty() {
+        println!("   (none loaded yet)");
+    }
+    for (coin, amount) in &report.balances {
+        println!("   {coin:<8} {amount:.8}");
+    }
+    if !report.external_flows.is_empty() {
+        println!("   External flows (not trading profit):");
+        for (coin, amount) in &report.external_flows {
+            println!("   {coin:<8} {amount:+.8}");
+        }
+    }
+
+    println!();
+    let money = |usd: f64| match &report.reporting {
+        Some(reporting) => reporting.format(usd, 6),
+        None => format!("${usd:.6}"),
+    };
+    match &report.last_trade {
+        Some(trade) => println!(
+            "📜 Last trade: {} {} at {} → {} ({:.2}%){}",
+            if trade.success { "✅" } else { "❌" },
+            trade.path,
+            trade.at.format("%Y-%m-%d %H:%M:%S UTC"),
+            money(trade.profit_usd),
+            trade.profit_pct,
+            trade
+                .error
+                .as_ref()
+                .map(|e| format!(" - {e}"))
+                .unwrap_or_default()
+        ),
+        None => println!("📜 Last trade: none this session"),
+    }
+    match &report.best_opportunity {
+        Some(opp) => println!(
+            "🏆 Best current opportunity: {:.4}% via {} ({})",
+            opp.profit_pct, opp.path, opp.pairs
+        ),
+        None => println!("🏆 Best current opportunity: none"),
+    }
+
+    println!();
+    println!("🩺 Health:");
+    println!(
+        "   {} Prices fresh{}",
+        flag(report.health.prices_fresh),
+        report
+            .health
+            .last_price_update_age_secs
+            .map(|a| format!(" (last update {a}s ago)"))
+            .unwrap_or_default()
+    );
+    println!("   {} Balances fresh", flag(report.health.balances_fresh));
+    let reconciliation = &report.reconciliation;
+    if reconciliation.runs > 0 {
+        let mismatches: u64 = reconciliation.mismatches.values().sum();
+        println!(
+            "   {} Fills reconciled ({} checked, {mismatches} mismatches, profit corrected by {})",
+            flag(mismatches == 0),
+            reconciliation.legs_checked,
+            money(reconciliation.pnl_adjustment_usd)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_uptime() {
+        assert_eq!(format_uptime(59), "0m 59s");
+        assert_eq!(format_uptime(3_661), "1h 1m 1s");
+        assert_eq!(format_uptime(90_061), "1d 1h 1m");
+    }
+}
@@ -0,0 +1,438 @@
+use crate::config::Config;
+use crate::journal::JournalLeg;
+use crate::models::ArbitrageOpportunity;
+use crate::trader::ArbitrageExecutionResult;
+use crate::writer::{RecordSink, SupervisedWriter};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS trades (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ts TEXT NOT NULL,
+    dry_run INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    pairs TEXT NOT NULL,
+    estimated_profit_pct REAL NOT NULL,
+    amount REAL NOT NULL,
+    success INTEGER NOT NULL,
+    profit_usd REAL NOT NULL,
+    profit_pct REAL NOT NULL,
+    fees_usd REAL NOT NULL,
+    dust_value_usd REAL NOT NULL,
+    execution_time_ms INTEGER NOT NULL,
+    error TEXT,
+    opportunity TEXT NOT NULL,
+    result TEXT
+);
+CREATE TABLE IF NOT EXISTS legs (
+    trade_id INTEGER NOT NULL REFERENCES trades(id),
+    order_id TEXT NOT NULL,
+    order_link_id TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    side TEXT NOT NULL,
+    qty REAL NOT NULL,
+    value REAL NOT NULL,
+    fees TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS trade_symbols (
+    trade_id INTEGER NOT NULL REFERENCES trades(id),
+    symbol TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_trades_ts ON trades(ts);
+CREATE INDEX IF NOT EXISTS idx_trade_symbols_symbol ON trade_symbols(symbol);
+";
+
+/// How `history` groups trades
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    Day,
+    Symbol,
+    Path,
+}
+
+impl GroupBy {
+    /// Parse `--by`: `day`, `symbol` or `path`
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "day" => Ok(GroupBy::Day),
+            "symbol" => Ok(GroupBy::Symbol),
+            "path" => Ok(GroupBy::Path),
+            other => Err(anyhow!(
+                "unknown grouping '{other}' (expected day, symbol or path)"
+            )),
+        }
+    }
+}
+
+/// Realized results of the trades sharing a day, symbol or path
+#[derive(Debug, Clone, PartialEq)]
+pub struct PnlRow {
+    pub key: String,
+    pub trades: u64,
+    pub successful: u64,
+    pub profit_usd: f64,
+    pub fees_usd: f64,
+}
+
+/// An execution attempt as it is stored
+struct TradeRecord {
+    ts: DateTime<Utc>,
+    opportunity: ArbitrageOpportunity,
+    amount: f64,
+    dry_run: bool,
+    result: Option<ArbitrageExecutionResult>,
+    error: Option<String>,
+    legs: Vec<JournalLeg>,
+}
+
+/// SQLite history of every execution attempt, with its fills and the opportunity behind it
+pub struct TradeStore {
+    conn: Arc<Mutex<Connection>>,
+    /// Records go to a supervised writer task, so a slow disk never stalls the scanning loop
+    writer: Option<SupervisedWriter<Box<TradeRecord>>>,
+}
+
+impl TradeStore {
+    /// Open the database with writes handed to a supervised background task
+    pub fn spawn_writer(path: &Path, restart_on_panic: bool) -> Result<Self> {
+        let store = Self::open(path)?;
+        let conn = store.conn.clone();
+        let writer = SupervisedWriter::spawn(
+            "Trade store writer",
+            restart_on_panic,
+            store.conn.clone(),
+            move || Ok(conn.clone()),
+        );
+        Ok(Self {
+            writer: Some(writer),
+            ..store
+        })
+    }
+
+    /// Open (or create) the database and its tables
+    pub fn open(path: &Path) -> Result<Self> {
+        crate::logger::create_parent_dir(path)?;
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open trade database {}", path.display()))?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)
+            .context("Failed to create the trade history tables")?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            writer: None,
+        })
+    }
+
+    /// Store an execution attempt, failed or not; failures are logged rather than
+    /// interrupting trading
+    pub fn record(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        amount: f64,
+        dry_run: bool,
+        execution: &Result<ArbitrageExecutionResult>,
+        legs: &[JournalLeg],
+    ) {
+        if let Err(e) = self.try_record(Utc::now(), opportunity, amount, dry_run, execution, legs) {
+            warn!("⚠️ Failed to store trade history: {e:#}");
+        }
+    }
+
+    fn try_record(
+        &self,
+        ts: DateTime<Utc>,
+        opportunity: &ArbitrageOpportunity,
+        amount: f64,
+        dry_run: bool,
+        execution: &Result<ArbitrageExecutionResult>,
+        legs: &[JournalLeg],
+    ) -> Result<()> {
+        let record = TradeRecord {
+            ts,
+            opportunity: opportunity.clone(),
+            amount,
+            dry_run,
+            result: execution.as_ref().ok().cloned(),
+            error: match execution {
+                Ok(r) => r.error_message.clone(),
+                Err(e) => Some(format!("{e:#}")),
+            },
+            legs: legs.to_vec(),
+        };
+        match &self.writer {
+            Some(writer) => writer.send(Box::new(record)),
+            None => insert(&self.conn, &record),
+        }
+    }
+
+    /// Wait until every trade recorded so far is in the database
+    pub async fn flush(&self) {
+        if let Some(writer) = &self.writer {
+            writer.flush().await;
+        }
+    }
+
+    /// Realized profit and fees of live or dry-run trades: days newest first, symbols and
+    /// paths most profitable first. By symbol, a trade counts toward each of its pairs.
+    pub fn pnl(&self, by: GroupBy, dry_run: bool) -> Result<Vec<PnlRow>> {
+        let (key, from) = match by {
+            GroupBy::Day => ("substr(t.ts, 1, 10)", "trades t"),
+            GroupBy::Symbol => (
+                "s.symbol",
+                "trades t JOIN trade_symbols s ON s.trade_id = t.id",
+            ),
+            GroupBy::Path => ("t.path", "trades t"),
+        };
+        let order = match by {
+            GroupBy::Day => "key DESC",
+            GroupBy::Symbol | GroupBy::Path => "profit_usd DESC",
+        };
+        let sql = format!(
+            "SELECT {key} AS key, COUNT(*), SUM(t.success), SUM(t.profit_usd) AS profit_usd,
+                SUM(t.fees_usd)
+             FROM {from} WHERE t.dry_run = ?1 GROUP BY key ORDER BY {order}"
+        );
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| anyhow!("trade database lock poisoned"))?;
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![dry_run], |row| {
+            Ok(PnlRow {
+                key: row.get(0)?,
+                trades: row.get::<_, i64>(1)? as u64,
+                successful: row.get::<_, i64>(2)? as u64,
+                profit_usd: row.get(3)?,
+                fees_usd: row.get(4)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+/// Insert a trade with its legs and symbols in one transaction
+fn insert(conn: &Mutex<Connection>, record: &TradeRecord) -> Result<()> {
+    let result = record.result.as_ref();
+    let mut conn = conn
+        .lock()
+        .map_err(|_| anyhow!("trade database lock poisoned"))?;
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO trades (ts, dry_run, path, pairs, estimated_profit_pct, amount, success,
+            profit_usd, profit_pct, fees_usd, dust_value_usd, execution_time_ms, error,
+            opportunity, result)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            record.ts.to_rfc3339(),
+            record.dry_run,
+            record.opportunity.display_path(),
+            record.opportunity.display_pairs(),
+            record.opportunity.estimated_profit_pct.value(),
+            record.amount,
+            result.is_some_and(|r| r.success),
+            result.map_or(0.0, |r| r.actual_profit),
+            result.map_or(0.0, |r| r.actual_profit_pct),
+            result.map_or(0.0, |r| r.total_fees),
+            result.map_or(0.0, |r| r.dust_value_usd),
+            result.map_or(0, |r| r.execution_time_ms) as i64,
+            record.error,
+            serde_json::to_string(&record.opportunity)?,
+            result.map(serde_json::to_string).transpose()?,
+        ],
+    )?;
+    let trade_id = tx.last_insert_rowid();
+    for leg in &record.legs {
+        tx.execute(
+            "INSERT INTO legs (trade_id, order_id, order_link_id, symbol, side, qty, value, fees)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                trade_id,
+                leg.order_id,
+                leg.order_link_id,
+                leg.symbol,
+                leg.side,
+                leg.qty,
+                leg.value,
+                serde_json::to_string(&leg.fees)?,
+            ],
+        )?;
+    }
+    for symbol in &record.opportunity.pairs {
+        tx.execute(
+            "INSERT INTO trade_symbols (trade_id, symbol) VALUES (?1, ?2)",
+            params![trade_id, symbol],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// The database connection is what the writer task writes to
+impl RecordSink<Box<TradeRecord>> for Arc<Mutex<Connection>> {
+    fn write(&mut self, record: Box<TradeRecord>, _more: bool) -> Result<()> {
+        insert(self, &record)
+    }
+}
+
+/// Print PnL from the trade database, for the mode the configuration trades in
+pub fn run_history(config: Config, by: GroupBy) -> Result<()> {
+    if config.trade_db_path.is_empty() {
+        return Err(anyhow!(
+            "TRADE_DB_PATH is empty, so no trade history is kept"
+        ));
+    }
+    let path = Path::new(&config.trade_db_path);
+    if !path.exists() {
+        return Err(anyhow!("No trade history at {}", path.display()));
+    }
+    let store = TradeStore::open(path)?;
+    let rows = store.pnl(by, config.dry_run)?;
+    let mode = if config.dry_run { "dry-run" } else { "live" };
+    if rows.is_empty() {
+        println!("No {mode} trades recorded in {}", path.display());
+        return Ok(());
+    }
+    println!("📚 {mode} trade history from {}", path.display());
+    println!(
+        "{:<32} {:>7} {:>7} {:>12} {:>10}",
+        "", "trades", "filled", "profit USD", "fees USD"
+    );
+    for row in &rows {
+        println!(
+            "{:<32} {:>7} {:>7} {:>12.4} {:>10.4}",
+            row.key, row.trades, row.successful, row.profit_usd, row.fees_usd
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    fn result(success: bool, profit: f64, fees: f64) -> ArbitrageExecutionResult {
+        ArbitrageExecutionResult {
+            schema_version: 1,
+            success,
+            initial_amount: 100.0,
+            actual_profit: profit,
+            actual_profit_pct: profit,
+            dust_value_usd: 0.0,
+            dust: BTreeMap::new(),
+            total_fees: fees,
+            execution_time_ms: 120,
+            error_message: None,
+            exchange_error: None,
+        }
+    }
+
+    #[test]
+    fn test_pnl_by_day_symbol_and_path() {
+        let store = TradeStore::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let btc = ArbitrageOpportunity::test()
+            .with_path(&["USDT", "BTC", "ETH", "USDT"])
+            .with_pairs(&["BTCUSDT", "ETHBTC", "ETHUSDT"]);
+        let sol = ArbitrageOpportunity::test()
+            .with_path(&["USDT", "SOL", "BTC", "USDT"])
+            .with_pairs(&["SOLUSDT", "SOLBTC", "BTCUSDT"]);
+        let day1 = "2026-03-01T10:00:00Z".parse().unwrap();
+        let day2 = "2026-03-02T10:00:00Z".parse().unwrap();
+        let leg = JournalLeg {
+            order_id: "1".to_string(),
+            order_link_id: "arb-1".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: "Buy".to_string(),
+            qty: 0.002,
+            value: 100.0,
+            fees: BTreeMap::from([("BTC".to_string(), 0.000002)]),
+        };
+
+        store
+            .try_record(
+                day1,
+                &btc,
+                100.0,
+                false,
+                &Ok(result(true, 0.4, 0.3)),
+                &[leg],
+            )
+            .unwrap();
+        store
+            .try_record(day2, &sol, 100.0, false, &Ok(result(true, -0.1, 0.3)), &[])
+            .unwrap();
+        store
+            .try_record(
+                day2,
+                &sol,
+                100.0,
+                false,
+                &Err(anyhow!("leg 1 rejected")),
+                &[],
+            )
+            .unwrap();
+        store
+            .try_record(day2, &btc, 100.0, true, &Ok(result(true, 5.0, 0.3)), &[])
+            .unwrap();
+
+        let by_day = store.pnl(GroupBy::Day, false).unwrap();
+        assert_eq!(by_day.len(), 2);
+        assert_eq!(by_day[0].key, "2026-03-02");
+        assert_eq!((by_day[0].trades, by_day[0].successful), (2, 1));
+        assert!((by_day[0].profit_usd + 0.1).abs() < 1e-9);
+
+        let by_symbol = store.pnl(GroupBy::Symbol, false).unwrap();
+        let btcusdt = by_symbol.iter().find(|r| r.key == "BTCUSDT").unwrap();
+        assert_eq!(btcusdt.trades, 3);
+        assert!((btcusdt.profit_usd - 0.3).abs() < 1e-9);
+        assert!((by_symbol[0].profit_usd - 0.4).abs() < 1e-9);
+
+        let by_path = store.pnl(GroupBy::Path, false).unwrap();
+        assert_eq!(by_path[0].key, "USDT → BTC → ETH → USDT");
+        assert!((by_path[0].fees_usd - 0.3).abs() < 1e-9);
+        assert_eq!(store.pnl(GroupBy::Path, true).unwrap()[0].trades, 1);
+
+        let conn = store.conn.lock().unwrap();
+        let (legs, error): (i64, String) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM legs), error FROM trades WHERE success = 0",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((legs, error.as_str()), (1, "leg 1 rejected"));
+    }
+
+    #[tokio::test]
+    async fn test_writer_task_stores_trades_by_flush() {
+        let path = std::env::temp_dir().join(format!("trades_task_test_{}.db", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let store = TradeStore::spawn_writer(&path, true).unwrap();
+        let opportunity = ArbitrageOpportunity::test();
+        for profit in [0.2, 0.3] {
+            store.record(
+                &opportunity,
+                100.0,
+                false,
+                &Ok(result(true, profit, 0.1)),
+                &[],
+            );
+        }
+        store.flush().await;
+
+        let rows = store.pnl(GroupBy::Path, false).unwrap();
+        assert_eq!(rows[0].trades, 2);
+        assert!((rows[0].profit_usd - 0.5).abs() < 1e-9);
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,103 @@
+use crate::balance::BalanceManager;
+use crate::config::Config;
+use crate::models::{ArbitrageOpportunity, TickerInfo};
+use crate::pairs::PairManager;
+use std::time::Instant;
+
+/// What a strategy sees of the market when asked for trades
+pub struct MarketView<'a> {
+    pub pairs: &'a PairManager,
+    pub balances: &'a BalanceManager,
+    /// Smallest amount a trade is sized at
+    pub min_trade_amount: f64,
+}
+
+/// A way of finding trades that reuses the bot's client, precision, balance, risk and
+/// execution subsystems. Proposed trades are triangles of market legs back to the start
+/// coin: they go through the same funding, limit and cooldown checks and are executed by
+/// the same trader as the triangular engine's.
+pub trait Strategy: Send {
+    fn name(&self) -> &str;
+
+    /// A quote arrived on the price feeds
+    fn on_quote(&mut self, _ticker: &TickerInfo) {}
+
+    /// Called once per scan cycle, whether or not prices changed
+    fn on_timer(&mut self, _now: Instant) {}
+
+    /// Trades to execute now, best first
+    fn propose_trades(&mut self, market: &MarketView) -> Vec<ArbitrageOpportunity>;
+}
+
+/// Strategies run next to the triangular engine. Add your own here.
+pub fn additional(_config: &Config) -> Vec<Box<dyn Strategy>> {
+    Vec::new()
+}
+
+/// Trades proposed by `primary`, then by each of `others` in order
+pub fn propose_all(
+    primary: &mut dyn Strategy,
+    others: &mut [Box<dyn Strategy>],
+    market: &MarketView,
+) -> Vec<ArbitrageOpportunity> {
+    let mut trades = primary.propose_trades(market);
+    for strategy in others {
+        trades.extend(strategy.propose_trades(market));
+    }
+    trades
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arbitrage::ArbitrageEngine;
+
+    /// Proposes one fixed triangle once it has seen a quote for its first symbol
+    struct Fixed {
+        quoted: bool,
+    }
+
+    impl Strategy for Fixed {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn on_quote(&mut self, ticker: &TickerInfo) {
+            self.quoted |= ticker.symbol == "USDCUSDT";
+        }
+
+        fn propose_trades(&mut self, _market: &MarketView) -> Vec<ArbitrageOpportunity> {
+            if !self.quoted {
+                return Vec::new();
+            }
+            vec![ArbitrageOpportunity::test()
+                .with_path(&["USDT", "USDC", "BTC", "USDT"])
+                .with_pairs(&["USDCUSDT", "BTCUSDC", "BTCUSDT"])
+                .with_profit_pct(0.2)]
+        }
+    }
+
+    #[test]
+    fn test_other_strategies_propose_after_the_triangular_engine() {
+        let (pairs, balances) = (
+            PairManager::new(Config::test_config()),
+            BalanceManager::new(),
+        );
+        let market = MarketView {
+            pairs: &pairs,
+            balances: &balances,
+            min_trade_amount: 10.0,
+        };
+        let mut engine = ArbitrageEngine::new();
+        let mut others: Vec<Box<dyn Strategy>> = vec![Box::new(Fixed { quoted: false })];
+        assert!(propose_all(&mut engine, &mut others, &market).is_empty());
+
+        let ticker: TickerInfo =
+            serde_json::from_value(serde_json::json!({ "symbol": "USDCUSDT" })).unwrap();
+        others.iter_mut().for_each(|s| s.on_quote(&ticker));
+        let trades = propose_all(&mut engine, &mut others, &market);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].pairs[0], "USDCUSDT");
+        assert_eq!(others[0].name(), "fixed");
+    }
+}
@@ -0,0 +1,203 @@
+use crate::maker::ExecutionStrategy;
+use crate::models::ArbitrageOpportunity;
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use tracing::info;
+
+/// Parse `EXECUTION_AB_STRATEGIES`: two or more distinct strategies separated by commas,
+/// or nothing to trade every triangle with `EXECUTION_STRATEGY`
+pub fn parse(value: &str) -> Result<Vec<ExecutionStrategy>> {
+    let mut strategies = Vec::new();
+    for name in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let strategy = ExecutionStrategy::parse(name)?;
+        if strategies.contains(&strategy) {
+            bail!("{name} is listed twice");
+        }
+        strategies.push(strategy);
+    }
+    if strategies.len() == 1 {
+        bail!("comparing needs at least two strategies");
+    }
+    Ok(strategies)
+}
+
+/// Executions of one strategy, and the edge they were estimated at and realized
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EdgeStats {
+    pub executions: u32,
+    /// Executions that failed, realizing no edge
+    pub failed: u32,
+    pub estimated_pct: f64,
+    pub realized_pct: f64,
+}
+
+impl EdgeStats {
+    fn record(&mut self, estimated_pct: f64, realized_pct: Option<f64>) {
+        self.executions += 1;
+        self.estimated_pct += estimated_pct;
+        match realized_pct {
+            Some(pct) => self.realized_pct += pct,
+            None => self.failed += 1,
+        }
+    }
+
+    fn merge(&mut self, other: &EdgeStats) {
+        self.executions += other.executions;
+        self.failed += other.failed;
+        self.estimated_pct += other.estimated_pct;
+        self.realized_pct += other.realized_pct;
+    }
+
+    pub fn avg_estimated_pct(&self) -> f64 {
+        self.estimated_pct / self.executions.max(1) as f64
+    }
+
+    /// Realized edge per execution, failures counting as none
+    pub fn avg_realized_pct(&self) -> f64 {
+        self.realized_pct / self.executions.max(1) as f64
+    }
+}
+
+/// A/B comparison of execution strategies. Each triangle's executions alternate between
+/// the strategies, so every strategy trades the same triangles at similar times, and the
+/// realized edge of each is tracked per triangle.
+#[derive(Debug, Clone)]
+pub struct StrategyAb {
+    strategies: Vec<ExecutionStrategy>,
+    /// Stats of each strategy, in the order of `strategies`, by the triangle's pairs
+    triangles: BTreeMap<String, Vec<EdgeStats>>,
+}
+
+impl StrategyAb {
+    pub fn new(strategies: Vec<ExecutionStrategy>) -> Self {
+        Self {
+            strategies,
+            triangles: BTreeMap::new(),
+        }
+    }
+
+    /// Strategy for the next execution of `opportunity`'s triangle: the one it has been
+    /// executed with least, the first listed on a tie
+    pub fn pick(&self, opportunity: &ArbitrageOpportunity) -> ExecutionStrategy {
+        let Some(stats) = self.triangles.get(&opportunity.pairs.join(",")) else {
+            return self.strategies[0];
+        };
+        let least = (0..self.strategies.len())
+            .min_by_key(|&i| stats[i].executions)
+            .unwrap_or(0);
+        self.strategies[least]
+    }
+
+    /// An execution of `opportunity` with `strategy` that sent orders, with the profit it
+    /// realized; None when it failed
+    pub fn record(
+        &mut self,
+        strategy: ExecutionStrategy,
+        opportunity: &ArbitrageOpportunity,
+        realized_pct: Option<f64>,
+    ) {
+        let Some(i) = self.strategies.iter().position(|s| *s == strategy) else {
+            return;
+        };
+        let n = self.strategies.len();
+        self.triangles
+            .entry(opportunity.pairs.join(","))
+            .or_insert_with(|| vec![EdgeStats::default(); n])[i]
+            .record(opportunity.estimated_profit_pct.value(), realized_pct);
+    }
+
+    /// Stats of each strategy across every triangle
+    pub fn totals(&self) -> Vec<(ExecutionStrategy, EdgeStats)> {
+        let mut totals = vec![EdgeStats::default(); self.strategies.len()];
+        for stats in self.triangles.values() {
+            for (total, stats) in totals.iter_mut().zip(stats) {
+                total.merge(stats);
+            }
+        }
+        self.strategies.iter().copied().zip(totals).collect()
+    }
+
+    /// Strategy with the best average realized edge on each triangle executed with every
+    /// strategy
+    pub fn best_by_triangle(&self) -> Vec<(&str, ExecutionStrategy)> {
+        self.triangles
+            .iter()
+            .filter(|(_, stats)| stats.iter().all(|s| s.executions > 0))
+            .filter_map(|(triangle, stats)| {
+                let best = (0..stats.len()).max_by(|&a, &b| {
+                    stats[a]
+                        .avg_realized_pct()
+                        .total_cmp(&stats[b].avg_realized_pct())
+                })?;
+                Some((triangle.as_str(), self.strategies[best]))
+            })
+            .collect()
+    }
+
+    pub fn log_summary(&self) {
+        info!("🆎 Execution Strategy A/B:");
+        for (strategy, stats) in self.totals() {
+            info!(
+                "   • {}: {} executions ({} failed), realized {:.3}% vs estimated {:.3}% on average",
+                strategy.as_str(),
+                stats.executions,
+                stats.failed,
+                stats.avg_realized_pct(),
+                stats.avg_estimated_pct()
+            );
+        }
+        for (triangle, strategy) in self.best_by_triangle() {
+            info!("   • Best on {triangle}: {}", strategy.as_str());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert!(parse("").unwrap().is_empty());
+        assert_eq!(
+            parse("taker, maker_first").unwrap(),
+            vec![ExecutionStrategy::Taker, ExecutionStrategy::MakerFirst]
+        );
+        assert!(parse("taker").is_err());
+        assert!(parse("taker,taker").is_err());
+        assert!(parse("taker,maker").is_err());
+    }
+
+    #[test]
+    fn test_triangles_alternate_and_edges_are_compared() {
+        let mut ab = StrategyAb::new(vec![
+            ExecutionStrategy::Taker,
+            ExecutionStrategy::MakerFirst,
+        ]);
+        let btc = ArbitrageOpportunity::test().with_profit_pct(0.3);
+        let sol = ArbitrageOpportunity::test()
+            .with_pairs(&["SOLUSDT", "SOLBTC", "BTCUSDT"])
+            .with_profit_pct(0.2);
+
+        assert_eq!(ab.pick(&btc), ExecutionStrategy::Taker);
+        ab.record(ExecutionStrategy::Taker, &btc, Some(0.1));
+        assert_eq!(ab.pick(&btc), ExecutionStrategy::MakerFirst);
+        // Each triangle alternates on its own
+        assert_eq!(ab.pick(&sol), ExecutionStrategy::Taker);
+        ab.record(ExecutionStrategy::MakerFirst, &btc, Some(0.25));
+        assert_eq!(ab.pick(&btc), ExecutionStrategy::Taker);
+        ab.record(ExecutionStrategy::Taker, &sol, None);
+
+        let totals = ab.totals();
+        assert_eq!(totals[0].0, ExecutionStrategy::Taker);
+        assert_eq!((totals[0].1.executions, totals[0].1.failed), (2, 1));
+        assert!((totals[0].1.avg_realized_pct() - 0.05).abs() < 1e-9);
+        assert!((totals[0].1.avg_estimated_pct() - 0.25).abs() < 1e-9);
+        assert!((totals[1].1.avg_realized_pct() - 0.25).abs() < 1e-9);
+
+        // Only the BTC triangle has been executed with both
+        let best = ab.best_by_triangle();
+        assert_eq!(best.len(), 1);
+        assert_eq!(best[0].1, ExecutionStrategy::MakerFirst);
+    }
+}
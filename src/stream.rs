@@ -0,0 +1,98 @@
+use crate::bandwidth::BandwidthMeter;
+use crate::crosscheck::FeedCrossCheck;
+use crate::degraded::FeedHealth;
+use crate::focus::FocusFilter;
+use crate::network::WsTransport;
+use crate::quote_delay::QuoteDelays;
+use futures_util::future::BoxFuture;
+use std::sync::Arc;
+
+/// One market-data connection, run until the process stops. It reconnects by itself;
+/// the caller supervises it and builds a new one if it exits.
+pub type StreamTask = BoxFuture<'static, ()>;
+
+/// Transport, meters and filters every market-data connection of the bot shares
+#[derive(Clone)]
+pub struct StreamTaps {
+    pub transport: WsTransport,
+    /// Bytes received, for the bandwidth budget
+    pub bandwidth: Arc<BandwidthMeter>,
+    /// Top-of-book connections up; depth and mirror connections don't count
+    pub health: Arc<FeedHealth>,
+    /// Symbols streamed while focus mode is on
+    pub focus: Arc<FocusFilter>,
+    pub cross_check: Option<Arc<FeedCrossCheck>>,
+    pub quote_delays: Option<Arc<QuoteDelays>>,
+}
+
+/// Split `symbols` into the subscriptions of as few connections as `max_per_connection`
+/// allows
+pub fn connections(symbols: &[String], max_per_connection: usize) -> Vec<Vec<String>> {
+    symbols
+        .chunks(max_per_connection.max(1))
+        .map(<[String]>::to_vec)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{ExchangeApi, MockExchange, MockMarket};
+    use std::time::Instant;
+    use tokio::sync::mpsc;
+
+    fn symbols(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_symbols_are_split_across_connections() {
+        let all = symbols(&["A", "B", "C", "D", "E"]);
+        assert_eq!(
+            connections(&all, 2),
+            vec![symbols(&["A", "B"]), symbols(&["C", "D"]), symbols(&["E"])]
+        );
+        assert_eq!(connections(&all, 10), vec![all.clone()]);
+        assert!(connections(&[], 10).is_empty());
+    }
+
+    /// The scanning loop only sees the exchange through the trait
+    async fn first_quotes<E: ExchangeApi>(exchange: &E, subscribed: &[&str]) -> Vec<String> {
+        let taps = StreamTaps {
+            transport: WsTransport::default(),
+            bandwidth: BandwidthMeter::new(),
+            health: FeedHealth::new(Instant::now()),
+            focus: FocusFilter::new(),
+            cross_check: None,
+            quote_delays: None,
+        };
+        let (tx, mut rx) = mpsc::channel(16);
+        tokio::spawn(exchange.ticker_stream(1, symbols(subscribed), tx, &taps));
+        let mut quotes = Vec::new();
+        for _ in subscribed {
+            let ticker = rx.recv().await.unwrap();
+            quotes.push(format!(
+                "{} {}/{}",
+                ticker.symbol,
+                ticker.bid1_price.unwrap(),
+                ticker.ask1_price.unwrap()
+            ));
+        }
+        quotes
+    }
+
+    #[tokio::test]
+    async fn test_ticker_stream_sends_the_subscribed_symbols() {
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_market("ETHBTC", MockMarket::new("ETH", "BTC", 0.0499, 0.05))
+            .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_560.0));
+        assert_eq!(
+            first_quotes(&exchange, &["ETHUSDT", "BTCUSDT"]).await,
+            vec!["ETHUSDT 2550/2560", "BTCUSDT 49990/50000"]
+        );
+    }
+}
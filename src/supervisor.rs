@@ -0,0 +1,130 @@
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{error, warn};
+
+/// Initial delay before restarting a crashed task
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Upper bound for the restart delay
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A task that ran at least this long is considered healthy and resets the backoff
+const HEALTHY_RUNTIME: Duration = Duration::from_secs(60);
+
+/// Exponential restart delay, reset once a task has stayed up long enough
+pub struct Backoff {
+    next: Duration,
+}
+
+impl Backoff {
+    pub fn new() -> Self {
+        Self {
+            next: INITIAL_BACKOFF,
+        }
+    }
+
+    /// Delay before restarting a task that ran for `runtime` before failing
+    pub fn next_delay(&mut self, runtime: Duration) -> Duration {
+        if runtime >= HEALTHY_RUNTIME {
+            self.next = INITIAL_BACKOFF;
+        }
+        let delay = self.next;
+        self.next = (self.next * 2).min(MAX_BACKOFF);
+        delay
+    }
+}
+
+/// Route panic messages through tracing so they reach the log file in daemon mode
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        error!("💥 PANIC: {info}");
+        default_hook(info);
+    }));
+}
+
+/// Spawn a long-running subsystem task and watch it.
+///
+/// Panics are always logged. When `restart_on_panic` is set, the task is
+/// recreated with exponential backoff; a task that returns normally is
+/// restarted as well since subsystems are expected to run forever.
+pub fn spawn_supervised<F, Fut>(name: String, restart_on_panic: bool, factory: F)
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut backoff = Backoff::new();
+
+        loop {
+            let started = Instant::now();
+            match tokio::spawn(factory()).await {
+                Ok(()) => warn!("⚠️ {name} exited unexpectedly"),
+                Err(e) if e.is_panic() => {
+                    error!("💥 {name} panicked: {}", panic_message(e.into_panic()));
+                    if !restart_on_panic {
+                        error!("❌ {name} will not be restarted (RESTART_ON_PANIC=false)");
+                        return;
+                    }
+                }
+                Err(_) => return, // Cancelled during runtime shutdown
+            }
+
+            let delay = backoff.next_delay(started.elapsed());
+            warn!("🔄 Restarting {name} in {}s", delay.as_secs());
+            tokio::time::sleep(delay).await;
+        }
+    });
+}
+
+pub fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_panicking_task_is_restarted() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+
+        spawn_supervised("test task".to_string(), true, move || {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("boom");
+                }
+                std::future::pending::<()>().await;
+            }
+        });
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_panicking_task_not_restarted_when_disabled() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+
+        spawn_supervised("test task".to_string(), false, move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                panic!("boom");
+            }
+        });
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+}
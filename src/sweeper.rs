@@ -0,0 +1,172 @@
+use crate::balance::BalanceManager;
+use crate::dust::DustInventory;
+use crate::models::Price;
+use crate::pairs::PairManager;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Coin leftovers are swept back into
+pub const SWEEP_COIN: &str = "USDT";
+
+/// Leftover coin worth converting back to USDT with a single market order
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sweep {
+    pub coin: String,
+    pub symbol: String,
+    /// "Sell" the coin on `<COIN>USDT`, or "Buy" USDT with it on `USDT<COIN>`
+    pub side: &'static str,
+    /// Amount of the coin to convert
+    pub amount: f64,
+    pub price: Price,
+    /// USDT the conversion should bring back, before fees
+    pub value: f64,
+}
+
+/// Converts the dust inventory back to USDT every `interval`. A coin is swept once its
+/// leftovers are worth an order on its USDT pair; coins worth more than `max_usd` are left
+/// alone, as they are no longer dust.
+pub struct DustSweeper {
+    interval: Duration,
+    max_usd: f64,
+    last_sweep: Instant,
+    reclaimed_usd: f64,
+}
+
+impl DustSweeper {
+    pub fn new(interval: Duration, max_usd: f64) -> Self {
+        Self {
+            interval,
+            max_usd,
+            last_sweep: Instant::now(),
+            reclaimed_usd: 0.0,
+        }
+    }
+
+    /// Whether the interval has passed since the last sweep; starts the next one if so
+    pub fn due(&mut self) -> bool {
+        if self.last_sweep.elapsed() < self.interval {
+            return false;
+        }
+        self.last_sweep = Instant::now();
+        true
+    }
+
+    /// Conversions for every coin in the inventory that can be swept now. Never more than
+    /// the wallet holds, nor anything but the leftovers of the coin.
+    pub fn plan(
+        &self,
+        inventory: &DustInventory,
+        balance_manager: &BalanceManager,
+        pair_manager: &PairManager,
+    ) -> Vec<Sweep> {
+        inventory
+            .coins()
+            .iter()
+            .filter(|(coin, _)| coin.as_str() != SWEEP_COIN)
+            .filter_map(|(coin, qty)| {
+                let amount = qty.min(balance_manager.get_balance(coin));
+                if amount <= 0.0 {
+                    return None;
+                }
+                let sweep = sweep_for(coin, amount, pair_manager)?;
+                if sweep.value > self.max_usd {
+                    debug!(
+                        "🧹 {amount:.8} {coin} is worth {:.2} USDT, above the dust sweep limit",
+                        sweep.value
+                    );
+                    return None;
+                }
+                Some(sweep)
+            })
+            .collect()
+    }
+
+    /// Count USDT a sweep brought back
+    pub fn record(&mut self, reclaimed: f64) {
+        self.reclaimed_usd += reclaimed;
+    }
+
+    /// USDT reclaimed from dust this session
+    pub fn reclaimed_usd(&self) -> f64 {
+        self.reclaimed_usd
+    }
+}
+
+/// Conversion of `amount` of `coin` on its USDT pair, if the pair trades and the amount is
+/// at least its minimum order
+fn sweep_for(coin: &str, amount: f64, pair_manager: &PairManager) -> Option<Sweep> {
+    let active = |symbol: String| pair_manager.get_pair(&symbol).filter(|p| p.is_active);
+    if let Some(pair) = active(format!("{coin}{SWEEP_COIN}")) {
+        let value = amount * pair.bid_price;
+        return (pair.bid_price > 0.0 && amount >= pair.min_qty && value >= pair.min_notional)
+            .then(|| Sweep {
+                coin: coin.to_string(),
+                symbol: pair.symbol.clone(),
+                side: "Sell",
+                amount,
+                price: Price::new(pair.bid_price),
+                value,
+            });
+    }
+    let pair = active(format!("{SWEEP_COIN}{coin}"))?;
+    let value = amount / pair.ask_price;
+    (pair.ask_price > 0.0 && value >= pair.min_qty && amount >= pair.min_notional).then(|| Sweep {
+        coin: coin.to_string(),
+        symbol: pair.symbol.clone(),
+        side: "Buy",
+        amount,
+        price: Price::new(pair.ask_price),
+        value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::exchange::{MockExchange, MockMarket};
+    use std::collections::BTreeMap;
+
+    #[tokio::test]
+    async fn test_sweeps_leftovers_worth_an_order_and_below_the_limit() {
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_market("XRPUSDT", MockMarket::new("XRP", "USDT", 0.5, 0.51))
+            .with_market("SOLUSDT", MockMarket::new("SOL", "USDT", 150.0, 150.1))
+            .with_market("ETHBTC", MockMarket::new("ETH", "BTC", 0.0499, 0.05))
+            .with_balance("BTC", 0.000_05)
+            .with_balance("XRP", 0.5)
+            .with_balance("SOL", 1.0)
+            .with_balance("ETH", 0.01)
+            .with_balance("USDT", 3.0);
+        let mut pairs = PairManager::new(Config::test_config());
+        pairs.update_pairs_and_prices(&exchange).await.unwrap();
+        let mut balances = BalanceManager::new();
+        balances.update_balances(&exchange).await.unwrap();
+        let mut inventory = DustInventory::default();
+        inventory.record(&BTreeMap::from([
+            // More than the wallet still holds
+            ("BTC".to_string(), 0.000_1),
+            // Worth 0.25 USDT, below the 1 USDT minimum order
+            ("XRP".to_string(), 0.5),
+            // Worth 150 USDT, not dust
+            ("SOL".to_string(), 1.0),
+            // No USDT pair
+            ("ETH".to_string(), 0.01),
+            ("USDT".to_string(), 3.0),
+        ]));
+
+        let sweeper = DustSweeper::new(Duration::from_secs(3600), 10.0);
+        let sweeps = sweeper.plan(&inventory, &balances, &pairs);
+        assert_eq!(sweeps.len(), 1, "{sweeps:?}");
+        assert_eq!(
+            (sweeps[0].symbol.as_str(), sweeps[0].side),
+            ("BTCUSDT", "Sell")
+        );
+        assert_eq!(sweeps[0].amount, 0.000_05);
+        assert!((sweeps[0].value - 2.4995).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,176 @@
+use crate::precision::PrecisionManager;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Compact handle of an interned currency such as BTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CurrencyId(u32);
+
+/// Compact handle of an interned symbol such as BTCUSDT
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SymbolId(u32);
+
+impl SymbolId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SymbolEntry {
+    name: Arc<str>,
+    base: CurrencyId,
+}
+
+#[derive(Debug, Default)]
+struct Tables {
+    currencies: Vec<Arc<str>>,
+    currency_ids: HashMap<Arc<str>, CurrencyId>,
+    symbols: Vec<SymbolEntry>,
+    symbol_ids: HashMap<Arc<str>, SymbolId>,
+    /// Symbol trading each pair of coins, under both orders of the coins
+    between: HashMap<(CurrencyId, CurrencyId), SymbolId>,
+}
+
+impl Tables {
+    fn intern_currency(&mut self, currency: &str) -> CurrencyId {
+        if let Some(&id) = self.currency_ids.get(currency) {
+            return id;
+        }
+        let id = CurrencyId(self.currencies.len() as u32);
+        let name: Arc<str> = currency.into();
+        self.currencies.push(name.clone());
+        self.currency_ids.insert(name, id);
+        id
+    }
+}
+
+/// Currency and symbol strings interned once when instruments are loaded, so hot paths
+/// look symbols up by their coins without building `format!("{from}{to}")` keys. One
+/// registry is shared (as `Arc<SymbolRegistry>`) by the pair manager, the arbitrage engine
+/// and the trader, so an ID means the same symbol everywhere. IDs are never reused:
+/// interning only appends, and an ID stays valid across pair refreshes.
+#[derive(Debug, Default)]
+pub struct SymbolRegistry {
+    tables: RwLock<Tables>,
+}
+
+impl SymbolRegistry {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Tables> {
+        self.tables.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Tables> {
+        self.tables.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn intern_currency(&self, currency: &str) -> CurrencyId {
+        if let Some(id) = self.currency_id(currency) {
+            return id;
+        }
+        self.write().intern_currency(currency)
+    }
+
+    /// Intern `symbol` trading `base` against `quote`
+    pub fn intern_symbol(&self, symbol: &str, base: &str, quote: &str) -> SymbolId {
+        if let Some(id) = self.symbol_id(symbol) {
+            return id;
+        }
+        let mut tables = self.write();
+        if let Some(&id) = tables.symbol_ids.get(symbol) {
+            return id;
+        }
+        let (base, quote) = (tables.intern_currency(base), tables.intern_currency(quote));
+        let id = SymbolId(tables.symbols.len() as u32);
+        let name: Arc<str> = symbol.into();
+        tables.symbols.push(SymbolEntry {
+            name: name.clone(),
+            base,
+        });
+        tables.symbol_ids.insert(name, id);
+        tables.between.entry((base, quote)).or_insert(id);
+        tables.between.entry((quote, base)).or_insert(id);
+        id
+    }
+
+    /// Intern every symbol `precision` knows the rules of
+    pub fn intern_all(&self, precision: &PrecisionManager) {
+        for (symbol, info) in precision.get_all_symbols() {
+            self.intern_symbol(symbol, &info.base_coin, &info.quote_coin);
+        }
+    }
+
+    pub fn currency_id(&self, currency: &str) -> Option<CurrencyId> {
+        self.read().currency_ids.get(currency).copied()
+    }
+
+    pub fn symbol_id(&self, symbol: &str) -> Option<SymbolId> {
+        self.read().symbol_ids.get(symbol).copied()
+    }
+
+    pub fn symbol(&self, id: SymbolId) -> Arc<str> {
+        self.read().symbols[id.index()].name.clone()
+    }
+
+    /// Number of symbols interned so far, one more than the highest SymbolId
+    pub fn symbol_count(&self) -> usize {
+        self.read().symbols.len()
+    }
+
+    /// Symbol converting `from` into `to` by coin name, and whether that sells the base
+    pub fn conversion(&self, from: &str, to: &str) -> Option<(SymbolId, bool)> {
+        let tables = self.read();
+        let from = *tables.currency_ids.get(from)?;
+        let to = *tables.currency_ids.get(to)?;
+        let id = *tables.between.get(&(from, to))?;
+        Some((id, tables.symbols[id.index()].base == from))
+    }
+
+    /// IDs of `symbols` that are interned, with their values
+    pub fn by_id<V>(&self, values: HashMap<String, V>) -> HashMap<SymbolId, V> {
+        values
+            .into_iter()
+            .filter_map(|(symbol, value)| Some((self.symbol_id(&symbol)?, value)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interning_is_stable_and_finds_symbols_by_their_coins() {
+        let registry = SymbolRegistry::new();
+        let eth_usdt = registry.intern_symbol("ETHUSDT", "ETH", "USDT");
+        let eth_btc = registry.intern_symbol("ETHBTC", "ETH", "BTC");
+        assert_eq!(registry.intern_symbol("ETHUSDT", "ETH", "USDT"), eth_usdt);
+        assert_eq!(registry.symbol_count(), 2);
+        assert_eq!(&*registry.symbol(eth_btc), "ETHBTC");
+
+        // Selling ETH for USDT sells the base, buying it back doesn't
+        assert_eq!(registry.conversion("ETH", "USDT"), Some((eth_usdt, true)));
+        assert_eq!(registry.conversion("USDT", "ETH"), Some((eth_usdt, false)));
+        assert_eq!(registry.conversion("BTC", "USDT"), None);
+        assert_eq!(registry.conversion("XRP", "USDT"), None);
+    }
+
+    #[test]
+    fn test_clones_of_the_registry_see_the_same_ids() {
+        let registry = SymbolRegistry::new();
+        let shared = Arc::clone(&registry);
+        let id = registry.intern_symbol("BTCUSDT", "BTC", "USDT");
+        assert_eq!(shared.symbol_id("BTCUSDT"), Some(id));
+        assert_eq!(
+            shared.by_id(HashMap::from([
+                ("BTCUSDT".to_string(), 1),
+                ("XRPUSDT".to_string(), 2)
+            ])),
+            HashMap::from([(id, 1)])
+        );
+    }
+}
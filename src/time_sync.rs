@@ -0,0 +1,30 @@
+use crate::client::BybitClient;
+use crate::supervisor;
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+/// Sync the client's clock with Bybit's now, then again every `interval_secs` on a
+/// supervised task, so signed requests stay inside the receive window as the local clock
+/// drifts. Requests rejected for their timestamp resync on their own either way; an
+/// `interval_secs` of 0 leaves it at that.
+pub async fn start_time_sync(client: &BybitClient, interval_secs: u64, restart_on_panic: bool) {
+    match client.sync_time().await {
+        Ok(offset) => info!("🕒 Clock synced with Bybit (offset {offset:+}ms)"),
+        Err(e) => warn!("⚠️ Failed to sync the clock with Bybit, signing with local time: {e:#}"),
+    }
+    if interval_secs == 0 {
+        return;
+    }
+    let client = client.clone();
+    supervisor::spawn_supervised("Clock sync".to_string(), restart_on_panic, move || {
+        let client = client.clone();
+        async move {
+            loop {
+                sleep(Duration::from_secs(interval_secs)).await;
+                if let Err(e) = client.sync_time().await {
+                    warn!("⚠️ Clock sync failed: {e:#}");
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,168 @@
+use crate::arbitrage::ArbitrageEngine;
+use crate::balance::BalanceManager;
+use crate::client::BybitClient;
+use crate::config::Config;
+use crate::fees::FeeSchedule;
+use crate::models::ArbitrageOpportunity;
+use crate::pairs::{PairManager, TriangleDefinition};
+use crate::precision::PrecisionManager;
+use crate::time_sync;
+use crate::trader::ArbitrageTrader;
+use anyhow::{anyhow, Context, Result};
+use std::io::{self, BufRead, Write};
+use tracing::warn;
+
+/// Price a user-specified triangle with live data, confirm, and execute it once
+pub async fn run_trade_path(
+    config: Config,
+    path: Vec<String>,
+    amount: f64,
+    assume_yes: bool,
+) -> Result<()> {
+    let client = BybitClient::new(config.clone()).context("Failed to create Bybit client")?;
+    time_sync::start_time_sync(&client, 0, false).await;
+
+    let mut precision_manager = PrecisionManager::new();
+    if let Err(e) = precision_manager
+        .load_cache_from_file("precision_cache.json")
+        .await
+    {
+        warn!("⚠️ Failed to load precision cache: {e}");
+    }
+    precision_manager
+        .initialize(&client)
+        .await
+        .context("Failed to load precision data")?;
+
+    let mut pair_manager = PairManager::new(config.clone());
+    pair_manager
+        .update_pairs_and_prices(&client)
+        .await
+        .context("Failed to fetch trading pairs and prices")?;
+
+    let triangle = pair_manager.find_triangle(&path).ok_or_else(|| {
+        anyhow!(
+            "No tradeable spot pairs connect {} on Bybit",
+            path.join(" → ")
+        )
+    })?;
+
+    let fees = FeeSchedule::load(&client, config.trading_fee_rate, config.fee_discount).await;
+    let mut engine = ArbitrageEngine::with_config(
+        config.min_profit_threshold,
+        config.max_triangles_to_scan,
+        config.effective_fee_rate(),
+    );
+    engine.set_fee_schedule(fees.clone());
+    let opportunity = engine
+        .calculate_arbitrage_profit(&triangle, amount, &pair_manager)
+        .ok_or_else(|| {
+            anyhow!(
+                "{} cannot be priced (missing quotes or a loss above 1%)",
+                path.join(" → ")
+            )
+        })?;
+    let liquid = engine.is_triangle_liquid_enough(&triangle, &pair_manager, amount);
+
+    print_plan(&triangle, &opportunity, &pair_manager, amount, liquid);
+
+    let mut balance_manager = BalanceManager::new();
+    balance_manager
+        .update_balances(&client)
+        .await
+        .context("Failed to fetch balances")?;
+    let available = balance_manager.get_balance(&path[0]);
+    if available < amount {
+        return Err(anyhow!(
+            "Insufficient {} balance: {available:.6} available, {amount:.6} required",
+            path[0]
+        ));
+    }
+
+    let dry_run = config.dry_run;
+    if dry_run {
+        println!("🧪 DRY_RUN=true - execution will be simulated");
+    } else {
+        println!("🚀 DRY_RUN=false - REAL orders will be placed");
+    }
+
+    if !assume_yes && !confirm("Execute this trade?")? {
+        println!("Trade cancelled.");
+        return Ok(());
+    }
+
+    let mut trader =
+        ArbitrageTrader::new(client, dry_run, precision_manager).with_fees(fees, config.fee_coin());
+    let result = trader.execute_arbitrage(&opportunity, amount).await?;
+
+    if result.success {
+        println!("✅ Trade completed");
+        println!(
+            "   Realized Profit: ${:.6} ({:.2}%)",
+            result.actual_profit, result.actual_profit_pct
+        );
+        println!("   Execution time: {}ms", result.execution_time_ms);
+        println!("   Total fees: ${:.6}", result.total_fees);
+    } else {
+        println!(
+            "❌ Trade failed: {}",
+            result
+                .error_message
+                .unwrap_or_else(|| "Unknown error".to_string())
+        );
+    }
+
+    if let Err(e) = trader.get_precision_manager().auto_save_cache().await {
+        warn!("⚠️ Failed to save precision cache: {e}");
+    }
+
+    Ok(())
+}
+
+/// Print each leg of the triangle with its live quote
+fn print_plan(
+    triangle: &TriangleDefinition,
+    opportunity: &ArbitrageOpportunity,
+    pair_manager: &PairManager,
+    amount: f64,
+    liquid: bool,
+) {
+    println!();
+    println!("📋 Trade Plan: {}", opportunity.display_path());
+    println!("   Amount: {amount} {}", triangle.base_currency);
+    for (step, &idx) in triangle.indices.iter().enumerate() {
+        let pair = &pair_manager.pairs[idx];
+        let side = if pair.base == triangle.path[step] {
+            "Sell"
+        } else {
+            "Buy"
+        };
+        println!(
+            "   {}. {:<4} {:<12} @ {:<14} (bid {} / ask {}, spread {:.3}%)",
+            step + 1,
+            side,
+            pair.symbol,
+            opportunity.prices[step],
+            pair.bid_price,
+            pair.ask_price,
+            pair.spread_percent
+        );
+    }
+    println!(
+        "   Estimated Profit: {:.4}% (${:.6}), after fees and slippage buffer",
+        opportunity.estimated_profit_pct, opportunity.estimated_profit_usd
+    );
+    if !liquid {
+        println!("   ⚠️ One or more legs fail the volume/spread/depth filters for this amount");
+    }
+    println!();
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    print!("{question} [y/N]: ");
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    Ok(matches!(line.trim().to_lowercase().as_str(), "y" | "yes"))
+}
@@ -1,51 +1,291 @@
+use crate::backend::TradeExecutor;
+use crate::bundle::{OrderLog, OrderRecord};
 use crate::client::BybitClient;
-use crate::models::{ArbitrageOpportunity, OrderInfo, PlaceOrderRequest};
+use crate::clock::{Clock, SystemClock};
+use crate::deadlines;
+use crate::depth::DepthBook;
+use crate::errors::BybitError;
+use crate::exchange::ExchangeApi;
+use crate::execution_stats::ExecutionStats;
+use crate::fees::FeeSchedule;
+use crate::imbalance::TopOfBook;
+use crate::instance;
+use crate::journal::{Journal, JournalEvent};
+use crate::maker::{self, ExecutionStrategy};
+use crate::models::{
+    AmendOrderRequest, ArbitrageOpportunity, Notional, OrderInfo, OrderQty, OrderState, Pct,
+    PlaceOrderRequest, PlaceOrderResult, Price, Qty, SCHEMA_VERSION,
+};
+use crate::paper::PaperTrader;
+use crate::placement::{self, InFlightOrders};
+use crate::positions::{ClosedPosition, OpenPosition, PositionManager};
 use crate::precision::PrecisionManager;
+use crate::presim::{self, SimulatedLeg, Simulation};
+use crate::resume::{Checkpoint, ExecutionState, ResumeMode};
+use crate::symbols::{SymbolId, SymbolRegistry};
+use crate::wallet_stream::WalletFeed;
 use anyhow::{Context, Result};
-use std::collections::HashMap;
-use tokio::time::{sleep, Duration};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use tokio::time::Duration;
 use tracing::{debug, error, info, warn};
-use uuid::Uuid;
+
+/// Bybit spot taker fee, for simulated fills and for estimating conversions not yet placed
+const TAKER_FEE_RATE: f64 = 0.001;
+/// How long raced leg-1 limit orders may rest before both are cancelled
+const RACE_WINDOW: Duration = Duration::from_secs(3);
+const RACE_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const MAKER_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// How long legs 2 and 3 wait for a stacked book to even out before going ahead anyway
+const IMBALANCE_MAX_DELAY: Duration = Duration::from_secs(1);
+const IMBALANCE_RECHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A leg the bot gave up on itself, rather than one Bybit rejected
+#[derive(Debug, thiserror::Error)]
+enum LegError {
+    #[error("Order book stacked {ratio:.1}x against a {side} on {symbol} (max {max_ratio})")]
+    BookImbalance {
+        ratio: f64,
+        side: String,
+        symbol: String,
+        max_ratio: f64,
+    },
+    #[error("Insufficient {coin} balance: have {available:.6}, need {required:.6} for step {step} {side} on {symbol}")]
+    InsufficientBalance {
+        coin: String,
+        available: f64,
+        required: f64,
+        step: usize,
+        side: String,
+        symbol: String,
+    },
+    #[error("Order execution timeout after {0}ms")]
+    Timeout(u128),
+}
+
+impl LegError {
+    /// The leg error anywhere in an error's chain of causes
+    fn find(error: &anyhow::Error) -> Option<&LegError> {
+        error.chain().find_map(|cause| cause.downcast_ref())
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct TradeExecution {
     pub side: String,
-    pub executed_price: f64,
-    pub executed_quantity: f64,
-    pub executed_value: f64,
+    pub executed_price: Price,
+    pub executed_quantity: Qty,
+    pub executed_value: Notional,
+    /// Charged in the received coin, so its unit depends on the side
     pub fee: f64,
+    /// Fees charged in other coins instead (e.g. a discount token), by coin
+    pub other_fees: BTreeMap<String, f64>,
 }
 
-#[derive(Debug, Clone)]
+impl TradeExecution {
+    /// Amount of the bought coin (Buys) or the quote coin (Sells) that arrived, net of fees
+    pub fn received(&self) -> f64 {
+        if self.side == "Buy" {
+            self.executed_quantity.value() - self.fee
+        } else {
+            self.executed_value.value() - self.fee
+        }
+    }
+
+    /// Amount of the quote coin (Buys) or the base coin (Sells) that was spent
+    pub fn spent(&self) -> f64 {
+        if self.side == "Buy" {
+            self.executed_value.value()
+        } else {
+            self.executed_quantity.value()
+        }
+    }
+}
+
+/// What a filled (or partially filled) order executed
+fn trade_execution(order: &OrderInfo, side: String) -> Result<TradeExecution> {
+    let executed_price = Price::new(
+        order
+            .avg_price
+            .parse()
+            .context("Failed to parse executed price")?,
+    );
+    let executed_quantity = Qty::new(
+        order
+            .cum_exec_qty
+            .parse()
+            .context("Failed to parse executed quantity")?,
+    );
+    let executed_value = Notional::new(
+        order
+            .cum_exec_value
+            .parse()
+            .context("Failed to parse executed value")?,
+    );
+    let fee: f64 = order
+        .cum_exec_fee
+        .parse()
+        .context("Failed to parse execution fee")?;
+
+    Ok(TradeExecution {
+        side,
+        executed_price,
+        executed_quantity,
+        executed_value,
+        fee,
+        other_fees: BTreeMap::new(),
+    })
+}
+
+/// Net amount an order has delivered so far
+fn order_received(order: &OrderInfo) -> f64 {
+    let parse = |s: &str| s.parse::<f64>().unwrap_or(0.0);
+    let gross = if order.side == "Buy" {
+        parse(&order.cum_exec_qty)
+    } else {
+        parse(&order.cum_exec_value)
+    };
+    gross - parse(&order.cum_exec_fee)
+}
+
+/// Amount an order has spent so far
+fn order_spent(order: &OrderInfo) -> f64 {
+    let parse = |s: &str| s.parse::<f64>().unwrap_or(0.0);
+    if order.side == "Buy" {
+        parse(&order.cum_exec_value)
+    } else {
+        parse(&order.cum_exec_qty)
+    }
+}
+
+/// Where a rollback left the triangle
+struct Rollback {
+    /// Start coin held afterwards
+    recovered: f64,
+    /// What is still held of each other coin, e.g. below a lot size or after a failed step
+    residual: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ArbitrageExecutionResult {
+    pub schema_version: u32,
     pub success: bool,
     pub initial_amount: f64,
     pub actual_profit: f64,
     pub actual_profit_pct: f64,
     pub dust_value_usd: f64,
+    /// Leftover coins the execution didn't convert, by coin
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dust: BTreeMap<String, f64>,
     pub total_fees: f64,
     pub execution_time_ms: u64,
     pub error_message: Option<String>,
+    /// What Bybit rejected the failed leg with, if it answered at all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exchange_error: Option<Box<BybitError>>,
 }
 
-pub struct ArbitrageTrader {
-    client: BybitClient,
+pub struct ArbitrageTrader<E: ExchangeApi = BybitClient> {
+    client: E,
+    clock: Arc<dyn Clock>,
     dry_run: bool,
     max_order_wait_time: Duration,
+    /// Fill deadline of the triangle leg in progress, replacing `max_order_wait_time`
+    leg_deadline: Option<Duration>,
+    /// How fast the edge of the opportunity about to be traded decays, if known
+    edge_half_life: Option<Duration>,
+    /// Largest top-of-book imbalance against a leg's side it is placed into (None disables the filter)
+    book_imbalance_max_ratio: Option<f64>,
+    /// Least USD profit a triangle must be expected to make after rounding, at its actual size
+    min_profit_usd: f64,
+    /// Top of book of the symbols about to be traded, from the local book
+    local_book: HashMap<SymbolId, TopOfBook>,
+    /// USD price of the coins about to be traded, from live pairs
+    usd_prices: HashMap<String, f64>,
+    /// Depth of the symbols about to be traded, where streamed
+    depth_books: HashMap<SymbolId, DepthBook>,
+    /// Executes dry runs instead of the fixed slippage guess, e.g. on paper
+    backend: Option<Box<dyn TradeExecutor>>,
     precision_manager: PrecisionManager,
-    /// Cache for currency pair mappings: "FROMUPTO" -> (symbol, action)
-    /// e.g., "USDCUSDT" -> ("USDCUSDT", "SELL"), "USDTUSDC" -> ("USDCUSDT", "BUY")
-    symbol_map: HashMap<String, (String, String)>,
+    /// Symbols of the precision cache by their coins, for currency conversions, in the
+    /// registry shared with the pair manager so the books handed over match by ID
+    symbols: Arc<SymbolRegistry>,
+    execution_stats: ExecutionStats,
+    /// Park the coin after a failed leg 3 when rolling back would lose at least this percentage
+    hedge_min_rollback_loss_pct: Option<f64>,
+    positions: PositionManager,
+    /// Positions parked since the caller last took them, for journaling
+    newly_parked: Vec<OpenPosition>,
+    /// Orders placed and filled since the caller last took them, for debug bundles
+    /// and the event log
+    order_log: Mutex<OrderLog>,
+    /// Starts every orderLinkId, telling this bot's orders apart from manual or other bots'
+    order_link_prefix: String,
+    /// Fee rates of each symbol, discount included, for estimates and simulated fills
+    fees: FeeSchedule,
+    /// Coin fees may be charged in instead of the received coin; fills are then split by
+    /// fee coin from their execution records
+    fee_coin: Option<String>,
+    /// Journal every order's intent is written to before the order is sent, in audit mode
+    order_audit: Option<Journal>,
+    /// Price each symbol of the execution in progress was quoted at, for order intents
+    quoted_prices: HashMap<String, Price>,
+    /// Orders sent whose outcome is still unknown, e.g. after a cancelled placement
+    in_flight: InFlightOrders,
+    /// Market orders only, or leg 1 offered as a maker order first
+    execution_strategy: ExecutionStrategy,
+    /// How long a maker leg 1 may rest before the strategy's fallback
+    maker_timeout: Duration,
+    /// Send legs 2 and 3 together in one batch request once leg 1 has filled
+    batch_legs: bool,
+    /// Where the triangle in progress is saved before each order, so a crash mid-triangle
+    /// can be resolved on the next start
+    checkpoint: Option<Checkpoint>,
+    /// Pushed wallet balances, waited on for settlement instead of polling while streaming
+    wallet: Option<Arc<WalletFeed>>,
+    /// What the order of the last failed leg filled before it was aborted, for the rollback
+    failed_fill: Option<TradeExecution>,
+    /// Whether the last leg's order was cut short and the triangle continues with its fill
+    partial_leg: bool,
 }
 
-impl ArbitrageTrader {
-    pub fn new(client: BybitClient, dry_run: bool, precision_manager: PrecisionManager) -> Self {
+impl<E: ExchangeApi> ArbitrageTrader<E> {
+    pub fn new(client: E, dry_run: bool, precision_manager: PrecisionManager) -> Self {
         let mut trader = Self {
             client,
+            clock: SystemClock::shared(),
             dry_run,
             max_order_wait_time: Duration::from_secs(30),
+            leg_deadline: None,
+            edge_half_life: None,
+            book_imbalance_max_ratio: None,
+            min_profit_usd: 0.0,
+            local_book: HashMap::new(),
+            usd_prices: HashMap::new(),
+            depth_books: HashMap::new(),
+            backend: None,
             precision_manager,
-            symbol_map: HashMap::new(),
+            symbols: SymbolRegistry::new(),
+            execution_stats: ExecutionStats::new(),
+            hedge_min_rollback_loss_pct: None,
+            positions: PositionManager::new(chrono::Duration::hours(1)),
+            newly_parked: Vec::new(),
+            order_log: Mutex::new(OrderLog::default()),
+            order_link_prefix: instance::DEFAULT_ORDER_LINK_PREFIX.to_string(),
+            fees: FeeSchedule::flat(TAKER_FEE_RATE),
+            fee_coin: None,
+            order_audit: None,
+            quoted_prices: HashMap::new(),
+            in_flight: InFlightOrders::default(),
+            execution_strategy: ExecutionStrategy::Taker,
+            maker_timeout: Duration::from_secs(2),
+            batch_legs: false,
+            checkpoint: None,
+            wallet: None,
+            failed_fill: None,
+            partial_leg: false,
         };
 
         // Initialize symbol mapping cache
@@ -53,42 +293,198 @@ impl ArbitrageTrader {
         trader
     }
 
-    /// Build the symbol mapping cache for efficient lookups
-    /// Maps "FROM+TO" -> (symbol, action) for all available trading pairs
-    fn build_symbol_map(&mut self) {
-        info!("🗺️ Building symbol mapping cache...");
-        let mut mappings = 0;
+    /// Run timeouts and polling delays on another clock
+    #[cfg(test)]
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
 
-        // Get all available symbols from precision manager
-        for (symbol, precision_info) in self.precision_manager.get_all_symbols() {
-            let base = &precision_info.base_coin;
-            let quote = &precision_info.quote_coin;
+    /// Park the intermediate coin behind a breakeven limit order, instead of rolling back,
+    /// when leg 3 fails and the rollback would lose at least `min_rollback_loss_pct`
+    pub fn with_hedging(mut self, min_rollback_loss_pct: f64) -> Self {
+        self.hedge_min_rollback_loss_pct = Some(min_rollback_loss_pct);
+        self
+    }
 
-            // Example: For symbol ETHUSDT (base=ETH, quote=USDT):
-            // - Converting ETH → USDT: key "ETHUSDT" → (ETHUSDT, Sell) - sell ETH to get USDT
-            // - Converting USDT → ETH: key "USDTETH" → (ETHUSDT, Buy) - buy ETH using USDT
+    /// Manage these parked positions, e.g. restored from a previous run
+    pub fn with_positions(mut self, positions: PositionManager) -> Self {
+        self.positions = positions;
+        self
+    }
 
-            // Map for direct conversion: FROM(base) -> TO(quote) = Sell base
-            let direct_key = format!("{base}{quote}");
-            self.symbol_map
-                .insert(direct_key.clone(), (symbol.clone(), "Sell".to_string()));
+    /// Skip leg 1, or briefly delay legs 2 and 3, while the book is stacked more than
+    /// `max_ratio` times against the leg's side
+    pub fn with_imbalance_filter(mut self, max_ratio: f64) -> Self {
+        self.book_imbalance_max_ratio = Some(max_ratio);
+        self
+    }
 
-            // Map for reverse conversion: FROM(quote) -> TO(base) = Buy base
-            let reverse_key = format!("{quote}{base}");
-            self.symbol_map
-                .insert(reverse_key.clone(), (symbol.clone(), "Buy".to_string()));
+    /// Refuse triangles expected to make less than `min_profit_usd` at the amount traded
+    pub fn with_min_profit_usd(mut self, min_profit_usd: f64) -> Self {
+        self.min_profit_usd = min_profit_usd;
+        self
+    }
 
-            mappings += 2;
-            debug!(
-                "📊 Mapped {}: {} → Sell {}, {} → Buy {}",
-                symbol, direct_key, base, reverse_key, base
-            );
+    /// Tag every orderLinkId with `prefix` instead of the default
+    pub fn with_order_link_prefix(mut self, prefix: &str) -> Self {
+        self.order_link_prefix = prefix.to_string();
+        self
+    }
+
+    /// Use the account's fee rates in estimates and simulated fills. With `fee_coin` set, each
+    /// fill's execution records tell which part of its fee left the received coin.
+    pub fn with_fees(mut self, fees: FeeSchedule, fee_coin: Option<String>) -> Self {
+        self.fees = fees;
+        self.fee_coin = fee_coin;
+        self
+    }
+
+    /// Offer leg 1 as a post-only limit at the best bid/ask, resting up to `maker_timeout`
+    /// before the strategy's fallback
+    pub fn with_execution_strategy(
+        mut self,
+        strategy: ExecutionStrategy,
+        maker_timeout: Duration,
+    ) -> Self {
+        self.execution_strategy = strategy;
+        self.maker_timeout = maker_timeout;
+        self
+    }
+
+    /// Place the next triangles with `strategy`, keeping the maker timeout
+    pub fn set_execution_strategy(&mut self, strategy: ExecutionStrategy) {
+        self.execution_strategy = strategy;
+    }
+
+    /// Send legs 2 and 3 at their pre-simulated sizes in one batch request as soon as leg 1
+    /// has filled, instead of one request per leg
+    pub fn with_batch_legs(mut self) -> Self {
+        self.batch_legs = true;
+        self
+    }
+
+    /// Wait for legs to settle on wallet pushes while the stream is up, instead of polling
+    pub fn with_wallet_feed(mut self, wallet: Arc<WalletFeed>) -> Self {
+        self.wallet = Some(wallet);
+        self
+    }
+
+    /// Write each order's intent to `journal` before sending it, and refuse to send an
+    /// order whose intent could not be written
+    pub fn with_order_audit(mut self, journal: Journal) -> Self {
+        self.order_audit = Some(journal);
+        self
+    }
+
+    /// Intern symbols into `registry`, shared with the pair manager, instead of a registry
+    /// of its own
+    pub fn with_symbols(mut self, registry: Arc<SymbolRegistry>) -> Self {
+        self.symbols = registry;
+        self.build_symbol_map();
+        self
+    }
+
+    /// Fill dry runs against the live book out of `balances`, at the fees set so far
+    pub fn with_paper_trading(self, balances: BTreeMap<String, f64>) -> Self {
+        let paper = PaperTrader::new(
+            self.precision_manager.clone(),
+            self.fees.clone(),
+            balances,
+            self.symbols.clone(),
+        );
+        self.with_backend(Box::new(paper))
+    }
+
+    /// Hand dry runs to `backend` instead of simulating them
+    pub fn with_backend(mut self, backend: Box<dyn TradeExecutor>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Save the triangle in progress to `checkpoint` before each of its orders
+    pub fn with_checkpoint(mut self, checkpoint: Checkpoint) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Record that `completed_legs` have filled and the next leg is about to spend
+    /// `held_amount`. A checkpoint that can't be written doesn't stop the trade.
+    fn save_checkpoint(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        amount: f64,
+        completed_legs: usize,
+        held_amount: f64,
+        started_at: DateTime<Utc>,
+    ) {
+        let Some(checkpoint) = &self.checkpoint else {
+            return;
+        };
+        let state = ExecutionState {
+            opportunity: opportunity.clone(),
+            amount,
+            completed_legs,
+            held_coin: opportunity.path[completed_legs].clone(),
+            held_amount,
+            started_at,
+            updated_at: Utc::now(),
+        };
+        if let Err(e) = checkpoint.save(&state) {
+            warn!("⚠️ Failed to checkpoint step {}: {e:#}", completed_legs + 1);
         }
+    }
+
+    fn clear_checkpoint(&self) {
+        if let Some(checkpoint) = &self.checkpoint {
+            checkpoint.clear();
+        }
+    }
+
+    /// Quote the legs of the opportunities about to be traded, for order intents
+    fn quote(&mut self, opportunities: &[&ArbitrageOpportunity]) {
+        self.quoted_prices = opportunities
+            .iter()
+            .flat_map(|o| o.pairs.iter().cloned().zip(o.prices.iter().copied()))
+            .collect();
+    }
+
+    /// Top of book of the symbols the next trade goes through
+    pub fn set_local_book(&mut self, book: HashMap<SymbolId, TopOfBook>) {
+        self.local_book = book;
+    }
+
+    /// USD prices of the coins the next trade goes through
+    pub fn set_usd_prices(&mut self, prices: HashMap<String, f64>) {
+        self.usd_prices = prices;
+    }
+
+    /// Depth of the symbols the next trade goes through, where streamed
+    pub fn set_depth_books(&mut self, books: HashMap<SymbolId, DepthBook>) {
+        self.depth_books = books;
+    }
+
+    /// Decay half-life of the next opportunity's edge, which caps its leg deadlines
+    pub fn set_edge_half_life(&mut self, half_life: Option<Duration>) {
+        self.edge_half_life = half_life;
+    }
+
+    fn elapsed(&self, since: tokio::time::Instant) -> Duration {
+        self.clock.now().duration_since(since)
+    }
+
+    /// Intern every symbol of the precision cache, so conversions are looked up by coin
+    fn build_symbol_map(&mut self) {
+        info!("🗺️ Building symbol mapping cache...");
+
+        // Example: for ETHUSDT (base=ETH, quote=USDT), ETH → USDT sells ETH and
+        // USDT → ETH buys it
+        self.symbols.intern_all(&self.precision_manager);
 
         info!(
             "✅ Symbol mapping complete: {} mappings for {} symbols",
-            mappings,
-            mappings / 2
+            self.symbols.symbol_count() * 2,
+            self.symbols.symbol_count()
         );
     }
 
@@ -98,27 +494,143 @@ impl ArbitrageTrader {
         opportunity: &ArbitrageOpportunity,
         amount: f64,
     ) -> Result<ArbitrageExecutionResult> {
-        let start_time = std::time::Instant::now();
+        let start_time = self.clock.now();
 
         if self.dry_run {
-            info!("🧪 DRY RUN: Simulating arbitrage execution");
-            return self.simulate_execution(opportunity, amount);
+            return self.dry_run_execution(opportunity, amount).await;
         }
 
+        self.resolve_interrupted_orders().await?;
         info!("🚀 LIVE EXECUTION: Starting arbitrage trade with ${amount:.2}");
+        self.quote(&[opportunity]);
         info!(
             "📊 Path: {} → {} → {} → {}",
             opportunity.path[0], opportunity.path[1], opportunity.path[2], opportunity.path[3]
         );
 
+        let simulation = match self.presimulate(opportunity, amount, start_time) {
+            Ok(simulation) => simulation,
+            Err(rejected) => return Ok(rejected),
+        };
+        let result = self
+            .run_legs(opportunity, amount, &simulation, start_time, None)
+            .await;
+        self.clear_checkpoint();
+        result
+    }
+
+    /// Size every leg with the exchange's rounding up front; the failed result if that alone
+    /// eats the edge or leaves less than the USD profit floor, or the opportunity has expired
+    fn presimulate(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        amount: f64,
+        start_time: tokio::time::Instant,
+    ) -> std::result::Result<Simulation, ArbitrageExecutionResult> {
+        if opportunity.is_expired(Utc::now()) {
+            warn!(
+                "⌛ Refusing to execute {}: the opportunity has expired",
+                opportunity.display_path()
+            );
+            return Err(self.rejection(amount, start_time, "Opportunity expired".to_string()));
+        }
+        // The start coin's USD price, so the floor holds for non-stable funding too; without
+        // one the floor can't be checked
+        let start = &opportunity.path[0];
+        let start_usd = self.usd_prices.get(start).copied();
+        if start_usd.is_none() && self.min_profit_usd > 0.0 {
+            warn!(
+                "⛔ Refusing to execute {}: no live USD price for {start}",
+                opportunity.display_path()
+            );
+            return Err(self.rejection(amount, start_time, format!("No USD price for {start}")));
+        }
+        let profit_usd = |simulation: &Simulation| {
+            start_usd.map_or(0.0, |price| amount * price * simulation.profit_pct / 100.0)
+        };
+        match presim::simulate(
+            &self.precision_manager,
+            opportunity,
+            amount,
+            &self.fees,
+            self.execution_strategy,
+        ) {
+            Ok(simulation)
+                if simulation.profit_pct > 0.0
+                    && profit_usd(&simulation) >= self.min_profit_usd =>
+            {
+                info!(
+                    "🧮 Pre-simulation: {:.4}% after rounding ({:.4}pp lost to rounding)",
+                    simulation.profit_pct,
+                    simulation.rounding_cost_pct()
+                );
+                Ok(simulation)
+            }
+            outcome => {
+                let reason = match outcome {
+                    Ok(simulation) if simulation.profit_pct > 0.0 => format!(
+                        "${:.4} expected on {amount:.2} is below MIN_PROFIT_USD ${:.2}",
+                        profit_usd(&simulation),
+                        self.min_profit_usd
+                    ),
+                    Ok(simulation) => format!(
+                        "rounding leaves {:.4}% ({:.4}pp lost to rounding)",
+                        simulation.profit_pct,
+                        simulation.rounding_cost_pct()
+                    ),
+                    Err(e) => format!("{e:#}"),
+                };
+                warn!(
+                    "⛔ Pre-simulation rejected {}: {reason}",
+                    opportunity.display_path()
+                );
+                Err(self.rejection(amount, start_time, format!("Pre-simulation: {reason}")))
+            }
+        }
+    }
+
+    /// Result of a triangle refused before any order was placed
+    fn rejection(
+        &self,
+        amount: f64,
+        start_time: tokio::time::Instant,
+        reason: String,
+    ) -> ArbitrageExecutionResult {
+        ArbitrageExecutionResult {
+            schema_version: SCHEMA_VERSION,
+            success: false,
+            initial_amount: amount,
+            actual_profit: 0.0,
+            actual_profit_pct: 0.0,
+            dust_value_usd: 0.0,
+            dust: BTreeMap::new(),
+            total_fees: 0.0,
+            execution_time_ms: self.elapsed(start_time).as_millis() as u64,
+            error_message: Some(reason),
+            exchange_error: None,
+        }
+    }
+
+    /// Execute the legs of a pre-simulated triangle, starting from an already filled leg 1 if given
+    async fn run_legs(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        mut amount: f64,
+        simulation: &Simulation,
+        start_time: tokio::time::Instant,
+        mut leg1: Option<TradeExecution>,
+    ) -> Result<ArbitrageExecutionResult> {
         let mut executions: Vec<TradeExecution> = Vec::new();
         let mut current_amount = amount;
         let mut total_fees = 0.0;
-        let mut dust_assets: HashMap<String, f64> = HashMap::new();
+        let mut dust_assets: BTreeMap<String, f64> = BTreeMap::new();
         let mut dust_value_usd = 0.0;
+        let started_at = Utc::now();
 
         // Track confirmed balance to avoid redundant API calls
         let mut confirmed_balance: Option<f64> = None;
+        // Orders of legs already sent in a batch, by step
+        let mut staged: [Option<String>; 3] = Default::default();
 
         // Pre-fetch balance for Step 1 if not dry run - REMOVED for latency optimization
         // We trust the main loop's balance check or let the order fail if insufficient
@@ -130,28 +642,21 @@ impl ArbitrageTrader {
 
         // Execute each step of the arbitrage
         for (step, pair_symbol) in opportunity.pairs.iter().enumerate() {
-            // Check if execution is taking too long (abort after 10 seconds to prevent stale prices)
-            if start_time.elapsed() > Duration::from_secs(10) {
-                error!(
-                    "❌ Aborting arbitrage: execution time exceeded 10 seconds (current: {}ms)",
-                    start_time.elapsed().as_millis()
-                );
-                return Ok(ArbitrageExecutionResult {
-                    success: false,
-                    initial_amount: amount,
-                    actual_profit: current_amount - amount,
-                    actual_profit_pct: ((current_amount - amount) / amount) * 100.0,
-                    dust_value_usd,
-                    total_fees,
-                    execution_time_ms: start_time.elapsed().as_millis() as u64,
-                    error_message: Some(
-                        "Execution timeout - market conditions may have changed".to_string(),
-                    ),
-                });
-            }
+            // Give the leg as long as its fills usually take, unless the edge decays faster
+            let deadline = deadlines::leg_deadline(
+                self.execution_stats
+                    .fill_latency_percentile(pair_symbol, 0.95),
+                self.edge_half_life,
+            );
+            debug!(
+                "⏱️ Step {}: {}ms to fill on {pair_symbol}",
+                step + 1,
+                deadline.as_millis()
+            );
 
-            // For steps 2 and 3, verify we have the balance from the previous step
-            if step > 0 {
+            // For steps 2 and 3, verify we have the balance from the previous step, unless the
+            // leg was already sent in a batch and may have spent it
+            if step > 0 && staged[step].is_none() {
                 let bal = self
                     .wait_for_balance_settlement(step + 1, opportunity)
                     .await?;
@@ -159,72 +664,91 @@ impl ArbitrageTrader {
             }
 
             // Use the actual amount we have from the previous step
-            let trade_amount = current_amount;
+            let mut trade_amount = current_amount;
 
-            match self
-                .execute_trade_step(
-                    step + 1,
-                    pair_symbol,
-                    trade_amount,
-                    confirmed_balance,
-                    opportunity,
-                )
-                .await
-            {
+            // A raced leg 1 has already filled; every other leg is about to be sent
+            if step > 0 || leg1.is_none() {
+                self.save_checkpoint(opportunity, amount, step, trade_amount, started_at);
+            }
+            self.leg_deadline = Some(deadline);
+            if step == 1 && self.batch_legs {
+                if let [_, leg2, leg3] = simulation.legs.as_slice() {
+                    if leg2.quantity.value() <= trade_amount {
+                        [staged[1], staged[2]] = self.stage_legs([leg2, leg3]).await;
+                    }
+                }
+            }
+            let staged_fill = match staged[step].take() {
+                Some(order_id) => {
+                    let side = simulation.legs[step].side.clone();
+                    match self.await_staged(&order_id, pair_symbol, side).await {
+                        Ok(execution) => Some(execution),
+                        Err(e) => {
+                            warn!(
+                                "⚠️ Step {}: batched order {order_id} failed ({e:#}) - placing it again",
+                                step + 1
+                            );
+                            None
+                        }
+                    }
+                }
+                None => None,
+            };
+            let outcome = match leg1.take().or(staged_fill) {
+                Some(execution) => Ok(execution),
+                None => {
+                    self.execute_trade_step(
+                        step + 1,
+                        pair_symbol,
+                        trade_amount,
+                        confirmed_balance,
+                        opportunity,
+                        simulation.legs.get(step),
+                    )
+                    .await
+                }
+            };
+            self.leg_deadline = None;
+            match outcome {
                 Ok(execution) => {
-                    // Calculate dust (unused balance)
-                    let used_amount = if execution.side == "Buy" {
-                        execution.executed_value // Quote currency used
-                    } else {
-                        execution.executed_quantity // Base currency used
-                    };
+                    if std::mem::take(&mut self.partial_leg) && step == 0 {
+                        // The unfilled rest of the start coin never left: the triangle is
+                        // just smaller
+                        amount = execution.spent();
+                        trade_amount = amount;
+                        warn!(
+                            "✂️ Continuing with {amount:.8} {} after a partial leg 1",
+                            opportunity.path[0]
+                        );
+                    }
 
-                    let dust = trade_amount - used_amount;
+                    // Calculate dust (unused balance)
+                    let dust = trade_amount - execution.spent();
                     if dust > 0.00000001 {
                         // Ignore tiny floating point errors
                         let currency = &opportunity.path[step];
                         *dust_assets.entry(currency.clone()).or_insert(0.0) += dust;
 
-                        // Estimate USD value of dust
-                        let estimated_value = if step == 0 {
-                            // Dust is in start currency (e.g. USDT)
-                            dust
-                        } else if step == 2 {
-                            // Dust is in 3rd currency (e.g. MET), about to be converted to start (USDT)
-                            // Step 3 trade is MET -> USDT.
-                            if execution.side == "Sell" {
-                                dust * execution.executed_price
-                            } else {
-                                dust / execution.executed_price
-                            }
-                        } else {
-                            // Step 2 dust (e.g. USDC).
-                            // Use implied price from Step 1 execution to convert to USDT
-                            if let Some(prev_exec) = executions.last() {
-                                if prev_exec.executed_quantity > 0.0 {
-                                    // Implied rate: USDT / USDC
-                                    let rate =
-                                        prev_exec.executed_value / prev_exec.executed_quantity;
-                                    dust * rate
-                                } else {
-                                    0.0
-                                }
-                            } else {
-                                0.0
+                        // Valued at the coin's live USD price
+                        match self.usd_prices.get(currency) {
+                            Some(price) => {
+                                let value = dust * price;
+                                dust_value_usd += value;
+                                info!("🧹 Leftover dust: {dust:.8} {currency} (≈${value:.4})");
                             }
-                        };
-                        dust_value_usd += estimated_value;
-
-                        info!("🧹 Leftover dust: {dust:.8} {currency} (≈${estimated_value:.4})");
+                            None => warn!(
+                                "🧹 Leftover dust: {dust:.8} {currency} - no live USD price, left out of the dust value"
+                            ),
+                        }
                     }
 
                     // For each step, calculate what amount we actually have in the target currency
                     // If we Bought (Base), we have executed_quantity
                     // If we Sold (Base), we have executed_value (Quote)
                     let received_amount = if execution.side == "Buy" {
-                        execution.executed_quantity
+                        execution.executed_quantity.value()
                     } else {
-                        execution.executed_value
+                        execution.executed_value.value()
                     };
 
                     // Account for potential small rounding differences/fees not included in qty
@@ -246,52 +770,157 @@ impl ArbitrageTrader {
                     executions.push(execution);
                 }
                 Err(e) => {
-                    let error_str = e.to_string();
+                    let error_str = format!("{e:#}");
                     error!("❌ Step {} failed: {}", step + 1, error_str);
+                    let exchange_error = BybitError::find(&e).cloned().map(Box::new);
 
                     // Categorize the error for better handling
-                    let error_category = if error_str.contains("170348") {
-                        "Geographical/API restriction"
-                    } else if error_str.contains("insufficient") || error_str.contains("balance") {
-                        "Insufficient balance"
-                    } else if error_str.contains("Order quantity has too many decimals") {
-                        "Precision error"
-                    } else if error_str.contains("timeout") {
-                        "Timeout error"
-                    } else {
-                        "Unknown error"
+                    let error_category = match (LegError::find(&e), exchange_error.as_deref()) {
+                        (Some(LegError::BookImbalance { .. }), _) => "Book imbalance",
+                        (Some(LegError::InsufficientBalance { .. }), _)
+                        | (_, Some(BybitError::InsufficientBalance { .. })) => {
+                            "Insufficient balance"
+                        }
+                        (Some(LegError::Timeout(_)), _) => "Timeout error",
+                        (_, Some(BybitError::GeoRestricted { .. })) => {
+                            "Geographical/API restriction"
+                        }
+                        (_, Some(BybitError::PrecisionTooLong { .. })) => "Precision error",
+                        (_, Some(BybitError::RateLimited { .. })) => "Rate limited",
+                        _ => "Unknown error",
                     };
 
-                    info!("🔍 Error category: {}", error_category);
+                    match &exchange_error {
+                        Some(error) => {
+                            info!(
+                                "🔍 Error category: {error_category} (retCode {})",
+                                error.code()
+                            )
+                        }
+                        None => info!("🔍 Error category: {}", error_category),
+                    }
+                    // The failed leg's order may have filled part before it was aborted: that
+                    // part is held in the next coin, and no longer in this one
+                    let mut held =
+                        BTreeMap::from([(opportunity.path[step].clone(), current_amount)]);
+                    let mut completed = executions.len();
+                    if let Some(partial) = self.failed_fill.take() {
+                        warn!(
+                            "🛑 Step {} filled {:.8} {} before it was aborted - rolling that back too",
+                            step + 1,
+                            partial.received(),
+                            opportunity.path[step + 1]
+                        );
+                        *held.entry(opportunity.path[step].clone()).or_insert(0.0) -=
+                            partial.spent();
+                        *held
+                            .entry(opportunity.path[step + 1].clone())
+                            .or_insert(0.0) += partial.received();
+                        total_fees += partial.fee;
+                        completed = step + 1;
+                    }
+
+                    // Leg 3 went out in the batch before leg 2 filled, and may have sold an
+                    // existing balance: stop it and count its fill, so the rollback buys it back
+                    if let Some(order_id) = staged[2].take() {
+                        let symbol = &opportunity.pairs[2];
+                        warn!("⚠️ Leg 3 was already sent in the batch (order {order_id}) - aborting it");
+                        if let Some(order) = self.abort_order(&order_id, symbol).await {
+                            *held.entry(opportunity.path[2].clone()).or_insert(0.0) -=
+                                order_spent(&order);
+                            *held.entry(opportunity.path[3].clone()).or_insert(0.0) +=
+                                order_received(&order);
+                            if let Ok(fill) = self.record_fill(&order, order.side.clone()).await {
+                                total_fees += fill.fee;
+                            }
+                            completed = 3;
+                        }
+                    }
+
+                    // Leg 3 failed: parking may beat realizing the rollback loss
+                    if step == 2 {
+                        let unsold = held[&opportunity.path[2]];
+                        if let Some(position) = self.try_park(opportunity, amount, unsold).await {
+                            return Ok(ArbitrageExecutionResult {
+                                schema_version: SCHEMA_VERSION,
+                                success: false,
+                                initial_amount: amount,
+                                actual_profit: 0.0,
+                                actual_profit_pct: 0.0,
+                                dust_value_usd,
+                                dust: dust_assets,
+                                total_fees,
+                                execution_time_ms: self.elapsed(start_time).as_millis() as u64,
+                                error_message: Some(format!(
+                                    "{error_category}: {error_str} (parked {} {} behind a {} limit @ {})",
+                                    position.quantity,
+                                    position.coin,
+                                    position.side,
+                                    position.limit_price
+                                )),
+                                exchange_error: exchange_error.clone(),
+                            });
+                        }
+                    }
 
-                    // Try to rollback previous trades if possible
-                    if !executions.is_empty() {
+                    // Try to rollback previous trades if possible. The start coin held
+                    // afterwards is what the trade realized; without a rollback that is what
+                    // leg 1 never spent.
+                    let mut recovered = held.get(&opportunity.path[0]).copied().unwrap_or(0.0);
+                    let mut residual_note = String::new();
+                    if completed > 0 {
                         warn!("🔄 Attempting to rollback previous trades...");
-                        if let Err(rollback_err) =
-                            self.rollback_trades(&executions, opportunity).await
-                        {
-                            error!("❌ Rollback failed: {}", rollback_err);
-                        } else {
-                            warn!("✅ Rollback completed successfully");
+                        match self.rollback_legs(completed, opportunity, held).await {
+                            Ok(rollback) if rollback.residual.is_empty() => {
+                                recovered = rollback.recovered;
+                                warn!("✅ Rollback completed successfully")
+                            }
+                            Ok(Rollback {
+                                recovered: start_coin,
+                                residual,
+                            }) => {
+                                recovered = start_coin;
+                                // Left over like dust, so it shows up in the trade record
+                                let mut coins = Vec::new();
+                                for (coin, left) in residual {
+                                    coins.push(format!("{left:.8} {coin}"));
+                                    if let Some(price) = self.usd_prices.get(&coin) {
+                                        dust_value_usd += left * price;
+                                    }
+                                    *dust_assets.entry(coin).or_insert(0.0) += left;
+                                }
+                                residual_note = format!(" (still holding {})", coins.join(", "));
+                            }
+                            Err(rollback_err) => error!("❌ Rollback failed: {}", rollback_err),
                         }
                     }
 
                     return Ok(ArbitrageExecutionResult {
+                        schema_version: SCHEMA_VERSION,
                         success: false,
                         initial_amount: amount,
-                        actual_profit: current_amount - amount,
-                        actual_profit_pct: ((current_amount - amount) / amount) * 100.0,
+                        actual_profit: recovered - amount,
+                        actual_profit_pct: ((recovered - amount) / amount) * 100.0,
                         dust_value_usd,
+                        dust: dust_assets,
                         total_fees,
-                        execution_time_ms: start_time.elapsed().as_millis() as u64,
-                        error_message: Some(format!("{error_category}: {error_str}")),
+                        execution_time_ms: self.elapsed(start_time).as_millis() as u64,
+                        error_message: Some(format!(
+                            "{error_category}: {error_str}{residual_note}"
+                        )),
+                        exchange_error,
                     });
                 }
             }
         }
 
-        let execution_time = start_time.elapsed().as_millis() as u64;
-        let actual_profit = current_amount - amount;
+        // Fees paid in another coin came out of its balance, not out of the legs
+        let other_fees_value = self
+            .other_fees_value(&executions, &opportunity.path[0])
+            .await;
+        total_fees += other_fees_value;
+        let execution_time = self.elapsed(start_time).as_millis() as u64;
+        let actual_profit = current_amount - amount - other_fees_value;
         let actual_profit_pct = (actual_profit / amount) * 100.0;
         let total_profit_with_dust = actual_profit + dust_value_usd;
         let total_profit_pct_with_dust = (total_profit_with_dust / amount) * 100.0;
@@ -308,94 +937,657 @@ impl ArbitrageTrader {
         warn!("   Total fees: ${total_fees:.6}");
         warn!("   Execution time: {execution_time}ms");
         Ok(ArbitrageExecutionResult {
+            schema_version: SCHEMA_VERSION,
             success: true,
             initial_amount: amount,
             actual_profit,
             actual_profit_pct,
             dust_value_usd,
+            dust: dust_assets,
             total_fees,
             execution_time_ms: execution_time,
             error_message: None,
+            exchange_error: None,
         })
     }
 
-    /// Attempt to rollback trades to return to the initial currency
-    async fn rollback_trades(
+    /// Race two opportunities that share no symbols: leg 1 of each goes out as a limit order at
+    /// its quoted price, the first to fill completes its triangle and the other is cancelled
+    /// (and unwound if it partially filled). Returns which contender was traded.
+    pub async fn execute_race(
         &mut self,
-        executions: &[TradeExecution],
-        opportunity: &ArbitrageOpportunity,
-    ) -> Result<()> {
-        // We need to reverse the executed steps
-        // If we executed step 1 (A->B), we need to do B->A
-        // If we executed step 1 & 2 (A->B, B->C), we need to do C->B, then B->A
+        contenders: [(&ArbitrageOpportunity, f64); 2],
+    ) -> (usize, Result<ArbitrageExecutionResult>) {
+        let start_time = self.clock.now();
 
-        let mut current_step = executions.len();
+        if self.dry_run {
+            info!("🧪 DRY RUN: Simulating the race as a win for the best opportunity");
+            let (opportunity, amount) = contenders[0];
+            return (0, self.dry_run_execution(opportunity, amount).await);
+        }
+        if let Err(e) = self.resolve_interrupted_orders().await {
+            return (0, Err(e));
+        }
 
-        while current_step > 0 {
-            let step_index = current_step - 1;
+        self.quote(&contenders.map(|(opportunity, _)| opportunity));
+        let simulations = contenders
+            .map(|(opportunity, amount)| self.presimulate(opportunity, amount, start_time));
+        let simulations = match simulations {
+            [Ok(first), Ok(second)] => [first, second],
+            // Nothing to race: trade whichever contender survived the pre-simulation
+            [Err(_), Ok(_)] => {
+                let (opportunity, amount) = contenders[1];
+                return (1, self.execute_arbitrage(opportunity, amount).await);
+            }
+            [_, Err(_)] => {
+                let (opportunity, amount) = contenders[0];
+                return (0, self.execute_arbitrage(opportunity, amount).await);
+            }
+        };
 
-            // The currency we currently hold
-            let current_currency = &opportunity.path[current_step];
-            // The currency we want to go back to
-            let target_currency = &opportunity.path[current_step - 1];
+        warn!(
+            "🏁 RACE: Starting leg 1 of {} and {}",
+            contenders[0].0.display_pairs(),
+            contenders[1].0.display_pairs()
+        );
+        // Both legs 1 go out at once, so neither starts a round trip behind
+        let placed = tokio::join!(
+            self.place_race_order(contenders[0].0, &simulations[0].legs[0]),
+            self.place_race_order(contenders[1].0, &simulations[1].legs[0]),
+        );
+        let order_ids: Vec<Option<String>> = [placed.0, placed.1]
+            .into_iter()
+            .zip(&contenders)
+            .map(|(placed, (opportunity, _))| {
+                placed
+                    .inspect_err(|e| {
+                        warn!("⚠️ Race leg 1 on {} failed: {e:#}", opportunity.pairs[0])
+                    })
+                    .ok()
+            })
+            .collect();
+
+        let winner = 'race: loop {
+            for (i, order_id) in order_ids.iter().enumerate() {
+                let Some(order_id) = order_id else { continue };
+                let symbol = &contenders[i].0.pairs[0];
+                if let Ok(order) = self.client.get_order("spot", order_id, symbol).await {
+                    if order.state() == Some(OrderState::Filled) {
+                        break 'race Some((i, order));
+                    }
+                }
+            }
+            if self.elapsed(start_time) >= RACE_WINDOW || order_ids.iter().all(Option::is_none) {
+                break None;
+            }
+            self.clock.sleep(RACE_POLL_INTERVAL).await;
+        };
 
-            // The pair we used
-            let pair_symbol = &opportunity.pairs[step_index];
+        // Stop every other leg 1, converting back whatever it filled
+        for (i, order_id) in order_ids.iter().enumerate() {
+            let Some(order_id) = order_id else { continue };
+            if winner.as_ref().is_some_and(|(w, _)| *w == i) {
+                continue;
+            }
+            let (opportunity, _) = contenders[i];
+            if let Err(e) = self.cancel_race_order(opportunity, order_id).await {
+                error!(
+                    "❌ Failed to unwind race leg 1 on {}: {e:#}",
+                    opportunity.pairs[0]
+                );
+            }
+        }
 
-            info!(
-                "🔄 Rollback Step {}: Converting {} back to {} via {}",
-                current_step, current_currency, target_currency, pair_symbol
+        let Some((i, order)) = winner else {
+            warn!(
+                "🏁 RACE: Neither leg 1 filled within {}s",
+                RACE_WINDOW.as_secs()
+            );
+            let (_, amount) = contenders[0];
+            return (
+                0,
+                Ok(ArbitrageExecutionResult {
+                    schema_version: SCHEMA_VERSION,
+                    success: false,
+                    initial_amount: amount,
+                    actual_profit: 0.0,
+                    actual_profit_pct: 0.0,
+                    dust_value_usd: 0.0,
+                    dust: BTreeMap::new(),
+                    total_fees: 0.0,
+                    execution_time_ms: self.elapsed(start_time).as_millis() as u64,
+                    error_message: Some(format!(
+                        "Race: neither leg 1 filled within {}s",
+                        RACE_WINDOW.as_secs()
+                    )),
+                    exchange_error: None,
+                }),
             );
+        };
+
+        let (opportunity, amount) = contenders[i];
+        warn!("🏁 RACE: {} filled first", opportunity.display_pairs());
+        let latency = self.elapsed(start_time);
+        self.execution_stats
+            .record_fill(&opportunity.pairs[0], latency);
+        let execution = match self.record_fill(&order, order.side.clone()).await {
+            Ok(execution) => execution,
+            Err(e) => return (i, Err(e)),
+        };
+        let result = self
+            .run_legs(
+                opportunity,
+                amount,
+                &simulations[i],
+                start_time,
+                Some(execution),
+            )
+            .await;
+        self.clear_checkpoint();
+        (i, result)
+    }
 
-            // Get the balance of the currency we hold
-            let balance = self.get_actual_balance(current_currency).await?;
+    /// Place leg 1 as a resting limit order at the quoted price, no worse than planned
+    async fn place_race_order(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        leg: &SimulatedLeg,
+    ) -> Result<String> {
+        let symbol = &leg.symbol;
+        let (qty, price) = self.limit_order_size(leg, opportunity.prices[0])?;
 
-            // Use 99% of balance to ensure we can cover fees and avoid precision issues
-            let trade_amount = balance * 0.99;
+        info!(
+            "🏁 Placing race {} limit: {qty} {symbol} @ {price}",
+            leg.side
+        );
+        let order = self
+            .send_order(PlaceOrderRequest {
+                category: "spot".to_string(),
+                symbol: symbol.clone(),
+                side: leg.side.clone(),
+                order_type: "Limit".to_string(),
+                qty,
+                price: Some(price),
+                time_in_force: Some("GTC".to_string()),
+                order_link_id: Some(instance::order_link_id(&self.order_link_prefix, "race")),
+                reduce_only: None,
+            })
+            .await?;
+        Ok(order.order_id)
+    }
 
-            if trade_amount <= 0.0 {
-                warn!(
-                    "⚠️ No balance of {} found for rollback, skipping step",
-                    current_currency
+    /// Quantity and price strings of a limit order for the leg at `price`, no worse than it
+    fn limit_order_size(&self, leg: &SimulatedLeg, price: Price) -> Result<(String, String)> {
+        let symbol = &leg.symbol;
+        Ok(match leg.quantity {
+            OrderQty::Quote(notional) => {
+                // Limit Buys are sized in the base coin
+                let price = self.precision_manager.format_price(symbol, price, false);
+                let qty = self.precision_manager.format_order_quantity(
+                    symbol,
+                    OrderQty::Base(notional / Price::new(price.parse()?)),
                 );
-                current_step -= 1;
-                continue;
+                (qty, price)
+            }
+            OrderQty::Base(_) => {
+                let price = self.precision_manager.format_price(symbol, price, true);
+                (leg.formatted.clone(), price)
             }
+        })
+    }
 
-            // Determine action to go from current -> target
-            // Note: determine_trade_action takes (symbol, from, to, amount)
-            let (action, quantity) = self
-                .determine_trade_action(
-                    pair_symbol,
-                    current_currency,
-                    target_currency,
-                    trade_amount,
-                )
-                .await?;
+    /// Cancel a losing race order and roll back anything it filled
+    async fn cancel_race_order(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        order_id: &str,
+    ) -> Result<()> {
+        let symbol = &opportunity.pairs[0];
+        if let Err(e) = self.client.cancel_order("spot", order_id, symbol).await {
+            // Usually it filled in the meantime
+            debug!("Race order {order_id} not cancelled: {e}");
+        }
+        let order = self.client.get_order("spot", order_id, symbol).await?;
+        if order_received(&order) <= 0.0 {
+            info!("🏁 Cancelled race leg 1 on {symbol}");
+            return Ok(());
+        }
+        warn!(
+            "🔄 Race leg 1 on {symbol} filled {} before it was cancelled - rolling back",
+            order.cum_exec_qty
+        );
+        self.record_fill(&order, order.side.clone()).await?;
+        let held = BTreeMap::from([(opportunity.path[1].clone(), order_received(&order))]);
+        self.rollback_legs(1, opportunity, held).await.map(|_| ())
+    }
+
+    /// Park the coin held after leg 2 if hedging is enabled and rolling back would cost too much
+    async fn try_park(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        amount: f64,
+        held: f64,
+    ) -> Option<OpenPosition> {
+        let min_loss_pct = self.hedge_min_rollback_loss_pct?;
+        let Some(rollback_value) = self.estimate_rollback_value(opportunity, held).await else {
+            warn!("⚠️ Could not price the rollback - rolling back without hedging");
+            return None;
+        };
+        let loss_pct = (amount - rollback_value) / amount * 100.0;
+        if loss_pct < min_loss_pct {
+            info!("🔄 Rollback would lose {loss_pct:.3}% (hedging from {min_loss_pct:.2}%) - rolling back");
+            return None;
+        }
+
+        warn!(
+            "🅿️ Rollback would lose {loss_pct:.3}% - parking {held:.8} {} instead",
+            opportunity.path[2]
+        );
+        match self.park_position(opportunity, amount, held).await {
+            Ok(position) => {
+                warn!(
+                    "🅿️ Parked {} {}: {} limit on {} @ {} (order {})",
+                    position.quantity,
+                    position.coin,
+                    position.side,
+                    position.symbol,
+                    position.limit_price,
+                    position.order_id
+                );
+                self.positions.open(position.clone());
+                self.newly_parked.push(position.clone());
+                Some(position)
+            }
+            Err(e) => {
+                error!("❌ Failed to park {}: {e:#}", opportunity.path[2]);
+                None
+            }
+        }
+    }
+
+    /// What converting `held` of the leg-2 coin back along legs 2 and 1 would return, at top of book
+    async fn estimate_rollback_value(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        held: f64,
+    ) -> Option<f64> {
+        let mut value = held;
+        for step in (0..2).rev() {
+            let symbol = &opportunity.pairs[step];
+            let info = self.precision_manager.get_symbol_precision(symbol)?;
+            let ticker = self.client.get_ticker("spot", symbol).await.ok()?;
+            let ticker = ticker.list.first()?;
+            let price = |p: &Option<String>| p.as_deref()?.parse::<f64>().ok();
+            value = if info.base_coin == opportunity.path[step + 1] {
+                value * price(&ticker.bid1_price)?
+            } else {
+                value / price(&ticker.ask1_price)?
+            } * (1.0 - self.fees.taker(symbol));
+        }
+        Some(value)
+    }
+
+    /// Place a resting limit order on leg 3 that returns at least the starting amount after fees
+    async fn park_position(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        amount: f64,
+        held: f64,
+    ) -> Result<OpenPosition> {
+        let symbol = &opportunity.pairs[2];
+        let coin = &opportunity.path[2];
+        let info = self
+            .precision_manager
+            .get_symbol_precision(symbol)
+            .with_context(|| format!("Symbol {symbol} not found in precision manager"))?;
+        // The exit rests on the book, so it pays the maker fee
+        let net = 1.0 - self.fees.maker(symbol);
+
+        let (side, qty, price) = if &info.base_coin == coin {
+            // Sell what we hold at a price that returns the starting amount after fees
+            let qty = self
+                .precision_manager
+                .format_order_quantity(symbol, OrderQty::Base(Qty::new(held)));
+            let breakeven = amount / (qty.parse::<f64>()? * net);
+            let price = self
+                .precision_manager
+                .format_price(symbol, Price::new(breakeven), true);
+            ("Sell", qty, price)
+        } else {
+            // Buy back the starting amount (fees come out of it) with no more than we hold
+            let qty = self
+                .precision_manager
+                .format_order_quantity(symbol, OrderQty::Base(Qty::new(amount / net)));
+            let breakeven = held / qty.parse::<f64>()?;
+            let price = self
+                .precision_manager
+                .format_price(symbol, Price::new(breakeven), false);
+            ("Buy", qty, price)
+        };
+
+        let order = self
+            .send_order(PlaceOrderRequest {
+                category: "spot".to_string(),
+                symbol: symbol.clone(),
+                side: side.to_string(),
+                order_type: "Limit".to_string(),
+                qty,
+                price: Some(price.clone()),
+                time_in_force: Some("GTC".to_string()),
+                order_link_id: Some(instance::order_link_id(&self.order_link_prefix, "park")),
+                reduce_only: None,
+            })
+            .await?;
+
+        Ok(OpenPosition {
+            symbol: symbol.clone(),
+            side: side.to_string(),
+            coin: coin.clone(),
+            quantity: held,
+            target_coin: opportunity.path[3].clone(),
+            breakeven_amount: amount,
+            limit_price: price,
+            order_id: order.order_id,
+            opened_at: Utc::now(),
+        })
+    }
+
+    pub fn has_open_positions(&self) -> bool {
+        !self.positions.positions().is_empty()
+    }
+
+    /// Positions parked since the last call
+    pub fn take_parked_positions(&mut self) -> Vec<OpenPosition> {
+        std::mem::take(&mut self.newly_parked)
+    }
+
+    /// Close parked positions whose exit order filled, and liquidate those held too long
+    pub async fn manage_positions(&mut self, now: DateTime<Utc>) -> Vec<ClosedPosition> {
+        // Liquidations are priced by the book, not by any opportunity
+        self.quoted_prices.clear();
+        let mut closed = Vec::new();
+        for position in self.positions.positions().to_vec() {
+            match self.check_position(&position, now).await {
+                Ok(Some(outcome)) => {
+                    self.positions.close(&position.order_id);
+                    closed.push(outcome);
+                }
+                Ok(None) => {}
+                Err(e) => warn!(
+                    "⚠️ Failed to manage parked {} on {}: {e:#}",
+                    position.coin, position.symbol
+                ),
+            }
+        }
+        closed
+    }
+
+    async fn check_position(
+        &mut self,
+        position: &OpenPosition,
+        now: DateTime<Utc>,
+    ) -> Result<Option<ClosedPosition>> {
+        let order = self
+            .client
+            .get_order("spot", &position.order_id, &position.symbol)
+            .await?;
+        let state = order.state();
+        let limit_received = order_received(&order);
+
+        if state == Some(OrderState::Filled) {
+            info!(
+                "✅ Parked {} exited at {}: received {limit_received:.8} {} (breakeven {:.8})",
+                position.coin,
+                position.limit_price,
+                position.target_coin,
+                position.breakeven_amount
+            );
+            return Ok(Some(ClosedPosition {
+                position: position.clone(),
+                reason: "filled",
+                received: limit_received,
+            }));
+        }
+
+        let resting = !state.is_some_and(OrderState::is_terminal);
+        if resting {
+            if !self.positions.is_expired(position, now) {
+                return Ok(None);
+            }
+            warn!(
+                "⏰ Parked {} held past the maximum holding time - liquidating at market",
+                position.coin
+            );
+            self.client
+                .cancel_order("spot", &position.order_id, &position.symbol)
+                .await?;
+        }
+
+        // Whatever the exit order didn't fill goes back at market
+        let remaining = position
+            .quantity
+            .min(self.get_actual_balance(&position.coin).await?);
+        let fill = self
+            .place_and_fill(
+                &position.symbol,
+                position.side.clone(),
+                OrderQty::for_side(&position.side, remaining),
+                None,
+                99,
+            )
+            .await?;
+        let received = limit_received + fill.received();
+        warn!(
+            "🧯 Liquidated parked {}: received {received:.8} {} (breakeven {:.8})",
+            position.coin, position.target_coin, position.breakeven_amount
+        );
+        Ok(Some(ClosedPosition {
+            position: position.clone(),
+            reason: "liquidated",
+            received,
+        }))
+    }
+
+    /// Unwind the first `completed` legs at market, converting what the triangle holds of
+    /// each coin (`held`) back towards the starting coin
+    async fn rollback_legs(
+        &mut self,
+        completed: usize,
+        opportunity: &ArbitrageOpportunity,
+        mut held: BTreeMap<String, f64>,
+    ) -> Result<Rollback> {
+        // We need to reverse the executed steps
+        // If we executed step 1 (A->B), we need to do B->A
+        // If we executed step 1 & 2 (A->B, B->C), we need to do C->B, then B->A
+        for current_step in (1..=completed).rev() {
+            // The currency we currently hold
+            let current_currency = &opportunity.path[current_step];
+            // The currency we want to go back to
+            let target_currency = &opportunity.path[current_step - 1];
+
+            // The pair we used
+            let pair_symbol = &opportunity.pairs[current_step - 1];
+
+            info!(
+                "🔄 Rollback Step {}: Converting {} back to {} via {}",
+                current_step, current_currency, target_currency, pair_symbol
+            );
+
+            // What the triangle holds, never more than the wallet does (the rest of the
+            // balance isn't ours to sell)
+            let tracked = held.get(current_currency).copied().unwrap_or(0.0);
+            let trade_amount = tracked.min(self.get_actual_balance(current_currency).await?);
+
+            if trade_amount <= 0.0 {
+                warn!(
+                    "⚠️ No balance of {} found for rollback, skipping step",
+                    current_currency
+                );
+                continue;
+            }
+
+            // Determine action to go from current -> target
+            let (action, quantity) = match self
+                .determine_trade_action(
+                    pair_symbol,
+                    current_currency,
+                    target_currency,
+                    trade_amount,
+                )
+                .await
+            {
+                Ok(action) => action,
+                Err(e) => {
+                    error!("❌ Rollback Step {current_step} failed: {e:#}");
+                    continue;
+                }
+            };
 
             info!(
                 "🔄 Rollback Action: {} {} of {}",
-                action, quantity, pair_symbol
+                action,
+                quantity.value(),
+                pair_symbol
             );
 
             // Execute the trade
             // We use a special step number 99 to indicate rollback in logs if needed
-            let order_result = self
-                .place_order_with_precision_retry(pair_symbol, &action, quantity, 99)
-                .await?;
+            let order_result = match self
+                .place_order_with_precision_retry(pair_symbol, &action, quantity, None, 99)
+                .await
+            {
+                Ok(order_result) => order_result,
+                Err(e) => {
+                    error!("❌ Rollback Step {current_step} failed: {e:#}");
+                    continue;
+                }
+            };
 
-            // Wait for execution
-            match self
+            // Wait for execution, aborting an order that doesn't fill in time
+            let order = match self
                 .wait_for_order_execution(&order_result.order_id, pair_symbol)
                 .await
             {
-                Ok(_) => info!("✅ Rollback Step {} complete", current_step),
-                Err(e) => error!("❌ Rollback Step {} failed: {}", current_step, e),
+                Ok(order) => {
+                    info!("✅ Rollback Step {} complete", current_step);
+                    Some(order)
+                }
+                Err(e) => {
+                    error!("❌ Rollback Step {} failed: {}", current_step, e);
+                    self.abort_order(&order_result.order_id, pair_symbol).await
+                }
+            };
+            if let Some(order) = order {
+                *held.entry(current_currency.clone()).or_insert(0.0) -= order_spent(&order);
+                *held.entry(target_currency.clone()).or_insert(0.0) += order_received(&order);
+            }
+        }
+
+        let recovered = held.get(&opportunity.path[0]).copied().unwrap_or(0.0);
+        Ok(Rollback {
+            recovered,
+            residual: self.residual_exposure(opportunity, held).await?,
+        })
+    }
+
+    /// What the triangle still holds of each coin but the starting one after a rollback,
+    /// checked against the wallet and reported
+    async fn residual_exposure(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        held: BTreeMap<String, f64>,
+    ) -> Result<BTreeMap<String, f64>> {
+        let mut residual = BTreeMap::new();
+        for (coin, tracked) in held {
+            if coin == opportunity.path[0] || tracked <= 1e-12 {
+                continue;
+            }
+            let left = tracked.min(self.get_actual_balance(&coin).await?);
+            if left <= 0.0 {
+                continue;
+            }
+            match self.usd_prices.get(&coin) {
+                Some(price) => warn!(
+                    "📦 Residual exposure after rollback: {left:.8} {coin} (≈${:.4})",
+                    left * price
+                ),
+                None => warn!("📦 Residual exposure after rollback: {left:.8} {coin}"),
             }
+            residual.insert(coin, left);
+        }
+        Ok(residual)
+    }
+
+    /// Resolve a triangle a previous run left half-done: roll its filled legs back, or
+    /// trade the remaining ones at market
+    pub async fn resume_interrupted(
+        &mut self,
+        state: &ExecutionState,
+        mode: ResumeMode,
+    ) -> Result<()> {
+        let opportunity = &state.opportunity;
+        if state.completed_legs == 0 {
+            info!("♻️ No leg had filled; nothing to resume");
+            return Ok(());
+        }
+        let balance = self.get_actual_balance(&state.held_coin).await?;
+        // Most of it gone means the leg in flight at the interruption filled after all
+        if balance < state.held_amount * 0.01 {
+            warn!(
+                "⚠️ Only {balance:.8} of the {:.8} {} held is left - step {} likely filled before the interruption; resolve it manually",
+                state.held_amount,
+                state.held_coin,
+                state.completed_legs + 1
+            );
+            return Ok(());
+        }
 
-            current_step -= 1;
+        if mode == ResumeMode::Rollback {
+            warn!(
+                "🔄 Rolling back {} filled leg(s) of {}",
+                state.completed_legs,
+                opportunity.display_pairs()
+            );
+            let held = BTreeMap::from([(state.held_coin.clone(), state.held_amount.min(balance))]);
+            return self
+                .rollback_legs(state.completed_legs, opportunity, held)
+                .await
+                .map(|_| ());
         }
 
+        warn!(
+            "▶️ Completing {} from step {}",
+            opportunity.display_pairs(),
+            state.completed_legs + 1
+        );
+        self.quote(&[opportunity]);
+        let mut amount = state.held_amount.min(balance);
+        let mut confirmed_balance = balance;
+        for step in state.completed_legs..opportunity.pairs.len() {
+            if step > state.completed_legs {
+                confirmed_balance = self
+                    .wait_for_balance_settlement(step + 1, opportunity)
+                    .await?;
+            }
+            self.save_checkpoint(opportunity, state.amount, step, amount, state.started_at);
+            let execution = self
+                .execute_trade_step(
+                    step + 1,
+                    &opportunity.pairs[step],
+                    amount,
+                    Some(confirmed_balance),
+                    opportunity,
+                    None,
+                )
+                .await?;
+            amount = execution.received();
+        }
+        warn!(
+            "✅ Completed {}: {amount:.6} {} back from {:.6}",
+            opportunity.display_pairs(),
+            opportunity.path[0],
+            state.amount
+        );
         Ok(())
     }
 
@@ -411,11 +1603,38 @@ impl ArbitrageTrader {
             _ => return Ok(0.0),       // Step 1 doesn't need previous balance
         };
 
-        let start_time = std::time::Instant::now();
+        let start_time = self.clock.now();
         let max_wait = Duration::from_millis(5000); // Increased to 5 seconds for better settlement
 
+        if let Some(wallet) = self.wallet.as_ref().filter(|w| w.is_connected()) {
+            let mut pushes = wallet.subscribe();
+            let settled = tokio::select! {
+                biased;
+                balances = pushes.wait_for(|b| b.get(required_currency).is_some_and(|&v| v > 0.0)) => {
+                    balances.ok().and_then(|b| b.get(required_currency).copied())
+                }
+                _ = self.clock.sleep(max_wait) => None,
+            };
+            match settled {
+                Some(available_balance) => {
+                    debug!(
+                        "✅ Balance settled: {available_balance} {required_currency} pushed in {}ms",
+                        self.elapsed(start_time).as_millis()
+                    );
+                    return Ok(available_balance);
+                }
+                None => {
+                    warn!(
+                        "⚠️ Balance settlement timeout for {} - proceeding anyway",
+                        required_currency
+                    );
+                    return Ok(0.0);
+                }
+            }
+        }
+
         loop {
-            if start_time.elapsed() > max_wait {
+            if self.elapsed(start_time) > max_wait {
                 warn!(
                     "⚠️ Balance settlement timeout for {} - proceeding anyway",
                     required_currency
@@ -449,7 +1668,7 @@ impl ArbitrageTrader {
                 }
             }
 
-            sleep(Duration::from_millis(20)).await; // Check every 20ms
+            self.clock.sleep(Duration::from_millis(20)).await; // Check every 20ms
         }
     }
 
@@ -461,13 +1680,40 @@ impl ArbitrageTrader {
         amount: f64,
         confirmed_balance: Option<f64>,
         opportunity: &ArbitrageOpportunity,
+        planned: Option<&SimulatedLeg>,
     ) -> Result<TradeExecution> {
         info!("📈 Step {step}: Executing trade on {symbol}");
 
-        // Determine trade direction and calculate quantity
-        let (side, quantity) = self
-            .calculate_trade_parameters(step, symbol, amount, opportunity, confirmed_balance)
-            .await?;
+        // Send the pre-simulated quantity, unless the previous leg delivered less than planned
+        let (side, quantity, formatted) = match planned {
+            Some(leg) if leg.quantity.value() <= amount => {
+                info!(
+                    "🧮 Step {step}: Sending pre-simulated {} of {} on {symbol}",
+                    leg.side, leg.formatted
+                );
+                (leg.side.clone(), leg.quantity, Some(leg.formatted.as_str()))
+            }
+            _ => {
+                if let Some(leg) = planned {
+                    warn!(
+                        "⚠️ Step {step}: Holding {amount:.8}, short of the planned {}; re-sizing",
+                        leg.formatted
+                    );
+                }
+                let (side, quantity) = self
+                    .calculate_trade_parameters(
+                        step,
+                        symbol,
+                        amount,
+                        opportunity,
+                        confirmed_balance,
+                    )
+                    .await?;
+                (side, quantity, None)
+            }
+        };
+
+        self.check_book_imbalance(step, symbol, &side).await?;
 
         // Verify we have sufficient balance before placing the order
         self.verify_balance_for_trade(
@@ -480,41 +1726,451 @@ impl ArbitrageTrader {
         )
         .await?;
 
-        // Use precision manager to format quantity with automatic retry logic
-        let order_result = self
-            .place_order_with_precision_retry(symbol, &side, quantity, step)
+        if step == 1 && formatted.is_some() && self.execution_strategy.maker_leg1() {
+            if let Some(leg) = planned {
+                return self.place_maker_first(leg).await;
+            }
+        }
+        self.place_and_fill(symbol, side, quantity, formatted, step)
+            .await
+    }
+
+    /// Offer leg 1 as a post-only limit at the best bid/ask, amended to follow the quote
+    /// while nothing has filled. What hasn't filled by the maker timeout is cancelled, then
+    /// goes to market with `MakerFirst`; with `Hybrid` the triangle continues with the part
+    /// that filled, or fails if nothing did.
+    async fn place_maker_first(&mut self, leg: &SimulatedLeg) -> Result<TradeExecution> {
+        let symbol = &leg.symbol;
+        self.failed_fill = None;
+        self.partial_leg = false;
+        let placed = match self.place_post_only(leg).await {
+            Ok(placed) => Some(placed),
+            Err(e) => {
+                warn!("⚠️ Maker leg 1 on {symbol} not placed: {e:#}");
+                None
+            }
+        };
+
+        let mut filled = None;
+        if let Some((order_id, mut price)) = placed {
+            let order_id = &order_id;
+            let started = self.clock.now();
+            // Fills only grow, so the last state seen is a floor if the read-back fails
+            let mut last_seen = None;
+            loop {
+                if let Ok(order) = self.client.get_order("spot", order_id, symbol).await {
+                    last_seen = Some(order.clone());
+                    match order.state() {
+                        Some(OrderState::Filled) => {
+                            filled = Some(order);
+                            break;
+                        }
+                        // A post-only order that would have taken liquidity is cancelled
+                        Some(OrderState::Cancelled | OrderState::Rejected) => break,
+                        Some(OrderState::Acked) if order_received(&order) == 0.0 => {
+                            self.requote(leg, order_id, &mut price).await;
+                        }
+                        _ => {}
+                    }
+                }
+                if self.elapsed(started) >= self.maker_timeout {
+                    break;
+                }
+                self.clock.sleep(MAKER_POLL_INTERVAL).await;
+            }
+            if filled.is_none() {
+                filled = self
+                    .abort_order(order_id, symbol)
+                    .await
+                    .or(last_seen.filter(|order| order_received(order) > 0.0));
+            }
+        }
+
+        let partial = match filled {
+            Some(order) if order_received(&order) > 0.0 => {
+                let execution = self.record_fill(&order, leg.side.clone()).await?;
+                if order.state() == Some(OrderState::Filled) {
+                    info!("🪙 Maker leg 1 on {symbol} filled @ {}", order.avg_price);
+                    return Ok(execution);
+                }
+                Some(execution)
+            }
+            _ => None,
+        };
+
+        match (partial, self.execution_strategy) {
+            (None, ExecutionStrategy::Hybrid) => Err(anyhow::anyhow!(
+                "Maker leg 1 on {symbol} not filled within {}ms",
+                self.maker_timeout.as_millis()
+            )),
+            (None, _) => {
+                info!("🪙 Maker leg 1 on {symbol} not filled - going to market");
+                self.place_and_fill(
+                    symbol,
+                    leg.side.clone(),
+                    leg.quantity,
+                    Some(&leg.formatted),
+                    1,
+                )
+                .await
+            }
+            (Some(part), ExecutionStrategy::Hybrid) => {
+                warn!(
+                    "🪙 Maker leg 1 on {symbol} only filled {} - continuing with it",
+                    part.executed_quantity
+                );
+                self.partial_leg = true;
+                Ok(part)
+            }
+            (Some(part), _) => {
+                let rest = maker::remainder(leg.quantity, &part);
+                info!(
+                    "🪙 Maker leg 1 on {symbol} filled {} - sending the remaining {} to market",
+                    part.executed_quantity,
+                    rest.value()
+                );
+                match self
+                    .place_and_fill(symbol, leg.side.clone(), rest, None, 1)
+                    .await
+                {
+                    Ok(execution) => Ok(maker::merge(part, execution)),
+                    Err(e) => {
+                        warn!("⚠️ Market remainder of leg 1 on {symbol} failed: {e:#} - continuing with the maker fill");
+                        self.partial_leg = true;
+                        match self.failed_fill.take() {
+                            Some(more) => Ok(maker::merge(part, more)),
+                            None => Ok(part),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Send both legs as market orders in one batch request. Returns the order id of each
+    /// leg placed; a leg that wasn't is left to be placed on its own.
+    async fn stage_legs(&self, legs: [&SimulatedLeg; 2]) -> [Option<String>; 2] {
+        let requests: Vec<PlaceOrderRequest> = legs
+            .iter()
+            .zip([2, 3])
+            .map(|(leg, step)| PlaceOrderRequest {
+                category: "spot".to_string(),
+                symbol: leg.symbol.clone(),
+                side: leg.side.clone(),
+                order_type: "Market".to_string(),
+                qty: leg.formatted.clone(),
+                price: None,
+                time_in_force: Some("IOC".to_string()),
+                order_link_id: Some(instance::order_link_id(&self.order_link_prefix, step)),
+                reduce_only: None,
+            })
+            .collect();
+        info!(
+            "📦 Batching legs 2 and 3: {} {} {} and {} {} {}",
+            legs[0].side,
+            legs[0].formatted,
+            legs[0].symbol,
+            legs[1].side,
+            legs[1].formatted,
+            legs[1].symbol
+        );
+
+        let results = match self.send_batch(requests).await {
+            Ok(results) => results,
+            Err(e) => {
+                warn!("⚠️ Batch of legs 2 and 3 failed: {e:#} - placing them one by one");
+                return [None, None];
+            }
+        };
+        let mut order_ids = [None, None];
+        for ((order_id, result), step) in order_ids.iter_mut().zip(results).zip([2, 3]) {
+            match result {
+                Ok(placed) => *order_id = Some(placed.order_id),
+                Err(e) => warn!("⚠️ Batched leg {step} not placed: {e:#}"),
+            }
+        }
+        order_ids
+    }
+
+    /// Wait for a batched leg to fill and record it in the execution stats
+    async fn await_staged(
+        &mut self,
+        order_id: &str,
+        symbol: &str,
+        side: String,
+    ) -> Result<TradeExecution> {
+        let started = self.clock.now();
+        let result = self.await_fill(order_id, symbol, side).await;
+        match &result {
+            Ok(_) => {
+                let latency = self.elapsed(started);
+                self.execution_stats.record_fill(symbol, latency);
+            }
+            Err(_) => self.execution_stats.record_failure(symbol),
+        }
+        result
+    }
+
+    /// Quantity and price of a post-only limit joining the current best bid (Buy) or ask
+    /// (Sell) of the leg's symbol
+    async fn maker_quote(&self, leg: &SimulatedLeg) -> Result<(String, String)> {
+        let symbol = &leg.symbol;
+        let tickers = self.client.get_ticker("spot", symbol).await?;
+        let ticker = tickers
+            .list
+            .first()
+            .with_context(|| format!("No ticker for {symbol}"))?;
+        let price = |p: &Option<String>| -> Result<f64> {
+            p.as_deref()
+                .and_then(|p| p.parse().ok())
+                .with_context(|| format!("No best bid/ask for {symbol}"))
+        };
+        let join = maker::join_price(
+            &leg.side,
+            price(&ticker.bid1_price)?,
+            price(&ticker.ask1_price)?,
+        );
+        self.limit_order_size(leg, Price::new(join))
+    }
+
+    /// Place a post-only limit joining the best bid or ask. Returns its order id and price.
+    async fn place_post_only(&self, leg: &SimulatedLeg) -> Result<(String, String)> {
+        let symbol = &leg.symbol;
+        let (qty, price) = self.maker_quote(leg).await?;
+        info!(
+            "🪙 Placing maker {} limit: {qty} {symbol} @ {price}",
+            leg.side
+        );
+        let order = self
+            .send_order(PlaceOrderRequest {
+                category: "spot".to_string(),
+                symbol: symbol.clone(),
+                side: leg.side.clone(),
+                order_type: "Limit".to_string(),
+                qty,
+                price: Some(price.clone()),
+                time_in_force: Some("PostOnly".to_string()),
+                order_link_id: Some(instance::order_link_id(&self.order_link_prefix, "maker")),
+                reduce_only: None,
+            })
             .await?;
+        Ok((order.order_id, price))
+    }
+
+    /// Amend an unfilled maker order onto the best bid/ask once the quote has moved off
+    /// `price`, keeping its place in the maker timeout instead of cancelling and replacing
+    async fn requote(&self, leg: &SimulatedLeg, order_id: &str, price: &mut String) {
+        let symbol = &leg.symbol;
+        let (qty, join) = match self.maker_quote(leg).await {
+            Ok(quote) => quote,
+            Err(e) => {
+                debug!("Maker leg 1 on {symbol} not requoted: {e:#}");
+                return;
+            }
+        };
+        if join == *price {
+            return;
+        }
+        let request = AmendOrderRequest {
+            category: "spot".to_string(),
+            symbol: symbol.clone(),
+            order_id: order_id.to_string(),
+            qty: Some(qty),
+            price: Some(join.clone()),
+        };
+        match self.client.amend_order(request).await {
+            Ok(_) => {
+                info!("🪙 Maker leg 1 on {symbol} requoted @ {join}");
+                *price = join;
+            }
+            // Usually it filled or was cancelled in the meantime
+            Err(e) => debug!("Maker order {order_id} not amended: {e}"),
+        }
+    }
+
+    /// Fills into a book stacked against our side come with extra slippage: refuse leg 1,
+    /// and give legs 2 and 3 a moment for the book to even out before going ahead
+    async fn check_book_imbalance(&mut self, step: usize, symbol: &str, side: &str) -> Result<()> {
+        let Some(max_ratio) = self.book_imbalance_max_ratio else {
+            return Ok(());
+        };
+        let Some(id) = self.symbols.symbol_id(symbol) else {
+            return Ok(());
+        };
+        let started = self.clock.now();
+        loop {
+            let Some(book) = self.local_book.get(&id) else {
+                return Ok(());
+            };
+            let ratio = book.imbalance_against(side);
+            if ratio <= max_ratio {
+                return Ok(());
+            }
+            if step == 1 {
+                return Err(LegError::BookImbalance {
+                    ratio,
+                    side: side.to_string(),
+                    symbol: symbol.to_string(),
+                    max_ratio,
+                }
+                .into());
+            }
+            if self.elapsed(started) >= IMBALANCE_MAX_DELAY {
+                warn!("⚖️ {symbol} still stacked {ratio:.1}x against a {side} - placing step {step} anyway");
+                return Ok(());
+            }
+            info!("⚖️ {symbol} stacked {ratio:.1}x against a {side} - delaying step {step}");
+            self.clock.sleep(IMBALANCE_RECHECK_INTERVAL).await;
+
+            // Refresh the symbol's top of book; keep the old one if that fails
+            if let Ok(tickers) = self.client.get_ticker("spot", symbol).await {
+                if let Some(book) = tickers.list.first().and_then(TopOfBook::from_ticker) {
+                    self.local_book.insert(id, book);
+                }
+            }
+        }
+    }
+
+    /// Place an order, wait for the fill and record the outcome in the execution stats.
+    /// `formatted` is an exact quantity string to try first, e.g. from the pre-simulation.
+    async fn place_and_fill(
+        &mut self,
+        symbol: &str,
+        side: String,
+        quantity: OrderQty,
+        formatted: Option<&str>,
+        step: usize,
+    ) -> Result<TradeExecution> {
+        let started = self.clock.now();
+        // Use precision manager to format quantity with automatic retry logic
+        self.failed_fill = None;
+        self.partial_leg = false;
+        let result = match self
+            .place_order_with_precision_retry(symbol, &side, quantity, formatted, step)
+            .await
+        {
+            Ok(order_result) => {
+                let filled = self
+                    .await_fill(&order_result.order_id, symbol, side.clone())
+                    .await;
+                match filled {
+                    Err(e) => match self.abort_order(&order_result.order_id, symbol).await {
+                        // Big enough to trade on: the remaining legs are sized from it
+                        Some(order) if self.is_tradeable_fill(&order) => {
+                            warn!(
+                                "✂️ Step {step}: continuing with the partial fill of {} of {} on {symbol}",
+                                order.cum_exec_qty, order.qty
+                            );
+                            self.partial_leg = true;
+                            self.record_fill(&order, side).await
+                        }
+                        // Whatever it filled is kept for the rollback
+                        Some(order) => {
+                            self.failed_fill = self.record_fill(&order, side).await.ok();
+                            Err(e)
+                        }
+                        None => Err(e),
+                    },
+                    filled => filled,
+                }
+            }
+            Err(e) => Err(e),
+        };
+
+        match &result {
+            Ok(_) => {
+                let latency = self.elapsed(started);
+                self.execution_stats.record_fill(symbol, latency);
+            }
+            Err(_) => self.execution_stats.record_failure(symbol),
+        }
+        result
+    }
+
+    /// Place a single market order outside a triangle, e.g. a stablecoin conversion,
+    /// and wait for the fill. `amount` is in the coin being spent (quote for Buys).
+    pub async fn execute_conversion(
+        &mut self,
+        symbol: &str,
+        side: &str,
+        amount: f64,
+        expected_price: Price,
+    ) -> Result<TradeExecution> {
+        let quantity = OrderQty::for_side(side, amount);
 
-        // Wait for order execution
+        if self.dry_run {
+            info!("🧪 DRY RUN: Simulating {side} of {amount:.6} on {symbol} @ {expected_price}");
+            let (executed_quantity, executed_value) = match quantity {
+                OrderQty::Base(qty) => (qty, qty * expected_price),
+                OrderQty::Quote(value) => (value / expected_price, value),
+            };
+            let received = if side == "Buy" {
+                executed_quantity.value()
+            } else {
+                executed_value.value()
+            };
+            return Ok(TradeExecution {
+                side: side.to_string(),
+                executed_price: expected_price,
+                executed_quantity,
+                executed_value,
+                fee: received * self.fees.taker(symbol),
+                other_fees: BTreeMap::new(),
+            });
+        }
+
+        self.quoted_prices = HashMap::from([(symbol.to_string(), expected_price)]);
+        self.place_and_fill(symbol, side.to_string(), quantity, None, 1)
+            .await
+    }
+
+    /// Cancel an order that failed to fill and read back what it executed anyway. Returns
+    /// the order if it filled anything.
+    async fn abort_order(&self, order_id: &str, symbol: &str) -> Option<OrderInfo> {
+        if let Err(e) = self.client.cancel_order("spot", order_id, symbol).await {
+            // Usually it was rejected or cancelled already
+            debug!("Order {order_id} not cancelled: {e}");
+        }
+        let order = match self.client.get_order("spot", order_id, symbol).await {
+            Ok(order) => order,
+            Err(e) => {
+                warn!("⚠️ Could not read back aborted order {order_id} on {symbol}: {e:#}");
+                return None;
+            }
+        };
+        if order_received(&order) <= 0.0 {
+            return None;
+        }
+        warn!(
+            "🛑 Aborted order {order_id} on {symbol} after it filled {} of {}",
+            order.cum_exec_qty, order.qty
+        );
+        Some(order)
+    }
+
+    /// Whether a partial fill is at least the symbol's minimum order quantity and value
+    fn is_tradeable_fill(&self, order: &OrderInfo) -> bool {
+        let parse = |s: &str| s.parse::<f64>().unwrap_or(0.0);
+        let (qty, value) = (parse(&order.cum_exec_qty), parse(&order.cum_exec_value));
+        match self.precision_manager.get_symbol_precision(&order.symbol) {
+            Some(info) => qty > 0.0 && qty >= info.min_order_qty && value >= info.min_notional,
+            None => qty > 0.0,
+        }
+    }
+
+    /// Wait for an order to fill and read back what was executed
+    async fn await_fill(
+        &self,
+        order_id: &str,
+        symbol: &str,
+        side: String,
+    ) -> Result<TradeExecution> {
         let executed_order = self
-            .wait_for_order_execution(&order_result.order_id, symbol)
+            .wait_for_order_execution(order_id, symbol)
             .await
             .context("Order execution failed or timed out")?;
 
-        let executed_price: f64 = executed_order
-            .avg_price
-            .parse()
-            .context("Failed to parse executed price")?;
-        let executed_quantity: f64 = executed_order
-            .cum_exec_qty
-            .parse()
-            .context("Failed to parse executed quantity")?;
-        let executed_value: f64 = executed_order
-            .cum_exec_value
-            .parse()
-            .context("Failed to parse executed value")?;
-        let fee: f64 = executed_order
-            .cum_exec_fee
-            .parse()
-            .context("Failed to parse execution fee")?;
-
-        Ok(TradeExecution {
-            side,
-            executed_price,
-            executed_quantity,
-            executed_value,
-            fee,
-        })
+        self.record_fill(&executed_order, side).await
     }
 
     /// Verify we have sufficient balance for the trade
@@ -523,7 +2179,7 @@ impl ArbitrageTrader {
         step: usize,
         side: &str,
         symbol: &str,
-        quantity: f64,
+        quantity: OrderQty,
         opportunity: &ArbitrageOpportunity,
         confirmed_balance: Option<f64>,
     ) -> Result<()> {
@@ -582,14 +2238,8 @@ impl ArbitrageTrader {
             */
         };
 
-        // Calculate required amount based on order type
-        let required_amount = if side == "Sell" {
-            // For sell orders, we need the exact quantity of the asset
-            quantity
-        } else {
-            // For buy orders, quantity is already the quote currency amount to spend
-            quantity
-        };
+        // Sells need the base quantity, market Buys the quote amount to spend
+        let required_amount = quantity.value();
 
         if available_balance >= required_amount {
             info!(
@@ -597,9 +2247,15 @@ impl ArbitrageTrader {
             );
             Ok(())
         } else {
-            Err(anyhow::anyhow!(
-                "Insufficient {required_currency} balance: have {available_balance:.6}, need {required_amount:.6} for step {step} {side} on {symbol}"
-            ))
+            Err(LegError::InsufficientBalance {
+                coin: required_currency.clone(),
+                available: available_balance,
+                required: required_amount,
+                step,
+                side: side.to_string(),
+                symbol: symbol.to_string(),
+            }
+            .into())
         }
     }
 
@@ -611,7 +2267,7 @@ impl ArbitrageTrader {
         amount: f64,
         opportunity: &ArbitrageOpportunity,
         confirmed_balance: Option<f64>,
-    ) -> Result<(String, f64)> {
+    ) -> Result<(String, OrderQty)> {
         info!("🔍 Calculating trade parameters for Step {step}: {symbol} with amount {amount:.6}");
 
         // Parse the triangle path to understand trade directions
@@ -689,7 +2345,10 @@ impl ArbitrageTrader {
             }
         };
 
-        info!("💡 Trade decision: {side} {quantity:.6} on {symbol}");
+        info!(
+            "💡 Trade decision: {side} {:.6} on {symbol}",
+            quantity.value()
+        );
         Ok((side, quantity))
     }
 
@@ -705,7 +2364,7 @@ impl ArbitrageTrader {
         from_currency: &str,
         to_currency: &str,
         amount: f64,
-    ) -> Result<(String, f64)> {
+    ) -> Result<(String, OrderQty)> {
         info!("🧭 Converting {from_currency} → {to_currency} via {symbol} (amount: {amount:.6})");
 
         // First, try the cached mapping approach for speed
@@ -713,21 +2372,17 @@ impl ArbitrageTrader {
             self.get_action_for_conversion(from_currency, to_currency)
         {
             if mapped_symbol == symbol {
-                let final_quantity = if action == "Buy" {
-                    // For Buy orders, use the quote currency amount (amount to spend)
-                    amount
-                } else {
-                    // For Sell orders, amount is already in base currency
-                    amount
-                };
+                // Buys spend `amount` of the quote coin, Sells sell `amount` of the base coin
+                let final_quantity = OrderQty::for_side(&action, amount);
 
                 info!(
-                    "✅ Cached mapping: {action} {} on {symbol} (final quantity: {final_quantity:.8})",
+                    "✅ Cached mapping: {action} {} on {symbol} (final quantity: {:.8})",
                     if action == "Sell" {
                         from_currency
                     } else {
                         to_currency
-                    }
+                    },
+                    final_quantity.value()
                 );
                 return Ok((action, final_quantity));
             } else {
@@ -757,7 +2412,7 @@ impl ArbitrageTrader {
             // Symbol format is FROM+TO (e.g., USDCUSDT for USDC→USDT)
             // Action: SELL from_currency (base) to get to_currency (quote)
             info!("✅ Direct pair {symbol}: SELL {from_currency} to get {to_currency}");
-            Ok(("Sell".to_string(), amount))
+            Ok(("Sell".to_string(), OrderQty::Base(Qty::new(amount))))
         } else if base_coin == to_currency && quote_coin == from_currency {
             // Symbol format is TO+FROM (e.g., NOTUSDC for USDC→NOT)
             // Action: BUY to_currency (base) using from_currency (quote)
@@ -765,7 +2420,7 @@ impl ArbitrageTrader {
             info!(
                 "✅ Reverse pair {symbol}: BUY {to_currency} using {from_currency} (spending: {amount:.6} {from_currency})"
             );
-            Ok(("Buy".to_string(), amount))
+            Ok(("Buy".to_string(), OrderQty::Quote(Notional::new(amount))))
         } else {
             Err(anyhow::anyhow!(
                 "Cannot convert {from_currency} → {to_currency} using symbol {symbol} (base: {base_coin}, quote: {quote_coin})"
@@ -775,16 +2430,21 @@ impl ArbitrageTrader {
 
     /// Get action for currency conversion using cached symbol mapping
     /// Returns (symbol, action) where action is "Sell" or "Buy"
-    /// O(1) lookup using prebuilt HashMap - much faster than string concatenation + precision manager lookups
+    /// O(1) lookup by interned coin IDs, without building a key per conversion
     fn get_action_for_conversion(&self, from: &str, to: &str) -> Option<(String, String)> {
-        let key = format!("{}{}", from.to_uppercase(), to.to_uppercase());
-
-        if let Some((symbol, action)) = self.symbol_map.get(&key) {
-            let direction_currency = if action == "Sell" { from } else { to };
-            info!("🎯 Found mapping {key}: {action} {direction_currency} using {symbol}");
-            Some((symbol.clone(), action.clone()))
+        let (from, to) = (from.to_uppercase(), to.to_uppercase());
+        let (from, to) = (from.as_str(), to.as_str());
+        if let Some((id, sells_base)) = self.symbols.conversion(from, to) {
+            let symbol = self.symbols.symbol(id);
+            let (action, direction_currency) = if sells_base {
+                ("Sell", from)
+            } else {
+                ("Buy", to)
+            };
+            info!("🎯 Found mapping {from} → {to}: {action} {direction_currency} using {symbol}");
+            Some((symbol.to_string(), action.to_string()))
         } else {
-            warn!("⚠️ No mapping found for {from} → {to} (key: {key})");
+            warn!("⚠️ No mapping found for {from} → {to}");
             None
         }
     }
@@ -814,100 +2474,171 @@ impl ArbitrageTrader {
         }
     }
 
-    /// Get estimated market price for order value validation
-    async fn get_estimated_market_price(&self, symbol: &str) -> Option<f64> {
-        // Try to get current market price from ticker
+    /// Price to validate an order's value at: the live pair price of the execution in
+    /// progress, else the ticker's last price. None rather than a guess when neither is known.
+    async fn get_market_price(&self, symbol: &str) -> Option<Price> {
+        // The live pair price the execution in progress was quoted at
+        if let Some(price) = self.quoted_prices.get(symbol) {
+            return Some(*price);
+        }
         match self.client.get_ticker("spot", symbol).await {
-            Ok(ticker_result) => {
-                if let Some(ticker) = ticker_result.list.first() {
-                    ticker
-                        .last_price
-                        .as_ref()
-                        .and_then(|s| s.parse::<f64>().ok())
-                } else {
-                    None
-                }
-            }
-            Err(_) => {
-                // Fallback: use a reasonable estimate based on common prices
-                if symbol.contains("BTC") {
-                    Some(50000.0) // Conservative BTC price estimate
-                } else if symbol.contains("ETH") {
-                    Some(3000.0) // Conservative ETH price estimate
-                } else if symbol.contains("USDT") || symbol.contains("USDC") {
-                    Some(1.0) // Stablecoin
-                } else {
-                    Some(10.0) // Default estimate for other tokens
-                }
+            Ok(ticker_result) => ticker_result
+                .list
+                .first()
+                .and_then(|ticker| ticker.last_price.as_deref()?.parse::<f64>().ok())
+                .filter(|price| *price > 0.0)
+                .map(Price::new),
+            Err(e) => {
+                warn!("⚠️ No ticker for {symbol}: {e}");
+                None
             }
         }
     }
 
-    /// Wait for order to be executed
+    /// Wait for order to be executed; an order still open after the wait is cancelled
     async fn wait_for_order_execution(&self, order_id: &str, symbol: &str) -> Result<OrderInfo> {
-        let start_time = std::time::Instant::now();
+        let start_time = self.clock.now();
+        let mut state = OrderState::Created;
+        let max_wait = self.leg_deadline.unwrap_or(self.max_order_wait_time);
 
         loop {
-            if start_time.elapsed() > self.max_order_wait_time {
-                return Err(anyhow::anyhow!("Order execution timeout"));
+            if self.elapsed(start_time) > max_wait {
+                match self.client.cancel_order("spot", order_id, symbol).await {
+                    Ok(_) => warn!(
+                        "⏰ Order {order_id} on {symbol} not filled within {}ms - cancelled",
+                        max_wait.as_millis()
+                    ),
+                    // Usually it filled or was cancelled in the meantime
+                    Err(e) => debug!("Timed out order {order_id} not cancelled: {e}"),
+                }
+                return Err(LegError::Timeout(max_wait.as_millis()).into());
             }
 
             match self.client.get_order("spot", order_id, symbol).await {
-                Ok(order) => {
-                    match order.order_status.as_str() {
-                        "Filled" => {
-                            debug!("✅ Order {order_id} filled");
+                Ok(order) => match order.state() {
+                    Some(next) if !state.can_transition_to(next) => {
+                        // Stale read from a lagging replica; keep polling from the known state
+                        warn!("⚠️ Order {order_id} reported invalid transition {state:?} → {next:?}, ignoring");
+                    }
+                    Some(next) => {
+                        state = next;
+                        match state {
+                            OrderState::Filled => {
+                                debug!("✅ Order {order_id} filled");
 
-                            // Quick balance verification instead of blind delay
-                            info!("⚡ Verifying balance settlement...");
-                            sleep(Duration::from_millis(200)).await; // Minimal delay
+                                // Quick balance verification instead of blind delay
+                                info!("⚡ Verifying balance settlement...");
+                                self.clock.sleep(Duration::from_millis(200)).await; // Minimal delay
 
-                            return Ok(order);
-                        }
-                        "PartiallyFilled" => {
-                            debug!("🔄 Order {order_id} partially filled, waiting...");
-                        }
-                        "Cancelled" | "Rejected" => {
-                            return Err(anyhow::anyhow!("Order {order_id} was cancelled/rejected"));
-                        }
-                        _ => {
-                            debug!("⏳ Order {order_id} status: {}", order.order_status);
+                                return Ok(order);
+                            }
+                            OrderState::PartiallyFilled => {
+                                debug!("🔄 Order {order_id} partially filled, waiting...");
+                            }
+                            OrderState::PartiallyFilledCancelled => {
+                                // IOC remainder was cancelled, so whatever filled is final
+                                if self.is_tradeable_fill(&order) {
+                                    warn!(
+                                        "⚠️ Order {order_id} only partially filled ({} executed), continuing with the filled amount",
+                                        order.cum_exec_qty
+                                    );
+                                    return Ok(order);
+                                }
+                                if order_received(&order) <= 0.0 {
+                                    return Err(anyhow::anyhow!(
+                                        "Order {order_id} was cancelled without filling"
+                                    ));
+                                }
+                                return Err(anyhow::anyhow!(
+                                    "Order {order_id} was cancelled after filling {}, below the minimum order size of {symbol}",
+                                    order.cum_exec_qty
+                                ));
+                            }
+                            OrderState::Cancelled | OrderState::Rejected => {
+                                return Err(anyhow::anyhow!(
+                                    "Order {order_id} was cancelled/rejected"
+                                ));
+                            }
+                            OrderState::Created | OrderState::Acked => {
+                                debug!("⏳ Order {order_id} status: {}", order.order_status);
+                            }
                         }
                     }
-                }
+                    None => {
+                        debug!("⏳ Order {order_id} status: {}", order.order_status);
+                    }
+                },
                 Err(e) => {
                     warn!("Failed to get order status: {e}");
                 }
             }
 
-            sleep(Duration::from_millis(500)).await;
+            self.clock.sleep(Duration::from_millis(500)).await;
+        }
+    }
+
+    /// Hand a dry run to the configured backend, or simulate it
+    async fn dry_run_execution(
+        &mut self,
+        opportunity: &ArbitrageOpportunity,
+        amount: f64,
+    ) -> Result<ArbitrageExecutionResult> {
+        let Some(backend) = self.backend.as_mut() else {
+            info!("🧪 DRY RUN: Simulating arbitrage execution");
+            return self.simulate_execution(opportunity, amount);
+        };
+        info!("🧪 DRY RUN: {}", backend.health());
+        backend.set_market(&self.local_book, &self.depth_books, &self.usd_prices);
+        backend.execute_arbitrage(opportunity, amount).await
+    }
+
+    /// What executes trades and its state, for the logs
+    pub fn health(&self) -> String {
+        match &self.backend {
+            Some(backend) => backend.health(),
+            None if self.dry_run => "simulated: fixed 0.5% slippage".to_string(),
+            None => format!(
+                "live: {} parked position(s){}",
+                self.positions.positions().len(),
+                if self.in_flight.is_empty() {
+                    ""
+                } else {
+                    ", orders in flight"
+                }
+            ),
         }
     }
 
-    /// Simulate execution for dry runs
-    fn simulate_execution(
+    /// Simulate execution for dry runs and shadow mode
+    pub fn simulate_execution(
         &self,
         opportunity: &ArbitrageOpportunity,
         amount: f64,
     ) -> Result<ArbitrageExecutionResult> {
-        info!("🧪 Simulating execution...");
+        debug!("🧪 Simulating execution...");
 
         // Simulate execution with some slippage
+        let initial = Notional::new(amount);
         let slippage_factor = 0.995; // 0.5% slippage
         let simulated_final =
-            amount * (1.0 + opportunity.estimated_profit_pct / 100.0) * slippage_factor;
-        let simulated_fees = amount * 0.003; // 0.3% total fees
-        let actual_profit = simulated_final - amount - simulated_fees;
+            (initial + opportunity.estimated_profit_pct.of(initial)) * slippage_factor;
+        // Each leg's taker fee, about 0.3% in total
+        let fee_pct: f64 = opportunity.pairs.iter().map(|s| self.fees.taker(s)).sum();
+        let simulated_fees = Pct::new(fee_pct * 100.0).of(initial);
+        let net_final = simulated_final - simulated_fees;
 
         Ok(ArbitrageExecutionResult {
+            schema_version: SCHEMA_VERSION,
             success: true,
             initial_amount: amount,
-            actual_profit,
-            actual_profit_pct: (actual_profit / amount) * 100.0,
+            actual_profit: (net_final - initial).value(),
+            actual_profit_pct: Pct::change(initial, net_final).value(),
             dust_value_usd: 0.0,
-            total_fees: simulated_fees,
+            dust: BTreeMap::new(),
+            total_fees: simulated_fees.value(),
             execution_time_ms: 100,
             error_message: None,
+            exchange_error: None,
         })
     }
 
@@ -916,43 +2647,68 @@ impl ArbitrageTrader {
         &mut self,
         symbol: &str,
         side: &str,
-        quantity: f64,
+        quantity: OrderQty,
+        planned: Option<&str>,
         step: usize,
     ) -> Result<crate::models::PlaceOrderResult> {
-        // First try with cached working decimals if available
-        if let Some(cached_decimals) = self.precision_manager.get_cached_decimals(symbol) {
-            info!("🎯 Using cached decimals for {symbol}: {cached_decimals} decimals");
-            let formatted_quantity = self
+        // First try the planned quantity, else the quantity on the symbol's lot step, which
+        // Bybit accepts without any decimals retry
+        let first_attempt = match planned {
+            Some(formatted) => Some(formatted.to_string()),
+            None if self
                 .precision_manager
-                .format_quantity_smart(symbol, quantity);
-
+                .get_symbol_precision(symbol)
+                .is_some() =>
+            {
+                Some(
+                    self.precision_manager
+                        .format_order_quantity(symbol, quantity),
+                )
+            }
+            None => self
+                .precision_manager
+                .get_cached_decimals(symbol)
+                .map(|cached_decimals| {
+                    info!("🎯 Using cached decimals for {symbol}: {cached_decimals} decimals");
+                    self.precision_manager
+                        .format_quantity_smart(symbol, quantity)
+                }),
+        };
+        if let Some(formatted_quantity) = first_attempt {
+            // Planned legs had their value checked by the pre-simulation
+            if planned.is_none() {
+                let rounded = formatted_quantity
+                    .parse()
+                    .unwrap_or_else(|_| quantity.value());
+                self.check_order_value(symbol, quantity.with_value(rounded))
+                    .await?;
+            }
             match self
                 .attempt_order_placement(symbol, side, &formatted_quantity, step)
                 .await
             {
                 Ok(order_result) => {
                     info!(
-                        "✅ Order placed successfully using cached precision: {}",
+                        "✅ Order placed successfully using planned/lot-step precision: {}",
                         order_result.order_id
                     );
                     return Ok(order_result);
                 }
-                Err(e) => {
-                    let error_str = e.to_string();
-                    if error_str.contains("170137")
-                        || error_str.contains("170148")
-                        || error_str.contains("too many decimals")
-                    {
-                        warn!(
-                            "⚠️ Cached precision failed for {}, falling back to retry logic",
-                            symbol
-                        );
-                        // Continue to retry logic below
-                    } else {
-                        // Non-precision error, return immediately
-                        return Err(e);
-                    }
+                Err(e)
+                    if matches!(
+                        BybitError::find(&e),
+                        Some(BybitError::PrecisionTooLong { .. })
+                    ) =>
+                {
+                    warn!(
+                        "⚠️ Planned/lot-step precision failed for {}, falling back to retry logic",
+                        symbol
+                    );
+                    self.execution_stats.record_precision_retry(symbol);
+                    // Continue to retry logic below
                 }
+                // Non-precision error, return immediately
+                Err(e) => return Err(e),
             }
         }
 
@@ -965,47 +2721,35 @@ impl ArbitrageTrader {
                 self.precision_manager
                     .format_quantity_with_retry(symbol, quantity, retry_count);
 
-            // Parse the formatted quantity back to f64 to ensure we use the exact truncated amount
-            let actual_quantity: f64 = formatted_quantity.parse().unwrap_or(quantity);
+            // Parse the formatted quantity back to ensure we use the exact truncated amount
+            let actual_quantity = quantity.with_value(
+                formatted_quantity
+                    .parse()
+                    .unwrap_or_else(|_| quantity.value()),
+            );
 
             if retry_count > 0 {
                 warn!(
                     "🔄 Retry #{} for {}: Reducing precision (using {:.8})",
-                    retry_count, symbol, actual_quantity
+                    retry_count,
+                    symbol,
+                    actual_quantity.value()
                 );
             }
 
             // Validate the truncated quantity meets symbol requirements
-            // For Buy orders, we're using quote currency amounts, so skip base currency validations
-            if side == "Sell" {
-                if let Err(e) = self
-                    .precision_manager
-                    .validate_quantity(symbol, actual_quantity)
-                {
+            // (lot size limits are in base units, so they only apply to Sells)
+            if let OrderQty::Base(qty) = actual_quantity {
+                if let Err(e) = self.precision_manager.validate_quantity(symbol, qty) {
                     return Err(anyhow::anyhow!("Quantity validation failed: {e}"));
                 }
             }
 
-            // For market orders, estimate price for order value validation
-            if let Some(market_price) = self.get_estimated_market_price(symbol).await {
-                // For Buy orders, the order value is the quote amount we're spending (already in quantity)
-                // For Sell orders, the order value is quantity * price
-                let order_value = if side == "Buy" {
-                    actual_quantity // For Buy orders, quantity is already the quote currency amount
-                } else {
-                    actual_quantity * market_price // For Sell orders, calculate value
-                };
-
-                if let Err(e) =
-                    self.precision_manager
-                        .validate_order_value(symbol, order_value, 1.0)
-                {
-                    return Err(anyhow::anyhow!("Order value validation failed: {e}"));
-                }
-            }
+            self.check_order_value(symbol, actual_quantity).await?;
 
             info!(
-                "📊 Using precision for {symbol}: {actual_quantity:.8} (formatted: {formatted_quantity})"
+                "📊 Using precision for {symbol}: {:.8} (formatted: {formatted_quantity})",
+                actual_quantity.value()
             );
 
             // Attempt to place the order
@@ -1031,58 +2775,60 @@ impl ArbitrageTrader {
 
                     return Ok(order_result);
                 }
-                Err(e) => {
-                    let error_str = e.to_string();
-
-                    // Check if it's the "too many decimals" error
-                    if error_str.contains("170137") || error_str.contains("too many decimals") {
-                        if retry_count < MAX_RETRIES {
-                            warn!("⚠️ API Error 170137 (too many decimals) on attempt #{} - retrying with fewer decimals", retry_count + 1);
-                            continue; // Try again with fewer decimals
-                        } else {
-                            error!("❌ Failed after {} attempts - no more precision reduction possible", MAX_RETRIES + 1);
-                            return Err(anyhow::anyhow!(
-                                "Order placement failed after {} precision reduction attempts: {}",
-                                MAX_RETRIES + 1,
-                                error_str
-                            ));
-                        }
-                    } else if error_str.contains("170148")
-                        || error_str.contains("Market order amount decimal too long")
-                    {
-                        if retry_count < MAX_RETRIES {
-                            warn!("⚠️ API Error 170148 (market order decimal too long) on attempt #{} - retrying with fewer decimals", retry_count + 1);
-                            continue; // Try again with fewer decimals
-                        } else {
-                            error!("❌ Failed after {} attempts - no more precision reduction possible for market order", MAX_RETRIES + 1);
-                            return Err(anyhow::anyhow!("Market order placement failed after {} precision reduction attempts: {}", MAX_RETRIES + 1, error_str));
-                        }
-                    } else if error_str.contains("170131")
-                        || error_str.contains("Insufficient balance")
+                Err(e) => match BybitError::find(&e) {
+                    // Too many decimals (170137), or too long a market order amount (170148)
+                    Some(BybitError::PrecisionTooLong { code, .. })
+                        if retry_count < MAX_RETRIES =>
                     {
-                        // For insufficient balance, try reducing the quantity a bit more
-                        if retry_count < MAX_RETRIES {
-                            warn!("⚠️ API Error 170131 (insufficient balance) - will retry with reduced quantity/precision");
-                            continue; // Try again with more aggressive quantity reduction
-                        } else {
-                            error!("❌ Insufficient balance even after precision and quantity reduction");
-                            return Err(anyhow::anyhow!(
-                                "Order placement failed due to insufficient balance: {}",
-                                error_str
-                            ));
-                        }
-                    } else {
+                        warn!("⚠️ API Error {code} (too many decimals) on attempt #{} - retrying with fewer decimals", retry_count + 1);
+                        self.execution_stats.record_precision_retry(symbol);
+                        continue; // Try again with fewer decimals
+                    }
+                    Some(BybitError::PrecisionTooLong { .. }) => {
+                        error!(
+                            "❌ Failed after {} attempts - no more precision reduction possible",
+                            MAX_RETRIES + 1
+                        );
+                        return Err(e.context(format!(
+                            "Order placement failed after {} precision reduction attempts",
+                            MAX_RETRIES + 1
+                        )));
+                    }
+                    // For insufficient balance, try reducing the quantity a bit more
+                    Some(BybitError::InsufficientBalance { .. }) if retry_count < MAX_RETRIES => {
+                        warn!("⚠️ API Error 170131 (insufficient balance) - will retry with reduced quantity/precision");
+                        continue; // Try again with more aggressive quantity reduction
+                    }
+                    Some(BybitError::InsufficientBalance { .. }) => {
+                        error!(
+                            "❌ Insufficient balance even after precision and quantity reduction"
+                        );
+                        return Err(e.context("Order placement failed due to insufficient balance"));
+                    }
+                    _ => {
                         // Different error, don't retry
-                        error!("Failed to place order on {symbol}: {e}");
-                        return Err(anyhow::anyhow!("Order placement failed: {error_str}"));
+                        error!("Failed to place order on {symbol}: {e:#}");
+                        return Err(e);
                     }
-                }
+                },
             }
         }
 
         Err(anyhow::anyhow!("Unexpected end of retry loop"))
     }
 
+    /// Validate an order's value at a real price; without one the order isn't sent
+    async fn check_order_value(&self, symbol: &str, quantity: OrderQty) -> Result<()> {
+        let Some(market_price) = self.get_market_price(symbol).await else {
+            return Err(anyhow::anyhow!(
+                "No live price for {symbol} to validate the order value"
+            ));
+        };
+        self.precision_manager
+            .validate_order_value(symbol, quantity.notional_at(market_price))
+            .map_err(|e| anyhow::anyhow!("Order value validation failed: {e}"))
+    }
+
     /// Helper method to attempt order placement
     async fn attempt_order_placement(
         &self,
@@ -1090,8 +2836,8 @@ impl ArbitrageTrader {
         side: &str,
         formatted_quantity: &str,
         step: usize,
-    ) -> Result<crate::models::PlaceOrderResult> {
-        let order_link_id = format!("arb_{}_{step}", Uuid::new_v4().simple());
+    ) -> Result<PlaceOrderResult> {
+        let order_link_id = instance::order_link_id(&self.order_link_prefix, step);
 
         // Create market order for immediate execution
         let order_request = PlaceOrderRequest {
@@ -1111,7 +2857,193 @@ impl ArbitrageTrader {
             order_request.price
         );
 
-        self.client.place_order(order_request).await
+        self.send_order(order_request).await
+    }
+
+    /// Place an order, keeping the request and response for debug bundles. In audit mode the
+    /// intent is journaled first, so no order leaves without a record of it.
+    async fn send_order(&self, request: PlaceOrderRequest) -> Result<PlaceOrderResult> {
+        if let Some(journal) = &self.order_audit {
+            let quoted = self.quoted_prices.get(&request.symbol).map(|p| p.value());
+            journal
+                .try_record(JournalEvent::order_intent(&request, quoted))
+                .with_context(|| {
+                    format!(
+                        "Order on {} not sent: its intent could not be journaled",
+                        request.symbol
+                    )
+                })?;
+        }
+        let result = placement::place_order(&self.client, request.clone(), &self.in_flight).await;
+        self.order_log
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .placed
+            .push(OrderRecord::new(request, &result));
+        result
+    }
+
+    /// Send orders in one batch request, journaling and logging each like `send_order`
+    async fn send_batch(
+        &self,
+        requests: Vec<PlaceOrderRequest>,
+    ) -> Result<Vec<Result<PlaceOrderResult>>> {
+        if let Some(journal) = &self.order_audit {
+            for request in &requests {
+                let quoted = self.quoted_prices.get(&request.symbol).map(|p| p.value());
+                journal
+                    .try_record(JournalEvent::order_intent(request, quoted))
+                    .with_context(|| {
+                        format!(
+                            "Batch with {} not sent: its intent could not be journaled",
+                            request.symbol
+                        )
+                    })?;
+            }
+        }
+        let results =
+            placement::place_batch(&self.client, "spot", requests.clone(), &self.in_flight).await?;
+        let mut log = self.order_log.lock().unwrap_or_else(|e| e.into_inner());
+        for (request, result) in requests.into_iter().zip(&results) {
+            log.placed.push(OrderRecord::new(request, result));
+        }
+        Ok(results)
+    }
+
+    /// Find out what became of orders whose placement was interrupted before trading on.
+    /// Those Bybit has are kept for debug bundles; reconciliation accounts for their fills.
+    async fn resolve_interrupted_orders(&self) -> Result<()> {
+        if self.in_flight.is_empty() {
+            return Ok(());
+        }
+        let placed = placement::resolve_pending(&self.client, &self.in_flight).await;
+        self.order_log
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .fills
+            .extend(placed);
+        if !self.in_flight.is_empty() {
+            anyhow::bail!("Not trading while the outcome of an earlier order is unknown");
+        }
+        Ok(())
+    }
+
+    /// What a filled order executed, keeping the order for debug bundles
+    async fn record_fill(&self, order: &OrderInfo, side: String) -> Result<TradeExecution> {
+        self.order_log
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .fills
+            .push(order.clone());
+        let mut execution = trade_execution(order, side)?;
+        if self.fee_coin.is_some() && execution.fee > 0.0 {
+            self.split_fees(order, &mut execution).await;
+        }
+        if let Some(info) = self.precision_manager.get_symbol_precision(&order.symbol) {
+            let received_coin = if execution.side == "Buy" {
+                &info.base_coin
+            } else {
+                &info.quote_coin
+            };
+            let mut fees = execution.other_fees.clone();
+            if execution.fee > 0.0 {
+                *fees.entry(received_coin.clone()).or_insert(0.0) += execution.fee;
+            }
+            self.order_log
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .fees
+                .insert(order.order_id.clone(), fees);
+        }
+        Ok(execution)
+    }
+
+    /// Move the part of a fill's fee charged outside the received coin into `other_fees`,
+    /// from its execution records. Keeps assuming the received coin if they can't be read.
+    async fn split_fees(&self, order: &OrderInfo, execution: &mut TradeExecution) {
+        let Some(info) = self.precision_manager.get_symbol_precision(&order.symbol) else {
+            return;
+        };
+        let received_coin = if execution.side == "Buy" {
+            &info.base_coin
+        } else {
+            &info.quote_coin
+        };
+        let records = match self.client.get_executions("spot", &order.order_id).await {
+            Ok(records) if !records.is_empty() => records,
+            Ok(_) => return,
+            Err(e) => {
+                warn!(
+                    "⚠️ Could not read the fee coin of {}: {e:#} - assuming {received_coin}",
+                    order.order_id
+                );
+                return;
+            }
+        };
+
+        let mut fee = 0.0;
+        let mut other_fees = BTreeMap::new();
+        for record in &records {
+            let amount: f64 = record.exec_fee.parse().unwrap_or(0.0);
+            if record.fee_currency.is_empty() || &record.fee_currency == received_coin {
+                fee += amount;
+            } else {
+                *other_fees.entry(record.fee_currency.clone()).or_insert(0.0) += amount;
+            }
+        }
+        for (coin, amount) in &other_fees {
+            info!(
+                "🎟️ Fee on {} paid in {coin}: {amount:.8} (received {received_coin} left whole)",
+                order.symbol
+            );
+        }
+        execution.fee = fee;
+        execution.other_fees = other_fees;
+    }
+
+    /// Value in `coin` of the fees charged outside the traded coins, priced from the last
+    /// trade of `<fee coin><coin>`. Fees that can't be priced are left out with a warning.
+    async fn other_fees_value(&self, executions: &[TradeExecution], coin: &str) -> f64 {
+        let mut fees: BTreeMap<&str, f64> = BTreeMap::new();
+        for (fee_coin, amount) in executions.iter().flat_map(|e| &e.other_fees) {
+            *fees.entry(fee_coin).or_insert(0.0) += amount;
+        }
+        let mut value = 0.0;
+        for (fee_coin, amount) in fees {
+            if fee_coin == coin {
+                value += amount;
+                continue;
+            }
+            let symbol = format!("{fee_coin}{coin}");
+            let price = self
+                .client
+                .get_ticker("spot", &symbol)
+                .await
+                .ok()
+                .and_then(|t| t.list.first()?.last_price.as_deref()?.parse::<f64>().ok());
+            match price {
+                Some(price) => value += amount * price,
+                None => warn!(
+                    "⚠️ Could not price {amount:.8} {fee_coin} of fees in {coin} ({symbol}) - left out of the profit"
+                ),
+            }
+        }
+        value
+    }
+
+    /// Orders placed and filled since the last call
+    pub fn take_order_log(&mut self) -> OrderLog {
+        std::mem::take(self.order_log.get_mut().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    #[cfg(test)]
+    pub fn client(&self) -> &E {
+        &self.client
+    }
+
+    /// Order outcomes per symbol, for ranking triangles by executability
+    pub fn execution_stats(&self) -> &ExecutionStats {
+        &self.execution_stats
     }
 
     /// Get a reference to the precision manager (for cache access)
@@ -1119,3 +3051,853 @@ impl ArbitrageTrader {
         &self.precision_manager
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_execution_result_round_trip() {
+        let result = ArbitrageExecutionResult {
+            schema_version: SCHEMA_VERSION,
+            success: false,
+            initial_amount: 10.0,
+            actual_profit: -0.02,
+            actual_profit_pct: -0.2,
+            dust_value_usd: 0.001,
+            dust: BTreeMap::from([("BTC".to_string(), 0.00000002)]),
+            total_fees: 0.03,
+            execution_time_ms: 850,
+            error_message: Some("Timeout error: Order execution timeout".to_string()),
+            exchange_error: None,
+        };
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["execution_time_ms"], 850);
+        assert_eq!(
+            json["error_message"],
+            "Timeout error: Order execution timeout"
+        );
+
+        let parsed: ArbitrageExecutionResult = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, result);
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_run_on_virtual_time() {
+        use crate::clock::ManualClock;
+        use crate::exchange::MockExchange;
+
+        let clock = Arc::new(ManualClock::new());
+        let trader = ArbitrageTrader::new(MockExchange::new(), false, PrecisionManager::new())
+            .with_clock(clock.clone());
+
+        // BTC never arrives: settlement gives up after 5s of virtual time
+        let settled = trader
+            .wait_for_balance_settlement(2, &ArbitrageOpportunity::test())
+            .await
+            .unwrap();
+        assert_eq!(settled, 0.0);
+        let waited = clock.elapsed();
+        assert!(waited > Duration::from_secs(5) && waited < Duration::from_millis(5100));
+
+        // Unknown order: the status poll times out after max_order_wait_time
+        let result = trader.wait_for_order_execution("missing", "BTCUSDT").await;
+        assert!(result.unwrap_err().to_string().contains("timeout"));
+        let waited = clock.elapsed() - waited;
+        assert!(waited > Duration::from_secs(30) && waited < Duration::from_secs(31));
+    }
+
+    /// Trader over a BTCUSDT mock with `resting` Buy limits 10,000 under the market
+    async fn resting_orders_trader(
+        resting: usize,
+    ) -> (ArbitrageTrader<crate::exchange::MockExchange>, Vec<String>) {
+        use crate::clock::ManualClock;
+        use crate::exchange::{MockExchange, MockMarket};
+
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_balance("USDT", 1_000.0);
+        let trader = ArbitrageTrader::new(exchange, false, PrecisionManager::new())
+            .with_clock(Arc::new(ManualClock::new()));
+        let mut order_ids = Vec::new();
+        for _ in 0..resting {
+            let request = PlaceOrderRequest {
+                category: "spot".to_string(),
+                symbol: "BTCUSDT".to_string(),
+                side: "Buy".to_string(),
+                order_type: "Limit".to_string(),
+                qty: "0.001".to_string(),
+                price: Some("40000".to_string()),
+                time_in_force: Some("GTC".to_string()),
+                order_link_id: None,
+                reduce_only: None,
+            };
+            order_ids.push(trader.client().place_order(request).await.unwrap().order_id);
+        }
+        (trader, order_ids)
+    }
+
+    #[tokio::test]
+    async fn test_orders_still_open_after_the_wait_are_cancelled() {
+        let (trader, resting) = resting_orders_trader(2).await;
+
+        let result = trader
+            .wait_for_order_execution(&resting[0], "BTCUSDT")
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("timeout"));
+        let statuses: Vec<_> = trader
+            .client()
+            .orders()
+            .into_iter()
+            .map(|o| o.order_status)
+            .collect();
+        // Only the order waited on is cancelled
+        assert_eq!(statuses, ["Cancelled", "New"]);
+    }
+
+    #[tokio::test]
+    async fn test_amend_moves_an_open_order_only() {
+        let (trader, resting) = resting_orders_trader(2).await;
+        trader
+            .client()
+            .cancel_order("spot", &resting[0], "BTCUSDT")
+            .await
+            .unwrap();
+        let amend = |order_id: &str| AmendOrderRequest {
+            category: "spot".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            order_id: order_id.to_string(),
+            qty: Some("0.002".to_string()),
+            price: Some("45000".to_string()),
+        };
+
+        let amended = trader.client().amend_order(amend(&resting[1])).await;
+
+        assert_eq!(amended.unwrap().order_id, resting[1]);
+        let order = &trader.client().orders()[1];
+        assert_eq!(
+            (order.qty.as_str(), order.price.as_str()),
+            ("0.002", "45000")
+        );
+        let error = trader.client().amend_order(amend(&resting[0])).await;
+        assert!(matches!(
+            BybitError::find(&error.unwrap_err()),
+            Some(BybitError::OrderNotFound { .. })
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_maker_leg_follows_the_quote() {
+        let (trader, _) = race_trader(50_000.0, 100.0).await;
+        let leg = SimulatedLeg {
+            symbol: "BTCUSDT".to_string(),
+            side: "Buy".to_string(),
+            quantity: OrderQty::Quote(Notional::new(100.0)),
+            formatted: "100".to_string(),
+            received: 0.002,
+        };
+        let (order_id, mut price) = trader.place_post_only(&leg).await.unwrap();
+        assert_eq!(price, "49990.00000000");
+
+        // Unchanged quote: nothing to amend
+        trader.requote(&leg, &order_id, &mut price).await;
+        assert_eq!(trader.client().orders()[0].price, "49990.00000000");
+
+        trader.client().set_quote("BTCUSDT", 49_995.0, 50_000.0);
+        trader.requote(&leg, &order_id, &mut price).await;
+
+        let orders = trader.client().orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].order_id, order_id);
+        assert_eq!(orders[0].price, "49995.00000000");
+        assert_eq!(price, orders[0].price);
+        let notional = orders[0].qty.parse::<f64>().unwrap() * 49_995.0;
+        assert!((99.9..=100.0).contains(&notional), "{notional}");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_settlement_waits_on_wallet_pushes() {
+        use crate::exchange::MockExchange;
+
+        let wallet = WalletFeed::new();
+        wallet.set_connected(true);
+        let trader = ArbitrageTrader::new(MockExchange::new(), false, PrecisionManager::new())
+            .with_wallet_feed(wallet.clone());
+
+        let push = {
+            let wallet = wallet.clone();
+            async move {
+                tokio::time::sleep(Duration::from_millis(3)).await;
+                wallet.apply(HashMap::from([("BTC".to_string(), 0.002)]));
+            }
+        };
+        let opportunity = ArbitrageOpportunity::test();
+        let (settled, ()) = tokio::join!(trader.wait_for_balance_settlement(2, &opportunity), push);
+        assert_eq!(settled.unwrap(), 0.002);
+        assert_eq!(trader.client().wallet_calls(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_conversion_places_single_order() {
+        use crate::exchange::{MockExchange, MockMarket};
+
+        let exchange = MockExchange::new()
+            .with_market("USDCUSDT", MockMarket::new("USDC", "USDT", 0.997, 0.998))
+            .with_balance("USDT", 100.0);
+        let precision =
+            PrecisionManager::from_instruments(exchange.get_all_spot_instruments().await.unwrap());
+        let mut trader = ArbitrageTrader::new(exchange, false, precision);
+
+        let fill = trader
+            .execute_conversion("USDCUSDT", "Buy", 49.9, Price::new(0.998))
+            .await
+            .unwrap();
+
+        assert_eq!(fill.executed_quantity, Qty::new(50.0));
+        let orders = trader.client().orders();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].side, "Buy");
+    }
+
+    /// Two disjoint triangles: BTC quoted at `btc_price` against a 50,000 ask, SOL at its ask
+    async fn race_trader(
+        btc_price: f64,
+        sol_price: f64,
+    ) -> (
+        ArbitrageTrader<crate::exchange::MockExchange>,
+        [ArbitrageOpportunity; 2],
+    ) {
+        use crate::exchange::{MockExchange, MockMarket};
+
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_market("ETHBTC", MockMarket::new("ETH", "BTC", 0.0499, 0.05))
+            .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_560.0))
+            .with_market("SOLUSDT", MockMarket::new("SOL", "USDT", 99.9, 100.0))
+            .with_market("SOLUSDC", MockMarket::new("SOL", "USDC", 102.0, 102.1))
+            .with_market("USDCUSDT", MockMarket::new("USDC", "USDT", 1.0, 1.0001))
+            .with_balance("USDT", 200.0);
+        let precision =
+            PrecisionManager::from_instruments(exchange.get_all_spot_instruments().await.unwrap());
+        let trader = ArbitrageTrader::new(exchange, false, precision);
+
+        let btc = ArbitrageOpportunity::test().with_prices(&[btc_price, 0.05, 2_550.0]);
+        let sol = ArbitrageOpportunity::test()
+            .with_path(&["USDT", "SOL", "USDC", "USDT"])
+            .with_pairs(&["SOLUSDT", "SOLUSDC", "USDCUSDT"])
+            .with_prices(&[sol_price, 102.0, 1.0]);
+        (trader, [btc, sol])
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_race_completes_first_fill_and_cancels_other() {
+        // BTC's leg 1 bids under the ask and rests, SOL's crosses and fills
+        let (mut trader, [btc, sol]) = race_trader(49_900.0, 100.0).await;
+
+        let (winner, result) = trader.execute_race([(&btc, 100.0), (&sol, 100.0)]).await;
+
+        let result = result.unwrap();
+        assert_eq!(winner, 1);
+        assert!(result.success, "{:?}", result.error_message);
+        let orders = trader.client().orders();
+        let btc_leg = orders.iter().find(|o| o.symbol == "BTCUSDT").unwrap();
+        assert_eq!(btc_leg.order_status, "Cancelled");
+        let symbols: Vec<_> = orders.iter().map(|o| o.symbol.as_str()).collect();
+        assert_eq!(symbols, ["BTCUSDT", "SOLUSDT", "SOLUSDC", "USDCUSDT"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_race_gives_up_when_neither_leg_fills() {
+        let (mut trader, [btc, sol]) = race_trader(49_900.0, 99.0).await;
+
+        let (winner, result) = trader.execute_race([(&btc, 100.0), (&sol, 100.0)]).await;
+
+        let result = result.unwrap();
+        assert_eq!(winner, 0);
+        assert!(!result.success);
+        assert!(result
+            .error_message
+            .unwrap()
+            .contains("neither leg 1 filled"));
+        let orders = trader.client().orders();
+        assert_eq!(orders.len(), 2);
+        assert!(orders.iter().all(|o| o.order_status == "Cancelled"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unfilled_maker_leg_goes_to_market_or_abandons_the_triangle() {
+        let (trader, [btc, _]) = race_trader(50_000.0, 100.0).await;
+        let mut trader =
+            trader.with_execution_strategy(ExecutionStrategy::MakerFirst, Duration::from_secs(2));
+
+        // Leg 1 rests at the 49,990 bid until the timeout, then buys at market
+        let result = trader.execute_arbitrage(&btc, 100.0).await.unwrap();
+        assert!(result.success, "{:?}", result.error_message);
+        let orders: Vec<_> = trader
+            .client()
+            .orders()
+            .into_iter()
+            .map(|o| (o.symbol, o.order_type, o.order_status))
+            .collect();
+        let order = |symbol: &str, order_type: &str, status: &str| {
+            (
+                symbol.to_string(),
+                order_type.to_string(),
+                status.to_string(),
+            )
+        };
+        assert_eq!(
+            orders,
+            [
+                order("BTCUSDT", "Limit", "Cancelled"),
+                order("BTCUSDT", "Market", "Filled"),
+                order("ETHBTC", "Market", "Filled"),
+                order("ETHUSDT", "Market", "Filled"),
+            ]
+        );
+        assert_eq!(trader.client().orders()[0].price, "49990.00000000");
+
+        // Hybrid never takes on leg 1: nothing filled, nothing else is traded
+        let (trader, [btc, _]) = race_trader(50_000.0, 100.0).await;
+        let mut trader =
+            trader.with_execution_strategy(ExecutionStrategy::Hybrid, Duration::from_secs(2));
+        let result = trader.execute_arbitrage(&btc, 100.0).await.unwrap();
+        assert!(!result.success);
+        assert!(result
+            .error_message
+            .unwrap()
+            .contains("not filled within 2000ms"));
+        assert_eq!(trader.client().orders().len(), 1);
+    }
+
+    /// The BTC triangle with 100 USDT, its maker leg 1 filling 40% at the 49,990 bid and
+    /// resting with the rest until cancelled
+    async fn partly_filled_maker_trader(
+        strategy: ExecutionStrategy,
+        exchange: impl FnOnce(crate::exchange::MockExchange) -> crate::exchange::MockExchange,
+    ) -> ArbitrageTrader<crate::exchange::MockExchange> {
+        use crate::exchange::{MockExchange, MockMarket};
+
+        let exchange = exchange(
+            MockExchange::new()
+                .with_market(
+                    "BTCUSDT",
+                    MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+                )
+                .with_market("ETHBTC", MockMarket::new("ETH", "BTC", 0.0499, 0.05))
+                .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_560.0))
+                .with_balance("USDT", 100.0)
+                .partially_fill_next_resting_order("BTCUSDT", 0.4),
+        );
+        let precision =
+            PrecisionManager::from_instruments(exchange.get_all_spot_instruments().await.unwrap());
+        ArbitrageTrader::new(exchange, false, precision)
+            .with_execution_strategy(strategy, Duration::from_secs(2))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_partly_filled_maker_leg_shrinks_the_triangle() {
+        let opportunity = ArbitrageOpportunity::test();
+
+        // Hybrid continues with the 40 USDT that filled; the rest never left the wallet
+        let mut trader = partly_filled_maker_trader(ExecutionStrategy::Hybrid, |e| e).await;
+        let result = trader.execute_arbitrage(&opportunity, 100.0).await.unwrap();
+        assert!(result.success, "{:?}", result.error_message);
+        assert!((39.0..41.0).contains(&result.initial_amount), "{result:?}");
+        assert!(!result.dust.contains_key("USDT"), "{result:?}");
+        assert!(result.actual_profit > 0.0, "{result:?}");
+        let statuses: Vec<_> = trader
+            .client()
+            .orders()
+            .into_iter()
+            .map(|o| (o.symbol, o.order_status))
+            .collect();
+        assert_eq!(
+            statuses[0],
+            ("BTCUSDT".into(), "PartiallyFilledCanceled".into())
+        );
+        assert_eq!(statuses.len(), 3);
+
+        // MakerFirst whose market remainder fails does the same
+        let mut trader = partly_filled_maker_trader(ExecutionStrategy::MakerFirst, |e| {
+            e.fail_next_market_order("BTCUSDT", "API Error 170193: Order price too low")
+        })
+        .await;
+        let result = trader.execute_arbitrage(&opportunity, 100.0).await.unwrap();
+        assert!(result.success, "{:?}", result.error_message);
+        assert!((39.0..41.0).contains(&result.initial_amount), "{result:?}");
+        assert!(result.actual_profit > 0.0, "{result:?}");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_batched_legs_complete_the_triangle() {
+        let (trader, [btc, _]) = race_trader(50_000.0, 100.0).await;
+        let mut trader = trader.with_batch_legs();
+
+        let result = trader.execute_arbitrage(&btc, 100.0).await.unwrap();
+        assert!(result.success, "{:?}", result.error_message);
+        let orders: Vec<_> = trader
+            .client()
+            .orders()
+            .into_iter()
+            .map(|o| (o.symbol, o.order_status))
+            .collect();
+        assert_eq!(
+            orders,
+            [
+                ("BTCUSDT".to_string(), "Filled".to_string()),
+                ("ETHBTC".to_string(), "Filled".to_string()),
+                ("ETHUSDT".to_string(), "Filled".to_string()),
+            ]
+        );
+        // Leg 3 was sent with leg 2, so its input balance was never waited for
+        assert_eq!(trader.client().wallet_calls(), 1);
+        let log = trader.take_order_log();
+        assert_eq!(log.placed.len(), 3);
+        assert_eq!(log.fills.len(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_batched_leg3_is_unwound_when_leg2_fails() {
+        use crate::exchange::{MockExchange, MockMarket};
+
+        // Leg 3 sells ETH already in the wallet while leg 2 is rejected, in the batch and again
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_market("ETHBTC", MockMarket::new("ETH", "BTC", 0.0499, 0.05))
+            .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_560.0))
+            .with_balance("USDT", 200.0)
+            .with_balance("ETH", 1.0)
+            .fail_market_orders("ETHBTC", "API Error 170131: Insufficient balance", 2);
+        let precision =
+            PrecisionManager::from_instruments(exchange.get_all_spot_instruments().await.unwrap());
+        let mut trader = ArbitrageTrader::new(exchange, false, precision).with_batch_legs();
+        let btc = ArbitrageOpportunity::test().with_prices(&[50_000.0, 0.05, 2_550.0]);
+
+        let result = trader.execute_arbitrage(&btc, 100.0).await.unwrap();
+        assert!(!result.success);
+        let orders: Vec<_> = trader
+            .client()
+            .orders()
+            .into_iter()
+            .map(|o| (o.symbol, o.side))
+            .collect();
+        // The ETH leg 3 sold is bought back before BTC goes back to USDT
+        assert_eq!(
+            orders,
+            [
+                ("BTCUSDT".to_string(), "Buy".to_string()),
+                ("ETHUSDT".to_string(), "Sell".to_string()),
+                ("ETHUSDT".to_string(), "Buy".to_string()),
+                ("BTCUSDT".to_string(), "Sell".to_string()),
+            ]
+        );
+        // Leg 3's fill is kept with leg 1's for debug bundles
+        assert_eq!(trader.take_order_log().fills.len(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_usd_profit_floor_applies_at_the_traded_amount() {
+        let (trader, [btc, _]) = race_trader(50_000.0, 100.0).await;
+        // About 1.7% after rounding and fees: $0.84 on $50, short of a $1.50 floor
+        let mut trader = trader.with_min_profit_usd(1.5);
+
+        // Without a USD price for the start coin the floor can't be checked
+        let result = trader.execute_arbitrage(&btc, 100.0).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            result.error_message.as_deref(),
+            Some("No USD price for USDT")
+        );
+        trader.set_usd_prices(HashMap::from([("USDT".to_string(), 1.0)]));
+
+        let result = trader.execute_arbitrage(&btc, 50.0).await.unwrap();
+        assert!(!result.success);
+        assert!(result
+            .error_message
+            .unwrap()
+            .contains("below MIN_PROFIT_USD"));
+        assert!(trader.client().orders().is_empty());
+
+        let result = trader.execute_arbitrage(&btc, 100.0).await.unwrap();
+        assert!(result.success, "{:?}", result.error_message);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_order_audit_journals_every_intent_before_sending() {
+        let path = std::env::temp_dir().join(format!("audit_test_{}.jsonl", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let (trader, [btc, _]) = race_trader(50_000.0, 100.0).await;
+        let mut trader = trader.with_order_audit(Journal::open(&path).unwrap());
+
+        let result = trader.execute_arbitrage(&btc, 100.0).await.unwrap();
+        assert!(result.success, "{:?}", result.error_message);
+
+        let intents: Vec<JournalEvent> = Journal::read_records(&path)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.event)
+            .collect();
+        let orders = trader.client().orders();
+        assert_eq!(intents.len(), orders.len());
+        for (intent, order) in intents.iter().zip(&orders) {
+            let JournalEvent::OrderIntent {
+                order_link_id,
+                symbol,
+                quoted_price,
+                expected_fill,
+                ..
+            } = intent
+            else {
+                panic!("unexpected journal event {intent:?}");
+            };
+            assert_eq!(order_link_id, &order.order_link_id);
+            assert_eq!(symbol, &order.symbol);
+            let leg = btc.pairs.iter().position(|p| p == symbol).unwrap();
+            assert_eq!(*quoted_price, Some(btc.prices[leg].value()));
+            assert!(expected_fill.is_some_and(|fill| fill > 0.0));
+        }
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_expired_opportunity_is_never_started() {
+        let (mut trader, [btc, sol]) = race_trader(50_000.0, 100.0).await;
+        let btc = ArbitrageOpportunity {
+            valid_until: Some(btc.timestamp - chrono::Duration::milliseconds(1)),
+            ..btc
+        };
+
+        let result = trader.execute_arbitrage(&btc, 100.0).await.unwrap();
+        assert_eq!(result.error_message.as_deref(), Some("Opportunity expired"));
+        assert!(trader.client().orders().is_empty());
+
+        // A race trades the contender that is still valid
+        let (winner, result) = trader.execute_race([(&btc, 100.0), (&sol, 100.0)]).await;
+        assert_eq!(winner, 1);
+        assert!(result.unwrap().success);
+        assert!(trader
+            .client()
+            .orders()
+            .iter()
+            .all(|o| o.symbol != "BTCUSDT"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stacked_book_skips_leg1_and_delays_later_legs() {
+        let (trader, [btc, _]) = race_trader(50_000.0, 100.0).await;
+        let mut trader = trader.with_imbalance_filter(5.0);
+        let stacked = TopOfBook {
+            bid_price: 49_990.0,
+            ask_price: 50_000.0,
+            bid_size: 50.0,
+            ask_size: 5.0,
+        };
+
+        let btcusdt = trader.symbols.symbol_id("BTCUSDT").unwrap();
+        trader.set_local_book(HashMap::from([(btcusdt, stacked)]));
+        let result = trader.execute_arbitrage(&btc, 100.0).await.unwrap();
+        assert!(!result.success);
+        assert!(result
+            .error_message
+            .unwrap()
+            .starts_with("Book imbalance: Order book stacked 10.0x against a Buy on BTCUSDT"));
+        assert!(trader.client().orders().is_empty());
+
+        // Leg 2 waits for a fresh ticker, which shows a balanced book
+        let ethbtc = trader.symbols.symbol_id("ETHBTC").unwrap();
+        trader.set_local_book(HashMap::from([(ethbtc, stacked)]));
+        let result = trader.execute_arbitrage(&btc, 100.0).await.unwrap();
+        assert!(result.success, "{:?}", result.error_message);
+        assert_eq!(trader.client().orders().len(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fees_paid_in_another_coin_leave_legs_whole() {
+        use crate::exchange::{MockExchange, MockMarket};
+
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_market("ETHBTC", MockMarket::new("ETH", "BTC", 0.0499, 0.05))
+            .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_560.0))
+            .with_market("MNTUSDT", MockMarket::new("MNT", "USDT", 0.5, 0.5))
+            .with_fee_coin("MNT")
+            .with_balance("USDT", 100.0)
+            .with_balance("MNT", 10.0);
+        let precision =
+            PrecisionManager::from_instruments(exchange.get_all_spot_instruments().await.unwrap());
+        let fees = FeeSchedule::flat(TAKER_FEE_RATE).with_discount(0.5);
+        let mut trader =
+            ArbitrageTrader::new(exchange, false, precision).with_fees(fees, Some("MNT".into()));
+        assert_eq!(trader.fees.taker("BTCUSDT"), 0.0005);
+
+        let result = trader
+            .execute_arbitrage(&ArbitrageOpportunity::test(), 100.0)
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error_message);
+        // Leg 1 delivered all the BTC it bought, its fee went to MNT
+        let orders = trader.client().orders();
+        let leg1 = trader.record_fill(&orders[0], "Buy".into()).await.unwrap();
+        assert_eq!(leg1.fee, 0.0);
+        assert_eq!(leg1.received(), 0.002);
+        assert_eq!(leg1.other_fees["MNT"], 0.002 * 0.001);
+
+        // The MNT fees, worth 0.5 USDT each, come out of the profit instead
+        let mnt_fees: f64 = orders
+            .iter()
+            .map(|o| o.cum_exec_fee.parse::<f64>().unwrap())
+            .sum();
+        let usdt_received: f64 = orders[2].cum_exec_value.parse().unwrap();
+        let expected = usdt_received - 100.0 - mnt_fees * 0.5;
+        assert!((result.actual_profit - expected).abs() < 1e-9, "{result:?}");
+    }
+
+    /// Leg 3 rejected, with a rollback through wide BTC/USDT spreads losing ~2.6%
+    async fn parked_trader() -> ArbitrageTrader<crate::exchange::MockExchange> {
+        use crate::exchange::{MockExchange, MockMarket};
+
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_000.0, 50_000.0),
+            )
+            .with_market("ETHBTC", MockMarket::new("ETH", "BTC", 0.0499, 0.05))
+            .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_400.0, 2_410.0))
+            .with_balance("USDT", 100.0)
+            .fail_next_market_order("ETHUSDT", "API Error 170193: Order price too low");
+        let precision =
+            PrecisionManager::from_instruments(exchange.get_all_spot_instruments().await.unwrap());
+        let mut trader = ArbitrageTrader::new(exchange, false, precision).with_hedging(1.0);
+
+        let result = trader
+            .execute_arbitrage(&ArbitrageOpportunity::test(), 100.0)
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error_message.unwrap().contains("parked"));
+        trader
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_leg3_parks_behind_breakeven_limit() {
+        let mut trader = parked_trader().await;
+
+        let parked = trader.take_parked_positions();
+        assert_eq!(parked.len(), 1);
+        assert_eq!(
+            (parked[0].coin.as_str(), parked[0].side.as_str()),
+            ("ETH", "Sell")
+        );
+        let exit = trader.client().orders().pop().unwrap();
+        assert_eq!(
+            (exit.order_type.as_str(), exit.order_status.as_str()),
+            ("Limit", "New")
+        );
+        // Selling everything at the limit returns the starting 100 USDT after fees
+        let proceeds = exit.qty.parse::<f64>().unwrap()
+            * exit.price.parse::<f64>().unwrap()
+            * (1.0 - TAKER_FEE_RATE);
+        assert!((100.0..100.01).contains(&proceeds), "{proceeds}");
+
+        // Nothing happens until the market reaches the limit
+        let now = Utc::now();
+        assert!(trader.manage_positions(now).await.is_empty());
+        trader.client().fill_resting_orders();
+        let closed = trader.manage_positions(now).await;
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].reason, "filled");
+        assert!(closed[0].received >= 100.0);
+        assert!(!trader.has_open_positions());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_parked_position_liquidated_after_max_hold() {
+        let mut trader = parked_trader().await;
+
+        let closed = trader
+            .manage_positions(Utc::now() + chrono::Duration::hours(2))
+            .await;
+
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].reason, "liquidated");
+        // Sold at the 2400 bid instead of the ~2508 breakeven
+        assert!((95.0..100.0).contains(&closed[0].received));
+        let orders = trader.client().orders();
+        assert_eq!(orders[orders.len() - 2].order_status, "Cancelled");
+        assert_eq!(orders.last().unwrap().order_type, "Market");
+        assert!(!trader.has_open_positions());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_partial_fill_below_the_minimum_is_rolled_back() {
+        use crate::exchange::{MockExchange, MockMarket};
+
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_market("ETHBTC", MockMarket::new("ETH", "BTC", 0.0499, 0.05))
+            .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_560.0))
+            .with_balance("USDT", 100.0)
+            .partially_fill_next_market_order("ETHBTC", 0.00001);
+        let precision =
+            PrecisionManager::from_instruments(exchange.get_all_spot_instruments().await.unwrap());
+        let mut trader = ArbitrageTrader::new(exchange, false, precision);
+
+        let result = trader
+            .execute_arbitrage(&ArbitrageOpportunity::test(), 100.0)
+            .await
+            .unwrap();
+        assert!(!result.success);
+
+        // Leg 2 stalled after filling less ETH than ETHBTC's minimum order: it is cancelled
+        // and the BTC it didn't spend goes back to USDT
+        let orders = trader.client().orders();
+        let legs: Vec<_> = orders
+            .iter()
+            .map(|o| format!("{} {} {}", o.side, o.symbol, o.order_status))
+            .collect();
+        assert_eq!(
+            legs,
+            [
+                "Buy BTCUSDT Filled",
+                "Buy ETHBTC PartiallyFilledCanceled",
+                "Sell BTCUSDT Filled"
+            ]
+        );
+        let bought: f64 = orders[0].cum_exec_qty.parse().unwrap();
+        let sold: f64 = orders[2].qty.parse().unwrap();
+        assert!(sold < bought && sold > bought * 0.99, "{sold} of {bought}");
+        // The few ETH it bought are too few to sell back
+        assert!(result.dust["ETH"] > 0.0);
+        // Realized in USDT: the spread and fees of going there and back, not the whole amount
+        assert!((result.actual_profit - (order_received(&orders[2]) - 100.0)).abs() < 1e-9);
+        assert!(
+            result.actual_profit < 0.0 && result.actual_profit > -1.5,
+            "{result:?}"
+        );
+        assert!(result.error_message.unwrap().contains("still holding"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_partial_leg1_continues_at_the_filled_size() {
+        use crate::exchange::{MockExchange, MockMarket};
+
+        let exchange = MockExchange::new()
+            .with_market(
+                "BTCUSDT",
+                MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+            )
+            .with_market("ETHBTC", MockMarket::new("ETH", "BTC", 0.0499, 0.05))
+            .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_560.0))
+            .with_balance("USDT", 100.0)
+            .partially_fill_next_market_order("BTCUSDT", 0.4);
+        let precision =
+            PrecisionManager::from_instruments(exchange.get_all_spot_instruments().await.unwrap());
+        let mut trader = ArbitrageTrader::new(exchange, false, precision);
+
+        let result = trader
+            .execute_arbitrage(&ArbitrageOpportunity::test(), 100.0)
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error_message);
+
+        // Leg 1 stalled after 40 USDT: the triangle runs on the BTC that bought
+        let orders = trader.client().orders();
+        assert_eq!(orders.len(), 3);
+        assert_eq!(orders[0].order_status, "PartiallyFilledCanceled");
+        assert!((result.initial_amount - 40.0).abs() < 1e-9, "{result:?}");
+        // The 60 USDT it didn't spend are not dust
+        assert!(result.actual_profit > 0.0, "{result:?}");
+        assert!(!result.dust.contains_key("USDT"), "{result:?}");
+        let eth_bought: f64 = orders[1].cum_exec_qty.parse().unwrap();
+        assert!((0.0159..0.016).contains(&eth_bought), "{eth_bought}");
+    }
+
+    #[tokio::test]
+    async fn test_order_without_a_live_price_is_not_sent() {
+        use crate::exchange::{MockExchange, MockMarket};
+
+        let listed = MockExchange::new().with_market(
+            "BTCUSDT",
+            MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+        );
+        let precision =
+            PrecisionManager::from_instruments(listed.get_all_spot_instruments().await.unwrap());
+        // No ticker for BTCUSDT and no quote from an opportunity
+        let mut trader = ArbitrageTrader::new(MockExchange::new(), false, precision);
+
+        let error = trader
+            .place_order_with_precision_retry(
+                "BTCUSDT",
+                "Buy",
+                OrderQty::Quote(Notional::new(100.0)),
+                None,
+                1,
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("No live price"), "{error}");
+        assert!(trader.client().orders().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_interrupted_triangle_is_rolled_back_or_completed() {
+        use crate::exchange::{MockExchange, MockMarket};
+
+        let resume = |mode: ResumeMode| async move {
+            let exchange = MockExchange::new()
+                .with_market(
+                    "BTCUSDT",
+                    MockMarket::new("BTC", "USDT", 49_990.0, 50_000.0),
+                )
+                .with_market("ETHBTC", MockMarket::new("ETH", "BTC", 0.0499, 0.05))
+                .with_market("ETHUSDT", MockMarket::new("ETH", "USDT", 2_550.0, 2_560.0))
+                .with_balance("BTC", 0.002);
+            let precision = PrecisionManager::from_instruments(
+                exchange.get_all_spot_instruments().await.unwrap(),
+            );
+            let mut trader = ArbitrageTrader::new(exchange, false, precision);
+            // Killed after leg 1 filled, just before leg 2 went out
+            let state = ExecutionState {
+                opportunity: ArbitrageOpportunity::test(),
+                amount: 100.0,
+                completed_legs: 1,
+                held_coin: "BTC".to_string(),
+                held_amount: 0.002,
+                started_at: Utc::now(),
+                updated_at: Utc::now(),
+            };
+            trader.resume_interrupted(&state, mode).await.unwrap();
+            trader
+                .client()
+                .orders()
+                .iter()
+                .map(|o| format!("{} {}", o.side, o.symbol))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(resume(ResumeMode::Rollback).await, ["Sell BTCUSDT"]);
+        assert_eq!(
+            resume(ResumeMode::Complete).await,
+            ["Buy ETHBTC", "Sell ETHUSDT"]
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Recent window turnover and idle time are measured over
+const WINDOW_SECS: i64 = 3600;
+
+/// How much of the account's capital trading puts to work, for the status and metrics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UtilizationSummary {
+    /// Account value the turnover is measured against, in USD
+    pub account_value_usd: f64,
+    /// Notional traded over the last hour, in USD
+    pub notional_last_hour_usd: f64,
+    /// Notional traded over the last hour as a multiple of the account value
+    pub turnover_last_hour: Option<f64>,
+    /// Share of the last hour, or of the session if shorter, no trade was executing
+    pub idle_last_hour_pct: Option<f64>,
+    pub notional_session_usd: f64,
+    /// Notional traded per hour this session as a multiple of the account value
+    pub turnover_per_hour_session: Option<f64>,
+    pub idle_session_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Execution {
+    started_at: DateTime<Utc>,
+    busy_secs: f64,
+    notional_usd: f64,
+}
+
+/// Notional traded and time spent executing, this session and over the last hour
+#[derive(Debug)]
+pub struct CapitalUtilization {
+    started_at: DateTime<Utc>,
+    recent: VecDeque<Execution>,
+    notional_session_usd: f64,
+    busy_session_secs: f64,
+}
+
+impl CapitalUtilization {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            started_at: now,
+            recent: VecDeque::new(),
+            notional_session_usd: 0.0,
+            busy_session_secs: 0.0,
+        }
+    }
+
+    /// Note an execution that started at `started_at`, took `busy_secs` and traded
+    /// `notional_usd` across its orders
+    pub fn record(&mut self, started_at: DateTime<Utc>, busy_secs: f64, notional_usd: f64) {
+        self.recent.push_back(Execution {
+            started_at,
+            busy_secs,
+            notional_usd,
+        });
+        self.notional_session_usd += notional_usd;
+        self.busy_session_secs += busy_secs;
+    }
+
+    pub fn summary(&mut self, now: DateTime<Utc>, account_value_usd: f64) -> UtilizationSummary {
+        let window_start = now - Duration::seconds(WINDOW_SECS);
+        while self
+            .recent
+            .front()
+            .is_some_and(|e| e.started_at < window_start)
+        {
+            self.recent.pop_front();
+        }
+        let notional_last_hour_usd: f64 = self.recent.iter().map(|e| e.notional_usd).sum();
+        let busy_last_hour_secs: f64 = self.recent.iter().map(|e| e.busy_secs).sum();
+
+        let session_secs = (now - self.started_at).as_seconds_f64();
+        let last_hour_secs = session_secs.min(WINDOW_SECS as f64);
+        let idle_pct = |busy: f64, span: f64| {
+            (span >= 1.0).then(|| (100.0 - busy / span * 100.0).clamp(0.0, 100.0))
+        };
+        let turnover =
+            |notional: f64| (account_value_usd > 0.0).then(|| notional / account_value_usd);
+        UtilizationSummary {
+            account_value_usd,
+            notional_last_hour_usd,
+            turnover_last_hour: turnover(notional_last_hour_usd),
+            idle_last_hour_pct: idle_pct(busy_last_hour_secs, last_hour_secs),
+            notional_session_usd: self.notional_session_usd,
+            turnover_per_hour_session: turnover(self.notional_session_usd)
+                .filter(|_| session_secs >= 1.0)
+                .map(|turnover| turnover / (session_secs / 3600.0)),
+            idle_session_pct: idle_pct(self.busy_session_secs, session_secs),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turnover_and_idle_time_over_the_last_hour_and_session() {
+        let start = Utc::now();
+        let mut utilization = CapitalUtilization::new(start);
+        // $300 traded in 9s early on, then $600 in 27s during the second hour
+        utilization.record(start + Duration::seconds(60), 9.0, 300.0);
+        utilization.record(start + Duration::seconds(5400), 27.0, 600.0);
+
+        let summary = utilization.summary(start + Duration::seconds(7200), 1000.0);
+        assert_eq!(summary.notional_last_hour_usd, 600.0);
+        assert_eq!(summary.turnover_last_hour, Some(0.6));
+        assert!((summary.idle_last_hour_pct.unwrap() - 99.25).abs() < 1e-9);
+        assert_eq!(summary.notional_session_usd, 900.0);
+        assert!((summary.turnover_per_hour_session.unwrap() - 0.45).abs() < 1e-9);
+        assert!((summary.idle_session_pct.unwrap() - 99.5).abs() < 1e-9);
+
+        // Without a known account value there is no turnover to report
+        assert_eq!(utilization.summary(start, 0.0).turnover_last_hour, None);
+    }
+}
@@ -0,0 +1,293 @@
+use crate::client::synced_millis;
+use crate::credentials::{Credentials, KeyRing};
+use crate::endpoints::Endpoints;
+use crate::models::{BalanceMap, WalletAccount};
+use crate::network::WsTransport;
+use anyhow::{anyhow, bail, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::time::{sleep, Duration};
+use tokio_tungstenite::tungstenite::protocol::Message;
+use tracing::{debug, info, warn};
+use url::Url;
+
+const PING_INTERVAL: Duration = Duration::from_secs(20);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// How often the connection checks whether a failover moved the endpoint
+const ENDPOINT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// How long the signed auth request stays valid
+const AUTH_EXPIRY_MS: u64 = 10_000;
+
+#[derive(Debug, Deserialize)]
+struct PrivateMessage {
+    topic: Option<String>,
+    op: Option<String>,
+    success: Option<bool>,
+    ret_msg: Option<String>,
+    #[serde(default)]
+    data: Vec<WalletAccount>,
+}
+
+/// Balances pushed by Bybit's private `wallet` topic, shared by the balance sync task and
+/// the trader waiting for a leg to settle
+#[derive(Debug)]
+pub struct WalletFeed {
+    balances: watch::Sender<BalanceMap>,
+    connected: AtomicBool,
+}
+
+impl WalletFeed {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            balances: watch::Sender::new(BalanceMap::new()),
+            connected: AtomicBool::new(false),
+        })
+    }
+
+    /// Whether the stream is authenticated and subscribed, so pushes can be relied on
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    pub fn set_connected(&self, connected: bool) {
+        self.connected.store(connected, Ordering::SeqCst);
+    }
+
+    /// Latest pushed balance of every coin pushed so far, changed on each push
+    pub fn subscribe(&self) -> watch::Receiver<BalanceMap> {
+        self.balances.subscribe()
+    }
+
+    /// Take up a push, replacing the balances of the coins it covers
+    pub fn apply(&self, pushed: BalanceMap) {
+        self.balances
+            .send_modify(|balances| balances.extend(pushed));
+    }
+}
+
+/// Balance of each coin in a wallet push; a coin in several accounts counts its largest
+fn pushed_balances(accounts: &[WalletAccount]) -> BalanceMap {
+    let mut balances = BalanceMap::new();
+    for coin in accounts.iter().flat_map(|account| &account.coin) {
+        let balance = [&coin.wallet_balance, &coin.equity]
+            .into_iter()
+            .find_map(|value| value.as_deref()?.parse::<f64>().ok())
+            .unwrap_or(0.0);
+        let entry = balances.entry(coin.coin.clone()).or_insert(0.0);
+        *entry = entry.max(balance);
+    }
+    balances
+}
+
+/// Signed `auth` request for the private stream, valid until `expires` (Unix milliseconds)
+fn auth_request(credentials: &Credentials, expires: u64) -> Result<String> {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(credentials.api_secret.as_bytes())
+        .map_err(|e| anyhow!("Failed to create HMAC: {e}"))?;
+    mac.update(format!("GET/realtime{expires}").as_bytes());
+    let signature = hex::encode(mac.finalize().into_bytes());
+    Ok(serde_json::json!({
+        "op": "auth",
+        "args": [credentials.api_key, expires, signature],
+    })
+    .to_string())
+}
+
+/// Authenticate on the private stream at `url` and subscribe to `wallet`, returning how
+/// long it took. Used by `doctor`, since wallet pushes only arrive when balances change.
+pub async fn probe(
+    url: &str,
+    credentials: &Credentials,
+    time_offset: &AtomicI64,
+    transport: &WsTransport,
+    timeout: Duration,
+) -> Result<Duration> {
+    let start = std::time::Instant::now();
+    let probe = async {
+        let (mut write, mut read) = transport.connect(&Url::parse(url)?).await?.split();
+        let expires = synced_millis(time_offset) + AUTH_EXPIRY_MS;
+        write
+            .send(Message::Text(auth_request(credentials, expires)?.into()))
+            .await?;
+        while let Some(msg) = read.next().await {
+            let Message::Text(text) = msg? else {
+                continue;
+            };
+            let Ok(message) = serde_json::from_str::<PrivateMessage>(&text) else {
+                continue;
+            };
+            match message.op.as_deref() {
+                Some("auth" | "subscribe") if message.success != Some(true) => bail!(
+                    "{} failed: {}",
+                    message.op.as_deref().unwrap_or_default(),
+                    message.ret_msg.as_deref().unwrap_or_default()
+                ),
+                Some("auth") => {
+                    let subscribe = serde_json::json!({ "op": "subscribe", "args": ["wallet"] });
+                    write
+                        .send(Message::Text(subscribe.to_string().into()))
+                        .await?;
+                }
+                Some("subscribe") => return Ok(start.elapsed()),
+                _ => {}
+            }
+        }
+        bail!("Stream closed before the wallet subscription was confirmed")
+    };
+    tokio::time::timeout(timeout, probe)
+        .await
+        .map_err(|_| anyhow!("No wallet subscription within {timeout:?}"))?
+}
+
+/// Connection to Bybit's private stream, pushing wallet balance changes into a `WalletFeed`
+pub struct WalletStream {
+    endpoints: Arc<Endpoints>,
+    keys: Arc<KeyRing>,
+    transport: WsTransport,
+    feed: Arc<WalletFeed>,
+    /// The REST client's offset to Bybit's clock, so auth expires on Bybit's time
+    time_offset: Arc<AtomicI64>,
+}
+
+impl WalletStream {
+    pub fn new(
+        endpoints: Arc<Endpoints>,
+        keys: Arc<KeyRing>,
+        transport: WsTransport,
+        feed: Arc<WalletFeed>,
+        time_offset: Arc<AtomicI64>,
+    ) -> Self {
+        Self {
+            endpoints,
+            keys,
+            transport,
+            feed,
+            time_offset,
+        }
+    }
+
+    /// Stream pushes until the task is dropped, reconnecting after errors and failovers
+    pub async fn run(self) {
+        loop {
+            if let Err(e) = self.stream().await {
+                warn!("⚠️ Wallet stream disconnected: {e:#}");
+            }
+            self.feed.set_connected(false);
+            sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    async fn stream(&self) -> Result<()> {
+        let url = self.endpoints.active().private_ws_url.clone();
+        let (mut write, mut read) = self.transport.connect(&Url::parse(&url)?).await?.split();
+
+        let expires = synced_millis(&self.time_offset) + AUTH_EXPIRY_MS;
+        let auth = auth_request(&self.keys.signing().await.active, expires)?;
+        write.send(Message::Text(auth.into())).await?;
+
+        let mut ping_interval = tokio::time::interval(PING_INTERVAL);
+        let mut endpoint_interval = tokio::time::interval(ENDPOINT_CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = endpoint_interval.tick() => {
+                    if self.endpoints.active().private_ws_url != url {
+                        info!("🔐 Endpoint changed, reconnecting the wallet stream");
+                        return Ok(());
+                    }
+                }
+                _ = ping_interval.tick() => {
+                    let ping = serde_json::json!({ "op": "ping" }).to_string();
+                    write.send(Message::Text(ping.into())).await?;
+                }
+                msg = read.next() => match msg {
+                    Some(Ok(Message::Text(text))) => {
+                        let Ok(message) = serde_json::from_str::<PrivateMessage>(&text) else {
+                            debug!("Ignoring private stream message: {text}");
+                            continue;
+                        };
+                        match (message.op.as_deref(), message.topic.as_deref()) {
+                            (Some("auth"), _) if message.success == Some(true) => {
+                                let subscribe =
+                                    serde_json::json!({ "op": "subscribe", "args": ["wallet"] });
+                                write.send(Message::Text(subscribe.to_string().into())).await?;
+                            }
+                            (Some("auth" | "subscribe"), _) if message.success != Some(true) => {
+                                bail!(
+                                    "{} failed: {}",
+                                    message.op.as_deref().unwrap_or_default(),
+                                    message.ret_msg.as_deref().unwrap_or_default()
+                                );
+                            }
+                            (Some("subscribe"), _) => {
+                                info!("🔐 Wallet stream connected: balances are pushed as they change");
+                                self.feed.set_connected(true);
+                            }
+                            (_, Some("wallet")) => {
+                                let pushed = pushed_balances(&message.data);
+                                debug!("🔐 Wallet push for {} coin(s)", pushed.len());
+                                self.feed.apply(pushed);
+                            }
+                            _ => {}
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => bail!("connection closed"),
+                    Some(Err(e)) => return Err(e.into()),
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wallet_push_updates_the_feed() {
+        let push = r#"{
+            "id": "5923240c6880ab-c59f-420b-9adb-3639adc9dd90",
+            "topic": "wallet",
+            "creationTime": 1672364262474,
+            "data": [{
+                "accountType": "UNIFIED",
+                "coin": [
+                    {"coin": "BTC", "walletBalance": "0.0015", "equity": "0.0015"},
+                    {"coin": "USDT", "walletBalance": "0", "equity": "0"}
+                ]
+            }]
+        }"#;
+        let message: PrivateMessage = serde_json::from_str(push).unwrap();
+        assert_eq!(message.topic.as_deref(), Some("wallet"));
+
+        let feed = WalletFeed::new();
+        feed.apply(BalanceMap::from([("ETH".to_string(), 0.2)]));
+        feed.apply(pushed_balances(&message.data));
+        let balances = feed.subscribe().borrow().clone();
+        assert_eq!(balances.get("BTC"), Some(&0.0015));
+        assert_eq!(balances.get("USDT"), Some(&0.0));
+        assert_eq!(balances.get("ETH"), Some(&0.2));
+    }
+
+    #[test]
+    fn test_auth_request_signs_the_expiry() {
+        let credentials = Credentials {
+            api_key: "key".to_string(),
+            api_secret: "secret".to_string(),
+        };
+        let request = |expires| -> serde_json::Value {
+            serde_json::from_str(&auth_request(&credentials, expires).unwrap()).unwrap()
+        };
+        let first = request(1_700_000_000_000);
+        assert_eq!(first["op"], "auth");
+        assert_eq!(first["args"][0], "key");
+        assert_eq!(first["args"][1], 1_700_000_000_000u64);
+        assert_eq!(first["args"][2].as_str().unwrap().len(), 64);
+        assert_ne!(first["args"][2], request(1_700_000_000_001)["args"][2]);
+    }
+}
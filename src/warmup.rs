@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// How often unconfirmed symbols are re-priced from REST during warm-up
+const REST_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+/// Stop polling REST after this long, even if some symbols never got a WebSocket quote
+const WARMUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Tracks which subscribed symbols have had a WebSocket quote. Until they all have, the
+/// scanner keeps the rest priced from periodic REST snapshots.
+#[derive(Debug)]
+pub struct QuoteWarmup {
+    pending: HashSet<String>,
+    total: usize,
+    started: Instant,
+    last_rest_refresh: Instant,
+    finished: bool,
+}
+
+impl QuoteWarmup {
+    /// Start warming up `symbols`, which were just priced from a REST snapshot
+    pub fn new(symbols: &[String], now: Instant) -> Self {
+        Self {
+            pending: symbols.iter().cloned().collect(),
+            total: symbols.len(),
+            started: now,
+            last_rest_refresh: now,
+            finished: symbols.is_empty(),
+        }
+    }
+
+    /// Record a WebSocket quote. Returns true when it was the last symbol waiting for one.
+    pub fn confirm(&mut self, symbol: &str) -> bool {
+        if self.finished || !self.pending.remove(symbol) {
+            return false;
+        }
+        self.finished = self.pending.is_empty();
+        self.finished
+    }
+
+    /// Symbols still priced from REST
+    pub fn pending(&self) -> &HashSet<String> {
+        &self.pending
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Whether the unconfirmed symbols are due a REST refresh; false once warm-up is over
+    pub fn rest_refresh_due(&mut self, now: Instant) -> bool {
+        if self.finished {
+            return false;
+        }
+        if now.duration_since(self.started) >= WARMUP_TIMEOUT {
+            warn!(
+                "⚠️ {} of {} symbols never got a WebSocket quote - no longer re-pricing them from REST",
+                self.pending.len(),
+                self.total
+            );
+            self.finished = true;
+            return false;
+        }
+        if now.duration_since(self.last_rest_refresh) < REST_REFRESH_INTERVAL {
+            return false;
+        }
+        self.last_rest_refresh = now;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_rest_refreshes_stop_once_every_symbol_is_quoted() {
+        let start = Instant::now();
+        let mut warmup = QuoteWarmup::new(&symbols(&["BTCUSDT", "ETHUSDT"]), start);
+
+        assert!(!warmup.rest_refresh_due(start + Duration::from_secs(1)));
+        assert!(warmup.rest_refresh_due(start + Duration::from_secs(2)));
+        assert!(!warmup.rest_refresh_due(start + Duration::from_secs(3)));
+
+        assert!(!warmup.confirm("BTCUSDT"));
+        assert!(!warmup.confirm("BTCUSDT"));
+        assert_eq!(warmup.pending().len(), 1);
+        assert!(warmup.confirm("ETHUSDT"));
+        assert!(warmup.pending().is_empty());
+        assert!(!warmup.rest_refresh_due(start + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_warmup_gives_up_on_silent_symbols() {
+        let start = Instant::now();
+        let mut warmup = QuoteWarmup::new(&symbols(&["BTCUSDT"]), start);
+
+        assert!(!warmup.rest_refresh_due(start + WARMUP_TIMEOUT));
+        assert!(!warmup.rest_refresh_due(start + WARMUP_TIMEOUT + REST_REFRESH_INTERVAL));
+        assert!(!warmup.confirm("BTCUSDT"));
+
+        assert!(!QuoteWarmup::new(&[], start).rest_refresh_due(start + Duration::from_secs(5)));
+    }
+}
@@ -1,27 +1,42 @@
+use crate::bandwidth::BandwidthMeter;
+use crate::crosscheck::{FeedCrossCheck, QuoteSource};
+use crate::degraded::FeedHealth;
+use crate::depth::DepthUpdate;
+use crate::endpoints::Endpoints;
+use crate::focus::FocusFilter;
 use crate::models::TickerInfo;
+use crate::network::WsTransport;
+use crate::quote_delay::QuoteDelays;
+use crate::sequence::{SequenceTracker, Verdict};
 use futures_util::{SinkExt, StreamExt};
 use serde::Deserialize;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tokio::time::sleep;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use tokio_tungstenite::tungstenite::protocol::Message;
 use tracing::{error, info, warn};
 use url::Url;
 
-const BYBIT_WS_URL: &str = "wss://stream.bybit.com/v5/public/spot";
+pub const BYBIT_WS_URL: &str = "wss://stream.bybit.com/v5/public/spot";
 const PING_INTERVAL: u64 = 20;
+/// How often subscriptions follow trimming, focus and cross-check changes
+const RESUBSCRIBE_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Debug, Deserialize)]
 struct WsResponse {
     topic: Option<String>,
     #[serde(rename = "type")]
-    #[allow(dead_code)]
     msg_type: Option<String>,
     data: Option<serde_json::Value>, // Change to Value to handle both single object and array
     success: Option<bool>,
     ret_msg: Option<String>,
     #[allow(dead_code)]
     op: Option<String>,
+    /// When Bybit's system generated the message, in Unix milliseconds
+    ts: Option<i64>,
+    /// When the matching engine produced the update, in Unix milliseconds
+    cts: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,12 +46,148 @@ struct OrderbookData {
     b: Vec<Vec<String>>,
     #[serde(default)]
     a: Vec<Vec<String>>,
+    /// Update id, consecutive across a depth feed's deltas
+    u: Option<u64>,
+}
+
+impl OrderbookData {
+    /// Best bid at or above best ask, which a consistent book never shows
+    fn is_crossed(&self) -> bool {
+        let best = |levels: &[Vec<String>]| {
+            levels
+                .first()
+                .and_then(|l| l.first())
+                .and_then(|p| p.parse::<f64>().ok())
+        };
+        matches!((best(&self.b), best(&self.a)), (Some(bid), Some(ask)) if bid >= ask)
+    }
+}
+
+/// Whether to apply an orderbook message, noting symbols that need a fresh snapshot in
+/// `resync`
+fn sequenced(
+    sequence: &mut SequenceTracker,
+    ob: &OrderbookData,
+    msg_type: Option<&str>,
+    resync: &mut Vec<String>,
+) -> bool {
+    let verdict = match ob.u {
+        Some(u) => sequence.check(&ob.s, msg_type == Some("snapshot"), u),
+        None => Verdict::Apply,
+    };
+    match verdict {
+        Verdict::Apply if ob.is_crossed() => {
+            warn!("🔁 Crossed book on {}, resyncing", ob.s);
+            resync.push(ob.s.clone());
+            false
+        }
+        Verdict::Apply => true,
+        Verdict::Stale => false,
+        Verdict::Gap { last } => {
+            match last {
+                Some(last) => warn!(
+                    "🔁 Missed updates on {} (u {} after {last}), resyncing",
+                    ob.s,
+                    ob.u.unwrap_or_default()
+                ),
+                None => warn!("🔁 Delta for {} before any snapshot, resyncing", ob.s),
+            }
+            resync.push(ob.s.clone());
+            false
+        }
+    }
+}
+
+/// Convert a depth-1 orderbook update into a ticker with best bid/ask only
+fn ticker_from_orderbook(ob: OrderbookData) -> TickerInfo {
+    let level = |levels: &[Vec<String>], i: usize| levels.first().and_then(|l| l.get(i)).cloned();
+    TickerInfo {
+        bid1_price: level(&ob.b, 0),
+        bid1_size: level(&ob.b, 1),
+        ask1_price: level(&ob.a, 0),
+        ask1_size: level(&ob.a, 1),
+        symbol: ob.s,
+        // Initialize other fields as None since we don't get them from orderbook
+        last_price: None,
+        prev_price_24h: None,
+        price_24h_pcnt: None,
+        high_price_24h: None,
+        low_price_24h: None,
+        prev_price_1h: None,
+        mark_price: None,
+        index_price: None,
+        open_interest: None,
+        open_interest_value: None,
+        turnover24h: None,
+        volume24h: None,
+        funding_rate: None,
+        next_funding_time: None,
+        predicted_delivery_price: None,
+        basis_rate: None,
+        delivery_fee_rate: None,
+        delivery_time: None,
+        basis: None,
+    }
+}
+
+/// Parse `[price, size]` levels, skipping malformed ones
+fn parse_levels(levels: &[Vec<String>]) -> Vec<(f64, f64)> {
+    levels
+        .iter()
+        .filter_map(|l| Some((l.first()?.parse().ok()?, l.get(1)?.parse().ok()?)))
+        .collect()
+}
+
+/// Convert a depth-50 orderbook message into a book update
+fn depth_update(ob: OrderbookData, msg_type: Option<&str>) -> DepthUpdate {
+    DepthUpdate {
+        snapshot: msg_type == Some("snapshot"),
+        bids: parse_levels(&ob.b),
+        asks: parse_levels(&ob.a),
+        symbol: ob.s,
+    }
+}
+
+/// `subscribe`/`unsubscribe` requests for `topic` on `symbols`.
+/// Bybit allows max 10 args per request, so they are chunked.
+fn topic_requests(op: &str, topic: &str, symbols: &[String]) -> Vec<String> {
+    symbols
+        .chunks(10)
+        .map(|chunk| {
+            let args: Vec<String> = chunk.iter().map(|s| format!("{topic}.{s}")).collect();
+            serde_json::json!({ "op": op, "args": args }).to_string()
+        })
+        .collect()
+}
+
+/// What a connection subscribes to and where it sends updates
+#[derive(Clone)]
+enum Feed {
+    /// Best bid/ask only, for every liquid symbol
+    TopOfBook(mpsc::Sender<TickerInfo>),
+    /// 50 levels, for symbols where orders outgrow the top level
+    Depth(mpsc::Sender<DepthUpdate>),
+    /// Best bid/ask of the cross-checked symbols, a second opinion on the top of book
+    Mirror,
 }
 
 pub struct BybitWebsocket {
     id: usize,
     symbols: Vec<String>,
-    sender: mpsc::Sender<TickerInfo>,
+    feed: Feed,
+    /// Where to connect; the default stream when unset
+    endpoints: Option<Arc<Endpoints>>,
+    transport: WsTransport,
+    /// Counts received bytes and says which symbols to drop
+    bandwidth: Option<Arc<BandwidthMeter>>,
+    /// Told whether this connection is up
+    health: Option<Arc<FeedHealth>>,
+    /// Limits the symbols streamed while focus mode is on
+    focus: Option<Arc<FocusFilter>>,
+    /// Told every quote of the cross-checked symbols, and which feed this is
+    cross_check: Option<(Arc<FeedCrossCheck>, QuoteSource)>,
+    /// Told how late each quote arrives after Bybit stamped it
+    quote_delays: Option<Arc<QuoteDelays>>,
 }
 
 impl BybitWebsocket {
@@ -44,51 +195,198 @@ impl BybitWebsocket {
         Self {
             id,
             symbols,
-            sender,
+            feed: Feed::TopOfBook(sender),
+            endpoints: None,
+            transport: WsTransport::default(),
+            bandwidth: None,
+            health: None,
+            focus: None,
+            cross_check: None,
+            quote_delays: None,
         }
     }
 
-    pub async fn run(self) {
-        let url = Url::parse(BYBIT_WS_URL).expect("Invalid WebSocket URL");
+    /// Connection streaming 50 levels of depth for `symbols`
+    pub fn depth(id: usize, symbols: Vec<String>, sender: mpsc::Sender<DepthUpdate>) -> Self {
+        Self {
+            id,
+            symbols,
+            feed: Feed::Depth(sender),
+            endpoints: None,
+            transport: WsTransport::default(),
+            bandwidth: None,
+            health: None,
+            focus: None,
+            cross_check: None,
+            quote_delays: None,
+        }
+    }
+
+    /// Connection streaming whatever symbols `cross_check` watches, for comparing with the
+    /// regular connections
+    pub fn mirror(id: usize, cross_check: Arc<FeedCrossCheck>) -> Self {
+        Self {
+            id,
+            symbols: Vec::new(),
+            feed: Feed::Mirror,
+            endpoints: None,
+            transport: WsTransport::default(),
+            bandwidth: None,
+            health: None,
+            focus: None,
+            cross_check: Some((cross_check, QuoteSource::Mirror)),
+            quote_delays: None,
+        }
+    }
+
+    /// Connect to the active endpoint set, reconnecting when a failover changes it
+    pub fn with_endpoints(mut self, endpoints: Arc<Endpoints>) -> Self {
+        self.endpoints = Some(endpoints);
+        self
+    }
+
+    /// Connect through a proxy and/or with extra trusted certificates
+    pub fn with_transport(mut self, transport: WsTransport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Count received bytes, and unsubscribe symbols once the meter trims them
+    pub fn with_bandwidth(mut self, meter: Arc<BandwidthMeter>) -> Self {
+        self.bandwidth = Some(meter);
+        self
+    }
+
+    /// Report while this connection is up, for falling back to REST when none are
+    pub fn with_health(mut self, health: Arc<FeedHealth>) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    /// Follow focus mode, streaming only the focused symbols while it is on
+    pub fn with_focus(mut self, focus: Arc<FocusFilter>) -> Self {
+        self.focus = Some(focus);
+        self
+    }
+
+    /// Report this connection's quotes of cross-checked symbols as the primary feed
+    pub fn with_cross_check(mut self, cross_check: Arc<FeedCrossCheck>) -> Self {
+        self.cross_check = Some((cross_check, QuoteSource::Primary));
+        self
+    }
+
+    /// Measure how late this connection's quotes arrive after Bybit stamped them
+    pub fn with_quote_delays(mut self, delays: Arc<QuoteDelays>) -> Self {
+        self.quote_delays = Some(delays);
+        self
+    }
 
+    fn is_trimmed(&self, symbol: &str) -> bool {
+        self.bandwidth
+            .as_ref()
+            .is_some_and(|meter| meter.is_trimmed(symbol))
+    }
+
+    /// Symbols this connection should stream now: not trimmed, and in focus if one is on.
+    /// A mirror streams the cross-checked symbols.
+    fn wanted_symbols(&self) -> Vec<String> {
+        if let (Feed::Mirror, Some((cross_check, _))) = (&self.feed, &self.cross_check) {
+            return cross_check.symbols();
+        }
+        self.symbols
+            .iter()
+            .filter(|s| !self.is_trimmed(s))
+            .filter(|s| self.focus.as_ref().is_none_or(|focus| focus.allows(s)))
+            .cloned()
+            .collect()
+    }
+
+    fn url(&self) -> &str {
+        self.endpoints
+            .as_ref()
+            .map_or(BYBIT_WS_URL, |e| e.active().ws_url.as_str())
+    }
+
+    pub async fn run(self) {
         loop {
+            let url = Url::parse(self.url()).expect("Invalid WebSocket URL");
             info!("[Conn #{}] Connecting to Bybit WebSocket...", self.id);
-            match connect_async(url.to_string()).await {
-                Ok((ws_stream, _)) => {
+            match self.transport.connect(&url).await {
+                Ok(ws_stream) => {
                     info!("[Conn #{}] Connected to Bybit WebSocket", self.id);
+                    let _up = self.health.as_ref().map(|health| health.connect());
                     let (mut write, mut read) = ws_stream.split();
 
-                    // Subscribe to orderbook (depth 1) for best bid/ask
-                    // Bybit allows max 10 args per request. We need to chunk subscriptions.
-                    let mut subscribed_count = 0;
-                    for chunk in self.symbols.chunks(10) {
-                        let args: Vec<String> =
-                            chunk.iter().map(|s| format!("orderbook.1.{s}")).collect();
-                        let subscribe_msg = serde_json::json!({
-                            "op": "subscribe",
-                            "args": args
-                        });
-
-                        if let Err(e) = write
-                            .send(Message::Text(subscribe_msg.to_string().into()))
-                            .await
-                        {
+                    // Subscribe to orderbook (depth 1) for best bid/ask, or depth 50 for depth pricing
+                    let (topic, label) = match self.feed {
+                        Feed::TopOfBook(_) => ("orderbook.1", "Orderbook"),
+                        Feed::Depth(_) => ("orderbook.50", "Depth"),
+                        Feed::Mirror => ("orderbook.1", "Mirror"),
+                    };
+                    let connection = format!("{topic}#{}", self.id);
+                    let mut subscribed = self.wanted_symbols();
+                    for request in topic_requests("subscribe", topic, &subscribed) {
+                        if let Err(e) = write.send(Message::Text(request.into())).await {
                             error!("Failed to send subscription: {e}");
                             break;
                         }
-                        subscribed_count += chunk.len();
                     }
                     info!(
-                        "[Conn #{}] Subscribed to {} symbols (Orderbook)",
-                        self.id, subscribed_count
+                        "[Conn #{}] Subscribed to {} symbols ({label})",
+                        self.id,
+                        subscribed.len()
                     );
 
                     // Heartbeat task
                     let mut ping_interval =
                         tokio::time::interval(Duration::from_secs(PING_INTERVAL));
+                    let mut resubscribe_interval = tokio::time::interval(RESUBSCRIBE_INTERVAL);
+                    let mut sequence = match self.feed {
+                        Feed::Depth(_) => SequenceTracker::deltas(),
+                        Feed::TopOfBook(_) | Feed::Mirror => SequenceTracker::snapshots(),
+                    };
+                    // Symbols to resubscribe for a fresh snapshot after a gap or crossed book
+                    let mut resync: Vec<String> = Vec::new();
 
                     loop {
+                        if !resync.is_empty() {
+                            resync.sort();
+                            resync.dedup();
+                            let symbols = std::mem::take(&mut resync);
+                            for request in topic_requests("unsubscribe", topic, &symbols)
+                                .into_iter()
+                                .chain(topic_requests("subscribe", topic, &symbols))
+                            {
+                                if let Err(e) = write.send(Message::Text(request.into())).await {
+                                    error!("Failed to send resubscription: {e}");
+                                }
+                            }
+                        }
                         tokio::select! {
+                            _ = resubscribe_interval.tick() => {
+                                if self.url() != url.as_str() {
+                                    info!("[Conn #{}] Endpoint changed, reconnecting to {}", self.id, self.url());
+                                    break;
+                                }
+                                // Drop symbols trimmed to stay within the bandwidth budget or
+                                // out of focus, and pick up those a focus change brought back
+                                let wanted = self.wanted_symbols();
+                                let dropped: Vec<String> =
+                                    subscribed.iter().filter(|s| !wanted.contains(s)).cloned().collect();
+                                let added: Vec<String> =
+                                    wanted.iter().filter(|s| !subscribed.contains(s)).cloned().collect();
+                                subscribed = wanted;
+                                for request in topic_requests("unsubscribe", topic, &dropped) {
+                                    if let Err(e) = write.send(Message::Text(request.into())).await {
+                                        error!("Failed to send unsubscription: {e}");
+                                    }
+                                }
+                                for request in topic_requests("subscribe", topic, &added) {
+                                    if let Err(e) = write.send(Message::Text(request.into())).await {
+                                        error!("Failed to send subscription: {e}");
+                                    }
+                                }
+                            }
                             _ = ping_interval.tick() => {
                                 let ping_msg = serde_json::json!({ "op": "ping" });
                                 if let Err(e) = write.send(Message::Text(ping_msg.to_string().into())).await {
@@ -97,46 +395,52 @@ impl BybitWebsocket {
                                 }
                             }
                             msg = read.next() => {
+                                let bytes = match &msg {
+                                    Some(Ok(message)) => message.len(),
+                                    _ => 0,
+                                };
+                                if let Some(meter) = &self.bandwidth {
+                                    meter.record_message(&connection, bytes);
+                                }
                                 match msg {
                                     Some(Ok(Message::Text(text))) => {
                                         match serde_json::from_str::<WsResponse>(&text) {
                                             Ok(response) => {
+                                                if let (Some(meter), Some(topic)) = (&self.bandwidth, &response.topic) {
+                                                    meter.record_topic(topic, bytes);
+                                                }
                                                 if let Some(data_val) = response.data {
                                                     // Check topic to decide how to parse
-                                                    if let Some(topic) = &response.topic {
+                                                    if let (Some(topic), Feed::Depth(sender)) = (&response.topic, &self.feed) {
+                                                        if topic.starts_with("orderbook.50") {
+                                                            match serde_json::from_value::<OrderbookData>(data_val) {
+                                                                Ok(ob) if !sequenced(&mut sequence, &ob, response.msg_type.as_deref(), &mut resync) => {}
+                                                                Ok(ob) => {
+                                                                    let update = depth_update(ob, response.msg_type.as_deref());
+                                                                    if let Err(e) = sender.send(update).await {
+                                                                        error!("Failed to send depth update: {e}");
+                                                                        break;
+                                                                    }
+                                                                }
+                                                                Err(e) => {
+                                                                    warn!("Failed to deserialize depth data: {e}");
+                                                                }
+                                                            }
+                                                        }
+                                                    } else if let (Some(topic), Feed::TopOfBook(sender)) = (&response.topic, &self.feed) {
                                                         if topic.starts_with("orderbook.1") {
                                                             match serde_json::from_value::<OrderbookData>(data_val) {
+                                                                Ok(ob) if !sequenced(&mut sequence, &ob, response.msg_type.as_deref(), &mut resync) => {}
                                                                 Ok(ob) => {
-                                                                    // Direct conversion to TickerInfo without intermediate JSON serialization
-                                                                    let ticker = TickerInfo {
-                                                                        symbol: ob.s,
-                                                                        bid1_price: ob.b.first().map(|v| v[0].clone()),
-                                                                        bid1_size: ob.b.first().map(|v| v[1].clone()),
-                                                                        ask1_price: ob.a.first().map(|v| v[0].clone()),
-                                                                        ask1_size: ob.a.first().map(|v| v[1].clone()),
-                                                                        // Initialize other fields as None since we don't get them from orderbook
-                                                                        last_price: None,
-                                                                        prev_price_24h: None,
-                                                                        price_24h_pcnt: None,
-                                                                        high_price_24h: None,
-                                                                        low_price_24h: None,
-                                                                        prev_price_1h: None,
-                                                                        mark_price: None,
-                                                                        index_price: None,
-                                                                        open_interest: None,
-                                                                        open_interest_value: None,
-                                                                        turnover24h: None,
-                                                                        volume24h: None,
-                                                                        funding_rate: None,
-                                                                        next_funding_time: None,
-                                                                        predicted_delivery_price: None,
-                                                                        basis_rate: None,
-                                                                        delivery_fee_rate: None,
-                                                                        delivery_time: None,
-                                                                        basis: None,
-                                                                    };
-
-                                                                    if let Err(e) = self.sender.send(ticker).await {
+                                                                    let ticker = ticker_from_orderbook(ob);
+                                                                    if let Some((cross_check, source)) = &self.cross_check {
+                                                                        cross_check.record(*source, &ticker, Instant::now());
+                                                                    }
+                                                                    if let (Some(delays), Some(stamped)) = (&self.quote_delays, response.cts.or(response.ts)) {
+                                                                        delays.record(&ticker.symbol, stamped, chrono::Utc::now().timestamp_millis());
+                                                                    }
+
+                                                                    if let Err(e) = sender.send(ticker).await {
                                                                         error!("Failed to send ticker update: {e}");
                                                                         break;
                                                                     }
@@ -149,7 +453,7 @@ impl BybitWebsocket {
                                                             // Fallback for tickers topic if we ever use it
                                                             match serde_json::from_value::<TickerInfo>(data_val.clone()) {
                                                                 Ok(ticker) => {
-                                                                    if let Err(e) = self.sender.send(ticker).await {
+                                                                    if let Err(e) = sender.send(ticker).await {
                                                                         error!("Failed to send ticker update: {e}");
                                                                         break;
                                                                     }
@@ -159,6 +463,12 @@ impl BybitWebsocket {
                                                                 }
                                                             }
                                                         }
+                                                    } else if let (Feed::Mirror, Some((cross_check, source))) = (&self.feed, &self.cross_check) {
+                                                        match serde_json::from_value::<OrderbookData>(data_val) {
+                                                            Ok(ob) if !sequenced(&mut sequence, &ob, response.msg_type.as_deref(), &mut resync) => {}
+                                                            Ok(ob) => cross_check.record(*source, &ticker_from_orderbook(ob), Instant::now()),
+                                                            Err(e) => warn!("Failed to deserialize mirror orderbook data: {e}"),
+                                                        }
                                                     }
                                                 } else if let Some(success) = response.success {
                                                     if !success {
@@ -204,3 +514,126 @@ impl BybitWebsocket {
         }
     }
 }
+
+/// Connect to a stream, subscribe to a single topic and wait for the first data message.
+/// Returns the time it took to receive data.
+pub async fn probe_stream(
+    url: &str,
+    topic: &str,
+    transport: &WsTransport,
+    timeout: Duration,
+) -> anyhow::Result<Duration> {
+    let start = std::time::Instant::now();
+
+    let probe = async {
+        let ws_stream = transport.connect(&Url::parse(url)?).await?;
+        let (mut write, mut read) = ws_stream.split();
+
+        let subscribe_msg = serde_json::json!({ "op": "subscribe", "args": [topic] });
+        write
+            .send(Message::Text(subscribe_msg.to_string().into()))
+            .await?;
+
+        while let Some(msg) = read.next().await {
+            if let Message::Text(text) = msg? {
+                let response: WsResponse = match serde_json::from_str(&text) {
+                    Ok(r) => r,
+                    Err(_) => continue,
+                };
+                if response.success == Some(false) {
+                    anyhow::bail!("Subscription to {topic} rejected: {:?}", response.ret_msg);
+                }
+                if response.data.is_some() {
+                    return Ok(start.elapsed());
+                }
+            }
+        }
+
+        anyhow::bail!("Stream closed before any data was received")
+    };
+
+    tokio::time::timeout(timeout, probe)
+        .await
+        .map_err(|_| anyhow::anyhow!("No data received on {topic} within {timeout:?}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orderbook_ticker(json: &str) -> TickerInfo {
+        let response: WsResponse = serde_json::from_str(json).unwrap();
+        assert!(response.topic.unwrap().starts_with("orderbook.1"));
+        ticker_from_orderbook(serde_json::from_value(response.data.unwrap()).unwrap())
+    }
+
+    #[test]
+    fn test_fixture_orderbook_snapshot() {
+        let ticker = orderbook_ticker(include_str!(
+            "../tests/fixtures/bybit/ws/orderbook_snapshot.json"
+        ));
+        assert_eq!(ticker.symbol, "BTCUSDT");
+        assert_eq!(ticker.bid1_price.as_deref(), Some("67012.5"));
+        assert_eq!(ticker.ask1_size.as_deref(), Some("1.208894"));
+
+        // An empty side leaves that side unset instead of panicking
+        let ticker = orderbook_ticker(include_str!(
+            "../tests/fixtures/bybit/ws/orderbook_one_sided.json"
+        ));
+        assert!(ticker.bid1_price.is_none());
+        assert_eq!(ticker.ask1_price.as_deref(), Some("0.1502"));
+    }
+
+    #[test]
+    fn test_stale_and_crossed_books_are_not_applied() {
+        let book = |u: u64, bid: &str, ask: &str| OrderbookData {
+            s: "BTCUSDT".to_string(),
+            b: vec![vec![bid.to_string(), "1".to_string()]],
+            a: vec![vec![ask.to_string(), "1".to_string()]],
+            u: Some(u),
+        };
+        let mut sequence = SequenceTracker::snapshots();
+        let mut resync = Vec::new();
+        let snapshot = Some("snapshot");
+
+        assert!(sequenced(
+            &mut sequence,
+            &book(5, "100", "101"),
+            snapshot,
+            &mut resync
+        ));
+        assert!(!sequenced(
+            &mut sequence,
+            &book(4, "99", "100"),
+            snapshot,
+            &mut resync
+        ));
+        assert!(resync.is_empty());
+        assert!(!sequenced(
+            &mut sequence,
+            &book(6, "101", "101"),
+            snapshot,
+            &mut resync
+        ));
+        assert_eq!(resync, ["BTCUSDT"]);
+    }
+
+    #[test]
+    fn test_fixture_control_messages() {
+        for json in [
+            include_str!("../tests/fixtures/bybit/ws/subscribe_ok.json"),
+            include_str!("../tests/fixtures/bybit/ws/pong.json"),
+        ] {
+            let response: WsResponse = serde_json::from_str(json).unwrap();
+            assert!(response.data.is_none());
+            assert_eq!(response.success, Some(true));
+        }
+
+        let rejected: WsResponse = serde_json::from_str(include_str!(
+            "../tests/fixtures/bybit/ws/subscribe_rejected.json"
+        ))
+        .unwrap();
+        assert_eq!(rejected.success, Some(false));
+        assert!(rejected.ret_msg.unwrap().contains("FOOBARUSDT"));
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::{anyhow, Result};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Duration;
+use tracing::warn;
+
+/// Longest `flush` waits for the writer task
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where a supervised writer puts its records, e.g. a file or a database
+pub trait RecordSink<R>: Send + 'static {
+    /// Write one record; `more` when others are already queued behind it
+    fn write(&mut self, record: R, more: bool) -> Result<()>;
+
+    /// Make everything written so far durable
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+enum WriterMsg<R> {
+    Record(R),
+    /// Answered once every earlier record has been written
+    Flush(oneshot::Sender<()>),
+}
+
+/// Records handed to a supervised background task, so a failing or slow disk never stalls
+/// trading. Writes run on the blocking pool, and the sink is reopened after a write error.
+pub struct SupervisedWriter<R> {
+    name: &'static str,
+    tx: mpsc::UnboundedSender<WriterMsg<R>>,
+}
+
+impl<R: Send + 'static> SupervisedWriter<R> {
+    /// Start writing to `sink`, opening a new one with `open` once it fails
+    pub fn spawn<S, F>(name: &'static str, restart_on_panic: bool, sink: S, open: F) -> Self
+    where
+        S: RecordSink<R>,
+        F: Fn() -> Result<S> + Send + Sync + 'static,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let rx = Arc::new(tokio::sync::Mutex::new(rx));
+        let first = Arc::new(Mutex::new(Some(sink)));
+        let open = Arc::new(open);
+        crate::supervisor::spawn_supervised(name.to_string(), restart_on_panic, move || {
+            let sink = first.lock().ok().and_then(|mut s| s.take());
+            run(name, sink, open.clone(), rx.clone())
+        });
+        Self { name, tx }
+    }
+
+    /// Queue a record; fails only once the task is gone
+    pub fn send(&self, record: R) -> Result<()> {
+        self.tx
+            .send(WriterMsg::Record(record))
+            .map_err(|_| anyhow!("{} stopped", self.name))
+    }
+
+    /// Wait until every record sent so far is written
+    pub async fn flush(&self) {
+        let (done, written) = oneshot::channel();
+        if self.tx.send(WriterMsg::Flush(done)).is_err()
+            || !matches!(
+                tokio::time::timeout(FLUSH_TIMEOUT, written).await,
+                Ok(Ok(()))
+            )
+        {
+            warn!(
+                "⚠️ {} did not confirm the flush; recent records may be missing",
+                self.name
+            );
+        }
+    }
+}
+
+/// Write records until the writer is dropped
+async fn run<R, S, F>(
+    name: &'static str,
+    mut sink: Option<S>,
+    open: Arc<F>,
+    rx: Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<WriterMsg<R>>>>,
+) where
+    R: Send + 'static,
+    S: RecordSink<R>,
+    F: Fn() -> Result<S> + Send + Sync + 'static,
+{
+    let mut rx = rx.lock().await;
+    while let Some(msg) = rx.recv().await {
+        let more = !rx.is_empty();
+        let (taken, open) = (sink.take(), open.clone());
+        sink = tokio::task::spawn_blocking(move || handle(name, taken, &*open, msg, more))
+            .await
+            .unwrap_or_else(|e| {
+                warn!("⚠️ {name} failed: {e}");
+                None
+            });
+    }
+    // The writer is gone; park instead of returning so the supervisor doesn't restart us
+    std::future::pending::<()>().await;
+}
+
+/// Handle one message, returning the sink to keep: none after it failed
+fn handle<R, S: RecordSink<R>>(
+    name: &str,
+    mut sink: Option<S>,
+    open: &dyn Fn() -> Result<S>,
+    msg: WriterMsg<R>,
+    more: bool,
+) -> Option<S> {
+    match msg {
+        WriterMsg::Record(record) => {
+            if sink.is_none() {
+                sink = open()
+                    .map_err(|e| warn!("⚠️ {name} failed to open: {e:#}"))
+                    .ok();
+            }
+            let mut sink = sink?;
+            match sink.write(record, more) {
+                Ok(()) => Some(sink),
+                Err(e) => {
+                    warn!("⚠️ {name} failed to write: {e:#}");
+                    None
+                }
+            }
+        }
+        WriterMsg::Flush(done) => {
+            if let Some(Err(e)) = sink.as_mut().map(RecordSink::flush) {
+                warn!("⚠️ {name} failed to flush: {e:#}");
+                sink = None;
+            }
+            done.send(()).ok();
+            sink
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails every other write, like a disk coming and going
+    struct Flaky(Arc<Mutex<Vec<u32>>>);
+
+    impl RecordSink<u32> for Flaky {
+        fn write(&mut self, record: u32, _more: bool) -> Result<()> {
+            if record % 2 == 1 {
+                return Err(anyhow!("disk full"));
+            }
+            self.0.lock().unwrap().push(record);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_writes_in_order_and_reopens_after_a_failure() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let opened = Arc::new(Mutex::new(0));
+        let (w, o) = (written.clone(), opened.clone());
+        let first = Flaky(written.clone());
+        let writer = SupervisedWriter::spawn("Test writer", true, first, move || {
+            *o.lock().unwrap() += 1;
+            Ok(Flaky(w.clone()))
+        });
+
+        for record in 0..5 {
+            writer.send(record).unwrap();
+        }
+        writer.flush().await;
+
+        assert_eq!(*written.lock().unwrap(), [0, 2, 4]);
+        assert_eq!(*opened.lock().unwrap(), 2);
+    }
+}